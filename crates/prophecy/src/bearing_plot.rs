@@ -0,0 +1,181 @@
+use futures::StreamExt;
+use futuresdr_types::Pmt;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use leptos::logging::*;
+use leptos::*;
+use std::collections::VecDeque;
+
+/// Extract a bearing in degrees from a PMT.
+///
+/// Numbers are taken as they are, maps have to contain the bearing under `key`, e.g.,
+/// `bearing` for the `DoaEstimator` and `Interferometer` or `azimuth` for the `Georeference`
+/// block.
+pub fn bearing_from_pmt(p: &Pmt, key: &str) -> Option<f64> {
+    let b = match p {
+        Pmt::F32(b) => *b as f64,
+        Pmt::F64(b) => *b,
+        Pmt::MapStrPmt(m) => match m.get(key) {
+            Some(Pmt::F32(b)) => *b as f64,
+            Some(Pmt::F64(b)) => *b,
+            _ => return None,
+        },
+        _ => return None,
+    };
+    b.is_finite().then(|| b.rem_euclid(360.0))
+}
+
+/// Point on the circle with radius `r` in SVG coordinates, i.e., with the y-axis pointing down.
+///
+/// In `compass` mode, angles are clockwise from north, otherwise counter-clockwise from the
+/// x-axis.
+fn polar(angle: f64, r: f64, compass: bool) -> (f64, f64) {
+    let a = angle.to_radians();
+    if compass {
+        (r * a.sin(), -r * a.cos())
+    } else {
+        (r * a.cos(), -r * a.sin())
+    }
+}
+
+/// SVG path of a ring segment between `a0` and `a1` degrees.
+fn wedge(a0: f64, a1: f64, inner: f64, outer: f64, compass: bool) -> String {
+    let sweep = u8::from(compass);
+    let (x0, y0) = polar(a0, outer, compass);
+    let (x1, y1) = polar(a1, outer, compass);
+    let (x2, y2) = polar(a1, inner, compass);
+    let (x3, y3) = polar(a0, inner, compass);
+    format!(
+        "M {x0} {y0} A {outer} {outer} 0 0 {sweep} {x1} {y1} L {x2} {y2} \
+         A {inner} {inner} 0 0 {} {x3} {y3} Z",
+        1 - sweep
+    )
+}
+
+#[component]
+/// Bearing Plot
+///
+/// Polar plot of bearings received as JSON text messages through a WebSocket, e.g., from a
+/// `WebsocketPmtSink` connected to a direction-finding block. The current bearing is shown as
+/// needle, the latest `history` bearings as dots and, on the outer ring, as heatmap with `bins`
+/// sectors. Bearings are extracted with [bearing_from_pmt] using `key`.
+///
+/// By default, bearings are drawn counter-clockwise from the x-axis (pointing right), like the
+/// output of the `DoaEstimator`. In `compass` mode, they are drawn clockwise from north
+/// (pointing up), like the azimuth of the `Georeference` block.
+pub fn BearingPlot(
+    #[prop(optional, into, default = "ws://127.0.0.1:9006".to_string())] websocket: String,
+    #[prop(optional, into, default = "bearing".to_string())] key: String,
+    #[prop(default = 100)] history: usize,
+    #[prop(default = 72)] bins: usize,
+    #[prop(optional)] compass: bool,
+    #[prop(optional, into, default = "#3b82f6".to_string())] color: String,
+    #[prop(into, optional)] label_class: String,
+) -> impl IntoView {
+    let (bearings, set_bearings) = create_signal(VecDeque::<f64>::new());
+
+    spawn_local(async move {
+        let mut ws = WebSocket::open(&websocket).unwrap();
+        while let Some(msg) = ws.next().await {
+            match msg {
+                Ok(Message::Text(t)) => match serde_json::from_str::<Pmt>(&t) {
+                    Ok(p) => {
+                        if let Some(b) = bearing_from_pmt(&p, &key) {
+                            set_bearings.update(|h| {
+                                h.push_back(b);
+                                while h.len() > history.max(1) {
+                                    h.pop_front();
+                                }
+                            });
+                        }
+                    }
+                    Err(_) => log!("BearingPlot: cannot parse PMT {}", t),
+                },
+                _ => {
+                    log!("BearingPlot: WebSocket {:?}", msg);
+                }
+            }
+        }
+        log!("BearingPlot: WebSocket Closed");
+    });
+
+    let grid = (0..12)
+        .map(|i| {
+            let a = i as f64 * 30.0;
+            let (x0, y0) = polar(a, 0.1, compass);
+            let (x1, y1) = polar(a, 0.85, compass);
+            let (tx, ty) = polar(a, 1.0, compass);
+            view! {
+                <line x1=x0 y1=y0 x2=x1 y2=y1 stroke="gray" stroke-width="0.005" />
+                <text x=tx y=ty font-size="0.07" fill="gray" text-anchor="middle"
+                    dominant-baseline="middle">{format!("{a}")}</text>
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let heatmap = {
+        let color = color.clone();
+        move || {
+            let h = bearings.get();
+            let bins = bins.max(1);
+            let width = 360.0 / bins as f64;
+            let mut counts = vec![0usize; bins];
+            for b in h.iter() {
+                counts[((b / width) as usize).min(bins - 1)] += 1;
+            }
+            let peak = counts.iter().copied().max().unwrap_or(0).max(1);
+            counts
+                .into_iter()
+                .enumerate()
+                .filter(|(_, n)| *n > 0)
+                .map(|(i, n)| {
+                    let a0 = i as f64 * width;
+                    let d = wedge(a0, a0 + width, 0.86, 0.92, compass);
+                    let opacity = n as f64 / peak as f64;
+                    view! { <path d=d fill=color.clone() fill-opacity=opacity /> }
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let dots = {
+        let color = color.clone();
+        move || {
+            let h = bearings.get();
+            let n = h.len();
+            h.into_iter()
+                .enumerate()
+                .map(|(i, b)| {
+                    let (x, y) = polar(b, 0.8, compass);
+                    let opacity = (i + 1) as f64 / n as f64;
+                    view! { <circle cx=x cy=y r="0.015" fill=color.clone() fill-opacity=opacity /> }
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let needle = move || {
+        bearings.get().back().map(|b| {
+            let (x, y) = polar(*b, 0.8, compass);
+            view! { <line x1="0" y1="0" x2=x y2=y stroke=color.clone() stroke-width="0.02" /> }
+        })
+    };
+
+    view! {
+        <div style="display: flex; flex-direction: column; width: 100%; height: 100%">
+            <svg viewBox="-1.1 -1.1 2.2 2.2" style="flex-grow: 1">
+                <circle cx="0" cy="0" r="0.85" fill="none" stroke="gray" stroke-width="0.005" />
+                <circle cx="0" cy="0" r="0.425" fill="none" stroke="gray" stroke-width="0.005" />
+                {grid}
+                {heatmap}
+                {dots}
+                {needle}
+            </svg>
+            <span class=label_class>
+                {move || match bearings.get().back() {
+                    Some(b) => format!("bearing: {b:.1}°"),
+                    None => "bearing: -".to_string(),
+                }}
+            </span>
+        </div>
+    }
+}
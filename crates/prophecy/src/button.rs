@@ -0,0 +1,77 @@
+use futuresdr_types::Pmt;
+use futuresdr_types::PortId;
+use leptos::logging::*;
+use leptos::*;
+
+use crate::FlowgraphHandle;
+
+#[component]
+/// Button
+///
+/// Clicking the button triggers sending a PMT. If a `release` PMT is given, the button is
+/// momentary, i.e., `pmt` is sent when the button is pressed and `release` when it is released
+/// again.
+pub fn Button<P: Into<PortId>>(
+    fg_handle: FlowgraphHandle,
+    block_id: usize,
+    handler: P,
+    #[prop(default = Pmt::Null)] pmt: Pmt,
+    #[prop(optional)] release: Option<Pmt>,
+    #[prop(into, optional, default = "Send".to_string())] text: String,
+    #[prop(into, optional)] button_class: String,
+) -> impl IntoView {
+    let handler = handler.into();
+    let momentary = release.is_some();
+    let (pressed, set_pressed) = create_signal(false);
+
+    let send = move |pmt: Pmt| {
+        let mut fg_handle = fg_handle.clone();
+        let handler = handler.clone();
+        spawn_local(async move {
+            log!(
+                "sending block {} handler {:?} pmt {:?}",
+                block_id,
+                &handler,
+                &pmt
+            );
+            let _ = fg_handle.call(block_id, handler, pmt).await;
+        });
+    };
+
+    let on_release = {
+        let send = send.clone();
+        move || {
+            if pressed.get_untracked() {
+                set_pressed(false);
+                if let Some(p) = release.clone() {
+                    send(p);
+                }
+            }
+        }
+    };
+
+    view! {
+        <button class=button_class
+            on:click={
+                let send = send.clone();
+                let pmt = pmt.clone();
+                move |_| {
+                    if !momentary {
+                        send(pmt.clone());
+                    }
+                }
+            }
+            on:pointerdown=move |_| {
+                if momentary {
+                    set_pressed(true);
+                    send(pmt.clone());
+                }
+            }
+            on:pointerup={
+                let on_release = on_release.clone();
+                move |_| on_release()
+            }
+            on:pointerleave=move |_| on_release()
+        >{text}</button>
+    }
+}
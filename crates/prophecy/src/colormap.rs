@@ -0,0 +1,142 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Color Map
+///
+/// Maps normalized values to colors in WebGL shaders. All maps are selected through the
+/// `u_colormap` uniform, i.e., they can be switched at runtime without recompiling the shader.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ColorMap {
+    /// Viridis
+    #[default]
+    Viridis,
+    /// Inferno
+    Inferno,
+    /// Magma
+    Magma,
+    /// Plasma
+    Plasma,
+    /// Turbo
+    Turbo,
+    /// Grayscale
+    Gray,
+    /// Black, blue, cyan, yellow, red, as used by many SDR applications
+    Classic,
+}
+
+impl ColorMap {
+    /// All available color maps.
+    pub const ALL: [ColorMap; 7] = [
+        ColorMap::Viridis,
+        ColorMap::Inferno,
+        ColorMap::Magma,
+        ColorMap::Plasma,
+        ColorMap::Turbo,
+        ColorMap::Gray,
+        ColorMap::Classic,
+    ];
+
+    /// Value of the `u_colormap` uniform.
+    pub(crate) fn id(&self) -> i32 {
+        match self {
+            ColorMap::Viridis => 0,
+            ColorMap::Inferno => 1,
+            ColorMap::Magma => 2,
+            ColorMap::Plasma => 3,
+            ColorMap::Turbo => 4,
+            ColorMap::Gray => 5,
+            ColorMap::Classic => 6,
+        }
+    }
+}
+
+impl fmt::Display for ColorMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            ColorMap::Viridis => "viridis",
+            ColorMap::Inferno => "inferno",
+            ColorMap::Magma => "magma",
+            ColorMap::Plasma => "plasma",
+            ColorMap::Turbo => "turbo",
+            ColorMap::Gray => "gray",
+            ColorMap::Classic => "classic",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for ColorMap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ColorMap::ALL
+            .into_iter()
+            .find(|c| c.to_string() == s.to_lowercase())
+            .ok_or_else(|| format!("unknown color map {s}"))
+    }
+}
+
+/// GLSL implementation of `vec3 color_map(float t)`, using the `u_colormap` uniform.
+///
+/// Polynomial approximations of the matplotlib color maps are from Matt Zucker, the Turbo
+/// approximation is from Google.
+pub(crate) const COLOR_MAP_GLSL: &str = r"
+    uniform int u_colormap;
+
+    vec3 poly6(float t, vec3 c0, vec3 c1, vec3 c2, vec3 c3, vec3 c4, vec3 c5, vec3 c6) {
+        return c0+t*(c1+t*(c2+t*(c3+t*(c4+t*(c5+t*c6)))));
+    }
+
+    vec3 color_map(float t) {
+        if (u_colormap == 1) {
+            return poly6(t,
+                vec3(0.0002189403691192265, 0.001651004631001012, -0.01948089843709184),
+                vec3(0.1065134194856116, 0.5639564367884091, 3.932712388889277),
+                vec3(11.60249308247187, -3.972853965665698, -15.9423941062914),
+                vec3(-41.70399613139459, 17.43639888205313, 44.35414519872813),
+                vec3(77.162935699427, -33.40235894210092, -81.80730925738993),
+                vec3(-71.31942824499214, 32.62606426397723, 73.20951985803202),
+                vec3(25.13112622477341, -12.24266895238567, -23.07032500287172));
+        } else if (u_colormap == 2) {
+            return poly6(t,
+                vec3(-0.002136485053939582, -0.000749655052795221, -0.005386127855323933),
+                vec3(0.2516605407371642, 0.6775232436837668, 2.494026599312351),
+                vec3(8.353717279216625, -3.577719514958484, 0.3144679030132573),
+                vec3(-27.66873308576866, 14.26473078096533, -13.64921318813922),
+                vec3(52.17613981234068, -27.94360607168351, 12.94416944238394),
+                vec3(-50.76852536473588, 29.04658282127291, 4.23415299384598),
+                vec3(18.65570506591883, -11.48977351997711, -5.601961508734096));
+        } else if (u_colormap == 3) {
+            return poly6(t,
+                vec3(0.05873234392399702, 0.02333670892565664, 0.5433401826748754),
+                vec3(2.176514634195958, 0.2383834171260182, 0.7539604599784036),
+                vec3(-2.689460476458034, -7.455851135738909, 3.110799939717086),
+                vec3(6.130348345893603, 42.3461881477227, -28.51885465332158),
+                vec3(-11.10743619062271, -82.66631109428045, 60.13984767418263),
+                vec3(10.02306557647065, 71.41361770095349, -54.07218655560067),
+                vec3(-3.658713842777788, -22.93153465461149, 18.19190778539828));
+        } else if (u_colormap == 4) {
+            vec4 v4 = vec4(1.0, t, t * t, t * t * t);
+            vec2 v2 = v4.zw * v4.z;
+            return vec3(
+                dot(v4, vec4(0.13572138, 4.61539260, -42.66032258, 132.13108234)) + dot(v2, vec2(-152.94239396, 59.28637943)),
+                dot(v4, vec4(0.09140261, 2.19418839, 4.84296658, -14.18503333)) + dot(v2, vec2(4.27729857, 2.82956604)),
+                dot(v4, vec4(0.10667330, 12.64194608, -60.58204836, 110.36276771)) + dot(v2, vec2(-89.90310912, 27.34824973)));
+        } else if (u_colormap == 5) {
+            return vec3(t, t, t);
+        } else if (u_colormap == 6) {
+            return vec3(
+                clamp(4.0 * t - 2.0, 0.0, 1.0),
+                min(clamp(4.0 * t - 1.0, 0.0, 1.0), clamp(4.0 - 4.0 * t, 0.0, 1.0)),
+                min(clamp(4.0 * t, 0.0, 1.0), clamp(3.0 - 4.0 * t, 0.0, 1.0)));
+        }
+        return poly6(t,
+            vec3(0.2777273272234177, 0.005407344544966578, 0.3340998053353061),
+            vec3(0.1050930431085774, 1.404613529898575, 1.384590162594685),
+            vec3(-0.3308618287255563, 0.214847559468213, 0.09509516302823659),
+            vec3(-4.634230498983486, -5.799100973351585, -19.33244095627987),
+            vec3(6.228269936347081, 14.17993336680509, 56.69055260068105),
+            vec3(4.776384997670288, -13.74514537774601, -65.35303263337234),
+            vec3(-5.435455855934631, 4.645852612178535, 26.3124352495832));
+    }
+";
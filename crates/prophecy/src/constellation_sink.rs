@@ -4,22 +4,69 @@ use leptos::html::Canvas;
 use leptos::logging::*;
 use leptos::*;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
 use web_sys::WebGlProgram;
 use web_sys::WebGlRenderingContext as GL;
 
+use crate::ArrayView;
+
+/// Reference constellation, drawn on top of the received symbols.
+///
+/// The points are normalized to unit average power.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Constellation {
+    /// BPSK
+    Bpsk,
+    /// QPSK
+    Qpsk,
+    /// 16-QAM
+    Qam16,
+}
+
+impl Constellation {
+    /// Constellation points as interleaved `(re, im)` pairs
+    pub fn points(&self) -> Vec<f32> {
+        match self {
+            Constellation::Bpsk => vec![-1.0, 0.0, 1.0, 0.0],
+            Constellation::Qpsk => {
+                let a = std::f32::consts::FRAC_1_SQRT_2;
+                vec![-a, -a, -a, a, a, -a, a, a]
+            }
+            Constellation::Qam16 => {
+                let levels = [-3.0, -1.0, 1.0, 3.0];
+                let scale = 1.0 / 10.0f32.sqrt();
+                levels
+                    .iter()
+                    .flat_map(|re| levels.iter().flat_map(move |im| [re * scale, im * scale]))
+                    .collect()
+            }
+        }
+    }
+}
+
 struct RenderState {
     canvas: HtmlElement<Canvas>,
     gl: GL,
     shader: WebGlProgram,
-    vertex_len: i32,
+    persistence: MaybeSignal<usize>,
+    points: MaybeSignal<Option<usize>>,
+    reference: MaybeSignal<Option<Constellation>>,
+    frames: VecDeque<Vec<u8>>,
 }
 
 #[component]
 /// Constellation Sink
+///
+/// Shows the symbols of the last `persistence` frames (default: 1), older frames fading out.
+/// If `points` is set, only the latest points of each frame are shown. An optional `reference`
+/// constellation is drawn on top.
 pub fn ConstellationSink(
     #[prop(into)] width: MaybeSignal<f32>,
+    #[prop(into, optional, default = MaybeSignal::Static(1))] persistence: MaybeSignal<usize>,
+    #[prop(into, optional)] points: MaybeSignal<Option<usize>>,
+    #[prop(into, optional)] reference: MaybeSignal<Option<Constellation>>,
     #[prop(optional, into, default = "ws://127.0.0.1:9002".to_string())] websocket: String,
 ) -> impl IntoView {
     let data = Rc::new(RefCell::new(None));
@@ -54,12 +101,13 @@ pub fn ConstellationSink(
             let vert_code = r"
                 attribute vec2 coordinates;
                 uniform float u_width;
+                uniform float u_point_size;
 
                 void main(void) {
                     float x = coordinates.x / u_width;
                     float y = coordinates.y / u_width;
                     gl_Position = vec4(x, y, 0.0, 1.0);
-                    gl_PointSize = 10.0;
+                    gl_PointSize = u_point_size;
                 }
             ";
 
@@ -69,9 +117,10 @@ pub fn ConstellationSink(
 
             let frag_code = r"
                 precision mediump float;
+                uniform vec4 u_color;
 
                 void main(void) {
-                    gl_FragColor = vec4(0, 0.5, 0.5, 0.4);
+                    gl_FragColor = u_color;
                 }
             ";
 
@@ -85,6 +134,9 @@ pub fn ConstellationSink(
             gl.link_program(&shader);
             gl.use_program(Some(&shader));
 
+            gl.enable(GL::BLEND);
+            gl.blend_func(GL::SRC_ALPHA, GL::ONE_MINUS_SRC_ALPHA);
+
             {
                 let gl = gl.clone();
                 let shader = shader.clone();
@@ -101,7 +153,10 @@ pub fn ConstellationSink(
                 canvas,
                 gl,
                 shader,
-                vertex_len: 0,
+                persistence,
+                points,
+                reference,
+                frames: VecDeque::new(),
             }));
             request_animation_frame(render(state, data))
         });
@@ -122,7 +177,10 @@ fn render(
                 canvas,
                 gl,
                 shader,
-                vertex_len,
+                persistence,
+                points,
+                reference,
+                frames,
             } = &mut (*state.borrow_mut());
 
             let display_width = canvas.client_width() as u32;
@@ -136,16 +194,45 @@ fn render(
                 gl.viewport(0, 0, display_width as i32, display_height as i32);
             }
 
-            if let Some(bytes) = data.borrow_mut().take() {
-                gl.buffer_data_with_u8_array(GL::ARRAY_BUFFER, &bytes, GL::DYNAMIC_DRAW);
-
-                *vertex_len = bytes.len() as i32 / 8;
+            if let Some(mut bytes) = data.borrow_mut().take() {
+                if let Some(n) = points.get_untracked() {
+                    let len = bytes.len() - (bytes.len() % 8);
+                    bytes.drain(0..len.saturating_sub(n * 8));
+                }
+                frames.push_back(bytes);
             };
+            let persistence = persistence.get_untracked().max(1);
+            while frames.len() > persistence {
+                frames.pop_front();
+            }
+
+            gl.clear_color(0.0, 0.0, 0.0, 0.0);
+            gl.clear(GL::COLOR_BUFFER_BIT);
 
             let position = gl.get_attrib_location(shader, "coordinates") as u32;
-            gl.vertex_attrib_pointer_with_i32(position, 2, GL::FLOAT, false, 0, 0);
-            gl.enable_vertex_attrib_array(position);
-            gl.draw_arrays(GL::POINTS, 0, *vertex_len);
+            let u_color = gl.get_uniform_location(shader, "u_color");
+            let u_point_size = gl.get_uniform_location(shader, "u_point_size");
+
+            gl.uniform1f(u_point_size.as_ref(), 10.0);
+            for (i, bytes) in frames.iter().enumerate() {
+                let alpha = 0.4 * (i + 1) as f32 / frames.len() as f32;
+                gl.uniform4f(u_color.as_ref(), 0.0, 0.5, 0.5, alpha);
+                gl.buffer_data_with_u8_array(GL::ARRAY_BUFFER, bytes, GL::DYNAMIC_DRAW);
+                gl.vertex_attrib_pointer_with_i32(position, 2, GL::FLOAT, false, 0, 0);
+                gl.enable_vertex_attrib_array(position);
+                gl.draw_arrays(GL::POINTS, 0, bytes.len() as i32 / 8);
+            }
+
+            if let Some(c) = reference.get_untracked() {
+                let p = c.points();
+                let view = unsafe { f32::view(&p) };
+                gl.uniform1f(u_point_size.as_ref(), 6.0);
+                gl.uniform4f(u_color.as_ref(), 1.0, 0.3, 0.3, 1.0);
+                gl.buffer_data_with_array_buffer_view(GL::ARRAY_BUFFER, &view, GL::DYNAMIC_DRAW);
+                gl.vertex_attrib_pointer_with_i32(position, 2, GL::FLOAT, false, 0, 0);
+                gl.enable_vertex_attrib_array(position);
+                gl.draw_arrays(GL::POINTS, 0, p.len() as i32 / 2);
+            }
         }
         request_animation_frame(render(state, data))
     }
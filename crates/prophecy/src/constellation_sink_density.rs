@@ -17,15 +17,20 @@ struct RenderState {
     canvas: HtmlElement<Canvas>,
     gl: GL,
     width: MaybeSignal<f32>,
+    decay: MaybeSignal<f32>,
     texture: [f32; BINS * BINS],
 }
 
 #[component]
 /// Constellation Sink
 ///
+/// Symbols are accumulated in a density map that fades with the given per-symbol `decay`
+/// factor. Values close to one result in long persistence, zero only shows the latest frame.
+///
 /// See WLAN receiver for an example.
 pub fn ConstellationSinkDensity(
     #[prop(into)] width: MaybeSignal<f32>,
+    #[prop(into, optional, default = MaybeSignal::Static(0.999))] decay: MaybeSignal<f32>,
     #[prop(optional, into, default = "ws://127.0.0.1:9002".to_string())] websocket: String,
 ) -> impl IntoView {
     let data = Rc::new(RefCell::new(None));
@@ -160,7 +165,7 @@ pub fn ConstellationSinkDensity(
             gl.vertex_attrib_pointer_with_i32(loc, 2, GL::FLOAT, false, 0, 0);
 
             let state = Rc::new(RefCell::new(RenderState {
-                canvas, gl, texture, width,
+                canvas, gl, texture, width, decay,
             }));
             request_animation_frame(render(state, data))
         });
@@ -182,6 +187,7 @@ fn render(
                 gl,
                 texture,
                 width,
+                decay,
             } = &mut (*state.borrow_mut());
 
             let display_width = canvas.client_width() as u32;
@@ -202,7 +208,10 @@ fn render(
                     std::slice::from_raw_parts(p as *const Complex32, s)
                 };
 
-                let decay = 0.999f32.powi(samples.len() as i32);
+                let decay = decay
+                    .get_untracked()
+                    .clamp(0.0, 1.0)
+                    .powi(samples.len() as i32);
                 texture.iter_mut().for_each(|v| *v *= decay);
 
                 let width = width.get_untracked();
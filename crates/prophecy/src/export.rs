@@ -0,0 +1,100 @@
+//! Export of displayed data as CSV and of plots as PNG.
+use leptos::wasm_bindgen::closure::Closure;
+use leptos::wasm_bindgen::JsCast;
+use leptos::wasm_bindgen::JsValue;
+use leptos::*;
+use web_sys::HtmlCanvasElement;
+
+/// Export requested through the buttons of a widget
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Csv,
+    Png,
+}
+
+/// Let the browser download `url` as `filename`.
+pub(crate) fn download(filename: &str, url: &str) {
+    let a: web_sys::HtmlElement = document().create_element("a").unwrap().unchecked_into();
+    let _ = a.set_attribute("href", url);
+    let _ = a.set_attribute("download", filename);
+    a.click();
+}
+
+/// Let the browser download `csv` as `filename`.
+pub(crate) fn download_csv(filename: &str, csv: &str) {
+    let parts = js_sys::Array::of1(&JsValue::from_str(csv));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("text/csv");
+    let url = web_sys::Blob::new_with_str_sequence_and_options(&parts, &options)
+        .and_then(|blob| web_sys::Url::create_object_url_with_blob(&blob));
+    if let Ok(url) = url {
+        download(filename, &url);
+        let _ = web_sys::Url::revoke_object_url(&url);
+    }
+}
+
+/// Let the browser download the content of `canvas` as PNG.
+///
+/// For WebGL canvases, this has to be called after drawing, in the same animation frame.
+pub(crate) fn download_canvas(filename: &str, canvas: &HtmlCanvasElement) {
+    if let Ok(url) = canvas.to_data_url() {
+        download(filename, &url);
+    }
+}
+
+/// Render an SVG element to PNG and let the browser download it.
+pub(crate) fn download_svg(filename: String, svg: &web_sys::Element) {
+    let (width, height) = (svg.client_width().max(1), svg.client_height().max(1));
+    let (xml, image) = match (
+        web_sys::XmlSerializer::new().and_then(|s| s.serialize_to_string(svg)),
+        web_sys::HtmlImageElement::new(),
+    ) {
+        (Ok(xml), Ok(image)) => (xml, image),
+        _ => return,
+    };
+    let url = format!(
+        "data:image/svg+xml;charset=utf-8,{}",
+        js_sys::encode_uri_component(&xml)
+    );
+
+    let onload = {
+        let image = image.clone();
+        Closure::once(move || {
+            let canvas: HtmlCanvasElement = document()
+                .create_element("canvas")
+                .unwrap()
+                .unchecked_into();
+            canvas.set_width(width as u32);
+            canvas.set_height(height as u32);
+            let ctx: web_sys::CanvasRenderingContext2d =
+                canvas.get_context("2d").unwrap().unwrap().unchecked_into();
+            let _ = ctx.draw_image_with_html_image_element_and_dw_and_dh(
+                &image,
+                0.0,
+                0.0,
+                width as f64,
+                height as f64,
+            );
+            download_canvas(&filename, &canvas);
+        })
+    };
+    image.set_onload(Some(onload.as_ref().unchecked_ref()));
+    onload.forget();
+    image.set_src(&url);
+}
+
+#[component]
+/// Buttons to request an export of the displayed data as CSV or of the plot as PNG.
+pub(crate) fn ExportButtons(
+    request: WriteSignal<Option<ExportFormat>>,
+    #[prop(into, optional)] button_class: String,
+) -> impl IntoView {
+    view! {
+        <div style="position: absolute; top: 0; right: 0">
+            <button class=button_class.clone()
+                on:click=move |_| request(Some(ExportFormat::Csv))>"CSV"</button>
+            <button class=button_class
+                on:click=move |_| request(Some(ExportFormat::Png))>"PNG"</button>
+        </div>
+    }
+}
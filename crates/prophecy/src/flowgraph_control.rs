@@ -0,0 +1,76 @@
+use futuresdr_types::FlowgraphDescription;
+use leptos::logging::*;
+use leptos::*;
+use std::time::Duration;
+
+use crate::FlowgraphHandle;
+
+#[component]
+/// Flowgraph Control
+///
+/// Shows whether the flowgraph is running and lists its blocks, with a button to terminate
+/// the flowgraph. The state is refreshed with the given `interval`.
+pub fn FlowgraphControl(
+    fg_handle: FlowgraphHandle,
+    #[prop(default = Duration::from_secs(1))] interval: Duration,
+    #[prop(into, optional)] button_class: String,
+    #[prop(into, optional)] list_class: String,
+) -> impl IntoView {
+    let (running, set_running) = create_signal(None::<bool>);
+    let (desc, set_desc) = create_signal(None::<FlowgraphDescription>);
+
+    {
+        let mut fg_handle = fg_handle.clone();
+        spawn_local(async move {
+            loop {
+                match fg_handle.description().await {
+                    Ok(d) => {
+                        set_running(Some(true));
+                        set_desc(Some(d));
+                    }
+                    Err(_) => {
+                        set_running(Some(false));
+                        break;
+                    }
+                }
+                gloo_timers::future::sleep(interval).await;
+            }
+        });
+    }
+
+    let terminate = move |_| {
+        let mut fg_handle = fg_handle.clone();
+        spawn_local(async move {
+            log!("terminating flowgraph {:?}", &fg_handle);
+            if fg_handle.terminate().await.is_err() {
+                warn!("terminating flowgraph {:?} failed", &fg_handle);
+            }
+        });
+    };
+
+    view! {
+        <div>
+            <span class="m-2">
+                "state: " {move || match running() {
+                    Some(true) => "running",
+                    Some(false) => "terminated",
+                    None => "connecting",
+                }}
+            </span>
+            <button class=button_class disabled=move || running() != Some(true) on:click=terminate>
+                "Stop"
+            </button>
+            <ul class=list_class> {move || {
+                desc.get()
+                    .map(|d| d.blocks)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|b| view! {
+                        <li>{format!("{}: {} ({})", b.id, b.instance_name, b.type_name)}</li>
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            </ul>
+        </div>
+    }
+}
@@ -172,6 +172,22 @@ impl FlowgraphHandle {
             Self::Web(h) => Ok(h.description().await?),
         }
     }
+    pub async fn terminate(&mut self) -> Result<(), Error> {
+        match self {
+            Self::Remote(u) => {
+                let response = Request::post(&format!("{u}terminate/")).send().await?;
+                if response.ok() {
+                    Ok(())
+                } else {
+                    Err(Error::Gloo(format!("Request failed {:?}", response)))
+                }
+            }
+            Self::Web(h) => h
+                .terminate()
+                .await
+                .or(Err(Error::FutureSdr(runtime::Error::FlowgraphTerminated))),
+        }
+    }
     pub async fn call(
         &mut self,
         block_id: usize,
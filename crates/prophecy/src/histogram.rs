@@ -0,0 +1,85 @@
+use futures::StreamExt;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use leptos::logging::*;
+use leptos::*;
+
+/// Count `samples` into `bins` bins between `min` and `max`.
+///
+/// Samples outside the range are counted in the first or last bin.
+pub fn histogram(samples: &[f32], bins: usize, min: f32, max: f32) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    if bins == 0 || max <= min {
+        return counts;
+    }
+    for s in samples.iter().filter(|s| s.is_finite()) {
+        let i = ((s - min) / (max - min) * bins as f32).floor();
+        let i = (i.max(0.0) as usize).min(bins - 1);
+        counts[i] += 1;
+    }
+    counts
+}
+
+#[component]
+/// Histogram
+///
+/// Histogram of `f32` samples received through a WebSocket, e.g., from a
+/// `WebsocketSink<f32>`. Each WebSocket message is evaluated separately. If no `range` is
+/// given, the range is set to the minimum and maximum of each message.
+pub fn Histogram(
+    #[prop(optional, into, default = "ws://127.0.0.1:9005".to_string())] websocket: String,
+    #[prop(default = 64)] bins: usize,
+    #[prop(into, optional)] range: Option<MaybeSignal<(f32, f32)>>,
+    #[prop(into, optional)] bar_class: String,
+    #[prop(into, optional)] label_class: String,
+) -> impl IntoView {
+    let (counts, set_counts) = create_signal((vec![0usize; bins], 0.0f32, 0.0f32));
+
+    spawn_local(async move {
+        let mut ws = WebSocket::open(&websocket).unwrap();
+        while let Some(msg) = ws.next().await {
+            match msg {
+                Ok(Message::Bytes(b)) => {
+                    let samples: Vec<f32> = b
+                        .chunks_exact(4)
+                        .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+                        .collect();
+                    let (min, max) = match range {
+                        Some(r) => r.get_untracked(),
+                        None => samples
+                            .iter()
+                            .filter(|s| s.is_finite())
+                            .fold((f32::INFINITY, f32::NEG_INFINITY), |(a, b), s| {
+                                (a.min(*s), b.max(*s))
+                            }),
+                    };
+                    set_counts((histogram(&samples, bins, min, max), min, max));
+                }
+                _ => {
+                    log!("Histogram: WebSocket {:?}", msg);
+                }
+            }
+        }
+        log!("Histogram: WebSocket Closed");
+    });
+
+    view! {
+        <div style="display: flex; flex-direction: column; width: 100%; height: 100%">
+            <div style="display: flex; align-items: flex-end; flex-grow: 1">
+                {move || {
+                    let (c, _, _) = counts.get();
+                    let peak = c.iter().copied().max().unwrap_or(0).max(1);
+                    c.into_iter()
+                        .map(|n| {
+                            let style = format!("flex-grow: 1; height: {}%", n * 100 / peak);
+                            view! { <div class=bar_class.clone() style=style /> }
+                        })
+                        .collect::<Vec<_>>()
+                }}
+            </div>
+            <div class=label_class style="display: flex; justify-content: space-between">
+                <span>{move || format!("{:.3}", counts.get().1)}</span>
+                <span>{move || format!("{:.3}", counts.get().2)}</span>
+            </div>
+        </div>
+    }
+}
@@ -0,0 +1,72 @@
+use futures::StreamExt;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use leptos::logging::*;
+use leptos::*;
+
+#[component]
+/// Level Meter
+///
+/// Shows peak and RMS level in dBFS of `f32` samples received through a WebSocket, e.g., from
+/// a `WebsocketSink<f32>`. Each WebSocket message is one measurement.
+pub fn LevelMeter(
+    #[prop(optional, into, default = "ws://127.0.0.1:9004".to_string())] websocket: String,
+    #[prop(into, default = MaybeSignal::Static(-60.0))] min: MaybeSignal<f32>,
+    #[prop(into, optional)] bar_class: String,
+    #[prop(into, optional)] label_class: String,
+) -> impl IntoView {
+    let (peak, set_peak) = create_signal(f32::NEG_INFINITY);
+    let (rms, set_rms) = create_signal(f32::NEG_INFINITY);
+
+    spawn_local(async move {
+        let mut ws = WebSocket::open(&websocket).unwrap();
+        while let Some(msg) = ws.next().await {
+            match msg {
+                Ok(Message::Bytes(b)) => {
+                    let samples: Vec<f32> = b
+                        .chunks_exact(4)
+                        .map(|c| f32::from_le_bytes(c.try_into().unwrap()))
+                        .collect();
+                    if samples.is_empty() {
+                        continue;
+                    }
+                    let p = samples.iter().fold(0.0f32, |a, s| a.max(s.abs()));
+                    let r =
+                        (samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32).sqrt();
+                    set_peak(20.0 * p.log10());
+                    set_rms(20.0 * r.log10());
+                }
+                _ => {
+                    log!("LevelMeter: WebSocket {:?}", msg);
+                }
+            }
+        }
+        log!("LevelMeter: WebSocket Closed");
+    });
+
+    let width = move |db: f32| {
+        let min = min.get();
+        let w = ((db - min) / -min).clamp(0.0, 1.0) * 100.0;
+        format!("width: {w}%; height: 100%")
+    };
+    let format_db = |db: f32| {
+        if db.is_finite() {
+            format!("{db:.1} dBFS")
+        } else {
+            "-inf dBFS".to_string()
+        }
+    };
+
+    view! {
+        <div>
+            <div style="height: 0.5em">
+                <div class=bar_class.clone() style=move || width(peak()) />
+            </div>
+            <div style="height: 0.5em">
+                <div class=bar_class style=move || width(rms()) />
+            </div>
+            <span class=label_class>
+                "peak: " {move || format_db(peak())} " rms: " {move || format_db(rms())}
+            </span>
+        </div>
+    }
+}
@@ -8,7 +8,18 @@ pub use leptos;
 mod array_view;
 pub use array_view::ArrayView;
 
+mod bearing_plot;
+pub use bearing_plot::bearing_from_pmt;
+pub use bearing_plot::BearingPlot;
+
+mod button;
+pub use button::Button;
+
+mod colormap;
+pub use colormap::ColorMap;
+
 mod constellation_sink;
+pub use constellation_sink::Constellation;
 pub use constellation_sink::ConstellationSink;
 
 mod constellation_sink_density;
@@ -21,15 +32,38 @@ pub use handle::poll_periodically;
 pub use handle::FlowgraphHandle;
 pub use handle::RuntimeHandle;
 
+mod export;
+
 mod flowgraph_canvas;
 pub use flowgraph_canvas::FlowgraphCanvas;
 
+mod flowgraph_control;
+pub use flowgraph_control::FlowgraphControl;
+
 mod flowgraph_mermaid;
 pub use flowgraph_mermaid::FlowgraphMermaid;
 
+mod histogram;
+pub use histogram::histogram;
+pub use histogram::Histogram;
+
+mod level_meter;
+pub use level_meter::LevelMeter;
+
 mod list_selector;
 pub use list_selector::ListSelector;
 
+mod map;
+pub use map::Map;
+pub use map::MapTiles;
+
+mod message_log;
+pub use message_log::format_pmt;
+pub use message_log::MessageLog;
+
+mod number_input;
+pub use number_input::NumberInput;
+
 mod pmt;
 pub use pmt::Pmt;
 pub use pmt::PmtInput;
@@ -38,13 +72,25 @@ pub use pmt::PmtInputList;
 mod radio_selector;
 pub use radio_selector::RadioSelector;
 
+mod scope;
+pub use scope::find_trigger;
+pub use scope::Scope;
+pub use scope::TriggerEdge;
+pub use scope::TriggerMode;
+
 mod slider;
 pub use slider::Slider;
 
+mod spectrogram;
+pub use spectrogram::Spectrogram;
+
 mod time_sink;
 pub use time_sink::TimeSink;
 pub use time_sink::TimeSinkMode;
 
+mod toggle;
+pub use toggle::Toggle;
+
 mod waterfall;
 pub use waterfall::Waterfall;
 pub use waterfall::WaterfallMode;
@@ -0,0 +1,342 @@
+use futures::StreamExt;
+use futuresdr_types::Pmt;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use leptos::logging::*;
+use leptos::*;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+const TILE_SIZE: f64 = 256.0;
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Source of the map tiles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum MapTiles {
+    /// Tiles from the OpenStreetMap tile server
+    #[default]
+    OpenStreetMap,
+    /// Tiles from a URL template with `{z}`, `{x}`, and `{y}` placeholders, e.g.,
+    /// `/tiles/{z}/{x}/{y}.png` for tiles that are served with the GUI for offline use
+    Url(String),
+    /// No tiles, only the overlays are drawn
+    None,
+}
+
+impl MapTiles {
+    fn url(&self, z: u8, x: i64, y: i64) -> Option<String> {
+        let template = match self {
+            MapTiles::OpenStreetMap => "https://tile.openstreetmap.org/{z}/{x}/{y}.png",
+            MapTiles::Url(u) => u,
+            MapTiles::None => return None,
+        };
+        Some(
+            template
+                .replace("{z}", &z.to_string())
+                .replace("{x}", &x.to_string())
+                .replace("{y}", &y.to_string()),
+        )
+    }
+}
+
+/// Web Mercator pixel coordinates of a position at zoom level `z`.
+fn project(lat: f64, lon: f64, z: u8) -> (f64, f64) {
+    let scale = TILE_SIZE * 2f64.powi(z as i32);
+    let lat = lat.clamp(-85.0511, 85.0511).to_radians();
+    let x = (lon + 180.0) / 360.0 * scale;
+    let y = (1.0 - (lat.tan() + 1.0 / lat.cos()).ln() / std::f64::consts::PI) / 2.0 * scale;
+    (x, y)
+}
+
+/// Meters per pixel at latitude `lat` and zoom level `z`.
+fn meters_per_pixel(lat: f64, z: u8) -> f64 {
+    2.0 * std::f64::consts::PI * EARTH_RADIUS * lat.to_radians().cos()
+        / (TILE_SIZE * 2f64.powi(z as i32))
+}
+
+/// Position `east` and `north` meters away from `origin`.
+fn offset(origin: (f64, f64), east: f64, north: f64) -> (f64, f64) {
+    let (lat, lon) = origin;
+    (
+        lat + (north / EARTH_RADIUS).to_degrees(),
+        lon + (east / (EARTH_RADIUS * lat.to_radians().cos())).to_degrees(),
+    )
+}
+
+/// Bearing line of a receiver
+#[derive(Clone, Debug, PartialEq)]
+struct Line {
+    position: (f64, f64),
+    azimuth: Option<f64>,
+}
+
+/// Position estimate with its confidence ellipse
+#[derive(Clone, Debug, PartialEq)]
+struct Fix {
+    position: (f64, f64),
+    major: f64,
+    minor: f64,
+    orientation: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct MapState {
+    receivers: HashMap<String, Line>,
+    fixes: VecDeque<Fix>,
+}
+
+impl MapState {
+    /// Update the state with a message of a direction-finding block.
+    ///
+    /// Returns `false` if the message is not understood.
+    fn update(
+        &mut self,
+        p: &Pmt,
+        stations: &HashMap<String, (f64, f64)>,
+        origin: (f64, f64),
+    ) -> bool {
+        let m = match p {
+            Pmt::MapStrPmt(m) => m,
+            _ => return false,
+        };
+        let float = |key: &str| -> Option<f64> {
+            let f: f64 = m.get(key)?.clone().try_into().ok()?;
+            f.is_finite().then_some(f)
+        };
+        let station = match m.get("station") {
+            Some(Pmt::String(s)) => Some(s.clone()),
+            _ => None,
+        };
+
+        // Triangulation output in the local plane
+        if station.is_none() {
+            if let (Some(x), Some(y)) = (float("x"), float("y")) {
+                self.fixes.push_back(Fix {
+                    position: offset(origin, x, y),
+                    major: float("major").unwrap_or(0.0),
+                    minor: float("minor").unwrap_or(0.0),
+                    orientation: float("orientation").unwrap_or(0.0),
+                });
+                return true;
+            }
+        }
+
+        let name = station.unwrap_or_else(|| "receiver".to_string());
+        // Georeferenced bearing with receiver position and azimuth
+        if let (Some(lat), Some(lon)) = (float("lat"), float("lon")) {
+            self.receivers.insert(
+                name,
+                Line {
+                    position: (lat, lon),
+                    azimuth: float("azimuth"),
+                },
+            );
+            return true;
+        }
+        // Bearing of a known station in the local plane, i.e., counter-clockwise from east
+        if let (Some(position), Some(bearing)) = (stations.get(&name), float("bearing")) {
+            let position = match (float("x"), float("y")) {
+                (Some(x), Some(y)) => offset(origin, x, y),
+                _ => *position,
+            };
+            self.receivers.insert(
+                name,
+                Line {
+                    position,
+                    azimuth: Some((90.0 - bearing).rem_euclid(360.0)),
+                },
+            );
+            return true;
+        }
+        false
+    }
+}
+
+#[component]
+/// Map
+///
+/// Slippy map for direction finding that shows receiver positions, bearing lines, and position
+/// fixes received as JSON text messages through a WebSocket, e.g., from a `WebsocketPmtSink`.
+/// The map understands the messages of the direction-finding blocks:
+///
+/// - the output of the `Georeference` block, i.e., a map with `lat`, `lon`, and optionally
+///   `azimuth` and the name of the `station`, which moves the receiver and sets its bearing
+///   line,
+/// - the input of the `Triangulation` block, i.e., a map with the `station` and the `bearing`
+///   in the local plane, for `stations` with known position,
+/// - the output of the `Triangulation` block, i.e., a map with `x`, `y`, and the confidence
+///   ellipse, which is added as fix. Only the latest `fixes` are kept.
+///
+/// Positions are latitude and longitude in degrees. The local plane of the `Triangulation`
+/// block has its x-axis pointing east and its y-axis pointing north from the `origin`, which
+/// defaults to the `center` of the map. Bearing lines are drawn with `line_length` meters.
+///
+/// Tiles are loaded from OpenStreetMap by default. For offline use, tiles can be served with
+/// the GUI, using a [MapTiles::Url], or disabled with [MapTiles::None].
+pub fn Map(
+    #[prop(optional, into, default = "ws://127.0.0.1:9007".to_string())] websocket: String,
+    center: (f64, f64),
+    #[prop(default = 13)] zoom: u8,
+    #[prop(default = 768)] width: u32,
+    #[prop(default = 512)] height: u32,
+    #[prop(optional)] tiles: MapTiles,
+    #[prop(optional)] stations: Vec<(String, (f64, f64))>,
+    #[prop(optional)] origin: Option<(f64, f64)>,
+    #[prop(default = 10_000.0)] line_length: f64,
+    #[prop(default = 20)] fixes: usize,
+    #[prop(into, optional)] button_class: String,
+) -> impl IntoView {
+    let (zoom, set_zoom) = create_signal(zoom.min(19));
+    let (state, set_state) = create_signal(MapState::default());
+    let stations: HashMap<String, (f64, f64)> = stations.into_iter().collect();
+    let origin = origin.unwrap_or(center);
+
+    {
+        let stations = stations.clone();
+        spawn_local(async move {
+            let mut ws = WebSocket::open(&websocket).unwrap();
+            while let Some(msg) = ws.next().await {
+                match msg {
+                    Ok(Message::Text(t)) => match serde_json::from_str::<Pmt>(&t) {
+                        Ok(p) => {
+                            set_state.update(|s| {
+                                if !s.update(&p, &stations, origin) {
+                                    log!("Map: unknown message {:?}", p);
+                                }
+                                while s.fixes.len() > fixes {
+                                    s.fixes.pop_front();
+                                }
+                            });
+                        }
+                        Err(_) => log!("Map: cannot parse PMT {}", t),
+                    },
+                    _ => {
+                        log!("Map: WebSocket {:?}", msg);
+                    }
+                }
+            }
+            log!("Map: WebSocket Closed");
+        });
+    }
+
+    let (w, h) = (width as f64, height as f64);
+    let view_box = move || {
+        let (cx, cy) = project(center.0, center.1, zoom());
+        format!("{} {} {w} {h}", cx - w / 2.0, cy - h / 2.0)
+    };
+
+    let tile_images = {
+        let tiles = tiles.clone();
+        move || {
+            let z = zoom();
+            let n = 1i64 << z;
+            let (cx, cy) = project(center.0, center.1, z);
+            let x0 = ((cx - w / 2.0) / TILE_SIZE).floor() as i64;
+            let x1 = ((cx + w / 2.0) / TILE_SIZE).floor() as i64;
+            let y0 = (((cy - h / 2.0) / TILE_SIZE).floor() as i64).max(0);
+            let y1 = (((cy + h / 2.0) / TILE_SIZE).floor() as i64).min(n - 1);
+            (y0..=y1)
+                .flat_map(|y| (x0..=x1).map(move |x| (x, y)))
+                .filter_map(|(x, y)| {
+                    let url = tiles.url(z, x.rem_euclid(n), y)?;
+                    let (px, py) = (x as f64 * TILE_SIZE, y as f64 * TILE_SIZE);
+                    Some(view! {
+                        <image href=url x=px y=py width=TILE_SIZE height=TILE_SIZE />
+                    })
+                })
+                .collect::<Vec<_>>()
+        }
+    };
+
+    let known = stations
+        .iter()
+        .map(|(name, (lat, lon))| (name.clone(), *lat, *lon))
+        .collect::<Vec<_>>();
+    let known_stations = move || {
+        let z = zoom();
+        known
+            .iter()
+            .map(|(name, lat, lon)| {
+                let (x, y) = project(*lat, *lon, z);
+                view! {
+                    <rect x=x - 4.0 y=y - 4.0 width="8" height="8" fill="gray" />
+                    <text x=x + 6.0 y=y - 6.0 font-size="12">{name.clone()}</text>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let receivers = move || {
+        let z = zoom();
+        state
+            .get()
+            .receivers
+            .into_iter()
+            .map(|(name, line)| {
+                let (lat, lon) = line.position;
+                let (x, y) = project(lat, lon, z);
+                let bearing = line.azimuth.map(|a| {
+                    let a = a.to_radians();
+                    let end = offset(line.position, line_length * a.sin(), line_length * a.cos());
+                    let (ex, ey) = project(end.0, end.1, z);
+                    view! {
+                        <line x1=x y1=y x2=ex y2=ey stroke="#3b82f6" stroke-width="2" />
+                    }
+                });
+                view! {
+                    {bearing}
+                    <circle cx=x cy=y r="5" fill="#3b82f6" stroke="white" />
+                    <text x=x + 6.0 y=y - 6.0 font-size="12">{name}</text>
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let position_fixes = move || {
+        let z = zoom();
+        let f = state.get().fixes;
+        let n = f.len();
+        f.into_iter()
+            .enumerate()
+            .map(|(i, fix)| {
+                let (lat, lon) = fix.position;
+                let (x, y) = project(lat, lon, z);
+                let mpp = meters_per_pixel(lat, z);
+                let opacity = (i + 1) as f64 / n as f64;
+                let transform = format!("rotate({} {x} {y})", -fix.orientation);
+                view! {
+                    <ellipse cx=x cy=y rx=fix.major / mpp ry=fix.minor / mpp transform=transform
+                        fill="#ef4444" fill-opacity=0.2 * opacity stroke="#ef4444"
+                        stroke-opacity=opacity />
+                    <circle cx=x cy=y r="4" fill="#ef4444" fill-opacity=opacity />
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let attribution = (tiles == MapTiles::OpenStreetMap).then(|| {
+        view! {
+            <span style="position: absolute; right: 0; bottom: 0; font-size: 10px;
+                background: rgba(255, 255, 255, 0.7)">
+                "© OpenStreetMap contributors"
+            </span>
+        }
+    });
+
+    view! {
+        <div style=format!("position: relative; width: {width}px; height: {height}px")>
+            <svg viewBox=view_box width=width height=height>
+                {tile_images}
+                {known_stations}
+                {receivers}
+                {position_fixes}
+            </svg>
+            <div style="position: absolute; left: 0; top: 0">
+                <button class=button_class.clone()
+                    on:click=move |_| set_zoom.update(|z| *z = (*z + 1).min(19))>"+"</button>
+                <button class=button_class
+                    on:click=move |_| set_zoom.update(|z| *z = z.saturating_sub(1))>"-"</button>
+            </div>
+            {attribution}
+        </div>
+    }
+}
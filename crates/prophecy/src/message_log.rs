@@ -0,0 +1,92 @@
+use futures::StreamExt;
+use futuresdr_types::Pmt;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use leptos::logging::*;
+use leptos::*;
+use std::collections::VecDeque;
+
+/// Format a PMT for the message log.
+///
+/// Blobs are shown as hex dump with ASCII representation, maps as `key: value` lines.
+pub fn format_pmt(p: &Pmt) -> String {
+    match p {
+        Pmt::Blob(b) => b
+            .chunks(16)
+            .enumerate()
+            .map(|(i, c)| {
+                let hex: Vec<String> = c.iter().map(|b| format!("{b:02x}")).collect();
+                let ascii: String = c
+                    .iter()
+                    .map(|b| {
+                        if b.is_ascii_graphic() || *b == b' ' {
+                            *b as char
+                        } else {
+                            '.'
+                        }
+                    })
+                    .collect();
+                format!("{:04x}  {:<47}  {}", i * 16, hex.join(" "), ascii)
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        Pmt::MapStrPmt(m) => {
+            let mut keys: Vec<&String> = m.keys().collect();
+            keys.sort();
+            keys.into_iter()
+                .map(|k| format!("{}: {}", k, format_pmt(&m[k])))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        p => p.to_string(),
+    }
+}
+
+#[component]
+/// Message Log
+///
+/// Scrolling log of PMTs received as JSON text messages through a WebSocket, e.g., from a
+/// `WebsocketPmtSink`. Only the latest `max_entries` messages are kept.
+pub fn MessageLog(
+    #[prop(optional, into, default = "ws://127.0.0.1:9003".to_string())] websocket: String,
+    #[prop(default = 100)] max_entries: usize,
+    #[prop(into, optional)] entry_class: String,
+) -> impl IntoView {
+    let (entries, set_entries) = create_signal(VecDeque::<(usize, String)>::new());
+
+    spawn_local(async move {
+        let mut ws = WebSocket::open(&websocket).unwrap();
+        let mut n = 0;
+        while let Some(msg) = ws.next().await {
+            match msg {
+                Ok(Message::Text(t)) => match serde_json::from_str::<Pmt>(&t) {
+                    Ok(p) => {
+                        set_entries.update(|e| {
+                            e.push_back((n, format_pmt(&p)));
+                            while e.len() > max_entries {
+                                e.pop_front();
+                            }
+                        });
+                        n += 1;
+                    }
+                    Err(_) => log!("MessageLog: cannot parse PMT {}", t),
+                },
+                _ => {
+                    log!("MessageLog: WebSocket {:?}", msg);
+                }
+            }
+        }
+        log!("MessageLog: WebSocket Closed");
+    });
+
+    view! {
+        <div style="overflow-y: auto; height: 100%">
+            <For
+                each=move || entries.get()
+                key=|(n, _)| *n
+                children=move |(_, s)| view! {
+                    <pre class=entry_class.clone()>{s}</pre>
+                }
+            />
+        </div>
+    }
+}
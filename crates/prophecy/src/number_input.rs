@@ -0,0 +1,96 @@
+use futuresdr_types::Pmt;
+use futuresdr_types::PortId;
+use leptos::html::Input;
+use leptos::logging::*;
+use leptos::*;
+
+use crate::FlowgraphHandle;
+
+const ENTER_KEY: u32 = 13;
+
+#[component]
+/// Numeric Input
+///
+/// Pressing enter sends the value, scaled by `multiplier`, as [`Pmt::F64`]. Values are entered
+/// (and `init` is given) in units of the multiplier, e.g., MHz with a multiplier of `1e6`. Blocks
+/// that expect another type get the value converted with `to_pmt`, e.g.,
+/// `|v| Pmt::U32(v as u32)`. If a `value` signal is given (e.g., from
+/// [`poll_periodically`](crate::poll_periodically)), the field follows the value reported by the
+/// block.
+pub fn NumberInput<P: Into<PortId>>(
+    fg_handle: FlowgraphHandle,
+    block_id: usize,
+    handler: P,
+    #[prop(default = 1.0)] multiplier: f64,
+    #[prop(default = Pmt::F64)] to_pmt: fn(f64) -> Pmt,
+    #[prop(optional)] init: Option<f64>,
+    #[prop(into, optional)] value: Option<Signal<Pmt>>,
+    #[prop(into, optional)] unit: String,
+    #[prop(into, optional)] input_class: String,
+    #[prop(into, optional)] error_class: String,
+    #[prop(into, optional)] unit_class: String,
+) -> impl IntoView {
+    let handler = handler.into();
+    let (error, set_error) = create_signal(false);
+    let classes = create_memo(move |_| {
+        if error() {
+            format!("{} {}", input_class, error_class)
+        } else {
+            input_class.to_string()
+        }
+    });
+
+    let input_ref = create_node_ref::<Input>();
+    if let Some(value) = value {
+        create_effect(move |_| {
+            let v = match value.get() {
+                Pmt::F64(v) => v,
+                Pmt::F32(v) => v as f64,
+                Pmt::U32(v) => v as f64,
+                Pmt::U64(v) => v as f64,
+                Pmt::Usize(v) => v as f64,
+                _ => return,
+            };
+            if let Some(input) = input_ref.get() {
+                input.set_value(&(v / multiplier).to_string());
+            }
+        });
+    }
+
+    let submit = move || {
+        let input = input_ref().unwrap();
+        let v = match input.value().trim().parse::<f64>() {
+            Ok(v) => v * multiplier,
+            Err(_) => {
+                set_error(true);
+                return;
+            }
+        };
+        let mut fg_handle = fg_handle.clone();
+        let handler = handler.clone();
+        spawn_local(async move {
+            let pmt = to_pmt(v);
+            log!(
+                "sending block {} handler {:?} pmt {:?}",
+                block_id,
+                &handler,
+                &pmt
+            );
+            let _ = fg_handle.call(block_id, handler, pmt).await;
+        });
+    };
+
+    let on_input = move |ev: web_sys::KeyboardEvent| {
+        ev.stop_propagation();
+        set_error(false);
+        if ev.key_code() == ENTER_KEY {
+            submit();
+        }
+    };
+
+    view! {
+        <input type="number" step="any" class=classes node_ref=input_ref on:keydown=on_input
+            value=init.map(|v| v.to_string()).unwrap_or_default() />
+        <span class=unit_class>{unit}</span>
+    }
+}
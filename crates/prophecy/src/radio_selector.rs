@@ -10,12 +10,13 @@ use crate::FlowgraphHandle;
 #[component]
 /// Radio Selector
 ///
-/// Selecting an entry triggers sending a PMT.
+/// Selecting an entry triggers sending a PMT. The `setter` receives the selected PMT.
 pub fn RadioSelector<P: Into<PortId>, V: IntoIterator<Item = (String, Pmt)>>(
     fg_handle: FlowgraphHandle,
     block_id: usize,
     handler: P,
     values: V,
+    #[prop(optional)] setter: Option<WriteSignal<Pmt>>,
     #[prop(into, optional)] label_class: String,
 ) -> impl IntoView {
     let handler = handler.into();
@@ -33,6 +34,9 @@ pub fn RadioSelector<P: Into<PortId>, V: IntoIterator<Item = (String, Pmt)>>(
                 view! {
                     <input type="radio" id={id.to_string()} name={uuid.to_string()} on:change=move |_| {
                         let p = p.clone();
+                        if let Some(setter) = setter {
+                            setter(p.clone());
+                        }
                         let mut fg_handle = fg_handle.clone();
                         let handler = handler.clone();
                         leptos::spawn_local(async move {
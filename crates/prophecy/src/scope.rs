@@ -0,0 +1,317 @@
+use futures::StreamExt;
+use gloo_net::websocket::{futures::WebSocket, Message};
+use leptos::logging::*;
+use leptos::*;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+use crate::export::download_csv;
+use crate::export::download_svg;
+use crate::export::ExportButtons;
+use crate::export::ExportFormat;
+
+const MAX_BUFFER: usize = 1 << 16;
+const MAX_POINTS: usize = 2048;
+const DEFAULT_SPAN: usize = 1024;
+const TICKS: usize = 5;
+
+/// Trigger mode of the [Scope](crate::Scope).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TriggerMode {
+    /// Show the latest samples without waiting for a trigger
+    #[default]
+    FreeRun,
+    /// Only update the display on a trigger
+    Normal,
+    /// Update the display on the next trigger while armed
+    Single,
+}
+
+/// Trigger edge of the [Scope](crate::Scope).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TriggerEdge {
+    /// Trigger when the signal rises above the level
+    #[default]
+    Rising,
+    /// Trigger when the signal falls below the level
+    Falling,
+}
+
+/// Index of the first crossing of `level` with the given `edge` in `samples`, starting at
+/// index `from`.
+pub fn find_trigger(samples: &[f32], from: usize, level: f32, edge: TriggerEdge) -> Option<usize> {
+    (from.max(1)..samples.len()).find(|&i| {
+        let (a, b) = (samples[i - 1], samples[i]);
+        match edge {
+            TriggerEdge::Rising => a < level && b >= level,
+            TriggerEdge::Falling => a > level && b <= level,
+        }
+    })
+}
+
+/// Reduce the samples to at most `MAX_POINTS` points, keeping minimum and maximum of each
+/// bucket, so that peaks stay visible. Points are `(index, value)` pairs.
+fn decimate(samples: &[f32]) -> Vec<(f32, f32)> {
+    if samples.len() <= MAX_POINTS {
+        return samples
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (i as f32, *s))
+            .collect();
+    }
+    let bucket = 2 * samples.len() / MAX_POINTS + 1;
+    samples
+        .chunks(bucket)
+        .enumerate()
+        .flat_map(|(k, c)| {
+            let (min, max) = c.iter().enumerate().fold((0, 0), |(lo, hi), (i, s)| {
+                (
+                    if *s < c[lo] { i } else { lo },
+                    if *s > c[hi] { i } else { hi },
+                )
+            });
+            let (a, b) = (min.min(max), min.max(max));
+            [
+                ((k * bucket + a) as f32, c[a]),
+                ((k * bucket + b) as f32, c[b]),
+            ]
+        })
+        .collect()
+}
+
+/// Format a time in seconds with a suitable unit.
+fn format_time(t: f64) -> String {
+    match t.abs() {
+        a if a == 0.0 => "0 s".to_string(),
+        a if a >= 1.0 => format!("{t:.3} s"),
+        a if a >= 1e-3 => format!("{:.3} ms", t * 1e3),
+        a if a >= 1e-6 => format!("{:.3} µs", t * 1e6),
+        _ => format!("{:.1} ns", t * 1e9),
+    }
+}
+
+/// Displayed trace
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Trace {
+    points: Vec<(f32, f32)>,
+    samples: usize,
+    pre: usize,
+    triggered: bool,
+}
+
+#[component]
+/// Scope
+///
+/// Oscilloscope for `f32` samples received through a WebSocket, e.g., from a
+/// `WebsocketSink<f32>`. The display shows `span` seconds of samples, based on the
+/// `sample_rate`. Without a `span`, 1024 samples are shown. The time axis is relative to the
+/// trigger, which is placed at the `pre_trigger` fraction of the span.
+///
+/// In [TriggerMode::FreeRun], the latest samples are shown without triggering. In
+/// [TriggerMode::Normal], the display is only updated when the signal crosses the
+/// `trigger_level` with the `trigger_edge`. [TriggerMode::Single] works like normal mode, but
+/// clears `armed` after the first trigger, freezing the display until `armed` is set again.
+///
+/// The amplitude axis ranges from `min` to `max` (default: `-1.0` to `1.0`).
+///
+/// With `export`, buttons are shown to download the displayed samples as CSV, i.e., the time
+/// relative to the trigger and the value of each sample, or the trace as PNG, named after
+/// `export_name`.
+pub fn Scope(
+    #[prop(optional, into, default = "ws://127.0.0.1:9008".to_string())] websocket: String,
+    #[prop(into, default = MaybeSignal::Static(-1.0))] min: MaybeSignal<f32>,
+    #[prop(into, default = MaybeSignal::Static(1.0))] max: MaybeSignal<f32>,
+    #[prop(into, default = MaybeSignal::Static(1.0))] sample_rate: MaybeSignal<f64>,
+    #[prop(into, optional)] span: MaybeSignal<Option<f64>>,
+    #[prop(into, optional)] trigger_mode: MaybeSignal<TriggerMode>,
+    #[prop(into, optional)] trigger_edge: MaybeSignal<TriggerEdge>,
+    #[prop(into, optional)] trigger_level: MaybeSignal<f32>,
+    #[prop(into, default = MaybeSignal::Static(0.1))] pre_trigger: MaybeSignal<f32>,
+    #[prop(optional)] armed: Option<RwSignal<bool>>,
+    #[prop(optional, into, default = "#3b82f6".to_string())] color: String,
+    #[prop(into, optional)] label_class: String,
+    #[prop(optional)] export: bool,
+    #[prop(into, default = "scope".to_string())] export_name: String,
+    #[prop(into, optional)] button_class: String,
+) -> impl IntoView {
+    let armed = armed.unwrap_or_else(|| create_rw_signal(true));
+    let (trace, set_trace) = create_signal(Trace::default());
+    // samples of the displayed trace before decimation
+    let window = Rc::new(RefCell::new(Vec::<f32>::new()));
+    let svg_ref = create_node_ref::<svg::Svg>();
+
+    let export_request = create_rw_signal(None::<ExportFormat>);
+    {
+        let window = window.clone();
+        create_effect(move |_| {
+            let format = match export_request.get() {
+                Some(f) => f,
+                None => return,
+            };
+            export_request.set(None);
+            match format {
+                ExportFormat::Csv => {
+                    let (pre, rate) = (trace.get_untracked().pre, sample_rate.get_untracked());
+                    let mut csv = String::from("time,value\n");
+                    for (i, v) in window.borrow().iter().enumerate() {
+                        csv += &format!("{},{}\n", (i as f64 - pre as f64) / rate, v);
+                    }
+                    download_csv(&format!("{export_name}.csv"), &csv);
+                }
+                ExportFormat::Png => {
+                    if let Some(svg) = svg_ref.get_untracked() {
+                        download_svg(format!("{export_name}.png"), &svg);
+                    }
+                }
+            }
+        });
+    }
+
+    spawn_local(async move {
+        let mut ws = WebSocket::open(&websocket).unwrap();
+        let mut buffer = VecDeque::<f32>::new();
+        while let Some(msg) = ws.next().await {
+            match msg {
+                Ok(Message::Bytes(b)) => {
+                    buffer.extend(
+                        b.chunks_exact(4)
+                            .map(|c| f32::from_le_bytes(c.try_into().unwrap())),
+                    );
+                    if buffer.len() > MAX_BUFFER {
+                        buffer.drain(..buffer.len() - MAX_BUFFER);
+                    }
+
+                    let n = match span.get_untracked() {
+                        Some(s) => (s * sample_rate.get_untracked()).round().max(2.0) as usize,
+                        None => DEFAULT_SPAN,
+                    }
+                    .min(MAX_BUFFER / 2);
+                    let pre = (pre_trigger.get_untracked().clamp(0.0, 1.0) * n as f32) as usize;
+                    let pre = pre.min(n - 1);
+                    let samples = buffer.make_contiguous();
+
+                    match trigger_mode.get_untracked() {
+                        TriggerMode::FreeRun => {
+                            if samples.len() >= n {
+                                *window.borrow_mut() = samples[samples.len() - n..].to_vec();
+                                set_trace(Trace {
+                                    points: decimate(&samples[samples.len() - n..]),
+                                    samples: n,
+                                    pre,
+                                    triggered: false,
+                                });
+                                buffer.clear();
+                            }
+                        }
+                        mode => {
+                            if mode == TriggerMode::Single && !armed.get_untracked() {
+                                buffer.clear();
+                                continue;
+                            }
+                            // the trigger needs `pre` samples before and `n - pre` after it
+                            let end = (samples.len() + pre + 1).saturating_sub(n);
+                            let level = trigger_level.get_untracked();
+                            let edge = trigger_edge.get_untracked();
+                            match find_trigger(&samples[..end], pre, level, edge) {
+                                Some(i) => {
+                                    *window.borrow_mut() = samples[i - pre..i - pre + n].to_vec();
+                                    set_trace(Trace {
+                                        points: decimate(&samples[i - pre..i - pre + n]),
+                                        samples: n,
+                                        pre,
+                                        triggered: true,
+                                    });
+                                    buffer.drain(..i - pre + n);
+                                    if mode == TriggerMode::Single {
+                                        armed.set(false);
+                                    }
+                                }
+                                None => {
+                                    if buffer.len() > n {
+                                        buffer.drain(..buffer.len() - n);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                _ => {
+                    log!("Scope: WebSocket {:?}", msg);
+                }
+            }
+        }
+        log!("Scope: WebSocket Closed");
+    });
+
+    // display coordinates, the view box is 1000 x 1000
+    let y = move |v: f32| {
+        let (min, max) = (min.get(), max.get());
+        (max - v) / (max - min) * 1000.0
+    };
+    let x = move |i: f32, n: usize| i / (n.max(2) - 1) as f32 * 1000.0;
+
+    let polyline = move || {
+        let t = trace.get();
+        t.points
+            .iter()
+            .map(|(i, v)| format!("{},{}", x(*i, t.samples), y(*v)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let level = move || {
+        let l = y(trigger_level.get());
+        view! {
+            <line x1="0" y1=l x2="1000" y2=l stroke="gray" stroke-dasharray="4"
+                vector-effect="non-scaling-stroke" />
+        }
+    };
+    let trigger_position = move || {
+        let t = trace.get();
+        let p = x(t.pre as f32, t.samples);
+        view! {
+            <line x1=p y1="0" x2=p y2="1000" stroke="gray" stroke-dasharray="4"
+                vector-effect="non-scaling-stroke" />
+        }
+    };
+    let ticks = move || {
+        let t = trace.get();
+        let rate = sample_rate.get();
+        (0..=TICKS)
+            .map(|k| {
+                let i = k as f64 * (t.samples.max(2) - 1) as f64 / TICKS as f64;
+                let time = (i - t.pre as f64) / rate;
+                let left = k as f64 * 100.0 / TICKS as f64;
+                let style = format!(
+                    "position: absolute; bottom: 0; left: {left}%; transform: translateX(-{left}%)"
+                );
+                view! { <span style=style>{format_time(time)}</span> }
+            })
+            .collect::<Vec<_>>()
+    };
+    let status = move || match (trigger_mode.get(), trace.get().triggered, armed.get()) {
+        (TriggerMode::FreeRun, _, _) => "free run",
+        (TriggerMode::Single, _, false) => "stopped",
+        (_, true, _) => "triggered",
+        (_, false, _) => "waiting",
+    };
+
+    view! {
+        <div style="position: relative; width: 100%; height: 100%">
+            <svg node_ref=svg_ref viewBox="0 0 1000 1000" preserveAspectRatio="none"
+                style="width: 100%; height: 100%">
+                {level}
+                {trigger_position}
+                <polyline points=polyline fill="none" stroke=color
+                    vector-effect="non-scaling-stroke" />
+            </svg>
+            <div class=label_class>
+                {ticks}
+                <span style="position: absolute; top: 0; left: 0">{status}</span>
+            </div>
+            {export.then(|| view! {
+                <ExportButtons request=export_request.write_only() button_class=button_class />
+            })}
+        </div>
+    }
+}
@@ -1,7 +1,9 @@
 use futuresdr_types::Pmt;
 use futuresdr_types::PortId;
+use leptos::html::Input;
 use leptos::wasm_bindgen::JsCast;
 use leptos::*;
+use uuid::Uuid;
 use web_sys::HtmlInputElement;
 
 use crate::FlowgraphHandle;
@@ -9,7 +11,13 @@ use crate::FlowgraphHandle;
 #[component]
 /// Slider
 ///
-/// Changing the value triggers sending a corresponding PMT.
+/// Changing the value triggers sending a corresponding PMT. If a `value` signal is given
+/// (e.g., from [`poll_periodically`](crate::poll_periodically)), the slider follows the value
+/// reported back by the block, so it does not drift from the actual state.
+///
+/// With `log_scale`, the slider position is logarithmic in the value, which is useful for
+/// ranges spanning several decades (`min` has to be positive). If `presets` are given, the
+/// value snaps to the closest preset, which are also shown as tick marks.
 pub fn Slider<P: Into<PortId>>(
     fg_handle: FlowgraphHandle,
     block_id: usize,
@@ -19,13 +27,61 @@ pub fn Slider<P: Into<PortId>>(
     #[prop(default = 1.0)] step: f64,
     #[prop(optional)] init: Option<f64>,
     #[prop(optional)] setter: Option<WriteSignal<f64>>,
+    #[prop(into, optional)] value: Option<Signal<Pmt>>,
+    #[prop(optional)] log_scale: bool,
+    #[prop(optional)] presets: Vec<f64>,
     #[prop(into, optional)] input_class: String,
 ) -> impl IntoView {
     let handler = handler.into();
     let init = init.unwrap_or(min);
+    let input_ref = create_node_ref::<Input>();
+    let list_id = Uuid::new_v4().to_string();
+
+    let to_pos = move |v: f64| if log_scale { v.log10() } else { v };
+    let from_pos = move |p: f64| if log_scale { 10f64.powf(p) } else { p };
+    let snap = {
+        let presets = presets.clone();
+        move |v: f64| {
+            presets
+                .iter()
+                .copied()
+                .min_by(|a, b| {
+                    (to_pos(*a) - to_pos(v))
+                        .abs()
+                        .total_cmp(&(to_pos(*b) - to_pos(v)).abs())
+                })
+                .unwrap_or(v)
+        }
+    };
+    let (pos_min, pos_max) = (to_pos(min), to_pos(max));
+    let pos_step = if log_scale {
+        (pos_max - pos_min) / 1000.0
+    } else {
+        step
+    };
+
+    if let Some(value) = value {
+        create_effect(move |_| {
+            let v = match value.get() {
+                Pmt::F64(v) => v,
+                Pmt::F32(v) => v as f64,
+                Pmt::U32(v) => v as f64,
+                Pmt::U64(v) => v as f64,
+                Pmt::Usize(v) => v as f64,
+                _ => return,
+            };
+            if let Some(input) = input_ref.get() {
+                input.set_value(&to_pos(v).to_string());
+            }
+            if let Some(setter) = setter {
+                setter(v);
+            }
+        });
+    }
 
     view! {
-        <input type="range" min=min max =max step=step value=init class=input_class
+        <input type="range" min=pos_min max=pos_max step=pos_step value=to_pos(init) class=input_class
+            node_ref=input_ref list=list_id.clone()
             on:change={
                 let handler = handler.clone();
                 let fg_handle = fg_handle.clone();
@@ -35,7 +91,8 @@ pub fn Slider<P: Into<PortId>>(
                     let mut fg_handle = fg_handle.clone();
                     let target = v.target().unwrap();
                     let input: HtmlInputElement = target.dyn_into().unwrap();
-                    let value: f64 = input.value().parse().unwrap();
+                    let value = snap(from_pos(input.value().parse().unwrap()));
+                    input.set_value(&to_pos(value).to_string());
                     let pmt = Pmt::F64(value);
 
                     if let Some(setter) = setter {
@@ -47,5 +104,11 @@ pub fn Slider<P: Into<PortId>>(
                     });
                 }
         } />
+        <datalist id=list_id> {
+            presets.iter()
+            .map(|p| view! { <option value={to_pos(*p).to_string()} /> })
+            .collect::<Vec<_>>()
+        }
+        </datalist>
     }
 }
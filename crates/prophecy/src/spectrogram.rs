@@ -0,0 +1,153 @@
+use futuresdr::blocks::FftWindow;
+use futuresdr_types::Pmt;
+use leptos::logging::*;
+use leptos::*;
+
+use crate::ColorMap;
+use crate::FlowgraphHandle;
+use crate::Waterfall;
+use crate::WaterfallMode;
+
+const FFT_SIZES: [usize; 6] = [256, 512, 1024, 2048, 4096, 8192];
+
+#[component]
+/// Spectrogram
+///
+/// [`Waterfall`] with controls for the `fft_size`, `overlap`, and `window` handlers of a
+/// FutureSDR `Spectrogram` block with the given `block_id`. The controls are initialized from
+/// the block's current settings. Since the block outputs a fixed number of bins per FFT, the
+/// FFT can be reconfigured while the waterfall keeps running.
+///
+/// `sample_rate`, `center_frequency`, `selection`, and the `export` settings are passed to the
+/// [`Waterfall`]. If a `selection_block` is given, selected channels are sent to its `freq` and
+/// `bandwidth` handlers.
+pub fn Spectrogram(
+    fg_handle: FlowgraphHandle,
+    block_id: usize,
+    #[prop(into)] min: MaybeSignal<f32>,
+    #[prop(into)] max: MaybeSignal<f32>,
+    #[prop(into, optional)] colormap: MaybeSignal<ColorMap>,
+    #[prop(optional)] mode: WaterfallMode,
+    #[prop(optional)] fft_sizes: Option<Vec<usize>>,
+    #[prop(into, default = MaybeSignal::Static(1.0))] sample_rate: MaybeSignal<f64>,
+    #[prop(into, optional)] center_frequency: MaybeSignal<f64>,
+    #[prop(optional)] selection: Option<WriteSignal<(f64, f64)>>,
+    #[prop(optional)] selection_block: Option<usize>,
+    #[prop(into, optional)] select_class: String,
+    #[prop(into, optional)] input_class: String,
+    #[prop(optional)] export: bool,
+    #[prop(into, default = "spectrogram".to_string())] export_name: String,
+    #[prop(into, optional)] button_class: String,
+) -> impl IntoView {
+    let fft_sizes = fft_sizes.unwrap_or_else(|| FFT_SIZES.to_vec());
+    let selection_handle = selection_block.map(|_| fg_handle.clone());
+    let (fft_size, set_fft_size) = create_signal(None::<usize>);
+    let (overlap, set_overlap) = create_signal(0.0f64);
+    let (window, set_window) = create_signal(FftWindow::Rectangular.to_string());
+
+    {
+        let mut fg_handle = fg_handle.clone();
+        spawn_local(async move {
+            if let Ok(Pmt::Usize(s)) = fg_handle.callback(block_id, "fft_size", Pmt::Null).await {
+                set_fft_size(Some(s));
+            }
+            if let Ok(Pmt::F32(o)) = fg_handle.callback(block_id, "overlap", Pmt::Null).await {
+                set_overlap(o as f64);
+            }
+            if let Ok(Pmt::String(w)) = fg_handle.callback(block_id, "window", Pmt::Null).await {
+                set_window(w);
+            }
+        });
+    }
+
+    let send = move |handler: &'static str, pmt: Pmt| {
+        let mut fg_handle = fg_handle.clone();
+        spawn_local(async move {
+            match fg_handle.callback(block_id, handler, pmt.clone()).await {
+                Ok(Pmt::Ok) => {}
+                r => log!(
+                    "Spectrogram: setting {} to {:?} failed: {:?}",
+                    handler,
+                    pmt,
+                    r
+                ),
+            }
+        });
+    };
+
+    let options = move || {
+        let mut sizes = fft_sizes.clone();
+        if let Some(s) = fft_size() {
+            if !sizes.contains(&s) {
+                sizes.push(s);
+                sizes.sort_unstable();
+            }
+        }
+        sizes
+            .into_iter()
+            .map(|s| {
+                let selected = move || fft_size() == Some(s);
+                view! { <option value={s.to_string()} prop:selected=selected>{s}</option> }
+            })
+            .collect::<Vec<_>>()
+    };
+
+    view! {
+        <div style="display: flex; flex-direction: column; width: 100%; height: 100%">
+            <div>
+                <select class=select_class.clone()
+                    on:change={
+                        let send = send.clone();
+                        move |v| {
+                            if let Ok(s) = event_target_value(&v).parse::<usize>() {
+                                set_fft_size(Some(s));
+                                send("fft_size", Pmt::Usize(s));
+                            }
+                        }
+                    }>
+                    {options}
+                </select>
+                <span class="p-2">"FFT size"</span>
+                <select class=select_class
+                    on:change={
+                        let send = send.clone();
+                        move |v| {
+                            let w = event_target_value(&v);
+                            set_window(w.clone());
+                            send("window", Pmt::String(w));
+                        }
+                    }> {
+                    FftWindow::ALL.into_iter()
+                        .map(|w| {
+                            let name = w.to_string();
+                            let selected = {
+                                let name = name.clone();
+                                move || window() == name
+                            };
+                            view! {
+                                <option value={name.clone()} prop:selected=selected>{name}</option>
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                }
+                </select>
+                <span class="p-2">"window"</span>
+                <input type="range" min="0" max="0.95" step="0.05" class=input_class
+                    prop:value=move || overlap().to_string()
+                    on:change=move |v| {
+                        if let Ok(o) = event_target_value(&v).parse::<f64>() {
+                            set_overlap(o);
+                            send("overlap", Pmt::F64(o));
+                        }
+                    } />
+                <span class="p-2">"overlap: " {move || format!("{:.0} %", overlap() * 100.0)}</span>
+            </div>
+            <div style="flex-grow: 1; min-height: 0">
+                <Waterfall min=min max=max colormap=colormap mode=mode sample_rate=sample_rate
+                    center_frequency=center_frequency selection=selection
+                    fg_handle=selection_handle block_id=selection_block.unwrap_or(0)
+                    export=export export_name=export_name button_class=button_class />
+            </div>
+        </div>
+    }
+}
@@ -1,15 +1,22 @@
 use futures::StreamExt;
+use futuresdr_types::Pmt;
 use gloo_net::websocket::{futures::WebSocket, Message};
 use leptos::html::Canvas;
 use leptos::logging::*;
 use leptos::*;
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
 use web_sys::WebGl2RenderingContext as GL;
 use web_sys::WebGlProgram;
 
+use crate::export::download_canvas;
+use crate::export::download_csv;
+use crate::export::ExportButtons;
+use crate::export::ExportFormat;
 use crate::ArrayView;
+use crate::FlowgraphHandle;
 
 const MAX_SAMPLES: usize = 4096;
 
@@ -29,15 +36,109 @@ struct RenderState {
     gl: GL,
     shader: WebGlProgram,
     vertex_len: i32,
+    averaging: MaybeSignal<f32>,
+    max_hold: MaybeSignal<bool>,
+    min_hold: MaybeSignal<bool>,
+    avg: Vec<f32>,
+    max: Vec<f32>,
+    min: Vec<f32>,
+    marks: ReadSignal<Vec<f32>>,
+    set_powers: WriteSignal<Vec<f32>>,
+    trace: Rc<RefCell<Vec<f32>>>,
+    export: RwSignal<Option<ExportFormat>>,
+    export_name: String,
+    sample_rate: MaybeSignal<f64>,
+    center_frequency: MaybeSignal<f64>,
+}
+
+const MARKER_STYLE: &str = "position: absolute; top: 0; bottom: 0; width: 0; \
+    border-left: 1px dashed white; pointer-events: none";
+const READOUT_STYLE: &str = "position: absolute; top: 0; left: 0; padding: 0.25em; \
+    background: rgba(0, 0, 0, 0.5); color: white; font-family: monospace; font-size: 0.8em; \
+    white-space: pre; pointer-events: none";
+
+/// Power in dB of the trace at position `x`, relative to the full span.
+fn power_at(trace: &[f32], x: f32) -> f32 {
+    if trace.is_empty() {
+        return f32::NAN;
+    }
+    let i = ((x * trace.len() as f32) as usize).min(trace.len() - 1);
+    10.0 * trace[i].log10()
+}
+
+/// CSV with the frequency of each bin and the power in dB of the given traces.
+fn spectrum_csv(traces: &[(&str, &[f32])], center_frequency: f64, sample_rate: f64) -> String {
+    let traces: Vec<&(&str, &[f32])> = traces.iter().filter(|(_, t)| !t.is_empty()).collect();
+    let l = traces.iter().map(|(_, t)| t.len()).min().unwrap_or(0);
+    let mut csv = String::from("frequency");
+    for (name, _) in traces.iter() {
+        csv += &format!(",{name}");
+    }
+    csv.push('\n');
+    for i in 0..l {
+        let f = center_frequency + (i as f64 / l as f64 - 0.5) * sample_rate;
+        csv += &f.to_string();
+        for (_, t) in traces.iter() {
+            csv += &format!(",{}", 10.0 * t[i].log10());
+        }
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Format a frequency in Hz with a suitable unit.
+fn format_freq(f: f64) -> String {
+    match f.abs() {
+        a if a >= 1e9 => format!("{:.6} GHz", f / 1e9),
+        a if a >= 1e6 => format!("{:.3} MHz", f / 1e6),
+        a if a >= 1e3 => format!("{:.3} kHz", f / 1e3),
+        _ => format!("{f:.1} Hz"),
+    }
 }
 
 #[component]
 /// Time Sink
+///
+/// Optionally, the displayed trace is smoothed with exponential `averaging` (factor of the
+/// previous trace, `0.0` disables averaging). Max- and min-hold traces are drawn on top when
+/// enabled and reset when disabled.
+///
+/// A click on the trace places a marker, a right click removes the closest marker. Up to
+/// `max_markers` markers are kept, placing more removes the oldest one. The markers show the
+/// frequency and power of the averaged trace and the deltas to the first marker. Frequencies
+/// are derived from the `sample_rate` of the displayed span and its `center_frequency`,
+/// assuming that the trace is a spectrum with the center frequency in the middle.
+///
+/// When markers change, their frequencies in Hz and powers in dB are written to the `markers`
+/// signal and, if a `fg_handle` is given, sent to the `marker_handler` (default: `markers`) of
+/// block `block_id` as [`Pmt::VecPmt`] of [`Pmt::MapStrPmt`] with `freq` ([`Pmt::F64`]) and
+/// `power` ([`Pmt::F32`]), e.g., to retune to a marked signal.
+///
+/// With `export`, buttons are shown to download the displayed traces as CSV, i.e., the
+/// frequency of each bin and the power in dB, or the plot as PNG, named after `export_name`.
 pub fn TimeSink(
     #[prop(into)] min: MaybeSignal<f32>,
     #[prop(into)] max: MaybeSignal<f32>,
     #[prop(optional)] mode: TimeSinkMode,
+    #[prop(into, optional)] averaging: MaybeSignal<f32>,
+    #[prop(into, optional)] max_hold: MaybeSignal<bool>,
+    #[prop(into, optional)] min_hold: MaybeSignal<bool>,
+    #[prop(into, default = MaybeSignal::Static(1.0))] sample_rate: MaybeSignal<f64>,
+    #[prop(into, optional)] center_frequency: MaybeSignal<f64>,
+    #[prop(default = 4)] max_markers: usize,
+    #[prop(optional_no_strip)] markers: Option<WriteSignal<Vec<(f64, f32)>>>,
+    #[prop(optional_no_strip)] fg_handle: Option<FlowgraphHandle>,
+    #[prop(optional)] block_id: usize,
+    #[prop(into, default = "markers".to_string())] marker_handler: String,
+    #[prop(optional)] export: bool,
+    #[prop(into, default = "spectrum".to_string())] export_name: String,
+    #[prop(into, optional)] button_class: String,
 ) -> impl IntoView {
+    let export_request = create_rw_signal(None::<ExportFormat>);
+    let (marks, set_marks) = create_signal(Vec::<f32>::new());
+    let (powers, set_powers) = create_signal(Vec::<f32>::new());
+    let trace = Rc::new(RefCell::new(Vec::<f32>::new()));
+
     let data = match mode {
         TimeSinkMode::Data(d) => d,
         TimeSinkMode::Websocket(s) => {
@@ -64,6 +165,71 @@ pub fn TimeSink(
     };
 
     let canvas_ref = create_node_ref::<Canvas>();
+    let freq_at = move |x: f32| {
+        center_frequency.get_untracked() + (x - 0.5) as f64 * sample_rate.get_untracked()
+    };
+    let position = move |ev: &ev::MouseEvent| {
+        let width = canvas_ref
+            .get_untracked()
+            .map(|c| c.client_width())
+            .unwrap_or(1)
+            .max(1);
+        (ev.offset_x() as f32 / width as f32).clamp(0.0, 1.0)
+    };
+    // update the readout and send the markers
+    let update_markers = {
+        let trace = trace.clone();
+        move |m: Vec<f32>| {
+            let p: Vec<f32> = m.iter().map(|x| power_at(&trace.borrow(), *x)).collect();
+            let values: Vec<(f64, f32)> = m.iter().map(|x| freq_at(*x)).zip(p.clone()).collect();
+            set_marks(m);
+            set_powers(p);
+            if let Some(markers) = markers {
+                markers(values.clone());
+            }
+            if let Some(fg_handle) = fg_handle.clone() {
+                let marker_handler = marker_handler.clone();
+                let pmt = Pmt::VecPmt(
+                    values
+                        .into_iter()
+                        .map(|(f, p)| {
+                            Pmt::MapStrPmt(HashMap::from([
+                                ("freq".to_string(), Pmt::F64(f)),
+                                ("power".to_string(), Pmt::F32(p)),
+                            ]))
+                        })
+                        .collect(),
+                );
+                spawn_local(async move {
+                    let mut fg_handle = fg_handle;
+                    let _ = fg_handle.call(block_id, marker_handler, pmt).await;
+                });
+            }
+        }
+    };
+    let readout = move || {
+        let m = marks();
+        let p = powers();
+        let f: Vec<f64> = m.iter().map(|x| freq_at(*x)).collect();
+        let mut lines: Vec<String> = f
+            .iter()
+            .zip(p.iter())
+            .enumerate()
+            .map(|(i, (f, p))| format!("M{}: {} {:.1} dB", i + 1, format_freq(*f), p))
+            .collect();
+        if let (Some(f0), Some(p0)) = (f.first(), p.first()) {
+            for (i, (f, p)) in f.iter().zip(p.iter()).enumerate().skip(1) {
+                lines.push(format!(
+                    "M{}-M1: {} {:+.1} dB",
+                    i + 1,
+                    format_freq(f - f0),
+                    p - p0
+                ));
+            }
+        }
+        lines.join("\n")
+    };
+
     canvas_ref.on_load(move |canvas_ref| {
         let _ = canvas_ref.on_mount(move |canvas| {
             let gl: GL = canvas
@@ -94,6 +260,7 @@ pub fn TimeSink(
 
             let frag_code = r"
                 precision mediump float;
+                uniform float u_alpha;
                 varying float power;
 
                 vec3 color_map(float t) {
@@ -109,7 +276,7 @@ pub fn TimeSink(
                 }
 
                 void main(void) {
-                    gl_FragColor = vec4(color_map(clamp(power, 0.0, 1.0)), 0.9);
+                    gl_FragColor = vec4(color_map(clamp(power, 0.0, 1.0)), u_alpha);
                 }
             ";
 
@@ -135,7 +302,7 @@ pub fn TimeSink(
             }
 
             let vertex_buffer = gl.create_buffer().unwrap();
-            let init_data = [0.0f32; MAX_SAMPLES * 2];
+            let init_data = [0.0f32; MAX_SAMPLES * 2 * 3];
             let view = unsafe { f32::view(&init_data) };
             gl.bind_buffer(GL::ARRAY_BUFFER, Some(&vertex_buffer));
             gl.buffer_data_with_array_buffer_view(
@@ -149,14 +316,72 @@ pub fn TimeSink(
             gl.enable_vertex_attrib_array(position);
 
             let state = Rc::new(RefCell::new(RenderState {
-                canvas, gl, shader, vertex_len: 0
+                canvas,
+                gl,
+                shader,
+                vertex_len: 0,
+                averaging,
+                max_hold,
+                min_hold,
+                avg: Vec::new(),
+                max: Vec::new(),
+                min: Vec::new(),
+                marks,
+                set_powers,
+                trace,
+                export: export_request,
+                export_name,
+                sample_rate,
+                center_frequency,
             }));
             request_animation_frame(render(state, data))
         });
     });
 
     view! {
-        <canvas node_ref=canvas_ref style="width: 100%; height: 100%" />
+        <div style="position: relative; width: 100%; height: 100%">
+            <canvas node_ref=canvas_ref style="width: 100%; height: 100%"
+                on:click={
+                    let update_markers = update_markers.clone();
+                    move |ev| {
+                        let mut m = marks.get_untracked();
+                        m.push(position(&ev));
+                        while m.len() > max_markers {
+                            m.remove(0);
+                        }
+                        update_markers(m);
+                    }
+                }
+                on:contextmenu=move |ev| {
+                    ev.prevent_default();
+                    let x = position(&ev);
+                    let mut m = marks.get_untracked();
+                    let closest = m
+                        .iter()
+                        .enumerate()
+                        .min_by(|(_, a), (_, b)| (*a - x).abs().total_cmp(&(*b - x).abs()))
+                        .map(|(i, _)| i);
+                    if let Some(i) = closest {
+                        m.remove(i);
+                        update_markers(m);
+                    }
+                } />
+            {move || {
+                marks()
+                    .into_iter()
+                    .map(|x| {
+                        let style = format!("left: {}%; {}", x * 100.0, MARKER_STYLE);
+                        view! { <div style=style /> }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            <div style=move || if marks().is_empty() { "display: none" } else { READOUT_STYLE }>
+                {readout}
+            </div>
+            {export.then(|| view! {
+                <ExportButtons request=export_request.write_only() button_class=button_class />
+            })}
+        </div>
     }
 }
 
@@ -171,6 +396,19 @@ fn render(
                 gl,
                 shader,
                 vertex_len,
+                averaging,
+                max_hold,
+                min_hold,
+                avg,
+                max,
+                min,
+                marks,
+                set_powers,
+                trace,
+                export,
+                export_name,
+                sample_rate,
+                center_frequency,
             } = &mut (*state.borrow_mut());
 
             let display_width = canvas.client_width() as u32;
@@ -184,6 +422,9 @@ fn render(
                 gl.viewport(0, 0, display_width as i32, display_height as i32);
             }
 
+            let max_hold = max_hold.get_untracked();
+            let min_hold = min_hold.get_untracked();
+
             if let Some(bytes) = data.borrow_mut().take() {
                 let samples = unsafe {
                     let s = std::cmp::min(bytes.len() / 4, MAX_SAMPLES);
@@ -192,10 +433,40 @@ fn render(
                 };
 
                 let l = samples.len();
-                let vertices: Vec<f32> = samples
-                    .iter()
-                    .enumerate()
-                    .flat_map(|(i, v)| vec![i as f32, *v])
+                let alpha = averaging.get_untracked().clamp(0.0, 1.0);
+                if avg.len() != l {
+                    *avg = samples.to_vec();
+                    max.clear();
+                    min.clear();
+                } else {
+                    avg.iter_mut()
+                        .zip(samples)
+                        .for_each(|(a, s)| *a = alpha * *a + (1.0 - alpha) * s);
+                }
+
+                if !max_hold {
+                    max.clear();
+                } else if max.is_empty() {
+                    *max = avg.clone();
+                } else {
+                    max.iter_mut()
+                        .zip(avg.iter())
+                        .for_each(|(m, a)| *m = m.max(*a));
+                }
+
+                if !min_hold {
+                    min.clear();
+                } else if min.is_empty() {
+                    *min = avg.clone();
+                } else {
+                    min.iter_mut()
+                        .zip(avg.iter())
+                        .for_each(|(m, a)| *m = m.min(*a));
+                }
+
+                let vertices: Vec<f32> = [&*avg, &*max, &*min]
+                    .into_iter()
+                    .flat_map(|trace| trace.iter().enumerate().flat_map(|(i, v)| [i as f32, *v]))
                     .collect();
 
                 let view = unsafe { f32::view(&vertices) };
@@ -205,9 +476,48 @@ fn render(
                 gl.uniform1f(u_nsamples.as_ref(), l as f32);
 
                 *vertex_len = l as i32;
+
+                trace.borrow_mut().clone_from(avg);
+                let marks = marks.get_untracked();
+                if !marks.is_empty() {
+                    set_powers.set(marks.iter().map(|x| power_at(avg, *x)).collect());
+                }
             };
 
+            let u_alpha = gl.get_uniform_location(shader, "u_alpha");
+            gl.uniform1f(u_alpha.as_ref(), 0.9);
             gl.draw_arrays(GL::LINE_STRIP, 0, *vertex_len);
+
+            gl.uniform1f(u_alpha.as_ref(), 0.5);
+            let mut offset = *vertex_len;
+            if !max.is_empty() {
+                gl.draw_arrays(GL::LINE_STRIP, offset, *vertex_len);
+                offset += *vertex_len;
+            }
+            if !min.is_empty() {
+                gl.draw_arrays(GL::LINE_STRIP, offset, *vertex_len);
+            }
+
+            // the drawing buffer is only valid until the end of the frame
+            if let Some(format) = export.get_untracked() {
+                export.set(None);
+                match format {
+                    ExportFormat::Csv => {
+                        let traces = [
+                            ("power", &avg[..]),
+                            ("max_hold", &max[..]),
+                            ("min_hold", &min[..]),
+                        ];
+                        let csv = spectrum_csv(
+                            &traces,
+                            center_frequency.get_untracked(),
+                            sample_rate.get_untracked(),
+                        );
+                        download_csv(&format!("{export_name}.csv"), &csv);
+                    }
+                    ExportFormat::Png => download_canvas(&format!("{export_name}.png"), canvas),
+                }
+            }
         }
         request_animation_frame(render(state, data))
     }
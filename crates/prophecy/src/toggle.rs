@@ -0,0 +1,45 @@
+use futuresdr_types::Pmt;
+use futuresdr_types::PortId;
+use leptos::logging::*;
+use leptos::*;
+
+use crate::FlowgraphHandle;
+
+#[component]
+/// Toggle
+///
+/// Checking or unchecking the box triggers sending the corresponding PMT.
+pub fn Toggle<P: Into<PortId>>(
+    fg_handle: FlowgraphHandle,
+    block_id: usize,
+    handler: P,
+    #[prop(default = Pmt::Bool(true))] on: Pmt,
+    #[prop(default = Pmt::Bool(false))] off: Pmt,
+    #[prop(optional)] init: bool,
+    #[prop(optional)] setter: Option<WriteSignal<bool>>,
+    #[prop(into, optional)] input_class: String,
+) -> impl IntoView {
+    let handler = handler.into();
+
+    view! {
+        <input type="checkbox" checked=init class=input_class on:change=move |v| {
+            let checked = event_target_checked(&v);
+            if let Some(setter) = setter {
+                setter(checked);
+            }
+
+            let mut fg_handle = fg_handle.clone();
+            let handler = handler.clone();
+            let pmt = if checked { on.clone() } else { off.clone() };
+            spawn_local(async move {
+                log!(
+                    "sending block {} handler {:?} pmt {:?}",
+                    block_id,
+                    &handler,
+                    &pmt
+                );
+                let _ = fg_handle.call(block_id, handler, pmt).await;
+            });
+        } />
+    }
+}
@@ -1,15 +1,25 @@
 use futures::StreamExt;
+use futuresdr_types::Pmt;
 use gloo_net::websocket::{futures::WebSocket, Message};
 use leptos::html::Canvas;
 use leptos::logging::*;
 use leptos::*;
 use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::rc::Rc;
 use wasm_bindgen::JsCast;
+use wasm_bindgen::JsValue;
 use web_sys::WebGl2RenderingContext as GL;
 use web_sys::WebGlProgram;
 
+use crate::colormap::COLOR_MAP_GLSL;
+use crate::export::download_canvas;
+use crate::export::download_csv;
+use crate::export::ExportButtons;
+use crate::export::ExportFormat;
 use crate::ArrayView;
+use crate::ColorMap;
+use crate::FlowgraphHandle;
 
 pub enum WaterfallMode {
     Websocket(String),
@@ -27,17 +37,80 @@ struct RenderState {
     gl: GL,
     shader: WebGlProgram,
     texture_offset: i32,
+    last_row: Option<f64>,
+    row_rate: f64,
+    rows: VecDeque<(f64, Vec<f32>)>,
+    last_axis: f64,
 }
 
 const SHADER_HEIGHT: usize = 256;
+const TIME_TICKS: usize = 4;
+const TIME_STYLE: &str = "position: absolute; left: 0; padding: 0 0.25em; color: white; \
+    font-family: monospace; font-size: 0.8em; text-shadow: 0 0 2px black; pointer-events: none";
+const MIN_ZOOM: f32 = 1.0 / 64.0;
+const SELECTION_STYLE: &str = "position: absolute; top: 0; bottom: 0; \
+    background: rgba(255, 255, 255, 0.2); border-left: 1px solid white; \
+    border-right: 1px solid white; pointer-events: none";
 
 #[component]
 /// Waterfall Sink
+///
+/// The dB range (`min`, `max`) and the color map can be adjusted at runtime through signals.
+///
+/// `history` limits the displayed rows to the given number of seconds, based on the rate at
+/// which spectra arrive. By default, all rows that fit into the texture are shown. While
+/// `paused` is set, incoming spectra are dropped and the display is frozen.
+///
+/// Independent of the display, the latest `history_depth` rows are kept with their time of
+/// arrival (default: as many rows as fit into the texture). Each row takes 8 kB, so a deep
+/// history should be combined with a low row rate. With `time_axis`, the time of arrival of the
+/// displayed rows is shown on the left.
+///
+/// Scrolling over the waterfall zooms in and out around the cursor, a double click resets the
+/// zoom. The zoom is stored in `zoom` as center and width, normalized to the full span. The
+/// center is relative to the middle of the span, i.e., in `[-0.5, 0.5]`, the width in `[0, 1]`.
+///
+/// Dragging a box over the waterfall selects a channel. Its center frequency and bandwidth in
+/// Hz are derived from the `sample_rate` of the displayed span and its `center_frequency`. By
+/// default, the center frequency is zero, i.e., the selection is an offset from the middle of
+/// the span, as expected by a frequency-translating filter. If a `fg_handle` is given, the
+/// selection is sent as [`Pmt::F64`] to the `freq_handler` (default: `freq`) and the
+/// `bandwidth_handler` (default: `bandwidth`) of block `block_id`. The `selection` signal
+/// receives the same values. A click without dragging selects a bandwidth of zero and only
+/// sends the frequency.
+///
+/// `selection` and `fg_handle` take an [`Option`], so that they can be passed on by wrapping
+/// components, like [`Spectrogram`](crate::Spectrogram).
+///
+/// With `export`, buttons are shown to download the history as CSV, i.e., the frequency of
+/// each displayed bin in the first line and the UTC time of arrival and the power in dB of each
+/// row, newest first, in the following lines, or the waterfall as PNG, named after
+/// `export_name`.
 pub fn Waterfall(
     #[prop(into)] min: MaybeSignal<f32>,
     #[prop(into)] max: MaybeSignal<f32>,
+    #[prop(into, optional)] colormap: MaybeSignal<ColorMap>,
+    #[prop(into, optional)] history: MaybeSignal<Option<f32>>,
+    #[prop(default = SHADER_HEIGHT)] history_depth: usize,
+    #[prop(optional)] time_axis: bool,
+    #[prop(into, optional)] paused: MaybeSignal<bool>,
+    #[prop(optional)] zoom: Option<RwSignal<(f32, f32)>>,
     #[prop(optional)] mode: WaterfallMode,
+    #[prop(into, default = MaybeSignal::Static(1.0))] sample_rate: MaybeSignal<f64>,
+    #[prop(into, optional)] center_frequency: MaybeSignal<f64>,
+    #[prop(optional_no_strip)] selection: Option<WriteSignal<(f64, f64)>>,
+    #[prop(optional_no_strip)] fg_handle: Option<FlowgraphHandle>,
+    #[prop(optional)] block_id: usize,
+    #[prop(into, default = "freq".to_string())] freq_handler: String,
+    #[prop(into, default = "bandwidth".to_string())] bandwidth_handler: String,
+    #[prop(optional)] export: bool,
+    #[prop(into, default = "waterfall".to_string())] export_name: String,
+    #[prop(into, optional)] button_class: String,
 ) -> impl IntoView {
+    let export_request = create_rw_signal(None::<ExportFormat>);
+    let (times, set_times) = create_signal(Vec::<(f32, String)>::new());
+    let zoom = zoom.unwrap_or_else(|| create_rw_signal((0.0, 1.0)));
+
     let data = match mode {
         WaterfallMode::Data(d) => d,
         WaterfallMode::Websocket(s) => {
@@ -87,36 +160,27 @@ pub fn Waterfall(
             gl.shader_source(&vert_shader, vert_code);
             gl.compile_shader(&vert_shader);
 
-            let frag_code = r"
+            let frag_code = String::from(r"
                 precision mediump float;
 
                 varying vec2 coord;
                 uniform float u_min;
                 uniform float u_max;
                 uniform float yoffset;
+                uniform float u_rows;
+                uniform vec2 u_zoom;
                 uniform sampler2D frequency_data;
-
-                vec3 color_map(float t) {
-                    const vec3 c0 = vec3(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
-                    const vec3 c1 = vec3(0.1050930431085774, 1.404613529898575, 1.384590162594685);
-                    const vec3 c2 = vec3(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
-                    const vec3 c3 = vec3(-4.634230498983486, -5.799100973351585, -19.33244095627987);
-                    const vec3 c4 = vec3(6.228269936347081, 14.17993336680509, 56.69055260068105);
-                    const vec3 c5 = vec3(4.776384997670288, -13.74514537774601, -65.35303263337234);
-                    const vec3 c6 = vec3(-5.435455855934631, 4.645852612178535, 26.3124352495832);
-
-                    return c0+t*(c1+t*(c2+t*(c3+t*(c4+t*(c5+t*c6)))));
-                }
-
+            ") + COLOR_MAP_GLSL + r"
                 void main()
                 {
-                    vec4 sample = texture2D(frequency_data, vec2(coord.x * 0.5 + 0.5, coord.y * 0.5 - 0.5 + yoffset));
+                    vec2 pos = vec2(0.5 + u_zoom.x + coord.x * 0.5 * u_zoom.y, yoffset + (coord.y * 0.5 - 0.5) * u_rows);
+                    vec4 sample = texture2D(frequency_data, pos);
                     float power = (10.0 * log(sample.r) / log(10.0) - u_min) / (u_max - u_min);
                     gl_FragColor = vec4(color_map(clamp(power, 0.0, 1.0)), 1.0);
                 }
             ";
             let frag_shader = gl.create_shader(GL::FRAGMENT_SHADER).unwrap();
-            gl.shader_source(&frag_shader, frag_code);
+            gl.shader_source(&frag_shader, &frag_code);
             gl.compile_shader(&frag_shader);
 
             let shader = gl.create_program().unwrap();
@@ -179,24 +243,189 @@ pub fn Waterfall(
                     gl.uniform1f(u_min.as_ref(), min.get());
                     let u_max = gl.get_uniform_location(&shader, "u_max");
                     gl.uniform1f(u_max.as_ref(), max.get());
+                    let u_colormap = gl.get_uniform_location(&shader, "u_colormap");
+                    gl.uniform1i(u_colormap.as_ref(), colormap.get().id());
                 });
             }
 
             let state = RenderState {
-                canvas,gl, shader, texture_offset: 0,
+                canvas,gl, shader, texture_offset: 0, last_row: None, row_rate: 0.0,
+                rows: VecDeque::new(), last_axis: 0.0,
+            };
+            let settings = ViewSettings {
+                history, history_depth: history_depth.max(1), paused, zoom,
+                time_axis: time_axis.then_some(set_times), export: export_request, export_name,
+                sample_rate, center_frequency,
             };
-            request_animation_frame(render(Rc::new(RefCell::new(state)), data))
+            request_animation_frame(render(Rc::new(RefCell::new(state)), data, settings))
         });
     });
 
+    let (selected, set_selected) = create_signal(None::<(f32, f32)>);
+    let (dragging, set_dragging) = create_signal(false);
+    let position = move |ev: &ev::MouseEvent| {
+        let width = canvas_ref
+            .get_untracked()
+            .map(|c| c.client_width())
+            .unwrap_or(1)
+            .max(1);
+        (ev.offset_x() as f32 / width as f32).clamp(0.0, 1.0)
+    };
+    // position on the display to position relative to the full span
+    let unzoom = move |x: f32| {
+        let (center, width) = zoom.get_untracked();
+        0.5 + center + (x - 0.5) * width
+    };
+    // send the selection, given relative to the full span, in Hz
+    let select = move |lo: f32, hi: f32| {
+        let rate = sample_rate.get_untracked();
+        let freq = center_frequency.get_untracked() + ((lo + hi) / 2.0 - 0.5) as f64 * rate;
+        let bandwidth = (hi - lo) as f64 * rate;
+        if let Some(selection) = selection {
+            selection((freq, bandwidth));
+        }
+        if let Some(fg_handle) = fg_handle.clone() {
+            let freq_handler = freq_handler.clone();
+            let bandwidth_handler = bandwidth_handler.clone();
+            spawn_local(async move {
+                let mut fg_handle = fg_handle;
+                let _ = fg_handle.call(block_id, freq_handler, Pmt::F64(freq)).await;
+                if bandwidth > 0.0 {
+                    let _ = fg_handle
+                        .call(block_id, bandwidth_handler, Pmt::F64(bandwidth))
+                        .await;
+                }
+            });
+        }
+    };
+    let box_style = move || match selected() {
+        Some((x0, x1)) => format!(
+            "left: {}%; width: {}%; {}",
+            x0.min(x1) * 100.0,
+            (x1 - x0).abs() * 100.0,
+            SELECTION_STYLE
+        ),
+        None => "display: none".to_string(),
+    };
+
     view! {
-        <canvas node_ref=canvas_ref style="width: 100%; height: 100%" />
+        <div style="position: relative; width: 100%; height: 100%">
+            <canvas node_ref=canvas_ref style="width: 100%; height: 100%"
+                on:mousedown=move |ev| {
+                    let x = position(&ev);
+                    set_selected(Some((x, x)));
+                    set_dragging(true);
+                }
+                on:mousemove=move |ev| {
+                    if dragging.get_untracked() {
+                        if let Some((x0, _)) = selected.get_untracked() {
+                            set_selected(Some((x0, position(&ev))));
+                        }
+                    }
+                }
+                on:mouseup=move |ev| {
+                    if !dragging.get_untracked() {
+                        return;
+                    }
+                    set_dragging(false);
+                    if let Some((x0, _)) = selected.get_untracked() {
+                        let x1 = position(&ev);
+                        set_selected(Some((x0, x1)));
+                        select(unzoom(x0.min(x1)), unzoom(x0.max(x1)));
+                    }
+                }
+                on:wheel=move |ev| {
+                    ev.prevent_default();
+                    let x = position(&ev);
+                    let p = unzoom(x) - 0.5;
+                    let (_, width) = zoom.get_untracked();
+                    let factor = if ev.delta_y() > 0.0 { 1.25 } else { 0.8 };
+                    let width = (width * factor).clamp(MIN_ZOOM, 1.0);
+                    let center = (p - (x - 0.5) * width).clamp(width / 2.0 - 0.5, 0.5 - width / 2.0);
+                    set_selected(None);
+                    zoom.set((center, width));
+                }
+                on:dblclick=move |_| {
+                    set_selected(None);
+                    zoom.set((0.0, 1.0));
+                } />
+            <div style=box_style />
+            {move || {
+                times()
+                    .into_iter()
+                    .map(|(f, t)| {
+                        let style = format!(
+                            "top: {}%; transform: translateY(-{}%); {}",
+                            f * 100.0,
+                            f * 100.0,
+                            TIME_STYLE
+                        );
+                        view! { <span style=style>{t}</span> }
+                    })
+                    .collect::<Vec<_>>()
+            }}
+            {export.then(|| view! {
+                <ExportButtons request=export_request.write_only() button_class=button_class />
+            })}
+        </div>
+    }
+}
+
+/// Display settings that are read on every frame.
+#[derive(Clone)]
+struct ViewSettings {
+    history: MaybeSignal<Option<f32>>,
+    history_depth: usize,
+    paused: MaybeSignal<bool>,
+    zoom: RwSignal<(f32, f32)>,
+    time_axis: Option<WriteSignal<Vec<(f32, String)>>>,
+    export: RwSignal<Option<ExportFormat>>,
+    export_name: String,
+    sample_rate: MaybeSignal<f64>,
+    center_frequency: MaybeSignal<f64>,
+}
+
+/// Local time of day of a timestamp in ms since the epoch.
+fn format_clock(ms: f64) -> String {
+    let d = js_sys::Date::new(&JsValue::from_f64(ms));
+    format!(
+        "{:02}:{:02}:{:02}",
+        d.get_hours(),
+        d.get_minutes(),
+        d.get_seconds()
+    )
+}
+
+/// CSV with the frequency of the bins `lo..hi` in the first line and the UTC time and the power
+/// in dB of each row in the following lines.
+fn waterfall_csv<'a>(
+    rows: impl Iterator<Item = &'a (f64, Vec<f32>)>,
+    (lo, hi): (usize, usize),
+    center_frequency: f64,
+    sample_rate: f64,
+) -> String {
+    let mut csv = String::from("time");
+    for i in lo..hi {
+        csv += &format!(
+            ",{}",
+            center_frequency + (i as f64 / 2048.0 - 0.5) * sample_rate
+        );
     }
+    csv.push('\n');
+    for (t, row) in rows {
+        csv += &String::from(js_sys::Date::new(&JsValue::from_f64(*t)).to_iso_string());
+        for p in row[lo..hi].iter() {
+            csv += &format!(",{}", 10.0 * p.log10());
+        }
+        csv.push('\n');
+    }
+    csv
 }
 
 fn render(
     state: Rc<RefCell<RenderState>>,
     data: Rc<RefCell<Option<Vec<u8>>>>,
+    settings: ViewSettings,
 ) -> impl FnOnce() + 'static {
     move || {
         {
@@ -205,6 +434,10 @@ fn render(
                 gl,
                 shader,
                 texture_offset,
+                last_row,
+                row_rate,
+                rows: spectra,
+                last_axis,
             } = &mut (*state.borrow_mut());
 
             let display_width = canvas.client_width() as u32;
@@ -218,15 +451,40 @@ fn render(
                 gl.viewport(0, 0, display_width as i32, display_height as i32);
             }
 
-            if let Some(bytes) = data.borrow_mut().take() {
+            let paused = settings.paused.get_untracked();
+            if paused {
+                *last_row = None;
+            }
+
+            let bytes = if paused {
+                None
+            } else {
+                data.borrow_mut().take()
+            };
+            if let Some(bytes) = bytes {
                 assert_eq!(bytes.len(), 2048 * 4);
 
+                let now = js_sys::Date::now();
+                if let Some(last) = last_row.replace(now) {
+                    let rate = 1000.0 / (now - last).max(1.0);
+                    *row_rate = if *row_rate > 0.0 {
+                        0.9 * *row_rate + 0.1 * rate
+                    } else {
+                        rate
+                    };
+                }
+
                 let samples = unsafe {
                     let s = bytes.len() / 4;
                     let p = bytes.as_ptr();
                     std::slice::from_raw_parts(p as *const f32, s)
                 };
 
+                spectra.push_back((now, samples.to_vec()));
+                while spectra.len() > settings.history_depth {
+                    spectra.pop_front();
+                }
+
                 let view = unsafe { f32::view(samples) };
                 gl.tex_sub_image_2d_with_i32_and_i32_and_u32_and_type_and_array_buffer_view_and_src_offset(
                     GL::TEXTURE_2D,
@@ -245,10 +503,60 @@ fn render(
                 let loc = gl.get_uniform_location(shader, "yoffset");
                 gl.uniform1f(loc.as_ref(), *texture_offset as f32 / SHADER_HEIGHT as f32);
                 *texture_offset = (*texture_offset + 1) % SHADER_HEIGHT as i32;
+            }
 
-                gl.draw_elements_with_i32(GL::TRIANGLES, 6, GL::UNSIGNED_SHORT, 0);
+            let rows = match settings.history.get_untracked() {
+                Some(h) if *row_rate > 0.0 => {
+                    (h as f64 * *row_rate).clamp(1.0, SHADER_HEIGHT as f64)
+                }
+                _ => SHADER_HEIGHT as f64,
+            };
+            // newest row at the top, oldest displayed row at the bottom
+            let now = js_sys::Date::now();
+            if let Some(set_times) = settings.time_axis {
+                if now - *last_axis >= 250.0 {
+                    *last_axis = now;
+                    let times = (0..=TIME_TICKS)
+                        .filter_map(|k| {
+                            let f = k as f32 / TIME_TICKS as f32;
+                            let age = ((f as f64 * rows) as usize).min(rows as usize - 1);
+                            let (t, _) = spectra.iter().rev().nth(age)?;
+                            Some((f, format_clock(*t)))
+                        })
+                        .collect();
+                    set_times.set(times);
+                }
+            }
+
+            let loc = gl.get_uniform_location(shader, "u_rows");
+            gl.uniform1f(loc.as_ref(), (rows / SHADER_HEIGHT as f64) as f32);
+            let (center, width) = settings.zoom.get_untracked();
+            let loc = gl.get_uniform_location(shader, "u_zoom");
+            gl.uniform2f(loc.as_ref(), center, width);
+
+            gl.draw_elements_with_i32(GL::TRIANGLES, 6, GL::UNSIGNED_SHORT, 0);
+
+            // the drawing buffer is only valid until the end of the frame
+            if let Some(format) = settings.export.get_untracked() {
+                settings.export.set(None);
+                let name = &settings.export_name;
+                match format {
+                    ExportFormat::Csv => {
+                        let lo = ((0.5 + center - width / 2.0) * 2048.0).round() as usize;
+                        let lo = lo.min(2047);
+                        let hi = ((0.5 + center + width / 2.0) * 2048.0).round() as usize;
+                        let csv = waterfall_csv(
+                            spectra.iter().rev(),
+                            (lo, hi.clamp(lo + 1, 2048)),
+                            settings.center_frequency.get_untracked(),
+                            settings.sample_rate.get_untracked(),
+                        );
+                        download_csv(&format!("{name}.csv"), &csv);
+                    }
+                    ExportFormat::Png => download_canvas(&format!("{name}.png"), canvas),
+                }
             }
         }
-        request_animation_frame(render(state, data))
+        request_animation_frame(render(state, data, settings))
     }
 }
@@ -13,6 +13,13 @@ pub struct FlowgraphDescription {
     pub message_edges: Vec<(usize, usize, usize, usize)>,
 }
 
+impl FlowgraphDescription {
+    /// Get the description of a block by its instance name
+    pub fn block_by_name(&self, name: &str) -> Option<&BlockDescription> {
+        self.blocks.iter().find(|b| b.instance_name == name)
+    }
+}
+
 /// Description of a `Block`.
 ///
 /// This struct can be serialized to be used with the REST API.
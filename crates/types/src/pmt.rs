@@ -47,6 +47,12 @@ impl dyn PmtAny {
 ///
 /// PMTs are used as input and output for the FutureSDR message passing interface. At the moment,
 /// the `Any` type is ignored for de-/serialization.
+///
+/// PMTs can be exchanged with other tools as JSON ([`to_json`](Self::to_json),
+/// [`from_json`](Self::from_json)) or CBOR ([`to_cbor`](Self::to_cbor),
+/// [`from_cbor`](Self::from_cbor)). Variants are externally tagged, e.g., `{"U32": 123}` or
+/// `{"MapStrPmt": {"freq": {"F64": 2.45e9}}}`, variants without value are plain strings, e.g.,
+/// `"Null"`, and complex values are `[re, im]` arrays.
 #[non_exhaustive]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Pmt {
@@ -138,6 +144,7 @@ impl PartialEq for Pmt {
             (Pmt::U64(x), Pmt::U64(y)) => x == y,
             (Pmt::F32(x), Pmt::F32(y)) => x == y,
             (Pmt::F64(x), Pmt::F64(y)) => x == y,
+            (Pmt::VecCF32(x), Pmt::VecCF32(y)) => x == y,
             (Pmt::VecF32(x), Pmt::VecF32(y)) => x == y,
             (Pmt::VecU64(x), Pmt::VecU64(y)) => x == y,
             (Pmt::Blob(x), Pmt::Blob(y)) => x == y,
@@ -178,6 +185,32 @@ impl std::str::FromStr for Pmt {
 }
 
 impl Pmt {
+    /// Serialize to JSON.
+    ///
+    /// Fails for [`Pmt::Any`].
+    pub fn to_json(&self) -> Result<String, PmtConversionError> {
+        serde_json::to_string(self).or(Err(PmtConversionError))
+    }
+
+    /// Deserialize from JSON.
+    pub fn from_json(s: &str) -> Result<Pmt, PmtConversionError> {
+        serde_json::from_str(s).or(Err(PmtConversionError))
+    }
+
+    /// Serialize to CBOR.
+    ///
+    /// Fails for [`Pmt::Any`].
+    pub fn to_cbor(&self) -> Result<Vec<u8>, PmtConversionError> {
+        let mut v = Vec::new();
+        ciborium::ser::into_writer(self, &mut v).or(Err(PmtConversionError))?;
+        Ok(v)
+    }
+
+    /// Deserialize from CBOR.
+    pub fn from_cbor(b: &[u8]) -> Result<Pmt, PmtConversionError> {
+        ciborium::de::from_reader(b).or(Err(PmtConversionError))
+    }
+
     /// Create a [`Pmt`] by parsing a string into a specific [`PmtKind`].
     pub fn from_string(s: &str, t: &PmtKind) -> Option<Pmt> {
         match t {
@@ -397,6 +430,35 @@ mod test {
         assert_eq!(p, p2);
     }
 
+    #[test]
+    fn pmt_json_cbor() {
+        let p = Pmt::MapStrPmt(HashMap::from([
+            ("freq".to_owned(), Pmt::F64(2.45e9)),
+            ("blob".to_owned(), Pmt::Blob(vec![0, 1, 255])),
+            (
+                "samples".to_owned(),
+                Pmt::VecCF32(vec![Complex32::new(1.0, -1.0), Complex32::new(0.5, 0.25)]),
+            ),
+            (
+                "list".to_owned(),
+                Pmt::VecPmt(vec![Pmt::Null, Pmt::Bool(true), Pmt::VecF32(vec![1.5])]),
+            ),
+        ]));
+
+        assert_eq!(Pmt::from_json(&p.to_json().unwrap()), Ok(p.clone()));
+        assert_eq!(Pmt::from_cbor(&p.to_cbor().unwrap()), Ok(p));
+
+        assert_eq!(Pmt::U32(123).to_json(), Ok("{\"U32\":123}".to_string()));
+        assert_eq!(Pmt::from_json("\"Finished\""), Ok(Pmt::Finished));
+        assert_eq!(
+            Pmt::from_json("{\"VecCF32\": [[1.0, 2.0]]}"),
+            Ok(Pmt::VecCF32(vec![Complex32::new(1.0, 2.0)]))
+        );
+        assert!(Pmt::from_json("{\"Foo\": 1}").is_err());
+        assert!(Pmt::Any(Box::new(1u8)).to_json().is_err());
+        assert!(Pmt::Any(Box::new(1u8)).to_cbor().is_err());
+    }
+
     #[allow(clippy::many_single_char_names)]
     #[test]
     fn pmt_eq() {
@@ -20,6 +20,7 @@ use std::sync::Arc;
 use std::thread;
 
 use futuresdr_egui::ChannelSink;
+use futuresdr_egui::Spectrum;
 use futuresdr_egui::FFT_SIZE;
 
 fn main() -> Result<(), eframe::Error> {
@@ -169,190 +170,3 @@ impl eframe::App for MyApp {
         }
     }
 }
-
-struct Spectrum {
-    rx_samples: Receiver<Box<[f32; FFT_SIZE]>>,
-    program: glow::Program,
-    vertex_array: glow::VertexArray,
-    array_buffer: glow::NativeBuffer,
-    coordinates: [f32; FFT_SIZE * 2],
-    new_min: Option<f32>,
-    new_max: Option<f32>,
-}
-
-impl Spectrum {
-    fn new(gl: &glow::Context, rx_samples: Receiver<Box<[f32; FFT_SIZE]>>) -> Self {
-        use glow::HasContext as _;
-
-        let shader_version = if cfg!(target_arch = "wasm32") {
-            "#version 300 es"
-        } else {
-            "#version 330"
-        };
-
-        unsafe {
-            let program = gl.create_program().expect("Cannot create program");
-
-            let (vertex_shader_source, fragment_shader_source) = (
-                r#"
-                attribute vec2 coordinates;
-                uniform float u_nsamples;
-                uniform float u_min;
-                uniform float u_max;
-                varying float power;
-
-                void main(void) {
-                    float x = -1.0 + 2.0 * coordinates.x / u_nsamples;
-                    power = (10.0 * log(coordinates.y) / log(10.0) - u_min) / (u_max - u_min);
-                    float y = 2.0 * power - 1.0;
-                    gl_Position = vec4(x, y, 0.0, 1.0);
-                }
-                "#,
-                r#"
-                precision mediump float;
-                varying float power;
-
-                vec3 color_map(float t) {
-                    const vec3 c0 = vec3(0.2777273272234177, 0.005407344544966578, 0.3340998053353061);
-                    const vec3 c1 = vec3(0.1050930431085774, 1.404613529898575, 1.384590162594685);
-                    const vec3 c2 = vec3(-0.3308618287255563, 0.214847559468213, 0.09509516302823659);
-                    const vec3 c3 = vec3(-4.634230498983486, -5.799100973351585, -19.33244095627987);
-                    const vec3 c4 = vec3(6.228269936347081, 14.17993336680509, 56.69055260068105);
-                    const vec3 c5 = vec3(4.776384997670288, -13.74514537774601, -65.35303263337234);
-                    const vec3 c6 = vec3(-5.435455855934631, 4.645852612178535, 26.3124352495832);
-
-                    return c0+t*(c1+t*(c2+t*(c3+t*(c4+t*(c5+t*c6)))));
-                }
-
-                void main(void) {
-                    gl_FragColor = vec4(color_map(clamp(power, 0.0, 1.0)), 0.9);
-                }
-
-
-                "#,
-            );
-
-            let shader_sources = [
-                (glow::VERTEX_SHADER, vertex_shader_source),
-                (glow::FRAGMENT_SHADER, fragment_shader_source),
-            ];
-
-            let shaders: Vec<_> = shader_sources
-                .iter()
-                .map(|(shader_type, shader_source)| {
-                    let shader = gl
-                        .create_shader(*shader_type)
-                        .expect("Cannot create shader");
-                    gl.shader_source(shader, &format!("{shader_version}\n{shader_source}"));
-                    gl.compile_shader(shader);
-                    assert!(
-                        gl.get_shader_compile_status(shader),
-                        "Failed to compile {shader_type}: {}",
-                        gl.get_shader_info_log(shader)
-                    );
-                    gl.attach_shader(program, shader);
-                    shader
-                })
-                .collect();
-
-            gl.link_program(program);
-            assert!(
-                gl.get_program_link_status(program),
-                "{}",
-                gl.get_program_info_log(program)
-            );
-
-            for shader in shaders {
-                gl.detach_shader(program, shader);
-                gl.delete_shader(shader);
-            }
-
-            gl.use_program(Some(program));
-
-            gl.uniform_1_f32(
-                gl.get_uniform_location(program, "u_nsamples").as_ref(),
-                FFT_SIZE as f32,
-            );
-            gl.uniform_1_f32(gl.get_uniform_location(program, "u_min").as_ref(), -50.0);
-            gl.uniform_1_f32(gl.get_uniform_location(program, "u_max").as_ref(), 50.0);
-
-            let vertex_array = gl
-                .create_vertex_array()
-                .expect("Cannot create vertex array");
-
-            Self {
-                program,
-                vertex_array,
-                array_buffer: gl.create_buffer().unwrap(),
-                rx_samples,
-                coordinates: [0.0; FFT_SIZE * 2],
-                new_min: None,
-                new_max: None,
-            }
-        }
-    }
-
-    fn destroy(&self, gl: &glow::Context) {
-        use glow::HasContext as _;
-        unsafe {
-            gl.delete_program(self.program);
-            gl.delete_vertex_array(self.vertex_array);
-        }
-    }
-
-    fn set_min(&mut self, min: f32) {
-        self.new_min = Some(min);
-    }
-
-    fn set_max(&mut self, max: f32) {
-        self.new_max = Some(max);
-    }
-
-    fn paint(&mut self, gl: &glow::Context) {
-        use glow::HasContext as _;
-
-        unsafe {
-            gl.use_program(Some(self.program));
-
-            if let Some(m) = self.new_min.take() {
-                gl.uniform_1_f32(gl.get_uniform_location(self.program, "u_min").as_ref(), m);
-            }
-
-            if let Some(m) = self.new_max.take() {
-                gl.uniform_1_f32(gl.get_uniform_location(self.program, "u_max").as_ref(), m);
-            }
-
-            if let Ok(Some(v)) = self.rx_samples.try_next() {
-                let mut samples = *v;
-                while let Ok(Some(v)) = self.rx_samples.try_next() {
-                    samples = *v;
-                }
-
-                for (a, (i, f)) in self
-                    .coordinates
-                    .chunks_exact_mut(2)
-                    .zip(samples.iter().enumerate())
-                {
-                    a[0] = i as f32;
-                    a[1] = *f;
-                }
-
-                let bytes = {
-                    let s = self.coordinates.len() * std::mem::size_of::<f32>();
-                    let p = self.coordinates.as_ptr();
-                    std::slice::from_raw_parts(p as *const u8, s)
-                };
-
-                gl.bind_buffer(glow::ARRAY_BUFFER, Some(self.array_buffer));
-                gl.buffer_data_u8_slice(glow::ARRAY_BUFFER, bytes, glow::STATIC_DRAW);
-
-                gl.bind_vertex_array(Some(self.vertex_array));
-                let coords = gl.get_attrib_location(self.program, "coordinates").unwrap();
-                gl.enable_vertex_attrib_array(coords);
-                gl.vertex_attrib_pointer_f32(coords, 2, glow::FLOAT, false, 0, 0);
-
-                gl.draw_arrays(glow::LINE_STRIP, 0, FFT_SIZE as i32);
-            }
-        }
-    }
-}
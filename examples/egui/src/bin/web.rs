@@ -0,0 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub fn main() {}
+
+#[cfg(target_arch = "wasm32")]
+pub fn main() {
+    futuresdr_egui::wasm::wasm_main()
+}
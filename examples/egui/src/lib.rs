@@ -6,6 +6,12 @@ pub use channel_sink::ChannelSink;
 mod keep_1_in_n;
 pub use keep_1_in_n::Keep1InN;
 
+mod spectrum;
+pub use spectrum::Spectrum;
+
+#[cfg(target_arch = "wasm32")]
+pub mod wasm;
+
 pub const FFT_SIZE: usize = 2048;
 
 use futuresdr::blocks::Apply;
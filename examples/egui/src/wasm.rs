@@ -0,0 +1,181 @@
+//! Browser version of the spectrum viewer
+//!
+//! The flowgraph runs in the browser, using the WebUSB HackRF source. Devices can only be
+//! requested in response to a user action, so the flowgraph is started with a button.
+use eframe::egui;
+use eframe::egui::mutex::Mutex;
+use eframe::egui_glow;
+use eframe::glow;
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::wasm::HackRf;
+use futuresdr::blocks::Fft;
+use futuresdr::blocks::FftDirection;
+use futuresdr::futures::channel::mpsc::channel;
+use futuresdr::futures::channel::mpsc::Sender;
+use futuresdr::macros::connect;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphHandle;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use crate::ChannelSink;
+use crate::Spectrum;
+use crate::FFT_SIZE;
+
+/// Id of the canvas in `index.html`
+const CANVAS_ID: &str = "futuresdr";
+
+pub fn wasm_main() {
+    console_error_panic_hook::set_once();
+    let _ = console_log::init_with_level(log::Level::Debug);
+
+    wasm_bindgen_futures::spawn_local(async {
+        eframe::WebRunner::new()
+            .start(
+                CANVAS_ID,
+                eframe::WebOptions::default(),
+                Box::new(|cc| Box::new(WebApp::new(cc))),
+            )
+            .await
+            .expect("failed to start eframe");
+    });
+}
+
+async fn run(
+    tx_samples: Sender<Box<[f32; FFT_SIZE]>>,
+    handle: Rc<RefCell<Option<FlowgraphHandle>>>,
+) -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = HackRf::new();
+    let fft = Fft::with_options(FFT_SIZE, FftDirection::Forward, true, None);
+    let mag_sqr = crate::power_block();
+    let keep = crate::Keep1InN::<FFT_SIZE>::new(0.1, 3);
+    let snk = ChannelSink::new(tx_samples);
+
+    futuresdr::runtime::config::set("slab_reserved", 0);
+    connect!(fg, src > fft > mag_sqr > keep > snk);
+
+    let rt = Runtime::new();
+    let (task, h) = rt.start(fg).await;
+    *handle.borrow_mut() = Some(h);
+
+    let _ = task.await;
+    *handle.borrow_mut() = None;
+
+    Ok(())
+}
+
+struct WebApp {
+    freq: u64,
+    min: f32,
+    max: f32,
+    tx_samples: Option<Sender<Box<[f32; FFT_SIZE]>>>,
+    handle: Rc<RefCell<Option<FlowgraphHandle>>>,
+    spectrum: Arc<Mutex<Spectrum>>,
+}
+
+impl WebApp {
+    fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let (tx_samples, rx_samples) = channel(10);
+
+        let gl = cc
+            .gl
+            .as_ref()
+            .expect("You need to run eframe with the glow backend");
+
+        Self {
+            min: -50.0,
+            max: 50.0,
+            freq: 100,
+            tx_samples: Some(tx_samples),
+            handle: Rc::new(RefCell::new(None)),
+            spectrum: Arc::new(Mutex::new(Spectrum::new(gl, rx_samples))),
+        }
+    }
+
+    fn set_freq(&self, freq: u64) {
+        if let Some(mut handle) = self.handle.borrow().clone() {
+            wasm_bindgen_futures::spawn_local(async move {
+                let _ = handle.call(0, "freq", Pmt::U64(freq * 1000000)).await;
+            });
+        }
+    }
+}
+
+impl eframe::App for WebApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("FutureSDR + egui");
+            if let Some(tx_samples) = self.tx_samples.as_ref() {
+                if ui.button("Start HackRF").clicked() {
+                    let tx_samples = tx_samples.clone();
+                    let handle = self.handle.clone();
+                    wasm_bindgen_futures::spawn_local(async move {
+                        if let Err(e) = run(tx_samples, handle).await {
+                            log::error!("flowgraph failed: {e:?}");
+                        }
+                    });
+                    self.tx_samples = None;
+                }
+            }
+            ui.columns(3, |columns| {
+                if columns[0]
+                    .add(
+                        egui::Slider::new(&mut self.freq, 80..=200)
+                            .clamp_to_range(false)
+                            .suffix("MHz")
+                            .text("frequency"),
+                    )
+                    .changed()
+                {
+                    self.set_freq(self.freq);
+                }
+                if columns[1]
+                    .add(
+                        egui::Slider::new(&mut self.min, -50.0..=0.0)
+                            .clamp_to_range(false)
+                            .suffix("dB")
+                            .text("min"),
+                    )
+                    .changed()
+                {
+                    self.spectrum.lock().set_min(self.min);
+                }
+                if columns[2]
+                    .add(
+                        egui::Slider::new(&mut self.max, -20.0..=50.0)
+                            .clamp_to_range(false)
+                            .suffix("dB")
+                            .text("max"),
+                    )
+                    .changed()
+                {
+                    self.spectrum.lock().set_max(self.max);
+                }
+            });
+            egui::Frame::canvas(ui.style()).show(ui, |ui| {
+                let (rect, _response) =
+                    ui.allocate_exact_size(ui.available_size(), egui::Sense::drag());
+                let spectrum = self.spectrum.clone();
+                let callback = egui::PaintCallback {
+                    rect,
+                    callback: Arc::new(egui_glow::CallbackFn::new(move |_info, painter| {
+                        spectrum.lock().paint(painter.gl());
+                    })),
+                };
+                ui.painter().add(callback);
+            });
+        });
+        ctx.request_repaint_after(std::time::Duration::from_millis(16));
+    }
+
+    fn on_exit(&mut self, gl: Option<&glow::Context>) {
+        if let Some(gl) = gl {
+            self.spectrum.lock().destroy(gl);
+        }
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! When you run the example, it will build a flowgraph consisting of the following blocks:
 //! * SeifySource: Gets data from your SDR
-//! * Demodulator: Demodulates the FM signal
+//! * WbfmReceive: Demodulates the FM signal and filters the audio
 //! * AudioSink: Plays the demodulated signal on your device
 //!
 //! After giving it some time to start up the SDR, it enters a loop where you will
@@ -15,10 +15,10 @@ use clap::Parser;
 use futuresdr::anyhow::Result;
 use futuresdr::async_io;
 use futuresdr::blocks::audio::AudioSink;
+use futuresdr::blocks::fm::WbfmReceiveBuilder;
 use futuresdr::blocks::seify::SourceBuilder;
 use futuresdr::blocks::Apply;
 use futuresdr::blocks::FirBuilder;
-use futuresdr::futuredsp::firdes;
 use futuresdr::macros::connect;
 use futuresdr::num_complex::Complex32;
 use futuresdr::num_integer::gcd;
@@ -106,15 +106,6 @@ fn main() -> Result<()> {
     println!("interp {interp}   decim {decim}");
     let resamp1 = FirBuilder::new_resampling::<Complex32, Complex32>(interp, decim);
 
-    // Demodulation block using the conjugate delay method
-    // See https://en.wikipedia.org/wiki/Detector_(radio)#Quadrature_detector
-    let mut last = Complex32::new(0.0, 0.0); // store sample x[n-1]
-    let demod = Apply::new(move |v: &Complex32| -> f32 {
-        let arg = (v * last.conj()).arg(); // Obtain phase of x[n] * conj(x[n-1])
-        last = *v;
-        arg
-    });
-
     let mut last = Complex32::new(1.0, 0.0);
     let add = Complex32::from_polar(
         1.0,
@@ -125,23 +116,15 @@ fn main() -> Result<()> {
         last * v
     });
 
-    // Design filter for the audio and decimate by 5.
-    // Ideally, this should be a FM de-emphasis filter, but the following works.
-    let cutoff = 2_000.0 / (audio_rate * audio_mult) as f64;
-    let transition = 10_000.0 / (audio_rate * audio_mult) as f64;
-    println!("cutoff {cutoff}   transition {transition}");
-    let audio_filter_taps = firdes::kaiser::lowpass::<f32>(cutoff, transition, 0.1);
-    let resamp2 = FirBuilder::new_resampling_with_taps::<f32, f32, _, _>(
-        1,
-        audio_mult as usize,
-        audio_filter_taps,
-    );
+    // Demodulate, filter the audio, decimate to the audio rate, and apply the de-emphasis
+    let demod =
+        WbfmReceiveBuilder::new((audio_rate * audio_mult) as f64, audio_mult as usize).build()?;
 
     // Single-channel `AudioSink` with the downsampled rate (sample_rate / (8*5) = 48_000)
     let snk = AudioSink::new(audio_rate, 1);
 
     // Add all the blocks to the `Flowgraph`...
-    connect!(fg, src > shift > resamp1 > demod > resamp2 > snk.in;);
+    connect!(fg, src > shift > resamp1 > demod > snk.in;);
 
     // Start the flowgraph and save the handle
     let rt = Runtime::new();
@@ -1,31 +1,49 @@
 use futuresdr::anyhow::Result;
 use futuresdr::blocks::seify::SourceBuilder;
-use futuresdr::blocks::Fft;
-use futuresdr::blocks::FftDirection;
+use futuresdr::blocks::FftWindow;
+use futuresdr::blocks::SpectrogramBuilder;
 use futuresdr::blocks::WebsocketSinkBuilder;
 use futuresdr::blocks::WebsocketSinkMode;
+use futuresdr::blocks::XlatingFirBuilder;
 use futuresdr::macros::connect;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Runtime;
 
 const FFT_SIZE: usize = 2048;
+const CHANNEL_FFT_SIZE: usize = 512;
+const SAMPLE_RATE: f64 = 3.2e6;
 
 fn main() -> Result<()> {
     let mut fg = Flowgraph::new();
 
     let src = SourceBuilder::new()
         .frequency(100e6)
-        .sample_rate(3.2e6)
+        .sample_rate(SAMPLE_RATE)
         .gain(34.0)
         .build()?;
-    let fft = Fft::with_options(FFT_SIZE, FftDirection::Forward, true, None);
-    let mag_sqr = spectrum::power_block();
+    // the FFT is reconfigured from the frontend, the output stays at FFT_SIZE bins
+    let spectrogram = SpectrogramBuilder::new(FFT_SIZE)
+        .window(FftWindow::Hann)
+        .build()?;
     let keep = spectrum::Keep1InN::<FFT_SIZE>::new(0.1, 3);
     let snk = WebsocketSinkBuilder::<f32>::new(9001)
         .mode(WebsocketSinkMode::FixedBlocking(FFT_SIZE))
         .build();
 
-    connect!(fg, src > fft > mag_sqr > keep > snk);
+    // channel, selected in the waterfall of the frontend
+    let xlating = XlatingFirBuilder::new(8, 0.0, SAMPLE_RATE)
+        .bandwidth(200e3)
+        .build()?;
+    let channel_spectrogram = SpectrogramBuilder::new(CHANNEL_FFT_SIZE)
+        .window(FftWindow::Hann)
+        .build()?;
+    let channel_keep = spectrum::Keep1InN::<CHANNEL_FFT_SIZE>::new(0.1, 3);
+    let channel_snk = WebsocketSinkBuilder::<f32>::new(9002)
+        .mode(WebsocketSinkMode::FixedDropping(CHANNEL_FFT_SIZE))
+        .build();
+
+    connect!(fg, src > spectrogram > keep > snk;
+        src > xlating > channel_spectrogram > channel_keep > channel_snk);
 
     Runtime::new().run(fg)?;
     Ok(())
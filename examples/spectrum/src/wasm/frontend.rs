@@ -5,18 +5,22 @@ use leptos::html::Span;
 use leptos::logging::*;
 use leptos::wasm_bindgen::JsCast;
 use leptos::*;
+use prophecy::ColorMap;
 use prophecy::FlowgraphHandle;
 use prophecy::FlowgraphMermaid;
 use prophecy::RadioSelector;
 use prophecy::RuntimeHandle;
+use prophecy::Spectrogram;
 use prophecy::TimeSink;
 use prophecy::TimeSinkMode;
-use prophecy::Waterfall;
 use prophecy::WaterfallMode;
 use std::cell::RefCell;
 use std::rc::Rc;
 use web_sys::HtmlInputElement;
 
+/// Block id of the channel filter in the cpu flowgraph
+const XLATING_BLOCK: usize = 4;
+
 #[component]
 /// Spectrum Widget
 pub fn Spectrum(fg_handle: FlowgraphHandle) -> impl IntoView {
@@ -39,15 +43,17 @@ pub fn Spectrum(fg_handle: FlowgraphHandle) -> impl IntoView {
 
     let time_data = Rc::new(RefCell::new(None));
     let waterfall_data = Rc::new(RefCell::new(None));
-    let ws_url = {
+    let ws_url = |port: u32| {
         let proto = window().location().protocol().unwrap();
         let host = window().location().hostname().unwrap();
         if proto == "http:" {
-            format!("ws://{}:9001", host)
+            format!("ws://{}:{}", host, port)
         } else {
-            format!("wss://{}:9001", host)
+            format!("wss://{}:{}", host, port)
         }
     };
+    let channel_url = ws_url(9002);
+    let ws_url = ws_url(9001);
     {
         let time_data = time_data.clone();
         let waterfall_data = waterfall_data.clone();
@@ -70,6 +76,28 @@ pub fn Spectrum(fg_handle: FlowgraphHandle) -> impl IntoView {
 
     let (min, set_min) = create_signal(-40.0f32);
     let (max, set_max) = create_signal(20.0f32);
+    let (colormap, set_colormap) = create_signal(ColorMap::default());
+    let (averaging, set_averaging) = create_signal(0.0f32);
+    let (max_hold, set_max_hold) = create_signal(false);
+    let (min_hold, set_min_hold) = create_signal(false);
+    let (sample_rate, set_sample_rate) = create_signal(Pmt::F64(3.2e6));
+    let (channel, set_channel) = create_signal((0.0f64, 200e3f64));
+
+    // the channel filter of the cpu flowgraph follows the sample rate of the source
+    let sample_rate = Signal::derive(move || match sample_rate() {
+        Pmt::F64(r) => r,
+        _ => 3.2e6,
+    });
+    {
+        let fg_handle = fg_handle.clone();
+        create_effect(move |_| {
+            let p = Pmt::F64(sample_rate());
+            let mut fg_handle = fg_handle.clone();
+            spawn_local(async move {
+                let _ = fg_handle.call(XLATING_BLOCK, "sample_rate", p).await;
+            });
+        });
+    }
 
     let min_label = create_node_ref::<Span>();
     let max_label = create_node_ref::<Span>();
@@ -141,8 +169,31 @@ pub fn Spectrum(fg_handle: FlowgraphHandle) -> impl IntoView {
                     }} />
                     <span class="text-white p-2 m-2" node_ref=gain_label>"gain: 60 dB"</span>
                 </div>
+                <div class="basis-1/3">
+                    <select class="text-black m-2"
+                        on:change= move |v| {
+                            set_colormap(event_target_value(&v).parse().unwrap());
+                        }> {
+                        ColorMap::ALL.into_iter()
+                            .map(|c| view! { <option value={c.to_string()}>{c.to_string()}</option> })
+                            .collect::<Vec<_>>()
+                    }
+                    </select>
+                    <span class="text-white p-2 m-2">"color map"</span>
+                </div>
+                <div class="basis-1/3 text-white">
+                    <input type="range" min="0" max="0.99" step="0.01" value="0" class="align-middle"
+                        on:change= move |v| {
+                            set_averaging(event_target_value(&v).parse().unwrap());
+                        } />
+                    <span class="p-2 m-2">"averaging: " {averaging}</span>
+                    <input type="checkbox" class="m-2" on:change=move |v| set_max_hold(event_target_checked(&v)) />
+                    <span class="p-2">"max hold"</span>
+                    <input type="checkbox" class="m-2" on:change=move |v| set_min_hold(event_target_checked(&v)) />
+                    <span class="p-2">"min hold"</span>
+                </div>
                 <div class="basis-1/2 text-white">
-                    <RadioSelector fg_handle=fg_handle.clone() block_id=0 handler="sample_rate" values=[
+                    <RadioSelector fg_handle=fg_handle.clone() block_id=0 handler="sample_rate" setter=set_sample_rate values=[
                         ("3.2 MHz".to_string(), Pmt::F64(3.2e6)),
                         ("8 MHz".to_string(), Pmt::F64(8e6)),
                         ("16 MHz".to_string(), Pmt::F64(16e6)),
@@ -153,10 +204,25 @@ pub fn Spectrum(fg_handle: FlowgraphHandle) -> impl IntoView {
             </div>
         </Show>
         <div class="border-2 border-slate-500 rounded-md m-4" style="height: 400px; max-height: 40vh">
-            <TimeSink min=min max=max mode=TimeSinkMode::Data(time_data) />
+            <TimeSink min=min max=max mode=TimeSinkMode::Data(time_data) averaging=averaging max_hold=max_hold min_hold=min_hold sample_rate=sample_rate
+                export=true button_class="bg-slate-500 text-white px-1 m-1 rounded" />
         </div>
-        <div class="border-2 border-slate-500 rounded-md m-4" style="height: 400px; max-height: 40vh">
-            <Waterfall min=min max=max mode=WaterfallMode::Data(waterfall_data) />
+        <div class="border-2 border-slate-500 rounded-md m-4 text-white" style="height: 400px; max-height: 40vh">
+            <Spectrogram fg_handle=fg_handle.clone() block_id=1 min=min max=max colormap=colormap
+                mode=WaterfallMode::Data(waterfall_data) select_class="text-black m-2"
+                input_class="align-middle" sample_rate=sample_rate selection=set_channel
+                selection_block=XLATING_BLOCK export=true
+                button_class="bg-slate-500 text-white px-1 m-1 rounded" />
+        </div>
+        <div class="text-white mx-4">
+            {move || {
+                let (freq, bandwidth) = channel();
+                format!("channel: {:+.1} kHz, bandwidth {:.1} kHz (drag over the waterfall to select)",
+                    freq / 1e3, bandwidth / 1e3)
+            }}
+        </div>
+        <div class="border-2 border-slate-500 rounded-md m-4" style="height: 200px; max-height: 20vh">
+            <TimeSink min=min max=max mode=TimeSinkMode::Websocket(channel_url) />
         </div>
         <div class="border-2 border-slate-500 rounded-md m-4 p-4">
             {move || {
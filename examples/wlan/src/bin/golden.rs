@@ -0,0 +1,34 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use futuresdr::anyhow::Result;
+
+use wlan::golden::generate;
+use wlan::golden::MCS;
+
+/// Generate golden samples for the receiver regression tests
+#[derive(Parser, Debug)]
+#[clap(version)]
+struct Args {
+    /// Output directory
+    #[clap(short, long, default_value = "tests/golden")]
+    output: PathBuf,
+    /// Frames per MCS
+    #[clap(short, long, default_value_t = 10)]
+    frames: usize,
+    /// Standard deviation of the noise added to the samples
+    #[clap(long, default_value_t = 0.01)]
+    noise: f32,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    std::fs::create_dir_all(&args.output)?;
+
+    for mcs in MCS {
+        let name = generate(&args.output, mcs, args.frames, args.noise)?;
+        println!("wrote {name} ({} frames)", args.frames);
+    }
+
+    Ok(())
+}
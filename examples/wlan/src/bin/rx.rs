@@ -18,6 +18,7 @@ use futuresdr::runtime::Runtime;
 
 use wlan::fft_tag_propagation;
 use wlan::parse_channel;
+use wlan::Bandwidth;
 use wlan::Decoder;
 use wlan::FrameEqualizer;
 use wlan::MovingAverage;
@@ -36,15 +37,18 @@ struct Args {
     /// Gain
     #[clap(short, long, default_value_t = 28.0)]
     gain: f64,
-    /// Sample Rate
-    #[clap(short, long, default_value_t = 20e6)]
-    sample_rate: f64,
+    /// Sample Rate (default: bandwidth)
+    #[clap(short, long)]
+    sample_rate: Option<f64>,
     /// WLAN Channel Number
     #[clap(short, long, value_parser = parse_channel, default_value = "34")]
     channel: f64,
     /// DC Offset
     #[clap(short, long, default_value_t = false)]
     dc_offset: bool,
+    /// Channel Bandwidth (20 or 40 MHz)
+    #[clap(long, value_parser = Bandwidth::parse, default_value = "20")]
+    bandwidth: Bandwidth,
 }
 
 fn main() -> Result<()> {
@@ -56,7 +60,7 @@ fn main() -> Result<()> {
 
     let mut seify = SourceBuilder::new()
         .frequency(args.channel)
-        .sample_rate(args.sample_rate)
+        .sample_rate(args.sample_rate.unwrap_or(args.bandwidth.sample_rate()))
         .gain(args.gain);
     if let Some(ref s) = args.args {
         seify = seify.args(s)?;
@@ -84,32 +88,34 @@ fn main() -> Result<()> {
         src
     };
 
-    let delay = Delay::<Complex32>::new(16);
+    // autocorrelation of the short training symbols
+    let f = args.bandwidth.fft_size() / 64;
+    let delay = Delay::<Complex32>::new(16 * f as isize);
     connect!(fg, prev > delay);
 
     let complex_to_mag_2 = Apply::new(|i: &Complex32| i.norm_sqr());
-    let float_avg = MovingAverage::<f32>::new(64);
+    let float_avg = MovingAverage::<f32>::new(64 * f);
     connect!(fg, prev > complex_to_mag_2 > float_avg);
 
     let mult_conj = Combine::new(|a: &Complex32, b: &Complex32| a * b.conj());
-    let complex_avg = MovingAverage::<Complex32>::new(48);
+    let complex_avg = MovingAverage::<Complex32>::new(48 * f);
     connect!(fg, prev > in0.mult_conj.out > complex_avg;
                  delay > mult_conj.in1);
 
     let divide_mag = Combine::new(|a: &Complex32, b: &f32| a.norm() / b);
     connect!(fg, complex_avg > divide_mag.in0; float_avg > divide_mag.in1);
 
-    let sync_short = SyncShort::new();
+    let sync_short = SyncShort::with_bandwidth(args.bandwidth);
     connect!(fg, delay > sync_short.in_sig;
                  complex_avg > sync_short.in_abs;
                  divide_mag > sync_short.in_cor);
 
-    let sync_long = SyncLong::new();
+    let sync_long = SyncLong::with_bandwidth(args.bandwidth);
     connect!(fg, sync_short > sync_long);
 
-    let mut fft = Fft::new(64);
+    let mut fft = Fft::new(args.bandwidth.fft_size());
     fft.set_tag_propagation(Box::new(fft_tag_propagation));
-    let frame_equalizer = FrameEqualizer::new();
+    let frame_equalizer = FrameEqualizer::with_bandwidth(args.bandwidth);
     let decoder = Decoder::new();
     let symbol_sink = WebsocketPmtSink::new(9002);
     connect!(fg, sync_long > fft > frame_equalizer > decoder;
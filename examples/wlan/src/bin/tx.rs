@@ -13,6 +13,7 @@ use futuresdr::runtime::Runtime;
 
 use wlan::fft_tag_propagation;
 use wlan::parse_channel;
+use wlan::Bandwidth;
 use wlan::Encoder;
 use wlan::Mac;
 use wlan::Mapper;
@@ -31,12 +32,18 @@ struct Args {
     /// Gain
     #[clap(short, long, default_value_t = 60.0)]
     gain: f64,
-    /// Sample Rate
-    #[clap(short, long, default_value_t = 20e6)]
-    sample_rate: f64,
+    /// Sample Rate (default: bandwidth)
+    #[clap(short, long)]
+    sample_rate: Option<f64>,
     /// WLAN Channel Number
     #[clap(short, long, value_parser = parse_channel, default_value = "34")]
     channel: f64,
+    /// Channel Bandwidth (20 or 40 MHz)
+    #[clap(long, value_parser = Bandwidth::parse, default_value = "20")]
+    bandwidth: Bandwidth,
+    /// MCS, e.g., qam16_1_2 or htmcs7
+    #[clap(short, long, value_parser = Mcs::parse, default_value = "qam16_1_2")]
+    mcs: Mcs,
 }
 
 use wlan::MAX_SYM;
@@ -50,14 +57,18 @@ fn main() -> Result<()> {
 
     let mut size = 4096;
     let prefix_in_size = loop {
-        if size / 8 >= MAX_SYM * 64 {
+        if size / 8 >= MAX_SYM * args.bandwidth.fft_size() {
             break size;
         }
         size += 4096
     };
     let mut size = 4096;
     let prefix_out_size = loop {
-        if size / 8 >= PAD_FRONT + std::cmp::max(PAD_TAIL, 1) + 320 + MAX_SYM * 80 {
+        if size / 8
+            >= PAD_FRONT
+                + std::cmp::max(PAD_TAIL, 1)
+                + (320 + MAX_SYM * 80) * args.bandwidth.fft_size() / 64
+        {
             break size;
         }
         size += 4096
@@ -65,20 +76,21 @@ fn main() -> Result<()> {
 
     let mut fg = Flowgraph::new();
     let mac = fg.add_block(Mac::new([0x42; 6], [0x23; 6], [0xff; 6]));
-    let encoder = fg.add_block(Encoder::new(Mcs::Qpsk_1_2));
+    let encoder = fg.add_block(Encoder::with_bandwidth(Mcs::Qpsk_1_2, args.bandwidth));
     fg.connect_message(mac, "tx", encoder, "tx")?;
     let mapper = fg.add_block(Mapper::new());
     fg.connect_stream(encoder, "out", mapper, "in")?;
+    let n = args.bandwidth.fft_size();
     let mut fft = Fft::with_options(
-        64,
+        n,
         FftDirection::Inverse,
         true,
-        Some((1.0f32 / 52.0).sqrt()),
+        Some((64.0f32 / 52.0 / n as f32).sqrt()),
     );
     fft.set_tag_propagation(Box::new(fft_tag_propagation));
     let fft = fg.add_block(fft);
     fg.connect_stream(mapper, "out", fft, "in")?;
-    let prefix = fg.add_block(Prefix::new(PAD_FRONT, PAD_TAIL));
+    let prefix = fg.add_block(Prefix::with_bandwidth(PAD_FRONT, PAD_TAIL, args.bandwidth));
     fg.connect_stream_with_type(
         fft,
         "out",
@@ -88,7 +100,7 @@ fn main() -> Result<()> {
     )?;
     let mut snk = SinkBuilder::new()
         .frequency(args.channel)
-        .sample_rate(args.sample_rate)
+        .sample_rate(args.sample_rate.unwrap_or(args.bandwidth.sample_rate()))
         .gain(args.gain);
     if let Some(a) = args.antenna {
         snk = snk.antenna(a);
@@ -109,6 +121,7 @@ fn main() -> Result<()> {
     let rt = Runtime::new();
     let (_fg, mut handle) = rt.start_sync(fg);
 
+    let mcs = args.mcs;
     let mut seq = 0u64;
     rt.block_on(async move {
         loop {
@@ -119,7 +132,7 @@ fn main() -> Result<()> {
                     0,
                     Pmt::Any(Box::new((
                         format!("FutureSDR {seq}xxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxxx").as_bytes().to_vec(),
-                        Mcs::Qam16_1_2,
+                        mcs,
                     ))),
                 )
                 .await
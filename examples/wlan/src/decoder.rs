@@ -20,12 +20,16 @@ use crate::MAX_PSDU_SIZE;
 use crate::MAX_SYM;
 use crate::{FrameParam, ViterbiDecoder};
 
+/// Decode the data symbols of a frame.
+///
+/// Frames with a valid FCS are posted to the `rx_frames` and `rftap` outputs. A-MPDUs of HT
+/// frames are de-aggregated, posting each MPDU with a valid FCS.
 pub struct Decoder {
     frame_complete: bool,
     frame_param: FrameParam,
     decoder: ViterbiDecoder,
     copied: usize,
-    rx_symbols: [u8; 48 * MAX_SYM],
+    rx_symbols: [u8; MAX_ENCODED_BITS],
     rx_bits: [u8; MAX_ENCODED_BITS],
     deinterleaved_bits: [u8; MAX_ENCODED_BITS],
     decoded_bits: [u8; MAX_ENCODED_BITS],
@@ -46,7 +50,7 @@ impl Decoder {
                 frame_param: FrameParam::new(Mcs::Bpsk_1_2, 0),
                 decoder: ViterbiDecoder::new(),
                 copied: 0,
-                rx_symbols: [0; 48 * MAX_SYM],
+                rx_symbols: [0; MAX_ENCODED_BITS],
                 rx_bits: [0; MAX_ENCODED_BITS],
                 deinterleaved_bits: [0; MAX_ENCODED_BITS],
                 decoded_bits: [0; MAX_ENCODED_BITS],
@@ -55,17 +59,18 @@ impl Decoder {
         )
     }
     fn deinterleave(&mut self) {
-        let n_cbps = self.frame_param.mcs().n_cbps();
+        let n_cbps = self.frame_param.n_cbps();
         let n_bpsc = self.frame_param.mcs().modulation().n_bpsc();
+        let n_col = self.frame_param.n_col();
         let mut first = vec![0usize; n_cbps];
         let mut second = vec![0usize; n_cbps];
         let s = std::cmp::max(n_bpsc / 2, 1);
 
         for j in 0..n_cbps {
-            first[j] = s * (j / s) + ((j + (16 * j / n_cbps)) % s);
+            first[j] = s * (j / s) + ((j + (n_col * j / n_cbps)) % s);
         }
         for i in 0..n_cbps {
-            second[i] = 16 * i - (n_cbps - 1) * (16 * i / n_cbps);
+            second[i] = n_col * i - (n_cbps - 1) * (n_col * i / n_cbps);
         }
 
         for i in 0..self.frame_param.n_symbols() {
@@ -76,16 +81,15 @@ impl Decoder {
         }
     }
 
-    fn decode(&mut self) -> bool {
+    /// MPDUs with a valid FCS, without the FCS
+    fn decode(&mut self) -> Vec<Vec<u8>> {
         let syms = self.frame_param.n_symbols();
         let bpsc = self.frame_param.mcs().modulation().n_bpsc();
-        for i in 0..syms * 48 {
+        for i in 0..syms * self.frame_param.n_sd() {
             for k in 0..bpsc {
                 self.rx_bits[i * bpsc + k] = u8::from((self.rx_symbols[i] & (1 << k)) > 0);
             }
         }
-        // println!("rx_symbols: {:?}", &self.rx_symbols[0..syms * 48]);
-        // println!("rx_bits: {:?}", &self.rx_bits[0..syms * 48 * bpsc]);
 
         self.deinterleave();
         self.decoder.decode(
@@ -95,8 +99,47 @@ impl Decoder {
         );
         self.descramble();
 
-        let crc = crc32fast::hash(&self.out_bytes[2..self.frame_param.psdu_size() + 2]);
-        crc == 558161692
+        let psdu = &self.out_bytes[2..self.frame_param.psdu_size() + 2];
+        if self.frame_param.aggregation() {
+            Self::deaggregate(psdu)
+        } else if Self::check_fcs(psdu) {
+            vec![psdu[0..psdu.len() - 4].to_vec()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn check_fcs(mpdu: &[u8]) -> bool {
+        mpdu.len() >= 4 && crc32fast::hash(mpdu) == 558161692
+    }
+
+    /// Split an A-MPDU into its MPDUs.
+    ///
+    /// Each MPDU is preceded by a 4-byte delimiter with its length and the signature 0x4E and
+    /// padded to a multiple of 4 bytes. If a delimiter or MPDU is corrupted, the next delimiter is
+    /// searched in steps of 4 bytes.
+    fn deaggregate(psdu: &[u8]) -> Vec<Vec<u8>> {
+        let mut mpdus = Vec::new();
+        let mut offset = 0;
+
+        while offset + 4 <= psdu.len() {
+            let delimiter = &psdu[offset..offset + 4];
+            let len = (u16::from_le_bytes([delimiter[0], delimiter[1]]) >> 4) as usize;
+            let end = offset + 4 + len;
+            if delimiter[3] != 0x4e || len == 0 || end > psdu.len() {
+                offset += 4;
+                continue;
+            }
+            let mpdu = &psdu[offset + 4..end];
+            if Self::check_fcs(mpdu) {
+                mpdus.push(mpdu[0..len - 4].to_vec());
+                offset = (end + 3) & !3;
+            } else {
+                offset += 4;
+            }
+        }
+
+        mpdus
     }
 
     fn descramble(&mut self) {
@@ -155,7 +198,10 @@ impl Kernel for Decoder {
                     warn!("decoder: previous frame not complete, canceling.");
                 }
                 let frame_param = any.downcast_ref::<FrameParam>().unwrap();
-                if frame_param.n_symbols() <= MAX_SYM && frame_param.psdu_size() <= MAX_PSDU_SIZE {
+                if frame_param.n_symbols() <= MAX_SYM
+                    && frame_param.n_symbols() * frame_param.n_cbps() <= MAX_ENCODED_BITS
+                    && frame_param.psdu_size() <= MAX_PSDU_SIZE
+                {
                     self.frame_param = frame_param.clone();
                     self.copied = 0;
                     self.frame_complete = false;
@@ -169,16 +215,15 @@ impl Kernel for Decoder {
 
         // println!("decoder: input len {}, complete {}, copied {}, frame {:?}, tags {:?}", input.len(), self.frame_complete, self.copied, self.frame_param, tags);
 
-        let max_i = input.len() / 48;
+        let n_sd = self.frame_param.n_sd();
+        let max_i = input.len() / n_sd;
         let mut i = 0;
 
-        // println!("decoder input: {:?}", &input[0..max_i * 48]);
-
         while i < max_i {
             if self.copied < self.frame_param.n_symbols() {
                 // println!("copying {} of {}", self.copied, self.frame_param.n_symbols());
-                self.rx_symbols[(self.copied * 48)..((self.copied + 1) * 48)]
-                    .copy_from_slice(&input[(i * 48)..((i + 1) * 48)]);
+                self.rx_symbols[(self.copied * n_sd)..((self.copied + 1) * n_sd)]
+                    .copy_from_slice(&input[(i * n_sd)..((i + 1) * n_sd)]);
             }
 
             i += 1;
@@ -187,14 +232,11 @@ impl Kernel for Decoder {
             if self.copied == self.frame_param.n_symbols() {
                 self.frame_complete = true;
 
-                if self.decode() {
+                for blob in self.decode() {
                     // println!(
                     //     "decoded: {:?}",
                     //     &self.out_bytes[0..self.frame_param.psdu_size() + 2]
                     // );
-                    let mut blob = vec![0; self.frame_param.psdu_size() - 4];
-                    blob.copy_from_slice(&self.out_bytes[2..self.frame_param.psdu_size() - 2]);
-
                     let mut rftap = vec![0; blob.len() + 12];
                     rftap[0..4].copy_from_slice("RFta".as_bytes());
                     rftap[4..6].copy_from_slice(&3u16.to_le_bytes());
@@ -210,7 +252,7 @@ impl Kernel for Decoder {
             }
         }
 
-        sio.input(0).consume(i * 48);
+        sio.input(0).consume(i * n_sd);
         if sio.input(0).finished() && i == max_i {
             mio.output_mut(0).post(Pmt::Null).await;
             io.finished = true;
@@ -1,3 +1,4 @@
+use crate::Bandwidth;
 use crate::FrameParam;
 use crate::Mcs;
 use crate::MAX_ENCODED_BITS;
@@ -24,8 +25,9 @@ use std::collections::VecDeque;
 const MAX_FRAMES: usize = 1000;
 
 pub struct Encoder {
-    tx_frames: VecDeque<(Vec<u8>, Mcs)>,
+    tx_frames: VecDeque<(Vec<u8>, Mcs, bool)>,
     default_mcs: Mcs,
+    bandwidth: Bandwidth,
     current_len: usize,
     current_index: usize,
     scrambler_seed: u8,
@@ -40,6 +42,12 @@ pub struct Encoder {
 
 impl Encoder {
     pub fn new(default_mcs: Mcs) -> Block {
+        Self::with_bandwidth(default_mcs, Bandwidth::Bw20)
+    }
+
+    /// Encoder for a 40 MHz channel sends HT frames in HT40 and legacy frames as non-HT duplicate.
+    /// The FFT and the [`Prefix`](crate::Prefix) have to be configured for the same bandwidth.
+    pub fn with_bandwidth(default_mcs: Mcs, bandwidth: Bandwidth) -> Block {
         Block::new(
             BlockMetaBuilder::new("Encoder").build(),
             StreamIoBuilder::new().add_output::<u8>("out").build(),
@@ -49,6 +57,7 @@ impl Encoder {
             Encoder {
                 tx_frames: VecDeque::new(),
                 default_mcs,
+                bandwidth,
                 current_len: 0,
                 current_index: 0,
                 scrambler_seed: 1,
@@ -63,6 +72,9 @@ impl Encoder {
         )
     }
 
+    /// Queue a frame, given as [`Pmt::Blob`] or as [`Pmt::Any`] with a `(Vec<u8>, Option<Mcs>)`,
+    /// or an A-MPDU with an HT MCS, given as [`Pmt::Any`] with a `(Vec<Vec<u8>>, Option<Mcs>)`.
+    /// Frames and MPDUs include the FCS.
     #[message_handler]
     async fn transmit(
         &mut self,
@@ -73,39 +85,22 @@ impl Encoder {
     ) -> Result<Pmt> {
         match p {
             Pmt::Blob(data) => {
-                if self.tx_frames.len() >= MAX_FRAMES {
-                    warn!(
-                        "WLAN Encoder: max number of frames already in TX queue ({}). Dropping.",
-                        MAX_FRAMES
-                    );
-                } else if data.len() > MAX_PSDU_SIZE {
-                    warn!(
-                        "WLAN Encoder: TX frame too large ({}, max {}). Dropping.",
-                        data.len(),
-                        MAX_PSDU_SIZE
-                    );
-                } else {
-                    self.tx_frames.push_back((data, self.default_mcs));
-                }
+                self.enqueue(data, self.default_mcs, false);
             }
             Pmt::Any(a) => {
                 if let Some((data, mcs)) = a.downcast_ref::<(Vec<u8>, Option<Mcs>)>() {
-                    let data = data.clone();
-                    if self.tx_frames.len() >= MAX_FRAMES {
-                        warn!(
-                                "WLAN Encoder: max number of frames already in TX queue ({}). Dropping.",
-                                MAX_FRAMES
-                            );
-                    } else if data.len() > MAX_PSDU_SIZE {
+                    self.enqueue(data.clone(), mcs.unwrap_or(self.default_mcs), false);
+                } else if let Some((mpdus, mcs)) = a.downcast_ref::<(Vec<Vec<u8>>, Option<Mcs>)>() {
+                    let mcs = mcs.unwrap_or(self.default_mcs);
+                    if !mcs.is_ht() {
                         warn!(
-                            "WLAN Encoder: TX frame too large ({}, max {}). Dropping.",
-                            data.len(),
-                            MAX_PSDU_SIZE
+                            "WLAN Encoder: A-MPDU requires an HT MCS ({:?}). Dropping.",
+                            mcs
                         );
-                    } else if let Some(m) = mcs {
-                        self.tx_frames.push_back((data, *m));
+                    } else if mpdus.is_empty() || mpdus.iter().any(|m| m.len() > MAX_PSDU_SIZE) {
+                        warn!("WLAN Encoder: invalid MPDUs in A-MPDU. Dropping.");
                     } else {
-                        self.tx_frames.push_back((data, self.default_mcs));
+                        self.enqueue(crate::aggregate(mpdus), mcs, true);
                     }
                 }
             }
@@ -122,6 +117,23 @@ impl Encoder {
         Ok(Pmt::Null)
     }
 
+    fn enqueue(&mut self, data: Vec<u8>, mcs: Mcs, aggregation: bool) {
+        if self.tx_frames.len() >= MAX_FRAMES {
+            warn!(
+                "WLAN Encoder: max number of frames already in TX queue ({}). Dropping.",
+                MAX_FRAMES
+            );
+        } else if data.len() > MAX_PSDU_SIZE {
+            warn!(
+                "WLAN Encoder: TX frame too large ({}, max {}). Dropping.",
+                data.len(),
+                MAX_PSDU_SIZE
+            );
+        } else {
+            self.tx_frames.push_back((data, mcs, aggregation));
+        }
+    }
+
     fn generate_bits(&mut self, data: &[u8]) {
         for i in 0..data.len() {
             for b in 0..8 {
@@ -161,7 +173,8 @@ impl Encoder {
     }
 
     fn puncture(&mut self, n_data_bits: usize, mcs: Mcs) {
-        if matches!(mcs, Mcs::Bpsk_1_2 | Mcs::Qpsk_1_2 | Mcs::Qam16_1_2) {
+        let pattern = mcs.depuncture_pattern();
+        if pattern.len() == 2 {
             self.punctured[0..n_data_bits * 2].copy_from_slice(&self.encoded[0..n_data_bits * 2]);
             return;
         }
@@ -169,36 +182,25 @@ impl Encoder {
         let mut out = 0;
 
         for i in 0..2 * n_data_bits {
-            match mcs {
-                Mcs::Qam64_2_3 => {
-                    if i % 4 != 3 {
-                        self.punctured[out] = self.encoded[i];
-                        out += 1;
-                    }
-                }
-                Mcs::Bpsk_3_4 | Mcs::Qpsk_3_4 | Mcs::Qam16_3_4 | Mcs::Qam64_3_4 => {
-                    let m = i % 6;
-                    if !(m == 3 || m == 4) {
-                        self.punctured[out] = self.encoded[i];
-                        out += 1;
-                    }
-                }
-                _ => panic!("half-rate case should be handled separately"),
+            if pattern[i % pattern.len()] == 1 {
+                self.punctured[out] = self.encoded[i];
+                out += 1;
             }
         }
     }
 
-    fn interleave(&mut self, n_cbps: usize, n_bpsc: usize, n_sym: usize) {
+    fn interleave(&mut self, n_cbps: usize, n_bpsc: usize, n_col: usize, n_sym: usize) {
         let mut first = vec![0; n_cbps];
         let mut second = vec![0; n_cbps];
         let s = std::cmp::max(n_bpsc / 2, 1);
+        let n_row = n_cbps / n_col;
 
         for j in 0..n_cbps {
-            first[j] = s * (j / s) + ((j + (16 * j / n_cbps)) % s);
+            first[j] = s * (j / s) + ((j + (n_col * j / n_cbps)) % s);
         }
 
         for i in 0..n_cbps {
-            second[i] = 16 * i - (n_cbps - 1) * (16 * i / n_cbps);
+            second[i] = n_col * i - (n_cbps - 1) * (i / n_row);
         }
 
         for i in 0..n_sym {
@@ -208,8 +210,8 @@ impl Encoder {
         }
     }
 
-    fn split_symbols(&mut self, n_bpsc: usize, n_sym: usize) {
-        let symbols = n_sym * 48;
+    fn split_symbols(&mut self, n_bpsc: usize, n_sd: usize, n_sym: usize) {
+        let symbols = n_sym * n_sd;
 
         for i in 0..symbols {
             self.symbols[i] = 0;
@@ -225,11 +227,16 @@ impl Encoder {
         self.convolutional_encode(frame.n_data_bits());
         self.puncture(frame.n_data_bits(), frame.mcs());
         self.interleave(
-            frame.mcs.n_cbps(),
+            frame.n_cbps(),
+            frame.mcs.modulation().n_bpsc(),
+            frame.n_col(),
+            frame.n_symbols(),
+        );
+        self.split_symbols(
             frame.mcs.modulation().n_bpsc(),
+            frame.n_sd(),
             frame.n_symbols(),
         );
-        self.split_symbols(frame.mcs.modulation().n_bpsc(), frame.n_symbols());
     }
 }
 
@@ -249,10 +256,11 @@ impl Kernel for Encoder {
             }
 
             if self.current_len == 0 {
-                if let Some((data, mcs)) = self.tx_frames.pop_front() {
-                    let frame = FrameParam::new(mcs, data.len());
+                if let Some((data, mcs, aggregation)) = self.tx_frames.pop_front() {
+                    let mut frame = FrameParam::with_bandwidth(mcs, data.len(), self.bandwidth);
+                    frame.set_aggregation(aggregation);
                     self.encode(&data, &frame);
-                    self.current_len = frame.n_symbols() * 48;
+                    self.current_len = frame.n_symbols() * frame.n_sd();
                     self.current_index = 0;
                     sio.output(0)
                         .add_tag(0, Tag::NamedAny("wifi_start".to_string(), Box::new(frame)));
@@ -1,3 +1,4 @@
+use crate::Bandwidth;
 use crate::FrameParam;
 use crate::Mcs;
 use crate::Modulation;
@@ -27,58 +28,123 @@ const INTERLEAVER_PATTERN: [usize; 48] = [
     28, 31, 34, 37, 40, 43, 46, 2, 5, 8, 11, 14, 17, 20, 23, 26, 29, 32, 35, 38, 41, 44, 47,
 ];
 
+/// sum of the legacy pilots, weighted with their sign, over all 20 MHz subchannels
+fn legacy_pilots(s: &[Complex32], p: [f32; 4]) -> Complex32 {
+    s.chunks(64)
+        .zip([Complex32::new(1.0, 0.0), Complex32::new(0.0, -1.0)])
+        .map(|(s, r)| r * (s[11] * p[0] + s[25] * p[1] + s[39] * p[2] + s[53] * p[3]))
+        .sum()
+}
+
 struct Equalizer {
-    h: [Complex32; 64],
+    h: [Complex32; 128],
+    h_ht: [Complex32; 128],
     snr: f32,
 }
 
 impl Equalizer {
     fn new() -> Self {
         Equalizer {
-            h: [Complex32::new(0.0, 0.0); 64],
+            h: [Complex32::new(0.0, 0.0); 128],
+            h_ht: [Complex32::new(0.0, 0.0); 128],
             snr: 0.0,
         }
     }
-    fn sync1(&mut self, s: &[Complex32; 64]) {
+    fn sync1(&mut self, s: &[Complex32]) {
         // println!("{:?}", s);
-        self.h.copy_from_slice(s);
+        self.h[0..s.len()].copy_from_slice(s);
     }
-    fn sync2(&mut self, s: &[Complex32; 64]) {
+    fn sync2(&mut self, s: &[Complex32]) {
         // println!("{:?}", s);
         let mut signal = 0.0f32;
         let mut noise = 0.0f32;
-        for i in 6..=58 {
-            if i == 32 {
-                continue;
-            }
-            noise += (self.h[i] - s[i]).norm_sqr();
-            signal += (self.h[i] + s[i]).norm_sqr();
+        for c in (0..s.len()).step_by(64) {
+            for i in 6..=58 {
+                if i == 32 {
+                    continue;
+                }
+                noise += (self.h[c + i] - s[c + i]).norm_sqr();
+                signal += (self.h[c + i] + s[c + i]).norm_sqr();
 
-            self.h[i] += s[i];
-            self.h[i] /= LONG[i] + LONG[i];
+                self.h[c + i] += s[c + i];
+                self.h[c + i] /= LONG[i] + LONG[i];
+            }
         }
         self.snr = 10.0 * (signal / noise / 2.0).log10();
     }
 
+    fn ht_ltf(&mut self, s: &[Complex32], ltf: &[Complex32]) {
+        for (i, l) in ltf.iter().enumerate() {
+            if l.norm_sqr() > 0.0 {
+                self.h_ht[i] = s[i] / l;
+            }
+        }
+    }
+
+    /// legacy symbol, combining the subchannels of non-HT duplicates
     fn equalize(
         &mut self,
-        input: &[Complex32; 64],
-        output_symbols: &mut [Complex32; 48],
-        output_bits: &mut [u8; 48],
+        input: &[Complex32],
+        output_symbols: &mut [Complex32],
+        output_bits: &mut [u8],
         modulation: Modulation,
     ) {
         for (o, i) in (6..=58)
             .filter(|x| ![11, 25, 32, 39, 53].contains(x))
             .enumerate()
         {
-            output_symbols[o] = input[i] / self.h[i];
+            if input.len() == 64 {
+                output_symbols[o] = input[i] / self.h[i];
+            } else {
+                let (mut num, mut den) = (Complex32::new(0.0, 0.0), 0.0);
+                for c in (0..input.len()).step_by(64) {
+                    num += input[c + i] * self.h[c + i].conj();
+                    den += self.h[c + i].norm_sqr();
+                }
+                output_symbols[o] = num / den;
+            }
+            output_bits[o] = modulation.demap(&output_symbols[o]);
+        }
+    }
+
+    fn equalize_ht(
+        &mut self,
+        input: &[Complex32],
+        output_symbols: &mut [Complex32],
+        output_bits: &mut [u8],
+        modulation: Modulation,
+        bandwidth: Bandwidth,
+    ) {
+        for (o, i) in bandwidth.ht_data_subcarriers().iter().enumerate() {
+            output_symbols[o] = input[*i] / self.h_ht[*i];
             output_bits[o] = modulation.demap(&output_symbols[o]);
         }
     }
 
+    /// phase of the pilots of HT data symbol `n` relative to the HT-LTF
+    fn ht_pilot_phase(&self, s: &[Complex32], bandwidth: Bandwidth, n: usize) -> f32 {
+        let p = POLARITY[(n + 3) % 127];
+        let pilots = bandwidth.ht_pilots();
+        bandwidth
+            .ht_pilot_subcarriers()
+            .iter()
+            .enumerate()
+            .map(|(m, c)| s[*c] * self.h_ht[*c].conj() * p * pilots[(n + m) % pilots.len()])
+            .sum::<Complex32>()
+            .arg()
+    }
+
     fn snr(&self) -> f32 {
         self.snr
     }
+
+    /// channel estimate of the used subcarriers -26..=26 without DC of all subchannels
+    fn csi(&self, bandwidth: Bandwidth) -> Vec<Complex32> {
+        (0..bandwidth.fft_size())
+            .step_by(64)
+            .flat_map(|c| (6..=58).filter(|i| *i != 32).map(move |i| self.h[c + i]))
+            .collect()
+    }
 }
 
 #[derive(Debug)]
@@ -86,37 +152,67 @@ enum State {
     Sync1,
     Sync2,
     Signal,
+    HtSignal1,
+    HtSignal2,
+    HtStf,
+    HtLtf,
     Copy(usize, usize, Modulation),
     Skip,
 }
 
+/// Equalize OFDM symbols and decode the signal field.
+///
+/// A 6 Mbit/s signal field, followed by a QBPSK symbol, starts an HT frame, whose HT-SIG is
+/// decoded and whose data symbols are equalized with the HT-LTF. With 40 MHz, legacy frames are
+/// expected as non-HT duplicates.
+///
+/// Equalized data symbols of each frame are posted to the `symbols` message output. The channel
+/// estimate of each frame with a valid signal field is posted to the `csi` output as a
+/// [`Pmt::VecCF32`] with the 52 used subcarriers, ordered from -26 to 26 without DC (104 for
+/// 40 MHz, starting with the lower subchannel).
 pub struct FrameEqualizer {
     equalizer: Equalizer,
     state: State,
-    sym_in: [Complex32; 64],
-    sym_out: [Complex32; 48],
-    decoded_bits: [u8; 24],
+    bandwidth: Bandwidth,
+    ht_ltf: Vec<Complex32>,
+    frame: FrameParam,
+    sym_in: [Complex32; 128],
+    sym_out: [Complex32; 108],
+    decoded_bits: [u8; 48],
     bits_out: [u8; 48],
+    ht_bits: [u8; 96],
     decoder: ViterbiDecoder,
     syms: Vec<Complex32>,
 }
 
 impl FrameEqualizer {
     pub fn new() -> Block {
+        Self::with_bandwidth(Bandwidth::Bw20)
+    }
+
+    /// Frame equalizer for 64 (20 MHz) or 128 (40 MHz) subcarriers
+    pub fn with_bandwidth(bandwidth: Bandwidth) -> Block {
         Block::new(
             BlockMetaBuilder::new("FrameEqualizer").build(),
             StreamIoBuilder::new()
                 .add_input::<Complex32>("in")
                 .add_output::<u8>("out")
                 .build(),
-            MessageIoBuilder::new().add_output("symbols").build(),
+            MessageIoBuilder::new()
+                .add_output("symbols")
+                .add_output("csi")
+                .build(),
             Self {
                 equalizer: Equalizer::new(),
                 state: State::Skip,
-                sym_in: [Complex32::new(0.0, 0.0); 64],
-                sym_out: [Complex32::new(0.0, 0.0); 48],
-                decoded_bits: [0; 24],
+                bandwidth,
+                ht_ltf: bandwidth.ht_ltf(),
+                frame: FrameParam::new(Mcs::Bpsk_1_2, 0),
+                sym_in: [Complex32::new(0.0, 0.0); 128],
+                sym_out: [Complex32::new(0.0, 0.0); 108],
+                decoded_bits: [0; 48],
                 bits_out: [0; 48],
+                ht_bits: [0; 96],
                 decoder: ViterbiDecoder::new(),
                 syms: Vec::new(),
             },
@@ -161,20 +257,137 @@ impl FrameEqualizer {
         }
 
         match r {
-            11 => Some(FrameParam::new(Mcs::Bpsk_1_2, bytes)),
-            15 => Some(FrameParam::new(Mcs::Bpsk_3_4, bytes)),
-            10 => Some(FrameParam::new(Mcs::Qpsk_1_2, bytes)),
-            14 => Some(FrameParam::new(Mcs::Qpsk_3_4, bytes)),
-            9 => Some(FrameParam::new(Mcs::Qam16_1_2, bytes)),
-            13 => Some(FrameParam::new(Mcs::Qam16_3_4, bytes)),
-            8 => Some(FrameParam::new(Mcs::Qam64_2_3, bytes)),
-            12 => Some(FrameParam::new(Mcs::Qam64_3_4, bytes)),
+            11 => Some(FrameParam::with_bandwidth(
+                Mcs::Bpsk_1_2,
+                bytes,
+                self.bandwidth,
+            )),
+            15 => Some(FrameParam::with_bandwidth(
+                Mcs::Bpsk_3_4,
+                bytes,
+                self.bandwidth,
+            )),
+            10 => Some(FrameParam::with_bandwidth(
+                Mcs::Qpsk_1_2,
+                bytes,
+                self.bandwidth,
+            )),
+            14 => Some(FrameParam::with_bandwidth(
+                Mcs::Qpsk_3_4,
+                bytes,
+                self.bandwidth,
+            )),
+            9 => Some(FrameParam::with_bandwidth(
+                Mcs::Qam16_1_2,
+                bytes,
+                self.bandwidth,
+            )),
+            13 => Some(FrameParam::with_bandwidth(
+                Mcs::Qam16_3_4,
+                bytes,
+                self.bandwidth,
+            )),
+            8 => Some(FrameParam::with_bandwidth(
+                Mcs::Qam64_2_3,
+                bytes,
+                self.bandwidth,
+            )),
+            12 => Some(FrameParam::with_bandwidth(
+                Mcs::Qam64_3_4,
+                bytes,
+                self.bandwidth,
+            )),
             _ => {
                 info!("signal: wrong encoding (r = {})", r);
                 None
             }
         }
     }
+
+    fn decode_ht_signal_field(&mut self) -> Option<FrameParam> {
+        let mut deinterleaved = [0u8; 96];
+        for s in 0..2 {
+            for i in 0..48 {
+                deinterleaved[s * 48 + i] = self.ht_bits[s * 48 + INTERLEAVER_PATTERN[i]];
+            }
+        }
+
+        self.decoder.decode(
+            FrameParam::new(Mcs::Bpsk_1_2, 3),
+            &deinterleaved,
+            &mut self.decoded_bits,
+        );
+        let bits = self.decoded_bits;
+
+        let crc = bits[34..42].iter().fold(0u8, |c, b| (c << 1) | b);
+        if crc != crate::ht_sig_crc(&bits[0..34]) {
+            info!("HT-SIG: wrong CRC");
+            return None;
+        }
+
+        let value = |r: std::ops::Range<usize>| {
+            r.enumerate()
+                .fold(0usize, |v, (k, i)| v | ((bits[i] as usize) << k))
+        };
+        let mcs = value(0..7);
+        let length = value(8..24);
+
+        if (bits[7] == 1) != (self.bandwidth == Bandwidth::Bw40) {
+            info!("HT-SIG: wrong bandwidth");
+            return None;
+        }
+        // STBC, LDPC, short GI, and extension spatial streams
+        if value(28..34) != 0 {
+            info!("HT-SIG: unsupported options");
+            return None;
+        }
+        if length == 0 || length > crate::MAX_PSDU_SIZE {
+            info!("HT-SIG: unsupported length {}", length);
+            return None;
+        }
+        let Some(mcs) = Mcs::from_ht_index(mcs) else {
+            info!("HT-SIG: unsupported MCS {}", mcs);
+            return None;
+        };
+
+        let mut frame = FrameParam::with_bandwidth(mcs, length, self.bandwidth);
+        frame.set_aggregation(bits[27] == 1);
+        Some(frame)
+    }
+
+    fn start_frame(&mut self, frame: FrameParam, o: usize, sio: &mut StreamIo) {
+        self.state = State::Copy(
+            frame.n_symbols(),
+            frame.n_symbols(),
+            frame.mcs().modulation(),
+        );
+        sio.output(0).add_tag(
+            o,
+            Tag::NamedAny("wifi_start".to_string(), Box::new(frame.clone())),
+        );
+        self.frame = frame;
+    }
+
+    /// collect the bits of one QBPSK HT-SIG symbol
+    fn ht_signal(&mut self, half: usize) -> bool {
+        let n_fft = self.bandwidth.fft_size();
+        self.equalizer.equalize(
+            &self.sym_in[0..n_fft],
+            &mut self.sym_out,
+            &mut self.bits_out,
+            Modulation::Bpsk,
+        );
+        let (re, im) = self.sym_out[0..48].iter().fold((0.0, 0.0), |(re, im), s| {
+            (re + s.re * s.re, im + s.im * s.im)
+        });
+        for (b, s) in self.ht_bits[half * 48..(half + 1) * 48]
+            .iter_mut()
+            .zip(&self.sym_out)
+        {
+            *b = Modulation::Bpsk.demap(&(s * Complex32::new(0.0, -1.0)));
+        }
+        im > re
+    }
 }
 
 #[async_trait]
@@ -214,50 +427,45 @@ impl Kernel for FrameEqualizer {
             }
         }
 
-        let max_i = input.len() / 64;
-        let max_o = out.len() / 48;
+        let n_fft = self.bandwidth.fft_size();
+        let max_i = input.len() / n_fft;
         let mut i = 0;
         let mut o = 0;
 
         while i < max_i {
             // copy symbol w/ fft shift
-            for k in 0..64 {
-                let m = (k + 32) % 64;
-                self.sym_in[m] = input[i * 64 + k];
+            for k in 0..n_fft {
+                let m = (k + n_fft / 2) % n_fft;
+                self.sym_in[m] = input[i * n_fft + k];
             }
 
-            match self.state {
+            let sym = &self.sym_in[0..n_fft];
+            let beta = match self.state {
                 State::Sync1 | State::Sync2 => {
-                    let beta =
-                        (self.sym_in[11] - self.sym_in[25] + self.sym_in[39] + self.sym_in[53])
-                            .arg();
-                    for i in 0..64 {
-                        self.sym_in[i] *= Complex32::from_polar(1.0, -beta);
-                    }
+                    Some(legacy_pilots(sym, [1.0, -1.0, 1.0, 1.0]).arg())
                 }
-                State::Signal => {
-                    let p = POLARITY[0];
-                    let beta = ((self.sym_in[11] * p)
-                        + (self.sym_in[39] * p)
-                        + (self.sym_in[25] * p)
-                        + (self.sym_in[53] * -p))
-                        .arg();
-                    for i in 0..64 {
-                        self.sym_in[i] *= Complex32::from_polar(1.0, -beta);
-                    }
+                State::Signal | State::HtSignal1 | State::HtSignal2 => {
+                    let p = match self.state {
+                        State::Signal => POLARITY[0].re,
+                        State::HtSignal1 => POLARITY[1].re,
+                        _ => POLARITY[2].re,
+                    };
+                    Some(legacy_pilots(sym, [p, p, p, -p]).arg())
                 }
                 State::Copy(left, n, _) => {
-                    let p = POLARITY[(n - left + 1) % 127];
-                    let beta = ((self.sym_in[11] * p)
-                        + (self.sym_in[39] * p)
-                        + (self.sym_in[25] * p)
-                        + (self.sym_in[53] * -p))
-                        .arg();
-                    for i in 0..64 {
-                        self.sym_in[i] *= Complex32::from_polar(1.0, -beta);
+                    if self.frame.mcs().is_ht() {
+                        Some(self.equalizer.ht_pilot_phase(sym, self.bandwidth, n - left))
+                    } else {
+                        let p = POLARITY[(n - left + 1) % 127].re;
+                        Some(legacy_pilots(sym, [p, p, p, -p]).arg())
                     }
                 }
-                _ => {}
+                _ => None,
+            };
+            if let Some(beta) = beta {
+                for s in self.sym_in[0..n_fft].iter_mut() {
+                    *s *= Complex32::from_polar(1.0, -beta);
+                }
             }
 
             // println!("equalizer state {:?}", self.state);
@@ -272,18 +480,18 @@ impl Kernel for FrameEqualizer {
 
             match &mut self.state {
                 State::Sync1 => {
-                    self.equalizer.sync1(&self.sym_in);
+                    self.equalizer.sync1(&self.sym_in[0..n_fft]);
                     self.state = State::Sync2;
                     i += 1;
                 }
                 State::Sync2 => {
-                    self.equalizer.sync2(&self.sym_in);
+                    self.equalizer.sync2(&self.sym_in[0..n_fft]);
                     self.state = State::Signal;
                     i += 1;
                 }
                 State::Signal => {
                     self.equalizer.equalize(
-                        &self.sym_in,
+                        &self.sym_in[0..n_fft],
                         &mut self.sym_out,
                         &mut self.bits_out,
                         Modulation::Bpsk,
@@ -292,16 +500,16 @@ impl Kernel for FrameEqualizer {
                     i += 1;
                     if let Some(frame) = self.decode_signal_field() {
                         // info!("signal field decoded {:?}, snr {}", &frame, self.equalizer.snr());
+                        mio.post(1, Pmt::VecCF32(self.equalizer.csi(self.bandwidth)))
+                            .await;
 
-                        self.state = State::Copy(
-                            frame.n_symbols(),
-                            frame.n_symbols(),
-                            frame.mcs().modulation(),
-                        );
-                        sio.output(0).add_tag(
-                            o * 48,
-                            Tag::NamedAny("wifi_start".to_string(), Box::new(frame)),
-                        );
+                        if frame.mcs() == Mcs::Bpsk_1_2 {
+                            // HT frames are sent with 6 Mbit/s in the legacy signal field
+                            self.frame = frame;
+                            self.state = State::HtSignal1;
+                        } else {
+                            self.start_frame(frame, o, sio);
+                        }
                     } else {
                         info!(
                             "signal field could not be decoded, snr {}",
@@ -310,19 +518,62 @@ impl Kernel for FrameEqualizer {
                         self.state = State::Skip;
                     }
                 }
+                State::HtSignal1 => {
+                    if self.ht_signal(0) {
+                        i += 1;
+                        self.state = State::HtSignal2;
+                    } else {
+                        // legacy frame, process the symbol again as data
+                        let frame = self.frame.clone();
+                        self.start_frame(frame, o, sio);
+                    }
+                }
+                State::HtSignal2 => {
+                    i += 1;
+                    if !self.ht_signal(1) {
+                        info!("HT-SIG: second symbol is not QBPSK");
+                        self.state = State::Skip;
+                    } else if let Some(frame) = self.decode_ht_signal_field() {
+                        self.frame = frame;
+                        self.state = State::HtStf;
+                    } else {
+                        self.state = State::Skip;
+                    }
+                }
+                State::HtStf => {
+                    i += 1;
+                    self.state = State::HtLtf;
+                }
+                State::HtLtf => {
+                    self.equalizer.ht_ltf(&self.sym_in[0..n_fft], &self.ht_ltf);
+                    i += 1;
+                    let frame = self.frame.clone();
+                    self.start_frame(frame, o, sio);
+                }
                 State::Copy(mut n_sym, all_sym, modulation) => {
-                    if o < max_o {
-                        self.equalizer.equalize(
-                            &self.sym_in,
-                            &mut self.sym_out,
-                            (&mut out[o * 48..(o + 1) * 48]).try_into().unwrap(),
-                            *modulation,
-                        );
+                    let n_sd = self.frame.n_sd();
+                    if o + n_sd <= out.len() {
+                        if self.frame.mcs().is_ht() {
+                            self.equalizer.equalize_ht(
+                                &self.sym_in[0..n_fft],
+                                &mut self.sym_out,
+                                &mut out[o..o + n_sd],
+                                *modulation,
+                                self.bandwidth,
+                            );
+                        } else {
+                            self.equalizer.equalize(
+                                &self.sym_in[0..n_fft],
+                                &mut self.sym_out,
+                                &mut out[o..o + n_sd],
+                                *modulation,
+                            );
+                        }
 
-                        self.syms.extend_from_slice(&self.sym_out);
+                        self.syms.extend_from_slice(&self.sym_out[0..n_sd]);
 
                         i += 1;
-                        o += 1;
+                        o += n_sd;
 
                         n_sym -= 1;
                         if n_sym == 0 {
@@ -344,8 +595,8 @@ impl Kernel for FrameEqualizer {
             }
         }
 
-        sio.input(0).consume(i * 64);
-        sio.output(0).produce(o * 48);
+        sio.input(0).consume(i * n_fft);
+        sio.output(0).produce(o);
 
         if sio.input(0).finished() && i == max_i {
             io.finished = true;
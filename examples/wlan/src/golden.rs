@@ -0,0 +1,150 @@
+//! Golden samples for the receiver regression tests
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use std::path::Path;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::Fft;
+use futuresdr::blocks::FftDirection;
+use futuresdr::blocks::FileSink;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::testing::write_frames;
+
+use crate::fft_tag_propagation;
+use crate::Encoder;
+use crate::Mapper;
+use crate::Mcs;
+use crate::Prefix;
+use crate::MAX_SYM;
+
+const PAD_FRONT: usize = 5000;
+const PAD_TAIL: usize = 5000;
+
+/// MCS for which fixtures are generated
+pub const MCS: [Mcs; 16] = [
+    Mcs::Bpsk_1_2,
+    Mcs::Bpsk_3_4,
+    Mcs::Qpsk_1_2,
+    Mcs::Qpsk_3_4,
+    Mcs::Qam16_1_2,
+    Mcs::Qam16_3_4,
+    Mcs::Qam64_2_3,
+    Mcs::Qam64_3_4,
+    Mcs::HtMcs0,
+    Mcs::HtMcs1,
+    Mcs::HtMcs2,
+    Mcs::HtMcs3,
+    Mcs::HtMcs4,
+    Mcs::HtMcs5,
+    Mcs::HtMcs6,
+    Mcs::HtMcs7,
+];
+
+/// Data frame with FCS
+pub fn frame(seq: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x08, 0x00, 0x00, 0x00];
+    frame.extend_from_slice(&[0x42; 6]);
+    frame.extend_from_slice(&[0x23; 6]);
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(&(seq << 4).to_le_bytes());
+    frame.extend_from_slice(payload);
+    let crc = crc32fast::hash(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// Write samples with `frames` frames of the given MCS and noise (standard deviation) to
+/// `dir`, together with the frames that the receiver should decode, returning the name of the
+/// fixture
+pub fn generate(dir: &Path, mcs: Mcs, frames: usize, noise: f32) -> Result<String> {
+    let name = format!("wlan_{mcs:?}").to_lowercase();
+
+    let mut size = 4096;
+    let prefix_in_size = loop {
+        if size / 8 >= MAX_SYM * 64 {
+            break size;
+        }
+        size += 4096
+    };
+    let mut size = 4096;
+    let prefix_out_size = loop {
+        if size / 8 >= PAD_FRONT + std::cmp::max(PAD_TAIL, 1) + 320 + MAX_SYM * 80 {
+            break size;
+        }
+        size += 4096
+    };
+
+    let mut fg = Flowgraph::new();
+    let encoder = fg.add_block(Encoder::new(mcs));
+    let mapper = fg.add_block(Mapper::new());
+    fg.connect_stream(encoder, "out", mapper, "in")?;
+    let mut fft = Fft::with_options(
+        64,
+        FftDirection::Inverse,
+        true,
+        Some((1.0f32 / 52.0).sqrt()),
+    );
+    fft.set_tag_propagation(Box::new(fft_tag_propagation));
+    let fft = fg.add_block(fft);
+    fg.connect_stream(mapper, "out", fft, "in")?;
+    let prefix = fg.add_block(Prefix::new(PAD_FRONT, PAD_TAIL));
+    fg.connect_stream_with_type(
+        fft,
+        "out",
+        prefix,
+        "in",
+        Circular::with_size(prefix_in_size),
+    )?;
+
+    // seeded noise, so that fixtures are reproducible
+    let normal = Normal::new(0.0f32, noise)?;
+    let mut rng = StdRng::seed_from_u64(42);
+    let noise = fg.add_block(Apply::new(move |i: &Complex32| -> Complex32 {
+        let re = normal.sample(&mut rng);
+        let imag = normal.sample(&mut rng);
+        i + Complex32::new(re, imag)
+    }));
+    fg.connect_stream_with_type(
+        prefix,
+        "out",
+        noise,
+        "in",
+        Circular::with_size(prefix_out_size),
+    )?;
+    let path = dir.join(format!("{name}.cf32"));
+    let snk = fg.add_block(FileSink::<Complex32>::new(path.to_string_lossy()));
+    fg.connect_stream(noise, "out", snk, "in")?;
+
+    let frames: Vec<Vec<u8>> = (0..frames)
+        .map(|i| frame(i as u16, format!("FutureSDR {i}").as_bytes()))
+        .collect();
+
+    let rt = Runtime::new();
+    let (_fg, mut handle) = rt.start_sync(fg);
+    block_on(async {
+        for f in frames.iter() {
+            handle
+                .call(encoder, "tx", Pmt::Any(Box::new((f.clone(), Some(mcs)))))
+                .await?;
+        }
+        // give the transmitter time to process the queue
+        Timer::after(Duration::from_secs(1)).await;
+        handle.terminate_and_wait().await?;
+        Ok::<(), futuresdr::anyhow::Error>(())
+    })?;
+
+    // the receiver strips the FCS
+    let expected: Vec<Vec<u8>> = frames.iter().map(|f| f[0..f.len() - 4].to_vec()).collect();
+    write_frames(dir.join(format!("{name}.frames")), &expected)?;
+
+    Ok(name)
+}
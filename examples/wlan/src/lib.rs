@@ -18,6 +18,9 @@ pub use encoder::Encoder;
 mod frame_equalizer;
 pub use frame_equalizer::FrameEqualizer;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod golden;
+
 mod mac;
 pub use mac::Mac;
 
@@ -45,7 +48,8 @@ pub mod wasm;
 pub const MAX_PAYLOAD_SIZE: usize = 1500;
 pub const MAX_PSDU_SIZE: usize = MAX_PAYLOAD_SIZE + 28; // MAC, CRC
 pub const MAX_SYM: usize = ((16 + 8 * MAX_PSDU_SIZE + 6) / 24) + 1;
-pub const MAX_ENCODED_BITS: usize = (16 + 8 * MAX_PSDU_SIZE + 6) * 2 + 288;
+// padding of up to one symbol (HT, 40 MHz) and the traceback of the Viterbi decoder
+pub const MAX_ENCODED_BITS: usize = (16 + 8 * MAX_PSDU_SIZE + 6 + 540) * 2 + 288;
 
 #[allow(clippy::needless_pass_by_ref_mut)]
 pub fn fft_tag_propagation(inputs: &mut [StreamInput], outputs: &mut [StreamOutput]) {
@@ -229,7 +233,183 @@ impl Modulation {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+/// Channel bandwidth
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bandwidth {
+    Bw20,
+    /// HT frames use the whole channel, legacy frames are sent as non-HT duplicate, i.e., in both
+    /// 20 MHz subchannels
+    Bw40,
+}
+
+impl Bandwidth {
+    /// samples per OFDM symbol without guard interval
+    pub fn fft_size(&self) -> usize {
+        match self {
+            Bandwidth::Bw20 => 64,
+            Bandwidth::Bw40 => 128,
+        }
+    }
+
+    /// samples of the guard interval
+    pub fn guard_interval(&self) -> usize {
+        self.fft_size() / 4
+    }
+
+    pub fn sample_rate(&self) -> f64 {
+        match self {
+            Bandwidth::Bw20 => 20e6,
+            Bandwidth::Bw40 => 40e6,
+        }
+    }
+
+    /// data subcarriers of HT symbols, FFT index with DC in the center
+    pub fn ht_data_subcarriers(&self) -> &'static [usize] {
+        match self {
+            Bandwidth::Bw20 => &HT_DATA_20,
+            Bandwidth::Bw40 => &HT_DATA_40,
+        }
+    }
+
+    /// pilot subcarriers of HT symbols, FFT index with DC in the center
+    pub fn ht_pilot_subcarriers(&self) -> &'static [usize] {
+        match self {
+            Bandwidth::Bw20 => &[11, 25, 39, 53],
+            Bandwidth::Bw40 => &[11, 39, 53, 75, 89, 117],
+        }
+    }
+
+    /// pilots of the first HT data symbol, cyclically rotated by one with each symbol
+    pub fn ht_pilots(&self) -> &'static [f32] {
+        match self {
+            Bandwidth::Bw20 => &[1.0, 1.0, 1.0, -1.0],
+            Bandwidth::Bw40 => &[1.0, 1.0, 1.0, -1.0, -1.0, 1.0],
+        }
+    }
+
+    /// HT long training field, FFT index with DC in the center
+    pub fn ht_ltf(&self) -> Vec<Complex32> {
+        let one = Complex32::new(1.0, 0.0);
+        match self {
+            Bandwidth::Bw20 => {
+                let mut ltf = LONG.to_vec();
+                ltf[4] = one;
+                ltf[5] = one;
+                ltf[59] = -one;
+                ltf[60] = -one;
+                ltf
+            }
+            Bandwidth::Bw40 => {
+                // legacy LTF in both subchannels, filling their DC and the gap in between
+                let mut ltf = [LONG, LONG].concat();
+                ltf[32] = one;
+                ltf[96] = one;
+                for (i, v) in [59, 60, 61, 62, 66, 67, 68, 69]
+                    .iter()
+                    .zip([-1.0, -1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0])
+                {
+                    ltf[*i] = Complex32::new(v, 0.0);
+                }
+                ltf
+            }
+        }
+    }
+
+    /// legacy field in all 20 MHz subchannels, with the upper one rotated by 90°
+    pub fn legacy_duplicate(&self, field: &[Complex32; 64]) -> Vec<Complex32> {
+        match self {
+            Bandwidth::Bw20 => field.to_vec(),
+            Bandwidth::Bw40 => field
+                .iter()
+                .copied()
+                .chain(field.iter().map(|s| s * Complex32::new(0.0, 1.0)))
+                .collect(),
+        }
+    }
+
+    pub fn parse(s: &str) -> Result<Bandwidth, String> {
+        let mut b = s.to_string().replace([' ', '_'], "");
+        b.make_ascii_lowercase();
+        match b.trim_end_matches("mhz") {
+            "20" => Ok(Bandwidth::Bw20),
+            "40" => Ok(Bandwidth::Bw40),
+            _ => Err(format!("Invalid bandwidth {s}")),
+        }
+    }
+}
+
+/// Short training field, FFT index with DC in the center
+pub fn short_training_field() -> [Complex32; 64] {
+    let mut stf = [Complex32::new(0.0, 0.0); 64];
+    for (i, s) in [8, 12, 16, 20, 24, 28, 36, 40, 44, 48, 52, 56].iter().zip([
+        1.0, -1.0, 1.0, -1.0, -1.0, 1.0, -1.0, -1.0, 1.0, 1.0, 1.0, 1.0,
+    ]) {
+        stf[*i] = Complex32::new(s, s) * (13.0f32 / 6.0).sqrt();
+    }
+    stf
+}
+
+/// Sample `n` of the periodic time-domain signal of the subcarriers `freq` (FFT index with DC in
+/// the center), with the power of a legacy symbol
+pub fn time_domain(freq: &[Complex32], n: isize) -> Complex32 {
+    let len = freq.len() as isize;
+    let scale = 1.0 / (52.0 * freq.len() as f64 / 64.0).sqrt();
+    let (re, im) = freq
+        .iter()
+        .enumerate()
+        .fold((0.0, 0.0), |(re, im), (i, f)| {
+            let k = i as isize - len / 2;
+            let phase = 2.0 * std::f64::consts::PI * (k * n).rem_euclid(len) as f64 / len as f64;
+            let (sin, cos) = phase.sin_cos();
+            (
+                re + f.re as f64 * cos - f.im as f64 * sin,
+                im + f.re as f64 * sin + f.im as f64 * cos,
+            )
+        });
+    Complex32::new((re * scale) as f32, (im * scale) as f32)
+}
+
+/// CRC-8 of the HT-SIG field (x^8 + x^2 + x + 1), transmitted MSB first
+///
+/// The shift register is initialized with ones and the result is inverted. The A-MPDU delimiter
+/// uses the same CRC.
+pub fn ht_sig_crc(bits: &[u8]) -> u8 {
+    let mut crc = 0xffu8;
+    for b in bits {
+        let feedback = (crc >> 7) ^ (b & 1);
+        crc <<= 1;
+        if feedback > 0 {
+            crc ^= 0x07;
+        }
+    }
+    !crc
+}
+
+/// Aggregate MPDUs, including their FCS, into an A-MPDU
+///
+/// Each MPDU is preceded by a 4-byte delimiter with its length, the CRC-8 of the first two
+/// delimiter bytes, and the signature 0x4E. All but the last subframe are padded to a multiple
+/// of 4 bytes. MPDUs have to be shorter than 4096 bytes.
+pub fn aggregate(mpdus: &[Vec<u8>]) -> Vec<u8> {
+    let mut ampdu = Vec::new();
+    for (i, mpdu) in mpdus.iter().enumerate() {
+        assert!(mpdu.len() < 4096, "MPDU too large for an A-MPDU");
+        let length = ((mpdu.len() as u16) << 4).to_le_bytes();
+        let bits: Vec<u8> = (0..16).map(|k| (length[k / 8] >> (k % 8)) & 1).collect();
+        ampdu.extend_from_slice(&length);
+        // the MSB of the CRC is transmitted first, i.e., it is the LSB of the byte
+        ampdu.push(ht_sig_crc(&bits).reverse_bits());
+        ampdu.push(0x4e);
+        ampdu.extend_from_slice(mpdu);
+        if i + 1 < mpdus.len() {
+            ampdu.resize((ampdu.len() + 3) & !3, 0);
+        }
+    }
+    ampdu
+}
+
+/// Legacy (802.11a/g) rates and HT (802.11n) MCS 0-7 with a single spatial stream
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[allow(non_camel_case_types)]
 pub enum Mcs {
     Bpsk_1_2,
@@ -240,14 +420,25 @@ pub enum Mcs {
     Qam16_3_4,
     Qam64_2_3,
     Qam64_3_4,
+    HtMcs0,
+    HtMcs1,
+    HtMcs2,
+    HtMcs3,
+    HtMcs4,
+    HtMcs5,
+    HtMcs6,
+    HtMcs7,
 }
 
 impl Mcs {
     pub fn depuncture_pattern(&self) -> &'static [usize] {
         match self {
             Mcs::Bpsk_1_2 | Mcs::Qpsk_1_2 | Mcs::Qam16_1_2 => &[1, 1],
+            Mcs::HtMcs0 | Mcs::HtMcs1 | Mcs::HtMcs3 => &[1, 1],
             Mcs::Bpsk_3_4 | Mcs::Qpsk_3_4 | Mcs::Qam16_3_4 | Mcs::Qam64_3_4 => &[1, 1, 1, 0, 0, 1],
-            Mcs::Qam64_2_3 => &[1, 1, 1, 0],
+            Mcs::HtMcs2 | Mcs::HtMcs4 | Mcs::HtMcs6 => &[1, 1, 1, 0, 0, 1],
+            Mcs::Qam64_2_3 | Mcs::HtMcs5 => &[1, 1, 1, 0],
+            Mcs::HtMcs7 => &[1, 1, 1, 0, 0, 1, 1, 0, 0, 1],
         }
     }
 
@@ -261,10 +452,14 @@ impl Mcs {
             Mcs::Qam16_3_4 => Modulation::Qam16,
             Mcs::Qam64_2_3 => Modulation::Qam64,
             Mcs::Qam64_3_4 => Modulation::Qam64,
+            Mcs::HtMcs0 => Modulation::Bpsk,
+            Mcs::HtMcs1 | Mcs::HtMcs2 => Modulation::Qpsk,
+            Mcs::HtMcs3 | Mcs::HtMcs4 => Modulation::Qam16,
+            Mcs::HtMcs5 | Mcs::HtMcs6 | Mcs::HtMcs7 => Modulation::Qam64,
         }
     }
 
-    // coded bits per symbol
+    // coded bits per symbol (20 MHz)
     pub fn n_cbps(&self) -> usize {
         match self {
             Mcs::Bpsk_1_2 => 48,
@@ -275,10 +470,12 @@ impl Mcs {
             Mcs::Qam16_3_4 => 192,
             Mcs::Qam64_2_3 => 288,
             Mcs::Qam64_3_4 => 288,
+            // 52 data subcarriers
+            _ => 52 * self.modulation().n_bpsc(),
         }
     }
 
-    // data bits per symbol
+    // data bits per symbol (20 MHz)
     pub fn n_dbps(&self) -> usize {
         match self {
             Mcs::Bpsk_1_2 => 24,
@@ -289,9 +486,17 @@ impl Mcs {
             Mcs::Qam16_3_4 => 144,
             Mcs::Qam64_2_3 => 192,
             Mcs::Qam64_3_4 => 216,
+            Mcs::HtMcs0 => 26,
+            Mcs::HtMcs1 => 52,
+            Mcs::HtMcs2 => 78,
+            Mcs::HtMcs3 => 104,
+            Mcs::HtMcs4 => 156,
+            Mcs::HtMcs5 => 208,
+            Mcs::HtMcs6 => 234,
+            Mcs::HtMcs7 => 260,
         }
     }
-    // rate field for signal field
+    // rate field for signal field, HT frames announce 6 Mbit/s in the legacy signal field
     pub fn rate_field(&self) -> u8 {
         match self {
             Mcs::Bpsk_1_2 => 0x0d,
@@ -302,6 +507,40 @@ impl Mcs {
             Mcs::Qam16_3_4 => 0x0b,
             Mcs::Qam64_2_3 => 0x01,
             Mcs::Qam64_3_4 => 0x03,
+            _ => 0x0d,
+        }
+    }
+
+    pub fn is_ht(&self) -> bool {
+        self.ht_index().is_some()
+    }
+
+    /// MCS index of the HT-SIG field
+    pub fn ht_index(&self) -> Option<usize> {
+        match self {
+            Mcs::HtMcs0 => Some(0),
+            Mcs::HtMcs1 => Some(1),
+            Mcs::HtMcs2 => Some(2),
+            Mcs::HtMcs3 => Some(3),
+            Mcs::HtMcs4 => Some(4),
+            Mcs::HtMcs5 => Some(5),
+            Mcs::HtMcs6 => Some(6),
+            Mcs::HtMcs7 => Some(7),
+            _ => None,
+        }
+    }
+
+    pub fn from_ht_index(index: usize) -> Option<Mcs> {
+        match index {
+            0 => Some(Mcs::HtMcs0),
+            1 => Some(Mcs::HtMcs1),
+            2 => Some(Mcs::HtMcs2),
+            3 => Some(Mcs::HtMcs3),
+            4 => Some(Mcs::HtMcs4),
+            5 => Some(Mcs::HtMcs5),
+            6 => Some(Mcs::HtMcs6),
+            7 => Some(Mcs::HtMcs7),
+            _ => None,
         }
     }
 
@@ -317,7 +556,13 @@ impl Mcs {
             "qam1634" => Ok(Mcs::Qam16_3_4),
             "qam6423" => Ok(Mcs::Qam64_2_3),
             "qam6434" => Ok(Mcs::Qam64_3_4),
-            _ => Err(format!("Invalid MCS {s}")),
+            _ => m
+                .strip_prefix("htmcs")
+                .or_else(|| m.strip_prefix("ht"))
+                .or_else(|| m.strip_prefix("mcs"))
+                .and_then(|i| i.parse().ok())
+                .and_then(Mcs::from_ht_index)
+                .ok_or_else(|| format!("Invalid MCS {s}")),
         }
     }
 }
@@ -325,7 +570,11 @@ impl Mcs {
 #[derive(Clone, Debug)]
 pub struct FrameParam {
     mcs: Mcs,
+    bandwidth: Bandwidth,
+    aggregation: bool,
     psdu_size: usize,
+    n_cbps: usize,
+    n_dbps: usize,
     n_data_bits: usize,
     n_symbols: usize,
     n_pad: usize,
@@ -333,20 +582,35 @@ pub struct FrameParam {
 
 impl FrameParam {
     pub fn new(mcs: Mcs, psdu_size: usize) -> Self {
+        Self::with_bandwidth(mcs, psdu_size, Bandwidth::Bw20)
+    }
+
+    pub fn with_bandwidth(mcs: Mcs, psdu_size: usize, bandwidth: Bandwidth) -> Self {
+        // HT40 has 108 instead of 52 data subcarriers
+        let (n_cbps, n_dbps) = if mcs.is_ht() && bandwidth == Bandwidth::Bw40 {
+            (mcs.n_cbps() * 108 / 52, mcs.n_dbps() * 108 / 52)
+        } else {
+            (mcs.n_cbps(), mcs.n_dbps())
+        };
+
         // n_symbols
         let bits = 16 + 8 * psdu_size + 6;
-        let mut n_symbols = bits / mcs.n_dbps();
-        if bits % mcs.n_dbps() > 0 {
+        let mut n_symbols = bits / n_dbps;
+        if bits % n_dbps > 0 {
             n_symbols += 1;
         }
 
         // n_pad
-        let n_data_bits = n_symbols * mcs.n_dbps();
+        let n_data_bits = n_symbols * n_dbps;
         let n_pad = n_data_bits - (16 + 8 * psdu_size + 6);
 
         FrameParam {
             mcs,
+            bandwidth,
+            aggregation: false,
             psdu_size,
+            n_cbps,
+            n_dbps,
             n_data_bits,
             n_symbols,
             n_pad,
@@ -360,6 +624,43 @@ impl FrameParam {
         self.mcs
     }
 
+    pub fn bandwidth(&self) -> Bandwidth {
+        self.bandwidth
+    }
+
+    /// whether the PSDU is an A-MPDU, as signaled in the HT-SIG field
+    pub fn aggregation(&self) -> bool {
+        self.aggregation
+    }
+
+    pub fn set_aggregation(&mut self, aggregation: bool) {
+        self.aggregation = aggregation;
+    }
+
+    // coded bits per symbol
+    pub fn n_cbps(&self) -> usize {
+        self.n_cbps
+    }
+
+    // data bits per symbol
+    pub fn n_dbps(&self) -> usize {
+        self.n_dbps
+    }
+
+    // data subcarriers per symbol
+    pub fn n_sd(&self) -> usize {
+        self.n_cbps / self.mcs.modulation().n_bpsc()
+    }
+
+    // columns of the interleaver
+    pub fn n_col(&self) -> usize {
+        match (self.mcs.is_ht(), self.bandwidth) {
+            (false, _) => 16,
+            (true, Bandwidth::Bw20) => 13,
+            (true, Bandwidth::Bw40) => 18,
+        }
+    }
+
     pub fn n_data_bits(&self) -> usize {
         self.n_data_bits
     }
@@ -373,6 +674,20 @@ impl FrameParam {
     }
 }
 
+const HT_DATA_20: [usize; 52] = [
+    4, 5, 6, 7, 8, 9, 10, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 26, 27, 28, 29, 30,
+    31, 33, 34, 35, 36, 37, 38, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 54, 55, 56, 57,
+    58, 59, 60,
+];
+
+const HT_DATA_40: [usize; 108] = [
+    6, 7, 8, 9, 10, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
+    32, 33, 34, 35, 36, 37, 38, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 54, 55, 56, 57,
+    58, 59, 60, 61, 62, 66, 67, 68, 69, 70, 71, 72, 73, 74, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85,
+    86, 87, 88, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107,
+    108, 109, 110, 111, 112, 113, 114, 115, 116, 118, 119, 120, 121, 122,
+];
+
 pub const POLARITY: [Complex32; 127] = [
     Complex32::new(1.0, 0.0),
     Complex32::new(1.0, 0.0),
@@ -13,25 +13,73 @@ use futuresdr::runtime::Kernel;
 use futuresdr::runtime::MessageIo;
 use futuresdr::runtime::MessageIoBuilder;
 use futuresdr::runtime::Pmt;
+use futuresdr::runtime::StreamIo;
 use futuresdr::runtime::StreamIoBuilder;
 use futuresdr::runtime::WorkIo;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::time::Duration;
+use std::time::Instant;
 
+/// Maximum number of frames to queue while waiting for an ACK
+const MAX_FRAMES: usize = 1000;
+
+/// Frame that waits for an ACK
+struct Pending {
+    frame: Vec<u8>,
+    mcs: Option<Mcs>,
+    retries: usize,
+    deadline: Instant,
+}
+
+/// WLAN MAC
+///
+/// Data frames from the `tx` input are framed and posted to the `tx` output. Frames received
+/// from the decoder on the `rx` input are forwarded to the `rx` output if they are data frames
+/// addressed to this station or to a group address. Duplicates of retransmitted frames are
+/// dropped.
+///
+/// Unicast data frames for this station are acknowledged. The ACK is sent as soon as the frame
+/// is received, since SDR latencies do not allow meeting the SIFS.
+///
+/// With a retry limit, unicast frames are sent stop-and-wait: each frame is retransmitted until
+/// it is acknowledged or the retry limit is reached before the next frame is sent.
+/// Retransmissions are not supported on wasm.
 pub struct Mac {
     current_frame: [u8; MAX_PSDU_SIZE],
     sequence_number: u16,
+    address: [u8; 6],
+    retry_limit: usize,
+    ack_timeout: Duration,
+    pending: Option<Pending>,
+    tx_frames: VecDeque<(Vec<u8>, Option<Mcs>)>,
+    rx_sequence_numbers: HashMap<[u8; 6], u16>,
 }
 
 impl Mac {
+    /// Create MAC without retransmissions
     pub fn new(src_mac: [u8; 6], dst_mac: [u8; 6], bss_mac: [u8; 6]) -> Block {
+        Self::with_options(src_mac, dst_mac, bss_mac, 0, Duration::from_millis(100))
+    }
+
+    /// Create MAC that retransmits unicast frames up to `retry_limit` times, if they are not
+    /// acknowledged within `ack_timeout`
+    pub fn with_options(
+        src_mac: [u8; 6],
+        dst_mac: [u8; 6],
+        bss_mac: [u8; 6],
+        retry_limit: usize,
+        ack_timeout: Duration,
+    ) -> Block {
         let mut current_frame = [0; MAX_PSDU_SIZE];
 
         // frame control
         current_frame[0..2].copy_from_slice(&0x0008u16.to_le_bytes());
         // duration
         current_frame[2..4].copy_from_slice(&0x0000u16.to_le_bytes());
-        // mac addresses
-        current_frame[4..10].copy_from_slice(&src_mac);
-        current_frame[10..16].copy_from_slice(&dst_mac);
+        // mac addresses (receiver, transmitter, bss)
+        current_frame[4..10].copy_from_slice(&dst_mac);
+        current_frame[10..16].copy_from_slice(&src_mac);
         current_frame[16..22].copy_from_slice(&bss_mac);
 
         Block::new(
@@ -39,11 +87,19 @@ impl Mac {
             StreamIoBuilder::new().build(),
             MessageIoBuilder::new()
                 .add_input("tx", Self::transmit)
+                .add_input("rx", Self::receive)
                 .add_output("tx")
+                .add_output("rx")
                 .build(),
             Mac {
                 current_frame,
                 sequence_number: 0,
+                address: src_mac,
+                retry_limit,
+                ack_timeout,
+                pending: None,
+                tx_frames: VecDeque::new(),
+                rx_sequence_numbers: HashMap::new(),
             },
         )
     }
@@ -58,39 +114,11 @@ impl Mac {
     ) -> Result<Pmt> {
         match p {
             Pmt::Blob(data) => {
-                if data.len() > MAX_PAYLOAD_SIZE {
-                    warn!(
-                        "WLAN Mac: TX frame too large ({}, max {}). Dropping.",
-                        data.len(),
-                        MAX_PAYLOAD_SIZE
-                    );
-                } else {
-                    let len = self.generate_mac_data_frame(&data);
-                    debug!("mac frame {:?}", &self.current_frame[0..len]);
-                    let mut vec = vec![0; len];
-                    vec.copy_from_slice(&self.current_frame[0..len]);
-                    mio.output_mut(0)
-                        .post(Pmt::Any(Box::new((vec, None as Option<Mcs>))))
-                        .await;
-                }
+                self.enqueue(mio, &data, None).await;
             }
             Pmt::Any(a) => {
                 if let Some((data, mcs)) = a.downcast_ref::<(Vec<u8>, Mcs)>() {
-                    if data.len() > MAX_PAYLOAD_SIZE {
-                        warn!(
-                            "WLAN Mac: TX frame too large ({}, max {}). Dropping.",
-                            data.len(),
-                            MAX_PAYLOAD_SIZE
-                        );
-                    } else {
-                        let len = self.generate_mac_data_frame(data);
-                        debug!("mac frame {:?}", &self.current_frame[0..len]);
-                        let mut vec = vec![0; len];
-                        vec.copy_from_slice(&self.current_frame[0..len]);
-                        mio.output_mut(0)
-                            .post(Pmt::Any(Box::new((vec, Some(*mcs)))))
-                            .await;
-                    }
+                    self.enqueue(mio, data, Some(*mcs)).await;
                 }
             }
             Pmt::Finished => {
@@ -103,6 +131,152 @@ impl Mac {
         Ok(Pmt::Null)
     }
 
+    #[message_handler]
+    async fn receive(
+        &mut self,
+        _io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let frame = match p {
+            Pmt::Blob(frame) => frame,
+            Pmt::Null | Pmt::Finished => return Ok(Pmt::Null),
+            x => {
+                warn!("WLAN Mac: received wrong PMT type in RX callback. {:?}", x);
+                return Ok(Pmt::Null);
+            }
+        };
+
+        if frame.len() < 10 || (frame[4..10] != self.address && frame[4] & 0x01 == 0) {
+            return Ok(Pmt::Null);
+        }
+
+        // ack
+        if frame[0] == 0xd4 {
+            if self.pending.take().is_some() {
+                self.send_next(mio).await;
+            }
+            return Ok(Pmt::Null);
+        }
+
+        // data
+        if frame[0] & 0x0c != 0x08 || frame.len() < 24 {
+            return Ok(Pmt::Null);
+        }
+
+        let mut transmitter = [0; 6];
+        transmitter.copy_from_slice(&frame[10..16]);
+        let unicast = frame[4] & 0x01 == 0;
+
+        if unicast {
+            self.send_ack(mio, &transmitter).await;
+        }
+
+        let sequence_number = u16::from_le_bytes([frame[22], frame[23]]) >> 4;
+        let retry = frame[1] & 0x08 != 0;
+        let last = self
+            .rx_sequence_numbers
+            .insert(transmitter, sequence_number);
+        if retry && last == Some(sequence_number) {
+            debug!("WLAN Mac: dropping duplicate frame {}", sequence_number);
+            return Ok(Pmt::Null);
+        }
+
+        mio.output_mut(1).post(Pmt::Blob(frame)).await;
+        Ok(Pmt::Null)
+    }
+
+    async fn enqueue(&mut self, mio: &mut MessageIo<Self>, data: &[u8], mcs: Option<Mcs>) {
+        if data.len() > MAX_PAYLOAD_SIZE {
+            warn!(
+                "WLAN Mac: TX frame too large ({}, max {}). Dropping.",
+                data.len(),
+                MAX_PAYLOAD_SIZE
+            );
+            return;
+        }
+
+        let len = self.generate_mac_data_frame(data);
+        debug!("mac frame {:?}", &self.current_frame[0..len]);
+        let mut vec = vec![0; len];
+        vec.copy_from_slice(&self.current_frame[0..len]);
+
+        if self.retry_limit == 0
+            || cfg!(target_arch = "wasm32")
+            || self.current_frame[4] & 0x01 != 0
+        {
+            mio.output_mut(0).post(Pmt::Any(Box::new((vec, mcs)))).await;
+        } else if self.tx_frames.len() >= MAX_FRAMES {
+            warn!(
+                "WLAN Mac: max number of frames already in TX queue ({}). Dropping.",
+                MAX_FRAMES
+            );
+        } else {
+            self.tx_frames.push_back((vec, mcs));
+            if self.pending.is_none() {
+                self.send_next(mio).await;
+            }
+        }
+    }
+
+    async fn send_next(&mut self, mio: &mut MessageIo<Self>) {
+        if let Some((frame, mcs)) = self.tx_frames.pop_front() {
+            mio.output_mut(0)
+                .post(Pmt::Any(Box::new((frame.clone(), mcs))))
+                .await;
+            self.pending = Some(Pending {
+                frame,
+                mcs,
+                retries: 0,
+                deadline: Instant::now() + self.ack_timeout,
+            });
+        }
+    }
+
+    async fn retransmit(&mut self, mio: &mut MessageIo<Self>) {
+        let mut p = match self.pending.take() {
+            Some(p) => p,
+            None => return,
+        };
+
+        if p.retries >= self.retry_limit {
+            warn!(
+                "WLAN Mac: frame not acknowledged after {} retries. Dropping.",
+                p.retries
+            );
+            self.send_next(mio).await;
+            return;
+        }
+
+        // set retry flag and update crc
+        let len = p.frame.len() - 4;
+        p.frame[1] |= 0x08;
+        let crc = crc32fast::hash(&p.frame[0..len]);
+        p.frame[len..].copy_from_slice(&crc.to_le_bytes());
+
+        mio.output_mut(0)
+            .post(Pmt::Any(Box::new((p.frame.clone(), p.mcs))))
+            .await;
+        p.retries += 1;
+        p.deadline = Instant::now() + self.ack_timeout;
+        self.pending = Some(p);
+    }
+
+    async fn send_ack(&mut self, mio: &mut MessageIo<Self>, receiver: &[u8; 6]) {
+        let mut ack = vec![0; 14];
+        // frame control
+        ack[0..2].copy_from_slice(&0x00d4u16.to_le_bytes());
+        // receiver address
+        ack[4..10].copy_from_slice(receiver);
+        let crc = crc32fast::hash(&ack[0..10]);
+        ack[10..14].copy_from_slice(&crc.to_le_bytes());
+
+        mio.output_mut(0)
+            .post(Pmt::Any(Box::new((ack, Some(Mcs::Bpsk_1_2)))))
+            .await;
+    }
+
     fn generate_mac_data_frame(&mut self, data: &[u8]) -> usize {
         self.current_frame[22..24].copy_from_slice(&(self.sequence_number << 4).to_le_bytes());
         self.sequence_number = (self.sequence_number + 1) % (1 << 12);
@@ -125,4 +299,27 @@ impl Mac {
 }
 
 #[async_trait]
-impl Kernel for Mac {}
+impl Kernel for Mac {
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if matches!(&self.pending, Some(p) if p.deadline <= Instant::now()) {
+            self.retransmit(mio).await;
+        }
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(p) = &self.pending {
+            let deadline = p.deadline;
+            io.block_on(async move {
+                futuresdr::async_io::Timer::at(deadline).await;
+            });
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,6 @@
+use crate::Bandwidth;
 use crate::FrameParam;
+use crate::Mcs;
 use crate::Modulation;
 use crate::POLARITY;
 
@@ -17,11 +19,23 @@ use futuresdr::runtime::StreamIoBuilder;
 use futuresdr::runtime::Tag;
 use futuresdr::runtime::WorkIo;
 
+const INTERLEAVER_PATTERN: [usize; 48] = [
+    0, 3, 6, 9, 12, 15, 18, 21, 24, 27, 30, 33, 36, 39, 42, 45, 1, 4, 7, 10, 13, 16, 19, 22, 25,
+    28, 31, 34, 37, 40, 43, 46, 2, 5, 8, 11, 14, 17, 20, 23, 26, 29, 32, 35, 38, 41, 44, 47,
+];
+
+/// Map encoded symbols to subcarriers and add the signal field.
+///
+/// HT frames get an HT-SIG, HT-STF, and HT-LTF after the legacy signal field. For 40 MHz frames,
+/// the output has 128 subcarriers, with legacy symbols in both subchannels.
 pub struct Mapper {
     signal: [u8; 24],
     signal_encoded: [u8; 48],
     signal_interleaved: [u8; 48],
-    current_mod: Modulation,
+    ht_signal: [u8; 48],
+    ht_signal_encoded: [u8; 96],
+    ht_signal_interleaved: [u8; 96],
+    current_frame: FrameParam,
     current_len: usize,
     index: usize,
 }
@@ -39,7 +53,10 @@ impl Mapper {
                 signal: [0; 24],
                 signal_encoded: [0; 48],
                 signal_interleaved: [0; 48],
-                current_mod: Modulation::Bpsk,
+                ht_signal: [0; 48],
+                ht_signal_encoded: [0; 96],
+                ht_signal_interleaved: [0; 96],
+                current_frame: FrameParam::new(Mcs::Bpsk_1_2, 0),
                 current_len: 0,
                 index: 0,
             },
@@ -56,8 +73,12 @@ impl Mapper {
     }
 
     fn generate_signal_field(&mut self, frame: &FrameParam) {
-        let length = frame.psdu_size();
-        let rate = frame.mcs().rate_field();
+        let (length, rate) = if frame.mcs().is_ht() {
+            // duration of HT-SIG, HT-STF, HT-LTF, and data at 6 Mbit/s
+            (3 * (frame.n_symbols() + 3), Mcs::Bpsk_1_2.rate_field())
+        } else {
+            (frame.psdu_size(), frame.mcs().rate_field())
+        };
 
         // first 4 bits represent the modulation and coding scheme
         self.signal[0] = Self::get_bit(rate, 3);
@@ -83,23 +104,92 @@ impl Mapper {
         let sum: u8 = self.signal[0..17].iter().sum();
         self.signal[17] = sum % 2;
 
-        // encode
+        Self::encode_signal(
+            &self.signal,
+            &mut self.signal_encoded,
+            &mut self.signal_interleaved,
+        );
+    }
+
+    fn generate_ht_signal_field(&mut self, frame: &FrameParam) {
+        let mcs = frame.mcs().ht_index().unwrap();
+        let length = frame.psdu_size();
+
+        // HT-SIG1: 7 bits MCS, bandwidth, and 16 bits length
+        for i in 0..7 {
+            self.ht_signal[i] = Self::get_bit_usize(mcs, i);
+        }
+        self.ht_signal[7] = u8::from(frame.bandwidth() == Bandwidth::Bw40);
+        for i in 0..16 {
+            self.ht_signal[8 + i] = Self::get_bit_usize(length, i);
+        }
+        // HT-SIG2: smoothing, not sounding, and reserved are set
+        self.ht_signal[24..27].fill(1);
+        self.ht_signal[27] = u8::from(frame.aggregation());
+        // no STBC, LDPC, short GI, or extension spatial streams
+        self.ht_signal[28..34].fill(0);
+        let crc = crate::ht_sig_crc(&self.ht_signal[0..34]);
+        for i in 0..8 {
+            self.ht_signal[34 + i] = Self::get_bit(crc, 7 - i);
+        }
+        // tail
+        self.ht_signal[42..48].fill(0);
+
+        Self::encode_signal(
+            &self.ht_signal,
+            &mut self.ht_signal_encoded,
+            &mut self.ht_signal_interleaved,
+        );
+    }
+
+    /// BPSK 1/2, interleaved per symbol
+    fn encode_signal(bits: &[u8], encoded: &mut [u8], interleaved: &mut [u8]) {
         let mut state = 0;
-        for i in 0..24 {
-            state = ((state << 1) & 0x7e) | self.signal[i];
-            self.signal_encoded[i * 2] = (state & 0o155).count_ones() as u8 % 2;
-            self.signal_encoded[i * 2 + 1] = (state & 0o117).count_ones() as u8 % 2;
+        for i in 0..bits.len() {
+            state = ((state << 1) & 0x7e) | bits[i];
+            encoded[i * 2] = (state & 0o155).count_ones() as u8 % 2;
+            encoded[i * 2 + 1] = (state & 0o117).count_ones() as u8 % 2;
         }
 
-        // interleave
-        const INTERLEAVER_PATTERN: [usize; 48] = [
-            0, 3, 6, 9, 12, 15, 18, 21, 24, 27, 30, 33, 36, 39, 42, 45, 1, 4, 7, 10, 13, 16, 19,
-            22, 25, 28, 31, 34, 37, 40, 43, 46, 2, 5, 8, 11, 14, 17, 20, 23, 26, 29, 32, 35, 38,
-            41, 44, 47,
-        ];
+        for (s, symbol) in encoded.chunks(48).enumerate() {
+            for i in 0..48 {
+                interleaved[s * 48 + INTERLEAVER_PATTERN[i]] = symbol[i];
+            }
+        }
+    }
+
+    /// legacy signal field and, for HT frames, HT-SIG, HT-STF, and HT-LTF
+    fn header(&mut self, frame: &FrameParam, output: &mut [Complex32]) {
+        let bandwidth = frame.bandwidth();
+        let n = bandwidth.fft_size();
+        let mut symbol = [Complex32::new(0.0, 0.0); 64];
 
-        for i in 0..48 {
-            self.signal_interleaved[INTERLEAVER_PATTERN[i]] = self.signal_encoded[i];
+        self.generate_signal_field(frame);
+        Self::map(&self.signal_interleaved, &mut symbol, Modulation::Bpsk, 0);
+        Self::legacy(&symbol, &mut output[0..n], bandwidth);
+
+        if frame.mcs().is_ht() {
+            self.generate_ht_signal_field(frame);
+            for i in 0..2 {
+                Self::map_ht_signal(
+                    (&self.ht_signal_interleaved[i * 48..(i + 1) * 48])
+                        .try_into()
+                        .unwrap(),
+                    &mut symbol,
+                    i + 1,
+                );
+                Self::legacy(&symbol, &mut output[(i + 1) * n..(i + 2) * n], bandwidth);
+            }
+
+            Self::legacy(
+                &crate::short_training_field(),
+                &mut output[3 * n..4 * n],
+                bandwidth,
+            );
+
+            let ltf = &mut output[4 * n..5 * n];
+            ltf.copy_from_slice(&bandwidth.ht_ltf());
+            Self::ht_scale(ltf, bandwidth);
         }
     }
 
@@ -112,9 +202,9 @@ impl Mapper {
         }
         // pilots
         for i in [11, 25, 39] {
-            output[i] = POLARITY[index];
+            output[i] = POLARITY[index % 127];
         }
-        output[53] = -POLARITY[index];
+        output[53] = -POLARITY[index % 127];
         // data
         for (i, c) in (6..11)
             .chain(12..25)
@@ -128,6 +218,86 @@ impl Mapper {
             output[c] = modulation.map(input[i]);
         }
     }
+
+    /// BPSK with the data subcarriers rotated by 90°
+    fn map_ht_signal(input: &[u8; 48], output: &mut [Complex32; 64], index: usize) {
+        Self::map(input, output, Modulation::Bpsk, index);
+        for (i, s) in output.iter_mut().enumerate() {
+            if ![11, 25, 39, 53].contains(&i) {
+                *s *= Complex32::new(0.0, 1.0);
+            }
+        }
+    }
+
+    /// 40 MHz duplicates legacy symbols, with the upper subchannel rotated by 90°
+    fn legacy(symbol: &[Complex32; 64], output: &mut [Complex32], bandwidth: Bandwidth) {
+        match bandwidth {
+            Bandwidth::Bw20 => output.copy_from_slice(symbol),
+            Bandwidth::Bw40 => {
+                output[0..64].copy_from_slice(symbol);
+                for (o, s) in output[64..128].iter_mut().zip(symbol) {
+                    *o = s * Complex32::new(0.0, 1.0);
+                }
+            }
+        }
+    }
+
+    /// HT data symbol `n`
+    fn map_ht(
+        input: &[u8],
+        output: &mut [Complex32],
+        modulation: Modulation,
+        bandwidth: Bandwidth,
+        n: usize,
+    ) {
+        output.fill(Complex32::new(0.0, 0.0));
+        // pilots
+        let p = POLARITY[(n + 3) % 127];
+        let pilots = bandwidth.ht_pilots();
+        for (m, c) in bandwidth.ht_pilot_subcarriers().iter().enumerate() {
+            output[*c] = p * pilots[(n + m) % pilots.len()];
+        }
+        // data
+        for (i, c) in bandwidth.ht_data_subcarriers().iter().enumerate() {
+            output[*c] = modulation.map(input[i]);
+        }
+        Self::ht_scale(output, bandwidth);
+    }
+
+    /// HT symbols use more subcarriers, scale them to the power of the legacy symbols and, for
+    /// 40 MHz, rotate the upper subchannel by 90°
+    fn ht_scale(output: &mut [Complex32], bandwidth: Bandwidth) {
+        match bandwidth {
+            Bandwidth::Bw20 => {
+                let scale = (52.0f32 / 56.0).sqrt();
+                output.iter_mut().for_each(|s| *s *= scale);
+            }
+            Bandwidth::Bw40 => {
+                let scale = (104.0f32 / 114.0).sqrt();
+                output[0..65].iter_mut().for_each(|s| *s *= scale);
+                output[65..128]
+                    .iter_mut()
+                    .for_each(|s| *s *= Complex32::new(0.0, scale));
+            }
+        }
+    }
+
+    fn map_data(&self, input: &[u8], output: &mut [Complex32]) {
+        let frame = &self.current_frame;
+        let modulation = frame.mcs().modulation();
+        if frame.mcs().is_ht() {
+            Self::map_ht(input, output, modulation, frame.bandwidth(), self.index - 1);
+        } else {
+            let mut symbol = [Complex32::new(0.0, 0.0); 64];
+            Self::map(
+                input.try_into().unwrap(),
+                &mut symbol,
+                modulation,
+                self.index,
+            );
+            Self::legacy(&symbol, output, frame.bandwidth());
+        }
+    }
 }
 
 #[async_trait]
@@ -165,47 +335,44 @@ impl Kernel for Mapper {
             _ => None,
         }) {
             if *index == 0 {
-                self.generate_signal_field(frame);
-                Self::map(
-                    &self.signal_interleaved,
-                    (&mut output[0..64]).try_into().unwrap(),
-                    Modulation::Bpsk,
-                    0,
-                );
-                o += 1;
+                let n_fft = frame.bandwidth().fft_size();
+                let header = if frame.mcs().is_ht() { 5 } else { 1 };
+                if output.len() < (header + 1) * n_fft {
+                    return Ok(());
+                }
+                self.header(frame, &mut output[0..header * n_fft]);
+                o += header;
                 sio.output(0).add_tag(
                     0,
-                    Tag::NamedUsize("wifi_start".to_string(), frame.n_symbols() + 1),
+                    Tag::NamedUsize("wifi_start".to_string(), frame.n_symbols() + header),
                 );
                 assert_eq!(self.index, self.current_len);
-                self.current_mod = frame.mcs().modulation();
+                self.current_frame = frame.clone();
                 self.current_len = frame.n_symbols();
                 self.index = 0;
-                input = &input[0..std::cmp::min(input.len(), frame.n_symbols() * 48)];
+                input = &input[0..std::cmp::min(input.len(), frame.n_symbols() * frame.n_sd())];
             } else {
-                assert!(*index <= (self.current_len - self.index) * 48);
+                assert!(*index <= (self.current_len - self.index) * self.current_frame.n_sd());
                 input = &input[0..*index];
             }
         }
 
-        let n = std::cmp::min(input.len() / 48, (output.len() / 64) - o);
+        let n_sd = self.current_frame.n_sd();
+        let n_fft = self.current_frame.bandwidth().fft_size();
+        let n = std::cmp::min(input.len() / n_sd, (output.len() / n_fft) - o);
 
         for i in 0..n {
             self.index += 1;
-            Self::map(
-                (&input[i * 48..(i + 1) * 48]).try_into().unwrap(),
-                (&mut output[(i + o) * 64..(i + o + 1) * 64])
-                    .try_into()
-                    .unwrap(),
-                self.current_mod,
-                self.index,
+            self.map_data(
+                &input[i * n_sd..(i + 1) * n_sd],
+                &mut output[(i + o) * n_fft..(i + o + 1) * n_fft],
             );
         }
 
-        sio.input(0).consume(n * 48);
-        sio.output(0).produce((n + o) * 64);
+        sio.input(0).consume(n * n_sd);
+        sio.output(0).produce((n + o) * n_fft);
 
-        if sio.input(0).finished() && n == input.len() / 48 {
+        if sio.input(0).finished() && n == input.len() / n_sd {
             io.finished = true;
         }
 
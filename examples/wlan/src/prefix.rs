@@ -13,13 +13,22 @@ use futuresdr::runtime::StreamIoBuilder;
 use futuresdr::runtime::Tag;
 use futuresdr::runtime::WorkIo;
 
+use crate::Bandwidth;
+
 pub struct Prefix {
     pad_front: usize,
     pad_tail: usize,
+    bandwidth: Bandwidth,
+    sync: Vec<Complex32>,
 }
 
 impl Prefix {
     pub fn new(pad_front: usize, pad_tail: usize) -> Block {
+        Self::with_bandwidth(pad_front, pad_tail, Bandwidth::Bw20)
+    }
+
+    /// Prefix for OFDM symbols with 64 (20 MHz) or 128 (40 MHz) subcarriers
+    pub fn with_bandwidth(pad_front: usize, pad_tail: usize, bandwidth: Bandwidth) -> Block {
         Block::new(
             BlockMetaBuilder::new("Prefix").build(),
             StreamIoBuilder::new()
@@ -30,9 +39,29 @@ impl Prefix {
             Prefix {
                 pad_front,
                 pad_tail,
+                bandwidth,
+                sync: Self::sync_words(bandwidth),
             },
         )
     }
+
+    /// ten short training symbols, guard interval, and two long training symbols
+    fn sync_words(bandwidth: Bandwidth) -> Vec<Complex32> {
+        if bandwidth == Bandwidth::Bw20 {
+            return SYNC_WORDS.to_vec();
+        }
+
+        let n = bandwidth.fft_size() as isize;
+        let stf = bandwidth.legacy_duplicate(&crate::short_training_field());
+        let ltf = bandwidth.legacy_duplicate(&crate::LONG);
+        let mut sync: Vec<Complex32> = (0..n * 5 / 2)
+            .map(|i| crate::time_domain(&stf, i))
+            .chain((-n / 2..n * 2).map(|i| crate::time_domain(&ltf, i)))
+            .collect();
+        let i = (n * 5 / 2) as usize;
+        sync[i] = 0.5 * (sync[i] + crate::time_domain(&stf, n * 5 / 2));
+        sync
+    }
 }
 
 #[async_trait]
@@ -62,35 +91,39 @@ impl Kernel for Prefix {
             _ => None,
         }) {
             assert_eq!(*index, 0);
-            if output.len() >= self.pad_front + std::cmp::max(self.pad_tail, 1) + len * 80 + 320
-                && input.len() >= len * 64
+            let fft = self.bandwidth.fft_size();
+            let cp = self.bandwidth.guard_interval();
+            let sym = fft + cp;
+            let sync = self.sync.len();
+            if output.len() >= self.pad_front + std::cmp::max(self.pad_tail, 1) + len * sym + sync
+                && input.len() >= len * fft
             {
                 output[0..self.pad_front].fill(Complex32::new(0.0, 0.0));
-                output[self.pad_front..self.pad_front + 320].copy_from_slice(&SYNC_WORDS);
+                output[self.pad_front..self.pad_front + sync].copy_from_slice(&self.sync);
 
                 for k in 0..*len {
-                    let in_offset = k * 64;
-                    let out_offset = self.pad_front + 320 + k * 80;
-                    output[out_offset..out_offset + 16]
-                        .copy_from_slice(&input[in_offset + 48..in_offset + 64]);
-                    output[out_offset + 16..out_offset + 80]
-                        .copy_from_slice(&input[in_offset..in_offset + 64]);
+                    let in_offset = k * fft;
+                    let out_offset = self.pad_front + sync + k * sym;
+                    output[out_offset..out_offset + cp]
+                        .copy_from_slice(&input[in_offset + fft - cp..in_offset + fft]);
+                    output[out_offset + cp..out_offset + sym]
+                        .copy_from_slice(&input[in_offset..in_offset + fft]);
                 }
 
                 // windowing
-                let out_offset = self.pad_front + 320;
-                output[out_offset] = 0.5 * (output[out_offset] + SYNC_WORDS[320 - 64]);
+                let out_offset = self.pad_front + sync;
+                output[out_offset] = 0.5 * (output[out_offset] + self.sync[sync - fft]);
                 for k in 0..*len {
-                    output[out_offset + (k + 1) * 80] = 0.5
-                        * (output[out_offset + (k + 1) * 80] + output[out_offset + k * 80 + 16]);
+                    output[out_offset + (k + 1) * sym] = 0.5
+                        * (output[out_offset + (k + 1) * sym] + output[out_offset + k * sym + cp]);
                 }
 
-                let out_offset = self.pad_front + 320 + len * 80;
+                let out_offset = self.pad_front + sync + len * sym;
                 output[out_offset + 1..out_offset + std::cmp::max(self.pad_tail, 1)]
                     .fill(Complex32::new(0.0, 0.0));
 
-                sio.input(0).consume(len * 64);
-                let produce = self.pad_front + std::cmp::max(self.pad_tail, 1) + len * 80 + 320;
+                sio.input(0).consume(len * fft);
+                let produce = self.pad_front + std::cmp::max(self.pad_tail, 1) + len * sym + sync;
 
                 output[0..produce].iter_mut().for_each(|v| *v *= 0.6);
 
@@ -98,7 +131,7 @@ impl Kernel for Prefix {
                     .add_tag(0, Tag::NamedUsize("burst_start".to_string(), produce));
                 sio.output(0).produce(produce);
 
-                if sio.input(0).finished() && input.len() < len * 64 {
+                if sio.input(0).finished() && input.len() < len * fft {
                     io.finished = true;
                 }
             }
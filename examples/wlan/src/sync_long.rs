@@ -13,6 +13,8 @@ use futuresdr::runtime::StreamIoBuilder;
 use futuresdr::runtime::Tag;
 use futuresdr::runtime::WorkIo;
 
+use crate::Bandwidth;
+
 const SEARCH_WINDOW: usize = 320;
 
 #[derive(Debug)]
@@ -23,13 +25,34 @@ enum State {
 }
 
 pub struct SyncLong {
-    cor: [Complex32; SEARCH_WINDOW],
+    cor: Vec<Complex32>,
     cor_index: Vec<(usize, f32)>,
     state: State,
+    /// conjugate of the long training symbol in the time domain
+    long: Vec<Complex32>,
+    search_window: usize,
+    fft: usize,
+    cp: usize,
 }
 
 impl SyncLong {
     pub fn new() -> Block {
+        Self::with_bandwidth(Bandwidth::Bw20)
+    }
+
+    /// Long sync for OFDM symbols with 64 (20 MHz) or 128 (40 MHz) subcarriers
+    pub fn with_bandwidth(bandwidth: Bandwidth) -> Block {
+        let fft = bandwidth.fft_size();
+        let search_window = SEARCH_WINDOW * fft / 64;
+        let long = match bandwidth {
+            Bandwidth::Bw20 => LONG.to_vec(),
+            Bandwidth::Bw40 => {
+                let ltf = bandwidth.legacy_duplicate(&crate::LONG);
+                (0..fft as isize)
+                    .map(|n| crate::time_domain(&ltf, n).conj())
+                    .collect()
+            }
+        };
         Block::new(
             BlockMetaBuilder::new("SyncLong").build(),
             StreamIoBuilder::new()
@@ -38,21 +61,25 @@ impl SyncLong {
                 .build(),
             MessageIoBuilder::new().build(),
             Self {
-                cor: [Complex32::new(0.0, 0.0); SEARCH_WINDOW],
-                cor_index: Vec::with_capacity(SEARCH_WINDOW),
+                cor: vec![Complex32::new(0.0, 0.0); search_window],
+                cor_index: Vec::with_capacity(search_window),
                 state: State::Broken,
+                long,
+                search_window,
+                fft,
+                cp: bandwidth.guard_interval(),
             },
         )
     }
 
     fn sync(&mut self, input: &[Complex32]) -> (usize, f32) {
-        debug_assert_eq!(input.len(), SEARCH_WINDOW + 63);
+        debug_assert_eq!(input.len(), self.search_window + self.fft - 1);
 
-        for i in 0..SEARCH_WINDOW {
+        for i in 0..self.search_window {
             unsafe {
                 let mut sum = Complex32::new(0.0, 0.0);
-                for k in 0..64 {
-                    sum += *input.get_unchecked(i + k) * *LONG.get_unchecked(k);
+                for k in 0..self.fft {
+                    sum += *input.get_unchecked(i + k) * *self.long.get_unchecked(k);
                 }
                 *self.cor.get_unchecked_mut(i) = sum;
             }
@@ -72,7 +99,7 @@ impl SyncLong {
 
         (
             first,
-            (self.cor[first] * self.cor[second].conj()).arg() / 64.0,
+            (self.cor[first] * self.cor[second].conj()).arg() / self.fft as f32,
         )
     }
 }
@@ -90,6 +117,8 @@ impl Kernel for SyncLong {
         let out = sio.output(0).slice::<Complex32>();
 
         let mut m = std::cmp::min(input.len(), out.len());
+        let (fft, cp) = (self.fft, self.cp);
+        let sym = fft + cp;
 
         let tags = sio.input(0).tags();
         // println!("long tags {:?}", &tags);
@@ -110,7 +139,7 @@ impl Kernel for SyncLong {
                 self.state = State::Sync(*freq);
             } else {
                 m = std::cmp::min(m, *index);
-                if m < 80 {
+                if m < sym {
                     sio.input(0).consume(m);
                     return Ok(());
                 }
@@ -124,11 +153,11 @@ impl Kernel for SyncLong {
                 }
             }
             State::Sync(freq_offset_short) => {
-                if m >= SEARCH_WINDOW + 128 {
-                    let (offset, freq_offset) = self.sync(&input[0..SEARCH_WINDOW + 63]);
+                if m >= self.search_window + 2 * fft {
+                    let (offset, freq_offset) = self.sync(&input[0..self.search_window + fft - 1]);
                     // debug!("long start: offset {}   freq {}", offset, freq_offset);
 
-                    for i in 0..128 {
+                    for i in 0..2 * fft {
                         out[i] =
                             input[offset + i] * Complex32::from_polar(1.0, i as f32 * freq_offset);
                     }
@@ -137,31 +166,31 @@ impl Kernel for SyncLong {
                         Tag::NamedF32("wifi_start".to_string(), freq_offset_short + freq_offset),
                     );
 
-                    sio.input(0).consume(offset + 128);
-                    sio.output(0).produce(128);
+                    sio.input(0).consume(offset + 2 * fft);
+                    sio.output(0).produce(2 * fft);
                     io.call_again = true;
 
                     self.state = State::Copy(0, freq_offset);
                 }
             }
             State::Copy(n_copied, freq_offset) => {
-                let syms = m / 80;
+                let syms = m / sym;
                 for i in 0..syms {
-                    for k in 0..64 {
-                        out[i * 64 + k] = input[i * 80 + 16 + k]
+                    for k in 0..fft {
+                        out[i * fft + k] = input[i * sym + cp + k]
                             * Complex32::from_polar(
                                 1.0,
-                                ((n_copied + i) * 80 + 128 + 16 + k) as f32 * freq_offset,
+                                ((n_copied + i) * sym + 2 * fft + cp + k) as f32 * freq_offset,
                             );
                     }
                 }
-                sio.input(0).consume(syms * 80);
-                sio.output(0).produce(syms * 64);
-                self.state = State::Copy(n_copied + syms * 80, freq_offset);
+                sio.input(0).consume(syms * sym);
+                sio.output(0).produce(syms * fft);
+                self.state = State::Copy(n_copied + syms * sym, freq_offset);
             }
         }
 
-        if sio.input(0).finished() && input.len() - m < 80 {
+        if sio.input(0).finished() && input.len() - m < sym {
             io.finished = true;
         }
 
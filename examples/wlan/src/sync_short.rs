@@ -12,6 +12,8 @@ use futuresdr::runtime::StreamIoBuilder;
 use futuresdr::runtime::Tag;
 use futuresdr::runtime::WorkIo;
 
+use crate::Bandwidth;
+
 const MIN_GAP: usize = 480;
 const MAX_SAMPLES: usize = 540 * 80;
 const THRESHOLD: f32 = 0.56;
@@ -25,10 +27,18 @@ enum State {
 
 pub struct SyncShort {
     state: State,
+    /// samples per sample at 20 MHz
+    factor: usize,
 }
 
 impl SyncShort {
     pub fn new() -> Block {
+        Self::with_bandwidth(Bandwidth::Bw20)
+    }
+
+    /// Short sync for 20 or 40 MHz, expecting the delay and moving averages of the inputs to
+    /// scale with the sample rate
+    pub fn with_bandwidth(bandwidth: Bandwidth) -> Block {
         Block::new(
             BlockMetaBuilder::new("SyncShort").build(),
             StreamIoBuilder::new()
@@ -40,6 +50,7 @@ impl SyncShort {
             MessageIoBuilder::new().build(),
             Self {
                 state: State::Search,
+                factor: bandwidth.fft_size() / 64,
             },
         )
     }
@@ -73,7 +84,7 @@ impl Kernel for SyncShort {
                 }
                 State::Found => {
                     if in_cor[i] > THRESHOLD {
-                        let f_offset = -in_abs[i].arg() / 16.0;
+                        let f_offset = -in_abs[i].arg() / (16 * self.factor) as f32;
                         self.state = State::Copy(0, f_offset, false);
                         sio.output(0)
                             .add_tag(o, Tag::NamedF32("wifi_start".to_string(), f_offset));
@@ -84,8 +95,8 @@ impl Kernel for SyncShort {
                 State::Copy(n_copied, f_offset, mut last_above_threshold) => {
                     if in_cor[i] > THRESHOLD {
                         // resync
-                        if last_above_threshold && n_copied > MIN_GAP {
-                            let f_offset = -in_abs[i].arg() / 16.0;
+                        if last_above_threshold && n_copied > MIN_GAP * self.factor {
+                            let f_offset = -in_abs[i].arg() / (16 * self.factor) as f32;
                             self.state = State::Copy(0, f_offset, false);
                             sio.output(0)
                                 .add_tag(o, Tag::NamedF32("wifi_start".to_string(), f_offset));
@@ -101,7 +112,7 @@ impl Kernel for SyncShort {
                     out[o] = in_sig[i] * Complex32::from_polar(1.0, f_offset * n_copied as f32); // accum?
                     o += 1;
 
-                    if n_copied + 1 == MAX_SAMPLES {
+                    if n_copied + 1 == MAX_SAMPLES * self.factor {
                         self.state = State::Search;
                     } else {
                         self.state = State::Copy(n_copied + 1, f_offset, last_above_threshold);
@@ -6,7 +6,47 @@ use crate::MAX_ENCODED_BITS;
 
 const TRACEBACK_MAX: usize = 24;
 
+/// Implementation of the trellis update
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Butterfly {
+    Generic,
+    #[cfg(target_arch = "x86_64")]
+    Sse2,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+    #[cfg(target_arch = "aarch64")]
+    Neon,
+}
+
+impl Butterfly {
+    /// Fastest implementation supported by the CPU
+    #[cfg(target_arch = "x86_64")]
+    fn detect() -> Self {
+        if is_x86_feature_detected!("avx2") {
+            Self::Avx2
+        } else {
+            Self::Sse2
+        }
+    }
+
+    #[cfg(target_arch = "aarch64")]
+    fn detect() -> Self {
+        Self::Neon
+    }
+
+    #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+    fn detect() -> Self {
+        Self::Generic
+    }
+}
+
+/// One trellis step of a SIMD implementation
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+type SimdButterfly =
+    unsafe fn(&[u8; 32], &[u8; 32], &[u8; 64], &[u8; 64], &mut [u8; 64], &mut [u8; 64]);
+
 pub struct ViterbiDecoder {
+    butterfly: Butterfly,
     frame_param: FrameParam,
     n_traceback: usize,
     store_pos: usize,
@@ -17,6 +57,8 @@ pub struct ViterbiDecoder {
     path1: [u8; 64],
 
     branchtab27: [[u8; 32]; 2],
+    /// branch metrics `[metsvm, metsv]` of all symbol pairs, indexed by `3 * s0 + s1`
+    branch_metrics: [[[u8; 32]; 2]; 9],
 
     mmresult: [u8; 64],
     ppresult: [[u8; 64]; TRACEBACK_MAX],
@@ -25,8 +67,19 @@ pub struct ViterbiDecoder {
 }
 
 impl ViterbiDecoder {
+    /// Create a decoder, using the fastest implementation supported by the CPU
     pub fn new() -> Self {
+        Self::with_butterfly(Butterfly::detect())
+    }
+
+    /// Create a decoder, using the portable implementation, e.g., as a reference
+    pub fn generic() -> Self {
+        Self::with_butterfly(Butterfly::Generic)
+    }
+
+    fn with_butterfly(butterfly: Butterfly) -> Self {
         ViterbiDecoder {
+            butterfly,
             frame_param: FrameParam::new(Mcs::Bpsk_1_2, 0),
             n_traceback: 0,
             store_pos: 0,
@@ -37,6 +90,7 @@ impl ViterbiDecoder {
             path1: [0; 64],
 
             branchtab27: [[0; 32]; 2],
+            branch_metrics: [[[0; 32]; 2]; 9],
 
             mmresult: [0; 64],
             ppresult: [[0; 64]; TRACEBACK_MAX],
@@ -61,6 +115,24 @@ impl ViterbiDecoder {
         // info!("branchtab27 0: {:?}", self.branchtab27[0]);
         // info!("branchtab27 1: {:?}", self.branchtab27[1]);
 
+        // symbols are 0, 1, or 2 for punctured bits
+        for s0 in 0..3u8 {
+            for s1 in 0..3u8 {
+                let [metsvm, metsv] = &mut self.branch_metrics[usize::from(3 * s0 + s1)];
+                for i in 0..32 {
+                    let b0 = self.branchtab27[0][i] ^ s0;
+                    let b1 = self.branchtab27[1][i] ^ s1;
+                    (metsvm[i], metsv[i]) = if s0 == 2 {
+                        (b1, 1u8.wrapping_sub(b1))
+                    } else if s1 == 2 {
+                        (b0, 1u8.wrapping_sub(b0))
+                    } else {
+                        (b0 + b1, 2 - (b0 + b1))
+                    };
+                }
+            }
+        }
+
         self.store_pos = 0;
         self.mmresult.fill(0);
         self.ppresult.fill([0; 64]);
@@ -69,12 +141,21 @@ impl ViterbiDecoder {
             Mcs::Bpsk_1_2 | Mcs::Qpsk_1_2 | Mcs::Qam16_1_2 => {
                 self.n_traceback = 5;
             }
+            Mcs::HtMcs0 | Mcs::HtMcs1 | Mcs::HtMcs3 => {
+                self.n_traceback = 5;
+            }
             Mcs::Bpsk_3_4 | Mcs::Qpsk_3_4 | Mcs::Qam16_3_4 | Mcs::Qam64_3_4 => {
                 self.n_traceback = 10;
             }
-            Mcs::Qam64_2_3 => {
+            Mcs::HtMcs2 | Mcs::HtMcs4 | Mcs::HtMcs6 => {
+                self.n_traceback = 10;
+            }
+            Mcs::Qam64_2_3 | Mcs::HtMcs5 => {
                 self.n_traceback = 9;
             }
+            Mcs::HtMcs7 => {
+                self.n_traceback = 12;
+            }
         }
     }
 
@@ -83,7 +164,7 @@ impl ViterbiDecoder {
             self.depunctured[0..in_bits.len()].copy_from_slice(in_bits);
         } else {
             let pattern = self.frame_param.mcs.depuncture_pattern();
-            let n_cbps = self.frame_param.mcs().n_cbps();
+            let n_cbps = self.frame_param.n_cbps();
             let mut count = 0;
 
             for i in 0..self.frame_param.n_symbols() {
@@ -106,6 +187,48 @@ impl ViterbiDecoder {
         }
     }
 
+    fn viterbi_butterfly2(&mut self, symbols: &[u8; 4]) {
+        match self.butterfly {
+            Butterfly::Generic => self.viterbi_butterfly2_generic(symbols),
+            // SSE2 is part of the x86_64 baseline
+            #[cfg(target_arch = "x86_64")]
+            Butterfly::Sse2 => unsafe { self.viterbi_butterfly2_simd(symbols, sse2::butterfly) },
+            // only selected if the CPU supports AVX2
+            #[cfg(target_arch = "x86_64")]
+            Butterfly::Avx2 => unsafe { self.viterbi_butterfly2_simd(symbols, avx2::butterfly) },
+            // NEON is part of the aarch64 baseline
+            #[cfg(target_arch = "aarch64")]
+            Butterfly::Neon => unsafe { self.viterbi_butterfly2_simd(symbols, neon::butterfly) },
+        }
+    }
+
+    /// Two trellis steps with the precomputed branch metrics
+    ///
+    /// # Safety
+    ///
+    /// The CPU has to support the instructions used by `butterfly`.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    unsafe fn viterbi_butterfly2_simd(&mut self, symbols: &[u8; 4], butterfly: SimdButterfly) {
+        let [metsvm, metsv] = &self.branch_metrics[usize::from(3 * symbols[0] + symbols[1])];
+        butterfly(
+            metsvm,
+            metsv,
+            &self.metric0,
+            &self.path0,
+            &mut self.metric1,
+            &mut self.path1,
+        );
+        let [metsvm, metsv] = &self.branch_metrics[usize::from(3 * symbols[2] + symbols[3])];
+        butterfly(
+            metsvm,
+            metsv,
+            &self.metric1,
+            &self.path1,
+            &mut self.metric0,
+            &mut self.path0,
+        );
+    }
+
     fn viterbi_butterfly2_generic(&mut self, symbols: &[u8; 4]) {
         let mut metric0 = &mut self.metric0;
         let mut path0 = &mut self.path0;
@@ -503,9 +626,7 @@ impl ViterbiDecoder {
         while n_decoded < self.frame_param.n_data_bits() {
             if (in_count % 4) == 0 {
                 let index = in_count & !0b11;
-                self.viterbi_butterfly2_generic(
-                    &self.depunctured[index..index + 4].try_into().unwrap(),
-                );
+                self.viterbi_butterfly2(&self.depunctured[index..index + 4].try_into().unwrap());
 
                 if (in_count > 0) && (in_count % 16) == 8 {
                     // 8 or 11
@@ -534,6 +655,170 @@ impl Default for ViterbiDecoder {
     }
 }
 
+/// One trellis step for two symbols (one bit), processing 16 states per instruction
+#[cfg(target_arch = "x86_64")]
+mod sse2 {
+    use std::arch::x86_64::*;
+
+    #[inline]
+    pub unsafe fn butterfly(
+        metsvm: &[u8; 32],
+        metsv: &[u8; 32],
+        metric0: &[u8; 64],
+        path0: &[u8; 64],
+        metric1: &mut [u8; 64],
+        path1: &mut [u8; 64],
+    ) {
+        let load = |a: &[u8], i: usize| _mm_loadu_si128(a[i * 16..].as_ptr() as *const __m128i);
+
+        for i in 0..2 {
+            let metsvm = load(metsvm, i);
+            let metsv = load(metsv, i);
+
+            let m0 = _mm_add_epi8(load(metric0, i), metsv);
+            let m1 = _mm_add_epi8(load(metric0, i + 2), metsvm);
+            let m2 = _mm_add_epi8(load(metric0, i), metsvm);
+            let m3 = _mm_add_epi8(load(metric0, i + 2), metsv);
+
+            let decision0 = _mm_cmpgt_epi8(_mm_sub_epi8(m0, m1), _mm_setzero_si128());
+            let decision1 = _mm_cmpgt_epi8(_mm_sub_epi8(m2, m3), _mm_setzero_si128());
+            let survivor0 = _mm_or_si128(
+                _mm_and_si128(decision0, m0),
+                _mm_andnot_si128(decision0, m1),
+            );
+            let survivor1 = _mm_or_si128(
+                _mm_and_si128(decision1, m2),
+                _mm_andnot_si128(decision1, m3),
+            );
+
+            let shift0 = _mm_slli_epi16(load(path0, i), 1);
+            let shift1 = _mm_add_epi8(_mm_slli_epi16(load(path0, i + 2), 1), _mm_set1_epi8(1));
+
+            let tmp0 = _mm_or_si128(
+                _mm_and_si128(decision0, shift0),
+                _mm_andnot_si128(decision0, shift1),
+            );
+            let tmp1 = _mm_or_si128(
+                _mm_and_si128(decision1, shift0),
+                _mm_andnot_si128(decision1, shift1),
+            );
+
+            let out = metric1[2 * i * 16..].as_mut_ptr() as *mut __m128i;
+            _mm_storeu_si128(out, _mm_unpacklo_epi8(survivor0, survivor1));
+            _mm_storeu_si128(out.add(1), _mm_unpackhi_epi8(survivor0, survivor1));
+            let out = path1[2 * i * 16..].as_mut_ptr() as *mut __m128i;
+            _mm_storeu_si128(out, _mm_unpacklo_epi8(tmp0, tmp1));
+            _mm_storeu_si128(out.add(1), _mm_unpackhi_epi8(tmp0, tmp1));
+        }
+    }
+}
+
+/// One trellis step for two symbols (one bit), processing all 32 butterflies at once
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn load(a: &[u8], i: usize) -> __m256i {
+        _mm256_loadu_si256(a[i * 32..].as_ptr() as *const __m256i)
+    }
+
+    /// Interleave `a` and `b` and store the result in `out`
+    ///
+    /// The unpack instructions work on the 128-bit lanes, the permutes restore the order.
+    #[inline]
+    #[target_feature(enable = "avx2")]
+    unsafe fn store_interleaved(a: __m256i, b: __m256i, out: &mut [u8; 64]) {
+        let lo = _mm256_unpacklo_epi8(a, b);
+        let hi = _mm256_unpackhi_epi8(a, b);
+        let out = out.as_mut_ptr() as *mut __m256i;
+        _mm256_storeu_si256(out, _mm256_permute2x128_si256(lo, hi, 0x20));
+        _mm256_storeu_si256(out.add(1), _mm256_permute2x128_si256(lo, hi, 0x31));
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn butterfly(
+        metsvm: &[u8; 32],
+        metsv: &[u8; 32],
+        metric0: &[u8; 64],
+        path0: &[u8; 64],
+        metric1: &mut [u8; 64],
+        path1: &mut [u8; 64],
+    ) {
+        let metsvm = load(metsvm, 0);
+        let metsv = load(metsv, 0);
+
+        let m0 = _mm256_add_epi8(load(metric0, 0), metsv);
+        let m1 = _mm256_add_epi8(load(metric0, 1), metsvm);
+        let m2 = _mm256_add_epi8(load(metric0, 0), metsvm);
+        let m3 = _mm256_add_epi8(load(metric0, 1), metsv);
+
+        let decision0 = _mm256_cmpgt_epi8(_mm256_sub_epi8(m0, m1), _mm256_setzero_si256());
+        let decision1 = _mm256_cmpgt_epi8(_mm256_sub_epi8(m2, m3), _mm256_setzero_si256());
+        let survivor0 = _mm256_blendv_epi8(m1, m0, decision0);
+        let survivor1 = _mm256_blendv_epi8(m3, m2, decision1);
+
+        let shift0 = _mm256_slli_epi16(load(path0, 0), 1);
+        let shift1 = _mm256_add_epi8(_mm256_slli_epi16(load(path0, 1), 1), _mm256_set1_epi8(1));
+
+        let tmp0 = _mm256_blendv_epi8(shift1, shift0, decision0);
+        let tmp1 = _mm256_blendv_epi8(shift1, shift0, decision1);
+
+        store_interleaved(survivor0, survivor1, metric1);
+        store_interleaved(tmp0, tmp1, path1);
+    }
+}
+
+/// One trellis step for two symbols (one bit), processing 16 states per instruction
+#[cfg(target_arch = "aarch64")]
+mod neon {
+    use std::arch::aarch64::*;
+
+    #[inline]
+    pub unsafe fn butterfly(
+        metsvm: &[u8; 32],
+        metsv: &[u8; 32],
+        metric0: &[u8; 64],
+        path0: &[u8; 64],
+        metric1: &mut [u8; 64],
+        path1: &mut [u8; 64],
+    ) {
+        let load = |a: &[u8], i: usize| vld1q_u8(a[i * 16..].as_ptr());
+
+        for i in 0..2 {
+            let metsvm = load(metsvm, i);
+            let metsv = load(metsv, i);
+
+            let m0 = vaddq_u8(load(metric0, i), metsv);
+            let m1 = vaddq_u8(load(metric0, i + 2), metsvm);
+            let m2 = vaddq_u8(load(metric0, i), metsvm);
+            let m3 = vaddq_u8(load(metric0, i + 2), metsv);
+
+            let zero = vdupq_n_s8(0);
+            let decision0 = vcgtq_s8(vreinterpretq_s8_u8(vsubq_u8(m0, m1)), zero);
+            let decision1 = vcgtq_s8(vreinterpretq_s8_u8(vsubq_u8(m2, m3)), zero);
+            let survivor0 = vbslq_u8(decision0, m0, m1);
+            let survivor1 = vbslq_u8(decision1, m2, m3);
+
+            let shift =
+                |a: uint8x16_t| vreinterpretq_u8_u16(vshlq_n_u16(vreinterpretq_u16_u8(a), 1));
+            let shift0 = shift(load(path0, i));
+            let shift1 = vaddq_u8(shift(load(path0, i + 2)), vdupq_n_u8(1));
+
+            let tmp0 = vbslq_u8(decision0, shift0, shift1);
+            let tmp1 = vbslq_u8(decision1, shift0, shift1);
+
+            let out = metric1[2 * i * 16..].as_mut_ptr();
+            vst1q_u8(out, vzip1q_u8(survivor0, survivor1));
+            vst1q_u8(out.add(16), vzip2q_u8(survivor0, survivor1));
+            let out = path1[2 * i * 16..].as_mut_ptr();
+            vst1q_u8(out, vzip1q_u8(tmp0, tmp1));
+            vst1q_u8(out.add(16), vzip2q_u8(tmp0, tmp1));
+        }
+    }
+}
+
 /* Parity lookup table */
 const PARTAB: [u8; 256] = [
     0, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 0, 0, 1,
@@ -545,3 +830,65 @@ const PARTAB: [u8; 256] = [
     0, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 0, 0, 1,
     1, 0, 0, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 0, 0, 1, 0, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 1, 1, 0,
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::Rng;
+    use rand::SeedableRng;
+
+    fn simd_butterflies() -> Vec<Butterfly> {
+        let mut b = Vec::new();
+        #[cfg(target_arch = "x86_64")]
+        {
+            b.push(Butterfly::Sse2);
+            if is_x86_feature_detected!("avx2") {
+                b.push(Butterfly::Avx2);
+            }
+        }
+        #[cfg(target_arch = "aarch64")]
+        b.push(Butterfly::Neon);
+        b
+    }
+
+    #[test]
+    fn simd_matches_generic() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let frame = FrameParam::new(Mcs::Bpsk_1_2, 0);
+
+        for butterfly in simd_butterflies() {
+            let mut generic = ViterbiDecoder::generic();
+            let mut simd = ViterbiDecoder::with_butterfly(butterfly);
+            generic.reset(frame.clone());
+            simd.reset(frame.clone());
+
+            for _ in 0..10_000 {
+                // metrics are normalized to the minimum and paths are cleared regularly, i.e.,
+                // no lane overflows (which would panic in the generic implementation)
+                generic.metric0 = [0; 64].map(|_: u8| rng.gen_range(0..64));
+                generic.path0 = [0; 64].map(|_: u8| rng.gen_range(0..32));
+                simd.metric0 = generic.metric0;
+                simd.path0 = generic.path0;
+
+                // at most one symbol of a pair is punctured
+                let mut symbols = [0u8; 4];
+                for pair in symbols.chunks_mut(2) {
+                    pair[0] = rng.gen_range(0..3);
+                    pair[1] = if pair[0] == 2 {
+                        rng.gen_range(0..2)
+                    } else {
+                        rng.gen_range(0..3)
+                    };
+                }
+
+                generic.viterbi_butterfly2(&symbols);
+                simd.viterbi_butterfly2(&symbols);
+                assert_eq!(generic.metric1, simd.metric1, "{butterfly:?} {symbols:?}");
+                assert_eq!(generic.path1, simd.path1, "{butterfly:?} {symbols:?}");
+                assert_eq!(generic.metric0, simd.metric0, "{butterfly:?} {symbols:?}");
+                assert_eq!(generic.path0, simd.path0, "{butterfly:?} {symbols:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::Combine;
+use futuresdr::blocks::Delay;
+use futuresdr::blocks::Fft;
+use futuresdr::blocks::FileSource;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::testing::assert_frames_eq;
+use futuresdr::testing::golden_fixtures;
+use futuresdr::testing::run_with_timeout;
+use std::path::Path;
+use std::time::Duration;
+
+use wlan::fft_tag_propagation;
+use wlan::golden::generate;
+use wlan::golden::MCS;
+use wlan::Decoder;
+use wlan::FrameEqualizer;
+use wlan::MovingAverage;
+use wlan::SyncLong;
+use wlan::SyncShort;
+
+/// Fixtures are generated from the TX chain, like with `cargo run --release --bin golden`
+const GOLDEN: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/golden");
+
+fn decode(samples: &Path) -> Result<Vec<Vec<u8>>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(FileSource::<Complex32>::new(
+        samples.to_str().unwrap(),
+        false,
+    ));
+
+    let delay = fg.add_block(Delay::<Complex32>::new(16));
+    fg.connect_stream(src, "out", delay, "in")?;
+
+    let complex_to_mag_2 = fg.add_block(Apply::new(|i: &Complex32| i.norm_sqr()));
+    let float_avg = fg.add_block(MovingAverage::<f32>::new(64));
+    fg.connect_stream(src, "out", complex_to_mag_2, "in")?;
+    fg.connect_stream(complex_to_mag_2, "out", float_avg, "in")?;
+
+    let mult_conj = fg.add_block(Combine::new(|a: &Complex32, b: &Complex32| a * b.conj()));
+    let complex_avg = fg.add_block(MovingAverage::<Complex32>::new(48));
+    fg.connect_stream(src, "out", mult_conj, "in0")?;
+    fg.connect_stream(delay, "out", mult_conj, "in1")?;
+    fg.connect_stream(mult_conj, "out", complex_avg, "in")?;
+
+    let divide_mag = fg.add_block(Combine::new(|a: &Complex32, b: &f32| a.norm() / b));
+    fg.connect_stream(complex_avg, "out", divide_mag, "in0")?;
+    fg.connect_stream(float_avg, "out", divide_mag, "in1")?;
+
+    let sync_short = fg.add_block(SyncShort::new());
+    fg.connect_stream(delay, "out", sync_short, "in_sig")?;
+    fg.connect_stream(complex_avg, "out", sync_short, "in_abs")?;
+    fg.connect_stream(divide_mag, "out", sync_short, "in_cor")?;
+
+    let sync_long = fg.add_block(SyncLong::new());
+    fg.connect_stream(sync_short, "out", sync_long, "in")?;
+
+    let mut fft = Fft::new(64);
+    fft.set_tag_propagation(Box::new(fft_tag_propagation));
+    let fft = fg.add_block(fft);
+    fg.connect_stream(sync_long, "out", fft, "in")?;
+
+    let frame_equalizer = fg.add_block(FrameEqualizer::new());
+    fg.connect_stream(fft, "out", frame_equalizer, "in")?;
+
+    let decoder = fg.add_block(Decoder::new());
+    fg.connect_stream(frame_equalizer, "out", decoder, "in")?;
+
+    let (tx_frame, mut rx_frame) = mpsc::channel::<Pmt>(1000);
+    let message_pipe = fg.add_block(MessagePipe::new(tx_frame));
+    fg.connect_message(decoder, "rx_frames", message_pipe, "in")?;
+
+    run_with_timeout(fg, Duration::from_secs(60))?;
+
+    let mut frames = Vec::new();
+    while let Ok(Some(p)) = rx_frame.try_next() {
+        if let Pmt::Blob(f) = p {
+            frames.push(f);
+        }
+    }
+    Ok(frames)
+}
+
+#[test]
+fn golden() -> Result<()> {
+    std::fs::create_dir_all(GOLDEN)?;
+    for mcs in MCS {
+        generate(Path::new(GOLDEN), mcs, 10, 0.01)?;
+    }
+
+    let fixtures = golden_fixtures(GOLDEN, "cf32")?;
+    assert_eq!(fixtures.len(), MCS.len());
+    for fixture in fixtures {
+        let frames = decode(&fixture.samples)?;
+        assert_frames_eq(&fixture.name, &frames, &fixture.expected()?);
+    }
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::Combine;
+use futuresdr::blocks::Delay;
+use futuresdr::blocks::Fft;
+use futuresdr::blocks::FftDirection;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::testing::assert_frames_eq;
+use futuresdr::testing::run_with_timeout;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use std::time::Duration;
+
+use wlan::fft_tag_propagation;
+use wlan::Bandwidth;
+use wlan::Decoder;
+use wlan::Encoder;
+use wlan::FrameEqualizer;
+use wlan::Mapper;
+use wlan::Mcs;
+use wlan::MovingAverage;
+use wlan::Prefix;
+use wlan::SyncLong;
+use wlan::SyncShort;
+use wlan::MAX_SYM;
+
+const PAD_FRONT: usize = 5000;
+const PAD_TAIL: usize = 5000;
+
+const HT: [Mcs; 8] = [
+    Mcs::HtMcs0,
+    Mcs::HtMcs1,
+    Mcs::HtMcs2,
+    Mcs::HtMcs3,
+    Mcs::HtMcs4,
+    Mcs::HtMcs5,
+    Mcs::HtMcs6,
+    Mcs::HtMcs7,
+];
+
+/// Data frame with FCS
+fn frame(seq: u16, payload: &[u8]) -> Vec<u8> {
+    let mut frame = vec![0x08, 0x00, 0x00, 0x00];
+    frame.extend_from_slice(&[0x42; 6]);
+    frame.extend_from_slice(&[0x23; 6]);
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(&(seq << 4).to_le_bytes());
+    frame.extend_from_slice(payload);
+    let crc = crc32fast::hash(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// Frame with its MCS, as expected by the `tx` handler of the encoder
+fn message(frame: &[u8], mcs: Mcs) -> Pmt {
+    Pmt::Any(Box::new((frame.to_vec(), Some(mcs))))
+}
+
+/// A-MPDU with its MCS, as expected by the `tx` handler of the encoder
+fn ampdu_message(mpdus: &[Vec<u8>], mcs: Mcs) -> Pmt {
+    Pmt::Any(Box::new((mpdus.to_vec(), Some(mcs))))
+}
+
+fn strip_fcs(frames: &[Vec<u8>]) -> Vec<Vec<u8>> {
+    frames.iter().map(|f| f[0..f.len() - 4].to_vec()).collect()
+}
+
+fn transmit(messages: Vec<Pmt>, bandwidth: Bandwidth) -> Result<Vec<Complex32>> {
+    let n = bandwidth.fft_size();
+    let mut size = 4096;
+    let prefix_in_size = loop {
+        if size / 8 >= MAX_SYM * n {
+            break size;
+        }
+        size += 4096
+    };
+    let mut size = 4096;
+    let prefix_out_size = loop {
+        if size / 8 >= PAD_FRONT + std::cmp::max(PAD_TAIL, 1) + (320 + MAX_SYM * 80) * n / 64 {
+            break size;
+        }
+        size += 4096
+    };
+
+    let mut fg = Flowgraph::new();
+    let encoder = fg.add_block(Encoder::with_bandwidth(Mcs::Bpsk_1_2, bandwidth));
+    let mapper = fg.add_block(Mapper::new());
+    fg.connect_stream(encoder, "out", mapper, "in")?;
+    let mut fft = Fft::with_options(
+        n,
+        FftDirection::Inverse,
+        true,
+        Some((64.0f32 / 52.0 / n as f32).sqrt()),
+    );
+    fft.set_tag_propagation(Box::new(fft_tag_propagation));
+    let fft = fg.add_block(fft);
+    fg.connect_stream(mapper, "out", fft, "in")?;
+    let prefix = fg.add_block(Prefix::with_bandwidth(PAD_FRONT, PAD_TAIL, bandwidth));
+    fg.connect_stream_with_type(
+        fft,
+        "out",
+        prefix,
+        "in",
+        Circular::with_size(prefix_in_size),
+    )?;
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    fg.connect_stream_with_type(
+        prefix,
+        "out",
+        snk,
+        "in",
+        Circular::with_size(prefix_out_size),
+    )?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    let fg = block_on(async {
+        for m in messages {
+            handle.call(encoder, "tx", m).await?;
+        }
+        // give the transmitter time to process the queue
+        Timer::after(Duration::from_secs(1)).await;
+        handle.terminate_and_wait().await?;
+        task.await
+    })?;
+
+    // seeded noise, so that the test is reproducible
+    let normal = Normal::new(0.0f32, 0.01)?;
+    let mut rng = StdRng::seed_from_u64(42);
+    let samples = fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .iter()
+        .map(|s| s + Complex32::new(normal.sample(&mut rng), normal.sample(&mut rng)))
+        .collect();
+    Ok(samples)
+}
+
+fn receive(samples: Vec<Complex32>, bandwidth: Bandwidth) -> Result<Vec<Vec<u8>>> {
+    let f = bandwidth.fft_size() / 64;
+
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(samples));
+
+    let delay = fg.add_block(Delay::<Complex32>::new(16 * f as isize));
+    fg.connect_stream(src, "out", delay, "in")?;
+
+    let complex_to_mag_2 = fg.add_block(Apply::new(|i: &Complex32| i.norm_sqr()));
+    let float_avg = fg.add_block(MovingAverage::<f32>::new(64 * f));
+    fg.connect_stream(src, "out", complex_to_mag_2, "in")?;
+    fg.connect_stream(complex_to_mag_2, "out", float_avg, "in")?;
+
+    let mult_conj = fg.add_block(Combine::new(|a: &Complex32, b: &Complex32| a * b.conj()));
+    let complex_avg = fg.add_block(MovingAverage::<Complex32>::new(48 * f));
+    fg.connect_stream(src, "out", mult_conj, "in0")?;
+    fg.connect_stream(delay, "out", mult_conj, "in1")?;
+    fg.connect_stream(mult_conj, "out", complex_avg, "in")?;
+
+    let divide_mag = fg.add_block(Combine::new(|a: &Complex32, b: &f32| a.norm() / b));
+    fg.connect_stream(complex_avg, "out", divide_mag, "in0")?;
+    fg.connect_stream(float_avg, "out", divide_mag, "in1")?;
+
+    let sync_short = fg.add_block(SyncShort::with_bandwidth(bandwidth));
+    fg.connect_stream(delay, "out", sync_short, "in_sig")?;
+    fg.connect_stream(complex_avg, "out", sync_short, "in_abs")?;
+    fg.connect_stream(divide_mag, "out", sync_short, "in_cor")?;
+
+    let sync_long = fg.add_block(SyncLong::with_bandwidth(bandwidth));
+    fg.connect_stream(sync_short, "out", sync_long, "in")?;
+
+    let mut fft = Fft::new(bandwidth.fft_size());
+    fft.set_tag_propagation(Box::new(fft_tag_propagation));
+    let fft = fg.add_block(fft);
+    fg.connect_stream(sync_long, "out", fft, "in")?;
+
+    let frame_equalizer = fg.add_block(FrameEqualizer::with_bandwidth(bandwidth));
+    fg.connect_stream(fft, "out", frame_equalizer, "in")?;
+
+    let decoder = fg.add_block(Decoder::new());
+    fg.connect_stream(frame_equalizer, "out", decoder, "in")?;
+
+    let (tx_frame, mut rx_frame) = mpsc::channel::<Pmt>(1000);
+    let message_pipe = fg.add_block(MessagePipe::new(tx_frame));
+    fg.connect_message(decoder, "rx_frames", message_pipe, "in")?;
+
+    run_with_timeout(fg, Duration::from_secs(60))?;
+
+    let mut frames = Vec::new();
+    while let Ok(Some(p)) = rx_frame.try_next() {
+        if let Pmt::Blob(f) = p {
+            frames.push(f);
+        }
+    }
+    Ok(frames)
+}
+
+fn loopback(name: &str, mcs: &[Mcs], bandwidth: Bandwidth) -> Result<()> {
+    let frames: Vec<(Vec<u8>, Mcs)> = mcs
+        .iter()
+        .enumerate()
+        .map(|(i, m)| (frame(i as u16, format!("FutureSDR {m:?}").as_bytes()), *m))
+        .collect();
+
+    let messages = frames.iter().map(|(f, m)| message(f, *m)).collect();
+    let samples = transmit(messages, bandwidth)?;
+    let received = receive(samples, bandwidth)?;
+
+    // the receiver strips the FCS
+    let expected: Vec<Vec<u8>> = frames.iter().map(|(f, _)| f.clone()).collect();
+    assert_frames_eq(name, &received, &strip_fcs(&expected));
+    Ok(())
+}
+
+fn mpdus(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| frame(i as u16, format!("FutureSDR A-MPDU {i}").as_bytes()))
+        .collect()
+}
+
+#[test]
+fn ht20_mcs0() -> Result<()> {
+    loopback("ht20_mcs0", &[Mcs::HtMcs0], Bandwidth::Bw20)
+}
+
+#[test]
+fn ht40_mcs0() -> Result<()> {
+    loopback("ht40_mcs0", &[Mcs::HtMcs0], Bandwidth::Bw40)
+}
+
+#[test]
+fn ht20() -> Result<()> {
+    loopback("ht20", &HT, Bandwidth::Bw20)
+}
+
+#[test]
+fn ht40() -> Result<()> {
+    loopback("ht40", &HT, Bandwidth::Bw40)
+}
+
+#[test]
+fn legacy_duplicate() -> Result<()> {
+    loopback(
+        "legacy_duplicate",
+        &[Mcs::Bpsk_1_2, Mcs::Qpsk_3_4, Mcs::Qam64_3_4],
+        Bandwidth::Bw40,
+    )
+}
+
+#[test]
+fn parse_ht_mcs() {
+    assert_eq!(Mcs::parse("htmcs7"), Ok(Mcs::HtMcs7));
+    assert_eq!(Mcs::parse("mcs0"), Ok(Mcs::HtMcs0));
+    assert!(Mcs::parse("mcs8").is_err());
+    assert_eq!(Bandwidth::parse("40MHz"), Ok(Bandwidth::Bw40));
+}
+
+#[test]
+fn aggregate_layout() {
+    let mpdus = vec![vec![1u8; 5], vec![2u8; 8]];
+    let ampdu = wlan::aggregate(&mpdus);
+    // delimiter, MPDU, padding, delimiter, MPDU
+    assert_eq!(ampdu.len(), 4 + 5 + 3 + 4 + 8);
+    for (offset, len) in [(0, 5), (12, 8)] {
+        let d = &ampdu[offset..offset + 4];
+        assert_eq!((u16::from_le_bytes([d[0], d[1]]) >> 4) as usize, len);
+        assert_eq!(d[3], 0x4e);
+    }
+    assert_eq!(&ampdu[4..9], &mpdus[0][..]);
+    assert_eq!(&ampdu[9..12], &[0, 0, 0]);
+    assert_eq!(&ampdu[16..], &mpdus[1][..]);
+}
+
+#[test]
+fn ht20_ampdu() -> Result<()> {
+    let mpdus = mpdus(3);
+    let samples = transmit(vec![ampdu_message(&mpdus, Mcs::HtMcs3)], Bandwidth::Bw20)?;
+    let received = receive(samples, Bandwidth::Bw20)?;
+    assert_frames_eq("ht20_ampdu", &received, &strip_fcs(&mpdus));
+    Ok(())
+}
+
+#[test]
+fn ht40_ampdu() -> Result<()> {
+    let mpdus = mpdus(3);
+    let samples = transmit(vec![ampdu_message(&mpdus, Mcs::HtMcs5)], Bandwidth::Bw40)?;
+    let received = receive(samples, Bandwidth::Bw40)?;
+    assert_frames_eq("ht40_ampdu", &received, &strip_fcs(&mpdus));
+    Ok(())
+}
+
+#[test]
+fn ampdu_corrupted_mpdu() -> Result<()> {
+    let mut mpdus = mpdus(3);
+    // invalidate the FCS of the second MPDU, the receiver has to find the third delimiter
+    mpdus[1][30] ^= 0xff;
+    let samples = transmit(vec![ampdu_message(&mpdus, Mcs::HtMcs0)], Bandwidth::Bw20)?;
+    let received = receive(samples, Bandwidth::Bw20)?;
+    let expected = strip_fcs(&[mpdus[0].clone(), mpdus[2].clone()]);
+    assert_frames_eq("ampdu_corrupted_mpdu", &received, &expected);
+    Ok(())
+}
+
+#[test]
+fn ampdu_requires_ht() -> Result<()> {
+    let mpdus = mpdus(2);
+    let samples = transmit(vec![ampdu_message(&mpdus, Mcs::Qpsk_1_2)], Bandwidth::Bw20)?;
+    assert!(receive(samples, Bandwidth::Bw20)?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,213 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+use wlan::Mac;
+use wlan::Mcs;
+
+const LOCAL: [u8; 6] = [0x42; 6];
+const REMOTE: [u8; 6] = [0x23; 6];
+
+fn data_frame(sequence_number: u16, retry: bool) -> Vec<u8> {
+    let mut frame = vec![0x08, if retry { 0x08 } else { 0x00 }, 0, 0];
+    frame.extend_from_slice(&LOCAL);
+    frame.extend_from_slice(&REMOTE);
+    frame.extend_from_slice(&[0xff; 6]);
+    frame.extend_from_slice(&(sequence_number << 4).to_le_bytes());
+    frame.extend_from_slice(b"payload");
+    frame
+}
+
+#[test]
+fn ack_and_duplicates() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let (tx_sender, mut tx_receiver) = mpsc::channel(10);
+    let (rx_sender, mut rx_receiver) = mpsc::channel(10);
+    let mac = fg.add_block(Mac::new(LOCAL, REMOTE, [0xff; 6]));
+    let tx_pipe = fg.add_block(MessagePipe::new(tx_sender));
+    let rx_pipe = fg.add_block(MessagePipe::new(rx_sender));
+    fg.connect_message(mac, "tx", tx_pipe, "in")?;
+    fg.connect_message(mac, "rx", rx_pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut fg_handle) = rt.start_sync(fg);
+    block_on(async {
+        for frame in [data_frame(7, false), data_frame(7, true)] {
+            fg_handle
+                .callback(mac, "rx", Pmt::Blob(frame))
+                .await
+                .unwrap();
+        }
+        fg_handle.terminate_and_wait().await.unwrap();
+    });
+
+    for _ in 0..2 {
+        match tx_receiver.try_next() {
+            Ok(Some(Pmt::Any(a))) => {
+                let (ack, mcs) = a.downcast_ref::<(Vec<u8>, Option<Mcs>)>().unwrap();
+                assert_eq!(ack.len(), 14);
+                assert_eq!(ack[0], 0xd4);
+                assert_eq!(&ack[4..10], &REMOTE);
+                assert!(matches!(mcs, Some(Mcs::Bpsk_1_2)));
+            }
+            p => panic!("unexpected message {p:?}"),
+        }
+    }
+
+    match rx_receiver.try_next() {
+        Ok(Some(Pmt::Blob(b))) => assert_eq!(b, data_frame(7, false)),
+        p => panic!("unexpected message {p:?}"),
+    }
+    assert!(!matches!(rx_receiver.try_next(), Ok(Some(Pmt::Blob(_)))));
+
+    Ok(())
+}
+
+fn ack(receiver: &[u8; 6]) -> Vec<u8> {
+    let mut ack = vec![0xd4, 0, 0, 0];
+    ack.extend_from_slice(receiver);
+    let crc = crc32fast::hash(&ack);
+    ack.extend_from_slice(&crc.to_le_bytes());
+    ack
+}
+
+/// Frames posted to the `tx` output
+fn sent(rx: &mut mpsc::Receiver<Pmt>) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    while let Ok(Some(p)) = rx.try_next() {
+        match p {
+            Pmt::Any(a) => {
+                let (frame, _) = a.downcast_ref::<(Vec<u8>, Option<Mcs>)>().unwrap();
+                frames.push(frame.clone());
+            }
+            p => panic!("unexpected message {p:?}"),
+        }
+    }
+    frames
+}
+
+/// Check that `frame` is a (re)transmission of the data frame with the given sequence number
+fn check_data(frame: &[u8], sequence_number: u16, payload: &[u8], retry: bool) {
+    let len = frame.len() - 4;
+    assert_eq!(frame[0], 0x08);
+    assert_eq!(frame[1] & 0x08 != 0, retry);
+    assert_eq!(&frame[4..10], &REMOTE);
+    assert_eq!(&frame[10..16], &LOCAL);
+    assert_eq!(
+        u16::from_le_bytes([frame[22], frame[23]]) >> 4,
+        sequence_number
+    );
+    assert_eq!(&frame[24..len], payload);
+    assert_eq!(crc32fast::hash(&frame[0..len]).to_le_bytes(), frame[len..]);
+}
+
+#[test]
+fn retransmit_until_ack() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let (tx_sender, mut tx_receiver) = mpsc::channel(100);
+    let mac = fg.add_block(Mac::with_options(
+        LOCAL,
+        REMOTE,
+        [0xff; 6],
+        10,
+        Duration::from_millis(50),
+    ));
+    let tx_pipe = fg.add_block(MessagePipe::new(tx_sender));
+    fg.connect_message(mac, "tx", tx_pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut fg_handle) = rt.start_sync(fg);
+    let (before, after) = block_on(async {
+        for payload in [b"first", b"secnd"] {
+            fg_handle
+                .callback(mac, "tx", Pmt::Blob(payload.to_vec()))
+                .await
+                .unwrap();
+        }
+        // no ACK: the first frame is retransmitted, the second frame waits
+        Timer::after(Duration::from_millis(130)).await;
+        let before = sent(&mut tx_receiver);
+
+        fg_handle
+            .callback(mac, "rx", Pmt::Blob(ack(&LOCAL)))
+            .await
+            .unwrap();
+        Timer::after(Duration::from_millis(20)).await;
+        let after = sent(&mut tx_receiver);
+
+        fg_handle.terminate_and_wait().await.unwrap();
+        (before, after)
+    });
+
+    assert!(before.len() >= 2, "{} transmissions", before.len());
+    assert!(before.len() <= 4, "{} transmissions", before.len());
+    check_data(&before[0], 0, b"first", false);
+    for f in &before[1..] {
+        check_data(f, 0, b"first", true);
+    }
+
+    // the ACK releases the second frame
+    assert_eq!(after.len(), 1);
+    check_data(&after[0], 1, b"secnd", false);
+    Ok(())
+}
+
+#[test]
+fn retry_limit() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let (tx_sender, mut tx_receiver) = mpsc::channel(100);
+    let mac = fg.add_block(Mac::with_options(
+        LOCAL,
+        REMOTE,
+        [0xff; 6],
+        2,
+        Duration::from_millis(30),
+    ));
+    let tx_pipe = fg.add_block(MessagePipe::new(tx_sender));
+    fg.connect_message(mac, "tx", tx_pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut fg_handle) = rt.start_sync(fg);
+    let (first, second) = block_on(async {
+        fg_handle
+            .callback(mac, "tx", Pmt::Blob(b"first".to_vec()))
+            .await
+            .unwrap();
+        // the MAC gives up after two retries
+        Timer::after(Duration::from_millis(200)).await;
+        let first = sent(&mut tx_receiver);
+
+        // the next frame is sent right away
+        fg_handle
+            .callback(mac, "tx", Pmt::Blob(b"secnd".to_vec()))
+            .await
+            .unwrap();
+        fg_handle
+            .callback(mac, "rx", Pmt::Blob(ack(&LOCAL)))
+            .await
+            .unwrap();
+        Timer::after(Duration::from_millis(100)).await;
+        let second = sent(&mut tx_receiver);
+
+        fg_handle.terminate_and_wait().await.unwrap();
+        (first, second)
+    });
+
+    assert_eq!(first.len(), 3);
+    check_data(&first[0], 0, b"first", false);
+    check_data(&first[1], 0, b"first", true);
+    check_data(&first[2], 0, b"first", true);
+
+    assert_eq!(second.len(), 1);
+    check_data(&second[0], 1, b"secnd", false);
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use wlan::FrameParam;
+use wlan::Mcs;
+use wlan::ViterbiDecoder;
+
+fn encode(data: &[u8], mcs: Mcs) -> Vec<u8> {
+    let mut state = 0u8;
+    let mut encoded = Vec::new();
+    for b in data {
+        state = ((state << 1) & 0x7e) | b;
+        encoded.push((state & 0o155).count_ones() as u8 % 2);
+        encoded.push((state & 0o117).count_ones() as u8 % 2);
+    }
+
+    let pattern = mcs.depuncture_pattern();
+    encoded
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| pattern[i % pattern.len()] == 1)
+        .map(|(_, b)| b)
+        .collect()
+}
+
+#[test]
+fn viterbi_roundtrip() {
+    roundtrip(ViterbiDecoder::new());
+}
+
+#[test]
+fn viterbi_roundtrip_generic() {
+    roundtrip(ViterbiDecoder::generic());
+}
+
+fn roundtrip(mut decoder: ViterbiDecoder) {
+    for mcs in [
+        Mcs::Bpsk_1_2,
+        Mcs::Bpsk_3_4,
+        Mcs::Qpsk_1_2,
+        Mcs::Qpsk_3_4,
+        Mcs::Qam16_1_2,
+        Mcs::Qam16_3_4,
+        Mcs::Qam64_2_3,
+        Mcs::Qam64_3_4,
+    ] {
+        let frame = FrameParam::new(mcs, 100);
+        let n_bits = frame.n_symbols() * mcs.n_dbps();
+        let mut data: Vec<u8> = (0..n_bits)
+            .map(|i| ((i * 7 + i / 3) % 5 % 2) as u8)
+            .collect();
+        data[frame.n_data_bits() - 6..frame.n_data_bits()].fill(0);
+
+        let mut encoded = encode(&data, mcs);
+        // flip a few bits
+        for i in (0..encoded.len()).step_by(97) {
+            encoded[i] ^= 1;
+        }
+
+        let mut decoded = vec![0; n_bits + 8];
+        decoder.decode(frame.clone(), &encoded, &mut decoded);
+        assert_eq!(
+            &decoded[0..frame.n_data_bits() - 6],
+            &data[0..frame.n_data_bits() - 6],
+            "{mcs:?}"
+        );
+    }
+}
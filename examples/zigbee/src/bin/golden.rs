@@ -0,0 +1,31 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use futuresdr::anyhow::Result;
+
+use zigbee::golden::generate;
+use zigbee::Phy;
+
+/// Generate golden samples for the receiver regression tests
+#[derive(Parser, Debug)]
+#[clap(version)]
+struct Args {
+    /// Output directory
+    #[clap(short, long, default_value = "tests/golden")]
+    output: PathBuf,
+    /// Frames per PHY
+    #[clap(short, long, default_value_t = 10)]
+    frames: usize,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    std::fs::create_dir_all(&args.output)?;
+
+    for phy in [Phy::Oqpsk, Phy::Bpsk] {
+        let name = generate(&args.output, phy, args.frames)?;
+        println!("wrote {name} ({} frames)", args.frames);
+    }
+
+    Ok(())
+}
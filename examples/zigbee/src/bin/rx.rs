@@ -1,19 +1,23 @@
 use clap::Parser;
-use futuresdr::anyhow::Result;
+use futuresdr::anyhow::{ensure, Result};
 use futuresdr::blocks::seify::SourceBuilder;
 use futuresdr::blocks::Apply;
 use futuresdr::blocks::BlobToUdp;
 use futuresdr::blocks::FileSource;
 use futuresdr::blocks::NullSink;
+use futuresdr::blocks::SymbolSync;
 use futuresdr::macros::connect;
 use futuresdr::num_complex::Complex32;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Runtime;
 
+use zigbee::bpsk_chip_rate;
 use zigbee::parse_channel;
-use zigbee::ClockRecoveryMm;
+use zigbee::BpskDemodulator;
 use zigbee::Decoder;
 use zigbee::Mac;
+use zigbee::Phy;
+use zigbee::BPSK_SAMPLES_PER_CHIP;
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -30,12 +34,15 @@ struct Args {
     /// Gain
     #[clap(short, long, default_value_t = 30.0)]
     gain: f64,
-    /// Sample Rate
+    /// Sample Rate (O-QPSK only, BPSK uses four samples per chip)
     #[clap(short, long, default_value_t = 4e6)]
     sample_rate: f64,
-    /// Zigbee Channel Number (11..26)
+    /// Zigbee Channel Number (0..26, 0..10 for BPSK, 11..26 for O-QPSK)
     #[clap(id = "channel", short, long, value_parser = parse_channel, default_value = "26")]
     freq: f64,
+    /// PHY
+    #[clap(long, value_enum, default_value_t = Phy::Oqpsk)]
+    phy: Phy,
     /// UDP Sink [address:port]
     #[clap(short, long)]
     udp_addr: Option<String>,
@@ -45,6 +52,14 @@ fn main() -> Result<()> {
     let args = Args::parse();
     println!("Configuration: {args:?}");
 
+    let sample_rate = match args.phy {
+        Phy::Oqpsk => {
+            ensure!(args.freq > 2e9, "O-QPSK requires a 2.4 GHz channel");
+            args.sample_rate
+        }
+        Phy::Bpsk => bpsk_chip_rate(args.freq)? * BPSK_SAMPLES_PER_CHIP as f64,
+    };
+
     let mut fg = Flowgraph::new();
 
     let src = match args.file {
@@ -52,7 +67,7 @@ fn main() -> Result<()> {
         None => {
             let mut src = SourceBuilder::new()
                 .frequency(args.freq)
-                .sample_rate(args.sample_rate)
+                .sample_rate(sample_rate)
                 .gain(args.gain);
             if let Some(a) = args.antenna {
                 src = src.antenna(a);
@@ -64,34 +79,51 @@ fn main() -> Result<()> {
         }
     };
 
-    let mut last: Complex32 = Complex32::new(0.0, 0.0);
-    let mut iir: f32 = 0.0;
-    let alpha = 0.00016;
-    let avg = Apply::new(move |i: &Complex32| -> f32 {
-        let phase = (last.conj() * i).arg();
-        last = *i;
-        iir = (1.0 - alpha) * iir + alpha * phase;
-        phase - iir
-    });
-
-    let omega = 2.0;
-    let gain_omega = 0.000225;
-    let mu = 0.5;
-    let gain_mu = 0.03;
-    let omega_relative_limit = 0.0002;
-    let mm = ClockRecoveryMm::new(omega, gain_omega, mu, gain_mu, omega_relative_limit);
-
-    let decoder = Decoder::new(6);
     let mac = Mac::new();
     let snk = NullSink::<u8>::new();
+    connect!(fg, mac > snk);
+
+    match args.phy {
+        Phy::Oqpsk => {
+            let mut last: Complex32 = Complex32::new(0.0, 0.0);
+            let mut iir: f32 = 0.0;
+            let alpha = 0.00016;
+            let avg = Apply::new(move |i: &Complex32| -> f32 {
+                let phase = (last.conj() * i).arg();
+                last = *i;
+                iir = (1.0 - alpha) * iir + alpha * phase;
+                phase - iir
+            });
+
+            let omega = 2.0;
+            let gain_omega = 0.000225;
+            let mu = 0.5;
+            let gain_mu = 0.03;
+            let omega_relative_limit = 0.0002;
+            let mm =
+                SymbolSync::<f32>::new(omega, gain_omega, mu, gain_mu, omega_relative_limit, None);
+
+            let decoder = Decoder::new(6);
 
-    connect!(fg, src > avg > mm > decoder;
-                 mac > snk;
-                 decoder | mac.rx);
+            connect!(fg, src > avg > mm > decoder;
+                         decoder | mac.rx);
 
-    if let Some(u) = args.udp_addr {
-        let blob_to_udp = BlobToUdp::new(u);
-        connect!(fg, decoder | blob_to_udp);
+            if let Some(u) = args.udp_addr {
+                let blob_to_udp = BlobToUdp::new(u);
+                connect!(fg, decoder | blob_to_udp);
+            }
+        }
+        Phy::Bpsk => {
+            let demodulator = BpskDemodulator::new(BPSK_SAMPLES_PER_CHIP, 0.5);
+
+            connect!(fg, src > demodulator;
+                         demodulator | mac.rx);
+
+            if let Some(u) = args.udp_addr {
+                let blob_to_udp = BlobToUdp::new(u);
+                connect!(fg, demodulator | blob_to_udp);
+            }
+        }
     }
 
     let blob_to_udp = BlobToUdp::new("127.0.0.1:55555");
@@ -1,23 +1,28 @@
 use clap::Parser;
 use std::time::Duration;
 
-use futuresdr::anyhow::Result;
+use futuresdr::anyhow::{ensure, Result};
 use futuresdr::async_io::block_on;
 use futuresdr::async_io::Timer;
 use futuresdr::blocks::seify::SinkBuilder;
 use futuresdr::blocks::seify::SourceBuilder;
 use futuresdr::blocks::Apply;
+use futuresdr::blocks::SymbolSync;
 use futuresdr::num_complex::Complex32;
 use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
 
+use zigbee::bpsk_chip_rate;
 use zigbee::modulator;
 use zigbee::parse_channel;
-use zigbee::ClockRecoveryMm;
+use zigbee::BpskDemodulator;
+use zigbee::BpskModulator;
 use zigbee::Decoder;
 use zigbee::IqDelay;
 use zigbee::Mac;
+use zigbee::Phy;
+use zigbee::BPSK_SAMPLES_PER_CHIP;
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -30,6 +35,18 @@ struct Args {
     rx_gain: f64,
     #[clap(long, default_value_t = 18.0)]
     tx_gain: f64,
+    #[clap(long, value_enum, default_value_t = Phy::Oqpsk)]
+    phy: Phy,
+}
+
+fn sample_rate(phy: Phy, freq: f64) -> Result<f64> {
+    match phy {
+        Phy::Oqpsk => {
+            ensure!(freq > 2e9, "O-QPSK requires a 2.4 GHz channel");
+            Ok(4e6)
+        }
+        Phy::Bpsk => Ok(bpsk_chip_rate(freq)? * BPSK_SAMPLES_PER_CHIP as f64),
+    }
 }
 
 fn main() -> Result<()> {
@@ -42,19 +59,28 @@ fn main() -> Result<()> {
     // TRANSMITTER
     // ========================================
     let mac = fg.add_block(Mac::new());
-    let modulator = fg.add_block(modulator());
-    let iq_delay = fg.add_block(IqDelay::new());
     let snk = fg.add_block(
         SinkBuilder::new()
             .frequency(args.tx_freq)
-            .sample_rate(4e6)
+            .sample_rate(sample_rate(args.phy, args.tx_freq)?)
             .gain(args.tx_gain)
             .build()?,
     );
 
-    fg.connect_stream(mac, "out", modulator, "in")?;
-    fg.connect_stream(modulator, "out", iq_delay, "in")?;
-    fg.connect_stream(iq_delay, "out", snk, "in")?;
+    match args.phy {
+        Phy::Oqpsk => {
+            let modulator = fg.add_block(modulator());
+            let iq_delay = fg.add_block(IqDelay::new());
+            fg.connect_stream(mac, "out", modulator, "in")?;
+            fg.connect_stream(modulator, "out", iq_delay, "in")?;
+            fg.connect_stream(iq_delay, "out", snk, "in")?;
+        }
+        Phy::Bpsk => {
+            let modulator = fg.add_block(BpskModulator::new(BPSK_SAMPLES_PER_CHIP));
+            fg.connect_stream(mac, "out", modulator, "in")?;
+            fg.connect_stream(modulator, "out", snk, "in")?;
+        }
+    }
 
     // ========================================
     // Receiver
@@ -62,40 +88,50 @@ fn main() -> Result<()> {
     let src = fg.add_block(
         SourceBuilder::new()
             .frequency(args.rx_freq)
-            .sample_rate(4e6)
+            .sample_rate(sample_rate(args.phy, args.rx_freq)?)
             .gain(args.rx_gain)
             .build()?,
     );
 
-    let mut last: Complex32 = Complex32::new(0.0, 0.0);
-    let mut iir: f32 = 0.0;
-    let alpha = 0.00016;
-    let avg = fg.add_block(Apply::new(move |i: &Complex32| -> f32 {
-        let phase = (last.conj() * i).arg();
-        last = *i;
-        iir = (1.0 - alpha) * iir + alpha * phase;
-        phase - iir
-    }));
-
-    let omega = 2.0;
-    let gain_omega = 0.000225;
-    let mu = 0.5;
-    let gain_mu = 0.03;
-    let omega_relative_limit = 0.0002;
-    let mm = fg.add_block(ClockRecoveryMm::new(
-        omega,
-        gain_omega,
-        mu,
-        gain_mu,
-        omega_relative_limit,
-    ));
-
-    let decoder = fg.add_block(Decoder::new(6));
-
-    fg.connect_stream(src, "out", avg, "in")?;
-    fg.connect_stream(avg, "out", mm, "in")?;
-    fg.connect_stream(mm, "out", decoder, "in")?;
-    fg.connect_message(decoder, "out", mac, "rx")?;
+    match args.phy {
+        Phy::Oqpsk => {
+            let mut last: Complex32 = Complex32::new(0.0, 0.0);
+            let mut iir: f32 = 0.0;
+            let alpha = 0.00016;
+            let avg = fg.add_block(Apply::new(move |i: &Complex32| -> f32 {
+                let phase = (last.conj() * i).arg();
+                last = *i;
+                iir = (1.0 - alpha) * iir + alpha * phase;
+                phase - iir
+            }));
+
+            let omega = 2.0;
+            let gain_omega = 0.000225;
+            let mu = 0.5;
+            let gain_mu = 0.03;
+            let omega_relative_limit = 0.0002;
+            let mm = fg.add_block(SymbolSync::<f32>::new(
+                omega,
+                gain_omega,
+                mu,
+                gain_mu,
+                omega_relative_limit,
+                None,
+            ));
+
+            let decoder = fg.add_block(Decoder::new(6));
+
+            fg.connect_stream(src, "out", avg, "in")?;
+            fg.connect_stream(avg, "out", mm, "in")?;
+            fg.connect_stream(mm, "out", decoder, "in")?;
+            fg.connect_message(decoder, "out", mac, "rx")?;
+        }
+        Phy::Bpsk => {
+            let demodulator = fg.add_block(BpskDemodulator::new(BPSK_SAMPLES_PER_CHIP, 0.5));
+            fg.connect_stream(src, "out", demodulator, "in")?;
+            fg.connect_message(demodulator, "out", mac, "rx")?;
+        }
+    }
 
     let rt = Runtime::new();
     let (fg, mut handle) = rt.start_sync(fg);
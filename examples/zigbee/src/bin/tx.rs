@@ -1,7 +1,7 @@
 use clap::Parser;
 use std::time::Duration;
 
-use futuresdr::anyhow::Result;
+use futuresdr::anyhow::{ensure, Result};
 use futuresdr::async_io::block_on;
 use futuresdr::async_io::Timer;
 use futuresdr::blocks::seify::SinkBuilder;
@@ -9,10 +9,14 @@ use futuresdr::runtime::Flowgraph;
 use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
 
+use zigbee::bpsk_chip_rate;
 use zigbee::modulator;
 use zigbee::parse_channel;
+use zigbee::BpskModulator;
 use zigbee::IqDelay;
 use zigbee::Mac;
+use zigbee::Phy;
+use zigbee::BPSK_SAMPLES_PER_CHIP;
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -26,27 +30,36 @@ struct Args {
     /// Gain
     #[clap(short, long, default_value_t = 60.0)]
     gain: f64,
-    /// Sample Rate
+    /// Sample Rate (O-QPSK only, BPSK uses four samples per chip)
     #[clap(short, long, default_value_t = 4e6)]
     sample_rate: f64,
-    /// Zigbee Channel Number (11..26)
+    /// Zigbee Channel Number (0..26, 0..10 for BPSK, 11..26 for O-QPSK)
     #[clap(id = "channel", short, long, value_parser = parse_channel, default_value = "26")]
     freq: f64,
+    /// PHY
+    #[clap(long, value_enum, default_value_t = Phy::Oqpsk)]
+    phy: Phy,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
     println!("Configuration: {args:?}");
 
+    let sample_rate = match args.phy {
+        Phy::Oqpsk => {
+            ensure!(args.freq > 2e9, "O-QPSK requires a 2.4 GHz channel");
+            args.sample_rate
+        }
+        Phy::Bpsk => bpsk_chip_rate(args.freq)? * BPSK_SAMPLES_PER_CHIP as f64,
+    };
+
     let mut fg = Flowgraph::new();
 
     let mac = fg.add_block(Mac::new());
-    let modulator = fg.add_block(modulator());
-    let iq_delay = fg.add_block(IqDelay::new());
 
     let mut snk = SinkBuilder::new()
         .frequency(args.freq)
-        .sample_rate(args.sample_rate)
+        .sample_rate(sample_rate)
         .gain(args.gain);
     if let Some(a) = args.antenna {
         snk = snk.antenna(a);
@@ -57,9 +70,20 @@ fn main() -> Result<()> {
 
     let snk = fg.add_block(snk.build()?);
 
-    fg.connect_stream(mac, "out", modulator, "in")?;
-    fg.connect_stream(modulator, "out", iq_delay, "in")?;
-    fg.connect_stream(iq_delay, "out", snk, "in")?;
+    match args.phy {
+        Phy::Oqpsk => {
+            let modulator = fg.add_block(modulator());
+            let iq_delay = fg.add_block(IqDelay::new());
+            fg.connect_stream(mac, "out", modulator, "in")?;
+            fg.connect_stream(modulator, "out", iq_delay, "in")?;
+            fg.connect_stream(iq_delay, "out", snk, "in")?;
+        }
+        Phy::Bpsk => {
+            let modulator = fg.add_block(BpskModulator::new(BPSK_SAMPLES_PER_CHIP));
+            fg.connect_stream(mac, "out", modulator, "in")?;
+            fg.connect_stream(modulator, "out", snk, "in")?;
+        }
+    }
 
     let rt = Runtime::new();
     let (fg, mut handle) = rt.start_sync(fg);
@@ -0,0 +1,427 @@
+use futuresdr::anyhow::{bail, Result};
+use futuresdr::macros::async_trait;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::ItemTag;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+
+/// Chip sequence of a zero bit, a one bit uses the inverted sequence
+const CHIPS: [f32; 15] = [
+    1.0, 1.0, 1.0, 1.0, -1.0, 1.0, -1.0, 1.0, 1.0, -1.0, -1.0, 1.0, -1.0, -1.0, -1.0,
+];
+const SFD: u8 = 0xa7;
+const PADDING: usize = 10000;
+
+/// Samples per chip used by the example applications
+pub const BPSK_SAMPLES_PER_CHIP: usize = 4;
+
+/// Chip rate of the BPSK PHY for the frequency of a sub-GHz channel
+pub fn bpsk_chip_rate(freq: f64) -> Result<f64> {
+    if (freq - 868.3e6).abs() < 1.0 {
+        Ok(300e3)
+    } else if (906e6 - 1.0..=924e6 + 1.0).contains(&freq) {
+        Ok(600e3)
+    } else {
+        bail!("no BPSK channel at {freq} Hz");
+    }
+}
+
+/// Raised cosine pulse with roll-off one, `t` in chips
+fn pulse(t: f32) -> f32 {
+    if t.abs() >= 1.0 {
+        return 0.0;
+    }
+    if (t.abs() - 0.5).abs() < 1e-6 {
+        return 0.5;
+    }
+    let x = std::f32::consts::PI * t;
+    let sinc = if t == 0.0 { 1.0 } else { x.sin() / x };
+    sinc * x.cos() / (1.0 - 4.0 * t * t)
+}
+
+/// Differential encoding, spreading, and pulse shaping
+struct Spreader {
+    shape: Vec<f32>,
+    differential: bool,
+    last: f32,
+}
+
+impl Spreader {
+    fn new(samples_per_chip: usize) -> Self {
+        let shape = (0..2 * samples_per_chip)
+            .map(|i| pulse(i as f32 / samples_per_chip as f32 - 1.0))
+            .collect();
+        Self {
+            shape,
+            differential: false,
+            last: 0.0,
+        }
+    }
+
+    fn samples_per_chip(&self) -> usize {
+        self.shape.len() / 2
+    }
+
+    fn chip(&mut self, c: f32, out: &mut Vec<Complex32>) {
+        let sps = self.samples_per_chip();
+        for i in 0..sps {
+            out.push(Complex32::new(
+                self.last * self.shape[sps + i] + c * self.shape[i],
+                0.0,
+            ));
+        }
+        self.last = c;
+    }
+
+    fn byte(&mut self, b: u8, out: &mut Vec<Complex32>) {
+        for i in 0..8 {
+            self.differential ^= (b >> i) & 1 == 1;
+            let s = if self.differential { -1.0 } else { 1.0 };
+            for c in CHIPS {
+                self.chip(s * c, out);
+            }
+        }
+    }
+
+    /// Flush the last chip and reset the differential encoder
+    fn flush(&mut self, out: &mut Vec<Complex32>) {
+        self.chip(0.0, out);
+        self.differential = false;
+    }
+}
+
+#[derive(Debug)]
+enum Frame {
+    Sfd,
+    Length {
+        bits: usize,
+    },
+    Data {
+        len: usize,
+        bits: usize,
+        data: Vec<u8>,
+    },
+}
+
+/// Despreading, timing recovery, differential detection, and deframing
+struct Despreader {
+    sps: usize,
+    threshold: f32,
+    history: Vec<Complex32>,
+    index: usize,
+    /// Correlation and metric of the last three samples
+    corr: [(Complex32, f32); 3],
+    /// Samples until the next bit decision, if locked
+    next: Option<usize>,
+    last: Complex32,
+    weak: usize,
+    shift: u16,
+    byte: u8,
+    frame: Frame,
+}
+
+impl Despreader {
+    fn new(samples_per_chip: usize, threshold: f32) -> Self {
+        Self {
+            sps: samples_per_chip,
+            threshold,
+            history: vec![Complex32::new(0.0, 0.0); 15 * samples_per_chip],
+            index: 0,
+            corr: [(Complex32::new(0.0, 0.0), 0.0); 3],
+            next: None,
+            last: Complex32::new(0.0, 0.0),
+            weak: 0,
+            shift: 0,
+            byte: 0,
+            frame: Frame::Sfd,
+        }
+    }
+
+    /// Correlation with the chip sequence, ending at the current sample, and the normalized
+    /// correlation energy
+    fn correlate(&self) -> (Complex32, f32) {
+        let n = self.history.len();
+        let mut c = Complex32::new(0.0, 0.0);
+        let mut e = 0.0;
+        for (k, &chip) in CHIPS.iter().enumerate() {
+            let x = self.history[(self.index + 1 + k * self.sps) % n];
+            c += x * chip;
+            e += x.norm_sqr();
+        }
+        let metric = if e > 0.0 {
+            c.norm_sqr() / (15.0 * e)
+        } else {
+            0.0
+        };
+        (c, metric)
+    }
+
+    fn reset(&mut self) {
+        self.next = None;
+        self.frame = Frame::Sfd;
+        self.shift = 0;
+    }
+
+    fn process(&mut self, x: Complex32) -> Option<Vec<u8>> {
+        let n = self.history.len();
+        self.index = (self.index + 1) % n;
+        self.history[self.index] = x;
+
+        self.corr[0] = self.corr[1];
+        self.corr[1] = self.corr[2];
+        self.corr[2] = self.correlate();
+
+        match self.next {
+            None => {
+                // search: lock on a local maximum of the correlation
+                if self.corr[1].1 > self.threshold
+                    && self.corr[1].1 >= self.corr[0].1
+                    && self.corr[1].1 >= self.corr[2].1
+                {
+                    self.last = self.corr[1].0;
+                    self.weak = 0;
+                    self.next = Some(15 * self.sps - 1);
+                }
+                None
+            }
+            Some(0) => {
+                // early-late: pick the best of the three samples around the expected peak
+                let (best, _) = self
+                    .corr
+                    .iter()
+                    .enumerate()
+                    .max_by(|a, b| a.1 .1.total_cmp(&b.1 .1))
+                    .unwrap();
+                let (c, metric) = self.corr[best];
+                self.next = Some(15 * self.sps + best - 2);
+
+                if metric < self.threshold / 2.0 {
+                    self.weak += 1;
+                    if self.weak > 4 {
+                        self.reset();
+                        return None;
+                    }
+                } else {
+                    self.weak = 0;
+                }
+
+                let bit = (c * self.last.conj()).re < 0.0;
+                self.last = c;
+                self.bit(bit)
+            }
+            Some(k) => {
+                self.next = Some(k - 1);
+                None
+            }
+        }
+    }
+
+    fn bit(&mut self, bit: bool) -> Option<Vec<u8>> {
+        self.shift = (self.shift >> 1) | (u16::from(bit) << 15);
+        self.byte = (self.byte >> 1) | (u8::from(bit) << 7);
+
+        match &mut self.frame {
+            Frame::Sfd => {
+                if self.shift == u16::from(SFD) << 8 {
+                    self.frame = Frame::Length { bits: 0 };
+                }
+            }
+            Frame::Length { bits } => {
+                *bits += 1;
+                if *bits == 8 {
+                    let len = (self.byte & 0x7f) as usize;
+                    if len < 3 {
+                        self.reset();
+                    } else {
+                        self.frame = Frame::Data {
+                            len,
+                            bits: 0,
+                            data: Vec::with_capacity(len),
+                        };
+                    }
+                }
+            }
+            Frame::Data { len, bits, data } => {
+                *bits += 1;
+                if *bits == 8 {
+                    *bits = 0;
+                    data.push(self.byte);
+                    if data.len() == *len {
+                        let data = std::mem::take(data);
+                        self.reset();
+                        return Some(data);
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Modulator for the 868/915 MHz BPSK PHY of IEEE 802.15.4.
+///
+/// Bits are differentially encoded, spread with a 15-chip sequence, and shaped with a raised
+/// cosine pulse (roll-off one). The input is expected to be framed by the [Mac](crate::Mac),
+/// i.e., each frame starts with a [`Tag::Id`] with its length in bytes. Each frame is output as a
+/// burst, padded with zeros.
+pub struct BpskModulator {
+    spreader: Spreader,
+    left: usize,
+    buf: Vec<Complex32>,
+    offset: usize,
+}
+
+impl BpskModulator {
+    pub fn new(samples_per_chip: usize) -> Block {
+        assert!(samples_per_chip >= 2);
+
+        Block::new(
+            BlockMetaBuilder::new("BpskModulator").build(),
+            StreamIoBuilder::new()
+                .add_input::<u8>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            Self {
+                spreader: Spreader::new(samples_per_chip),
+                left: 0,
+                buf: Vec::new(),
+                offset: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for BpskModulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _m: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<u8>();
+        let o = sio.output(0).slice::<Complex32>();
+        let sps = self.spreader.samples_per_chip();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+
+        loop {
+            if self.offset < self.buf.len() {
+                let n = std::cmp::min(o.len() - produced, self.buf.len() - self.offset);
+                o[produced..produced + n].copy_from_slice(&self.buf[self.offset..self.offset + n]);
+                produced += n;
+                self.offset += n;
+                if self.offset < self.buf.len() {
+                    break;
+                }
+            }
+            self.buf.clear();
+            self.offset = 0;
+
+            if consumed == i.len() {
+                break;
+            }
+
+            if self.left == 0 {
+                if let Some(ItemTag {
+                    tag: Tag::Id(id), ..
+                }) = sio
+                    .input(0)
+                    .tags()
+                    .iter()
+                    .find(|x| x.index == consumed)
+                    .cloned()
+                {
+                    self.left = id as usize;
+                    let len = 2 * PADDING + (self.left * 8 * 15 + 1) * sps;
+                    sio.output(0)
+                        .add_tag(produced, Tag::NamedUsize("burst_start".to_string(), len));
+                    self.buf.resize(PADDING, Complex32::new(0.0, 0.0));
+                }
+            }
+
+            self.spreader.byte(i[consumed], &mut self.buf);
+            consumed += 1;
+
+            if self.left > 0 {
+                self.left -= 1;
+                if self.left == 0 {
+                    self.spreader.flush(&mut self.buf);
+                    self.buf
+                        .extend(std::iter::repeat(Complex32::new(0.0, 0.0)).take(PADDING));
+                }
+            }
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+        if sio.input(0).finished() && consumed == i.len() && self.offset == self.buf.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Demodulator for the 868/915 MHz BPSK PHY of IEEE 802.15.4.
+///
+/// The input is correlated with the chip sequence. After a correlation peak above the
+/// `threshold` (normalized, between 0 and 1), the bit timing is tracked with an early-late
+/// decision and bits are detected differentially, which does not require carrier recovery.
+/// Received frames are posted to the `out` message output, including their CRC.
+pub struct BpskDemodulator {
+    despreader: Despreader,
+}
+
+impl BpskDemodulator {
+    pub fn new(samples_per_chip: usize, threshold: f32) -> Block {
+        assert!(samples_per_chip >= 2);
+
+        Block::new(
+            BlockMetaBuilder::new("BpskDemodulator").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            Self {
+                despreader: Despreader::new(samples_per_chip, threshold),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for BpskDemodulator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        for x in i.iter() {
+            if let Some(frame) = self.despreader.process(*x) {
+                mio.post(0, Pmt::Blob(frame)).await;
+            }
+        }
+
+        sio.input(0).consume(i.len());
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+//! Golden samples for the receiver regression tests
+use std::path::Path;
+use std::time::Duration;
+
+use futuresdr::anyhow::{Context, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::FileSink;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::testing::write_frames;
+
+use crate::modulator;
+use crate::BpskModulator;
+use crate::IqDelay;
+use crate::Mac;
+use crate::Phy;
+use crate::BPSK_SAMPLES_PER_CHIP;
+
+/// Split the MAC output, i.e., preamble, SFD, length, and frame, into frames
+pub fn split_frames(mut bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    let mut frames = Vec::new();
+    while !bytes.is_empty() {
+        let len = *bytes.get(4).context("truncated MAC output")? as usize;
+        let frame = bytes.get(5..5 + len).context("truncated MAC output")?;
+        frames.push(frame.to_vec());
+        bytes = &bytes[5 + len..];
+    }
+    Ok(frames)
+}
+
+/// Write samples with `frames` frames of the given PHY to `dir`, together with the frames that
+/// the receiver should decode, returning the name of the fixture
+pub fn generate(dir: &Path, phy: Phy, frames: usize) -> Result<String> {
+    let name = match phy {
+        Phy::Oqpsk => "zigbee_oqpsk",
+        Phy::Bpsk => "zigbee_bpsk",
+    };
+
+    let mut fg = Flowgraph::new();
+    let mac = fg.add_block(Mac::new());
+    let path = dir.join(format!("{name}.cf32"));
+    let snk = fg.add_block(FileSink::<Complex32>::new(path.to_string_lossy()));
+    match phy {
+        Phy::Oqpsk => {
+            let modulator = fg.add_block(modulator());
+            let iq_delay = fg.add_block(IqDelay::new());
+            fg.connect_stream(mac, "out", modulator, "in")?;
+            fg.connect_stream(modulator, "out", iq_delay, "in")?;
+            fg.connect_stream(iq_delay, "out", snk, "in")?;
+        }
+        Phy::Bpsk => {
+            let modulator = fg.add_block(BpskModulator::new(BPSK_SAMPLES_PER_CHIP));
+            fg.connect_stream(mac, "out", modulator, "in")?;
+            fg.connect_stream(modulator, "out", snk, "in")?;
+        }
+    }
+    let mac_out = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    fg.connect_stream(mac, "out", mac_out, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    let fg = block_on(async {
+        for i in 0..frames {
+            handle
+                .call(
+                    mac,
+                    "tx",
+                    Pmt::Blob(format!("FutureSDR {i}").as_bytes().to_vec()),
+                )
+                .await?;
+        }
+        // give the transmitter time to process the queue
+        Timer::after(Duration::from_secs(1)).await;
+        handle.terminate().await?;
+        task.await
+    })?;
+
+    // the receiver outputs frames with FCS, i.e., what the MAC sent
+    let mac_out = fg
+        .kernel::<VectorSink<u8>>(mac_out)
+        .context("wrong block type")?;
+    let expected = split_frames(mac_out.items())?;
+    write_frames(dir.join(format!("{name}.frames")), &expected)?;
+
+    Ok(name.to_string())
+}
@@ -1,15 +1,22 @@
 #![allow(clippy::new_ret_no_self)]
-mod clock_recovery_mm;
-pub use clock_recovery_mm::ClockRecoveryMm;
+mod bpsk;
+pub use bpsk::bpsk_chip_rate;
+pub use bpsk::BpskDemodulator;
+pub use bpsk::BpskModulator;
+pub use bpsk::BPSK_SAMPLES_PER_CHIP;
 
 mod decoder;
 pub use decoder::Decoder;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod golden;
+
 mod iq_delay;
 pub use iq_delay::IqDelay;
 
 mod mac;
 pub use mac::Mac;
+pub use mac::MacBuilder;
 
 mod modulator;
 pub use modulator::modulator;
@@ -22,8 +29,22 @@ pub mod wasm_worker;
 
 use futuresdr::anyhow::{bail, Result};
 
+/// PHY layer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(clap::ValueEnum))]
+pub enum Phy {
+    /// 2.4 GHz O-QPSK
+    Oqpsk,
+    /// 868/915 MHz BPSK
+    Bpsk,
+}
+
 pub fn channel_to_freq(chan: u32) -> Result<f64> {
-    if (11..=26).contains(&chan) {
+    if chan == 0 {
+        Ok(868.3e6)
+    } else if (1..=10).contains(&chan) {
+        Ok((906.0 + 2.0 * (chan as f64 - 1.0)) * 1e6)
+    } else if (11..=26).contains(&chan) {
         Ok((2400.0 + 5.0 * (chan as f64 - 10.0)) * 1e6)
     } else {
         bail!("wrong channel {chan}");
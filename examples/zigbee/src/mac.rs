@@ -1,4 +1,7 @@
+use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::time::Duration;
+use std::time::Instant;
 
 use futuresdr::anyhow::Result;
 use futuresdr::log::{debug, info, warn};
@@ -19,58 +22,170 @@ use futuresdr::runtime::WorkIo;
 const MAX_FRAMES: usize = 128;
 const MAX_FRAME_SIZE: usize = 127;
 const FRAME_CONTROL: u16 = 0x8841;
+const FRAME_TYPE_COMMAND: u16 = 0x0003;
+const ACK_REQUEST: u16 = 0x0020;
+const PAN_ID_COMPRESSION: u16 = 0x0040;
+const DESTINATION_SHORT: u16 = 0x0800;
+const DESTINATION_EXTENDED: u16 = 0x0c00;
+const SOURCE_EXTENDED: u16 = 0xc000;
+const ASSOCIATION_REQUEST: u8 = 0x01;
+const ASSOCIATION_RESPONSE: u8 = 0x02;
+/// Capability information of the association request: allocate a short address
+const ALLOCATE_ADDRESS: u8 = 0x80;
+const ASSOCIATION_SUCCESSFUL: u8 = 0x00;
+const PAN_AT_CAPACITY: u8 = 0x01;
+const BROADCAST: u16 = 0xffff;
 const DESTINATION_PAN: u16 = 0x1aaa;
 const DESTINATION_ADDRESS: u16 = 0xffff;
 const SOURCE_ADDRESS: u16 = 0x3344;
+/// Unit backoff period (20 symbols at 62.5 ksym/s)
+const BACKOFF_PERIOD: Duration = Duration::from_micros(320);
+
+/// Frame that waits for an ACK
+struct Pending {
+    frame: Vec<u8>,
+    sequence_number: u8,
+    retries: usize,
+    deadline: Option<Instant>,
+}
+
+/// Result of the channel access
+enum Access {
+    Clear,
+    Backoff(Instant),
+    Failure,
+}
+
+/// Result of loading the next frame
+enum Next {
+    Frame,
+    Wait(Instant),
+    Again,
+    Idle,
+}
+
+/// Address field of a frame
+#[derive(Debug, PartialEq, Eq)]
+enum Address {
+    None,
+    Short(u16),
+    Extended(u64),
+}
+
+/// Frame control and addressing fields
+struct Header {
+    fc: u16,
+    destination_pan: Option<u16>,
+    destination: Address,
+    source: Address,
+    /// Offset of the payload
+    payload: usize,
+}
+
+impl Header {
+    /// Parse the header of a frame with CRC
+    fn parse(data: &[u8]) -> Option<Self> {
+        let fc = u16::from_le_bytes([*data.first()?, *data.get(1)?]);
+        let mut index = 3;
+
+        let destination_mode = (fc >> 10) & 0x3;
+        let destination_pan = if destination_mode != 0 {
+            let pan = u16::from_le_bytes([*data.get(index)?, *data.get(index + 1)?]);
+            index += 2;
+            Some(pan)
+        } else {
+            None
+        };
+        let destination = Self::address(data, &mut index, destination_mode)?;
+
+        let source_mode = (fc >> 14) & 0x3;
+        if source_mode != 0 && !(fc & PAN_ID_COMPRESSION != 0 && destination_mode != 0) {
+            index += 2;
+        }
+        let source = Self::address(data, &mut index, source_mode)?;
+
+        if index + 2 > data.len() {
+            return None;
+        }
+        Some(Self {
+            fc,
+            destination_pan,
+            destination,
+            source,
+            payload: index,
+        })
+    }
+
+    fn address(data: &[u8], index: &mut usize, mode: u16) -> Option<Address> {
+        let address = match mode {
+            0 => Address::None,
+            2 => Address::Short(u16::from_le_bytes(
+                data.get(*index..*index + 2)?.try_into().ok()?,
+            )),
+            3 => Address::Extended(u64::from_le_bytes(
+                data.get(*index..*index + 8)?.try_into().ok()?,
+            )),
+            _ => return None,
+        };
+        *index += match address {
+            Address::None => 0,
+            Address::Short(_) => 2,
+            Address::Extended(_) => 8,
+        };
+        Some(address)
+    }
+}
+
+/// State of the PAN association
+enum Association {
+    Idle,
+    Pending { pan_id: u16 },
+    Associated,
+}
+
+/// State of the unslotted CSMA-CA
+struct Csma {
+    backoffs: usize,
+    exponent: u32,
+    until: Option<Instant>,
+}
 
 pub struct Mac {
     tx_frames: VecDeque<Vec<u8>>,
+    tx_acks: VecDeque<u8>,
+    tx_commands: VecDeque<Vec<u8>>,
     current_frame: [u8; 256],
     sequence_number: u8,
     current_index: usize,
     current_len: usize,
     n_received: u64,
     n_sent: u64,
+    pan_id: u16,
+    address: u16,
+    extended_address: u64,
+    destination: u16,
+    promiscuous: bool,
+    ack: bool,
+    max_retries: usize,
+    ack_timeout: Duration,
+    csma: bool,
+    min_be: u32,
+    max_be: u32,
+    max_backoffs: usize,
+    association: Association,
+    coordinator: Option<(u16, u16)>,
+    devices: HashMap<u64, u16>,
+    channel_busy: bool,
+    pending: Option<Pending>,
+    backoff: Csma,
+    rng: u32,
 }
 
 impl Mac {
+    /// Create a promiscuous MAC with default addresses, forwarding all frames with valid CRC
+    /// without acknowledging them
     pub fn new() -> Block {
-        let mut b = [0; 256];
-        b[0] = 0x0;
-        b[1] = 0x0;
-        b[2] = 0x0;
-        b[3] = 0xa7;
-        b[4] = 0x0; // len
-        b[5] = FRAME_CONTROL.to_le_bytes()[0];
-        b[6] = FRAME_CONTROL.to_le_bytes()[1];
-        b[7] = 0x0; // seq nr
-        b[8] = DESTINATION_PAN.to_le_bytes()[0];
-        b[9] = DESTINATION_PAN.to_le_bytes()[1];
-        b[10] = DESTINATION_ADDRESS.to_le_bytes()[0];
-        b[11] = DESTINATION_ADDRESS.to_le_bytes()[1];
-        b[12] = SOURCE_ADDRESS.to_le_bytes()[0];
-        b[13] = SOURCE_ADDRESS.to_le_bytes()[1];
-
-        Block::new(
-            BlockMetaBuilder::new("Mac").build(),
-            StreamIoBuilder::new().add_output::<u8>("out").build(),
-            MessageIoBuilder::new()
-                .add_input("rx", Self::received)
-                .add_input("tx", Self::transmit)
-                .add_input("stats", Self::stats)
-                .add_output("rxed")
-                .add_output("rftap")
-                .build(),
-            Mac {
-                tx_frames: VecDeque::new(),
-                current_frame: b,
-                sequence_number: 0,
-                current_index: 0,
-                current_len: 0,
-                n_received: 0,
-                n_sent: 0,
-            },
-        )
+        MacBuilder::new().promiscuous(true).build()
     }
 
     fn calc_crc(data: &[u8]) -> u16 {
@@ -98,6 +213,85 @@ impl Mac {
         Self::calc_crc(data) == 0
     }
 
+    /// Whether a data or command frame is addressed to this node (or broadcast) and whether it
+    /// has to be acknowledged
+    fn accept(&self, header: &Header) -> (bool, bool) {
+        let pan = match header.destination_pan {
+            Some(pan) => pan,
+            None => return (false, false),
+        };
+        let ours = pan == self.pan_id || pan == BROADCAST;
+        // the association response is sent in the PAN of the coordinator
+        let associating =
+            matches!(self.association, Association::Pending { pan_id } if pan_id == pan);
+        let for_us = match header.destination {
+            Address::Short(a) => ours && a == self.address,
+            Address::Extended(a) => (ours || associating) && a == self.extended_address,
+            Address::None => false,
+        };
+        let broadcast = ours && header.destination == Address::Short(BROADCAST);
+        (for_us || broadcast, for_us && header.fc & ACK_REQUEST != 0)
+    }
+
+    /// Handle a MAC command addressed to this node
+    fn command(&mut self, header: &Header, payload: &[u8]) {
+        match payload.first() {
+            Some(&ASSOCIATION_REQUEST) => {
+                let (first, last) = match self.coordinator {
+                    Some(range) => range,
+                    None => return,
+                };
+                let device = match header.source {
+                    Address::Extended(a) => a,
+                    _ => {
+                        warn!("ZigBee Mac: association request without extended address");
+                        return;
+                    }
+                };
+                let address = self.devices.get(&device).copied().or_else(|| {
+                    (first..=last)
+                        .find(|a| *a != self.address && !self.devices.values().any(|d| d == a))
+                });
+                let status = match address {
+                    Some(a) => {
+                        info!("associated device {:016x} with address {:#06x}", device, a);
+                        self.devices.insert(device, a);
+                        ASSOCIATION_SUCCESSFUL
+                    }
+                    None => {
+                        warn!(
+                            "ZigBee Mac: PAN at capacity, rejecting device {:016x}",
+                            device
+                        );
+                        PAN_AT_CAPACITY
+                    }
+                };
+                let frame = self.association_response(device, address.unwrap_or(BROADCAST), status);
+                if self.tx_commands.len() < MAX_FRAMES {
+                    self.tx_commands.push_back(frame);
+                }
+            }
+            Some(&ASSOCIATION_RESPONSE) if payload.len() >= 4 => {
+                if let Association::Pending { pan_id } = self.association {
+                    let address = u16::from_le_bytes([payload[1], payload[2]]);
+                    if payload[3] == ASSOCIATION_SUCCESSFUL {
+                        info!(
+                            "associated with PAN {:#06x}, address {:#06x}",
+                            pan_id, address
+                        );
+                        self.pan_id = pan_id;
+                        self.address = address;
+                        self.association = Association::Associated;
+                    } else {
+                        warn!("ZigBee Mac: association denied (status {})", payload[3]);
+                        self.association = Association::Idle;
+                    }
+                }
+            }
+            _ => debug!("received unsupported command frame"),
+        }
+    }
+
     #[message_handler]
     async fn received(
         &mut self,
@@ -118,6 +312,36 @@ impl Mac {
                     rftap[12..].copy_from_slice(&data);
                     mio.output_mut(1).post(Pmt::Blob(rftap)).await;
 
+                    // ack frame
+                    if data.len() == 5 && data[0] & 0x7 == 0x2 {
+                        if matches!(&self.pending, Some(p) if p.sequence_number == data[2]) {
+                            debug!("received ack for frame {}", data[2]);
+                            self.pending = None;
+                        }
+                        return Ok(Pmt::Ok);
+                    }
+
+                    let header = Header::parse(&data);
+                    let (for_us, ack) = match &header {
+                        Some(h) => self.accept(h),
+                        None => (false, false),
+                    };
+                    // a promiscuous MAC is a sniffer and does not acknowledge frames
+                    if ack && !self.promiscuous && self.tx_acks.len() < MAX_FRAMES {
+                        self.tx_acks.push_back(data[2]);
+                    }
+                    let command = match &header {
+                        Some(h) if for_us && h.fc & 0x7 == FRAME_TYPE_COMMAND => {
+                            self.command(h, &data[h.payload..data.len() - 2]);
+                            true
+                        }
+                        _ => false,
+                    };
+                    if !self.promiscuous && (!for_us || command) {
+                        debug!("received frame for other node");
+                        return Ok(Pmt::Ok);
+                    }
+
                     self.n_received += 1;
                     let s = String::from_iter(
                         data.iter()
@@ -192,13 +416,261 @@ impl Mac {
     ) -> Result<Pmt> {
         Ok(Pmt::VecU64(vec![self.n_sent, self.n_received]))
     }
+
+    #[message_handler]
+    async fn cca(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Bool(busy) => self.channel_busy = busy,
+            Pmt::Null => return Ok(Pmt::Bool(self.channel_busy)),
+            Pmt::Finished => {}
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn associate(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::VecU64(v) if v.len() == 2 => {
+                let (pan_id, coordinator) = match (u16::try_from(v[0]), u16::try_from(v[1])) {
+                    (Ok(p), Ok(c)) => (p, c),
+                    _ => return Ok(Pmt::InvalidValue),
+                };
+                if self.tx_commands.len() >= MAX_FRAMES {
+                    warn!("ZigBee Mac: command queue full. Dropping association request.");
+                    return Ok(Pmt::InvalidValue);
+                }
+                let frame = self.association_request(pan_id, coordinator);
+                self.tx_commands.push_back(frame);
+                self.association = Association::Pending { pan_id };
+            }
+            Pmt::Null => {
+                return Ok(match self.association {
+                    Association::Associated => {
+                        Pmt::VecU64(vec![self.pan_id.into(), self.address.into()])
+                    }
+                    _ => Pmt::Null,
+                })
+            }
+            Pmt::Finished => {}
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+
+    /// Frame control with the ACK request bit set, if ACKs are enabled and the destination is
+    /// not the broadcast address
+    fn frame_control(&self, fc: u16, broadcast: bool) -> u16 {
+        if self.ack && !broadcast {
+            fc | ACK_REQUEST
+        } else {
+            fc
+        }
+    }
+
+    /// Set the sequence number and append the CRC
+    fn finish(&mut self, mut frame: Vec<u8>) -> Vec<u8> {
+        frame[2] = self.sequence_number;
+        self.sequence_number = self.sequence_number.wrapping_add(1);
+        let crc = Self::calc_crc(&frame);
+        frame.extend_from_slice(&crc.to_le_bytes());
+        frame
+    }
+
+    /// Frame with header, payload, and CRC
+    fn data_frame(&mut self, payload: &[u8]) -> Vec<u8> {
+        let fc = self.frame_control(FRAME_CONTROL, self.destination == BROADCAST);
+
+        let mut frame = Vec::with_capacity(payload.len() + 11);
+        frame.extend_from_slice(&fc.to_le_bytes());
+        frame.push(0);
+        frame.extend_from_slice(&self.pan_id.to_le_bytes());
+        frame.extend_from_slice(&self.destination.to_le_bytes());
+        frame.extend_from_slice(&self.address.to_le_bytes());
+        frame.extend_from_slice(payload);
+        self.finish(frame)
+    }
+
+    /// Association request from our extended address to the coordinator, without sequence number
+    /// and CRC
+    fn association_request(&self, pan_id: u16, coordinator: u16) -> Vec<u8> {
+        let fc = self.frame_control(
+            FRAME_TYPE_COMMAND | DESTINATION_SHORT | SOURCE_EXTENDED,
+            coordinator == BROADCAST,
+        );
+
+        let mut frame = Vec::with_capacity(21);
+        frame.extend_from_slice(&fc.to_le_bytes());
+        frame.push(0);
+        frame.extend_from_slice(&pan_id.to_le_bytes());
+        frame.extend_from_slice(&coordinator.to_le_bytes());
+        frame.extend_from_slice(&BROADCAST.to_le_bytes());
+        frame.extend_from_slice(&self.extended_address.to_le_bytes());
+        frame.push(ASSOCIATION_REQUEST);
+        frame.push(ALLOCATE_ADDRESS);
+        frame
+    }
+
+    /// Association response to the extended address of a device, without sequence number and
+    /// CRC
+    fn association_response(&self, device: u64, address: u16, status: u8) -> Vec<u8> {
+        let fc = self.frame_control(
+            FRAME_TYPE_COMMAND | PAN_ID_COMPRESSION | DESTINATION_EXTENDED | SOURCE_EXTENDED,
+            false,
+        );
+
+        let mut frame = Vec::with_capacity(25);
+        frame.extend_from_slice(&fc.to_le_bytes());
+        frame.push(0);
+        frame.extend_from_slice(&self.pan_id.to_le_bytes());
+        frame.extend_from_slice(&device.to_le_bytes());
+        frame.extend_from_slice(&self.extended_address.to_le_bytes());
+        frame.push(ASSOCIATION_RESPONSE);
+        frame.extend_from_slice(&address.to_le_bytes());
+        frame.push(status);
+        frame
+    }
+
+    fn ack_frame(sequence_number: u8) -> Vec<u8> {
+        let mut frame = vec![0x02, 0x00, sequence_number];
+        let crc = Self::calc_crc(&frame);
+        frame.extend_from_slice(&crc.to_le_bytes());
+        frame
+    }
+
+    /// Copy frame with preamble and length to the output buffer
+    fn load(&mut self, frame: &[u8]) {
+        self.current_frame[4] = frame.len() as u8;
+        self.current_frame[5..5 + frame.len()].copy_from_slice(frame);
+        // 4 preamble + 1 len
+        self.current_len = frame.len() + 5;
+        self.current_index = 0;
+    }
+
+    fn random(&mut self) -> u32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 17;
+        self.rng ^= self.rng << 5;
+        self.rng
+    }
+
+    /// Run the unslotted CSMA-CA
+    fn channel_access(&mut self) -> Access {
+        if !self.csma {
+            return Access::Clear;
+        }
+
+        let now = Instant::now();
+        match self.backoff.until {
+            None => {
+                let periods = self.random() % (1 << self.backoff.exponent);
+                let t = now + BACKOFF_PERIOD * periods;
+                self.backoff.until = Some(t);
+                Access::Backoff(t)
+            }
+            Some(t) if t > now => Access::Backoff(t),
+            Some(_) => {
+                self.backoff.until = None;
+                if !self.channel_busy {
+                    self.backoff.backoffs = 0;
+                    self.backoff.exponent = self.min_be;
+                    return Access::Clear;
+                }
+                self.backoff.backoffs += 1;
+                self.backoff.exponent = std::cmp::min(self.backoff.exponent + 1, self.max_be);
+                if self.backoff.backoffs > self.max_backoffs {
+                    self.backoff.backoffs = 0;
+                    self.backoff.exponent = self.min_be;
+                    Access::Failure
+                } else {
+                    self.channel_access()
+                }
+            }
+        }
+    }
+
+    /// Load the next frame to send
+    fn next_frame(&mut self) -> Next {
+        // acks are sent without channel access
+        if let Some(seq) = self.tx_acks.pop_front() {
+            let frame = Self::ack_frame(seq);
+            self.load(&frame);
+            return Next::Frame;
+        }
+
+        if let Some(p) = self.pending.as_mut() {
+            if let Some(deadline) = p.deadline {
+                if deadline > Instant::now() {
+                    return Next::Wait(deadline);
+                }
+                if p.retries >= self.max_retries {
+                    warn!(
+                        "ZigBee Mac: frame {} not acknowledged after {} retries. Dropping.",
+                        p.sequence_number, p.retries
+                    );
+                    self.pending = None;
+                } else {
+                    p.retries += 1;
+                    p.deadline = None;
+                }
+            }
+        }
+
+        if self.pending.is_none() {
+            let frame = if let Some(command) = self.tx_commands.pop_front() {
+                self.finish(command)
+            } else {
+                match self.tx_frames.pop_front() {
+                    Some(payload) => self.data_frame(&payload),
+                    None => return Next::Idle,
+                }
+            };
+            self.pending = Some(Pending {
+                sequence_number: frame[2],
+                frame,
+                retries: 0,
+                deadline: None,
+            });
+        }
+
+        match self.channel_access() {
+            Access::Clear => {}
+            Access::Backoff(t) => return Next::Wait(t),
+            Access::Failure => {
+                warn!("ZigBee Mac: channel access failure. Dropping frame.");
+                self.pending = None;
+                return Next::Again;
+            }
+        }
+
+        let mut p = self.pending.take().unwrap();
+        self.load(&p.frame);
+        if u16::from_le_bytes([p.frame[0], p.frame[1]]) & ACK_REQUEST != 0 {
+            p.deadline = Some(Instant::now() + self.ack_timeout);
+            self.pending = Some(p);
+        }
+        Next::Frame
+    }
 }
 
 #[async_trait]
 impl Kernel for Mac {
+    #[cfg_attr(target_arch = "wasm32", allow(unused_variables))]
     async fn work(
         &mut self,
-        _io: &mut WorkIo,
+        io: &mut WorkIo,
         sio: &mut StreamIo,
         _m: &mut MessageIo<Self>,
         _b: &mut BlockMeta,
@@ -210,32 +682,22 @@ impl Kernel for Mac {
             }
 
             if self.current_len == 0 {
-                if let Some(v) = self.tx_frames.pop_front() {
-                    self.current_frame[4] = (v.len() + 11) as u8;
-                    self.current_frame[7] = self.sequence_number;
-                    self.sequence_number = self.sequence_number.wrapping_add(1);
-                    unsafe {
-                        std::ptr::copy_nonoverlapping(
-                            v.as_ptr(),
-                            self.current_frame.as_mut_ptr().add(14),
-                            v.len(),
-                        );
+                match self.next_frame() {
+                    Next::Frame => {}
+                    Next::Wait(t) => {
+                        #[cfg(not(target_arch = "wasm32"))]
+                        io.block_on(async move {
+                            futuresdr::async_io::Timer::at(t).await;
+                        });
+                        break;
                     }
-
-                    let crc = Self::calc_crc(&self.current_frame[5..14 + v.len()]);
-                    self.current_frame[14 + v.len()] = crc.to_le_bytes()[0];
-                    self.current_frame[15 + v.len()] = crc.to_le_bytes()[1];
-
-                    // 4 preamble + 1 len + 9 header + 2 crc
-                    self.current_len = v.len() + 16;
-                    self.current_index = 0;
-                    sio.output(0).add_tag(0, Tag::Id(self.current_len as u64));
-                    debug!("sending frame, len {}", self.current_len);
-                    self.n_sent += 1;
-                    debug!("{:?}", &self.current_frame[0..self.current_len]);
-                } else {
-                    break;
+                    Next::Again => continue,
+                    Next::Idle => break,
                 }
+                sio.output(0).add_tag(0, Tag::Id(self.current_len as u64));
+                debug!("sending frame, len {}", self.current_len);
+                self.n_sent += 1;
+                debug!("{:?}", &self.current_frame[0..self.current_len]);
             } else {
                 let n = std::cmp::min(out.len(), self.current_len - self.current_index);
                 unsafe {
@@ -258,3 +720,211 @@ impl Kernel for Mac {
         Ok(())
     }
 }
+
+/// Build an IEEE 802.15.4 [Mac].
+///
+/// The MAC sends data frames with short addresses and PAN ID compression. Received frames are
+/// filtered by PAN ID and short or extended address, unless the MAC is promiscuous.
+///
+/// Unicast frames for this node that request an ACK are acknowledged, unless the MAC is
+/// promiscuous. If ACKs are enabled,
+/// unicast frames are sent with the ACK request bit set and retransmitted if they are not
+/// acknowledged in time. Before each transmission, the unslotted CSMA-CA backs off for a random
+/// number of backoff periods and checks the channel state, which can be set through the `cca`
+/// message input ([`Pmt::Bool`], `true` if busy), e.g., from a power detector. Without a
+/// channel state, the channel is assumed to be clear.
+///
+/// The ACK timeout defaults to 50 ms, since SDR latencies do not allow meeting the timing of the
+/// standard. On wasm, CSMA-CA and retransmissions are disabled.
+///
+/// The association is only partially implemented. A coordinator accepts association requests
+/// and allocates short addresses from a configured range. It answers with the association
+/// response right away, i.e., without the indirect transmission (data request) of the standard.
+/// Beacons, disassociation, and persisting the allocated addresses are not supported. A device starts the association through the
+/// `associate` message input with [`Pmt::VecU64`] holding the PAN ID and the short address of
+/// the coordinator. Once the response is received, the device uses the PAN ID and the allocated
+/// short address. Calling `associate` with [`Pmt::Null`] returns the PAN ID and the short address
+/// as [`Pmt::VecU64`], if the device is associated, and [`Pmt::Null`] otherwise.
+pub struct MacBuilder {
+    pan_id: u16,
+    address: u16,
+    extended_address: Option<u64>,
+    coordinator: Option<(u16, u16)>,
+    destination: u16,
+    promiscuous: bool,
+    ack: bool,
+    max_retries: usize,
+    ack_timeout: Duration,
+    csma: bool,
+    min_be: u32,
+    max_be: u32,
+    max_backoffs: usize,
+}
+
+impl MacBuilder {
+    pub fn new() -> Self {
+        Self {
+            pan_id: DESTINATION_PAN,
+            address: SOURCE_ADDRESS,
+            extended_address: None,
+            coordinator: None,
+            destination: DESTINATION_ADDRESS,
+            promiscuous: false,
+            ack: false,
+            max_retries: 3,
+            ack_timeout: Duration::from_millis(50),
+            csma: false,
+            min_be: 3,
+            max_be: 5,
+            max_backoffs: 4,
+        }
+    }
+
+    /// PAN ID (default: 0x1aaa)
+    #[must_use]
+    pub fn pan_id(mut self, pan_id: u16) -> Self {
+        self.pan_id = pan_id;
+        self
+    }
+
+    /// Short address of this node (default: 0x3344)
+    #[must_use]
+    pub fn address(mut self, address: u16) -> Self {
+        self.address = address;
+        self
+    }
+
+    /// Extended address of this node (default: the short address)
+    #[must_use]
+    pub fn extended_address(mut self, extended_address: u64) -> Self {
+        self.extended_address = Some(extended_address);
+        self
+    }
+
+    /// Act as PAN coordinator, allocating short addresses from `first` to `last` to associating
+    /// devices (default: disabled)
+    #[must_use]
+    pub fn coordinator(mut self, first: u16, last: u16) -> Self {
+        self.coordinator = Some((first, last));
+        self
+    }
+
+    /// Short address of the destination (default: 0xffff, broadcast)
+    #[must_use]
+    pub fn destination(mut self, destination: u16) -> Self {
+        self.destination = destination;
+        self
+    }
+
+    /// Forward all frames with valid CRC (default: false)
+    #[must_use]
+    pub fn promiscuous(mut self, promiscuous: bool) -> Self {
+        self.promiscuous = promiscuous;
+        self
+    }
+
+    /// Request ACKs for unicast frames and retransmit up to `max_retries` times, if no ACK is
+    /// received within `timeout` (default: disabled)
+    ///
+    /// Not supported on wasm, where the timers of the MAC are not available. The setting is
+    /// ignored there, with a warning.
+    #[must_use]
+    pub fn ack(mut self, max_retries: usize, timeout: Duration) -> Self {
+        self.ack = true;
+        self.max_retries = max_retries;
+        self.ack_timeout = timeout;
+        self
+    }
+
+    /// Enable unslotted CSMA-CA (default: disabled)
+    ///
+    /// Not supported on wasm, where the timers of the MAC are not available. The setting is
+    /// ignored there, with a warning.
+    #[must_use]
+    pub fn csma(mut self, csma: bool) -> Self {
+        self.csma = csma;
+        self
+    }
+
+    /// Minimum and maximum backoff exponent and the maximum number of backoffs of the CSMA-CA
+    /// (default: 3, 5, 4)
+    #[must_use]
+    pub fn csma_parameters(mut self, min_be: u32, max_be: u32, max_backoffs: usize) -> Self {
+        self.min_be = min_be;
+        self.max_be = std::cmp::max(min_be, max_be);
+        self.max_backoffs = max_backoffs;
+        self
+    }
+
+    pub fn build(self) -> Block {
+        let mut b = [0; 256];
+        b[0] = 0x0;
+        b[1] = 0x0;
+        b[2] = 0x0;
+        b[3] = 0xa7;
+
+        let timers = cfg!(not(target_arch = "wasm32"));
+        if !timers && self.ack {
+            warn!("ZigBee Mac: ACKs and retransmissions are not supported on wasm, disabling them");
+        }
+        if !timers && self.csma {
+            warn!("ZigBee Mac: CSMA-CA is not supported on wasm, disabling it");
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("Mac").build(),
+            StreamIoBuilder::new().add_output::<u8>("out").build(),
+            MessageIoBuilder::new()
+                .add_input("rx", Mac::received)
+                .add_input("tx", Mac::transmit)
+                .add_input("stats", Mac::stats)
+                .add_input("cca", Mac::cca)
+                .add_input("associate", Mac::associate)
+                .add_output("rxed")
+                .add_output("rftap")
+                .build(),
+            Mac {
+                tx_frames: VecDeque::new(),
+                tx_acks: VecDeque::new(),
+                tx_commands: VecDeque::new(),
+                current_frame: b,
+                sequence_number: 0,
+                current_index: 0,
+                current_len: 0,
+                n_received: 0,
+                n_sent: 0,
+                pan_id: self.pan_id,
+                address: self.address,
+                extended_address: self
+                    .extended_address
+                    .unwrap_or_else(|| u64::from(self.address)),
+                destination: self.destination,
+                promiscuous: self.promiscuous,
+                ack: self.ack && timers,
+                max_retries: self.max_retries,
+                ack_timeout: self.ack_timeout,
+                csma: self.csma && timers,
+                min_be: self.min_be,
+                max_be: self.max_be,
+                max_backoffs: self.max_backoffs,
+                association: Association::Idle,
+                coordinator: self.coordinator,
+                devices: HashMap::new(),
+                channel_busy: false,
+                pending: None,
+                backoff: Csma {
+                    backoffs: 0,
+                    exponent: self.min_be,
+                    until: None,
+                },
+                rng: 0x9e37_79b9 ^ (u32::from(self.address) << 16) ^ u32::from(self.pan_id),
+            },
+        )
+    }
+}
+
+impl Default for MacBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
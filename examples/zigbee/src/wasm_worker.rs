@@ -3,6 +3,7 @@ use futuresdr::blocks::wasm::HackRf;
 use futuresdr::blocks::Apply;
 use futuresdr::blocks::MessagePipe;
 use futuresdr::blocks::NullSink;
+use futuresdr::blocks::SymbolSync;
 use futuresdr::futures::channel::mpsc;
 use futuresdr::futures::channel::mpsc::Receiver;
 use futuresdr::futures::SinkExt;
@@ -16,7 +17,6 @@ use futuresdr::runtime::Pmt;
 use futuresdr::runtime::Runtime;
 use gloo_worker::{HandlerId, WorkerScope};
 
-use crate::ClockRecoveryMm;
 use crate::Decoder;
 use crate::Mac;
 
@@ -82,12 +82,13 @@ impl gloo_worker::Worker for Worker {
                         let mu = 0.5;
                         let gain_mu = 0.03;
                         let omega_relative_limit = 0.0002;
-                        let mm = ClockRecoveryMm::new(
+                        let mm = SymbolSync::<f32>::new(
                             omega,
                             gain_omega,
                             mu,
                             gain_mu,
                             omega_relative_limit,
+                            None,
                         );
 
                         let decoder = Decoder::new(6);
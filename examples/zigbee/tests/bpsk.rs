@@ -0,0 +1,127 @@
+use futuresdr::anyhow::{Context, Result};
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::testing::assert_frames_eq;
+use futuresdr::testing::run_with_timeout;
+use std::time::Duration;
+
+use zigbee::golden::split_frames;
+use zigbee::BpskDemodulator;
+use zigbee::BpskModulator;
+use zigbee::Mac;
+use zigbee::BPSK_SAMPLES_PER_CHIP;
+
+/// Modulate the payloads, returning the samples and the frames that the MAC sent
+fn transmit(payloads: &[Vec<u8>]) -> Result<(Vec<Complex32>, Vec<Vec<u8>>)> {
+    let mut fg = Flowgraph::new();
+    let mac = fg.add_block(Mac::new());
+    let modulator = fg.add_block(BpskModulator::new(BPSK_SAMPLES_PER_CHIP));
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+    let mac_out = fg.add_block(VectorSinkBuilder::<u8>::new().build());
+    fg.connect_stream(mac, "out", modulator, "in")?;
+    fg.connect_stream(modulator, "out", snk, "in")?;
+    fg.connect_stream(mac, "out", mac_out, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    let fg = block_on(async {
+        for p in payloads {
+            handle.call(mac, "tx", Pmt::Blob(p.clone())).await?;
+        }
+        // give the transmitter time to process the queue
+        Timer::after(Duration::from_secs(1)).await;
+        handle.terminate_and_wait().await?;
+        task.await
+    })?;
+
+    let samples = fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .context("wrong block type")?
+        .items()
+        .clone();
+    let frames = split_frames(
+        fg.kernel::<VectorSink<u8>>(mac_out)
+            .context("wrong block type")?
+            .items(),
+    )?;
+    Ok((samples, frames))
+}
+
+/// Scale, rotate, and add uniform noise (from a fixed-seed xorshift) to the samples
+fn channel(samples: &[Complex32], gain: f32, phase: f32, noise: f32) -> Vec<Complex32> {
+    let mut state: u32 = 0x2545_f491;
+    let mut uniform = move || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state as f32 / u32::MAX as f32 - 0.5
+    };
+    let rot = Complex32::from_polar(gain, phase);
+    samples
+        .iter()
+        .map(|s| s * rot + Complex32::new(uniform(), uniform()) * noise)
+        .collect()
+}
+
+fn receive(samples: Vec<Complex32>) -> Result<Vec<Vec<u8>>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(samples));
+    let demodulator = fg.add_block(BpskDemodulator::new(BPSK_SAMPLES_PER_CHIP, 0.5));
+    let (tx_frame, mut rx_frame) = mpsc::channel::<Pmt>(1000);
+    let message_pipe = fg.add_block(MessagePipe::new(tx_frame));
+    fg.connect_stream(src, "out", demodulator, "in")?;
+    fg.connect_message(demodulator, "out", message_pipe, "in")?;
+
+    run_with_timeout(fg, Duration::from_secs(60))?;
+
+    let mut frames = Vec::new();
+    while let Ok(Some(p)) = rx_frame.try_next() {
+        if let Pmt::Blob(f) = p {
+            frames.push(f);
+        }
+    }
+    Ok(frames)
+}
+
+fn payloads(n: usize) -> Vec<Vec<u8>> {
+    (0..n)
+        .map(|i| format!("FutureSDR {i}").as_bytes().to_vec())
+        .collect()
+}
+
+#[test]
+fn loopback() -> Result<()> {
+    let (samples, sent) = transmit(&payloads(5))?;
+    assert_eq!(sent.len(), 5);
+    // the demodulator outputs frames with FCS, i.e., what the MAC sent
+    let received = receive(samples)?;
+    assert_frames_eq("bpsk_loopback", &received, &sent);
+    Ok(())
+}
+
+#[test]
+fn loopback_channel() -> Result<()> {
+    let (samples, sent) = transmit(&payloads(5))?;
+    // the threshold is normalized, so gain and phase must not matter
+    let samples = channel(&samples, 0.1, 2.0, 0.05);
+    let received = receive(samples)?;
+    assert_frames_eq("bpsk_loopback_channel", &received, &sent);
+    Ok(())
+}
+
+#[test]
+fn noise_only() -> Result<()> {
+    let silence = vec![Complex32::new(0.0, 0.0); 200_000];
+    let received = receive(channel(&silence, 1.0, 0.0, 1.0))?;
+    assert!(received.is_empty(), "decoded {} frames", received.len());
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::FileSource;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::SymbolSync;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::testing::assert_frames_eq;
+use futuresdr::testing::golden_fixtures;
+use futuresdr::testing::run_with_timeout;
+use std::path::Path;
+use std::time::Duration;
+
+use zigbee::golden::generate;
+use zigbee::BpskDemodulator;
+use zigbee::Decoder;
+use zigbee::Phy;
+use zigbee::BPSK_SAMPLES_PER_CHIP;
+
+/// Fixtures are generated from the TX chain, like with `cargo run --release --bin golden`
+const GOLDEN: &str = concat!(env!("CARGO_TARGET_TMPDIR"), "/golden");
+
+fn decode(samples: &Path, phy: Phy) -> Result<Vec<Vec<u8>>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(FileSource::<Complex32>::new(
+        samples.to_str().unwrap(),
+        false,
+    ));
+
+    let (tx_frame, mut rx_frame) = mpsc::channel::<Pmt>(1000);
+    let message_pipe = fg.add_block(MessagePipe::new(tx_frame));
+
+    match phy {
+        Phy::Oqpsk => {
+            let mut last: Complex32 = Complex32::new(0.0, 0.0);
+            let mut iir: f32 = 0.0;
+            let alpha = 0.00016;
+            let avg = fg.add_block(Apply::new(move |i: &Complex32| -> f32 {
+                let phase = (last.conj() * i).arg();
+                last = *i;
+                iir = (1.0 - alpha) * iir + alpha * phase;
+                phase - iir
+            }));
+            let mm = fg.add_block(SymbolSync::<f32>::new(
+                2.0, 0.000225, 0.5, 0.03, 0.0002, None,
+            ));
+            let decoder = fg.add_block(Decoder::new(6));
+            fg.connect_stream(src, "out", avg, "in")?;
+            fg.connect_stream(avg, "out", mm, "in")?;
+            fg.connect_stream(mm, "out", decoder, "in")?;
+            fg.connect_message(decoder, "out", message_pipe, "in")?;
+        }
+        Phy::Bpsk => {
+            let demodulator = fg.add_block(BpskDemodulator::new(BPSK_SAMPLES_PER_CHIP, 0.5));
+            fg.connect_stream(src, "out", demodulator, "in")?;
+            fg.connect_message(demodulator, "out", message_pipe, "in")?;
+        }
+    }
+
+    run_with_timeout(fg, Duration::from_secs(60))?;
+
+    let mut frames = Vec::new();
+    while let Ok(Some(p)) = rx_frame.try_next() {
+        if let Pmt::Blob(f) = p {
+            frames.push(f);
+        }
+    }
+    Ok(frames)
+}
+
+#[test]
+fn golden() -> Result<()> {
+    std::fs::create_dir_all(GOLDEN)?;
+    for phy in [Phy::Oqpsk, Phy::Bpsk] {
+        generate(Path::new(GOLDEN), phy, 10)?;
+    }
+
+    let fixtures = golden_fixtures(GOLDEN, "cf32")?;
+    assert_eq!(fixtures.len(), 2);
+    for fixture in fixtures {
+        let phy = if fixture.name.contains("bpsk") {
+            Phy::Bpsk
+        } else {
+            Phy::Oqpsk
+        };
+        let frames = decode(&fixture.samples, phy)?;
+        assert_frames_eq(&fixture.name, &frames, &fixture.expected()?);
+    }
+    Ok(())
+}
@@ -0,0 +1,302 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::ChannelSink;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::FlowgraphHandle;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+use zigbee::Mac;
+use zigbee::MacBuilder;
+
+const PAN: u16 = 0x1aaa;
+const BROADCAST_PAN: u16 = 0xffff;
+const A: u16 = 0x0001;
+const B: u16 = 0x0002;
+
+/// CRC of IEEE 802.15.4 (CRC-16/KERMIT)
+fn crc(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+    for b in data {
+        crc ^= u16::from(*b);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    crc
+}
+
+/// Data frame with short addresses and PAN ID compression
+fn data_frame(ack: bool, pan: u16, destination: u16, source: u16, payload: &[u8]) -> Vec<u8> {
+    let fc: u16 = if ack { 0x8861 } else { 0x8841 };
+    let mut frame = fc.to_le_bytes().to_vec();
+    frame.push(42);
+    frame.extend_from_slice(&pan.to_le_bytes());
+    frame.extend_from_slice(&destination.to_le_bytes());
+    frame.extend_from_slice(&source.to_le_bytes());
+    frame.extend_from_slice(payload);
+    let crc = crc(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+fn is_ack(frame: &[u8], sequence_number: u8) -> bool {
+    frame.len() == 5 && frame[0] & 0x7 == 0x2 && frame[2] == sequence_number
+}
+
+/// Frames written to the output stream of a MAC, i.e., without preamble, SFD, and length
+fn sent(rx: &mut mpsc::Receiver<Box<[u8]>>) -> Vec<Vec<u8>> {
+    let mut bytes = Vec::new();
+    while let Ok(Some(b)) = rx.try_next() {
+        bytes.extend_from_slice(&b);
+    }
+
+    let mut frames = Vec::new();
+    let mut bytes = &bytes[..];
+    while bytes.len() > 5 {
+        let len = bytes[4] as usize;
+        frames.push(bytes[5..5 + len].to_vec());
+        bytes = &bytes[5 + len..];
+    }
+    frames
+}
+
+/// Deliver the frames that were sent since the last call to the `rx` input of `to`
+async fn forward(
+    handle: &mut FlowgraphHandle,
+    rx: &mut mpsc::Receiver<Box<[u8]>>,
+    to: usize,
+) -> Result<Vec<Vec<u8>>> {
+    let frames = sent(rx);
+    for f in frames.iter() {
+        handle.callback(to, "rx", Pmt::Blob(f.clone())).await?;
+    }
+    Ok(frames)
+}
+
+fn received(rx: &mut mpsc::Receiver<Pmt>) -> Vec<Vec<u8>> {
+    let mut frames = Vec::new();
+    while let Ok(Some(p)) = rx.try_next() {
+        if let Pmt::Blob(f) = p {
+            frames.push(f);
+        }
+    }
+    frames
+}
+
+#[test]
+fn ack() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let a = fg.add_block(
+        MacBuilder::new()
+            .address(A)
+            .destination(B)
+            .ack(3, Duration::from_millis(50))
+            .build(),
+    );
+    let b = fg.add_block(MacBuilder::new().address(B).destination(A).build());
+    let (a_tx, mut a_rx) = mpsc::channel(100);
+    let (b_tx, mut b_rx) = mpsc::channel(100);
+    let (rxed_tx, mut rxed_rx) = mpsc::channel(100);
+    let a_snk = fg.add_block(ChannelSink::<u8>::new(a_tx));
+    let b_snk = fg.add_block(ChannelSink::<u8>::new(b_tx));
+    let pipe = fg.add_block(MessagePipe::new(rxed_tx));
+    fg.connect_stream(a, "out", a_snk, "in")?;
+    fg.connect_stream(b, "out", b_snk, "in")?;
+    fg.connect_message(b, "rxed", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+    let (data, acks, again) = block_on(async {
+        handle.call(a, "tx", Pmt::Blob(b"hello".to_vec())).await?;
+        Timer::after(Duration::from_millis(20)).await;
+        let data = forward(&mut handle, &mut a_rx, b).await?;
+        Timer::after(Duration::from_millis(20)).await;
+        let acks = forward(&mut handle, &mut b_rx, a).await?;
+        // acknowledged: no retransmission after the timeout
+        Timer::after(Duration::from_millis(100)).await;
+        let again = sent(&mut a_rx);
+        handle.terminate_and_wait().await?;
+        Ok::<_, futuresdr::anyhow::Error>((data, acks, again))
+    })?;
+
+    assert_eq!(data.len(), 1);
+    assert_eq!(data[0][0] & 0x20, 0x20, "no ACK request");
+    assert_eq!(acks.len(), 1);
+    assert!(is_ack(&acks[0], data[0][2]));
+    assert!(again.is_empty(), "{} retransmissions", again.len());
+    assert_eq!(received(&mut rxed_rx), data);
+    Ok(())
+}
+
+#[test]
+fn retry_limit() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let a = fg.add_block(
+        MacBuilder::new()
+            .address(A)
+            .destination(B)
+            .ack(2, Duration::from_millis(30))
+            .build(),
+    );
+    let (a_tx, mut a_rx) = mpsc::channel(100);
+    let a_snk = fg.add_block(ChannelSink::<u8>::new(a_tx));
+    fg.connect_stream(a, "out", a_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+    let (first, second) = block_on(async {
+        handle.call(a, "tx", Pmt::Blob(b"first".to_vec())).await?;
+        // three timeouts: one transmission and two retries
+        Timer::after(Duration::from_millis(200)).await;
+        let first = sent(&mut a_rx);
+        handle.call(a, "tx", Pmt::Blob(b"second".to_vec())).await?;
+        Timer::after(Duration::from_millis(10)).await;
+        let second = sent(&mut a_rx);
+        handle.terminate_and_wait().await?;
+        Ok::<_, futuresdr::anyhow::Error>((first, second))
+    })?;
+
+    assert_eq!(first.len(), 3, "{} transmissions", first.len());
+    assert!(first.iter().all(|f| f == &first[0]));
+    assert_eq!(second.len(), 1);
+    assert_eq!(second[0][2], first[0][2].wrapping_add(1));
+    Ok(())
+}
+
+#[test]
+fn filtering() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let b = fg.add_block(MacBuilder::new().pan_id(PAN).address(B).build());
+    let sniffer = fg.add_block(Mac::new());
+    let (b_tx, mut b_rx) = mpsc::channel(100);
+    let (s_tx, mut s_rx) = mpsc::channel(100);
+    let (rxed_tx, mut rxed_rx) = mpsc::channel(100);
+    let (sniffed_tx, mut sniffed_rx) = mpsc::channel(100);
+    let b_snk = fg.add_block(ChannelSink::<u8>::new(b_tx));
+    let s_snk = fg.add_block(ChannelSink::<u8>::new(s_tx));
+    let pipe = fg.add_block(MessagePipe::new(rxed_tx));
+    let sniffed = fg.add_block(MessagePipe::new(sniffed_tx));
+    fg.connect_stream(b, "out", b_snk, "in")?;
+    fg.connect_stream(sniffer, "out", s_snk, "in")?;
+    fg.connect_message(b, "rxed", pipe, "in")?;
+    fg.connect_message(sniffer, "rxed", sniffed, "in")?;
+
+    let for_b = data_frame(true, PAN, B, A, b"for b");
+    let broadcast = data_frame(true, PAN, 0xffff, A, b"broadcast");
+    let other_node = data_frame(true, PAN, 0x0003, A, b"other node");
+    let other_pan = data_frame(true, 0x1234, B, A, b"other pan");
+    // the sniffer has the default address
+    let for_sniffer = data_frame(true, 0x1aaa, 0x3344, A, b"for sniffer");
+    let mut corrupt = for_b.clone();
+    corrupt[9] ^= 0x01;
+    let frames = [
+        for_b,
+        broadcast,
+        other_node,
+        other_pan,
+        for_sniffer,
+        corrupt,
+    ];
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+    block_on(async {
+        for f in frames.iter() {
+            handle.callback(b, "rx", Pmt::Blob(f.clone())).await?;
+            handle.callback(sniffer, "rx", Pmt::Blob(f.clone())).await?;
+        }
+        Timer::after(Duration::from_millis(20)).await;
+        handle.terminate_and_wait().await?;
+        Ok::<_, futuresdr::anyhow::Error>(())
+    })?;
+
+    assert_eq!(received(&mut rxed_rx), frames[0..2].to_vec());
+    // broadcast frames are not acknowledged
+    let acks = sent(&mut b_rx);
+    assert_eq!(acks.len(), 1);
+    assert!(is_ack(&acks[0], 42));
+
+    // the promiscuous MAC forwards all frames with valid CRC and does not send ACKs
+    assert_eq!(received(&mut sniffed_rx), frames[0..5].to_vec());
+    assert!(sent(&mut s_rx).is_empty());
+    Ok(())
+}
+
+#[test]
+fn association() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let timeout = Duration::from_millis(50);
+    let coordinator = fg.add_block(
+        MacBuilder::new()
+            .pan_id(PAN)
+            .address(0x0000)
+            .extended_address(0x0011_2233_4455_6677)
+            .coordinator(0x0010, 0x0010)
+            .ack(3, timeout)
+            .build(),
+    );
+    let first = fg.add_block(
+        MacBuilder::new()
+            .pan_id(BROADCAST_PAN)
+            .address(0xfffe)
+            .extended_address(0x1111_1111_1111_1111)
+            .ack(3, timeout)
+            .build(),
+    );
+    let second = fg.add_block(
+        MacBuilder::new()
+            .pan_id(BROADCAST_PAN)
+            .address(0xfffe)
+            .extended_address(0x2222_2222_2222_2222)
+            .ack(3, timeout)
+            .build(),
+    );
+    let (c_tx, mut c_rx) = mpsc::channel(100);
+    let (f_tx, mut f_rx) = mpsc::channel(100);
+    let (s_tx, mut s_rx) = mpsc::channel(100);
+    let c_snk = fg.add_block(ChannelSink::<u8>::new(c_tx));
+    let f_snk = fg.add_block(ChannelSink::<u8>::new(f_tx));
+    let s_snk = fg.add_block(ChannelSink::<u8>::new(s_tx));
+    fg.connect_stream(coordinator, "out", c_snk, "in")?;
+    fg.connect_stream(first, "out", f_snk, "in")?;
+    fg.connect_stream(second, "out", s_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+    let (before, first_state, second_state) = block_on(async {
+        let before = handle.callback(first, "associate", Pmt::Null).await?;
+        let request = Pmt::VecU64(vec![PAN.into(), 0x0000]);
+        for (device, rx) in [(first, &mut f_rx), (second, &mut s_rx)] {
+            handle.call(device, "associate", request.clone()).await?;
+            // request, ACK and response, ACK
+            for _ in 0..2 {
+                Timer::after(Duration::from_millis(20)).await;
+                forward(&mut handle, rx, coordinator).await?;
+                Timer::after(Duration::from_millis(20)).await;
+                forward(&mut handle, &mut c_rx, device).await?;
+            }
+        }
+        let first_state = handle.callback(first, "associate", Pmt::Null).await?;
+        let second_state = handle.callback(second, "associate", Pmt::Null).await?;
+        handle.terminate_and_wait().await?;
+        Ok::<_, futuresdr::anyhow::Error>((before, first_state, second_state))
+    })?;
+
+    assert!(matches!(before, Pmt::Null));
+    match first_state {
+        Pmt::VecU64(v) => assert_eq!(v, vec![u64::from(PAN), 0x0010]),
+        p => panic!("first device not associated: {p:?}"),
+    }
+    // only one address to allocate: the PAN is at capacity
+    assert!(matches!(second_state, Pmt::Null));
+    Ok(())
+}
@@ -23,8 +23,10 @@ pub struct Agc<T> {
     gain: f32,
     /// reference value to adjust signal power to.
     reference_power: f32,
-    /// the update rate of the loop.
-    adjustment_rate: f32,
+    /// the update rate of the loop, if the output is above the reference.
+    attack_rate: f32,
+    /// the update rate of the loop, if the output is below the reference.
+    decay_rate: f32,
     /// Set when gain should not be adjusted anymore, but rather be locked to the current value
     gain_locked: bool,
     _type: std::marker::PhantomData<T>,
@@ -40,14 +42,22 @@ where
     /// - `squelch`: surpress anything below this level
     /// - `max_gain`: maximum gain setting
     /// - `gain`: initial gain setting
+    /// - `adjustment_rate`: update rate of the loop, used for attack and decay
     /// - `reference_power`: target power level
     /// - `gain_locked`: lock gain to fixed value
     ///
     /// ## Message Handler
     ///
-    /// - `gain_locked`: set `gain_locked` parameter with a [`Pmt::Bool`].
+    /// - `gain_locked`: set `gain_locked` parameter with a [`Pmt::Bool`], i.e., freeze or
+    ///   unfreeze the gain. [`Pmt::Null`] returns the current setting.
+    /// - `gain`: set the current gain with a [`Pmt::F32`]. [`Pmt::Null`] returns the current
+    ///   gain.
     /// - `max_gain`: set `max_gain` parameter with a [`Pmt::F32`].
-    /// - `adjustment_rate`: set `adjustment_rate` with a [`Pmt::F32`].
+    /// - `adjustment_rate`: set attack and decay rate with a [`Pmt::F32`].
+    /// - `attack_rate`: set the rate, used when the output is above the reference, with a
+    ///   [`Pmt::F32`].
+    /// - `decay_rate`: set the rate, used when the output is below the reference, with a
+    ///   [`Pmt::F32`].
     /// - `reference_power`: set `reference_power` with a [`Pmt::F32`].
     ///
     /// ## Stream Input
@@ -62,9 +72,34 @@ where
         adjustment_rate: f32,
         reference_power: f32,
         gain_locked: bool,
+    ) -> Block {
+        Self::with_rates(
+            squelch,
+            max_gain,
+            gain,
+            adjustment_rate,
+            adjustment_rate,
+            reference_power,
+            gain_locked,
+        )
+    }
+
+    /// Create AGC Block with separate attack and decay rates
+    ///
+    /// The gain is adjusted with the `attack_rate`, if the output is above the reference, and
+    /// with the `decay_rate` otherwise. See [`Agc::new`] for the other parameters.
+    pub fn with_rates(
+        squelch: f32,
+        max_gain: f32,
+        gain: f32,
+        attack_rate: f32,
+        decay_rate: f32,
+        reference_power: f32,
+        gain_locked: bool,
     ) -> Block {
         assert!(max_gain >= 0.0);
         assert!(squelch >= 0.0);
+        assert!(attack_rate >= 0.0 && decay_rate >= 0.0);
 
         Block::new(
             BlockMetaBuilder::new("AGC").build(),
@@ -74,8 +109,11 @@ where
                 .build(),
             MessageIoBuilder::<Self>::new()
                 .add_input("gain_locked", Self::gain_locked)
+                .add_input("gain", Self::gain)
                 .add_input("max_gain", Self::max_gain)
                 .add_input("adjustment_rate", Self::adjustment_rate)
+                .add_input("attack_rate", Self::attack_rate)
+                .add_input("decay_rate", Self::decay_rate)
                 .add_input("reference_power", Self::reference_power)
                 .build(),
             Agc {
@@ -83,7 +121,8 @@ where
                 max_gain,
                 gain,
                 reference_power,
-                adjustment_rate,
+                attack_rate,
+                decay_rate,
                 gain_locked,
                 _type: std::marker::PhantomData,
             },
@@ -98,8 +137,62 @@ where
         _meta: &mut BlockMeta,
         p: Pmt,
     ) -> Result<Pmt> {
-        if let Pmt::Bool(l) = p {
-            self.gain_locked = l;
+        match p {
+            Pmt::Bool(l) => {
+                self.gain_locked = l;
+                Ok(Pmt::Ok)
+            }
+            Pmt::Null => Ok(Pmt::Bool(self.gain_locked)),
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn gain(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(g) if g >= 0.0 => {
+                self.gain = g.min(self.max_gain);
+                Ok(Pmt::Ok)
+            }
+            Pmt::Null => Ok(Pmt::F32(self.gain)),
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn max_gain(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(r) if r >= 0.0 => {
+                self.max_gain = r;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn adjustment_rate(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Pmt::F32(r) = p {
+            self.attack_rate = r;
+            self.decay_rate = r;
             Ok(Pmt::Ok)
         } else {
             Ok(Pmt::InvalidValue)
@@ -107,7 +200,7 @@ where
     }
 
     #[message_handler]
-    async fn max_gain(
+    async fn attack_rate(
         &mut self,
         _io: &mut WorkIo,
         _mio: &mut MessageIo<Self>,
@@ -115,7 +208,7 @@ where
         p: Pmt,
     ) -> Result<Pmt> {
         if let Pmt::F32(r) = p {
-            self.max_gain = r;
+            self.attack_rate = r;
             Ok(Pmt::Ok)
         } else {
             Ok(Pmt::InvalidValue)
@@ -123,7 +216,7 @@ where
     }
 
     #[message_handler]
-    async fn adjustment_rate(
+    async fn decay_rate(
         &mut self,
         _io: &mut WorkIo,
         _mio: &mut MessageIo<Self>,
@@ -131,7 +224,7 @@ where
         p: Pmt,
     ) -> Result<Pmt> {
         if let Pmt::F32(r) = p {
-            self.adjustment_rate = r;
+            self.decay_rate = r;
             Ok(Pmt::Ok)
         } else {
             Ok(Pmt::InvalidValue)
@@ -158,9 +251,13 @@ where
     fn scale(&mut self, input: T) -> T {
         let output = input * T::from(self.gain).unwrap();
         if !self.gain_locked {
-            self.gain +=
-                (self.reference_power - output.abs().to_f32().unwrap()) * self.adjustment_rate;
-            self.gain = self.gain.min(self.max_gain);
+            let error = self.reference_power - output.abs().to_f32().unwrap();
+            let rate = if error < 0.0 {
+                self.attack_rate
+            } else {
+                self.decay_rate
+            };
+            self.gain = (self.gain + error * rate).clamp(0.0, self.max_gain);
         }
         output
     }
@@ -216,8 +313,10 @@ where
     gain: f32,
     /// reference value to adjust signal power to.
     reference_power: f32,
-    /// the update rate of the loop.
-    adjustment_rate: f32,
+    /// the update rate of the loop, if the output is above the reference.
+    attack_rate: f32,
+    /// the update rate of the loop, if the output is below the reference.
+    decay_rate: f32,
     /// Set when gain should not be adjusted anymore, but rather be locked to the current value
     gain_locked: bool,
     _type: std::marker::PhantomData<T>,
@@ -234,7 +333,8 @@ where
     /// - `max_gain`: 65536.0
     /// - `gain`: 1.0
     /// - `reference_power`: 1.0
-    /// - `adjustment_rate`: 0.0001
+    /// - `attack_rate`: 0.0001
+    /// - `decay_rate`: 0.0001
     /// - `gain_locked`: false
     pub fn new() -> AgcBuilder<T> {
         AgcBuilder {
@@ -242,7 +342,8 @@ where
             max_gain: 65536.0,
             gain: 1.0,
             reference_power: 1.0,
-            adjustment_rate: 0.0001,
+            attack_rate: 0.0001,
+            decay_rate: 0.0001,
             gain_locked: false,
             _type: std::marker::PhantomData,
         }
@@ -260,9 +361,30 @@ where
         self
     }
 
-    /// Adjustment rate, i.e., impact of current sample on gain setting
+    /// Initial gain
+    pub fn gain(mut self, gain: f32) -> AgcBuilder<T> {
+        self.gain = gain;
+        self
+    }
+
+    /// Adjustment rate, i.e., impact of current sample on gain setting, for attack and decay
     pub fn adjustment_rate(mut self, adjustment_rate: f32) -> AgcBuilder<T> {
-        self.adjustment_rate = adjustment_rate;
+        self.attack_rate = adjustment_rate;
+        self.decay_rate = adjustment_rate;
+        self
+    }
+
+    /// Attack rate, i.e., impact of current sample on gain setting, if the output is above the
+    /// reference
+    pub fn attack_rate(mut self, attack_rate: f32) -> AgcBuilder<T> {
+        self.attack_rate = attack_rate;
+        self
+    }
+
+    /// Decay rate, i.e., impact of current sample on gain setting, if the output is below the
+    /// reference
+    pub fn decay_rate(mut self, decay_rate: f32) -> AgcBuilder<T> {
+        self.decay_rate = decay_rate;
         self
     }
 
@@ -280,11 +402,12 @@ where
 
     /// Create [`Agc`] block
     pub fn build(self) -> Block {
-        Agc::<T>::new(
+        Agc::<T>::with_rates(
             self.squelch,
             self.max_gain,
             self.gain,
-            self.adjustment_rate,
+            self.attack_rate,
+            self.decay_rate,
             self.reference_power,
             self.gain_locked,
         )
@@ -0,0 +1,286 @@
+use futuredsp::firdes;
+use futuredsp::windows;
+use std::ops::Add;
+use std::ops::Mul;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Beta of the Kaiser window of the default prototype filter (about 70 dB attenuation)
+const KAISER_BETA: f64 = 7.0;
+
+/// Arbitrary resampler, using a polyphase filter bank.
+pub struct ArbitraryResampler<T>
+where
+    T: Copy + Default + Mul<f32, Output = T> + Add<Output = T> + Send + 'static,
+{
+    rate: f64,
+    phases: usize,
+    taps_per_phase: usize,
+    custom_taps: bool,
+    bank: Vec<Vec<f32>>,
+    position: f64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T> ArbitraryResampler<T>
+where
+    T: Copy + Default + Mul<f32, Output = T> + Add<Output = T> + Send + 'static,
+{
+    /// Create ArbitraryResampler block
+    ///
+    /// Without `taps`, the lowpass prototype filter is designed for the rate.
+    pub fn new(rate: f64, phases: usize, taps_per_phase: usize, taps: Option<Vec<f32>>) -> Block {
+        assert!(rate > 0.0);
+        assert!(phases > 0 && taps_per_phase > 0);
+        let custom_taps = taps.is_some();
+        let taps = taps.unwrap_or_else(|| Self::design(rate, phases, taps_per_phase));
+        Block::new(
+            BlockMetaBuilder::new("ArbitraryResampler").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("rate", Self::rate_handler)
+                .build(),
+            ArbitraryResampler::<T> {
+                rate,
+                phases,
+                taps_per_phase,
+                custom_taps,
+                bank: Self::bank(&taps, phases, taps_per_phase),
+                position: 0.0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    /// Lowpass prototype filter at `phases` times the input rate, with unit gain per phase
+    fn design(rate: f64, phases: usize, taps_per_phase: usize) -> Vec<f32> {
+        let cutoff = 0.4 * rate.min(1.0) / phases as f64;
+        let window = windows::kaiser(phases * taps_per_phase, KAISER_BETA);
+        firdes::lowpass::<f32>(cutoff, &window)
+            .into_iter()
+            .map(|t| t * phases as f32)
+            .collect()
+    }
+
+    /// Split the prototype filter in `phases` filters, reversed to be applied to the inputs in
+    /// order, i.e., `bank[p][j] = taps[p + (taps_per_phase - 1 - j) * phases]`
+    fn bank(taps: &[f32], phases: usize, taps_per_phase: usize) -> Vec<Vec<f32>> {
+        (0..phases)
+            .map(|p| {
+                (0..taps_per_phase)
+                    .map(|j| {
+                        taps.get(p + (taps_per_phase - 1 - j) * phases)
+                            .copied()
+                            .unwrap_or(0.0)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn filter(&self, input: &[T], phase: usize) -> T {
+        input
+            .iter()
+            .zip(self.bank[phase].iter())
+            .fold(T::default(), |acc, (x, t)| acc + *x * *t)
+    }
+
+    #[message_handler]
+    async fn rate_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let rate = match p {
+            Pmt::F32(r) if r > 0.0 => r as f64,
+            Pmt::F64(r) if r > 0.0 => r,
+            Pmt::Null => return Ok(Pmt::F64(self.rate)),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if !self.custom_taps && rate.min(1.0) != self.rate.min(1.0) {
+            let taps = Self::design(rate, self.phases, self.taps_per_phase);
+            self.bank = Self::bank(&taps, self.phases, self.taps_per_phase);
+        }
+        self.rate = rate;
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T> Kernel for ArbitraryResampler<T>
+where
+    T: Copy + Default + Mul<f32, Output = T> + Add<Output = T> + Send + 'static,
+{
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+        let l = self.taps_per_phase;
+        let step = 1.0 / self.rate;
+
+        let mut produced = 0;
+        let mut input_exhausted = false;
+        while produced < o.len() {
+            let index = self.position as usize;
+            // the interpolation between the last and the first phase uses the next input
+            if index + l + 1 > i.len() {
+                input_exhausted = true;
+                break;
+            }
+            let offset = (self.position - index as f64) * self.phases as f64;
+            let phase = offset as usize;
+            let frac = (offset - phase as f64) as f32;
+
+            let a = self.filter(&i[index..index + l], phase);
+            let b = if phase + 1 < self.phases {
+                self.filter(&i[index..index + l], phase + 1)
+            } else {
+                self.filter(&i[index + 1..index + 1 + l], 0)
+            };
+            o[produced] = a * (1.0 - frac) + b * frac;
+
+            produced += 1;
+            self.position += step;
+        }
+
+        let consumed = std::cmp::min(self.position as usize, i.len());
+        self.position -= consumed as f64;
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && input_exhausted {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build an [ArbitraryResampler].
+///
+/// Resamples by an arbitrary, non-integer `rate` (output rate / input rate), like the
+/// `Polyphase Arbitrary Resampler` of GNU Radio. A lowpass prototype filter at `phases` times
+/// the input rate is split in a bank of `phases` filters. Each output is interpolated linearly
+/// between the outputs of the two filters closest to its fractional position.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `rate`: Set the rate ([`Pmt::F32`], [`Pmt::F64`]), redesigning the default
+/// prototype filter, if required. [`Pmt::Null`] returns the current rate.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Resampled samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::ArbitraryResamplerBuilder;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex32;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 2.048 MS/s -> 500 kS/s
+/// let resamp = fg.add_block(
+///     ArbitraryResamplerBuilder::<Complex32>::new(500e3 / 2.048e6)
+///         .taps_per_phase(24)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct ArbitraryResamplerBuilder<T>
+where
+    T: Copy + Default + Mul<f32, Output = T> + Add<Output = T> + Send + 'static,
+{
+    rate: f64,
+    phases: usize,
+    taps_per_phase: Option<usize>,
+    taps: Option<Vec<f32>>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T> ArbitraryResamplerBuilder<T>
+where
+    T: Copy + Default + Mul<f32, Output = T> + Add<Output = T> + Send + 'static,
+{
+    /// Create ArbitraryResampler builder, resampling by `rate` (output rate / input rate)
+    pub fn new(rate: f64) -> ArbitraryResamplerBuilder<T> {
+        ArbitraryResamplerBuilder {
+            rate,
+            phases: 32,
+            taps_per_phase: None,
+            taps: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+    /// Number of filters of the filter bank (default: 32)
+    #[must_use]
+    pub fn phases(mut self, phases: usize) -> ArbitraryResamplerBuilder<T> {
+        self.phases = phases;
+        self
+    }
+    /// Length of each filter of the filter bank (default: 16 for interpolation, scaled with
+    /// the decimation otherwise, i.e., `16 / rate`)
+    #[must_use]
+    pub fn taps_per_phase(mut self, taps_per_phase: usize) -> ArbitraryResamplerBuilder<T> {
+        self.taps_per_phase = Some(taps_per_phase);
+        self
+    }
+    /// Prototype filter at `phases` times the input rate, overriding the default lowpass
+    ///
+    /// The taps are zero-padded to a multiple of the number of phases, which sets the length
+    /// of each filter.
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<f32>) -> ArbitraryResamplerBuilder<T> {
+        self.taps = Some(taps);
+        self
+    }
+    /// Build ArbitraryResampler block
+    pub fn build(self) -> Result<Block> {
+        if !(self.rate > 0.0 && self.rate.is_finite()) {
+            bail!("rate has to be positive");
+        }
+        if self.phases == 0 {
+            bail!("number of phases has to be positive");
+        }
+        let taps_per_phase = match &self.taps {
+            Some(t) if t.is_empty() => bail!("no taps"),
+            Some(t) => (t.len() + self.phases - 1) / self.phases,
+            None => match self.taps_per_phase {
+                Some(0) => bail!("taps per phase has to be positive"),
+                Some(l) => l,
+                None => (16.0 / self.rate.min(1.0)).ceil() as usize,
+            },
+        };
+        Ok(ArbitraryResampler::<T>::new(
+            self.rate,
+            self.phases,
+            taps_per_phase,
+            self.taps,
+        ))
+    }
+}
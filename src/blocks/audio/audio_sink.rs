@@ -7,7 +7,9 @@ use futures::channel::mpsc;
 use futures::channel::oneshot;
 use futures::SinkExt;
 
-use crate::anyhow::Result;
+use crate::anyhow::{Context, Result};
+use crate::blocks::audio::resampler::device_rate;
+use crate::blocks::audio::resampler::Resampler;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -23,6 +25,9 @@ use crate::runtime::WorkIo;
 pub struct AudioSink {
     sample_rate: u32,
     channels: u16,
+    device: Option<String>,
+    device_rate: Option<u32>,
+    resampler: Option<Resampler>,
     stream: Option<Stream>,
     min_buffer_size: usize,
     vec: Vec<f32>,
@@ -39,8 +44,18 @@ const STANDARD_RATES: [u32; 4] = [24000, 44100, 48000, 96000];
 
 impl AudioSink {
     /// Create AudioSink block
+    ///
+    /// Plays to the default output device, see [AudioSinkBuilder] to select the device.
     #[allow(clippy::new_ret_no_self)]
     pub fn new(sample_rate: u32, channels: u16) -> Block {
+        AudioSinkBuilder::new(sample_rate, channels).build()
+    }
+    fn with_device(
+        sample_rate: u32,
+        channels: u16,
+        device: Option<String>,
+        device_rate: Option<u32>,
+    ) -> Block {
         Block::new(
             BlockMetaBuilder::new("AudioSink").build(),
             StreamIoBuilder::new().add_input::<f32>("in").build(),
@@ -48,6 +63,9 @@ impl AudioSink {
             AudioSink {
                 sample_rate,
                 channels,
+                device,
+                device_rate,
+                resampler: None,
                 stream: None,
                 min_buffer_size: 2048,
                 vec: Vec::new(),
@@ -91,6 +109,13 @@ impl AudioSink {
         }
         Vec::new()
     }
+    /// Get names of the output devices
+    pub fn devices() -> Vec<String> {
+        cpal::default_host()
+            .output_devices()
+            .map(|d| d.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
 }
 
 #[doc(hidden)]
@@ -103,13 +128,31 @@ impl Kernel for AudioSink {
         _b: &mut BlockMeta,
     ) -> Result<()> {
         let host = cpal::default_host();
-        let device = host
-            .default_output_device()
-            .expect("no output device available");
+        let device = match &self.device {
+            Some(name) => host
+                .output_devices()?
+                .find(|d| d.name().map(|n| &n == name).unwrap_or(false))
+                .with_context(|| format!("output device {name:?} not found"))?,
+            None => host
+                .default_output_device()
+                .context("no output device available")?,
+        };
+
+        let rate = match self.device_rate {
+            Some(r) => r,
+            None => device_rate(&device, false, self.sample_rate, self.channels)?,
+        };
+        self.resampler = Resampler::new(self.sample_rate, rate, self.channels);
+        if self.resampler.is_some() {
+            info!(
+                "AudioSink: resampling from {} to {} Hz",
+                self.sample_rate, rate
+            );
+        }
 
         let config = StreamConfig {
             channels: self.channels,
-            sample_rate: SampleRate(self.sample_rate),
+            sample_rate: SampleRate(rate),
             buffer_size: BufferSize::Default,
         };
 
@@ -189,7 +232,17 @@ impl Kernel for AudioSink {
     ) -> Result<()> {
         let i = sio.input(0).slice::<f32>();
 
-        self.vec.extend_from_slice(i);
+        let n = match self.resampler.as_mut() {
+            Some(r) => {
+                let n = i.len() - i.len() % self.channels as usize;
+                r.process(&i[..n], &mut self.vec);
+                n
+            }
+            None => {
+                self.vec.extend_from_slice(i);
+                i.len()
+            }
+        };
         if self.vec.len() >= self.min_buffer_size || sio.input(0).finished() {
             self.tx
                 .as_mut()
@@ -198,7 +251,7 @@ impl Kernel for AudioSink {
                 .await?;
         }
 
-        sio.input(0).consume(i.len());
+        sio.input(0).consume(n);
 
         if sio.input(0).finished() {
             io.finished = true;
@@ -207,3 +260,69 @@ impl Kernel for AudioSink {
         Ok(())
     }
 }
+
+/// Build an [AudioSink].
+///
+/// Plays samples at the sample rate of the flowgraph on an output device. If the device does
+/// not support this rate, the samples are resampled to the default rate of the device.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Interleaved samples of all channels
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::audio::AudioSink;
+/// use futuresdr::blocks::audio::AudioSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// println!("output devices: {:?}", AudioSink::devices());
+/// let snk = fg.add_block(
+///     AudioSinkBuilder::new(250_000, 1)
+///         .device("default")
+///         .build(),
+/// );
+/// ```
+pub struct AudioSinkBuilder {
+    sample_rate: u32,
+    channels: u16,
+    device: Option<String>,
+    device_rate: Option<u32>,
+}
+
+impl AudioSinkBuilder {
+    /// Create AudioSink builder for samples at `sample_rate` Hz with `channels` channels
+    pub fn new(sample_rate: u32, channels: u16) -> AudioSinkBuilder {
+        AudioSinkBuilder {
+            sample_rate,
+            channels,
+            device: None,
+            device_rate: None,
+        }
+    }
+    /// Name of the output device (default: default output device)
+    #[must_use]
+    pub fn device(mut self, name: &str) -> AudioSinkBuilder {
+        self.device = Some(name.to_string());
+        self
+    }
+    /// Sample rate of the device, resampling if it differs from the flowgraph rate
+    /// (default: flowgraph rate, if supported by the device, default rate of the device
+    /// otherwise)
+    #[must_use]
+    pub fn device_sample_rate(mut self, rate: u32) -> AudioSinkBuilder {
+        self.device_rate = Some(rate);
+        self
+    }
+    /// Build AudioSink
+    pub fn build(self) -> Block {
+        AudioSink::with_device(
+            self.sample_rate,
+            self.channels,
+            self.device,
+            self.device_rate,
+        )
+    }
+}
@@ -4,7 +4,9 @@ use cpal::SampleRate;
 use cpal::Stream;
 use cpal::StreamConfig;
 
-use crate::anyhow::Result;
+use crate::anyhow::{Context, Result};
+use crate::blocks::audio::resampler::device_rate;
+use crate::blocks::audio::resampler::Resampler;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -22,6 +24,9 @@ use futures::StreamExt;
 pub struct AudioSource {
     sample_rate: u32,
     channels: u16,
+    device: Option<String>,
+    device_rate: Option<u32>,
+    resampler: Option<Resampler>,
     stream: Option<Stream>,
     rx: Option<mpsc::UnboundedReceiver<Vec<f32>>>,
     buff: Option<(Vec<f32>, usize)>,
@@ -33,8 +38,18 @@ unsafe impl Send for AudioSource {}
 
 impl AudioSource {
     /// Create AudioSource block
+    ///
+    /// Records from the default input device, see [AudioSourceBuilder] to select the device.
     #[allow(clippy::new_ret_no_self)]
     pub fn new(sample_rate: u32, channels: u16) -> Block {
+        AudioSourceBuilder::new(sample_rate, channels).build()
+    }
+    fn with_device(
+        sample_rate: u32,
+        channels: u16,
+        device: Option<String>,
+        device_rate: Option<u32>,
+    ) -> Block {
         Block::new(
             BlockMetaBuilder::new("AudioSource").build(),
             StreamIoBuilder::new().add_output::<f32>("out").build(),
@@ -42,12 +57,22 @@ impl AudioSource {
             AudioSource {
                 sample_rate,
                 channels,
+                device,
+                device_rate,
+                resampler: None,
                 stream: None,
                 rx: None,
                 buff: None,
             },
         )
     }
+    /// Get names of the input devices
+    pub fn devices() -> Vec<String> {
+        cpal::default_host()
+            .input_devices()
+            .map(|d| d.filter_map(|d| d.name().ok()).collect())
+            .unwrap_or_default()
+    }
 }
 
 #[doc(hidden)]
@@ -60,13 +85,31 @@ impl Kernel for AudioSource {
         _b: &mut BlockMeta,
     ) -> Result<()> {
         let host = cpal::default_host();
-        let device = host
-            .default_input_device()
-            .expect("no input device available");
+        let device = match &self.device {
+            Some(name) => host
+                .input_devices()?
+                .find(|d| d.name().map(|n| &n == name).unwrap_or(false))
+                .with_context(|| format!("input device {name:?} not found"))?,
+            None => host
+                .default_input_device()
+                .context("no input device available")?,
+        };
+
+        let rate = match self.device_rate {
+            Some(r) => r,
+            None => device_rate(&device, true, self.sample_rate, self.channels)?,
+        };
+        self.resampler = Resampler::new(rate, self.sample_rate, self.channels);
+        if self.resampler.is_some() {
+            info!(
+                "AudioSource: resampling from {} to {} Hz",
+                rate, self.sample_rate
+            );
+        }
 
         let config = StreamConfig {
             channels: self.channels,
-            sample_rate: SampleRate(self.sample_rate),
+            sample_rate: SampleRate(rate),
             buffer_size: BufferSize::Default,
         };
 
@@ -119,6 +162,14 @@ impl Kernel for AudioSource {
             sio.output(0).produce(n);
         } else if let Some(v) = self.rx.as_mut().unwrap().next().await {
             io.call_again = true;
+            let v = match self.resampler.as_mut() {
+                Some(r) => {
+                    let mut out = Vec::new();
+                    r.process(&v, &mut out);
+                    out
+                }
+                None => v,
+            };
             self.buff = Some((v, 0));
         } else {
             io.finished = true;
@@ -127,3 +178,69 @@ impl Kernel for AudioSource {
         Ok(())
     }
 }
+
+/// Build an [AudioSource].
+///
+/// Records samples from an input device. If the device does not support the sample rate of
+/// the flowgraph, it records at its default rate and the samples are resampled.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Interleaved samples of all channels
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::audio::AudioSource;
+/// use futuresdr::blocks::audio::AudioSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// println!("input devices: {:?}", AudioSource::devices());
+/// let src = fg.add_block(
+///     AudioSourceBuilder::new(32_000, 1)
+///         .device("default")
+///         .build(),
+/// );
+/// ```
+pub struct AudioSourceBuilder {
+    sample_rate: u32,
+    channels: u16,
+    device: Option<String>,
+    device_rate: Option<u32>,
+}
+
+impl AudioSourceBuilder {
+    /// Create AudioSource builder for samples at `sample_rate` Hz with `channels` channels
+    pub fn new(sample_rate: u32, channels: u16) -> AudioSourceBuilder {
+        AudioSourceBuilder {
+            sample_rate,
+            channels,
+            device: None,
+            device_rate: None,
+        }
+    }
+    /// Name of the input device (default: default input device)
+    #[must_use]
+    pub fn device(mut self, name: &str) -> AudioSourceBuilder {
+        self.device = Some(name.to_string());
+        self
+    }
+    /// Sample rate of the device, resampling if it differs from the flowgraph rate
+    /// (default: flowgraph rate, if supported by the device, default rate of the device
+    /// otherwise)
+    #[must_use]
+    pub fn device_sample_rate(mut self, rate: u32) -> AudioSourceBuilder {
+        self.device_rate = Some(rate);
+        self
+    }
+    /// Build AudioSource
+    pub fn build(self) -> Block {
+        AudioSource::with_device(
+            self.sample_rate,
+            self.channels,
+            self.device,
+            self.device_rate,
+        )
+    }
+}
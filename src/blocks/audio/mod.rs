@@ -2,11 +2,13 @@
 #[cfg(feature = "audio")]
 mod audio_sink;
 #[cfg(feature = "audio")]
-pub use audio_sink::AudioSink;
+pub use audio_sink::{AudioSink, AudioSinkBuilder};
 #[cfg(feature = "audio")]
 mod audio_source;
 #[cfg(feature = "audio")]
-pub use audio_source::AudioSource;
+pub use audio_source::{AudioSource, AudioSourceBuilder};
+#[cfg(feature = "audio")]
+mod resampler;
 
 #[cfg(all(not(target_arch = "wasm32"), feature = "audio"))]
 mod file_source;
@@ -0,0 +1,80 @@
+use cpal::traits::DeviceTrait;
+use cpal::Device;
+use cpal::SupportedStreamConfigRange;
+use futuredsp::fir::PolyphaseResamplingFirKernel;
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+
+use crate::anyhow::Result;
+
+/// Sample rate of a device: `rate`, if the device supports it, its default rate otherwise
+pub(super) fn device_rate(device: &Device, input: bool, rate: u32, channels: u16) -> Result<u32> {
+    let supported = |c: &SupportedStreamConfigRange| {
+        c.channels() == channels && c.min_sample_rate().0 <= rate && c.max_sample_rate().0 >= rate
+    };
+    if input {
+        if device.supported_input_configs()?.any(|c| supported(&c)) {
+            return Ok(rate);
+        }
+        Ok(device.default_input_config()?.sample_rate().0)
+    } else {
+        if device.supported_output_configs()?.any(|c| supported(&c)) {
+            return Ok(rate);
+        }
+        Ok(device.default_output_config()?.sample_rate().0)
+    }
+}
+
+/// Rational resampler for interleaved samples of multiple channels
+pub(super) struct Resampler {
+    interp: usize,
+    decim: usize,
+    kernel: PolyphaseResamplingFirKernel<f32, f32, Vec<f32>, f32>,
+    pending: Vec<Vec<f32>>,
+    out: Vec<Vec<f32>>,
+}
+
+impl Resampler {
+    /// Resampler from `from` to `to` Hz, `None` if the rates are equal
+    pub(super) fn new(from: u32, to: u32, channels: u16) -> Option<Resampler> {
+        if from == to {
+            return None;
+        }
+        let gcd = num_integer::gcd(from as usize, to as usize);
+        let interp = to as usize / gcd;
+        let decim = from as usize / gcd;
+        let taps = firdes::kaiser::multirate::<f32>(interp, decim, 12, 0.0001);
+        Some(Resampler {
+            interp,
+            decim,
+            kernel: PolyphaseResamplingFirKernel::new(interp, decim, taps),
+            pending: vec![Vec::new(); channels as usize],
+            out: vec![Vec::new(); channels as usize],
+        })
+    }
+
+    /// Resample whole frames of `input` and append the interleaved output to `output`
+    pub(super) fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let channels = self.pending.len();
+        debug_assert_eq!(input.len() % channels, 0);
+        for frame in input.chunks_exact(channels) {
+            for (p, x) in self.pending.iter_mut().zip(frame) {
+                p.push(*x);
+            }
+        }
+
+        let max = self.pending[0].len() * self.interp / self.decim + self.interp;
+        let mut produced = 0;
+        for (p, o) in self.pending.iter_mut().zip(self.out.iter_mut()) {
+            o.resize(max, 0.0);
+            let (n, m, _) = self.kernel.work(&p[..], &mut o[..]);
+            p.drain(..n);
+            produced = m;
+        }
+
+        output.reserve(produced * channels);
+        for k in 0..produced {
+            output.extend(self.out.iter().map(|o| o[k]));
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use rand::rngs::StdRng;
+
+use super::gaussian;
+use super::rng;
+use crate::anyhow::{bail, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Additive white Gaussian noise, with an optional flat-fading gain.
+pub struct Awgn {
+    noise_voltage: f32,
+    signal_power: f32,
+    gain: Complex32,
+    rng: StdRng,
+}
+
+impl Awgn {
+    /// Create Awgn block, adding noise with an RMS amplitude of `noise_voltage`
+    pub fn new(noise_voltage: f32, seed: Option<u64>) -> Result<Block> {
+        let mut builder = AwgnBuilder::new(noise_voltage);
+        builder.seed = seed;
+        builder.build()
+    }
+
+    fn with_options(
+        noise_voltage: f32,
+        signal_power: f32,
+        gain: Complex32,
+        seed: Option<u64>,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Awgn").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("noise_voltage", Self::noise_voltage_handler)
+                .add_input("noise_power", Self::noise_power_handler)
+                .add_input("snr", Self::snr_handler)
+                .add_input("gain", Self::gain_handler)
+                .build(),
+            Awgn {
+                noise_voltage,
+                signal_power,
+                gain,
+                rng: rng(seed),
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn noise_voltage_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F32(self.noise_voltage)),
+            (_, Ok(v)) if v >= 0.0 && v.is_finite() => {
+                self.noise_voltage = v as f32;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn noise_power_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F32(self.noise_voltage * self.noise_voltage)),
+            (_, Ok(v)) if v >= 0.0 && v.is_finite() => {
+                self.noise_voltage = v.sqrt() as f32;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    /// SNR in dB, relative to the signal power that was configured in the builder
+    #[message_handler]
+    async fn snr_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => {
+                let noise_power = self.noise_voltage * self.noise_voltage;
+                Ok(Pmt::F32(10.0 * (self.signal_power / noise_power).log10()))
+            }
+            (_, Ok(v)) if v.is_finite() => {
+                self.noise_voltage = noise_voltage(self.signal_power, v as f32);
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn gain_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let gain = match p {
+            Pmt::Null => return Ok(Pmt::VecCF32(vec![self.gain])),
+            Pmt::F32(v) => Complex32::new(v, 0.0),
+            Pmt::F64(v) => Complex32::new(v as f32, 0.0),
+            Pmt::VecCF32(v) if v.len() == 1 => v[0],
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if !(gain.re.is_finite() && gain.im.is_finite()) {
+            return Ok(Pmt::InvalidValue);
+        }
+        self.gain = gain;
+        Ok(Pmt::Ok)
+    }
+}
+
+/// RMS amplitude of the noise for a signal of `signal_power` at `snr_db`
+fn noise_voltage(signal_power: f32, snr_db: f32) -> f32 {
+    (signal_power / 10f32.powf(snr_db / 10.0)).sqrt()
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Awgn {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        // per component
+        let sigma = self.noise_voltage / std::f32::consts::SQRT_2;
+        let m = std::cmp::min(i.len(), o.len());
+        for (v, out) in i.iter().zip(o.iter_mut()) {
+            let (re, im) = gaussian(&mut self.rng);
+            *out = v * self.gain + Complex32::new(re, im) * sigma;
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build an [Awgn] block.
+///
+/// Adds complex white Gaussian noise with an RMS amplitude of `noise_voltage`, i.e., a noise
+/// power of `noise_voltage^2`, split evenly between the I and Q components. Before adding the
+/// noise, the samples are multiplied with a flat-fading `gain` (default: one). A controller can
+/// sweep the SNR or fade the signal at runtime through the message ports.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `noise_voltage`: Set the RMS amplitude of the noise ([`Pmt::F32`],
+/// [`Pmt::F64`]).
+///
+/// **Message** `noise_power`: Set the power of the noise ([`Pmt::F32`], [`Pmt::F64`]).
+///
+/// **Message** `snr`: Set the noise for an SNR in dB, relative to the `signal_power` of the
+/// builder ([`Pmt::F32`], [`Pmt::F64`]).
+///
+/// **Message** `gain`: Set the flat-fading gain, real ([`Pmt::F32`], [`Pmt::F64`]) or complex
+/// ([`Pmt::VecCF32`] with one element).
+///
+/// For all messages, [`Pmt::Null`] returns the current value. The gain is returned as
+/// [`Pmt::VecCF32`].
+///
+/// # Outputs
+///
+/// **Stream** `out`: Noisy samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::channel::AwgnBuilder;
+/// use futuresdr::num_complex::Complex32;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 20 dB SNR for a signal with unit power
+/// let awgn = fg.add_block(AwgnBuilder::new(0.1).seed(42).build().unwrap());
+///
+/// // 10 dB SNR for a signal with a power of 2, attenuated by 3 dB
+/// let awgn = fg.add_block(
+///     AwgnBuilder::with_snr(2.0, 10.0)
+///         .gain(Complex32::new(0.0, 0.5f32.sqrt()))
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct AwgnBuilder {
+    noise_voltage: f32,
+    signal_power: f32,
+    gain: Complex32,
+    seed: Option<u64>,
+}
+
+impl AwgnBuilder {
+    /// Create Awgn builder, adding noise with an RMS amplitude of `noise_voltage`
+    pub fn new(noise_voltage: f32) -> AwgnBuilder {
+        AwgnBuilder {
+            noise_voltage,
+            signal_power: 1.0,
+            gain: Complex32::new(1.0, 0.0),
+            seed: None,
+        }
+    }
+    /// Create Awgn builder for a signal of `signal_power` at `snr_db`
+    ///
+    /// The signal power is also the reference of the `snr` message handler.
+    pub fn with_snr(signal_power: f32, snr_db: f32) -> AwgnBuilder {
+        let mut builder = AwgnBuilder::new(noise_voltage(signal_power, snr_db));
+        builder.signal_power = signal_power;
+        builder
+    }
+    /// Flat-fading gain, applied before adding the noise (default: one)
+    #[must_use]
+    pub fn gain(mut self, gain: Complex32) -> AwgnBuilder {
+        self.gain = gain;
+        self
+    }
+    /// Seed of the noise generator (default: random)
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> AwgnBuilder {
+        self.seed = Some(seed);
+        self
+    }
+    /// Build Awgn block
+    pub fn build(self) -> Result<Block> {
+        if !(self.noise_voltage >= 0.0 && self.noise_voltage.is_finite()) {
+            bail!("noise voltage has to be non-negative");
+        }
+        if !(self.signal_power > 0.0 && self.signal_power.is_finite()) {
+            bail!("signal power has to be positive");
+        }
+        if !(self.gain.re.is_finite() && self.gain.im.is_finite()) {
+            bail!("gain has to be finite");
+        }
+        Ok(Awgn::with_options(
+            self.noise_voltage,
+            self.signal_power,
+            self.gain,
+            self.seed,
+        ))
+    }
+}
@@ -0,0 +1,255 @@
+use rand::rngs::StdRng;
+use rand::Rng;
+use std::f64::consts::PI;
+
+use super::rng;
+use crate::anyhow::{bail, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Flat Rayleigh or Rician fading.
+pub struct Fading {
+    max_doppler: f64,
+    k_factor: f64,
+    /// angles of arrival of the scattered paths
+    angles: Vec<f64>,
+    /// phases of the in-phase and quadrature sinusoids
+    phases: Vec<(f64, f64)>,
+    /// angle of arrival and phase of the line of sight
+    los: (f64, f64),
+}
+
+impl Fading {
+    /// Create Fading block
+    ///
+    /// ## Parameter
+    /// - `max_doppler`: maximum Doppler frequency, normalized to the sample rate
+    /// - `k_factor`: power of the line of sight relative to the scattered paths, zero for
+    ///   Rayleigh fading
+    /// - `sinusoids`: number of sinusoids of the sum-of-sinusoids model
+    /// - `seed`: seed of the random angles and phases, `None` for a random seed
+    pub fn new(max_doppler: f64, k_factor: f64, sinusoids: usize, seed: Option<u64>) -> Block {
+        assert!(max_doppler >= 0.0 && k_factor >= 0.0 && sinusoids > 0);
+        let mut rng = rng(seed);
+        let theta = Self::uniform(&mut rng);
+        // Zheng and Xiao: alpha_n = (2 pi n - pi + theta) / (4 M)
+        let angles = (1..=sinusoids)
+            .map(|n| (2.0 * PI * n as f64 - PI + theta) / (4 * sinusoids) as f64)
+            .collect();
+        let phases = (0..sinusoids)
+            .map(|_| (Self::uniform(&mut rng), Self::uniform(&mut rng)))
+            .collect();
+        let los = (Self::uniform(&mut rng), Self::uniform(&mut rng));
+        Block::new(
+            BlockMetaBuilder::new("Fading").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("max_doppler", Self::max_doppler_handler)
+                .add_input("k_factor", Self::k_factor_handler)
+                .build(),
+            Fading {
+                max_doppler,
+                k_factor,
+                angles,
+                phases,
+                los,
+            },
+        )
+    }
+
+    fn uniform(rng: &mut StdRng) -> f64 {
+        rng.gen_range(-PI..PI)
+    }
+
+    /// Channel gain of the current sample, advancing the sinusoids to the next sample
+    fn gain(&mut self) -> Complex32 {
+        let w = 2.0 * PI * self.max_doppler;
+        let mut scattered = (0.0, 0.0);
+        for (alpha, (phi, psi)) in self.angles.iter().zip(self.phases.iter_mut()) {
+            scattered.0 += phi.cos();
+            scattered.1 += psi.sin();
+            *phi = (*phi + w * alpha.cos()) % (2.0 * PI);
+            *psi = (*psi + w * alpha.sin()) % (2.0 * PI);
+        }
+        let scale = (1.0 / self.angles.len() as f64).sqrt();
+        let scattered = Complex32::new((scattered.0 * scale) as f32, (scattered.1 * scale) as f32);
+
+        let (angle, phase) = &mut self.los;
+        let los = Complex32::from_polar(1.0, *phase as f32);
+        *phase = (*phase + w * angle.cos()) % (2.0 * PI);
+
+        let k = self.k_factor;
+        los * (k / (k + 1.0)).sqrt() as f32 + scattered * (1.0 / (k + 1.0)).sqrt() as f32
+    }
+
+    #[message_handler]
+    async fn max_doppler_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F64(self.max_doppler)),
+            (_, Ok(f)) if f >= 0.0 => {
+                self.max_doppler = f;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn k_factor_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F64(self.k_factor)),
+            (_, Ok(k)) if k >= 0.0 => {
+                self.k_factor = k;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Fading {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (v, out) in i.iter().zip(o.iter_mut()) {
+            *out = v * self.gain();
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [Fading] block.
+///
+/// Multiplies the samples with the gain of a flat fading channel, using the sum-of-sinusoids
+/// model of Zheng and Xiao. Without a line of sight (`k_factor` zero), the gain is Rayleigh
+/// distributed. With a line of sight, it is Rician distributed. The average power gain is one.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `max_doppler`: Set the maximum Doppler frequency, normalized to the sample rate
+/// ([`Pmt::F32`], [`Pmt::F64`]). [`Pmt::Null`] returns the current frequency.
+///
+/// **Message** `k_factor`: Set the Rician K-factor, i.e., the power of the line of sight
+/// relative to the scattered paths ([`Pmt::F32`], [`Pmt::F64`]). [`Pmt::Null`] returns the
+/// current K-factor.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Faded samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::channel::FadingBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 100 Hz Doppler at 1 MS/s with a line of sight
+/// let fading = fg.add_block(
+///     FadingBuilder::new(100.0 / 1e6)
+///         .k_factor(4.0)
+///         .seed(42)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct FadingBuilder {
+    max_doppler: f64,
+    k_factor: f64,
+    sinusoids: usize,
+    seed: Option<u64>,
+}
+
+impl FadingBuilder {
+    /// Create Fading builder with the maximum Doppler frequency, normalized to the sample rate
+    pub fn new(max_doppler: f64) -> FadingBuilder {
+        FadingBuilder {
+            max_doppler,
+            k_factor: 0.0,
+            sinusoids: 8,
+            seed: None,
+        }
+    }
+    /// Rician K-factor (default: 0, i.e., Rayleigh fading)
+    #[must_use]
+    pub fn k_factor(mut self, k_factor: f64) -> FadingBuilder {
+        self.k_factor = k_factor;
+        self
+    }
+    /// Number of sinusoids of the model (default: 8)
+    #[must_use]
+    pub fn sinusoids(mut self, sinusoids: usize) -> FadingBuilder {
+        self.sinusoids = sinusoids;
+        self
+    }
+    /// Seed of the random angles and phases (default: random)
+    #[must_use]
+    pub fn seed(mut self, seed: u64) -> FadingBuilder {
+        self.seed = Some(seed);
+        self
+    }
+    /// Build Fading block
+    pub fn build(self) -> Result<Block> {
+        if !(self.max_doppler >= 0.0 && self.max_doppler < 0.5) {
+            bail!("maximum Doppler frequency has to be in [0, 0.5)");
+        }
+        if !(self.k_factor >= 0.0 && self.k_factor.is_finite()) {
+            bail!("K-factor has to be non-negative");
+        }
+        if self.sinusoids == 0 {
+            bail!("at least one sinusoid required");
+        }
+        Ok(Fading::new(
+            self.max_doppler,
+            self.k_factor,
+            self.sinusoids,
+            self.seed,
+        ))
+    }
+}
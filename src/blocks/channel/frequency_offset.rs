@@ -0,0 +1,140 @@
+use std::f64::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Static carrier frequency and phase offset.
+///
+/// Rotates the samples by `exp(j(2π frequency n + phase))`.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `frequency`: Set the frequency offset, normalized to the sample rate
+/// ([`Pmt::F32`], [`Pmt::F64`]). [`Pmt::Null`] returns the current offset.
+///
+/// **Message** `phase`: Set the phase offset in radians ([`Pmt::F32`], [`Pmt::F64`]).
+/// [`Pmt::Null`] returns the current offset.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Rotated samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::channel::FrequencyOffset;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 1 kHz at 1 MS/s
+/// let cfo = fg.add_block(FrequencyOffset::new(1e3 / 1e6, 0.0));
+/// ```
+pub struct FrequencyOffset {
+    frequency: f64,
+    phase: f64,
+    accumulator: f64,
+}
+
+impl FrequencyOffset {
+    /// Create FrequencyOffset block with the `frequency` offset in cycles per sample and the
+    /// `phase` offset in radians
+    pub fn new(frequency: f64, phase: f64) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("FrequencyOffset").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("frequency", Self::frequency_handler)
+                .add_input("phase", Self::phase_handler)
+                .build(),
+            FrequencyOffset {
+                frequency,
+                phase,
+                accumulator: 0.0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn frequency_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F64(self.frequency)),
+            (_, Ok(f)) => {
+                self.frequency = f;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn phase_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F64(self.phase)),
+            (_, Ok(phase)) => {
+                self.phase = phase;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for FrequencyOffset {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        let step = 2.0 * PI * self.frequency;
+        for (v, out) in i.iter().zip(o.iter_mut()) {
+            *out = v * Complex32::from_polar(1.0, (self.accumulator + self.phase) as f32);
+            self.accumulator = (self.accumulator + step) % (2.0 * PI);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
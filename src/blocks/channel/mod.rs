@@ -0,0 +1,43 @@
+//! ## Channel Model Blocks
+//!
+//! Impairments to test receivers without hardware: noise ([Awgn]), carrier frequency and phase
+//! offsets ([FrequencyOffset]), fractional timing and sample clock offsets ([TimingOffset]),
+//! multipath ([Multipath]), and Rayleigh or Rician fading ([Fading]). All parameters can be
+//! changed at runtime through message ports. Random impairments can be seeded to make
+//! impairment sweeps reproducible.
+
+mod awgn;
+pub use awgn::{Awgn, AwgnBuilder};
+
+mod fading;
+pub use fading::{Fading, FadingBuilder};
+
+mod frequency_offset;
+pub use frequency_offset::FrequencyOffset;
+
+mod multipath;
+pub use multipath::Multipath;
+
+mod timing_offset;
+pub use timing_offset::TimingOffset;
+
+use rand::rngs::StdRng;
+use rand::Rng;
+use rand::SeedableRng;
+
+/// Random number generator, seeded with `seed` or from the OS
+fn rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(s) => StdRng::seed_from_u64(s),
+        None => StdRng::from_entropy(),
+    }
+}
+
+/// Pair of independent standard normal samples (Box-Muller)
+fn gaussian(rng: &mut StdRng) -> (f32, f32) {
+    let u: f32 = 1.0 - rng.gen::<f32>();
+    let v: f32 = rng.gen();
+    let r = (-2.0 * u.ln()).sqrt();
+    let phi = 2.0 * std::f32::consts::PI * v;
+    (r * phi.cos(), r * phi.sin())
+}
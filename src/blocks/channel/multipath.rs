@@ -0,0 +1,133 @@
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Tapped-delay-line multipath channel.
+///
+/// Each tap is the complex gain of a path, delayed by its index in samples, i.e.,
+/// `y[n] = sum_k taps[k] * x[n - k]`.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `taps`: Set the taps ([`Pmt::VecCF32`]). [`Pmt::Null`] returns the current taps.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Samples, received over all paths
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::channel::Multipath;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex32;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // direct path and an echo, delayed by three samples
+/// let multipath = fg.add_block(Multipath::new(vec![
+///     Complex32::new(1.0, 0.0),
+///     Complex32::new(0.0, 0.0),
+///     Complex32::new(0.0, 0.0),
+///     Complex32::new(0.3, -0.2),
+/// ]));
+/// ```
+pub struct Multipath {
+    taps: Vec<Complex32>,
+    /// last `taps.len() - 1` input samples, followed by the samples of the current call
+    history: Vec<Complex32>,
+}
+
+impl Multipath {
+    /// Create Multipath block
+    pub fn new(taps: Vec<Complex32>) -> Block {
+        assert!(!taps.is_empty());
+        Block::new(
+            BlockMetaBuilder::new("Multipath").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("taps", Self::taps_handler)
+                .build(),
+            Multipath {
+                history: vec![Complex32::new(0.0, 0.0); taps.len() - 1],
+                taps,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn taps_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::VecCF32(taps) if !taps.is_empty() => {
+                // keep the most recent samples
+                let len = taps.len() - 1;
+                if len > self.history.len() {
+                    let zeros = vec![Complex32::new(0.0, 0.0); len - self.history.len()];
+                    self.history.splice(0..0, zeros);
+                } else {
+                    self.history.drain(..self.history.len() - len);
+                }
+                self.taps = taps;
+                Ok(Pmt::Ok)
+            }
+            Pmt::Null => Ok(Pmt::VecCF32(self.taps.clone())),
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Multipath {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        let delay = self.taps.len() - 1;
+        self.history.extend_from_slice(&i[..m]);
+        for (n, out) in o[..m].iter_mut().enumerate() {
+            *out = self
+                .taps
+                .iter()
+                .enumerate()
+                .map(|(k, t)| t * self.history[n + delay - k])
+                .sum();
+        }
+        self.history.drain(..m);
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
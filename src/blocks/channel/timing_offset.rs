@@ -0,0 +1,199 @@
+use std::f64::consts::PI;
+
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Length of the windowed-sinc interpolator
+const TAPS: usize = 16;
+/// Input samples, kept before the current position, to allow increasing the delay at runtime
+const HISTORY: usize = 64;
+
+/// Fractional timing offset and sample clock offset.
+///
+/// Resamples the input with a windowed-sinc interpolator, i.e., output `n` is the input at
+/// `n / (1 + ppm * 1e-6) - delay`. A positive `ppm` models a receiver clock that is faster than
+/// the transmitter clock, i.e., there are more output than input samples. Increasing the delay at
+/// runtime by more than 64 samples inserts zeros.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `delay`: Set the delay in samples ([`Pmt::F32`], [`Pmt::F64`]). [`Pmt::Null`]
+/// returns the current delay.
+///
+/// **Message** `ppm`: Set the sample clock offset in parts per million ([`Pmt::F32`],
+/// [`Pmt::F64`]). [`Pmt::Null`] returns the current offset.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Resampled samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::channel::TimingOffset;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // quarter sample delay and 20 ppm clock offset
+/// let timing = fg.add_block(TimingOffset::new(0.25, 20.0));
+/// ```
+pub struct TimingOffset {
+    delay: f64,
+    ppm: f64,
+    /// input time of the next output, relative to the first sample in the buffer
+    position: f64,
+    buffer: Vec<Complex32>,
+}
+
+impl TimingOffset {
+    /// Create TimingOffset block with a `delay` in samples and a sample clock offset in `ppm`
+    pub fn new(delay: f64, ppm: f64) -> Block {
+        assert!(delay >= 0.0);
+        assert!(ppm > -1e6);
+        Block::new(
+            BlockMetaBuilder::new("TimingOffset").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("delay", Self::delay_handler)
+                .add_input("ppm", Self::ppm_handler)
+                .build(),
+            TimingOffset {
+                delay,
+                ppm,
+                position: 0.0,
+                buffer: Vec::new(),
+            },
+        )
+    }
+
+    /// Input samples per output sample
+    fn step(&self) -> f64 {
+        1.0 / (1.0 + self.ppm * 1e-6)
+    }
+
+    /// Windowed-sinc interpolation of the buffer at time `t`, with zeros before the buffer
+    fn interpolate(&self, t: f64) -> Complex32 {
+        let index = t.floor();
+        let frac = t - index;
+        let half = (TAPS / 2) as f64;
+        let mut sum = Complex32::new(0.0, 0.0);
+        let mut gain = 0.0;
+        for m in -(TAPS as isize / 2 - 1)..=(TAPS as isize / 2) {
+            let x = frac - m as f64;
+            let sinc = if x.abs() < 1e-9 {
+                1.0
+            } else {
+                (PI * x).sin() / (PI * x)
+            };
+            // Blackman window
+            let window = 0.42 + 0.5 * (PI * x / half).cos() + 0.08 * (2.0 * PI * x / half).cos();
+            let tap = sinc * window;
+            gain += tap;
+            let j = index as isize + m;
+            if j >= 0 {
+                sum += self.buffer[j as usize] * tap as f32;
+            }
+        }
+        sum / gain as f32
+    }
+
+    #[message_handler]
+    async fn delay_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F64(self.delay)),
+            (_, Ok(d)) if d >= 0.0 => {
+                self.delay = d;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn ppm_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F64(self.ppm)),
+            (_, Ok(ppm)) if ppm > -1e6 => {
+                self.ppm = ppm;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for TimingOffset {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+        let step = self.step();
+
+        // buffer only the inputs, required for the available output space
+        let last = self.position - self.delay + o.len() as f64 * step;
+        let wanted = (last.max(0.0) as usize + TAPS / 2 + 1).saturating_sub(self.buffer.len());
+        let n = std::cmp::min(i.len(), wanted);
+        self.buffer.extend_from_slice(&i[..n]);
+
+        let mut produced = 0;
+        while produced < o.len() {
+            let t = self.position - self.delay;
+            if t.floor() + (TAPS / 2) as f64 >= self.buffer.len() as f64 {
+                break;
+            }
+            o[produced] = self.interpolate(t);
+            self.position += step;
+            produced += 1;
+        }
+
+        let keep = (self.position - self.delay).floor() - (TAPS / 2 + HISTORY) as f64;
+        let drained = std::cmp::min(keep.max(0.0) as usize, self.buffer.len());
+        self.buffer.drain(..drained);
+        self.position -= drained as f64;
+
+        sio.input(0).consume(n);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && n == i.len() && produced < o.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,172 @@
+use num_complex::Complex64;
+use serde::Deserialize;
+use serde::Serialize;
+use std::f64::consts::PI;
+
+use crate::anyhow::{bail, Result};
+
+/// Speed of light in m/s.
+const SPEED_OF_LIGHT: f64 = 299_792_458.0;
+
+/// Antenna array geometry.
+///
+/// Element positions are given in meters in the horizontal plane. Bearings are in degrees,
+/// measured counter-clockwise from the x-axis.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ArrayGeometry {
+    positions: Vec<(f64, f64)>,
+}
+
+impl ArrayGeometry {
+    /// Create array from element positions `(x, y)` in meters
+    pub fn new(positions: Vec<(f64, f64)>) -> Self {
+        Self { positions }
+    }
+
+    /// Uniform linear array with `n` elements along the x-axis, `spacing` meters apart
+    ///
+    /// A linear array cannot distinguish bearings mirrored at its axis, so it should only scan
+    /// from 0 to 180 degrees.
+    pub fn uniform_linear(n: usize, spacing: f64) -> Self {
+        Self::new((0..n).map(|i| (i as f64 * spacing, 0.0)).collect())
+    }
+
+    /// Uniform circular array with `n` elements on a circle with `radius` meters
+    ///
+    /// The first element is on the x-axis, the following elements are placed counter-clockwise.
+    pub fn uniform_circular(n: usize, radius: f64) -> Self {
+        Self::new(
+            (0..n)
+                .map(|i| {
+                    let phi = 2.0 * PI * i as f64 / n as f64;
+                    (radius * phi.cos(), radius * phi.sin())
+                })
+                .collect(),
+        )
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Whether the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Element positions `(x, y)` in meters
+    pub fn positions(&self) -> &[(f64, f64)] {
+        &self.positions
+    }
+
+    /// Response of the array to a plane wave from `bearing` degrees at `frequency` Hz
+    ///
+    /// The phase of each element is relative to the origin of the coordinate system.
+    pub fn steering_vector(&self, bearing: f64, frequency: f64) -> Vec<Complex64> {
+        let k = 2.0 * PI * frequency / SPEED_OF_LIGHT;
+        let (s, c) = bearing.to_radians().sin_cos();
+        self.positions
+            .iter()
+            .map(|(x, y)| Complex64::from_polar(1.0, k * (x * c + y * s)))
+            .collect()
+    }
+}
+
+/// Eigendecomposition of a Hermitian matrix with the cyclic Jacobi method.
+///
+/// Returns the eigenvalues in ascending order together with the corresponding eigenvectors.
+pub(crate) fn eigh(mut a: Vec<Vec<Complex64>>) -> Vec<(f64, Vec<Complex64>)> {
+    let n = a.len();
+    let mut v: Vec<Vec<Complex64>> = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| Complex64::new(if i == j { 1.0 } else { 0.0 }, 0.0))
+                .collect()
+        })
+        .collect();
+
+    let norm: f64 = a.iter().flatten().map(|x| x.norm_sqr()).sum();
+    for _ in 0..100 {
+        let off: f64 = (0..n)
+            .flat_map(|i| (0..n).filter(move |j| *j != i).map(move |j| (i, j)))
+            .map(|(i, j)| a[i][j].norm_sqr())
+            .sum();
+        if off <= 1e-24 * norm {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                let b = a[p][q].norm();
+                if b == 0.0 {
+                    continue;
+                }
+                // Rotate the sub-matrix into a real one, then apply a Jacobi rotation.
+                let e = a[p][q] / b;
+                let theta = 0.5 * (2.0 * b).atan2(a[q][q].re - a[p][p].re);
+                let (s, c) = theta.sin_cos();
+
+                for row in a.iter_mut().chain(v.iter_mut()) {
+                    let (x, y) = (row[p], row[q]);
+                    row[p] = x * c - y * e.conj() * s;
+                    row[q] = x * s + y * e.conj() * c;
+                }
+                for k in 0..n {
+                    let (x, y) = (a[p][k], a[q][k]);
+                    a[p][k] = x * c - y * e * s;
+                    a[q][k] = x * s + y * e * c;
+                }
+            }
+        }
+    }
+
+    let mut eig: Vec<(f64, Vec<Complex64>)> = (0..n)
+        .map(|i| (a[i][i].re, v.iter().map(|row| row[i]).collect()))
+        .collect();
+    eig.sort_by(|x, y| x.0.total_cmp(&y.0));
+    eig
+}
+
+/// Indices of the `n` largest local maxima of `spectrum`, strongest first.
+///
+/// If `circular` is set, the first and last values are considered neighbors.
+pub(crate) fn find_peaks(spectrum: &[f32], n: usize, circular: bool) -> Vec<usize> {
+    let l = spectrum.len();
+    let mut peaks: Vec<usize> = (0..l)
+        .filter(|i| {
+            let left = if *i > 0 {
+                Some(spectrum[i - 1])
+            } else if circular {
+                Some(spectrum[l - 1])
+            } else {
+                None
+            };
+            let right = if i + 1 < l {
+                Some(spectrum[i + 1])
+            } else if circular {
+                Some(spectrum[0])
+            } else {
+                None
+            };
+            left.map_or(true, |s| spectrum[*i] > s) && right.map_or(true, |s| spectrum[*i] >= s)
+        })
+        .collect();
+    peaks.sort_by(|a, b| spectrum[*b].total_cmp(&spectrum[*a]));
+    peaks.truncate(n);
+    peaks
+}
+
+/// Bearings of a scan range and whether the range wraps around the full circle.
+pub(crate) fn scan_grid((start, stop, step): (f64, f64, f64)) -> Result<(Vec<f64>, bool)> {
+    if step <= 0.0 || stop <= start {
+        bail!("invalid scan range");
+    }
+    let circular = stop - start >= 360.0;
+    let mut n = ((stop - start) / step + 1e-9).floor() as usize + 1;
+    if circular && start + (n - 1) as f64 * step >= start + 360.0 - 1e-9 {
+        n -= 1;
+    }
+    Ok(((0..n).map(|i| start + i as f64 * step).collect(), circular))
+}
@@ -0,0 +1,417 @@
+use num_complex::Complex32;
+use num_complex::Complex64;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+
+use crate::anyhow::{bail, Result};
+use crate::blocks::doa::array::find_peaks;
+use crate::blocks::doa::array::scan_grid;
+use crate::blocks::doa::interferometer::compute_manifold;
+use crate::blocks::doa::interferometer::correlate;
+use crate::blocks::doa::music::music_spectrum;
+use crate::blocks::doa::ArrayManifold;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Direction-finding algorithm of a [DoaEstimator].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DoaMethod {
+    /// Phase interferometry, i.e., correlation of the phase differences relative to the first
+    /// channel with the response of the array
+    Interferometry,
+    /// MUSIC with the given number of signals that span the signal subspace
+    Music {
+        /// Number of signals
+        sources: usize,
+    },
+}
+
+/// Estimate direction of arrival from `N` coherent channels.
+pub struct DoaEstimator {
+    array: ArrayManifold,
+    frequency: f64,
+    method: DoaMethod,
+    snapshots: usize,
+    bearings: Vec<f64>,
+    circular: bool,
+    manifold: Vec<Vec<Complex64>>,
+    covariance: Vec<Vec<Complex64>>,
+    n: usize,
+    gated: bool,
+    bursts: VecDeque<(u64, u64)>,
+    offset: u64,
+    due: bool,
+}
+
+impl DoaEstimator {
+    /// Create DoaEstimator block
+    pub fn new(
+        array: ArrayManifold,
+        frequency: f64,
+        method: DoaMethod,
+        snapshots: usize,
+        bearings: Vec<f64>,
+        circular: bool,
+        gated: bool,
+    ) -> Block {
+        let elements = array.len();
+        assert!(elements >= 2);
+        assert!(snapshots > 0);
+        if let DoaMethod::Music { sources } = method {
+            assert!(sources > 0 && sources < elements);
+        }
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..elements {
+            sio = sio.add_input::<Complex32>(&format!("in{i}"));
+        }
+
+        let manifold = match method {
+            DoaMethod::Interferometry => compute_manifold(&array, frequency, &bearings),
+            DoaMethod::Music { .. } => Vec::new(),
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("DoaEstimator").build(),
+            sio.add_output::<f32>("out").build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::freq_handler)
+                .add_input("burst", Self::burst_handler)
+                .add_output("bearing")
+                .add_output("spectrum")
+                .build(),
+            DoaEstimator {
+                array,
+                frequency,
+                method,
+                snapshots,
+                bearings,
+                circular,
+                manifold,
+                covariance: vec![vec![Complex64::new(0.0, 0.0); elements]; elements],
+                n: 0,
+                gated,
+                bursts: VecDeque::new(),
+                offset: 0,
+                due: false,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn freq_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let f = match p {
+            Pmt::F32(f) if f > 0.0 => f as f64,
+            Pmt::F64(f) if f > 0.0 => f,
+            Pmt::U32(f) if f > 0 => f as f64,
+            Pmt::U64(f) if f > 0 => f as f64,
+            Pmt::Null => return Ok(Pmt::F64(self.frequency)),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        self.frequency = f;
+        if self.method == DoaMethod::Interferometry {
+            self.manifold = compute_manifold(&self.array, f, &self.bearings);
+        }
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn burst_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let (start, len): (Option<u64>, u64) = match p {
+            Pmt::U32(_) | Pmt::U64(_) | Pmt::Usize(_) => (None, p.try_into()?),
+            Pmt::MapStrPmt(mut m) => {
+                let start: Option<u64> = match m.remove("start") {
+                    Some(s) => match s.try_into() {
+                        Ok(s) => Some(s),
+                        Err(_) => return Ok(Pmt::InvalidValue),
+                    },
+                    None => None,
+                };
+                match m.remove("len").map(TryInto::<u64>::try_into) {
+                    Some(Ok(l)) => (start, l),
+                    _ => return Ok(Pmt::InvalidValue),
+                }
+            }
+            Pmt::Finished => return Ok(Pmt::Ok),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if len == 0 {
+            return Ok(Pmt::InvalidValue);
+        }
+        if !self.gated {
+            return Ok(Pmt::Ok);
+        }
+
+        let start = start.unwrap_or(self.offset);
+        let end = start + len;
+        match self.bursts.back_mut() {
+            Some((_, e)) if start <= *e => *e = std::cmp::max(*e, end),
+            _ => self.bursts.push_back((start, end)),
+        }
+        Ok(Pmt::Ok)
+    }
+
+    /// Add a snapshot to the covariance matrix.
+    fn accumulate(&mut self, inputs: &[&[Complex32]], k: usize) {
+        let x: Vec<Complex64> = inputs
+            .iter()
+            .map(|i| Complex64::new(i[k].re as f64, i[k].im as f64))
+            .collect();
+        for (row, xi) in self.covariance.iter_mut().zip(x.iter()) {
+            for (r, xj) in row.iter_mut().zip(x.iter()) {
+                *r += *xi * xj.conj();
+            }
+        }
+        self.n += 1;
+    }
+
+    /// Spectrum over the scan grid and indices of its peaks, strongest first.
+    fn estimate(&self) -> (Vec<f32>, Vec<usize>) {
+        match self.method {
+            DoaMethod::Interferometry => {
+                let correlation: Vec<Complex64> = self.covariance.iter().map(|r| r[0]).collect();
+                let c = correlate(&self.manifold, &correlation);
+                let peaks = find_peaks(&c, 1, self.circular);
+                (c, peaks)
+            }
+            DoaMethod::Music { sources } => {
+                let s = music_spectrum(
+                    &self.covariance,
+                    &self.array,
+                    self.frequency,
+                    sources,
+                    &self.bearings,
+                );
+                let peaks = find_peaks(&s, sources, self.circular);
+                (s, peaks)
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for DoaEstimator {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let elements = self.array.len();
+        let inputs: Vec<&[Complex32]> = (0..elements)
+            .map(|i| sio.input(i).slice::<Complex32>())
+            .collect();
+        let m = inputs.iter().map(|i| i.len()).min().unwrap_or(0);
+
+        let mut k = 0;
+        while k < m && !self.due {
+            if !self.gated {
+                self.accumulate(&inputs, k);
+                k += 1;
+                self.due = self.n == self.snapshots;
+                continue;
+            }
+
+            let pos = self.offset + k as u64;
+            while matches!(self.bursts.front(), Some((_, end)) if *end <= pos) {
+                self.bursts.pop_front();
+            }
+            match self.bursts.front() {
+                Some((start, end)) if *start <= pos => {
+                    let end = *end;
+                    self.accumulate(&inputs, k);
+                    k += 1;
+                    self.due = self.n == self.snapshots || pos + 1 == end;
+                }
+                // discard samples until the next burst
+                Some((start, _)) => k = std::cmp::min(m, (*start - self.offset) as usize),
+                None => k = m,
+            }
+        }
+
+        for i in 0..elements {
+            sio.input(i).consume(k);
+        }
+        self.offset += k as u64;
+
+        let out = sio.output(0).slice::<f32>();
+        if self.due && !out.is_empty() {
+            let (spectrum, peaks) = self.estimate();
+            let bearings: Vec<f32> = peaks.into_iter().map(|i| self.bearings[i] as f32).collect();
+            let bearing = bearings.first().copied().unwrap_or(f32::NAN);
+
+            out[0] = bearing;
+            sio.output(0).produce(1);
+            mio.post(
+                0,
+                Pmt::MapStrPmt(HashMap::from([
+                    ("bearing".to_string(), Pmt::F32(bearing)),
+                    ("bearings".to_string(), Pmt::VecF32(bearings)),
+                ])),
+            )
+            .await;
+            mio.post(1, Pmt::VecF32(spectrum)).await;
+
+            for row in self.covariance.iter_mut() {
+                row.fill(Complex64::new(0.0, 0.0));
+            }
+            self.n = 0;
+            self.due = false;
+            io.call_again = true;
+        }
+
+        if !self.due && (0..elements).any(|i| sio.input(i).finished() && inputs[i].len() == k) {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [DoaEstimator].
+///
+/// Estimates the spatial covariance of `N` coherent channels over a number of snapshots and
+/// computes the bearing with phase interferometry or MUSIC (see [DoaMethod]). The response of
+/// the array is computed from the [ArrayGeometry](super::ArrayGeometry), weighted with the
+/// element calibration, if an [ArrayManifold] is given. Bearings are in degrees, measured
+/// counter-clockwise from the x-axis.
+///
+/// If the estimator is [gated](DoaEstimatorBuilder::gated), only samples within bursts, e.g.,
+/// frames reported by a detector, are used and samples in between are discarded. An estimate
+/// is made after the given number of snapshots or at the end of each burst, so that the
+/// estimates are not affected by noise between the frames.
+///
+/// # Inputs
+///
+/// **Stream** `in0`..`in{N-1}`: Channels of the array elements, in the order of the array
+/// geometry
+///
+/// **Message** `freq`: Set the center frequency in Hz ([`Pmt::F32`], [`Pmt::F64`],
+/// [`Pmt::U32`], [`Pmt::U64`]). [`Pmt::Null`] returns the current frequency.
+///
+/// **Message** `burst`: Gate a burst of `len` samples, given as [`Pmt::U32`], [`Pmt::U64`], or
+/// [`Pmt::Usize`], which starts at the next sample to be processed, or a [`Pmt::MapStrPmt`]
+/// with the `len` and the `start` index of the burst in the input stream. Overlapping bursts
+/// are merged. Ignored if the estimator is not gated.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Strongest bearing of each estimate
+///
+/// **Message** `bearing`: [`Pmt::MapStrPmt`] with the strongest `bearing` ([`Pmt::F32`]) and
+/// the `bearings` of all estimated signals, strongest first ([`Pmt::VecF32`])
+///
+/// **Message** `spectrum`: Spectrum for each bearing of the scan grid ([`Pmt::VecF32`]), i.e.,
+/// the correlation between 0 and 1 for interferometry and the pseudo-spectrum in dB for MUSIC
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::ArrayGeometry;
+/// use futuresdr::blocks::doa::DoaEstimatorBuilder;
+/// use futuresdr::blocks::doa::DoaMethod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let doa = fg.add_block(
+///     DoaEstimatorBuilder::new(ArrayGeometry::uniform_linear(2, 0.17), 868e6)
+///         .method(DoaMethod::Interferometry)
+///         .scan(0.0, 180.0, 0.5)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct DoaEstimatorBuilder {
+    array: ArrayManifold,
+    frequency: f64,
+    method: DoaMethod,
+    snapshots: usize,
+    scan: (f64, f64, f64),
+    gated: bool,
+}
+
+impl DoaEstimatorBuilder {
+    /// Create DoaEstimator builder for an array operating at `frequency` Hz
+    pub fn new(array: impl Into<ArrayManifold>, frequency: f64) -> DoaEstimatorBuilder {
+        DoaEstimatorBuilder {
+            array: array.into(),
+            frequency,
+            method: DoaMethod::Music { sources: 1 },
+            snapshots: 1024,
+            scan: (0.0, 360.0, 1.0),
+            gated: false,
+        }
+    }
+    /// Direction-finding algorithm (default: MUSIC with one source)
+    #[must_use]
+    pub fn method(mut self, method: DoaMethod) -> DoaEstimatorBuilder {
+        self.method = method;
+        self
+    }
+    /// Number of samples per estimate (default: 1024)
+    #[must_use]
+    pub fn snapshots(mut self, snapshots: usize) -> DoaEstimatorBuilder {
+        self.snapshots = snapshots;
+        self
+    }
+    /// Bearings to scan from `start` to `stop` degrees in steps of `step` degrees
+    /// (default: full circle in 1 degree steps)
+    #[must_use]
+    pub fn scan(mut self, start: f64, stop: f64, step: f64) -> DoaEstimatorBuilder {
+        self.scan = (start, stop, step);
+        self
+    }
+    /// Only use samples within bursts that are announced on the `burst` message input
+    /// (default: false)
+    #[must_use]
+    pub fn gated(mut self, gated: bool) -> DoaEstimatorBuilder {
+        self.gated = gated;
+        self
+    }
+    /// Build DoaEstimator block
+    pub fn build(self) -> Result<Block> {
+        if self.array.len() < 2 {
+            bail!("DoA estimation requires at least two array elements");
+        }
+        if let DoaMethod::Music { sources } = self.method {
+            if sources == 0 || sources >= self.array.len() {
+                bail!("number of sources has to be between 1 and the number of elements - 1");
+            }
+        }
+        if self.snapshots == 0 {
+            bail!("number of snapshots has to be positive");
+        }
+        let (bearings, circular) = scan_grid(self.scan)?;
+        Ok(DoaEstimator::new(
+            self.array,
+            self.frequency,
+            self.method,
+            self.snapshots,
+            bearings,
+            circular,
+            self.gated,
+        ))
+    }
+}
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Fix, heading, and time of the last NMEA sentences.
+#[derive(Debug, Default)]
+struct Fix {
+    position: Option<(f64, f64)>,
+    heading: Option<f64>,
+    course: Option<f64>,
+    time: Option<String>,
+    date: Option<String>,
+}
+
+/// Check the checksum of an NMEA sentence and return its fields.
+fn nmea_fields(sentence: &str) -> Option<Vec<&str>> {
+    let s = sentence.trim().strip_prefix('$')?;
+    let (data, checksum) = s.split_once('*')?;
+    let checksum = u8::from_str_radix(checksum.trim(), 16).ok()?;
+    if data.bytes().fold(0, |a, b| a ^ b) != checksum {
+        return None;
+    }
+    Some(data.split(',').collect())
+}
+
+/// Parse a coordinate in `(d)ddmm.mmmm` format with hemisphere.
+fn nmea_coordinate(value: &str, hemisphere: &str) -> Option<f64> {
+    let v: f64 = value.parse().ok()?;
+    let degrees = (v / 100.0).trunc();
+    let c = degrees + (v - degrees * 100.0) / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(c),
+        "S" | "W" => Some(-c),
+        _ => None,
+    }
+}
+
+/// Format `hhmmss.ss` as `hh:mm:ss.ss`.
+fn nmea_time(value: &str) -> Option<String> {
+    if value.len() < 6 || !value.is_ascii() {
+        return None;
+    }
+    Some(format!("{}:{}:{}", &value[0..2], &value[2..4], &value[4..]))
+}
+
+impl Fix {
+    fn update(&mut self, sentence: &str) -> bool {
+        let f = match nmea_fields(sentence) {
+            Some(f) if f[0].len() >= 5 && f[0].is_ascii() => f,
+            _ => return false,
+        };
+        let get = |i: usize| f.get(i).copied().unwrap_or("");
+
+        match &f[0][2..] {
+            "GGA" => {
+                if get(6) == "0" {
+                    return false;
+                }
+                self.time = nmea_time(get(1)).or(self.time.take());
+                if let (Some(lat), Some(lon)) = (
+                    nmea_coordinate(get(2), get(3)),
+                    nmea_coordinate(get(4), get(5)),
+                ) {
+                    self.position = Some((lat, lon));
+                }
+            }
+            "RMC" => {
+                if get(2) != "A" {
+                    return false;
+                }
+                self.time = nmea_time(get(1)).or(self.time.take());
+                if let (Some(lat), Some(lon)) = (
+                    nmea_coordinate(get(3), get(4)),
+                    nmea_coordinate(get(5), get(6)),
+                ) {
+                    self.position = Some((lat, lon));
+                }
+                self.course = get(8).parse().ok();
+                let d = get(9);
+                if d.len() == 6 && d.is_ascii() {
+                    self.date = Some(format!("20{}-{}-{}", &d[4..6], &d[2..4], &d[0..2]));
+                }
+            }
+            "HDT" => match get(1).parse() {
+                Ok(h) => self.heading = Some(h),
+                Err(_) => return false,
+            },
+            "VTG" => self.course = get(1).parse().ok(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn timestamp(&self) -> Option<String> {
+        match (&self.date, &self.time) {
+            (Some(d), Some(t)) => Some(format!("{d}T{t}Z")),
+            (None, Some(t)) => Some(t.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// Annotate bearings with position, heading, and time, e.g., for mobile direction finding.
+///
+/// NMEA 0183 sentences are parsed to keep track of the position (`GGA`, `RMC`), heading
+/// (`HDT`, or course over ground from `RMC` and `VTG`), and UTC time. Each bearing is forwarded
+/// with the current values. If a heading is known, the bearing, which is relative to the array,
+/// is converted to an azimuth, i.e., clockwise from true north. For this, the x-axis of the
+/// array has to point in the direction of the heading.
+///
+/// # Inputs
+///
+/// **Message** `nmea`: One or more NMEA sentences ([`Pmt::String`], [`Pmt::Blob`]), e.g., from
+/// gpsd or a serial GPS receiver. [`Pmt::Null`] returns the current position as latitude and
+/// longitude ([`Pmt::VecF32`]) or [`Pmt::Null`] if there is no fix.
+///
+/// **Message** `bearing`: Bearing in degrees ([`Pmt::F32`], [`Pmt::F64`]) or a
+/// [`Pmt::MapStrPmt`] with a `bearing`, as output by the
+/// [Interferometer](super::InterferometerBuilder).
+///
+/// # Outputs
+///
+/// **Message** `out`: [`Pmt::MapStrPmt`] with the `bearing` and, if available, `lat` and `lon`
+/// in degrees, `heading` and `azimuth` in degrees (all [`Pmt::F64`]), and the UTC `time`
+/// ([`Pmt::String`]).
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::Georeference;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let georeference = fg.add_block(Georeference::new());
+/// ```
+pub struct Georeference {
+    fix: Fix,
+}
+
+impl Georeference {
+    /// Create Georeference block
+    pub fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Georeference").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("nmea", Self::nmea_handler)
+                .add_input("bearing", Self::bearing_handler)
+                .add_output("out")
+                .build(),
+            Georeference {
+                fix: Fix::default(),
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn nmea_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let s = match p {
+            Pmt::String(s) => s,
+            Pmt::Blob(b) => String::from_utf8_lossy(&b).to_string(),
+            Pmt::Null => {
+                return Ok(match self.fix.position {
+                    Some((lat, lon)) => Pmt::VecF32(vec![lat as f32, lon as f32]),
+                    None => Pmt::Null,
+                })
+            }
+            Pmt::Finished => return Ok(Pmt::Ok),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        let mut valid = false;
+        for line in s.lines() {
+            valid |= self.fix.update(line);
+        }
+        if valid {
+            Ok(Pmt::Ok)
+        } else {
+            Ok(Pmt::InvalidValue)
+        }
+    }
+
+    #[message_handler]
+    async fn bearing_handler(
+        &mut self,
+        io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let mut m = match p {
+            Pmt::F32(b) => HashMap::from([("bearing".to_string(), Pmt::F32(b))]),
+            Pmt::F64(b) => HashMap::from([("bearing".to_string(), Pmt::F64(b))]),
+            Pmt::MapStrPmt(m) => m,
+            Pmt::Finished => {
+                io.finished = true;
+                return Ok(Pmt::Ok);
+            }
+            _ => return Ok(Pmt::InvalidValue),
+        };
+
+        if let Some((lat, lon)) = self.fix.position {
+            m.insert("lat".to_string(), Pmt::F64(lat));
+            m.insert("lon".to_string(), Pmt::F64(lon));
+        }
+        if let Some(t) = self.fix.timestamp() {
+            m.insert("time".to_string(), Pmt::String(t));
+        }
+        if let Some(h) = self.fix.heading.or(self.fix.course) {
+            m.insert("heading".to_string(), Pmt::F64(h));
+            let bearing = match m.get("bearing") {
+                Some(Pmt::F32(b)) => Some(*b as f64),
+                Some(Pmt::F64(b)) => Some(*b),
+                _ => None,
+            };
+            if let Some(b) = bearing {
+                m.insert("azimuth".to_string(), Pmt::F64((h - b).rem_euclid(360.0)));
+            }
+        }
+
+        mio.post(0, Pmt::MapStrPmt(m)).await;
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Georeference {}
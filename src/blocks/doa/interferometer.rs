@@ -0,0 +1,340 @@
+use num_complex::Complex32;
+use num_complex::Complex64;
+use std::collections::HashMap;
+
+use crate::anyhow::{bail, Result};
+use crate::blocks::doa::array::find_peaks;
+use crate::blocks::doa::array::scan_grid;
+use crate::blocks::doa::ArrayManifold;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Normalize a response vector to unit magnitude and phases relative to the first element.
+pub(crate) fn normalize(v: &[Complex64]) -> Vec<Complex64> {
+    let r = v[0].conj();
+    v.iter()
+        .map(|x| {
+            let x = *x * r;
+            if x.norm() > 0.0 {
+                x / x.norm()
+            } else {
+                x
+            }
+        })
+        .collect()
+}
+
+/// Normalized response of the array for each bearing.
+pub(crate) fn compute_manifold(
+    array: &ArrayManifold,
+    frequency: f64,
+    bearings: &[f64],
+) -> Vec<Vec<Complex64>> {
+    bearings
+        .iter()
+        .map(|b| normalize(&array.response(*b, frequency)))
+        .collect()
+}
+
+/// Correlation of the measured phase differences with the normalized response of each bearing.
+///
+/// The result is between 0 and 1.
+pub(crate) fn correlate(manifold: &[Vec<Complex64>], correlation: &[Complex64]) -> Vec<f32> {
+    let m = correlation.len() as f64;
+    let measured = normalize(correlation);
+    manifold
+        .iter()
+        .map(|a| {
+            let s: Complex64 = a
+                .iter()
+                .zip(measured.iter())
+                .map(|(a, x)| a.conj() * *x)
+                .sum();
+            (s.norm() / m) as f32
+        })
+        .collect()
+}
+
+/// Correlative interferometer direction finder.
+pub struct Interferometer {
+    array: ArrayManifold,
+    frequency: f64,
+    snapshots: usize,
+    bearings: Vec<f64>,
+    circular: bool,
+    measured: bool,
+    manifold: Vec<Vec<Complex64>>,
+    correlation: Vec<Complex64>,
+    n: usize,
+}
+
+impl Interferometer {
+    /// Create Interferometer block
+    ///
+    /// If a `manifold` is given, it is used instead of the response computed from the array
+    /// geometry and has to contain one response vector per bearing.
+    pub fn new(
+        array: ArrayManifold,
+        frequency: f64,
+        snapshots: usize,
+        bearings: Vec<f64>,
+        circular: bool,
+        manifold: Option<Vec<Vec<Complex32>>>,
+    ) -> Block {
+        let elements = array.len();
+        assert!(elements >= 2);
+        assert!(snapshots > 0);
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..elements {
+            sio = sio.add_input::<Complex32>(&format!("in{i}"));
+        }
+
+        let measured = manifold.is_some();
+        let manifold = match manifold {
+            Some(m) => {
+                assert_eq!(m.len(), bearings.len());
+                m.iter()
+                    .map(|v| {
+                        assert_eq!(v.len(), elements);
+                        let v: Vec<Complex64> = v
+                            .iter()
+                            .map(|x| Complex64::new(x.re as f64, x.im as f64))
+                            .collect();
+                        normalize(&v)
+                    })
+                    .collect()
+            }
+            None => compute_manifold(&array, frequency, &bearings),
+        };
+
+        Block::new(
+            BlockMetaBuilder::new("Interferometer").build(),
+            sio.build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::freq_handler)
+                .add_output("bearing")
+                .build(),
+            Interferometer {
+                array,
+                frequency,
+                snapshots,
+                bearings,
+                circular,
+                measured,
+                manifold,
+                correlation: vec![Complex64::new(0.0, 0.0); elements],
+                n: 0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn freq_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let f = match p {
+            Pmt::F32(f) if f > 0.0 => f as f64,
+            Pmt::F64(f) if f > 0.0 => f,
+            Pmt::U32(f) if f > 0 => f as f64,
+            Pmt::U64(f) if f > 0 => f as f64,
+            Pmt::Null => return Ok(Pmt::F64(self.frequency)),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        self.frequency = f;
+        if !self.measured {
+            self.manifold = compute_manifold(&self.array, f, &self.bearings);
+        }
+        Ok(Pmt::Ok)
+    }
+
+    fn estimate(&self) -> Pmt {
+        let c = correlate(&self.manifold, &self.correlation);
+
+        let peaks = find_peaks(&c, 2, self.circular);
+        let i = peaks.first().copied().unwrap_or(0);
+        let best = c[i];
+        let ambiguity = peaks.get(1).map_or(0.0, |i| c[*i] / best);
+
+        Pmt::MapStrPmt(HashMap::from([
+            ("bearing".to_string(), Pmt::F32(self.bearings[i] as f32)),
+            ("quality".to_string(), Pmt::F32(best)),
+            ("ambiguity".to_string(), Pmt::F32(ambiguity)),
+        ]))
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Interferometer {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let elements = self.array.len();
+        let inputs: Vec<&[Complex32]> = (0..elements)
+            .map(|i| sio.input(i).slice::<Complex32>())
+            .collect();
+        let m = inputs.iter().map(|i| i.len()).min().unwrap_or(0);
+        let m = std::cmp::min(m, self.snapshots - self.n);
+
+        for k in 0..m {
+            let r = inputs[0][k].conj();
+            for (c, i) in self.correlation.iter_mut().zip(inputs.iter()) {
+                let x = i[k] * r;
+                *c += Complex64::new(x.re as f64, x.im as f64);
+            }
+        }
+        self.n += m;
+
+        for i in 0..elements {
+            sio.input(i).consume(m);
+        }
+
+        if self.n == self.snapshots {
+            mio.post(0, self.estimate()).await;
+            self.correlation.fill(Complex64::new(0.0, 0.0));
+            self.n = 0;
+            io.call_again = true;
+        }
+
+        if (0..elements).any(|i| sio.input(i).finished() && inputs[i].len() == m) {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a correlative [Interferometer].
+///
+/// Measures the phase differences of `N` coherent channels relative to the first channel and
+/// correlates them with the array manifold, i.e., the expected response of the array for each
+/// bearing of the scan grid. The manifold is computed from the
+/// [ArrayGeometry](super::ArrayGeometry), including the element calibration of an
+/// [ArrayManifold], or taken from a measurement of the array response. Bearings are in degrees, measured counter-clockwise from the x-axis.
+///
+/// # Inputs
+///
+/// **Stream** `in0`..`in{N-1}`: Channels of the array elements, in the order of the array
+/// geometry
+///
+/// **Message** `freq`: Set the center frequency in Hz ([`Pmt::F32`], [`Pmt::F64`],
+/// [`Pmt::U32`], [`Pmt::U64`]). [`Pmt::Null`] returns the current frequency. A measured
+/// manifold is not changed by retuning.
+///
+/// # Outputs
+///
+/// **Message** `bearing`: [`Pmt::MapStrPmt`] with the estimated `bearing` in degrees, the
+/// `quality` of the estimate, i.e., the correlation with the manifold between 0 and 1, and the
+/// `ambiguity`, i.e., the ratio of the second-best to the best correlation peak (all
+/// [`Pmt::F32`]).
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::ArrayGeometry;
+/// use futuresdr::blocks::doa::InterferometerBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let df = fg.add_block(
+///     InterferometerBuilder::new(ArrayGeometry::uniform_circular(5, 0.1), 868e6)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct InterferometerBuilder {
+    array: ArrayManifold,
+    frequency: f64,
+    snapshots: usize,
+    scan: (f64, f64, f64),
+    manifold: Option<(Vec<f64>, Vec<Vec<Complex32>>)>,
+}
+
+impl InterferometerBuilder {
+    /// Create Interferometer builder for an array operating at `frequency` Hz
+    pub fn new(array: impl Into<ArrayManifold>, frequency: f64) -> InterferometerBuilder {
+        InterferometerBuilder {
+            array: array.into(),
+            frequency,
+            snapshots: 1024,
+            scan: (0.0, 360.0, 1.0),
+            manifold: None,
+        }
+    }
+    /// Number of samples per estimate (default: 1024)
+    #[must_use]
+    pub fn snapshots(mut self, snapshots: usize) -> InterferometerBuilder {
+        self.snapshots = snapshots;
+        self
+    }
+    /// Bearings to scan from `start` to `stop` degrees in steps of `step` degrees
+    /// (default: full circle in 1 degree steps)
+    #[must_use]
+    pub fn scan(mut self, start: f64, stop: f64, step: f64) -> InterferometerBuilder {
+        self.scan = (start, stop, step);
+        self
+    }
+    /// Use a measured manifold, i.e., the response of each element for the given bearings,
+    /// instead of the scan grid and the response computed from the array geometry
+    #[must_use]
+    pub fn manifold(
+        mut self,
+        bearings: Vec<f64>,
+        responses: Vec<Vec<Complex32>>,
+    ) -> InterferometerBuilder {
+        self.manifold = Some((bearings, responses));
+        self
+    }
+    /// Build Interferometer block
+    pub fn build(self) -> Result<Block> {
+        let elements = self.array.len();
+        if elements < 2 {
+            bail!("interferometer requires at least two array elements");
+        }
+        if self.snapshots == 0 {
+            bail!("number of snapshots has to be positive");
+        }
+        let (bearings, circular, manifold) = match self.manifold {
+            Some((bearings, responses)) => {
+                if bearings.len() < 2 || bearings.len() != responses.len() {
+                    bail!("manifold needs one response for each of at least two bearings");
+                }
+                if responses.iter().any(|r| r.len() != elements) {
+                    bail!("manifold responses have to contain one value per element");
+                }
+                let step = bearings[1] - bearings[0];
+                let circular = bearings[bearings.len() - 1] - bearings[0] + step >= 360.0 - 1e-9;
+                (bearings, circular, Some(responses))
+            }
+            None => {
+                let (bearings, circular) = scan_grid(self.scan)?;
+                (bearings, circular, None)
+            }
+        };
+        Ok(Interferometer::new(
+            self.array,
+            self.frequency,
+            self.snapshots,
+            bearings,
+            circular,
+            manifold,
+        ))
+    }
+}
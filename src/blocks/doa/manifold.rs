@@ -0,0 +1,167 @@
+use num_complex::Complex32;
+use num_complex::Complex64;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::doa::ArrayGeometry;
+
+/// Gain and phase of the array elements at a frequency.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Calibration {
+    /// Frequency in Hz
+    pub frequency: f64,
+    /// Linear gain of each element
+    pub gain: Vec<f64>,
+    /// Phase of each element in degrees
+    pub phase: Vec<f64>,
+}
+
+impl Calibration {
+    /// Create calibration from complex correction factors, as output by
+    /// [PhaseCalibration](super::PhaseCalibration)
+    ///
+    /// The response of an element is the inverse of its correction.
+    pub fn from_corrections(frequency: f64, corrections: &[Complex32]) -> Self {
+        let response: Vec<Complex64> = corrections
+            .iter()
+            .map(|c| Complex64::new(1.0, 0.0) / Complex64::new(c.re as f64, c.im as f64))
+            .collect();
+        Calibration {
+            frequency,
+            gain: response.iter().map(|r| r.norm()).collect(),
+            phase: response.iter().map(|r| r.arg().to_degrees()).collect(),
+        }
+    }
+
+    /// Complex response of each element
+    pub fn response(&self) -> Vec<Complex64> {
+        self.gain
+            .iter()
+            .zip(self.phase.iter())
+            .map(|(g, p)| Complex64::from_polar(*g, p.to_radians()))
+            .collect()
+    }
+}
+
+/// Antenna array manifold, i.e., the geometry of an array together with the measured gain and
+/// phase of its elements.
+///
+/// A manifold without calibrations can be created from an [ArrayGeometry] with [From].
+///
+/// Manifolds are stored as JSON files with the element `positions` `[x, y]` in meters and a list
+/// of `calibrations`, each with the `frequency` in Hz, and the linear `gain` and `phase` in
+/// degrees of each element:
+///
+/// ```json
+/// {
+///   "positions": [[0.1, 0.0], [0.0, 0.1], [-0.1, 0.0], [0.0, -0.1]],
+///   "calibrations": [
+///     { "frequency": 868e6, "gain": [1.0, 0.9, 1.1, 1.0], "phase": [0.0, 12.5, -3.0, 40.2] }
+///   ]
+/// }
+/// ```
+///
+/// Files can be created with the [ManifoldWriter](super::ManifoldWriter) block from a
+/// calibration measurement.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ArrayManifold {
+    #[serde(rename = "positions")]
+    geometry: ArrayGeometry,
+    #[serde(default)]
+    calibrations: Vec<Calibration>,
+}
+
+impl ArrayManifold {
+    /// Load manifold from a JSON file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let s = std::fs::read_to_string(path)
+            .with_context(|| format!("cannot read manifold {}", path.display()))?;
+        Self::from_json(&s)
+    }
+
+    /// Parse manifold from JSON
+    pub fn from_json(s: &str) -> Result<Self> {
+        let mut m: ArrayManifold = serde_json::from_str(s).context("invalid manifold")?;
+        for c in m.calibrations.iter() {
+            if c.gain.len() != m.len() || c.phase.len() != m.len() {
+                bail!("calibration at {} Hz does not match the array", c.frequency);
+            }
+        }
+        m.calibrations
+            .sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+        Ok(m)
+    }
+
+    /// Serialize manifold to JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Array geometry
+    pub fn geometry(&self) -> &ArrayGeometry {
+        &self.geometry
+    }
+
+    /// Number of elements
+    pub fn len(&self) -> usize {
+        self.geometry.len()
+    }
+
+    /// Whether the array has no elements
+    pub fn is_empty(&self) -> bool {
+        self.geometry.is_empty()
+    }
+
+    /// Calibrations, ordered by frequency
+    pub fn calibrations(&self) -> &[Calibration] {
+        &self.calibrations
+    }
+
+    /// Add calibration, replacing an existing calibration at the same frequency
+    pub fn add_calibration(&mut self, calibration: Calibration) -> Result<()> {
+        if calibration.gain.len() != self.len() || calibration.phase.len() != self.len() {
+            bail!("calibration does not match the array");
+        }
+        self.calibrations
+            .retain(|c| c.frequency != calibration.frequency);
+        self.calibrations.push(calibration);
+        self.calibrations
+            .sort_by(|a, b| a.frequency.total_cmp(&b.frequency));
+        Ok(())
+    }
+
+    /// Calibration closest to `frequency`, if any
+    pub fn calibration(&self, frequency: f64) -> Option<&Calibration> {
+        self.calibrations.iter().min_by(|a, b| {
+            (a.frequency - frequency)
+                .abs()
+                .total_cmp(&(b.frequency - frequency).abs())
+        })
+    }
+
+    /// Response of the array to a plane wave from `bearing` degrees at `frequency` Hz
+    ///
+    /// This is the [steering vector](ArrayGeometry::steering_vector), weighted with the element
+    /// response of the closest calibration.
+    pub fn response(&self, bearing: f64, frequency: f64) -> Vec<Complex64> {
+        let mut a = self.geometry.steering_vector(bearing, frequency);
+        if let Some(c) = self.calibration(frequency) {
+            for (a, r) in a.iter_mut().zip(c.response()) {
+                *a *= r;
+            }
+        }
+        a
+    }
+}
+
+impl From<ArrayGeometry> for ArrayManifold {
+    fn from(geometry: ArrayGeometry) -> Self {
+        Self {
+            geometry,
+            calibrations: Vec::new(),
+        }
+    }
+}
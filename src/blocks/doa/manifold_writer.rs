@@ -0,0 +1,116 @@
+use crate::anyhow::{Context, Result};
+use crate::blocks::doa::ArrayManifold;
+use crate::blocks::doa::Calibration;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Write an [ArrayManifold] file from calibration measurements.
+///
+/// Each set of corrections, e.g., from a [PhaseCalibration](super::PhaseCalibration) block, is
+/// stored as calibration at the current frequency, replacing a previous calibration at the same
+/// frequency. The file is updated after every calibration. Stepping the frequency, e.g., with a
+/// [Sweeper](crate::blocks::SweeperBuilder), and triggering a calibration after every step
+/// creates a manifold for a frequency range.
+///
+/// # Inputs
+///
+/// **Message** `freq`: Set the current frequency in Hz ([`Pmt::F32`], [`Pmt::F64`],
+/// [`Pmt::U32`], [`Pmt::U64`]). [`Pmt::Null`] returns the current frequency.
+///
+/// **Message** `corrections`: Correction factors of the elements ([`Pmt::VecCF32`]).
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::doa::ArrayGeometry;
+/// use futuresdr::blocks::doa::ManifoldWriter;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let writer = fg.add_block(ManifoldWriter::new(
+///     "manifold.json",
+///     ArrayGeometry::uniform_circular(4, 0.0864),
+///     868e6,
+/// ));
+/// ```
+pub struct ManifoldWriter {
+    path: String,
+    manifold: ArrayManifold,
+    frequency: f64,
+}
+
+impl ManifoldWriter {
+    /// Create ManifoldWriter block
+    ///
+    /// Calibrations are added to the given `manifold`, which can be loaded from an existing file.
+    pub fn new<S: Into<String>>(
+        path: S,
+        manifold: impl Into<ArrayManifold>,
+        frequency: f64,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ManifoldWriter").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::freq_handler)
+                .add_input("corrections", Self::corrections_handler)
+                .build(),
+            ManifoldWriter {
+                path: path.into(),
+                manifold: manifold.into(),
+                frequency,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn freq_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(f) if f > 0.0 => self.frequency = f as f64,
+            Pmt::F64(f) if f > 0.0 => self.frequency = f,
+            Pmt::U32(f) if f > 0 => self.frequency = f as f64,
+            Pmt::U64(f) if f > 0 => self.frequency = f as f64,
+            Pmt::Null => return Ok(Pmt::F64(self.frequency)),
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn corrections_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let c = match p {
+            Pmt::VecCF32(c) if c.len() == self.manifold.len() => c,
+            Pmt::Finished => return Ok(Pmt::Ok),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        self.manifold
+            .add_calibration(Calibration::from_corrections(self.frequency, &c))?;
+        async_fs::write(&self.path, self.manifold.to_json())
+            .await
+            .with_context(|| format!("ManifoldWriter: cannot write {}", self.path))?;
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ManifoldWriter {}
@@ -0,0 +1,41 @@
+//! ## Direction-Finding Blocks
+//!
+//! Bearing estimation from coherent multi-channel receivers ([DoaEstimator], [Interferometer],
+//! [Music], [PseudoDoppler]), calibration of the array ([PhaseCalibration],
+//! [PhaseCorrection], `ManifoldWriter`), and fusion of bearings to positions ([Georeference],
+//! [Triangulation]).
+
+mod array;
+pub use array::ArrayGeometry;
+
+mod estimator;
+pub use estimator::{DoaEstimator, DoaEstimatorBuilder, DoaMethod};
+
+mod georeference;
+pub use georeference::Georeference;
+
+mod interferometer;
+pub use interferometer::{Interferometer, InterferometerBuilder};
+
+mod manifold;
+pub use manifold::{ArrayManifold, Calibration};
+
+#[cfg(not(target_arch = "wasm32"))]
+mod manifold_writer;
+#[cfg(not(target_arch = "wasm32"))]
+pub use manifold_writer::ManifoldWriter;
+
+mod music;
+pub use music::{Music, MusicBuilder};
+
+mod phase_calibration;
+pub use phase_calibration::PhaseCalibration;
+
+mod phase_correction;
+pub use phase_correction::PhaseCorrection;
+
+mod pseudo_doppler;
+pub use pseudo_doppler::{PseudoDoppler, PseudoDopplerBuilder};
+
+mod triangulation;
+pub use triangulation::{Triangulation, TriangulationBuilder};
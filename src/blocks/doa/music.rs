@@ -0,0 +1,294 @@
+use num_complex::Complex32;
+use num_complex::Complex64;
+
+use crate::anyhow::{bail, Result};
+use crate::blocks::doa::array::eigh;
+use crate::blocks::doa::array::find_peaks;
+use crate::blocks::doa::array::scan_grid;
+use crate::blocks::doa::ArrayManifold;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// MUSIC pseudo-spectrum in dB, normalized to a maximum of 0 dB.
+///
+/// `covariance` is the spatial covariance matrix of the array, `sources` the number of signals
+/// that span the signal subspace.
+pub(crate) fn music_spectrum(
+    covariance: &[Vec<Complex64>],
+    manifold: &ArrayManifold,
+    frequency: f64,
+    sources: usize,
+    bearings: &[f64],
+) -> Vec<f32> {
+    let eig = eigh(covariance.to_vec());
+    let noise = &eig[..manifold.len() - sources];
+
+    let spectrum: Vec<f64> = bearings
+        .iter()
+        .map(|b| {
+            let a = manifold.response(*b, frequency);
+            let d: f64 = noise
+                .iter()
+                .map(|(_, e)| {
+                    a.iter()
+                        .zip(e.iter())
+                        .map(|(a, e)| a.conj() * *e)
+                        .sum::<Complex64>()
+                        .norm_sqr()
+                })
+                .sum();
+            1.0 / d.max(f64::MIN_POSITIVE)
+        })
+        .collect();
+
+    let max = spectrum.iter().copied().fold(f64::MIN_POSITIVE, f64::max);
+    spectrum
+        .iter()
+        .map(|s| (10.0 * (s / max).log10()) as f32)
+        .collect()
+}
+
+/// Estimate angle of arrival with the MUSIC algorithm.
+pub struct Music {
+    array: ArrayManifold,
+    frequency: f64,
+    sources: usize,
+    snapshots: usize,
+    bearings: Vec<f64>,
+    circular: bool,
+    covariance: Vec<Vec<Complex64>>,
+    n: usize,
+}
+
+impl Music {
+    /// Create Music block
+    pub fn new(
+        array: ArrayManifold,
+        frequency: f64,
+        sources: usize,
+        snapshots: usize,
+        bearings: Vec<f64>,
+        circular: bool,
+    ) -> Block {
+        let elements = array.len();
+        assert!(elements >= 2);
+        assert!(sources > 0 && sources < elements);
+        assert!(snapshots > 0);
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..elements {
+            sio = sio.add_input::<Complex32>(&format!("in{i}"));
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("Music").build(),
+            sio.build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::freq_handler)
+                .add_output("spectrum")
+                .add_output("bearings")
+                .build(),
+            Music {
+                array,
+                frequency,
+                sources,
+                snapshots,
+                bearings,
+                circular,
+                covariance: vec![vec![Complex64::new(0.0, 0.0); elements]; elements],
+                n: 0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn freq_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(f) if f > 0.0 => self.frequency = f as f64,
+            Pmt::F64(f) if f > 0.0 => self.frequency = f,
+            Pmt::U32(f) if f > 0 => self.frequency = f as f64,
+            Pmt::U64(f) if f > 0 => self.frequency = f as f64,
+            Pmt::Null => return Ok(Pmt::F64(self.frequency)),
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Music {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let elements = self.array.len();
+        let inputs: Vec<&[Complex32]> = (0..elements)
+            .map(|i| sio.input(i).slice::<Complex32>())
+            .collect();
+        let m = inputs.iter().map(|i| i.len()).min().unwrap_or(0);
+        let m = std::cmp::min(m, self.snapshots - self.n);
+
+        for k in 0..m {
+            let x: Vec<Complex64> = inputs
+                .iter()
+                .map(|i| Complex64::new(i[k].re as f64, i[k].im as f64))
+                .collect();
+            for (row, xi) in self.covariance.iter_mut().zip(x.iter()) {
+                for (r, xj) in row.iter_mut().zip(x.iter()) {
+                    *r += *xi * xj.conj();
+                }
+            }
+        }
+        self.n += m;
+
+        for i in 0..elements {
+            sio.input(i).consume(m);
+        }
+
+        if self.n == self.snapshots {
+            let spectrum = music_spectrum(
+                &self.covariance,
+                &self.array,
+                self.frequency,
+                self.sources,
+                &self.bearings,
+            );
+            let peaks: Vec<f32> = find_peaks(&spectrum, self.sources, self.circular)
+                .into_iter()
+                .map(|i| self.bearings[i] as f32)
+                .collect();
+            mio.post(0, Pmt::VecF32(spectrum)).await;
+            mio.post(1, Pmt::VecF32(peaks)).await;
+
+            for row in self.covariance.iter_mut() {
+                row.fill(Complex64::new(0.0, 0.0));
+            }
+            self.n = 0;
+            io.call_again = true;
+        }
+
+        if (0..elements).any(|i| sio.input(i).finished() && inputs[i].len() == m) {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [Music] angle-of-arrival estimator.
+///
+/// Estimates the spatial covariance of `N` coherent channels over a number of snapshots and
+/// computes the MUSIC pseudo-spectrum on a grid of bearings. The steering vectors are computed
+/// from the [ArrayGeometry](super::ArrayGeometry) and weighted with the element calibration, if
+/// an [ArrayManifold] is given. Bearings are in degrees, measured counter-clockwise from the
+/// x-axis.
+///
+/// # Inputs
+///
+/// **Stream** `in0`..`in{N-1}`: Channels of the array elements, in the order of the array
+/// geometry
+///
+/// **Message** `freq`: Set the center frequency in Hz ([`Pmt::F32`], [`Pmt::F64`],
+/// [`Pmt::U32`], [`Pmt::U64`]). [`Pmt::Null`] returns the current frequency.
+///
+/// # Outputs
+///
+/// **Message** `spectrum`: Pseudo-spectrum in dB for each bearing of the scan grid
+/// ([`Pmt::VecF32`])
+///
+/// **Message** `bearings`: Bearings of the strongest peaks, strongest first ([`Pmt::VecF32`])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::ArrayGeometry;
+/// use futuresdr::blocks::doa::MusicBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // Four-element circular array with a quarter-wavelength radius at 868 MHz
+/// let music = fg.add_block(
+///     MusicBuilder::new(ArrayGeometry::uniform_circular(4, 0.0864), 868e6)
+///         .snapshots(4096)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct MusicBuilder {
+    array: ArrayManifold,
+    frequency: f64,
+    sources: usize,
+    snapshots: usize,
+    scan: (f64, f64, f64),
+}
+
+impl MusicBuilder {
+    /// Create Music builder for an array operating at `frequency` Hz
+    pub fn new(array: impl Into<ArrayManifold>, frequency: f64) -> MusicBuilder {
+        MusicBuilder {
+            array: array.into(),
+            frequency,
+            sources: 1,
+            snapshots: 1024,
+            scan: (0.0, 360.0, 1.0),
+        }
+    }
+    /// Number of signals to estimate (default: 1)
+    #[must_use]
+    pub fn sources(mut self, sources: usize) -> MusicBuilder {
+        self.sources = sources;
+        self
+    }
+    /// Number of samples per estimate (default: 1024)
+    #[must_use]
+    pub fn snapshots(mut self, snapshots: usize) -> MusicBuilder {
+        self.snapshots = snapshots;
+        self
+    }
+    /// Bearings to scan from `start` to `stop` degrees in steps of `step` degrees
+    /// (default: full circle in 1 degree steps)
+    #[must_use]
+    pub fn scan(mut self, start: f64, stop: f64, step: f64) -> MusicBuilder {
+        self.scan = (start, stop, step);
+        self
+    }
+    /// Build Music block
+    pub fn build(self) -> Result<Block> {
+        if self.array.len() < 2 {
+            bail!("MUSIC requires at least two array elements");
+        }
+        if self.sources == 0 || self.sources >= self.array.len() {
+            bail!("number of sources has to be between 1 and the number of elements - 1");
+        }
+        if self.snapshots == 0 {
+            bail!("number of snapshots has to be positive");
+        }
+        let (bearings, circular) = scan_grid(self.scan)?;
+        Ok(Music::new(
+            self.array,
+            self.frequency,
+            self.sources,
+            self.snapshots,
+            bearings,
+            circular,
+        ))
+    }
+}
@@ -0,0 +1,184 @@
+use num_complex::Complex32;
+use num_complex::Complex64;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Estimate per-channel phase and gain corrections from a common reference tone.
+///
+/// All channels have to receive the same calibration signal, e.g., a tone or noise source that
+/// is fed into all receivers through a splitter. The corrections align the phase and gain of
+/// each channel to the first channel and can be applied with a
+/// [PhaseCorrection](super::PhaseCorrection) block.
+///
+/// A calibration starts with a message to the `calibrate` input, or continuously, if
+/// configured. Samples that are not used for a calibration are dropped.
+///
+/// # Inputs
+///
+/// **Stream** `in0`..`in{N-1}`: Channels to calibrate
+///
+/// **Message** `calibrate`: Start a calibration over the next `snapshots` samples. [`Pmt::Null`]
+/// returns the last corrections.
+///
+/// # Outputs
+///
+/// **Message** `corrections`: Complex correction factor for each channel ([`Pmt::VecCF32`]).
+/// Connect to the `corrections` input of the [PhaseCorrection](super::PhaseCorrection) block.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::PhaseCalibration;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let calibration = fg.add_block(PhaseCalibration::new(4, 4096, false));
+/// ```
+pub struct PhaseCalibration {
+    channels: usize,
+    snapshots: usize,
+    continuous: bool,
+    active: bool,
+    correlation: Vec<Complex64>,
+    power: Vec<f64>,
+    corrections: Vec<Complex32>,
+    n: usize,
+}
+
+impl PhaseCalibration {
+    /// Create PhaseCalibration block
+    ///
+    /// ## Parameter
+    /// - `channels`: number of channels
+    /// - `snapshots`: number of samples per calibration
+    /// - `continuous`: calibrate continuously instead of on request
+    pub fn new(channels: usize, snapshots: usize, continuous: bool) -> Block {
+        assert!(channels >= 2);
+        assert!(snapshots > 0);
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..channels {
+            sio = sio.add_input::<Complex32>(&format!("in{i}"));
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("PhaseCalibration").build(),
+            sio.build(),
+            MessageIoBuilder::new()
+                .add_input("calibrate", Self::calibrate_handler)
+                .add_output("corrections")
+                .build(),
+            PhaseCalibration {
+                channels,
+                snapshots,
+                continuous,
+                active: continuous,
+                correlation: vec![Complex64::new(0.0, 0.0); channels],
+                power: vec![0.0; channels],
+                corrections: vec![Complex32::new(1.0, 0.0); channels],
+                n: 0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn calibrate_handler(
+        &mut self,
+        io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Null => return Ok(Pmt::VecCF32(self.corrections.clone())),
+            Pmt::Finished => {}
+            _ => {
+                self.active = true;
+                io.call_again = true;
+            }
+        }
+        Ok(Pmt::Ok)
+    }
+
+    fn compute_corrections(&mut self) {
+        let p0 = self.power[0];
+        self.corrections = self
+            .correlation
+            .iter()
+            .zip(self.power.iter())
+            .map(|(r, p)| {
+                if r.norm() > 0.0 && *p > 0.0 {
+                    let c = r.conj() / r.norm() * (p0 / p).sqrt();
+                    Complex32::new(c.re as f32, c.im as f32)
+                } else {
+                    Complex32::new(1.0, 0.0)
+                }
+            })
+            .collect();
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PhaseCalibration {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let inputs: Vec<&[Complex32]> = (0..self.channels)
+            .map(|i| sio.input(i).slice::<Complex32>())
+            .collect();
+        let mut m = inputs.iter().map(|i| i.len()).min().unwrap_or(0);
+
+        if self.active {
+            m = std::cmp::min(m, self.snapshots - self.n);
+            for k in 0..m {
+                let r = inputs[0][k].conj();
+                for ((c, p), i) in self
+                    .correlation
+                    .iter_mut()
+                    .zip(self.power.iter_mut())
+                    .zip(inputs.iter())
+                {
+                    let x = i[k] * r;
+                    *c += Complex64::new(x.re as f64, x.im as f64);
+                    *p += i[k].norm_sqr() as f64;
+                }
+            }
+            self.n += m;
+
+            if self.n == self.snapshots {
+                self.compute_corrections();
+                mio.post(0, Pmt::VecCF32(self.corrections.clone())).await;
+                self.correlation.fill(Complex64::new(0.0, 0.0));
+                self.power.fill(0.0);
+                self.n = 0;
+                self.active = self.continuous;
+                io.call_again = true;
+            }
+        }
+
+        for i in 0..self.channels {
+            sio.input(i).consume(m);
+        }
+
+        if (0..self.channels).any(|i| sio.input(i).finished() && inputs[i].len() == m) {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
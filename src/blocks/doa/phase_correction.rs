@@ -0,0 +1,124 @@
+use num_complex::Complex32;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Apply per-channel phase and gain corrections.
+///
+/// Each channel is multiplied with its complex correction factor, e.g., as estimated by a
+/// [PhaseCalibration](super::PhaseCalibration) block. Initially, all factors are one.
+///
+/// # Inputs
+///
+/// **Stream** `in0`..`in{N-1}`: Channels to correct
+///
+/// **Message** `corrections`: Set the correction factors ([`Pmt::VecCF32`] with one value per
+/// channel). [`Pmt::Null`] returns the current corrections.
+///
+/// # Outputs
+///
+/// **Stream** `out0`..`out{N-1}`: Corrected channels
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::PhaseCorrection;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let correction = fg.add_block(PhaseCorrection::new(4));
+/// ```
+pub struct PhaseCorrection {
+    corrections: Vec<Complex32>,
+}
+
+impl PhaseCorrection {
+    /// Create PhaseCorrection block for `channels` channels
+    pub fn new(channels: usize) -> Block {
+        assert!(channels > 0);
+
+        let mut sio = StreamIoBuilder::new();
+        for i in 0..channels {
+            sio = sio.add_input::<Complex32>(&format!("in{i}"));
+        }
+        for i in 0..channels {
+            sio = sio.add_output::<Complex32>(&format!("out{i}"));
+        }
+
+        Block::new(
+            BlockMetaBuilder::new("PhaseCorrection").build(),
+            sio.build(),
+            MessageIoBuilder::new()
+                .add_input("corrections", Self::corrections_handler)
+                .build(),
+            PhaseCorrection {
+                corrections: vec![Complex32::new(1.0, 0.0); channels],
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn corrections_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::VecCF32(v) if v.len() == self.corrections.len() => self.corrections = v,
+            Pmt::Null => return Ok(Pmt::VecCF32(self.corrections.clone())),
+            Pmt::Finished => {}
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PhaseCorrection {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let channels = self.corrections.len();
+        let mut m = usize::MAX;
+        for i in 0..channels {
+            m = std::cmp::min(m, sio.input(i).slice::<Complex32>().len());
+            m = std::cmp::min(m, sio.output(i).slice::<Complex32>().len());
+        }
+
+        if m > 0 {
+            for (i, c) in self.corrections.iter().enumerate() {
+                let input = sio.input(i).slice::<Complex32>();
+                let output = sio.output(i).slice::<Complex32>();
+                for (x, y) in input[..m].iter().zip(output[..m].iter_mut()) {
+                    *y = x * c;
+                }
+                sio.input(i).consume(m);
+                sio.output(i).produce(m);
+            }
+        }
+
+        for i in 0..channels {
+            if sio.input(i).finished() && sio.input(i).slice::<Complex32>().is_empty() {
+                io.finished = true;
+            }
+        }
+
+        Ok(())
+    }
+}
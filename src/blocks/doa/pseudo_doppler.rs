@@ -0,0 +1,231 @@
+use num_complex::Complex32;
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Pseudo-Doppler direction finder.
+pub struct PseudoDoppler {
+    antennas: usize,
+    dwell: usize,
+    rotations: usize,
+    offset: f64,
+    last: Complex32,
+    phasor: Complex64,
+    index: usize,
+    n: usize,
+}
+
+impl PseudoDoppler {
+    /// Create PseudoDoppler block
+    ///
+    /// ## Parameter
+    /// - `antennas`: number of antennas of the circular array
+    /// - `dwell`: number of samples per antenna
+    /// - `rotations`: number of rotations per estimate
+    /// - `offset`: bearing offset in degrees
+    pub fn new(antennas: usize, dwell: usize, rotations: usize, offset: f64) -> Block {
+        assert!(antennas >= 3);
+        assert!(dwell > 0);
+        assert!(rotations > 0);
+
+        Block::new(
+            BlockMetaBuilder::new("PseudoDoppler").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new()
+                .add_input("state", Self::state_handler)
+                .add_input("offset", Self::offset_handler)
+                .add_output("bearing")
+                .build(),
+            PseudoDoppler {
+                antennas,
+                dwell,
+                rotations,
+                offset,
+                last: Complex32::new(1.0, 0.0),
+                phasor: Complex64::new(0.0, 0.0),
+                index: 0,
+                n: 0,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn state_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let state = match p {
+            Pmt::Usize(s) => s,
+            Pmt::U32(s) => s as usize,
+            Pmt::U64(s) => s as usize,
+            Pmt::Finished => return Ok(Pmt::Ok),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        self.index = (state % self.antennas) * self.dwell;
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn offset_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(o) => self.offset = o as f64,
+            Pmt::F64(o) => self.offset = o,
+            Pmt::Null => return Ok(Pmt::F64(self.offset)),
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+
+    fn bearing(&self) -> f32 {
+        // The phase steps between antennas correspond to the derivative of the phase at the
+        // midpoint between two antennas, i.e., half a dwell time before the switch.
+        let b = 90.0 - self.phasor.arg().to_degrees() - 180.0 / self.antennas as f64;
+        (b + self.offset).rem_euclid(360.0) as f32
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for PseudoDoppler {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let input = sio.input(0).slice::<Complex32>();
+        let period = self.antennas * self.dwell;
+        let samples = self.rotations * period;
+        let m = std::cmp::min(input.len(), samples - self.n);
+
+        for x in input[..m].iter() {
+            let d = (x * self.last.conj()).arg() as f64;
+            self.last = *x;
+            let w = 2.0 * PI * self.index as f64 / period as f64;
+            self.phasor += Complex64::from_polar(d, -w);
+            self.index = (self.index + 1) % period;
+        }
+        self.n += m;
+        sio.input(0).consume(m);
+
+        if self.n == samples {
+            mio.post(0, Pmt::F32(self.bearing())).await;
+            self.phasor = Complex64::new(0.0, 0.0);
+            self.n = 0;
+            io.call_again = true;
+        }
+
+        if sio.input(0).finished() && m == input.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [PseudoDoppler] direction finder.
+///
+/// A pseudo-Doppler direction finder uses a single receiver that is switched through the
+/// antennas of a uniform circular array, emulating a rotating antenna. The switching modulates
+/// the phase of the received signal with a tone at the rotation frequency, whose phase
+/// corresponds to the bearing of the signal. The block FM-demodulates the input and correlates
+/// it with the rotation.
+///
+/// The antennas are expected to be placed counter-clockwise, with the first antenna on the
+/// x-axis and each antenna active for `dwell` samples. The switching schedule can, for example,
+/// be generated with a `seify::AntennaSwitch` block. Its `state` output can be connected to the
+/// `state` input to align the rotation with the actual switching. Delays in the frontend shift
+/// the bearing, which can be compensated with an offset.
+///
+/// Bearings are in degrees, measured counter-clockwise from the x-axis.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `state`: Index of the active antenna ([`Pmt::Usize`], [`Pmt::U32`],
+/// [`Pmt::U64`]), resynchronizing the rotation.
+///
+/// **Message** `offset`: Set the bearing offset in degrees ([`Pmt::F32`], [`Pmt::F64`]).
+/// [`Pmt::Null`] returns the current offset.
+///
+/// # Outputs
+///
+/// **Message** `bearing`: Estimated bearing in degrees ([`Pmt::F32`])
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::PseudoDopplerBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // Four antennas, switched every 100 samples
+/// let df = fg.add_block(PseudoDopplerBuilder::new(4, 100).rotations(500).build().unwrap());
+/// ```
+pub struct PseudoDopplerBuilder {
+    antennas: usize,
+    dwell: usize,
+    rotations: usize,
+    offset: f64,
+}
+
+impl PseudoDopplerBuilder {
+    /// Create PseudoDoppler builder for `antennas` antennas, each active for `dwell` samples
+    pub fn new(antennas: usize, dwell: usize) -> PseudoDopplerBuilder {
+        PseudoDopplerBuilder {
+            antennas,
+            dwell,
+            rotations: 100,
+            offset: 0.0,
+        }
+    }
+    /// Number of rotations per estimate (default: 100)
+    #[must_use]
+    pub fn rotations(mut self, rotations: usize) -> PseudoDopplerBuilder {
+        self.rotations = rotations;
+        self
+    }
+    /// Bearing offset in degrees, compensating delays of the frontend (default: 0)
+    #[must_use]
+    pub fn offset(mut self, offset: f64) -> PseudoDopplerBuilder {
+        self.offset = offset;
+        self
+    }
+    /// Build PseudoDoppler block
+    pub fn build(self) -> Result<Block> {
+        if self.antennas < 3 {
+            bail!("pseudo-Doppler DF requires at least three antennas");
+        }
+        if self.dwell == 0 || self.rotations == 0 {
+            bail!("dwell time and number of rotations have to be positive");
+        }
+        Ok(PseudoDoppler::new(
+            self.antennas,
+            self.dwell,
+            self.rotations,
+            self.offset,
+        ))
+    }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+use std::time::Duration;
+use web_time::Instant;
+
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Least-squares position estimate from bearing lines.
+///
+/// Returns the position and its covariance matrix `[c_xx, c_xy, c_yy]` or `None` if the
+/// geometry does not allow an estimate.
+pub(crate) fn triangulate(
+    bearings: &[((f64, f64), f64)],
+    bearing_std: f64,
+) -> Option<((f64, f64), [f64; 3])> {
+    if bearings.len() < 2 {
+        return None;
+    }
+
+    let solve = |weights: &[f64]| {
+        let (mut a, mut b, mut c, mut u, mut v) = (0.0, 0.0, 0.0, 0.0, 0.0);
+        for (((x, y), theta), w) in bearings.iter().zip(weights.iter()) {
+            let (s, co) = theta.to_radians().sin_cos();
+            let (nx, ny) = (-s, co);
+            let d = nx * x + ny * y;
+            a += w * nx * nx;
+            b += w * nx * ny;
+            c += w * ny * ny;
+            u += w * nx * d;
+            v += w * ny * d;
+        }
+        let det = a * c - b * b;
+        if det.abs() <= 1e-12 * (a * c).abs().max(f64::MIN_POSITIVE) {
+            return None;
+        }
+        let pos = ((c * u - b * v) / det, (a * v - b * u) / det);
+        Some((pos, [c / det, -b / det, a / det]))
+    };
+
+    let (pos, _) = solve(&vec![1.0; bearings.len()])?;
+
+    // Weight lines with the inverse variance of the distance error at the estimate.
+    let sigma = bearing_std.to_radians();
+    let weights: Vec<f64> = bearings
+        .iter()
+        .map(|((x, y), _)| {
+            let d = ((pos.0 - x).powi(2) + (pos.1 - y).powi(2)).sqrt().max(1.0);
+            1.0 / (d * sigma).powi(2)
+        })
+        .collect();
+    let (pos, cov) = solve(&weights)?;
+
+    // Bearing lines have to intersect in front of the stations.
+    let ahead = bearings.iter().all(|((x, y), theta)| {
+        let (s, c) = theta.to_radians().sin_cos();
+        (pos.0 - x) * c + (pos.1 - y) * s > 0.0
+    });
+    ahead.then_some((pos, cov))
+}
+
+/// Fuse bearings of multiple direction-finding stations to a position estimate.
+pub struct Triangulation {
+    stations: HashMap<String, (f64, f64)>,
+    bearings: HashMap<String, ((f64, f64), f64, Instant)>,
+    max_age: Duration,
+    bearing_std: f64,
+}
+
+impl Triangulation {
+    /// Create Triangulation block
+    ///
+    /// ## Parameter
+    /// - `stations`: known station positions `(x, y)` in meters
+    /// - `max_age`: maximum age of the bearings used for an estimate
+    /// - `bearing_std`: standard deviation of the bearings in degrees
+    pub fn new(
+        stations: HashMap<String, (f64, f64)>,
+        max_age: Duration,
+        bearing_std: f64,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Triangulation").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("bearing", Self::bearing_handler)
+                .add_output("position")
+                .build(),
+            Triangulation {
+                stations,
+                bearings: HashMap::new(),
+                max_age,
+                bearing_std,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn bearing_handler(
+        &mut self,
+        io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let m = match p {
+            Pmt::MapStrPmt(m) => m,
+            Pmt::Finished => {
+                io.finished = true;
+                return Ok(Pmt::Ok);
+            }
+            _ => return Ok(Pmt::InvalidValue),
+        };
+
+        let station = match m.get("station") {
+            Some(Pmt::String(s)) => s.clone(),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        let float = |key: &str| -> Option<f64> { m.get(key)?.clone().try_into().ok() };
+        let bearing = match float("bearing") {
+            Some(b) => b,
+            None => return Ok(Pmt::InvalidValue),
+        };
+        let position = match (float("x"), float("y")) {
+            (Some(x), Some(y)) => (x, y),
+            _ => match self.stations.get(&station) {
+                Some(p) => *p,
+                None => return Ok(Pmt::InvalidValue),
+            },
+        };
+
+        let now = Instant::now();
+        self.bearings.insert(station, (position, bearing, now));
+        self.bearings
+            .retain(|_, (_, _, t)| now.duration_since(*t) <= self.max_age);
+
+        let lines: Vec<((f64, f64), f64)> =
+            self.bearings.values().map(|(p, b, _)| (*p, *b)).collect();
+        if let Some(((x, y), [cxx, cxy, cyy])) = triangulate(&lines, self.bearing_std) {
+            let mean = (cxx + cyy) / 2.0;
+            let diff = (((cxx - cyy) / 2.0).powi(2) + cxy * cxy).sqrt();
+            let orientation = 0.5 * (2.0 * cxy).atan2(cxx - cyy);
+            mio.post(
+                0,
+                Pmt::MapStrPmt(HashMap::from([
+                    ("x".to_string(), Pmt::F64(x)),
+                    ("y".to_string(), Pmt::F64(y)),
+                    ("major".to_string(), Pmt::F64((mean + diff).sqrt())),
+                    ("minor".to_string(), Pmt::F64((mean - diff).max(0.0).sqrt())),
+                    (
+                        "orientation".to_string(),
+                        Pmt::F64(orientation.to_degrees()),
+                    ),
+                    ("stations".to_string(), Pmt::Usize(lines.len())),
+                ])),
+            )
+            .await;
+        }
+
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Triangulation {}
+
+/// Build a [Triangulation] block.
+///
+/// Collects the bearings of multiple direction-finding stations, e.g., received through
+/// networked message blocks, and estimates the position of the transmitter as the weighted
+/// least-squares intersection of the bearing lines. For each station, only the latest bearing is
+/// used and bearings older than the maximum age are discarded.
+///
+/// Positions are in meters in a local plane, e.g., east and north of a reference point.
+/// Bearings are in degrees, measured counter-clockwise from the x-axis.
+///
+/// # Inputs
+///
+/// **Message** `bearing`: [`Pmt::MapStrPmt`] with the name of the `station` ([`Pmt::String`])
+/// and the `bearing` ([`Pmt::F32`], [`Pmt::F64`]). The position of the station can be given
+/// with `x` and `y` ([`Pmt::F32`], [`Pmt::F64`]) or configured in the builder.
+///
+/// # Outputs
+///
+/// **Message** `position`: [`Pmt::MapStrPmt`] with the estimated position `x` and `y`, the
+/// `major` and `minor` semi-axes of the one-sigma confidence ellipse, the `orientation` of the
+/// major axis in degrees (all [`Pmt::F64`]), and the number of `stations` ([`Pmt::Usize`]).
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::doa::TriangulationBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let triangulation = fg.add_block(
+///     TriangulationBuilder::new()
+///         .station("north", (0.0, 1000.0))
+///         .station("east", (1000.0, 0.0))
+///         .build(),
+/// );
+/// ```
+#[derive(Default)]
+pub struct TriangulationBuilder {
+    stations: HashMap<String, (f64, f64)>,
+    max_age: Option<Duration>,
+    bearing_std: Option<f64>,
+}
+
+impl TriangulationBuilder {
+    /// Create Triangulation builder
+    pub fn new() -> TriangulationBuilder {
+        TriangulationBuilder::default()
+    }
+    /// Position `(x, y)` of a station in meters
+    #[must_use]
+    pub fn station<S: Into<String>>(mut self, name: S, position: (f64, f64)) -> Self {
+        self.stations.insert(name.into(), position);
+        self
+    }
+    /// Maximum age of bearings (default: 10 s)
+    #[must_use]
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+    /// Standard deviation of the bearings in degrees (default: 2)
+    #[must_use]
+    pub fn bearing_std(mut self, bearing_std: f64) -> Self {
+        self.bearing_std = Some(bearing_std);
+        self
+    }
+    /// Build Triangulation block
+    pub fn build(self) -> Block {
+        Triangulation::new(
+            self.stations,
+            self.max_age.unwrap_or(Duration::from_secs(10)),
+            self.bearing_std.unwrap_or(2.0),
+        )
+    }
+}
@@ -10,6 +10,7 @@ use crate::runtime::BlockMetaBuilder;
 use crate::runtime::Kernel;
 use crate::runtime::MessageIo;
 use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
@@ -26,6 +27,11 @@ use crate::runtime::WorkIo;
 ///
 /// `out`: FFT results (Complex32)
 ///
+/// # Messages
+///
+/// `fft_size`: Set the FFT size ([`Pmt::Usize`], [`Pmt::U32`], [`Pmt::U64`]) at runtime.
+/// [`Pmt::Null`] returns the current size.
+///
 /// # Usage
 /// ```
 /// use futuresdr::blocks::Fft;
@@ -68,11 +74,7 @@ impl Fft {
         fft_shift: bool,
         normalize: Option<f32>,
     ) -> Block {
-        let mut planner = FftPlanner::<f32>::new();
-        let plan = match direction {
-            FftDirection::Forward => planner.plan_fft_forward(len),
-            FftDirection::Inverse => planner.plan_fft_inverse(len),
-        };
+        let plan = Self::plan(len, &direction);
 
         Block::new(
             BlockMetaBuilder::new("Fft").build(),
@@ -80,7 +82,9 @@ impl Fft {
                 .add_input::<Complex32>("in")
                 .add_output::<Complex32>("out")
                 .build(),
-            MessageIoBuilder::<Fft>::new().build(),
+            MessageIoBuilder::<Fft>::new()
+                .add_input("fft_size", Self::fft_size_handler)
+                .build(),
             Fft {
                 len,
                 plan,
@@ -91,6 +95,40 @@ impl Fft {
             },
         )
     }
+
+    fn plan(len: usize, direction: &FftDirection) -> Arc<dyn rustfft::Fft<f32>> {
+        let mut planner = FftPlanner::<f32>::new();
+        match direction {
+            FftDirection::Forward => planner.plan_fft_forward(len),
+            FftDirection::Inverse => planner.plan_fft_inverse(len),
+        }
+    }
+
+    #[message_handler]
+    async fn fft_size_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let len = match p {
+            Pmt::Usize(v) => v,
+            Pmt::U32(v) => v as usize,
+            Pmt::U64(v) => v as usize,
+            Pmt::Null => return Ok(Pmt::Usize(self.len)),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if len == 0 {
+            return Ok(Pmt::InvalidValue);
+        }
+        if len != self.len {
+            self.len = len;
+            self.plan = Self::plan(len, &self.direction);
+            self.scratch = vec![Complex32::new(0.0, 0.0); len * 10].into_boxed_slice();
+        }
+        Ok(Pmt::Ok)
+    }
 }
 
 #[doc(hidden)]
@@ -0,0 +1,230 @@
+use async_fs::File;
+use futures::io::AsyncWriteExt;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::anyhow::{Context, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Record samples to files, started and stopped through messages.
+///
+/// While not recording, samples are dropped. Samples are encoded like for
+/// [FileSink](super::FileSink).
+pub struct FileRecorder<T: Send + 'static> {
+    template: String,
+    n_recordings: usize,
+    start: bool,
+    file: Option<(File, String)>,
+    bytes: u64,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> FileRecorder<T> {
+    /// Create FileRecorder block
+    pub fn new<S: Into<String>>(template: S, start: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("FileRecorder").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new()
+                .add_input("record", Self::record_handler)
+                .add_input("size", Self::size_handler)
+                .add_output("file")
+                .build(),
+            FileRecorder::<T> {
+                template: template.into(),
+                n_recordings: 0,
+                start,
+                file: None,
+                bytes: 0,
+                _type: std::marker::PhantomData,
+            },
+        )
+    }
+
+    fn file_name(&self) -> String {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.template
+            .replace("{n}", &self.n_recordings.to_string())
+            .replace("{time}", &secs.to_string())
+    }
+
+    async fn start_recording(&mut self) -> Result<()> {
+        if self.file.is_none() {
+            let file_name = self.file_name();
+            let file = File::create(&file_name)
+                .await
+                .with_context(|| format!("FileRecorder: cannot create {file_name}"))?;
+            self.bytes = 0;
+            self.file = Some((file, file_name));
+        }
+        Ok(())
+    }
+
+    async fn stop_recording(&mut self, mio: &mut MessageIo<Self>) -> Result<()> {
+        if let Some((mut file, file_name)) = self.file.take() {
+            file.flush().await?;
+            file.sync_all().await?;
+            self.n_recordings += 1;
+            mio.post(0, Pmt::String(file_name)).await;
+        }
+        Ok(())
+    }
+
+    #[message_handler]
+    async fn record_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Bool(true) => self.start_recording().await?,
+            Pmt::Bool(false) => self.stop_recording(mio).await?,
+            Pmt::Null => return Ok(Pmt::Bool(self.file.is_some())),
+            Pmt::Finished => {}
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn size_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        _p: Pmt,
+    ) -> Result<Pmt> {
+        Ok(Pmt::U64(self.bytes))
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: Send + 'static> Kernel for FileRecorder<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice_unchecked::<u8>();
+
+        let item_size = std::mem::size_of::<T>();
+        let items = i.len() / item_size;
+
+        if items > 0 {
+            if let Some((file, file_name)) = self.file.as_mut() {
+                let i = &i[..items * item_size];
+                file.write_all(i)
+                    .await
+                    .with_context(|| format!("FileRecorder: writing to {file_name} failed"))?;
+                self.bytes += i.len() as u64;
+            }
+        }
+
+        sio.input(0).consume(items);
+
+        if sio.input(0).finished() {
+            self.stop_recording(mio).await?;
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if self.start {
+            self.start_recording().await?;
+        }
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some((file, _)) = self.file.as_mut() {
+            file.flush().await?;
+            file.sync_all().await?;
+        }
+        Ok(())
+    }
+}
+
+/// Build a [FileRecorder].
+///
+/// The file name template can contain `{n}`, which is replaced by the index of the recording,
+/// and `{time}`, which is replaced by the UNIX time in seconds at the start of the recording.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input
+///
+/// **Message** `record`: Start ([`Pmt::Bool`] `true`) or stop ([`Pmt::Bool`] `false`) recording.
+/// [`Pmt::Null`] returns whether a recording is active.
+///
+/// **Message** `size`: Returns the size of the current or last recording in bytes
+/// ([`Pmt::U64`]).
+///
+/// # Outputs
+///
+/// **Message** `file`: File name of a completed recording ([`Pmt::String`]).
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::FileRecorderBuilder;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex32;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let recorder = fg.add_block(FileRecorderBuilder::<Complex32>::new("capture-{time}.cf32").build());
+/// ```
+pub struct FileRecorderBuilder<T: Send + 'static> {
+    template: String,
+    start: bool,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> FileRecorderBuilder<T> {
+    /// Create FileRecorder builder
+    pub fn new<S: Into<String>>(template: S) -> Self {
+        Self {
+            template: template.into(),
+            start: false,
+            _type: std::marker::PhantomData,
+        }
+    }
+    /// Start recording right away (default: `false`)
+    #[must_use]
+    pub fn start(mut self, start: bool) -> Self {
+        self.start = start;
+        self
+    }
+    /// Build FileRecorder block
+    pub fn build(self) -> Block {
+        FileRecorder::<T>::new(self.template, self.start)
+    }
+}
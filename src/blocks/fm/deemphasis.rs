@@ -0,0 +1,111 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Single-pole lowpass with time constant `tau`, designed with the bilinear transform
+pub(super) struct DeemphasisFilter {
+    b: f32,
+    a: f32,
+    last_input: f32,
+    last_output: f32,
+}
+
+impl DeemphasisFilter {
+    pub(super) fn new(sample_rate: f64, tau: f64) -> DeemphasisFilter {
+        // prewarp the corner frequency
+        let w = 2.0 * sample_rate * (1.0 / (tau * 2.0 * sample_rate)).tan();
+        let k = -w / (2.0 * sample_rate);
+        DeemphasisFilter {
+            b: (-k / (1.0 - k)) as f32,
+            a: ((1.0 + k) / (1.0 - k)) as f32,
+            last_input: 0.0,
+            last_output: 0.0,
+        }
+    }
+
+    pub(super) fn filter(&mut self, x: f32) -> f32 {
+        self.last_output = self.b * (x + self.last_input) + self.a * self.last_output;
+        self.last_input = x;
+        self.last_output
+    }
+}
+
+/// FM de-emphasis filter.
+///
+/// Single-pole lowpass with time constant `tau`, undoing the pre-emphasis of FM broadcast,
+/// i.e., 75 µs in the Americas and 50 µs in Europe. The gain at DC is one.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Demodulated samples
+///
+/// # Outputs
+///
+/// **Stream** `out`: De-emphasized samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::fm::Deemphasis;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let deemphasis = fg.add_block(Deemphasis::new(48e3, 50e-6));
+/// ```
+pub struct Deemphasis {
+    filter: DeemphasisFilter,
+}
+
+impl Deemphasis {
+    /// Create Deemphasis block for samples at `sample_rate` Hz with time constant `tau` seconds
+    pub fn new(sample_rate: f64, tau: f64) -> Block {
+        assert!(sample_rate > 0.0 && tau > 0.0);
+        Block::new(
+            BlockMetaBuilder::new("Deemphasis").build(),
+            StreamIoBuilder::new()
+                .add_input::<f32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Deemphasis {
+                filter: DeemphasisFilter::new(sample_rate, tau),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Deemphasis {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<f32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (v, out) in i.iter().zip(o.iter_mut()) {
+            *out = self.filter.filter(*v);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,14 @@
+//! ## FM Demodulation Blocks
+//!
+//! Building blocks of FM receivers, i.e., the [QuadratureDemod] and the [Deemphasis], and
+//! complete receivers for FM broadcast ([WbfmReceiveBuilder]) and narrowband FM
+//! ([NbfmReceiveBuilder]), which turn a channel at baseband into audio.
+
+mod deemphasis;
+pub use deemphasis::Deemphasis;
+
+mod quadrature_demod;
+pub use quadrature_demod::QuadratureDemod;
+
+mod receiver;
+pub use receiver::{FmReceive, NbfmReceiveBuilder, WbfmReceiveBuilder};
@@ -0,0 +1,140 @@
+use crate::anyhow::Result;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Phase difference of consecutive samples, scaled by a gain
+pub(super) struct Demodulator {
+    pub(super) gain: f32,
+    last: Complex32,
+}
+
+impl Demodulator {
+    pub(super) fn new(gain: f32) -> Demodulator {
+        Demodulator {
+            gain,
+            last: Complex32::new(0.0, 0.0),
+        }
+    }
+
+    /// Gain that maps the `max_deviation` of a signal, sampled at `sample_rate`, to one
+    pub(super) fn gain(sample_rate: f64, max_deviation: f64) -> f32 {
+        (sample_rate / (2.0 * std::f64::consts::PI * max_deviation)) as f32
+    }
+
+    pub(super) fn demodulate(&mut self, v: Complex32) -> f32 {
+        let phase = (v * self.last.conj()).arg();
+        self.last = v;
+        self.gain * phase
+    }
+}
+
+/// Quadrature demodulator, i.e., FM demodulator.
+///
+/// Outputs the phase difference of consecutive samples, scaled by `gain`, like the
+/// `Quadrature Demod` of GNU Radio. Use [`QuadratureDemod::with_deviation`] to map the maximum
+/// frequency deviation to an amplitude of one.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Complex baseband samples
+///
+/// **Message** `gain`: Set the gain ([`Pmt::F32`]). [`Pmt::Null`] returns the current gain.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Demodulated samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::fm::QuadratureDemod;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 5 kHz deviation, sampled at 48 kHz
+/// let demod = fg.add_block(QuadratureDemod::with_deviation(48e3, 5e3));
+/// ```
+pub struct QuadratureDemod {
+    demodulator: Demodulator,
+}
+
+impl QuadratureDemod {
+    /// Create QuadratureDemod block
+    pub fn new(gain: f32) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("QuadratureDemod").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("gain", Self::gain_handler)
+                .build(),
+            QuadratureDemod {
+                demodulator: Demodulator::new(gain),
+            },
+        )
+    }
+
+    /// Create QuadratureDemod block, mapping `max_deviation` Hz of a signal sampled at
+    /// `sample_rate` Hz to one
+    pub fn with_deviation(sample_rate: f64, max_deviation: f64) -> Block {
+        Self::new(Demodulator::gain(sample_rate, max_deviation))
+    }
+
+    #[message_handler]
+    async fn gain_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(g) => {
+                self.demodulator.gain = g;
+                Ok(Pmt::Ok)
+            }
+            Pmt::Null => Ok(Pmt::F32(self.demodulator.gain)),
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for QuadratureDemod {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let m = std::cmp::min(i.len(), o.len());
+        for (v, out) in i.iter().zip(o.iter_mut()) {
+            *out = self.demodulator.demodulate(*v);
+        }
+
+        sio.input(0).consume(m);
+        sio.output(0).produce(m);
+
+        if sio.input(0).finished() && m == i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,360 @@
+use futuredsp::fir::PolyphaseResamplingFirKernel;
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+
+use super::deemphasis::DeemphasisFilter;
+use super::quadrature_demod::Demodulator;
+use crate::anyhow::{bail, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// FM receiver, i.e., quadrature demodulator, decimating audio lowpass, and de-emphasis.
+///
+/// Built with the [WbfmReceiveBuilder] or the [NbfmReceiveBuilder].
+pub struct FmReceive {
+    demodulator: Demodulator,
+    demodulated: Vec<f32>,
+    decimation: usize,
+    filter: PolyphaseResamplingFirKernel<f32, f32, Vec<f32>, f32>,
+    filter_len: usize,
+    deemphasis: Option<DeemphasisFilter>,
+}
+
+impl FmReceive {
+    /// Create FmReceive block
+    ///
+    /// ## Parameter
+    /// - `gain`: gain of the quadrature demodulator
+    /// - `decimation`: decimation of the audio lowpass
+    /// - `taps`: taps of the audio lowpass
+    /// - `deemphasis`: sample rate of the audio and time constant of the de-emphasis filter,
+    ///   `None` to disable de-emphasis
+    pub fn new(
+        gain: f32,
+        decimation: usize,
+        taps: Vec<f32>,
+        deemphasis: Option<(f64, f64)>,
+    ) -> Block {
+        Self::with_name("FmReceive", gain, decimation, taps, deemphasis)
+    }
+
+    fn with_name(
+        name: &str,
+        gain: f32,
+        decimation: usize,
+        taps: Vec<f32>,
+        deemphasis: Option<(f64, f64)>,
+    ) -> Block {
+        assert!(decimation > 0);
+        assert!(!taps.is_empty());
+        Block::new(
+            BlockMetaBuilder::new(name).build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            FmReceive {
+                demodulator: Demodulator::new(gain),
+                demodulated: Vec::new(),
+                decimation,
+                filter_len: taps.len(),
+                filter: PolyphaseResamplingFirKernel::new(1, decimation, taps),
+                deemphasis: deemphasis.map(|(rate, tau)| DeemphasisFilter::new(rate, tau)),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for FmReceive {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        // demodulate only what the lowpass can process with the available output buffer
+        let wanted =
+            (o.len() * self.decimation + self.filter_len).saturating_sub(self.demodulated.len());
+        let n = std::cmp::min(i.len(), wanted);
+        for v in i[..n].iter() {
+            let d = self.demodulator.demodulate(*v);
+            self.demodulated.push(d);
+        }
+
+        let (consumed, produced, status) = self.filter.work(&self.demodulated, o);
+        self.demodulated.drain(..consumed);
+        if let Some(deemphasis) = self.deemphasis.as_mut() {
+            for v in o[..produced].iter_mut() {
+                *v = deemphasis.filter(*v);
+            }
+        }
+
+        sio.input(0).consume(n);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && n == i.len() && status.produced_all_samples() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Taps of the audio lowpass with passband up to `bandwidth` and stopband from
+/// `bandwidth + transition`, limited to half the audio rate
+fn audio_filter(
+    quad_rate: f64,
+    decimation: usize,
+    bandwidth: Option<f64>,
+    default_bandwidth: f64,
+    transition: f64,
+) -> Result<Vec<f32>> {
+    if !(quad_rate > 0.0 && quad_rate.is_finite()) {
+        bail!("quadrature rate has to be positive");
+    }
+    if decimation == 0 {
+        bail!("audio decimation has to be positive");
+    }
+    let audio_rate = quad_rate / decimation as f64;
+    let bandwidth = bandwidth.unwrap_or_else(|| default_bandwidth.min(0.4 * audio_rate));
+    let stop = (bandwidth + transition).min(0.49 * audio_rate);
+    if bandwidth <= 0.0 || stop <= bandwidth {
+        bail!("audio bandwidth has to be between 0 and half the audio rate");
+    }
+    Ok(firdes::kaiser::lowpass::<f32>(
+        bandwidth / quad_rate,
+        (stop - bandwidth) / quad_rate,
+        0.001,
+    ))
+}
+
+/// Build a wideband FM receiver, i.e., for FM broadcast.
+///
+/// Demodulates the signal, sampled at `quad_rate`, filters the mono audio, decimates it by
+/// `audio_decimation`, and applies the de-emphasis, like the `WBFM Receive` of GNU Radio.
+/// The maximum deviation is mapped to an amplitude of one.
+///
+/// # Inputs
+///
+/// **Stream** `in`: FM broadcast channel at baseband
+///
+/// # Outputs
+///
+/// **Stream** `out`: Audio at `quad_rate / audio_decimation`
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::anyhow::Result;
+/// use futuresdr::blocks::fm::WbfmReceiveBuilder;
+/// use futuresdr::blocks::FileSink;
+/// use futuresdr::blocks::FileSource;
+/// use futuresdr::blocks::XlatingFirBuilder;
+/// use futuresdr::macros::connect;
+/// use futuresdr::runtime::Flowgraph;
+/// use futuresdr::runtime::Runtime;
+/// use num_complex::Complex32;
+///
+/// fn main() -> Result<()> {
+///     let mut fg = Flowgraph::new();
+///
+///     // station 200 kHz above the center of a 1.92 MS/s capture, 48 kHz audio
+///     let src = FileSource::<Complex32>::new("capture.cf32", false);
+///     let channel = XlatingFirBuilder::new(8, 200e3, 1.92e6).bandwidth(200e3).build()?;
+///     let wbfm = WbfmReceiveBuilder::new(240e3, 5).tau(50e-6).build()?;
+///     let snk = FileSink::<f32>::new("audio.f32");
+///
+///     connect!(fg, src > channel > wbfm > snk);
+///     Runtime::new().run(fg)?;
+///     Ok(())
+/// }
+/// ```
+pub struct WbfmReceiveBuilder {
+    quad_rate: f64,
+    audio_decimation: usize,
+    max_deviation: f64,
+    tau: Option<f64>,
+    audio_bandwidth: Option<f64>,
+}
+
+impl WbfmReceiveBuilder {
+    /// Create WbfmReceive builder for a channel sampled at `quad_rate` Hz
+    pub fn new(quad_rate: f64, audio_decimation: usize) -> WbfmReceiveBuilder {
+        WbfmReceiveBuilder {
+            quad_rate,
+            audio_decimation,
+            max_deviation: 75e3,
+            tau: Some(75e-6),
+            audio_bandwidth: None,
+        }
+    }
+    /// Maximum frequency deviation in Hz (default: 75 kHz)
+    #[must_use]
+    pub fn max_deviation(mut self, max_deviation: f64) -> WbfmReceiveBuilder {
+        self.max_deviation = max_deviation;
+        self
+    }
+    /// Time constant of the de-emphasis in seconds (default: 75 µs, 50 µs in Europe)
+    #[must_use]
+    pub fn tau(mut self, tau: f64) -> WbfmReceiveBuilder {
+        self.tau = Some(tau);
+        self
+    }
+    /// Disable the de-emphasis
+    #[must_use]
+    pub fn no_deemphasis(mut self) -> WbfmReceiveBuilder {
+        self.tau = None;
+        self
+    }
+    /// Bandwidth of the audio in Hz (default: 15 kHz, limited to 40% of the audio rate)
+    #[must_use]
+    pub fn audio_bandwidth(mut self, audio_bandwidth: f64) -> WbfmReceiveBuilder {
+        self.audio_bandwidth = Some(audio_bandwidth);
+        self
+    }
+    /// Build WbfmReceive block
+    pub fn build(self) -> Result<Block> {
+        // stopband below the stereo pilot at 19 kHz
+        let taps = audio_filter(
+            self.quad_rate,
+            self.audio_decimation,
+            self.audio_bandwidth,
+            15e3,
+            4e3,
+        )?;
+        build(
+            "WbfmReceive",
+            self.quad_rate,
+            self.audio_decimation,
+            self.max_deviation,
+            self.tau,
+            taps,
+        )
+    }
+}
+
+/// Build a narrowband FM receiver, e.g., for voice.
+///
+/// Demodulates the signal, sampled at `quad_rate`, filters the audio, decimates it by
+/// `audio_decimation`, and applies the de-emphasis, like the `NBFM Receive` of GNU Radio.
+/// The maximum deviation is mapped to an amplitude of one.
+///
+/// # Inputs
+///
+/// **Stream** `in`: NBFM channel at baseband
+///
+/// # Outputs
+///
+/// **Stream** `out`: Audio at `quad_rate / audio_decimation`
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::fm::NbfmReceiveBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // 48 kHz channel to 8 kHz audio
+/// let nbfm = fg.add_block(NbfmReceiveBuilder::new(48e3, 6).build().unwrap());
+/// ```
+pub struct NbfmReceiveBuilder {
+    quad_rate: f64,
+    audio_decimation: usize,
+    max_deviation: f64,
+    tau: Option<f64>,
+    audio_bandwidth: Option<f64>,
+}
+
+impl NbfmReceiveBuilder {
+    /// Create NbfmReceive builder for a channel sampled at `quad_rate` Hz
+    pub fn new(quad_rate: f64, audio_decimation: usize) -> NbfmReceiveBuilder {
+        NbfmReceiveBuilder {
+            quad_rate,
+            audio_decimation,
+            max_deviation: 5e3,
+            tau: Some(75e-6),
+            audio_bandwidth: None,
+        }
+    }
+    /// Maximum frequency deviation in Hz (default: 5 kHz)
+    #[must_use]
+    pub fn max_deviation(mut self, max_deviation: f64) -> NbfmReceiveBuilder {
+        self.max_deviation = max_deviation;
+        self
+    }
+    /// Time constant of the de-emphasis in seconds (default: 75 µs)
+    #[must_use]
+    pub fn tau(mut self, tau: f64) -> NbfmReceiveBuilder {
+        self.tau = Some(tau);
+        self
+    }
+    /// Disable the de-emphasis
+    #[must_use]
+    pub fn no_deemphasis(mut self) -> NbfmReceiveBuilder {
+        self.tau = None;
+        self
+    }
+    /// Bandwidth of the audio in Hz (default: 3 kHz, limited to 40% of the audio rate)
+    #[must_use]
+    pub fn audio_bandwidth(mut self, audio_bandwidth: f64) -> NbfmReceiveBuilder {
+        self.audio_bandwidth = Some(audio_bandwidth);
+        self
+    }
+    /// Build NbfmReceive block
+    pub fn build(self) -> Result<Block> {
+        let taps = audio_filter(
+            self.quad_rate,
+            self.audio_decimation,
+            self.audio_bandwidth,
+            3e3,
+            1e3,
+        )?;
+        build(
+            "NbfmReceive",
+            self.quad_rate,
+            self.audio_decimation,
+            self.max_deviation,
+            self.tau,
+            taps,
+        )
+    }
+}
+
+fn build(
+    name: &str,
+    quad_rate: f64,
+    audio_decimation: usize,
+    max_deviation: f64,
+    tau: Option<f64>,
+    taps: Vec<f32>,
+) -> Result<Block> {
+    if !(max_deviation > 0.0 && max_deviation.is_finite()) {
+        bail!("maximum deviation has to be positive");
+    }
+    let deemphasis = match tau {
+        Some(t) if t <= 0.0 => bail!("time constant has to be positive"),
+        Some(t) => Some((quad_rate / audio_decimation as f64, t)),
+        None => None,
+    };
+    Ok(FmReceive::with_name(
+        name,
+        Demodulator::gain(quad_rate, max_deviation),
+        audio_decimation,
+        taps,
+        deemphasis,
+    ))
+}
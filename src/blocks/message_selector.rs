@@ -0,0 +1,121 @@
+use crate::anyhow::Result;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Forward messages to one of `N` outputs.
+///
+/// Messages received on `in` are posted to the selected output `out<i>` or, in broadcast mode,
+/// to all outputs.
+///
+/// The output is selected with a [`Pmt::Usize`], [`Pmt::U32`], or [`Pmt::U64`] on the `select`
+/// port. Out-of-range indices are rejected with [`Pmt::InvalidValue`]. A [`Pmt::Null`] queries
+/// the current selection. Broadcast mode is enabled or disabled with a [`Pmt::Bool`] on the
+/// `broadcast` port, which also returns the current mode for a [`Pmt::Null`].
+pub struct MessageSelector<const N: usize> {
+    selected: usize,
+    broadcast: bool,
+}
+
+impl<const N: usize> MessageSelector<N> {
+    /// Create MessageSelector block, forwarding to output 0
+    pub fn new() -> Block {
+        Self::with_broadcast(false)
+    }
+
+    /// Create MessageSelector block, optionally starting in broadcast mode
+    pub fn with_broadcast(broadcast: bool) -> Block {
+        assert!(N > 0, "MessageSelector needs at least one output");
+
+        let mut mio = MessageIoBuilder::new()
+            .add_input("in", Self::handler)
+            .add_input("select", Self::select)
+            .add_input("broadcast", Self::broadcast);
+        for i in 0..N {
+            mio = mio.add_output(&format!("out{i}"));
+        }
+
+        Block::new(
+            BlockMetaBuilder::new(format!("MessageSelector<{N}>")).build(),
+            StreamIoBuilder::new().build(),
+            mio.build(),
+            MessageSelector {
+                selected: 0,
+                broadcast,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn handler(
+        &mut self,
+        io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Finished => {
+                io.finished = true;
+            }
+            p if self.broadcast => {
+                for i in 0..N {
+                    mio.post(i, p.clone()).await;
+                }
+            }
+            p => {
+                mio.post(self.selected, p).await;
+            }
+        }
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn select(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let index = match p {
+            Pmt::Null => return Ok(Pmt::Usize(self.selected)),
+            Pmt::Usize(v) => v,
+            Pmt::U32(v) => v as usize,
+            Pmt::U64(v) => v as usize,
+            _ => return Ok(Pmt::InvalidValue),
+        };
+
+        if index >= N {
+            return Ok(Pmt::InvalidValue);
+        }
+        self.selected = index;
+        Ok(Pmt::Usize(self.selected))
+    }
+
+    #[message_handler]
+    async fn broadcast(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Null => {}
+            Pmt::Bool(b) => self.broadcast = b,
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        Ok(Pmt::Bool(self.broadcast))
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<const N: usize> Kernel for MessageSelector<N> {}
@@ -18,8 +18,29 @@
 //! | [Agc](Agc) | Automatic Gain Control | ✅ |
 //! | [Fft](Fft) | Compute an FFT. | ✅ |
 //! | [Fir](FirBuilder) | FIR filter and resampler. | ✅ |
+//! | [Spectrogram](SpectrogramBuilder) | Power spectrum of overlapping, windowed segments. | ✅ |
+//! | [XlatingFir](XlatingFirBuilder) | Frequency-translating FIR filter: shift a channel to baseband, filter, and decimate. | ✅ |
+//! | [ArbitraryResampler](ArbitraryResamplerBuilder) | Polyphase resampler for arbitrary, non-integer rates. | ✅ |
+//! | [SymbolSync](SymbolSyncBuilder) | Symbol timing recovery with Mueller and Müller or polyphase filter bank timing error detectors. | ✅ |
 //! | [Iir](IirBuilder) | IIR filter. | ✅ |
 //!
+//! ## FM Demodulation
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [QuadratureDemod](fm::QuadratureDemod) | Demodulate FM, i.e., phase difference of consecutive samples. | ✅ |
+//! | [Deemphasis](fm::Deemphasis) | FM de-emphasis filter. | ✅ |
+//! | [WbfmReceive](fm::WbfmReceiveBuilder) | FM broadcast receiver: demodulation, audio filter, decimation, and de-emphasis. | ✅ |
+//! | [NbfmReceive](fm::NbfmReceiveBuilder) | Narrowband FM receiver: demodulation, audio filter, decimation, and de-emphasis. | ✅ |
+//!
+//! ## Channel Models
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [Awgn](channel::AwgnBuilder) | Add white Gaussian noise, with runtime SNR and flat-fading gain. | ✅ |
+//! | [Fading](channel::FadingBuilder) | Flat Rayleigh or Rician fading. | ✅ |
+//! | [FrequencyOffset](channel::FrequencyOffset) | Static carrier frequency and phase offset. | ✅ |
+//! | [Multipath](channel::Multipath) | Tapped-delay-line multipath channel. | ✅ |
+//! | [TimingOffset](channel::TimingOffset) | Fractional timing and sample clock offset. | ✅ |
+//!
 //! ## Misc
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
@@ -40,8 +61,10 @@
 //! | [MessageBurst] | Output a given number of messages in one burst and terminate. | ✅ |
 //! | [MessageCopy] | Forward messages. | ✅ |
 //! | [MessagePipe] | Push received messages into a channel. | ✅ |
+//! | [MessageSelector] | Forward messages to a selected output or to all outputs. | ✅ |
 //! | [MessageSink] | Black hole for messages. | ✅ |
 //! | [MessageSource](MessageSourceBuilder) | Output the same message periodically. | ✅ |
+//! | [Sweeper](SweeperBuilder) | Step through a frequency range, e.g., to scan a band. | ❌ |
 //!
 //! ## Performance Evaluation
 //! | Block | Usage | WebAssembly? | Feature |
@@ -57,21 +80,28 @@
 //! | [BlobToUdp] | Push [Blobs](crate::runtime::Pmt::Blob) into a UDP socket. | ❌ |
 //! | [ChannelSource] | Push samples through a channel into a stream connection. | ✅ |
 //! | [ChannelSink] | Read samples from Flowgraph and send them into a channel | ✅ |
+//! | [FileRecorder](FileRecorderBuilder) | Record samples to files, started and stopped through messages. | ❌ |
 //! | [FileSink] | Write samples to a file. | ❌ |
 //! | [FileSource] | Read samples from a file. | ❌ |
-//! | [TcpSource] | Reads samples from a TCP socket. | ❌ |
-//! | [TcpSink] | Push samples into a TCP socket. | ❌ |
+//! | [SemtechUdpForwarder](SemtechUdpForwarderBuilder) | Forward LoRa frames to a network server, using the Semtech UDP packet forwarder protocol. | ❌ |
+//! | [SigmfSink](SigmfSinkBuilder) | Write a [SigMF](https://github.com/sigmf/SigMF) recording, mapping tags to captures and annotations. | ❌ |
+//! | [SigmfSource](SigmfSourceBuilder) | Read a [SigMF](https://github.com/sigmf/SigMF) recording, mapping captures and annotations to tags. | ❌ |
+//! | [TcpSource](TcpSourceBuilder) | Reads samples from a TCP socket, optionally with TLS (`tls` feature) and authentication. | ❌ |
+//! | [TcpSink](TcpSinkBuilder) | Push samples into a TCP socket, optionally with TLS (`tls` feature) and authentication. | ❌ |
 //! | [UdpSource] | Reads samples from a UDP socket. | ❌ |
 //! | [WebsocketSink] | Push samples in a WebSocket. | ❌ |
 //! | [WebsocketPmtSink] | Push samples from Pmts a WebSocket. | ❌ |
-//! | [zeromq::PubSink] | Push samples into [ZeroMQ](https://zeromq.org/) socket. | ❌ |
-//! | [zeromq::SubSource] | Read samples from [ZeroMQ](https://zeromq.org/) socket. | ❌ |
+//! | [zeromq::PubSink] | Send samples through a [ZeroMQ](https://zeromq.org/) PUB or PUSH socket, compatible with GNU Radio. | ❌ |
+//! | [zeromq::SubSource] | Receive samples from a [ZeroMQ](https://zeromq.org/) SUB or PULL socket, compatible with GNU Radio. | ❌ |
+//! | [ZmqMessageSink](zeromq::ZmqMessageSinkBuilder) | Send [Pmts](crate::runtime::Pmt) through a [ZeroMQ](https://zeromq.org/) socket in the GNU Radio PMT format. | ❌ |
+//! | [ZmqMessageSource](zeromq::ZmqMessageSourceBuilder) | Receive [Pmts](crate::runtime::Pmt) in the GNU Radio PMT format from a [ZeroMQ](https://zeromq.org/) socket. | ❌ |
 //!
 //! ## SDR Hardware
 //! | Block | Usage | Feature | WebAssembly? |
 //! |---|---|---|---|
 //! | [SeifySink](seify::SinkBuilder) | Transmit samples with a Seify device. | seify | ❌ |
 //! | [SeifySource](seify::SourceBuilder) | Receive samples from a Seify device. | seify | ❌ |
+//! | [AntennaSwitch](seify::AntennaSwitchBuilder) | Select antenna ports of a Seify device, triggered by messages or a timed schedule. | seify | ❌ |
 //!
 //! ## Hardware Acceleration
 //! | Block | Usage | WebAssembly? | Feature |
@@ -92,11 +122,24 @@
 //! |---|---|---|
 //! | [SignalSource](SignalSourceBuilder) | Create signals (sin, cos, square). | ✅ |
 //!
+//! ## Direction Finding
+//! | Block | Usage | WebAssembly? |
+//! |---|---|---|
+//! | [DoaEstimator](doa::DoaEstimatorBuilder) | Estimate direction of arrival with phase interferometry or MUSIC. | ✅ |
+//! | [Georeference](doa::Georeference) | Annotate bearings with position, heading, and time from NMEA sentences. | ✅ |
+//! | [Interferometer](doa::InterferometerBuilder) | Correlative interferometer direction finder. | ✅ |
+//! | [ManifoldWriter](doa::ManifoldWriter) | Write an array manifold file from calibration measurements. | ❌ |
+//! | [Music](doa::MusicBuilder) | Estimate angle of arrival with the MUSIC algorithm. | ✅ |
+//! | [PhaseCalibration](doa::PhaseCalibration) | Estimate per-channel phase and gain corrections from a reference tone. | ✅ |
+//! | [PhaseCorrection](doa::PhaseCorrection) | Apply per-channel phase and gain corrections. | ✅ |
+//! | [PseudoDoppler](doa::PseudoDopplerBuilder) | Pseudo-Doppler direction finder for switched circular arrays. | ✅ |
+//! | [Triangulation](doa::TriangulationBuilder) | Fuse bearings of multiple stations to a position estimate. | ✅ |
+//!
 //! ## Audio (requires `audio` feature)
 //! | Block | Usage | WebAssembly? |
 //! |---|---|---|
-//! | [AudioSink](audio::AudioSink) | Audio sink. | ❌ |
-//! | [AudioSource](audio::AudioSource) | Audio source. | ❌ |
+//! | [AudioSink](audio::AudioSinkBuilder) | Play samples on an audio device, resampling to the device rate. | ❌ |
+//! | [AudioSource](audio::AudioSourceBuilder) | Record samples from an audio device, resampling from the device rate. | ❌ |
 //! | [FileSource](audio::FileSource) | Read an audio file and output its samples. | ❌ |
 //! | [WavSink](audio::WavSink) | Writes samples to a WAV file | ❌ |
 //!
@@ -113,6 +156,10 @@ pub use applynm::ApplyNM;
 mod applyintoiter;
 pub use applyintoiter::ApplyIntoIter;
 
+mod arbitrary_resampler;
+pub use arbitrary_resampler::ArbitraryResampler;
+pub use arbitrary_resampler::ArbitraryResamplerBuilder;
+
 pub mod audio;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -120,6 +167,8 @@ mod blob_to_udp;
 #[cfg(not(target_arch = "wasm32"))]
 pub use blob_to_udp::BlobToUdp;
 
+pub mod channel;
+
 mod channel_source;
 pub use channel_source::ChannelSource;
 
@@ -140,6 +189,8 @@ pub use copy_rand::{CopyRand, CopyRandBuilder};
 mod delay;
 pub use delay::Delay;
 
+pub mod doa;
+
 mod filter;
 pub use filter::Filter;
 
@@ -151,6 +202,11 @@ mod fft;
 pub use fft::Fft;
 pub use fft::FftDirection;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod file_recorder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use file_recorder::{FileRecorder, FileRecorderBuilder};
+
 #[cfg(not(target_arch = "wasm32"))]
 mod file_sink;
 #[cfg(not(target_arch = "wasm32"))]
@@ -164,6 +220,8 @@ pub use file_source::FileSource;
 mod finite_source;
 pub use finite_source::FiniteSource;
 
+pub mod fm;
+
 mod head;
 pub use head::Head;
 
@@ -179,6 +237,8 @@ mod message_copy;
 pub use message_copy::MessageCopy;
 mod message_pipe;
 pub use message_pipe::MessagePipe;
+mod message_selector;
+pub use message_selector::MessageSelector;
 mod message_sink;
 pub use message_sink::MessageSink;
 
@@ -196,10 +256,20 @@ pub use null_source::NullSource;
 #[cfg(feature = "seify")]
 pub mod seify;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod semtech_udp_forwarder;
+#[cfg(not(target_arch = "wasm32"))]
+pub use semtech_udp_forwarder::{SemtechUdpForwarder, SemtechUdpForwarderBuilder};
+
 mod selector;
 pub use selector::DropPolicy as SelectorDropPolicy;
 pub use selector::Selector;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub mod sigmf;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sigmf::{SigmfSink, SigmfSinkBuilder, SigmfSource, SigmfSourceBuilder};
+
 pub mod signal_source;
 pub use signal_source::FixedPointPhase;
 pub use signal_source::SignalSourceBuilder;
@@ -208,21 +278,43 @@ mod sink;
 pub use sink::Sink;
 mod source;
 pub use source::Source;
+mod spectrogram;
+pub use spectrogram::FftWindow;
+pub use spectrogram::Spectrogram;
+pub use spectrogram::SpectrogramBuilder;
 mod split;
 pub use split::Split;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod sweeper;
+#[cfg(not(target_arch = "wasm32"))]
+pub use sweeper::{Sweeper, SweeperBuilder};
+
+mod symbol_sync;
+pub use symbol_sync::SymbolSync;
+pub use symbol_sync::SymbolSyncBuilder;
+pub use symbol_sync::SymbolSyncSample;
+pub use symbol_sync::TimingErrorDetector;
+
 mod tag_debug;
 pub use tag_debug::TagDebug;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod tcp;
+#[cfg(not(target_arch = "wasm32"))]
+pub use tcp::TcpBufferPolicy;
+#[cfg(all(not(target_arch = "wasm32"), feature = "tls"))]
+pub use tcp::TcpTlsConfig;
+
 #[cfg(not(target_arch = "wasm32"))]
 mod tcp_sink;
 #[cfg(not(target_arch = "wasm32"))]
-pub use tcp_sink::TcpSink;
+pub use tcp_sink::{TcpSink, TcpSinkBuilder};
 
 #[cfg(not(target_arch = "wasm32"))]
 mod tcp_source;
 #[cfg(not(target_arch = "wasm32"))]
-pub use tcp_source::TcpSource;
+pub use tcp_source::{TcpSource, TcpSourceBuilder};
 
 mod throttle;
 pub use throttle::Throttle;
@@ -261,6 +353,10 @@ mod wgpu;
 #[cfg(feature = "wgpu")]
 pub use self::wgpu::Wgpu;
 
+mod xlating_fir;
+pub use xlating_fir::XlatingFir;
+pub use xlating_fir::XlatingFirBuilder;
+
 #[cfg(feature = "zeromq")]
 pub mod zeromq;
 
@@ -0,0 +1,297 @@
+use async_io::Timer;
+use seify::Device;
+use seify::DeviceTrait;
+use seify::Direction;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::anyhow::{anyhow, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Seify Antenna Switch block
+///
+/// Switch between a list of antenna ports of a Seify device, either triggered by messages or
+/// following a timed schedule, e.g., for pseudo-Doppler direction finding.
+///
+/// The block only selects antennas through [`DeviceTrait::set_antenna`]. Seify does not provide
+/// access to device GPIOs, so external frontends can only be switched if the driver maps its
+/// antenna ports to the GPIOs or RF switches that control them.
+///
+/// The index of the active state is posted after every switch, allowing downstream blocks to
+/// synchronize to the schedule.
+pub struct AntennaSwitch<D: DeviceTrait + Clone> {
+    dev: Device<D>,
+    direction: Direction,
+    channels: Vec<usize>,
+    states: Vec<String>,
+    current: usize,
+    period: Option<Duration>,
+    t_last: Instant,
+}
+
+impl<D: DeviceTrait + Clone> AntennaSwitch<D> {
+    fn new(
+        dev: Device<D>,
+        direction: Direction,
+        channels: Vec<usize>,
+        states: Vec<String>,
+        period: Option<Duration>,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("AntennaSwitch").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("select", Self::select_handler)
+                .add_input("next", Self::next_handler)
+                .add_input("period", Self::period_handler)
+                .add_output("state")
+                .build(),
+            AntennaSwitch {
+                dev,
+                direction,
+                channels,
+                states,
+                current: 0,
+                period,
+                t_last: Instant::now(),
+            },
+        )
+    }
+
+    fn switch(&mut self, state: usize) -> Result<()> {
+        let antenna = &self.states[state];
+        for c in &self.channels {
+            self.dev.set_antenna(self.direction, *c, antenna)?;
+        }
+        self.current = state;
+        self.t_last = Instant::now();
+        Ok(())
+    }
+
+    async fn apply(&mut self, mio: &mut MessageIo<Self>, state: usize) -> Result<()> {
+        self.switch(state)?;
+        mio.post(0, Pmt::Usize(state)).await;
+        Ok(())
+    }
+
+    #[message_handler]
+    async fn select_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let state = match &p {
+            Pmt::Usize(v) => *v,
+            Pmt::U32(v) => *v as usize,
+            Pmt::U64(v) => *v as usize,
+            Pmt::String(s) => match self.states.iter().position(|x| x == s) {
+                Some(i) => i,
+                None => return Ok(Pmt::InvalidValue),
+            },
+            Pmt::Null => return Ok(Pmt::Usize(self.current)),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+
+        if state >= self.states.len() {
+            return Ok(Pmt::InvalidValue);
+        }
+        self.apply(mio, state).await?;
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn next_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if matches!(p, Pmt::Finished) {
+            return Ok(Pmt::Ok);
+        }
+        let state = (self.current + 1) % self.states.len();
+        self.apply(mio, state).await?;
+        Ok(Pmt::Usize(state))
+    }
+
+    #[message_handler]
+    async fn period_handler(
+        &mut self,
+        io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v = match p {
+            Pmt::F32(v) => v as f64,
+            Pmt::F64(v) => v,
+            Pmt::Null => {
+                return Ok(Pmt::F64(
+                    self.period.map(|p| p.as_secs_f64()).unwrap_or(0.0),
+                ))
+            }
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if !v.is_finite() {
+            return Ok(Pmt::InvalidValue);
+        }
+        if v > 0.0 {
+            match Duration::try_from_secs_f64(v) {
+                Ok(d) => self.period = Some(d),
+                Err(_) => return Ok(Pmt::InvalidValue),
+            }
+        } else {
+            self.period = None;
+        }
+        self.t_last = Instant::now();
+        io.call_again = true;
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<D: DeviceTrait + Clone> Kernel for AntennaSwitch<D> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(period) = self.period {
+            if Instant::now() >= self.t_last + period {
+                let state = (self.current + 1) % self.states.len();
+                self.apply(mio, state).await?;
+            }
+            let t = self.t_last + period;
+            io.block_on(async move {
+                Timer::at(t).await;
+            });
+        }
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.switch(0)
+    }
+}
+
+/// Build a Seify [AntennaSwitch].
+///
+/// # Inputs
+///
+/// **Message** `select`: Switch to the state with the given index ([`Pmt::Usize`],
+/// [`Pmt::U32`], [`Pmt::U64`]) or antenna name ([`Pmt::String`]). [`Pmt::Null`] returns the
+/// current index.
+///
+/// **Message** `next`: Switch to the next state.
+///
+/// **Message** `period`: Set the switching period in seconds ([`Pmt::F32`], [`Pmt::F64`]).
+/// A value of zero disables timed switching. [`Pmt::Null`] returns the current period. Values
+/// that are not finite or out of range return [`Pmt::InvalidValue`].
+///
+/// # Outputs
+///
+/// **Message** `state`: Index of the active state ([`Pmt::Usize`]), posted after every switch.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::seify::AntennaSwitchBuilder;
+/// use std::time::Duration;
+///
+/// let dev = futuresdr::seify::Device::new()?;
+/// let switch = AntennaSwitchBuilder::new(dev)
+///     .states(["ANT1", "ANT2", "ANT3", "ANT4"])
+///     .period(Duration::from_millis(1))
+///     .build()?;
+/// # Ok::<(), futuresdr::anyhow::Error>(())
+/// ```
+pub struct AntennaSwitchBuilder<D: DeviceTrait + Clone> {
+    dev: Device<D>,
+    direction: Direction,
+    channels: Vec<usize>,
+    states: Vec<String>,
+    period: Option<Duration>,
+}
+
+impl<D: DeviceTrait + Clone> AntennaSwitchBuilder<D> {
+    /// Create Antenna Switch builder for a Seify device
+    pub fn new(dev: Device<D>) -> Self {
+        Self {
+            dev,
+            direction: Direction::Rx,
+            channels: vec![0],
+            states: Vec::new(),
+            period: None,
+        }
+    }
+    /// Direction (default: [`Direction::Rx`])
+    #[must_use]
+    pub fn direction(mut self, direction: Direction) -> Self {
+        self.direction = direction;
+        self
+    }
+    /// Channel (default: `0`)
+    #[must_use]
+    pub fn channel(mut self, c: usize) -> Self {
+        self.channels = vec![c];
+        self
+    }
+    /// Channels
+    #[must_use]
+    pub fn channels(mut self, c: Vec<usize>) -> Self {
+        self.channels = c;
+        self
+    }
+    /// Antennas to switch between
+    ///
+    /// If no states are set, all antennas of the device are used.
+    #[must_use]
+    pub fn states<S: Into<String>>(mut self, states: impl IntoIterator<Item = S>) -> Self {
+        self.states = states.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Switch periodically to the next state
+    #[must_use]
+    pub fn period(mut self, period: Duration) -> Self {
+        self.period = Some(period);
+        self
+    }
+    /// Build Antenna Switch block
+    pub fn build(mut self) -> Result<Block> {
+        if self.channels.is_empty() {
+            return Err(anyhow!("no channels to switch"));
+        }
+        if self.states.is_empty() {
+            self.states = self.dev.antennas(self.direction, self.channels[0])?;
+        }
+        if self.states.is_empty() {
+            return Err(anyhow!("no antennas to switch between"));
+        }
+        Ok(AntennaSwitch::new(
+            self.dev,
+            self.direction,
+            self.channels,
+            self.states,
+            self.period,
+        ))
+    }
+}
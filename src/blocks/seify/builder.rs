@@ -4,7 +4,10 @@ use seify::DeviceTrait;
 use seify::Direction;
 
 use crate::anyhow::{anyhow, Result};
+use crate::blocks::seify::device_from_args;
 use crate::blocks::seify::Config;
+use crate::blocks::seify::OverflowPolicy;
+use crate::blocks::seify::RxClock;
 use crate::blocks::seify::Sink;
 use crate::blocks::seify::Source;
 use crate::runtime::Block;
@@ -21,6 +24,8 @@ pub struct Builder<D: DeviceTrait + Clone> {
     config: Config,
     dev: Option<Device<D>>,
     start_time: Option<i64>,
+    overflow: OverflowPolicy,
+    clock: Option<Box<dyn RxClock>>,
     builder_type: BuilderType,
 }
 
@@ -33,6 +38,8 @@ impl<D: DeviceTrait + Clone> Builder<D> {
             config: Config::new(),
             dev: None,
             start_time: None,
+            overflow: OverflowPolicy::Warn,
+            clock: None,
             builder_type,
         }
     }
@@ -49,6 +56,8 @@ impl<D: DeviceTrait + Clone> Builder<D> {
             config: self.config,
             dev: Some(dev),
             start_time: self.start_time,
+            overflow: self.overflow,
+            clock: self.clock,
             builder_type: self.builder_type,
         }
     }
@@ -87,6 +96,23 @@ impl<D: DeviceTrait + Clone> Builder<D> {
         self.config.sample_rate = Some(s);
         self
     }
+    /// Handling of samples dropped on overflows (Source only, default:
+    /// [`OverflowPolicy::Warn`])
+    ///
+    /// [`OverflowPolicy::ZeroFill`] keeps all outputs aligned with the sample clock, which is
+    /// required, e.g., to combine the channels with other receivers. It requires an
+    /// [`rx_clock`](Self::rx_clock) to determine the number of dropped samples.
+    pub fn overflow(mut self, o: OverflowPolicy) -> Self {
+        self.overflow = o;
+        self
+    }
+    /// Hardware clock of the RX stream (Source only)
+    ///
+    /// Required to determine the number of dropped samples on overflows.
+    pub fn rx_clock<C: RxClock + 'static>(mut self, c: C) -> Self {
+        self.clock = Some(Box::new(c));
+        self
+    }
     /// Builder Seify block
     pub fn build(mut self) -> Result<Block> {
         match self.dev.take() {
@@ -97,11 +123,17 @@ impl<D: DeviceTrait + Clone> Builder<D> {
                 }
                 BuilderType::Source => {
                     self.config.apply(&dev, &self.channels, Direction::Rx)?;
-                    Ok(Source::new(dev, self.channels, self.start_time))
+                    Ok(Source::new(
+                        dev,
+                        self.channels,
+                        self.start_time,
+                        self.overflow,
+                        self.clock,
+                    ))
                 }
             },
             None => {
-                let dev = Device::from_args(&self.args)?;
+                let dev = device_from_args(self.args.clone())?;
                 match self.builder_type {
                     BuilderType::Sink => {
                         self.config.apply(&dev, &self.channels, Direction::Tx)?;
@@ -109,7 +141,13 @@ impl<D: DeviceTrait + Clone> Builder<D> {
                     }
                     BuilderType::Source => {
                         self.config.apply(&dev, &self.channels, Direction::Rx)?;
-                        Ok(Source::new(dev, self.channels, self.start_time))
+                        Ok(Source::new(
+                            dev,
+                            self.channels,
+                            self.start_time,
+                            self.overflow,
+                            self.clock,
+                        ))
                     }
                 }
             }
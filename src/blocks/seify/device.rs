@@ -0,0 +1,58 @@
+use seify::Args;
+use seify::Device;
+use seify::GenericDevice;
+
+use crate::anyhow::{anyhow, Result};
+#[cfg(feature = "seify_virtual")]
+use crate::blocks::seify::VirtualDevice;
+
+/// Enumerate Seify devices
+///
+/// Like [`seify::enumerate`], but with the `seify_virtual` feature, the list also contains the
+/// [VirtualDevice](super::VirtualDevice) (`driver=virtual`).
+pub fn enumerate() -> Result<Vec<Args>> {
+    enumerate_with_args(Args::new())
+}
+
+/// Enumerate Seify devices that match the given arguments
+///
+/// Like [`seify::enumerate_with_args`], but with the `seify_virtual` feature, `driver=virtual`
+/// or arguments without a driver also list the [VirtualDevice](super::VirtualDevice).
+pub fn enumerate_with_args<A: TryInto<Args>>(a: A) -> Result<Vec<Args>> {
+    let args: Args = a.try_into().or(Err(anyhow!("Couldn't convert to Args")))?;
+
+    #[cfg(feature = "seify_virtual")]
+    if VirtualDevice::matches(&args) {
+        return Ok(vec![args]);
+    }
+
+    let devs = seify::enumerate_with_args(args.clone())?;
+    #[cfg(feature = "seify_virtual")]
+    let devs = if args.get::<String>("driver").is_err() {
+        let mut devs = devs;
+        devs.push(
+            "driver=virtual"
+                .try_into()
+                .or(Err(anyhow!("Couldn't convert to Args")))?,
+        );
+        devs
+    } else {
+        devs
+    };
+    Ok(devs)
+}
+
+/// Create a Seify device from arguments
+///
+/// Like [`seify::Device::from_args`], but with the `seify_virtual` feature, `driver=virtual`
+/// resolves to a [VirtualDevice](super::VirtualDevice). The Seify [Source](super::Source) and
+/// [Sink](super::Sink) builders create their devices through this function.
+pub fn device_from_args<A: TryInto<Args>>(a: A) -> Result<Device<GenericDevice>> {
+    let args: Args = a.try_into().or(Err(anyhow!("Couldn't convert to Args")))?;
+
+    #[cfg(feature = "seify_virtual")]
+    if VirtualDevice::matches(&args) {
+        return Ok(Device::from_impl(VirtualDevice::from_args(&args)?));
+    }
+    Ok(Device::from_args(args)?)
+}
@@ -1,11 +1,22 @@
+mod antenna_switch;
+pub use antenna_switch::{AntennaSwitch, AntennaSwitchBuilder};
+
 mod builder;
 pub use builder::Builder;
 
 mod config;
 pub use crate::blocks::seify::config::Config;
 
+mod device;
+pub use device::{device_from_args, enumerate, enumerate_with_args};
+
 mod sink;
 pub use sink::{Sink, SinkBuilder};
 
 mod source;
-pub use source::{Source, SourceBuilder};
+pub use source::{OverflowPolicy, RxClock, Source, SourceBuilder, OVERFLOW_TAG};
+
+#[cfg(feature = "seify_virtual")]
+mod virtual_device;
+#[cfg(feature = "seify_virtual")]
+pub use virtual_device::{VirtualDevice, VirtualRxClock, VirtualRxStreamer, VirtualTxStreamer};
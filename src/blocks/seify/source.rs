@@ -18,18 +18,65 @@ use crate::runtime::MessageIoBuilder;
 use crate::runtime::Pmt;
 use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
 use crate::runtime::WorkIo;
 
+/// Name of the [`Tag::NamedUsize`] that marks the first sample after an overflow, holding the
+/// number of dropped samples or `0` if the number is unknown.
+pub const OVERFLOW_TAG: &str = "overflow";
+
+/// Handling of samples that are dropped by the device on overflows.
+///
+/// All channels of a [Source] are read with one streamer, i.e., a drop affects all channels
+/// equally and they stay aligned to each other. Seify streamers do not report how many samples
+/// were dropped. The size of the gap is, therefore, only known if the [Source] has an
+/// [`RxClock`] that provides the hardware timestamp of the next sample. Without a clock, the
+/// [Source] cannot keep the outputs aligned with the sample clock and only tags the overflow.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Only log a warning (default)
+    Warn,
+    /// Tag the first sample after an overflow on all outputs with the number of dropped samples
+    /// ([`OVERFLOW_TAG`])
+    Tag,
+    /// Insert the dropped samples as zeros into all outputs and tag the first inserted sample
+    /// ([`OVERFLOW_TAG`])
+    ///
+    /// Without an [`RxClock`], this falls back to [`OverflowPolicy::Tag`].
+    ZeroFill,
+}
+
+/// Hardware clock of an RX stream
+///
+/// Used by the [Source] to determine the number of samples that were dropped on an overflow.
+pub trait RxClock: Send {
+    /// Hardware timestamp of the next sample that the streamer returns in nanoseconds, if
+    /// available
+    fn next_sample_ns(&mut self) -> Option<i64>;
+}
+
 /// Seify Source block
 pub struct Source<D: DeviceTrait + Clone> {
     channels: Vec<usize>,
     dev: Device<D>,
     streamer: Option<D::RxStreamer>,
     start_time: Option<i64>,
+    overflow: OverflowPolicy,
+    clock: Option<Box<dyn RxClock>>,
+    sample_rate: f64,
+    next_ns: Option<i64>,
+    dropped: Option<usize>,
+    fill: usize,
 }
 
 impl<D: DeviceTrait + Clone> Source<D> {
-    pub(super) fn new(dev: Device<D>, channels: Vec<usize>, start_time: Option<i64>) -> Block {
+    pub(super) fn new(
+        dev: Device<D>,
+        channels: Vec<usize>,
+        start_time: Option<i64>,
+        overflow: OverflowPolicy,
+        clock: Option<Box<dyn RxClock>>,
+    ) -> Block {
         assert!(!channels.is_empty());
 
         let mut siob = StreamIoBuilder::new();
@@ -56,6 +103,12 @@ impl<D: DeviceTrait + Clone> Source<D> {
                 dev,
                 start_time,
                 streamer: None,
+                overflow,
+                clock,
+                sample_rate: 0.0,
+                next_ns: None,
+                dropped: None,
+                fill: 0,
             },
         )
     }
@@ -70,6 +123,9 @@ impl<D: DeviceTrait + Clone> Source<D> {
     ) -> Result<Pmt> {
         let c: Config = p.try_into()?;
         c.apply(&self.dev, &self.channels, Rx)?;
+        if c.sample_rate.is_some() {
+            self.reset_clock()?;
+        }
         Ok(Pmt::Ok)
     }
 
@@ -133,8 +189,35 @@ impl<D: DeviceTrait + Clone> Source<D> {
                 _ => return Ok(Pmt::InvalidValue),
             };
         }
+        self.reset_clock()?;
         Ok(Pmt::Ok)
     }
+
+    /// Restart tracking the sample clock, e.g., after the sample rate changed.
+    fn reset_clock(&mut self) -> Result<()> {
+        self.sample_rate = self.dev.sample_rate(Rx, self.channels[0])?;
+        self.next_ns = None;
+        Ok(())
+    }
+
+    /// Timestamp of the next sample, as reported by the hardware clock.
+    fn now_ns(&mut self) -> Option<i64> {
+        self.clock.as_mut().and_then(|c| c.next_sample_ns())
+    }
+
+    /// Number of samples dropped since the last read, if the hardware clock is available.
+    fn gap(&mut self) -> Option<usize> {
+        let last = self.next_ns?;
+        let now = self.now_ns()?;
+        let gap = (now - last) as f64 * self.sample_rate / 1e9;
+        Some(gap.round().max(0.0) as usize)
+    }
+
+    fn tag_overflow(sio: &mut StreamIo, dropped: usize) {
+        for o in sio.outputs_mut() {
+            o.add_tag(0, Tag::NamedUsize(OVERFLOW_TAG.to_string(), dropped));
+        }
+    }
 }
 
 #[doc(hidden)]
@@ -158,14 +241,51 @@ impl<D: DeviceTrait + Clone> Kernel for Source<D> {
             return Ok(());
         }
 
+        if self.fill > 0 {
+            let len = std::cmp::min(n, self.fill);
+            for b in bufs.iter_mut() {
+                b[0..len].fill(Complex32::new(0.0, 0.0));
+            }
+            if let Some(dropped) = self.dropped.take() {
+                Self::tag_overflow(sio, dropped);
+            }
+            for i in 0..bufs.len() {
+                sio.output(i).produce(len);
+            }
+            self.fill -= len;
+            io.call_again = true;
+            return Ok(());
+        }
+
         match streamer.read(&mut bufs, 1_000_000) {
             Ok(len) => {
-                for i in 0..outs.len() {
+                if len > 0 {
+                    if let Some(dropped) = self.dropped.take() {
+                        Self::tag_overflow(sio, dropped);
+                    }
+                }
+                for i in 0..bufs.len() {
                     sio.output(i).produce(len);
                 }
+                self.next_ns = self.now_ns();
             }
             Err(seify::Error::Overflow) => {
                 warn!("Seify Source Overflow");
+                if self.overflow != OverflowPolicy::Warn {
+                    match self.gap() {
+                        Some(dropped) => {
+                            debug!("Seify Source: {} dropped samples", dropped);
+                            self.dropped = Some(self.dropped.unwrap_or(0) + dropped);
+                            if self.overflow == OverflowPolicy::ZeroFill {
+                                self.fill += dropped;
+                            }
+                        }
+                        None => {
+                            self.dropped.get_or_insert(0);
+                        }
+                    }
+                }
+                self.next_ns = self.now_ns();
             }
             Err(e) => {
                 error!("Seify Source Error: {:?}", e);
@@ -183,11 +303,13 @@ impl<D: DeviceTrait + Clone> Kernel for Source<D> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        self.reset_clock()?;
         self.streamer = Some(self.dev.rx_streamer(&self.channels)?);
         self.streamer
             .as_mut()
             .context("no stream")?
             .activate_at(self.start_time)?;
+        self.next_ns = self.now_ns();
 
         Ok(())
     }
@@ -0,0 +1,797 @@
+use once_cell::sync::Lazy;
+use seify::Args;
+use seify::DeviceTrait;
+use seify::Direction;
+use seify::Driver;
+use seify::Error;
+use seify::Range;
+use seify::RangeItem;
+use seify::RxStreamer;
+use seify::TxStreamer;
+use std::any::Any;
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::Weak;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::blocks::seify::RxClock;
+use crate::num_complex::Complex32;
+
+const MTU: usize = 8192;
+const MAX_QUEUED: usize = 1 << 22;
+
+/// State of the devices created from [`Args`], keyed by their configuration
+static DEVICES: Lazy<Mutex<HashMap<String, Weak<Mutex<Inner>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Virtual Seify device
+///
+/// Simulated device that does not require any hardware. It can be used with the
+/// [Source](super::Source) and [Sink](super::Sink) blocks, allowing to run flowgraphs in CI or on
+/// machines without an SDR.
+///
+/// The RX stream of the device is either
+/// - generated from a file with interleaved `f32` IQ samples in native byte order (`cf32`), or
+/// - a loopback of the samples that are transmitted through the TX stream of the same device.
+///   Since clones of the device share their state, the TX stream can be set up in another
+///   flowgraph. When nothing is transmitted, the device receives zeros. Samples are only looped
+///   back while the RX stream is active.
+///
+/// By default, the device throttles the streams to the configured sample rate to mimic real
+/// hardware. This can be disabled to process files as fast as possible.
+///
+/// The device can be created through the FutureSDR Seify builders with `driver=virtual` as
+/// arguments. Additional keys are `file=<path>`, `repeat=<bool>`, `throttle=<bool>`,
+/// `channels=<n>`, and `id=<name>`. Devices that are created from the same arguments share
+/// their state, i.e., a [Source](super::Source) and a [Sink](super::Sink) that are built from
+/// `driver=virtual` form a loopback. Use different `id`s to create independent devices.
+///
+/// Seify itself does not know the driver. Use [`enumerate`](super::enumerate) and
+/// [`device_from_args`](super::device_from_args) of this module in place of
+/// [`seify::enumerate`] and [`seify::Device::from_args`] to list the device and to resolve
+/// `driver=virtual`.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::seify::SourceBuilder;
+///
+/// let src = SourceBuilder::new()
+///     .args("driver=virtual,file=recording.cf32,throttle=false")?
+///     .sample_rate(1e6)
+///     .build()?;
+/// # Ok::<(), futuresdr::anyhow::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct VirtualDevice {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Clone, Debug)]
+struct ChannelConfig {
+    antenna: String,
+    agc: bool,
+    gain: f64,
+    freq: f64,
+    sample_rate: f64,
+}
+
+impl Default for ChannelConfig {
+    fn default() -> Self {
+        Self {
+            antenna: "RX/TX".to_string(),
+            agc: false,
+            gain: 0.0,
+            freq: 100e6,
+            sample_rate: 1e6,
+        }
+    }
+}
+
+enum Signal {
+    Loopback,
+    File {
+        samples: Vec<Complex32>,
+        repeat: bool,
+    },
+}
+
+struct Inner {
+    rx: Vec<ChannelConfig>,
+    tx: Vec<ChannelConfig>,
+    signal: Signal,
+    throttle: bool,
+    rx_active: bool,
+    loopback: Vec<VecDeque<Complex32>>,
+    /// time of the next RX sample in seconds since the stream was activated
+    rx_time: Option<f64>,
+    /// samples to drop with the next read, simulating an overflow
+    rx_overflow: usize,
+}
+
+impl Inner {
+    fn config(&self, direction: Direction, channel: usize) -> Result<&ChannelConfig, Error> {
+        match direction {
+            Direction::Rx => self.rx.get(channel),
+            Direction::Tx => self.tx.get(channel),
+        }
+        .ok_or(Error::ValueError)
+    }
+
+    fn config_mut(
+        &mut self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<&mut ChannelConfig, Error> {
+        match direction {
+            Direction::Rx => self.rx.get_mut(channel),
+            Direction::Tx => self.tx.get_mut(channel),
+        }
+        .ok_or(Error::ValueError)
+    }
+}
+
+impl VirtualDevice {
+    /// Create a virtual loopback device with one RX and one TX channel
+    pub fn new() -> Self {
+        Self::with_signal(Signal::Loopback, 1, true)
+    }
+
+    /// Create a virtual device that receives the samples of a `cf32` file
+    pub fn from_file(path: impl AsRef<std::path::Path>, repeat: bool) -> Result<Self, Error> {
+        let samples = Self::read_file(path)?;
+        Ok(Self::with_signal(Signal::File { samples, repeat }, 1, true))
+    }
+
+    /// Create a virtual device from Seify [`Args`]
+    ///
+    /// Supported keys are `file`, `repeat` (default: `true`), `throttle` (default: `true`),
+    /// `channels` (default: `1`), and `id` (default: empty). While a device exists, calls with
+    /// the same arguments return a device that shares its state.
+    pub fn from_args(args: &Args) -> Result<Self, Error> {
+        let channels = args.get::<usize>("channels").unwrap_or(1);
+        let throttle = args.get::<bool>("throttle").unwrap_or(true);
+        let repeat = args.get::<bool>("repeat").unwrap_or(true);
+        let file = args.get::<String>("file").ok();
+        let id = args.get::<String>("id").unwrap_or_default();
+
+        if channels == 0 {
+            return Err(Error::ValueError);
+        }
+
+        let key = format!("{id}|{file:?}|{repeat}|{throttle}|{channels}");
+        let mut devices = DEVICES.lock().unwrap();
+        devices.retain(|_, d| d.strong_count() > 0);
+        if let Some(inner) = devices.get(&key).and_then(Weak::upgrade) {
+            return Ok(Self { inner });
+        }
+
+        let signal = match file {
+            Some(f) => Signal::File {
+                samples: Self::read_file(f)?,
+                repeat,
+            },
+            None => Signal::Loopback,
+        };
+        let dev = Self::with_signal(signal, channels, throttle);
+        devices.insert(key, Arc::downgrade(&dev.inner));
+        Ok(dev)
+    }
+
+    /// Check if the [`Args`] request a virtual device, i.e., `driver=virtual`
+    pub fn matches(args: &Args) -> bool {
+        matches!(args.get::<String>("driver"), Ok(d) if d == "virtual")
+    }
+
+    /// Simulate an overflow, dropping `samples` samples
+    ///
+    /// The next read of the RX stream returns [`Error::Overflow`] and the samples are skipped.
+    /// The [clock](Self::rx_clock) of the device accounts for the dropped samples.
+    pub fn inject_overflow(&self, samples: usize) {
+        self.inner.lock().unwrap().rx_overflow += samples;
+    }
+
+    /// Hardware clock of the RX stream, to be used with
+    /// [`Builder::rx_clock`](super::Builder::rx_clock)
+    ///
+    /// The time starts at zero when the RX stream is activated.
+    pub fn rx_clock(&self) -> VirtualRxClock {
+        VirtualRxClock {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Enable or disable throttling of the streams to the sample rate
+    pub fn set_throttle(&self, throttle: bool) {
+        self.inner.lock().unwrap().throttle = throttle;
+    }
+
+    fn with_signal(signal: Signal, channels: usize, throttle: bool) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                rx: vec![ChannelConfig::default(); channels],
+                tx: vec![ChannelConfig::default(); channels],
+                signal,
+                throttle,
+                rx_active: false,
+                loopback: vec![VecDeque::new(); channels],
+                rx_time: None,
+                rx_overflow: 0,
+            })),
+        }
+    }
+
+    fn read_file(path: impl AsRef<std::path::Path>) -> Result<Vec<Complex32>, Error> {
+        let bytes = std::fs::read(path.as_ref())
+            .map_err(|e| Error::Misc(format!("cannot read {:?}: {e}", path.as_ref())))?;
+        Ok(bytes
+            .chunks_exact(8)
+            .map(|c| {
+                Complex32::new(
+                    f32::from_ne_bytes(c[0..4].try_into().unwrap()),
+                    f32::from_ne_bytes(c[4..8].try_into().unwrap()),
+                )
+            })
+            .collect())
+    }
+
+    fn check_channels(&self, direction: Direction, channels: &[usize]) -> Result<(), Error> {
+        let n = self.num_channels(direction)?;
+        if channels.is_empty() || channels.iter().any(|c| *c >= n) {
+            return Err(Error::ValueError);
+        }
+        Ok(())
+    }
+}
+
+impl Default for VirtualDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeviceTrait for VirtualDevice {
+    type RxStreamer = VirtualRxStreamer;
+    type TxStreamer = VirtualTxStreamer;
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn driver(&self) -> Driver {
+        Driver::Dummy
+    }
+
+    fn id(&self) -> Result<String, Error> {
+        Ok("virtual".to_string())
+    }
+
+    fn info(&self) -> Result<Args, Error> {
+        let mut args = Args::new();
+        args.set("driver", "virtual");
+        Ok(args)
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(match direction {
+            Direction::Rx => inner.rx.len(),
+            Direction::Tx => inner.tx.len(),
+        })
+    }
+
+    fn full_duplex(&self, _direction: Direction, _channel: usize) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn rx_streamer(&self, channels: &[usize], _args: Args) -> Result<Self::RxStreamer, Error> {
+        self.check_channels(Direction::Rx, channels)?;
+        Ok(VirtualRxStreamer {
+            inner: self.inner.clone(),
+            channels: channels.to_vec(),
+            active: false,
+            pos: 0,
+            pacer: Pacer::new(),
+        })
+    }
+
+    fn tx_streamer(&self, channels: &[usize], _args: Args) -> Result<Self::TxStreamer, Error> {
+        self.check_channels(Direction::Tx, channels)?;
+        Ok(VirtualTxStreamer {
+            inner: self.inner.clone(),
+            channels: channels.to_vec(),
+            active: false,
+            pacer: Pacer::new(),
+        })
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.antenna(direction, channel).map(|a| vec![a])
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.config(direction, channel)?.antenna.clone())
+    }
+
+    fn set_antenna(&self, direction: Direction, channel: usize, name: &str) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.config_mut(direction, channel)?.antenna = name.to_string();
+        Ok(())
+    }
+
+    fn gain_elements(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        let inner = self.inner.lock().unwrap();
+        inner.config(direction, channel)?;
+        Ok(vec!["VIRTUAL".to_string()])
+    }
+
+    fn supports_agc(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let inner = self.inner.lock().unwrap();
+        inner.config(direction, channel)?;
+        Ok(true)
+    }
+
+    fn enable_agc(&self, direction: Direction, channel: usize, agc: bool) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.config_mut(direction, channel)?.agc = agc;
+        Ok(())
+    }
+
+    fn agc(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.config(direction, channel)?.agc)
+    }
+
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        if !self.gain_range(direction, channel)?.contains(gain) {
+            return Err(Error::ValueError);
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.config_mut(direction, channel)?.gain = gain;
+        Ok(())
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<Option<f64>, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(Some(inner.config(direction, channel)?.gain))
+    }
+
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        let inner = self.inner.lock().unwrap();
+        inner.config(direction, channel)?;
+        Ok(Range::new(vec![RangeItem::Interval(0.0, 100.0)]))
+    }
+
+    fn set_gain_element(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+        gain: f64,
+    ) -> Result<(), Error> {
+        if name != "VIRTUAL" {
+            return Err(Error::ValueError);
+        }
+        self.set_gain(direction, channel, gain)
+    }
+
+    fn gain_element(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<Option<f64>, Error> {
+        if name != "VIRTUAL" {
+            return Err(Error::ValueError);
+        }
+        self.gain(direction, channel)
+    }
+
+    fn gain_element_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<Range, Error> {
+        if name != "VIRTUAL" {
+            return Err(Error::ValueError);
+        }
+        self.gain_range(direction, channel)
+    }
+
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        let inner = self.inner.lock().unwrap();
+        inner.config(direction, channel)?;
+        Ok(Range::new(vec![RangeItem::Interval(0.0, 6e9)]))
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.config(direction, channel)?.freq)
+    }
+
+    fn set_frequency(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        _args: Args,
+    ) -> Result<(), Error> {
+        if !self
+            .frequency_range(direction, channel)?
+            .contains(frequency)
+        {
+            return Err(Error::ValueError);
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.config_mut(direction, channel)?.freq = frequency;
+        Ok(())
+    }
+
+    fn frequency_components(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<String>, Error> {
+        let inner = self.inner.lock().unwrap();
+        inner.config(direction, channel)?;
+        Ok(vec!["RF".to_string()])
+    }
+
+    fn component_frequency_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<Range, Error> {
+        if name != "RF" {
+            return Err(Error::ValueError);
+        }
+        self.frequency_range(direction, channel)
+    }
+
+    fn component_frequency(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+    ) -> Result<f64, Error> {
+        if name != "RF" {
+            return Err(Error::ValueError);
+        }
+        self.frequency(direction, channel)
+    }
+
+    fn set_component_frequency(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: &str,
+        frequency: f64,
+    ) -> Result<(), Error> {
+        if name != "RF" {
+            return Err(Error::ValueError);
+        }
+        self.set_frequency(direction, channel, frequency, Args::new())
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let inner = self.inner.lock().unwrap();
+        Ok(inner.config(direction, channel)?.sample_rate)
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        if !self
+            .get_sample_rate_range(direction, channel)?
+            .contains(rate)
+        {
+            return Err(Error::ValueError);
+        }
+        let mut inner = self.inner.lock().unwrap();
+        inner.config_mut(direction, channel)?.sample_rate = rate;
+        Ok(())
+    }
+
+    fn get_sample_rate_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        let inner = self.inner.lock().unwrap();
+        inner.config(direction, channel)?;
+        Ok(Range::new(vec![RangeItem::Interval(1.0, 100e6)]))
+    }
+}
+
+/// Limit the stream to the sample rate of the device
+struct Pacer {
+    start: Instant,
+    rate: f64,
+    items: u64,
+}
+
+impl Pacer {
+    fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            rate: 0.0,
+            items: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.start = Instant::now();
+        self.items = 0;
+    }
+
+    /// Number of items that can be processed now, waiting at most `timeout_us`
+    fn available(&mut self, rate: f64, max: usize, timeout_us: i64) -> usize {
+        if rate != self.rate {
+            self.rate = rate;
+            self.reset();
+        }
+
+        let due = |p: &Self| {
+            let elapsed = p.start.elapsed().as_secs_f64();
+            ((elapsed * p.rate) as u64).saturating_sub(p.items) as usize
+        };
+
+        let n = due(self);
+        if n > 0 {
+            return n.min(max);
+        }
+
+        let wait = Duration::from_secs_f64(max.min(MTU) as f64 / rate)
+            .min(Duration::from_micros(timeout_us.max(0) as u64));
+        std::thread::sleep(wait);
+        due(self).min(max)
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.items += n as u64;
+    }
+}
+
+/// RX streamer of the [`VirtualDevice`]
+pub struct VirtualRxStreamer {
+    inner: Arc<Mutex<Inner>>,
+    channels: Vec<usize>,
+    active: bool,
+    pos: usize,
+    pacer: Pacer,
+}
+
+impl RxStreamer for VirtualRxStreamer {
+    fn mtu(&self) -> Result<usize, Error> {
+        Ok(MTU)
+    }
+
+    fn activate(&mut self) -> Result<(), Error> {
+        self.activate_at(None)
+    }
+
+    fn activate_at(&mut self, _time_ns: Option<i64>) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.rx_active = true;
+        inner.rx_time = Some(0.0);
+        for c in &self.channels {
+            inner.loopback[*c].clear();
+        }
+        self.active = true;
+        self.pacer.reset();
+        Ok(())
+    }
+
+    fn deactivate(&mut self) -> Result<(), Error> {
+        self.deactivate_at(None)
+    }
+
+    fn deactivate_at(&mut self, _time_ns: Option<i64>) -> Result<(), Error> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.rx_active = false;
+        inner.rx_time = None;
+        self.active = false;
+        Ok(())
+    }
+
+    fn read(&mut self, buffers: &mut [&mut [Complex32]], timeout_us: i64) -> Result<usize, Error> {
+        if !self.active {
+            return Err(Error::Misc("stream not activated".to_string()));
+        }
+
+        let mut n = buffers.iter().map(|b| b.len()).min().unwrap_or(0).min(MTU);
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let (throttle, rate) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.throttle, inner.rx[self.channels[0]].sample_rate)
+        };
+        if throttle {
+            n = self.pacer.available(rate, n, timeout_us);
+            if n == 0 {
+                return Ok(0);
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.rx_overflow > 0 {
+            let dropped = std::mem::take(&mut inner.rx_overflow);
+            if let Signal::File { samples, repeat } = &inner.signal {
+                self.pos += dropped;
+                if *repeat && !samples.is_empty() {
+                    self.pos %= samples.len();
+                }
+            }
+            for c in &self.channels {
+                let queue = &mut inner.loopback[*c];
+                queue.drain(0..dropped.min(queue.len()));
+            }
+            if let Some(t) = inner.rx_time.as_mut() {
+                *t += dropped as f64 / rate;
+            }
+            self.pacer.consume(dropped);
+            return Err(Error::Overflow);
+        }
+
+        let Inner {
+            signal, loopback, ..
+        } = &mut *inner;
+
+        match signal {
+            Signal::File { samples, repeat } => {
+                if samples.is_empty() || (!*repeat && self.pos >= samples.len()) {
+                    return Err(Error::Misc("end of file".to_string()));
+                }
+                let mut produced = 0;
+                while produced < n {
+                    if self.pos >= samples.len() {
+                        if !*repeat {
+                            break;
+                        }
+                        self.pos = 0;
+                    }
+                    let k = (n - produced).min(samples.len() - self.pos);
+                    for b in buffers.iter_mut() {
+                        b[produced..produced + k].copy_from_slice(&samples[self.pos..self.pos + k]);
+                    }
+                    self.pos += k;
+                    produced += k;
+                }
+                n = produced;
+            }
+            Signal::Loopback => {
+                for (b, c) in buffers.iter_mut().zip(self.channels.iter()) {
+                    let queue = &mut loopback[*c];
+                    let k = n.min(queue.len());
+                    for (o, i) in b[0..k].iter_mut().zip(queue.drain(0..k)) {
+                        *o = i;
+                    }
+                    b[k..n].fill(Complex32::new(0.0, 0.0));
+                }
+            }
+        }
+
+        if let Some(t) = inner.rx_time.as_mut() {
+            *t += n as f64 / rate;
+        }
+        self.pacer.consume(n);
+        Ok(n)
+    }
+}
+
+/// RX clock of the [`VirtualDevice`], see [`VirtualDevice::rx_clock`]
+pub struct VirtualRxClock {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl RxClock for VirtualRxClock {
+    fn next_sample_ns(&mut self) -> Option<i64> {
+        self.inner
+            .lock()
+            .unwrap()
+            .rx_time
+            .map(|t| (t * 1e9).round() as i64)
+    }
+}
+
+/// TX streamer of the [`VirtualDevice`]
+pub struct VirtualTxStreamer {
+    inner: Arc<Mutex<Inner>>,
+    channels: Vec<usize>,
+    active: bool,
+    pacer: Pacer,
+}
+
+impl TxStreamer for VirtualTxStreamer {
+    fn mtu(&self) -> Result<usize, Error> {
+        Ok(MTU)
+    }
+
+    fn activate(&mut self) -> Result<(), Error> {
+        self.activate_at(None)
+    }
+
+    fn activate_at(&mut self, _time_ns: Option<i64>) -> Result<(), Error> {
+        self.active = true;
+        self.pacer.reset();
+        Ok(())
+    }
+
+    fn deactivate(&mut self) -> Result<(), Error> {
+        self.deactivate_at(None)
+    }
+
+    fn deactivate_at(&mut self, _time_ns: Option<i64>) -> Result<(), Error> {
+        self.active = false;
+        Ok(())
+    }
+
+    fn write(
+        &mut self,
+        buffers: &[&[Complex32]],
+        _at_ns: Option<i64>,
+        _end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        if !self.active {
+            return Err(Error::Misc("stream not activated".to_string()));
+        }
+
+        let mut n = buffers.iter().map(|b| b.len()).min().unwrap_or(0).min(MTU);
+        if n == 0 {
+            return Ok(0);
+        }
+
+        let (throttle, rate) = {
+            let inner = self.inner.lock().unwrap();
+            (inner.throttle, inner.tx[self.channels[0]].sample_rate)
+        };
+        if throttle {
+            n = self.pacer.available(rate, n, timeout_us);
+            if n == 0 {
+                return Ok(0);
+            }
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.rx_active {
+            for (b, c) in buffers.iter().zip(self.channels.iter()) {
+                let queue = &mut inner.loopback[*c];
+                queue.extend(b[0..n].iter());
+                if queue.len() > MAX_QUEUED {
+                    let excess = queue.len() - MAX_QUEUED;
+                    queue.drain(0..excess);
+                }
+            }
+        }
+
+        self.pacer.consume(n);
+        Ok(n)
+    }
+
+    fn write_all(
+        &mut self,
+        buffers: &[&[Complex32]],
+        at_ns: Option<i64>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<(), Error> {
+        let len = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        let mut written = 0;
+        while written < len {
+            let bufs: Vec<&[Complex32]> = buffers.iter().map(|b| &b[written..len]).collect();
+            written += self.write(&bufs, at_ns, end_burst && written + MTU >= len, timeout_us)?;
+        }
+        Ok(())
+    }
+}
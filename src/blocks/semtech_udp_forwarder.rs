@@ -0,0 +1,657 @@
+use async_io::Async;
+use async_io::Timer;
+use futures_lite::future;
+use rand::Rng;
+use serde_json::json;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::net::ToSocketAddrs;
+use std::net::UdpSocket;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+const VERSION: u8 = 2;
+const PUSH_DATA: u8 = 0;
+const PUSH_ACK: u8 = 1;
+const PULL_DATA: u8 = 2;
+const PULL_RESP: u8 = 3;
+const PULL_ACK: u8 = 4;
+const TX_ACK: u8 = 5;
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Packet forwarder statistics, reset with every `stat` report
+#[derive(Default)]
+struct Stats {
+    rxnr: u32,
+    rxok: u32,
+    rxfw: u32,
+    pushed: u32,
+    acked: u32,
+    dwnb: u32,
+    txnr: u32,
+}
+
+/// Forward LoRa frames to a network server, using the Semtech UDP packet forwarder protocol.
+pub struct SemtechUdpForwarder {
+    server: SocketAddr,
+    gateway_eui: u64,
+    frequency: f64,
+    spreading_factor: usize,
+    bandwidth: f64,
+    coding_rate: usize,
+    keepalive: Duration,
+    stat_interval: Duration,
+    location: Option<(f64, f64, f64)>,
+    socket: Option<Arc<Async<UdpSocket>>>,
+    token: u16,
+    start: Instant,
+    t_pull: Instant,
+    t_stat: Instant,
+    stats: Stats,
+    buf: Vec<u8>,
+}
+
+impl SemtechUdpForwarder {
+    fn new(builder: SemtechUdpForwarderBuilder, server: SocketAddr) -> Block {
+        let now = Instant::now();
+        Block::new(
+            BlockMetaBuilder::new("SemtechUdpForwarder").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("rx", Self::rx_handler)
+                .add_output("tx")
+                .build(),
+            SemtechUdpForwarder {
+                server,
+                gateway_eui: builder.gateway_eui,
+                frequency: builder.frequency,
+                spreading_factor: builder.spreading_factor,
+                bandwidth: builder.bandwidth,
+                coding_rate: builder.coding_rate,
+                keepalive: builder.keepalive,
+                stat_interval: builder.stat_interval,
+                location: builder.location,
+                socket: None,
+                token: rand::thread_rng().gen(),
+                start: now,
+                t_pull: now,
+                t_stat: now + builder.stat_interval,
+                stats: Stats::default(),
+                buf: vec![0; 65536],
+            },
+        )
+    }
+
+    /// Header with a new token and, for upstream datagrams, the gateway EUI
+    fn header(&mut self, kind: u8) -> Vec<u8> {
+        self.token = self.token.wrapping_add(1);
+        let mut h = vec![VERSION];
+        h.extend_from_slice(&self.token.to_be_bytes());
+        h.push(kind);
+        h.extend_from_slice(&self.gateway_eui.to_be_bytes());
+        h
+    }
+
+    async fn send(&self, datagram: &[u8]) {
+        if let Some(s) = self.socket.as_ref() {
+            if let Err(e) = s.send(datagram).await {
+                warn!("SemtechUdpForwarder: could not send datagram: {e:?}");
+            }
+        }
+    }
+
+    async fn push(&mut self, v: Value) {
+        let mut d = self.header(PUSH_DATA);
+        d.extend_from_slice(v.to_string().as_bytes());
+        self.stats.pushed += 1;
+        self.send(&d).await;
+    }
+
+    /// `rxpk` object of a frame and whether its CRC is valid
+    fn rxpk(&self, p: Pmt) -> Option<(Value, bool)> {
+        let mut m = match p {
+            Pmt::Blob(b) => HashMap::from([("payload".to_string(), Pmt::Blob(b))]),
+            Pmt::MapStrPmt(m) => m,
+            _ => return None,
+        };
+        let payload = match m.remove("payload") {
+            Some(Pmt::Blob(b)) => b,
+            _ => return None,
+        };
+        let get = |k: &str| -> Option<f64> { m.get(k)?.clone().try_into().ok() };
+        let get_usize = |k: &str| -> Option<usize> { m.get(k)?.clone().try_into().ok() };
+        let frequency = get("freq").unwrap_or(self.frequency);
+        let sf = get_usize("sf").unwrap_or(self.spreading_factor);
+        let bw = get("bw").unwrap_or(self.bandwidth);
+        let cr = get_usize("cr").unwrap_or(self.coding_rate);
+        let crc = !matches!(m.get("crc"), Some(Pmt::Bool(false)));
+        let tmst = get("tmst").map_or_else(
+            || self.start.elapsed().as_micros() as u32,
+            |v| v as u64 as u32,
+        );
+        let time = match m.get("time") {
+            Some(Pmt::String(s)) => s.clone(),
+            _ => {
+                let ((y, mo, d, h, mi, s), us) = utc(now());
+                format!("{y:04}-{mo:02}-{d:02}T{h:02}:{mi:02}:{s:02}.{us:06}Z")
+            }
+        };
+
+        let stat = if crc { 1 } else { -1 };
+        let mut rxpk = json!({
+            "time": time,
+            "tmst": tmst,
+            "chan": 0,
+            "rfch": 0,
+            "freq": frequency / 1e6,
+            "stat": stat,
+            "modu": "LORA",
+            "datr": format!("SF{}BW{}", sf, (bw / 1e3).round() as u64),
+            "codr": format!("4/{}", cr + 4),
+            "size": payload.len(),
+            "data": base64_encode(&payload),
+        });
+        if let Some(rssi) = get("rssi") {
+            rxpk["rssi"] = json!(rssi.round() as i64);
+        }
+        if let Some(snr) = get("snr") {
+            rxpk["lsnr"] = json!((snr * 10.0).round() / 10.0);
+        }
+        Some((rxpk, crc))
+    }
+
+    fn stat(&mut self) -> Value {
+        let s = std::mem::take(&mut self.stats);
+        let ackr = if s.pushed > 0 {
+            (1000.0 * s.acked as f64 / s.pushed as f64).round() / 10.0
+        } else {
+            0.0
+        };
+        let ((y, mo, d, h, mi, sec), _) = utc(now());
+        let mut stat = json!({
+            "time": format!("{y:04}-{mo:02}-{d:02} {h:02}:{mi:02}:{sec:02} GMT"),
+            "rxnr": s.rxnr,
+            "rxok": s.rxok,
+            "rxfw": s.rxfw,
+            "ackr": ackr,
+            "dwnb": s.dwnb,
+            "txnr": s.txnr,
+        });
+        if let Some((lat, lon, alt)) = self.location {
+            stat["lati"] = json!(lat);
+            stat["long"] = json!(lon);
+            stat["alti"] = json!(alt.round() as i64);
+        }
+        json!({ "stat": stat })
+    }
+
+    async fn datagram(&mut self, d: &[u8], mio: &mut MessageIo<Self>) {
+        if d.len() < 4 || d[0] != VERSION {
+            debug!("SemtechUdpForwarder: ignoring invalid datagram");
+            return;
+        }
+        match d[3] {
+            PUSH_ACK => self.stats.acked += 1,
+            PULL_ACK => debug!("SemtechUdpForwarder: pull ack"),
+            PULL_RESP => {
+                self.stats.dwnb += 1;
+                let downlink = serde_json::from_slice::<Value>(&d[4..])
+                    .ok()
+                    .as_ref()
+                    .and_then(txpk);
+                match downlink {
+                    Some(p) => {
+                        let mut ack = vec![VERSION, d[1], d[2], TX_ACK];
+                        ack.extend_from_slice(&self.gateway_eui.to_be_bytes());
+                        ack.extend_from_slice(
+                            json!({"txpk_ack": {"error": "NONE"}})
+                                .to_string()
+                                .as_bytes(),
+                        );
+                        self.send(&ack).await;
+                        self.stats.txnr += 1;
+                        mio.post(0, p).await;
+                    }
+                    None => warn!("SemtechUdpForwarder: invalid downlink"),
+                }
+            }
+            t => debug!("SemtechUdpForwarder: ignoring datagram of type {t}"),
+        }
+    }
+
+    #[message_handler]
+    async fn rx_handler(
+        &mut self,
+        io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if matches!(p, Pmt::Finished) {
+            io.finished = true;
+            return Ok(Pmt::Ok);
+        }
+        match self.rxpk(p) {
+            Some((rxpk, crc)) => {
+                self.stats.rxnr += 1;
+                if crc {
+                    self.stats.rxok += 1;
+                }
+                self.push(json!({ "rxpk": [rxpk] })).await;
+                self.stats.rxfw += 1;
+                Ok(Pmt::Ok)
+            }
+            None => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for SemtechUdpForwarder {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let socket = self.socket.clone().context("no socket")?;
+
+        loop {
+            match socket.get_ref().recv(&mut self.buf) {
+                Ok(n) => {
+                    let d = self.buf[..n].to_vec();
+                    self.datagram(&d, mio).await;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => {
+                    debug!("SemtechUdpForwarder: receive error {e:?}");
+                    break;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        if now >= self.t_pull {
+            let h = self.header(PULL_DATA);
+            self.send(&h).await;
+            self.t_pull = now + self.keepalive;
+        }
+        if now >= self.t_stat {
+            let stat = self.stat();
+            self.push(stat).await;
+            self.t_stat = now + self.stat_interval;
+        }
+
+        let deadline = std::cmp::min(self.t_pull, self.t_stat);
+        io.block_on(async move {
+            future::or(
+                async {
+                    let _ = socket.readable().await;
+                },
+                async {
+                    Timer::at(deadline).await;
+                },
+            )
+            .await;
+        });
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let bind = if self.server.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = Async::<UdpSocket>::bind(bind.parse::<SocketAddr>()?)?;
+        socket.get_ref().connect(self.server)?;
+        self.socket = Some(Arc::new(socket));
+        self.start = Instant::now();
+        self.t_pull = self.start;
+        self.t_stat = self.start + self.stat_interval;
+        Ok(())
+    }
+}
+
+/// Downlink of a `PULL_RESP` as [`Pmt::MapStrPmt`]
+fn txpk(v: &Value) -> Option<Pmt> {
+    let t = v.get("txpk")?;
+    if t.get("modu").and_then(Value::as_str).unwrap_or("LORA") != "LORA" {
+        return None;
+    }
+    let flag = |k: &str| Pmt::Bool(t.get(k).and_then(Value::as_bool).unwrap_or(false));
+    let mut m = HashMap::from([
+        (
+            "payload".to_string(),
+            Pmt::Blob(base64_decode(t.get("data")?.as_str()?)?),
+        ),
+        ("freq".to_string(), Pmt::F64(t.get("freq")?.as_f64()? * 1e6)),
+        ("immediate".to_string(), flag("imme")),
+        ("invert_iq".to_string(), flag("ipol")),
+    ]);
+    if let Some((sf, bw)) = t
+        .get("datr")
+        .and_then(Value::as_str)
+        .and_then(|d| d.strip_prefix("SF"))
+        .and_then(|d| d.split_once("BW"))
+    {
+        m.insert("sf".to_string(), Pmt::Usize(sf.parse().ok()?));
+        m.insert("bw".to_string(), Pmt::F64(bw.parse::<f64>().ok()? * 1e3));
+    }
+    if let Some(cr) = t
+        .get("codr")
+        .and_then(Value::as_str)
+        .and_then(|c| c.strip_prefix("4/"))
+    {
+        let cr = cr.parse::<usize>().ok()?.checked_sub(4)?;
+        if !(1..=4).contains(&cr) {
+            return None;
+        }
+        m.insert("cr".to_string(), Pmt::Usize(cr));
+    }
+    if let Some(p) = t.get("powe").and_then(Value::as_f64) {
+        m.insert("power".to_string(), Pmt::F64(p));
+    }
+    if let Some(tmst) = t.get("tmst").and_then(Value::as_u64) {
+        m.insert("tmst".to_string(), Pmt::U64(tmst));
+    }
+    Some(Pmt::MapStrPmt(m))
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut s = String::with_capacity((data.len() + 2) / 3 * 4);
+    for c in data.chunks(3) {
+        let n = ((c[0] as u32) << 16)
+            | ((*c.get(1).unwrap_or(&0) as u32) << 8)
+            | *c.get(2).unwrap_or(&0) as u32;
+        for i in 0..4 {
+            if i <= c.len() {
+                s.push(BASE64[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                s.push('=');
+            }
+        }
+    }
+    s
+}
+
+/// Decode base64 with optional padding, returning `None` for invalid input
+fn base64_decode(s: &str) -> Option<Vec<u8>> {
+    let data = s.trim_end_matches('=');
+    let padding = s.len() - data.len();
+    if padding > 2 || data.len() % 4 == 1 || (padding > 0 && s.len() % 4 != 0) {
+        return None;
+    }
+    let mut out = Vec::with_capacity(s.len() / 4 * 3);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in data.bytes() {
+        acc = (acc << 6) | BASE64.iter().position(|b| *b == c)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn now() -> Duration {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+}
+
+/// UTC date and time of a Unix timestamp as year, month, day, hour, minute, second, and
+/// microseconds
+fn utc(t: Duration) -> ((i64, u32, u32, u32, u32, u32), u32) {
+    let secs = t.as_secs();
+    let rem = secs % 86400;
+    // civil from days, see http://howardhinnant.github.io/date_algorithms.html
+    let z = (secs / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        (
+            year,
+            month as u32,
+            day as u32,
+            (rem / 3600) as u32,
+            (rem / 60 % 60) as u32,
+            (rem % 60) as u32,
+        ),
+        t.subsec_micros(),
+    )
+}
+
+/// Build a [SemtechUdpForwarder].
+///
+/// Forwards received frames to a LoRaWAN network server, e.g., ChirpStack or The Things
+/// Network, using the UDP protocol (version 2) of the Semtech packet forwarder. Together with a
+/// LoRa receiver, this makes a single-channel gateway. Frames are pushed as `rxpk`, keepalives
+/// (`PULL_DATA`) are sent periodically to receive downlinks, and gateway statistics are reported
+/// as `stat`.
+///
+/// Frames are radio metadata and a payload. Metadata that is not part of a frame, i.e., the
+/// frequency and modulation, default to the configuration of the builder.
+///
+/// # Inputs
+///
+/// **Message** `rx`: Received frame as payload ([`Pmt::Blob`]) or [`Pmt::MapStrPmt`] with the
+/// `payload` ([`Pmt::Blob`]) and optionally the spreading factor `sf` and coding rate `cr` (1 to
+/// 4 for 4/5 to 4/8) as [`Pmt::Usize`], the frequency `freq` in Hz, bandwidth `bw` in Hz, `snr`
+/// in dB, `rssi` in dBm, and receive timestamp `tmst` in microseconds (numbers that convert to
+/// `f64`, e.g., [`Pmt::F64`] or [`Pmt::U32`]), UTC `time` (ISO 8601 [`Pmt::String`]), and `crc`
+/// ([`Pmt::Bool`], `false` for frames with a CRC error). Without `tmst`, the time since the start
+/// of the block is used.
+///
+/// # Outputs
+///
+/// **Message** `tx`: Downlinks of the network server as [`Pmt::MapStrPmt`] with the `payload`
+/// ([`Pmt::Blob`]), `freq` in Hz ([`Pmt::F64`]), `immediate` and `invert_iq` ([`Pmt::Bool`]),
+/// and, if given, `sf` and `cr` ([`Pmt::Usize`]), `bw` in Hz and `power` in dBm
+/// ([`Pmt::F64`]), and the transmit timestamp `tmst` ([`Pmt::U64`]).
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SemtechUdpForwarderBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let forwarder = fg.add_block(
+///     SemtechUdpForwarderBuilder::new("localhost:1700", 0x0123_45ff_fe67_89ab)
+///         .frequency(868.1e6)
+///         .spreading_factor(7)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct SemtechUdpForwarderBuilder {
+    server: String,
+    gateway_eui: u64,
+    frequency: f64,
+    spreading_factor: usize,
+    bandwidth: f64,
+    coding_rate: usize,
+    keepalive: Duration,
+    stat_interval: Duration,
+    location: Option<(f64, f64, f64)>,
+}
+
+impl SemtechUdpForwarderBuilder {
+    /// Create SemtechUdpForwarder builder
+    ///
+    /// ## Parameter
+    /// - `server`: UDP socket address of the network server, e.g., `localhost:1700`
+    /// - `gateway_eui`: EUI-64 that identifies the gateway at the network server
+    pub fn new(server: impl Into<String>, gateway_eui: u64) -> SemtechUdpForwarderBuilder {
+        SemtechUdpForwarderBuilder {
+            server: server.into(),
+            gateway_eui,
+            frequency: 868.1e6,
+            spreading_factor: 7,
+            bandwidth: 125e3,
+            coding_rate: 1,
+            keepalive: Duration::from_secs(10),
+            stat_interval: Duration::from_secs(30),
+            location: None,
+        }
+    }
+    /// Receive frequency in Hz (default: 868.1 MHz)
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> SemtechUdpForwarderBuilder {
+        self.frequency = frequency;
+        self
+    }
+    /// Spreading factor (default: 7)
+    #[must_use]
+    pub fn spreading_factor(mut self, sf: usize) -> SemtechUdpForwarderBuilder {
+        self.spreading_factor = sf;
+        self
+    }
+    /// Bandwidth in Hz (default: 125 kHz)
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f64) -> SemtechUdpForwarderBuilder {
+        self.bandwidth = bandwidth;
+        self
+    }
+    /// Coding rate, 1 to 4 for 4/5 to 4/8 (default: 1)
+    #[must_use]
+    pub fn coding_rate(mut self, cr: usize) -> SemtechUdpForwarderBuilder {
+        self.coding_rate = cr;
+        self
+    }
+    /// Interval of the keepalives (default: 10 s)
+    #[must_use]
+    pub fn keepalive(mut self, interval: Duration) -> SemtechUdpForwarderBuilder {
+        self.keepalive = interval;
+        self
+    }
+    /// Interval of the statistics reports (default: 30 s)
+    #[must_use]
+    pub fn stat_interval(mut self, interval: Duration) -> SemtechUdpForwarderBuilder {
+        self.stat_interval = interval;
+        self
+    }
+    /// Gateway location, reported with the statistics, as latitude and longitude in degrees
+    /// and altitude in meters
+    #[must_use]
+    pub fn location(mut self, lat: f64, lon: f64, alt: f64) -> SemtechUdpForwarderBuilder {
+        self.location = Some((lat, lon, alt));
+        self
+    }
+    /// Build SemtechUdpForwarder block
+    pub fn build(self) -> Result<Block> {
+        if !(5..=12).contains(&self.spreading_factor) {
+            bail!("spreading factor has to be in [5, 12]");
+        }
+        if !(1..=4).contains(&self.coding_rate) {
+            bail!("coding rate has to be in [1, 4]");
+        }
+        if !(self.bandwidth > 0.0 && self.bandwidth.is_finite()) {
+            bail!("bandwidth has to be positive");
+        }
+        if self.keepalive.is_zero() || self.stat_interval.is_zero() {
+            bail!("intervals have to be positive");
+        }
+        let server = self
+            .server
+            .to_socket_addrs()
+            .with_context(|| format!("could not resolve {}", self.server))?
+            .next()
+            .context("no socket address")?;
+        Ok(SemtechUdpForwarder::new(self, server))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base64() {
+        let cases: [(&[u8], &str); 6] = [
+            (b"", ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"hello", "aGVsbG8="),
+            (&[0xfb, 0xff], "+/8="),
+        ];
+        for (data, encoded) in cases {
+            assert_eq!(base64_encode(data), encoded);
+            assert_eq!(base64_decode(encoded).as_deref(), Some(data));
+        }
+        // padding is optional
+        assert_eq!(base64_decode("Zg").as_deref(), Some(&b"f"[..]));
+        assert_eq!(base64_decode("Zm8").as_deref(), Some(&b"fo"[..]));
+
+        for invalid in [
+            "Z", "Zg=", "Zg===", "Z===", "Zg==Zg==", "Zm9v=", "Zm-v", "Zm9v\n", "Zm 9v",
+        ] {
+            assert_eq!(base64_decode(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn date() {
+        let t = |s| utc(Duration::from_secs(s)).0;
+        assert_eq!(t(0), (1970, 1, 1, 0, 0, 0));
+        assert_eq!(t(951_782_400), (2000, 2, 29, 0, 0, 0));
+        assert_eq!(t(1_234_567_890), (2009, 2, 13, 23, 31, 30));
+        assert_eq!(t(1_709_251_199), (2024, 2, 29, 23, 59, 59));
+        assert_eq!(t(4_107_542_400), (2100, 3, 1, 0, 0, 0));
+        assert_eq!(utc(Duration::from_micros(1_500_001)).1, 500_001);
+    }
+
+    #[test]
+    fn coding_rate() {
+        let cr = |codr: &str| {
+            let v = json!({"txpk": {"freq": 869.525, "data": "AQ==", "codr": codr}});
+            match txpk(&v) {
+                Some(Pmt::MapStrPmt(m)) => m.get("cr").cloned(),
+                _ => None,
+            }
+        };
+        assert_eq!(cr("4/5"), Some(Pmt::Usize(1)));
+        assert_eq!(cr("4/8"), Some(Pmt::Usize(4)));
+        assert_eq!(cr("4/3"), None);
+        assert_eq!(cr("4/4"), None);
+        assert_eq!(cr("4/9"), None);
+        assert_eq!(cr("4/x"), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! [SigMF](https://github.com/sigmf/SigMF) recording and playback
+//!
+//! A recording consists of a `<name>.sigmf-meta` file with JSON metadata and a
+//! `<name>.sigmf-data` file with the samples. Capture segments and annotations of the metadata
+//! are mapped to stream tags:
+//!
+//! - `Tag::NamedAny("sigmf:capture", Box<SigmfCapture>)` at the first sample of a capture
+//!   segment.
+//! - `Tag::NamedAny("sigmf:annotation", Box<SigmfAnnotation>)` at the first sample of an
+//!   annotation.
+//!
+//! The [`SigmfSource`] outputs these tags, and the [`SigmfSink`] records them, using the index of
+//! the tagged sample as `core:sample_start`. Only single-channel recordings are supported.
+use serde::Deserialize;
+use serde::Serialize;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::num_complex::Complex32;
+
+mod sink;
+pub use sink::{SigmfSink, SigmfSinkBuilder};
+mod source;
+pub use source::{SigmfSource, SigmfSourceBuilder};
+
+/// SigMF version written by the [`SigmfSink`]
+pub const SIGMF_VERSION: &str = "1.0.0";
+
+/// Tag name of capture segments
+pub const CAPTURE_TAG: &str = "sigmf:capture";
+/// Tag name of annotations
+pub const ANNOTATION_TAG: &str = "sigmf:annotation";
+
+type Extra = serde_json::Map<String, serde_json::Value>;
+
+/// SigMF metadata
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SigmfMeta {
+    /// Global information
+    pub global: SigmfGlobal,
+    /// Capture segments
+    #[serde(default)]
+    pub captures: Vec<SigmfCapture>,
+    /// Annotations
+    #[serde(default)]
+    pub annotations: Vec<SigmfAnnotation>,
+}
+
+impl SigmfMeta {
+    /// Read metadata from a `.sigmf-meta` file
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = meta_path(path.as_ref());
+        let s = std::fs::read_to_string(&path)
+            .with_context(|| format!("SigMF: cannot read {}", path.display()))?;
+        serde_json::from_str(&s).with_context(|| format!("SigMF: invalid {}", path.display()))
+    }
+
+    /// Write metadata to a `.sigmf-meta` file
+    pub fn to_file(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = meta_path(path.as_ref());
+        std::fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("SigMF: cannot write {}", path.display()))
+    }
+}
+
+/// Global information of a recording
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SigmfGlobal {
+    /// Sample format, e.g., `cf32_le`
+    #[serde(rename = "core:datatype")]
+    pub datatype: String,
+    /// SigMF version
+    #[serde(rename = "core:version")]
+    pub version: String,
+    /// Sample rate in Hz
+    #[serde(rename = "core:sample_rate", skip_serializing_if = "Option::is_none")]
+    pub sample_rate: Option<f64>,
+    /// Number of interleaved channels
+    #[serde(rename = "core:num_channels", skip_serializing_if = "Option::is_none")]
+    pub num_channels: Option<u64>,
+    /// Description
+    #[serde(rename = "core:description", skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Author
+    #[serde(rename = "core:author", skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Recording hardware
+    #[serde(rename = "core:hw", skip_serializing_if = "Option::is_none")]
+    pub hw: Option<String>,
+    /// Recording software
+    #[serde(rename = "core:recorder", skip_serializing_if = "Option::is_none")]
+    pub recorder: Option<String>,
+    /// Other fields, e.g., of extensions
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+impl Default for SigmfGlobal {
+    fn default() -> Self {
+        Self {
+            datatype: SigmfDatatype::CF32_LE.to_string(),
+            version: SIGMF_VERSION.to_string(),
+            sample_rate: None,
+            num_channels: None,
+            description: None,
+            author: None,
+            hw: None,
+            recorder: Some("FutureSDR".to_string()),
+            extra: Extra::new(),
+        }
+    }
+}
+
+/// Capture segment
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigmfCapture {
+    /// Index of the first sample of the segment
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    /// Center frequency in Hz
+    #[serde(rename = "core:frequency", skip_serializing_if = "Option::is_none")]
+    pub frequency: Option<f64>,
+    /// ISO 8601 timestamp of the first sample
+    #[serde(rename = "core:datetime", skip_serializing_if = "Option::is_none")]
+    pub datetime: Option<String>,
+    /// Other fields, e.g., of extensions
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Annotation of a range of samples
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SigmfAnnotation {
+    /// Index of the first annotated sample
+    #[serde(rename = "core:sample_start")]
+    pub sample_start: u64,
+    /// Number of annotated samples
+    #[serde(rename = "core:sample_count", skip_serializing_if = "Option::is_none")]
+    pub sample_count: Option<u64>,
+    /// Lower edge of the annotated signal in Hz
+    #[serde(
+        rename = "core:freq_lower_edge",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub freq_lower_edge: Option<f64>,
+    /// Upper edge of the annotated signal in Hz
+    #[serde(
+        rename = "core:freq_upper_edge",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub freq_upper_edge: Option<f64>,
+    /// Short label
+    #[serde(rename = "core:label", skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    /// Comment
+    #[serde(rename = "core:comment", skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Other fields, e.g., of extensions
+    #[serde(flatten)]
+    pub extra: Extra,
+}
+
+/// Format of the components of a sample
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SigmfFormat {
+    /// 64-bit float
+    F64,
+    /// 32-bit float
+    F32,
+    /// 32-bit signed integer
+    I32,
+    /// 16-bit signed integer
+    I16,
+    /// 8-bit signed integer
+    I8,
+    /// 32-bit unsigned integer
+    U32,
+    /// 16-bit unsigned integer
+    U16,
+    /// 8-bit unsigned integer
+    U8,
+}
+
+impl SigmfFormat {
+    fn size(&self) -> usize {
+        match self {
+            SigmfFormat::F64 => 8,
+            SigmfFormat::F32 | SigmfFormat::I32 | SigmfFormat::U32 => 4,
+            SigmfFormat::I16 | SigmfFormat::U16 => 2,
+            SigmfFormat::I8 | SigmfFormat::U8 => 1,
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SigmfFormat::F64 => "f64",
+            SigmfFormat::F32 => "f32",
+            SigmfFormat::I32 => "i32",
+            SigmfFormat::I16 => "i16",
+            SigmfFormat::I8 => "i8",
+            SigmfFormat::U32 => "u32",
+            SigmfFormat::U16 => "u16",
+            SigmfFormat::U8 => "u8",
+        }
+    }
+}
+
+/// SigMF datatype, e.g., `cf32_le` or `ci16_le`
+///
+/// Integers are scaled to `[-1, 1)`, unsigned integers are offset by half of their range.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigmfDatatype {
+    complex: bool,
+    format: SigmfFormat,
+    little_endian: bool,
+}
+
+impl SigmfDatatype {
+    /// Complex 32-bit float, little endian
+    pub const CF32_LE: SigmfDatatype = SigmfDatatype::new(true, SigmfFormat::F32, true);
+    /// Complex 16-bit signed integer, little endian
+    pub const CI16_LE: SigmfDatatype = SigmfDatatype::new(true, SigmfFormat::I16, true);
+    /// Complex 8-bit signed integer
+    pub const CI8: SigmfDatatype = SigmfDatatype::new(true, SigmfFormat::I8, true);
+    /// Complex 8-bit unsigned integer
+    pub const CU8: SigmfDatatype = SigmfDatatype::new(true, SigmfFormat::U8, true);
+
+    /// Create datatype
+    pub const fn new(complex: bool, format: SigmfFormat, little_endian: bool) -> Self {
+        Self {
+            complex,
+            format,
+            little_endian,
+        }
+    }
+
+    /// Whether samples are complex
+    pub fn is_complex(&self) -> bool {
+        self.complex
+    }
+
+    /// Format of the sample components
+    pub fn format(&self) -> SigmfFormat {
+        self.format
+    }
+
+    /// Size of a sample in bytes
+    pub fn sample_size(&self) -> usize {
+        self.format.size() * if self.complex { 2 } else { 1 }
+    }
+
+    fn component(&self, b: &[u8]) -> f32 {
+        macro_rules! get {
+            ($t:ty, $n:expr) => {{
+                let a: [u8; $n] = b[0..$n].try_into().unwrap();
+                if self.little_endian {
+                    <$t>::from_le_bytes(a)
+                } else {
+                    <$t>::from_be_bytes(a)
+                }
+            }};
+        }
+        match self.format {
+            SigmfFormat::F64 => get!(f64, 8) as f32,
+            SigmfFormat::F32 => get!(f32, 4),
+            SigmfFormat::I32 => (get!(i32, 4) as f64 / 2147483648.0) as f32,
+            SigmfFormat::I16 => get!(i16, 2) as f32 / 32768.0,
+            SigmfFormat::I8 => b[0] as i8 as f32 / 128.0,
+            SigmfFormat::U32 => ((get!(u32, 4) as f64 - 2147483648.0) / 2147483648.0) as f32,
+            SigmfFormat::U16 => (get!(u16, 2) as f32 - 32768.0) / 32768.0,
+            SigmfFormat::U8 => (b[0] as f32 - 128.0) / 128.0,
+        }
+    }
+
+    fn put_component(&self, v: f32, out: &mut Vec<u8>) {
+        macro_rules! put {
+            ($v:expr) => {{
+                let v = $v;
+                if self.little_endian {
+                    out.extend_from_slice(&v.to_le_bytes());
+                } else {
+                    out.extend_from_slice(&v.to_be_bytes());
+                }
+            }};
+        }
+        let v = v as f64;
+        match self.format {
+            SigmfFormat::F64 => put!(v),
+            SigmfFormat::F32 => put!(v as f32),
+            SigmfFormat::I32 => put!((v * 2147483648.0).round() as i32),
+            SigmfFormat::I16 => put!((v * 32768.0).round().clamp(-32768.0, 32767.0) as i16),
+            SigmfFormat::I8 => out.push((v * 128.0).round().clamp(-128.0, 127.0) as i8 as u8),
+            SigmfFormat::U32 => put!((v * 2147483648.0 + 2147483648.0).round() as u32),
+            SigmfFormat::U16 => put!((v * 32768.0 + 32768.0).round().clamp(0.0, 65535.0) as u16),
+            SigmfFormat::U8 => out.push((v * 128.0 + 128.0).round().clamp(0.0, 255.0) as u8),
+        }
+    }
+
+    /// Convert samples, `bytes` has to hold `out.len()` samples
+    pub fn decode(&self, bytes: &[u8], out: &mut [Complex32]) {
+        let size = self.format.size();
+        for (b, o) in bytes.chunks_exact(self.sample_size()).zip(out.iter_mut()) {
+            *o = if self.complex {
+                Complex32::new(self.component(&b[..size]), self.component(&b[size..]))
+            } else {
+                Complex32::new(self.component(b), 0.0)
+            };
+        }
+    }
+
+    /// Convert samples and append them to `out`
+    ///
+    /// For real datatypes, the imaginary part is dropped.
+    pub fn encode(&self, samples: &[Complex32], out: &mut Vec<u8>) {
+        out.reserve(samples.len() * self.sample_size());
+        for s in samples {
+            self.put_component(s.re, out);
+            if self.complex {
+                self.put_component(s.im, out);
+            }
+        }
+    }
+}
+
+impl FromStr for SigmfDatatype {
+    type Err = crate::anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (t, endian) = match s.split_once('_') {
+            Some((t, e)) => (t, Some(e)),
+            None => (s, None),
+        };
+        let complex = match t.chars().next() {
+            Some('c') => true,
+            Some('r') => false,
+            _ => bail!("SigMF: invalid datatype {s}"),
+        };
+        let format = match &t[1..] {
+            "f64" => SigmfFormat::F64,
+            "f32" => SigmfFormat::F32,
+            "i32" => SigmfFormat::I32,
+            "i16" => SigmfFormat::I16,
+            "i8" => SigmfFormat::I8,
+            "u32" => SigmfFormat::U32,
+            "u16" => SigmfFormat::U16,
+            "u8" => SigmfFormat::U8,
+            _ => bail!("SigMF: unsupported datatype {s}"),
+        };
+        let little_endian = match (endian, format.size()) {
+            (None, 1) | (Some("le"), _) => true,
+            (Some("be"), _) => false,
+            _ => bail!("SigMF: invalid datatype {s}"),
+        };
+        Ok(Self::new(complex, format, little_endian))
+    }
+}
+
+impl fmt::Display for SigmfDatatype {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}",
+            if self.complex { "c" } else { "r" },
+            self.format.name()
+        )?;
+        if self.format.size() > 1 {
+            write!(f, "{}", if self.little_endian { "_le" } else { "_be" })?;
+        }
+        Ok(())
+    }
+}
+
+/// Base name of a recording, i.e., the path with a `.sigmf-meta` or `.sigmf-data` suffix removed
+///
+/// Other dots in the file name are kept, e.g., `rec_868.1MHz` is a valid base name.
+fn base_path(path: &Path) -> PathBuf {
+    let name = path.file_name().and_then(|n| n.to_str());
+    match name.and_then(|n| {
+        n.strip_suffix(".sigmf-meta")
+            .or_else(|| n.strip_suffix(".sigmf-data"))
+    }) {
+        Some(base) if !base.is_empty() => path.with_file_name(base),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Append a suffix to the file name of a path
+fn with_suffix(path: PathBuf, suffix: &str) -> PathBuf {
+    let mut path = path.into_os_string();
+    path.push(suffix);
+    path.into()
+}
+
+/// Path of the metadata file of a recording, given as base name or path of one of its files
+pub fn meta_path(path: impl AsRef<Path>) -> PathBuf {
+    with_suffix(base_path(path.as_ref()), ".sigmf-meta")
+}
+
+/// Path of the data file of a recording, given as base name or path of one of its files
+pub fn data_path(path: impl AsRef<Path>) -> PathBuf {
+    with_suffix(base_path(path.as_ref()), ".sigmf-data")
+}
@@ -0,0 +1,260 @@
+use async_fs::File;
+use futures::io::AsyncWriteExt;
+use std::path::PathBuf;
+
+use crate::anyhow::{Context, Result};
+use crate::blocks::sigmf::data_path;
+use crate::blocks::sigmf::meta_path;
+use crate::blocks::sigmf::SigmfAnnotation;
+use crate::blocks::sigmf::SigmfCapture;
+use crate::blocks::sigmf::SigmfDatatype;
+use crate::blocks::sigmf::SigmfGlobal;
+use crate::blocks::sigmf::SigmfMeta;
+use crate::blocks::sigmf::ANNOTATION_TAG;
+use crate::blocks::sigmf::CAPTURE_TAG;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Write a SigMF recording.
+///
+/// Samples are converted to the configured datatype and written to the `.sigmf-data` file.
+/// Capture segment and annotation tags (see [module documentation](super)) are added to the
+/// metadata, which is written to the `.sigmf-meta` file, when the flowgraph terminates.
+///
+/// # Inputs
+///
+/// `in`: Samples
+///
+/// # Outputs
+///
+/// No outputs.
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::sigmf::SigmfDatatype;
+/// use futuresdr::blocks::SigmfSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     SigmfSinkBuilder::new("recording")
+///         .datatype(SigmfDatatype::CI16_LE)
+///         .sample_rate(4e6)
+///         .frequency(2.45e9)
+///         .build(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct SigmfSink {
+    path: PathBuf,
+    file: Option<File>,
+    datatype: SigmfDatatype,
+    global: SigmfGlobal,
+    frequency: Option<f64>,
+    captures: Vec<SigmfCapture>,
+    annotations: Vec<SigmfAnnotation>,
+    sample: u64,
+    buf: Vec<u8>,
+}
+
+impl SigmfSink {
+    fn new(
+        path: PathBuf,
+        datatype: SigmfDatatype,
+        global: SigmfGlobal,
+        frequency: Option<f64>,
+    ) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("SigmfSink").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::new().build(),
+            SigmfSink {
+                path,
+                file: None,
+                datatype,
+                global,
+                frequency,
+                captures: Vec::new(),
+                annotations: Vec::new(),
+                sample: 0,
+                buf: Vec::new(),
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for SigmfSink {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+
+        for t in sio.input(0).tags().iter() {
+            if t.index >= i.len() {
+                continue;
+            }
+            let sample_start = self.sample + t.index as u64;
+            if let Tag::NamedAny(n, a) = &t.tag {
+                if n == CAPTURE_TAG {
+                    if let Some(c) = a.downcast_ref::<SigmfCapture>() {
+                        self.captures.push(SigmfCapture {
+                            sample_start,
+                            ..c.clone()
+                        });
+                    }
+                } else if n == ANNOTATION_TAG {
+                    if let Some(a) = a.downcast_ref::<SigmfAnnotation>() {
+                        self.annotations.push(SigmfAnnotation {
+                            sample_start,
+                            ..a.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        if !i.is_empty() {
+            self.buf.clear();
+            self.datatype.encode(i, &mut self.buf);
+            self.file.as_mut().unwrap().write_all(&self.buf).await?;
+            self.sample += i.len() as u64;
+        }
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        sio.input(0).consume(i.len());
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let path = data_path(&self.path);
+        self.file = Some(
+            File::create(&path)
+                .await
+                .with_context(|| format!("SigMF: cannot create {}", path.display()))?,
+        );
+        Ok(())
+    }
+
+    async fn deinit(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.file.as_mut().unwrap().sync_all().await?;
+
+        let mut captures = std::mem::take(&mut self.captures);
+        if !captures.iter().any(|c| c.sample_start == 0) {
+            captures.insert(
+                0,
+                SigmfCapture {
+                    sample_start: 0,
+                    frequency: self.frequency,
+                    ..Default::default()
+                },
+            );
+        }
+
+        let meta = SigmfMeta {
+            global: self.global.clone(),
+            captures,
+            annotations: std::mem::take(&mut self.annotations),
+        };
+        meta.to_file(meta_path(&self.path))
+    }
+}
+
+/// Build a [SigmfSink].
+///
+/// The recording is given by its base name. `.sigmf-meta`, `.sigmf-data`, and `.sigmf`
+/// extensions are stripped.
+pub struct SigmfSinkBuilder {
+    path: PathBuf,
+    datatype: SigmfDatatype,
+    global: SigmfGlobal,
+    frequency: Option<f64>,
+}
+
+impl SigmfSinkBuilder {
+    /// Create builder
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            datatype: SigmfDatatype::CF32_LE,
+            global: SigmfGlobal::default(),
+            frequency: None,
+        }
+    }
+
+    /// Datatype of the data file (default: `cf32_le`)
+    #[must_use]
+    pub fn datatype(mut self, datatype: SigmfDatatype) -> Self {
+        self.datatype = datatype;
+        self
+    }
+
+    /// Sample rate
+    #[must_use]
+    pub fn sample_rate(mut self, sample_rate: f64) -> Self {
+        self.global.sample_rate = Some(sample_rate);
+        self
+    }
+
+    /// Center frequency of the initial capture segment
+    #[must_use]
+    pub fn frequency(mut self, frequency: f64) -> Self {
+        self.frequency = Some(frequency);
+        self
+    }
+
+    /// Description
+    #[must_use]
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.global.description = Some(description.into());
+        self
+    }
+
+    /// Author
+    #[must_use]
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.global.author = Some(author.into());
+        self
+    }
+
+    /// Recording hardware
+    #[must_use]
+    pub fn hw(mut self, hw: impl Into<String>) -> Self {
+        self.global.hw = Some(hw.into());
+        self
+    }
+
+    /// Create the block
+    pub fn build(mut self) -> Block {
+        self.global.datatype = self.datatype.to_string();
+        SigmfSink::new(self.path, self.datatype, self.global, self.frequency)
+    }
+}
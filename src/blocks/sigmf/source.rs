@@ -0,0 +1,222 @@
+use futures::AsyncReadExt;
+use std::path::PathBuf;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::blocks::sigmf::data_path;
+use crate::blocks::sigmf::SigmfDatatype;
+use crate::blocks::sigmf::SigmfMeta;
+use crate::blocks::sigmf::ANNOTATION_TAG;
+use crate::blocks::sigmf::CAPTURE_TAG;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Read a SigMF recording.
+///
+/// Samples are converted from the datatype of the recording to `Complex32`. Capture segments and
+/// annotations are output as [`Tag::NamedAny`] at their first sample (see [module
+/// documentation](super)).
+///
+/// # Inputs
+///
+/// No inputs.
+///
+/// # Outputs
+///
+/// `out`: Samples
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::SigmfSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(SigmfSourceBuilder::new("recording.sigmf-meta").build().unwrap());
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct SigmfSource {
+    data: PathBuf,
+    file: Option<async_fs::File>,
+    datatype: SigmfDatatype,
+    repeat: bool,
+    tags: Vec<(u64, Tag)>,
+    next_tag: usize,
+    sample: u64,
+    buf: Vec<u8>,
+    buffered: usize,
+}
+
+impl SigmfSource {
+    fn new(meta: SigmfMeta, data: PathBuf, repeat: bool) -> Result<Block> {
+        let datatype: SigmfDatatype = meta.global.datatype.parse()?;
+        if meta.global.num_channels.unwrap_or(1) != 1 {
+            bail!("SigMF: only single-channel recordings are supported");
+        }
+
+        let mut tags: Vec<(u64, Tag)> = meta
+            .captures
+            .into_iter()
+            .map(|c| {
+                (
+                    c.sample_start,
+                    Tag::NamedAny(CAPTURE_TAG.to_string(), Box::new(c)),
+                )
+            })
+            .chain(meta.annotations.into_iter().map(|a| {
+                (
+                    a.sample_start,
+                    Tag::NamedAny(ANNOTATION_TAG.to_string(), Box::new(a)),
+                )
+            }))
+            .collect();
+        tags.sort_by_key(|t| t.0);
+
+        Ok(Block::new(
+            BlockMetaBuilder::new("SigmfSource").build(),
+            StreamIoBuilder::new()
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new().build(),
+            SigmfSource {
+                data,
+                file: None,
+                datatype,
+                repeat,
+                tags,
+                next_tag: 0,
+                sample: 0,
+                buf: Vec::new(),
+                buffered: 0,
+            },
+        ))
+    }
+
+    async fn open(&mut self) -> Result<()> {
+        self.file = Some(
+            async_fs::File::open(&self.data)
+                .await
+                .with_context(|| format!("SigMF: cannot open {}", self.data.display()))?,
+        );
+        self.next_tag = 0;
+        self.sample = 0;
+        self.buffered = 0;
+        Ok(())
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for SigmfSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<Complex32>();
+        if out.is_empty() {
+            return Ok(());
+        }
+        let size = self.datatype.sample_size();
+        self.buf.resize(out.len() * size, 0);
+
+        while self.buffered < self.buf.len() {
+            match self
+                .file
+                .as_mut()
+                .unwrap()
+                .read(&mut self.buf[self.buffered..])
+                .await?
+            {
+                0 => {
+                    if !self.repeat || (self.sample == 0 && self.buffered < size) {
+                        io.finished = true;
+                    } else if self.buffered < size {
+                        // restart, dropping a truncated last sample
+                        self.open().await?;
+                        continue;
+                    }
+                    break;
+                }
+                n => self.buffered += n,
+            }
+        }
+
+        let items = self.buffered / size;
+        self.datatype
+            .decode(&self.buf[..items * size], &mut out[..items]);
+        self.buf.copy_within(items * size..self.buffered, 0);
+        self.buffered -= items * size;
+
+        let end = self.sample + items as u64;
+        while let Some((start, tag)) = self.tags.get(self.next_tag) {
+            if *start >= end {
+                break;
+            }
+            if *start >= self.sample {
+                sio.output(0)
+                    .add_tag((start - self.sample) as usize, tag.clone());
+            }
+            self.next_tag += 1;
+        }
+        self.sample = end;
+
+        sio.output(0).produce(items);
+
+        if io.finished && self.buffered > 0 {
+            warn!("SigmfSource: dropping truncated sample at end of file");
+        }
+
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.open().await
+    }
+}
+
+/// Build a [SigmfSource].
+///
+/// The recording can be given by the path of its metadata or data file or by its base name.
+pub struct SigmfSourceBuilder {
+    path: PathBuf,
+    repeat: bool,
+}
+
+impl SigmfSourceBuilder {
+    /// Create builder
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            repeat: false,
+        }
+    }
+
+    /// Restart at the beginning of the recording, when reaching its end
+    #[must_use]
+    pub fn repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Read metadata and create the block
+    pub fn build(self) -> Result<Block> {
+        let meta = SigmfMeta::from_file(&self.path)?;
+        SigmfSource::new(meta, data_path(&self.path), self.repeat)
+    }
+}
@@ -0,0 +1,368 @@
+use futuredsp::windows;
+use rustfft::num_complex::Complex32;
+use rustfft::FftPlanner;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Window, applied before the FFT of a [Spectrogram].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FftWindow {
+    /// No window
+    Rectangular,
+    /// Hann window
+    Hann,
+    /// Hamming window
+    Hamming,
+    /// Blackman window
+    Blackman,
+}
+
+impl FftWindow {
+    /// All windows
+    pub const ALL: [FftWindow; 4] = [
+        FftWindow::Rectangular,
+        FftWindow::Hann,
+        FftWindow::Hamming,
+        FftWindow::Blackman,
+    ];
+
+    fn taps(&self, len: usize) -> Vec<f32> {
+        let taps = match self {
+            FftWindow::Rectangular => return vec![1.0; len],
+            FftWindow::Hann => windows::hann(len, true),
+            FftWindow::Hamming => windows::hamming(len, true),
+            FftWindow::Blackman => windows::blackman(len, true),
+        };
+        taps.into_iter().map(|t| t as f32).collect()
+    }
+}
+
+impl fmt::Display for FftWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            FftWindow::Rectangular => "rectangular",
+            FftWindow::Hann => "hann",
+            FftWindow::Hamming => "hamming",
+            FftWindow::Blackman => "blackman",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for FftWindow {
+    type Err = crate::anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "rectangular" | "rect" | "none" => Ok(FftWindow::Rectangular),
+            "hann" | "hanning" => Ok(FftWindow::Hann),
+            "hamming" => Ok(FftWindow::Hamming),
+            "blackman" => Ok(FftWindow::Blackman),
+            _ => bail!("unknown window {s}"),
+        }
+    }
+}
+
+/// Compute the power spectrum of overlapping, windowed segments.
+pub struct Spectrogram {
+    fft_size: usize,
+    bins: usize,
+    overlap: f32,
+    window: FftWindow,
+    taps: Vec<f32>,
+    scale: f32,
+    plan: Arc<dyn rustfft::Fft<f32>>,
+    scratch: Vec<Complex32>,
+    frame: Vec<Complex32>,
+    buffer: Vec<Complex32>,
+}
+
+impl Spectrogram {
+    /// Create Spectrogram block, outputting `fft_size` bins per FFT
+    pub fn new(fft_size: usize) -> Block {
+        SpectrogramBuilder::new(fft_size).build().unwrap()
+    }
+
+    fn configure(&mut self) {
+        self.plan = FftPlanner::<f32>::new().plan_fft_forward(self.fft_size);
+        self.scratch = vec![Complex32::new(0.0, 0.0); self.plan.get_inplace_scratch_len()];
+        self.frame = vec![Complex32::new(0.0, 0.0); self.fft_size];
+        self.reset_window();
+        self.buffer.clear();
+    }
+
+    fn reset_window(&mut self) {
+        self.taps = self.window.taps(self.fft_size);
+        let sum: f32 = self.taps.iter().sum();
+        self.scale = 1.0 / (sum * sum);
+    }
+
+    fn hop(&self) -> usize {
+        ((self.fft_size as f32 * (1.0 - self.overlap)).round() as usize).clamp(1, self.fft_size)
+    }
+
+    /// FFT of the buffered segment, written as `out.len()` columns of linear power
+    fn spectrum(&mut self, out: &mut [f32]) {
+        for (f, (s, w)) in self
+            .frame
+            .iter_mut()
+            .zip(self.buffer.iter().zip(self.taps.iter()))
+        {
+            *f = s * w;
+        }
+        self.plan
+            .process_with_scratch(&mut self.frame, &mut self.scratch);
+
+        // fft shift and map the bins to the columns, keeping the peak of merged bins
+        let n = self.fft_size;
+        let bins = out.len();
+        for (c, o) in out.iter_mut().enumerate() {
+            let start = c * n / bins;
+            let end = ((c + 1) * n / bins).max(start + 1);
+            *o = (start..end)
+                .map(|k| self.frame[(k + n / 2) % n].norm_sqr())
+                .fold(0.0, f32::max)
+                * self.scale;
+        }
+    }
+
+    #[message_handler]
+    async fn fft_size_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let len = match p {
+            Pmt::Usize(v) => v,
+            Pmt::U32(v) => v as usize,
+            Pmt::U64(v) => v as usize,
+            Pmt::Null => return Ok(Pmt::Usize(self.fft_size)),
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        if len == 0 {
+            return Ok(Pmt::InvalidValue);
+        }
+        if len != self.fft_size {
+            self.fft_size = len;
+            self.configure();
+        }
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn overlap_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        let v: Result<f64, _> = p.clone().try_into();
+        match (&p, v) {
+            (Pmt::Null, _) => Ok(Pmt::F32(self.overlap)),
+            (_, Ok(v)) if (0.0..1.0).contains(&v) => {
+                self.overlap = v as f32;
+                Ok(Pmt::Ok)
+            }
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn window_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Null => Ok(Pmt::String(self.window.to_string())),
+            Pmt::String(s) => match s.parse() {
+                Ok(w) => {
+                    self.window = w;
+                    self.reset_window();
+                    Ok(Pmt::Ok)
+                }
+                Err(_) => Ok(Pmt::InvalidValue),
+            },
+            _ => Ok(Pmt::InvalidValue),
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Spectrogram {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<f32>();
+
+        let mut consumed = 0;
+        let mut produced = 0;
+
+        loop {
+            let n = std::cmp::min(self.fft_size - self.buffer.len(), i.len() - consumed);
+            self.buffer.extend_from_slice(&i[consumed..consumed + n]);
+            consumed += n;
+
+            if self.buffer.len() < self.fft_size || o.len() - produced < self.bins {
+                break;
+            }
+
+            self.spectrum(&mut o[produced..produced + self.bins]);
+            produced += self.bins;
+            let hop = self.hop();
+            self.buffer.drain(0..hop);
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && consumed == i.len() && self.buffer.len() < self.fft_size {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [Spectrogram] block.
+///
+/// The block computes the FFT of windowed input segments that overlap by the given fraction
+/// and outputs the linear power of each FFT, fft-shifted and normalized to the window, i.e.,
+/// a tone with unit amplitude shows up with a power of one. Each FFT produces `bins` output
+/// samples, independent of the FFT size. Larger FFTs are reduced to `bins` columns, keeping
+/// the peak, smaller ones are repeated. This way, the FFT size can change at runtime without
+/// affecting the framing of downstream blocks, like a
+/// [WebsocketSink](crate::blocks::WebsocketSink).
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples (Complex32)
+///
+/// **Message** `fft_size`: Set the FFT size ([`Pmt::Usize`], [`Pmt::U32`], [`Pmt::U64`]).
+///
+/// **Message** `overlap`: Set the overlap of consecutive segments as fraction in `[0, 1)`
+/// ([`Pmt::F32`], [`Pmt::F64`]).
+///
+/// **Message** `window`: Set the window by name ([`Pmt::String`], see [FftWindow]).
+///
+/// For all messages, [`Pmt::Null`] returns the current value.
+///
+/// # Outputs
+///
+/// **Stream** `out`: `bins` power values per FFT (f32)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::FftWindow;
+/// use futuresdr::blocks::SpectrogramBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let spectrogram = fg.add_block(
+///     SpectrogramBuilder::new(1024)
+///         .bins(2048)
+///         .overlap(0.5)
+///         .window(FftWindow::Hann)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct SpectrogramBuilder {
+    fft_size: usize,
+    bins: Option<usize>,
+    overlap: f32,
+    window: FftWindow,
+}
+
+impl SpectrogramBuilder {
+    /// Create Spectrogram builder
+    pub fn new(fft_size: usize) -> SpectrogramBuilder {
+        SpectrogramBuilder {
+            fft_size,
+            bins: None,
+            overlap: 0.0,
+            window: FftWindow::Rectangular,
+        }
+    }
+    /// Output samples per FFT (default: FFT size)
+    #[must_use]
+    pub fn bins(mut self, bins: usize) -> SpectrogramBuilder {
+        self.bins = Some(bins);
+        self
+    }
+    /// Overlap of consecutive segments (default: 0)
+    #[must_use]
+    pub fn overlap(mut self, overlap: f32) -> SpectrogramBuilder {
+        self.overlap = overlap;
+        self
+    }
+    /// Window (default: [FftWindow::Rectangular])
+    #[must_use]
+    pub fn window(mut self, window: FftWindow) -> SpectrogramBuilder {
+        self.window = window;
+        self
+    }
+    /// Build Spectrogram
+    pub fn build(self) -> Result<Block> {
+        let bins = self.bins.unwrap_or(self.fft_size);
+        if self.fft_size == 0 || bins == 0 {
+            bail!("FFT size and bins have to be positive");
+        }
+        if !(0.0..1.0).contains(&self.overlap) {
+            bail!("overlap has to be in [0, 1)");
+        }
+
+        let mut spectrogram = Spectrogram {
+            fft_size: self.fft_size,
+            bins,
+            overlap: self.overlap,
+            window: self.window,
+            taps: Vec::new(),
+            scale: 1.0,
+            plan: FftPlanner::<f32>::new().plan_fft_forward(self.fft_size),
+            scratch: Vec::new(),
+            frame: Vec::new(),
+            buffer: Vec::new(),
+        };
+        spectrogram.configure();
+
+        Ok(Block::new(
+            BlockMetaBuilder::new("Spectrogram").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<f32>("out")
+                .build(),
+            MessageIoBuilder::<Spectrogram>::new()
+                .add_input("fft_size", Spectrogram::fft_size_handler)
+                .add_input("overlap", Spectrogram::overlap_handler)
+                .add_input("window", Spectrogram::window_handler)
+                .build(),
+            spectrogram,
+        ))
+    }
+}
@@ -0,0 +1,202 @@
+use async_io::Timer;
+use std::time::Duration;
+use web_time::Instant;
+
+use crate::anyhow::{bail, Result};
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Step through a list of frequencies, dwelling a given time on each of them.
+pub struct Sweeper {
+    frequencies: Vec<f64>,
+    index: usize,
+    dwell: Duration,
+    t_last: Option<Instant>,
+    repeat: bool,
+}
+
+impl Sweeper {
+    /// Create Sweeper block
+    pub fn new(frequencies: Vec<f64>, dwell: Duration, repeat: bool) -> Block {
+        assert!(!frequencies.is_empty());
+
+        Block::new(
+            BlockMetaBuilder::new("Sweeper").build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("dwell", Self::dwell_handler)
+                .add_input("next", Self::next_handler)
+                .add_output("freq")
+                .add_output("sweep")
+                .build(),
+            Sweeper {
+                frequencies,
+                index: 0,
+                dwell,
+                t_last: None,
+                repeat,
+            },
+        )
+    }
+
+    async fn step(&mut self, io: &mut WorkIo, mio: &mut MessageIo<Self>) {
+        if self.index == self.frequencies.len() {
+            mio.post(1, Pmt::Ok).await;
+            if !self.repeat {
+                io.finished = true;
+                return;
+            }
+            self.index = 0;
+        }
+        mio.post(0, Pmt::F64(self.frequencies[self.index])).await;
+        self.index += 1;
+        self.t_last = Some(Instant::now());
+    }
+
+    #[message_handler]
+    async fn dwell_handler(
+        &mut self,
+        io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::F32(v) if v > 0.0 => self.dwell = Duration::from_secs_f32(v),
+            Pmt::F64(v) if v > 0.0 => self.dwell = Duration::from_secs_f64(v),
+            Pmt::Null => return Ok(Pmt::F64(self.dwell.as_secs_f64())),
+            _ => return Ok(Pmt::InvalidValue),
+        }
+        io.call_again = true;
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn next_handler(
+        &mut self,
+        io: &mut WorkIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if matches!(p, Pmt::Finished) {
+            return Ok(Pmt::Ok);
+        }
+        self.step(io, mio).await;
+        io.call_again = true;
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for Sweeper {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _b: &mut BlockMeta,
+    ) -> Result<()> {
+        let due = match self.t_last {
+            Some(t) => Instant::now() >= t + self.dwell,
+            None => true,
+        };
+        if due {
+            self.step(io, mio).await;
+            if io.finished {
+                return Ok(());
+            }
+        }
+
+        let t = self.t_last.unwrap() + self.dwell;
+        io.block_on(async move {
+            Timer::after(t.saturating_duration_since(Instant::now())).await;
+        });
+
+        Ok(())
+    }
+}
+
+/// Sweep a frequency range, e.g., to scan a band with a Seify source.
+///
+/// # Inputs
+///
+/// **Message** `dwell`: Set the dwell time in seconds ([`Pmt::F32`], [`Pmt::F64`]).
+/// [`Pmt::Null`] returns the current dwell time.
+///
+/// **Message** `next`: Step to the next frequency immediately.
+///
+/// # Outputs
+///
+/// **Message** `freq`: Frequency to tune to ([`Pmt::F64`]). Connect to the `freq` input of a
+/// source.
+///
+/// **Message** `sweep`: [`Pmt::Ok`] after every completed sweep.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SweeperBuilder;
+/// use futuresdr::runtime::Flowgraph;
+/// use std::time::Duration;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // Step from 100 MHz to 110 MHz in 1 MHz steps, staying 100 ms on each frequency
+/// let sweeper = fg.add_block(
+///     SweeperBuilder::new(100e6, 110e6, 1e6)
+///         .dwell(Duration::from_millis(100))
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+#[cfg_attr(docsrs, doc(cfg(not(target_arch = "wasm32"))))]
+pub struct SweeperBuilder {
+    start: f64,
+    stop: f64,
+    step: f64,
+    dwell: Duration,
+    repeat: bool,
+}
+
+impl SweeperBuilder {
+    /// Create Sweeper builder for the range from `start` to `stop` (inclusive)
+    pub fn new(start: f64, stop: f64, step: f64) -> SweeperBuilder {
+        SweeperBuilder {
+            start,
+            stop,
+            step,
+            dwell: Duration::from_millis(100),
+            repeat: true,
+        }
+    }
+    /// Time to stay on a frequency (default: 100 ms)
+    #[must_use]
+    pub fn dwell(mut self, dwell: Duration) -> SweeperBuilder {
+        self.dwell = dwell;
+        self
+    }
+    /// Restart after completing a sweep (default: `true`)
+    #[must_use]
+    pub fn repeat(mut self, repeat: bool) -> SweeperBuilder {
+        self.repeat = repeat;
+        self
+    }
+    /// Build Sweeper block
+    pub fn build(self) -> Result<Block> {
+        if self.step <= 0.0 || self.stop < self.start {
+            bail!("invalid sweep range");
+        }
+        let n = ((self.stop - self.start) / self.step + 1e-9).floor() as usize + 1;
+        let frequencies = (0..n).map(|i| self.start + i as f64 * self.step).collect();
+        Ok(Sweeper::new(frequencies, self.dwell, self.repeat))
+    }
+}
@@ -0,0 +1,397 @@
+use futuredsp::firdes;
+use std::ops::Add;
+use std::ops::Mul;
+use std::ops::Sub;
+
+use crate::anyhow::{bail, Result};
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Samples, the [SymbolSync] can recover the timing of.
+pub trait SymbolSyncSample:
+    Copy
+    + Default
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<f32, Output = Self>
+    + Send
+    + Sync
+    + 'static
+{
+    /// Hard decision, i.e., the sign of each component.
+    fn decision(self) -> Self;
+    /// Real part of `self * conj(other)`.
+    fn dot(self, other: Self) -> f32;
+}
+
+impl SymbolSyncSample for f32 {
+    fn decision(self) -> Self {
+        if self > 0.0 {
+            1.0
+        } else {
+            -1.0
+        }
+    }
+    fn dot(self, other: Self) -> f32 {
+        self * other
+    }
+}
+
+impl SymbolSyncSample for Complex32 {
+    fn decision(self) -> Self {
+        Complex32::new(self.re.decision(), self.im.decision())
+    }
+    fn dot(self, other: Self) -> f32 {
+        self.re * other.re + self.im * other.im
+    }
+}
+
+/// Timing error detector of the [SymbolSync].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TimingErrorDetector {
+    /// Mueller and Müller, on linearly interpolated samples. The input should already be
+    /// matched filtered.
+    #[default]
+    MuellerMuller,
+    /// Polyphase filter bank of matched filters and their derivatives, like the
+    /// `Polyphase Clock Sync` of GNU Radio. The error is the product of the filter output and
+    /// its derivative, which is zero at the peak of the pulse.
+    PolyphaseFilterBank,
+}
+
+/// Filter bank of the [TimingErrorDetector::PolyphaseFilterBank].
+struct FilterBank {
+    filters: Vec<Vec<f32>>,
+    derivatives: Vec<Vec<f32>>,
+}
+
+impl FilterBank {
+    /// Split the prototype filter at `filters` times the input rate and its derivative, i.e.,
+    /// `bank[p][j] = taps[p + (len - 1 - j) * filters]`
+    fn new(taps: &[f32], filters: usize) -> FilterBank {
+        let len = (taps.len() + filters - 1) / filters;
+        let tap = |n: isize| {
+            if n < 0 {
+                0.0
+            } else {
+                taps.get(n as usize).copied().unwrap_or(0.0)
+            }
+        };
+        // central difference, scaled to the input rate
+        let derivative = |n: isize| (tap(n + 1) - tap(n - 1)) / 2.0 * filters as f32;
+        let split = |f: &dyn Fn(isize) -> f32| -> Vec<Vec<f32>> {
+            (0..filters)
+                .map(|p| {
+                    (0..len)
+                        .map(|j| f((p + (len - 1 - j) * filters) as isize))
+                        .collect()
+                })
+                .collect()
+        };
+        FilterBank {
+            filters: split(&tap),
+            derivatives: split(&derivative),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.filters[0].len()
+    }
+
+    fn apply<T: SymbolSyncSample>(taps: &[f32], input: &[T]) -> T {
+        input
+            .iter()
+            .zip(taps.iter())
+            .fold(T::default(), |acc, (x, t)| acc + *x * *t)
+    }
+}
+
+/// Symbol synchronizer, recovering the symbol timing with a second-order loop.
+pub struct SymbolSync<T: SymbolSyncSample> {
+    omega: f32,
+    omega_mid: f32,
+    omega_limit: f32,
+    gain_omega: f32,
+    mu: f32,
+    gain_mu: f32,
+    /// input samples to skip, before the next symbol
+    skip: usize,
+    last_sample: T,
+    bank: Option<FilterBank>,
+}
+
+impl<T: SymbolSyncSample> SymbolSync<T> {
+    /// Create SymbolSync block
+    ///
+    /// ## Parameter
+    /// - `omega`: samples per symbol
+    /// - `gain_omega`: gain of the samples per symbol update
+    /// - `mu`: initial fractional sample offset
+    /// - `gain_mu`: gain of the sample offset update
+    /// - `omega_relative_limit`: maximum relative deviation of the samples per symbol
+    /// - `bank`: prototype filter at `filters` times the input rate, if the polyphase filter
+    ///   bank is used as timing error detector, or `None` for Mueller and Müller
+    pub fn new(
+        omega: f32,
+        gain_omega: f32,
+        mu: f32,
+        gain_mu: f32,
+        omega_relative_limit: f32,
+        bank: Option<(Vec<f32>, usize)>,
+    ) -> Block {
+        assert!(omega >= 1.0);
+        assert!((0.0..1.0).contains(&mu));
+        Block::new(
+            BlockMetaBuilder::new("SymbolSync").build(),
+            StreamIoBuilder::new()
+                .add_input::<T>("in")
+                .add_output::<T>("out")
+                .build(),
+            MessageIoBuilder::<Self>::new().build(),
+            SymbolSync {
+                omega,
+                omega_mid: omega,
+                omega_limit: omega * omega_relative_limit,
+                gain_omega,
+                mu,
+                gain_mu,
+                skip: 0,
+                last_sample: T::default(),
+                bank: bank.map(|(taps, filters)| FilterBank::new(&taps, filters)),
+            },
+        )
+    }
+
+    /// Output and timing error of the symbol at the fractional offset `mu` of `input`
+    fn detect(&mut self, input: &[T]) -> (T, f32) {
+        match &self.bank {
+            None => {
+                let sample = input[0] + (input[1] - input[0]) * self.mu;
+                let error = sample.dot(self.last_sample.decision())
+                    - self.last_sample.dot(sample.decision());
+                self.last_sample = sample;
+                (sample, error)
+            }
+            Some(bank) => {
+                let p = std::cmp::min(
+                    (self.mu * bank.filters.len() as f32) as usize,
+                    bank.filters.len() - 1,
+                );
+                let sample = FilterBank::apply(&bank.filters[p], input);
+                let derivative = FilterBank::apply(&bank.derivatives[p], input);
+                (sample, sample.dot(derivative))
+            }
+        }
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl<T: SymbolSyncSample> Kernel for SymbolSync<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<T>();
+        let o = sio.output(0).slice::<T>();
+        let look_ahead = self.bank.as_ref().map(|b| b.len()).unwrap_or(2);
+
+        let mut ii = self.skip;
+        let mut oo = 0;
+        while ii + look_ahead <= i.len() && oo < o.len() {
+            let (sample, error) = self.detect(&i[ii..ii + look_ahead]);
+            o[oo] = sample;
+
+            self.omega += self.gain_omega * error;
+            self.omega = self.omega_mid
+                + (self.omega - self.omega_mid).clamp(-self.omega_limit, self.omega_limit);
+            self.mu += self.omega + self.gain_mu * error;
+
+            ii += self.mu.floor() as usize;
+            self.mu -= self.mu.floor();
+            oo += 1;
+        }
+
+        let consumed = std::cmp::min(ii, i.len());
+        self.skip = ii - consumed;
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(oo);
+
+        if sio.input(0).finished() && ii + look_ahead > i.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [SymbolSync] block.
+///
+/// Recovers the symbol timing of a signal with `samples_per_symbol` samples per symbol and
+/// outputs one sample per symbol. The [TimingErrorDetector] drives a second-order loop that
+/// tracks the fractional sample offset (`mu`) and the samples per symbol (`omega`).
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples ([f32] or [Complex32])
+///
+/// # Outputs
+///
+/// **Stream** `out`: Symbols
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::SymbolSyncBuilder;
+/// use futuresdr::blocks::TimingErrorDetector;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex32;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let mm = fg.add_block(SymbolSyncBuilder::<f32>::new(2.0).build().unwrap());
+/// let pfb = fg.add_block(
+///     SymbolSyncBuilder::<Complex32>::new(4.0)
+///         .ted(TimingErrorDetector::PolyphaseFilterBank)
+///         .roll_off(0.5)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct SymbolSyncBuilder<T: SymbolSyncSample> {
+    samples_per_symbol: f32,
+    ted: TimingErrorDetector,
+    gain_mu: f32,
+    gain_omega: Option<f32>,
+    mu: f32,
+    omega_relative_limit: f32,
+    filters: usize,
+    roll_off: f64,
+    taps: Option<Vec<f32>>,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: SymbolSyncSample> SymbolSyncBuilder<T> {
+    /// Create SymbolSync builder for a signal with `samples_per_symbol` samples per symbol
+    pub fn new(samples_per_symbol: f32) -> SymbolSyncBuilder<T> {
+        SymbolSyncBuilder {
+            samples_per_symbol,
+            ted: TimingErrorDetector::default(),
+            gain_mu: 0.05,
+            gain_omega: None,
+            mu: 0.5,
+            omega_relative_limit: 0.005,
+            filters: 32,
+            roll_off: 0.35,
+            taps: None,
+            _type: std::marker::PhantomData,
+        }
+    }
+    /// Timing error detector (default: [TimingErrorDetector::MuellerMuller])
+    #[must_use]
+    pub fn ted(mut self, ted: TimingErrorDetector) -> SymbolSyncBuilder<T> {
+        self.ted = ted;
+        self
+    }
+    /// Gain of the sample offset update (default: 0.05)
+    #[must_use]
+    pub fn gain_mu(mut self, gain_mu: f32) -> SymbolSyncBuilder<T> {
+        self.gain_mu = gain_mu;
+        self
+    }
+    /// Gain of the samples per symbol update (default: `0.25 * gain_mu^2`, i.e., critically
+    /// damped)
+    #[must_use]
+    pub fn gain_omega(mut self, gain_omega: f32) -> SymbolSyncBuilder<T> {
+        self.gain_omega = Some(gain_omega);
+        self
+    }
+    /// Initial fractional sample offset in `[0, 1)` (default: 0.5)
+    #[must_use]
+    pub fn mu(mut self, mu: f32) -> SymbolSyncBuilder<T> {
+        self.mu = mu;
+        self
+    }
+    /// Maximum relative deviation of the samples per symbol (default: 0.005)
+    #[must_use]
+    pub fn omega_relative_limit(mut self, limit: f32) -> SymbolSyncBuilder<T> {
+        self.omega_relative_limit = limit;
+        self
+    }
+    /// Number of filters of the polyphase filter bank (default: 32)
+    #[must_use]
+    pub fn filters(mut self, filters: usize) -> SymbolSyncBuilder<T> {
+        self.filters = filters;
+        self
+    }
+    /// Roll-off of the default root-raised-cosine matched filter of the polyphase filter bank
+    /// (default: 0.35)
+    #[must_use]
+    pub fn roll_off(mut self, roll_off: f64) -> SymbolSyncBuilder<T> {
+        self.roll_off = roll_off;
+        self
+    }
+    /// Matched filter of the polyphase filter bank at `filters` times the input rate,
+    /// overriding the default root-raised-cosine
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<f32>) -> SymbolSyncBuilder<T> {
+        self.taps = Some(taps);
+        self
+    }
+    /// Build SymbolSync block
+    pub fn build(self) -> Result<Block> {
+        if !(self.samples_per_symbol >= 1.0 && self.samples_per_symbol.is_finite()) {
+            bail!("at least one sample per symbol required");
+        }
+        if !(0.0..1.0).contains(&self.mu) {
+            bail!("mu has to be in [0, 1)");
+        }
+        if !(0.0..1.0).contains(&self.omega_relative_limit) {
+            bail!("omega relative limit has to be in [0, 1)");
+        }
+        let bank = match self.ted {
+            TimingErrorDetector::MuellerMuller => None,
+            TimingErrorDetector::PolyphaseFilterBank => {
+                if self.filters == 0 {
+                    bail!("number of filters has to be positive");
+                }
+                let taps = match self.taps {
+                    Some(t) if t.is_empty() => bail!("no taps"),
+                    Some(t) => t,
+                    None => {
+                        if !(self.roll_off > 0.0 && self.roll_off <= 1.0) {
+                            bail!("roll-off has to be in (0, 1]");
+                        }
+                        let sps = (self.samples_per_symbol * self.filters as f32).round();
+                        let taps =
+                            firdes::root_raised_cosine::<f32>(8, sps as usize, self.roll_off);
+                        // unit gain per filter
+                        let gain = self.filters as f32 / taps.iter().sum::<f32>();
+                        taps.into_iter().map(|t| t * gain).collect()
+                    }
+                };
+                Some((taps, self.filters))
+            }
+        };
+        Ok(SymbolSync::<T>::new(
+            self.samples_per_symbol,
+            self.gain_omega
+                .unwrap_or(0.25 * self.gain_mu * self.gain_mu),
+            self.mu,
+            self.gain_mu,
+            self.omega_relative_limit,
+            bank,
+        ))
+    }
+}
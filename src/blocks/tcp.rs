@@ -0,0 +1,264 @@
+use async_executor::LocalExecutor;
+use async_io::Async;
+use async_io::Timer;
+use futures::channel::oneshot;
+use futures::future;
+use futures::future::Either;
+use futures::io::BufReader;
+use futures::task::AtomicWaker;
+use futures::AsyncBufReadExt;
+use futures::AsyncRead;
+use futures::AsyncWrite;
+use futures::Future;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::net::TcpListener;
+use std::net::TcpStream;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::task::Poll;
+use std::time::Duration;
+
+use crate::anyhow::{bail, Context, Result};
+
+#[cfg(feature = "tls")]
+use futures_rustls::rustls::server::WebPkiClientVerifier;
+#[cfg(feature = "tls")]
+use futures_rustls::rustls::RootCertStore;
+#[cfg(feature = "tls")]
+use futures_rustls::rustls::ServerConfig;
+#[cfg(feature = "tls")]
+use std::path::Path;
+
+/// Time a client has to complete the TLS handshake and authentication
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What a [TcpSink](super::TcpSink) does with samples while no client is connected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TcpBufferPolicy {
+    /// Backpressure. Keep samples in the input buffer until a client connects.
+    Block,
+    /// Drop samples.
+    Drop,
+    /// Keep the latest samples, up to the given number, and send them once a client connects.
+    Buffer(usize),
+}
+
+/// TLS configuration of the TCP blocks (requires `tls` feature).
+#[cfg(feature = "tls")]
+#[derive(Clone)]
+pub struct TcpTlsConfig {
+    config: Arc<ServerConfig>,
+}
+
+#[cfg(feature = "tls")]
+impl TcpTlsConfig {
+    /// Serve TLS with the certificate chain and private key from the given PEM files
+    pub fn new(cert: impl AsRef<Path>, key: impl AsRef<Path>) -> Result<Self> {
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(load_certs(cert.as_ref())?, load_key(key.as_ref())?)?;
+        Ok(Self {
+            config: Arc::new(config),
+        })
+    }
+
+    /// Serve TLS and authenticate clients with certificates signed by a CA from the `ca` PEM file
+    pub fn with_client_auth(
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+        ca: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let mut roots = RootCertStore::empty();
+        for c in load_certs(ca.as_ref())? {
+            roots.add(c)?;
+        }
+        let verifier = WebPkiClientVerifier::builder(Arc::new(roots)).build()?;
+        let config = ServerConfig::builder()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(load_certs(cert.as_ref())?, load_key(key.as_ref())?)?;
+        Ok(Self {
+            config: Arc::new(config),
+        })
+    }
+}
+
+#[cfg(feature = "tls")]
+fn load_certs(
+    path: &Path,
+) -> Result<Vec<futures_rustls::rustls::pki_types::CertificateDer<'static>>> {
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let certs = rustls_pemfile::certs(&mut reader).collect::<Result<Vec<_>, _>>()?;
+    if certs.is_empty() {
+        bail!("no certificates in {}", path.display());
+    }
+    Ok(certs)
+}
+
+#[cfg(feature = "tls")]
+fn load_key(path: &Path) -> Result<futures_rustls::rustls::pki_types::PrivateKeyDer<'static>> {
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    rustls_pemfile::private_key(&mut reader)?
+        .with_context(|| format!("no private key in {}", path.display()))
+}
+
+/// Security options of a TCP block
+#[derive(Clone, Default)]
+pub(super) struct TcpSecurity {
+    /// Token that clients have to send, terminated by a newline, before the samples
+    pub token: Option<String>,
+    #[cfg(feature = "tls")]
+    pub tls: Option<TcpTlsConfig>,
+}
+
+pub(super) trait Connection: AsyncRead + AsyncWrite + Send + Unpin {}
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Connection for T {}
+
+impl TcpSecurity {
+    /// Set up TLS and check the token of a new client
+    pub async fn handshake(&self, stream: Async<TcpStream>) -> Result<Box<dyn Connection>> {
+        let timeout = Timer::after(HANDSHAKE_TIMEOUT);
+        match future::select(Box::pin(self.handshake_inner(stream)), timeout).await {
+            Either::Left((conn, _)) => conn,
+            Either::Right(_) => bail!("handshake timed out"),
+        }
+    }
+
+    async fn handshake_inner(&self, stream: Async<TcpStream>) -> Result<Box<dyn Connection>> {
+        #[cfg(feature = "tls")]
+        let conn: Box<dyn Connection> = match &self.tls {
+            Some(tls) => Box::new(
+                futures_rustls::TlsAcceptor::from(tls.config.clone())
+                    .accept(stream)
+                    .await?,
+            ),
+            None => Box::new(stream),
+        };
+        #[cfg(not(feature = "tls"))]
+        let conn: Box<dyn Connection> = Box::new(stream);
+
+        match &self.token {
+            Some(token) => {
+                // read byte-wise to not consume samples that follow the token
+                let mut reader = BufReader::with_capacity(1, conn);
+                let mut line = Vec::new();
+                reader.read_until(b'\n', &mut line).await?;
+                let valid = line
+                    .strip_suffix(b"\n")
+                    .map_or(false, |l| constant_time_eq(l, token.as_bytes()));
+                if !valid {
+                    bail!("client sent wrong token");
+                }
+                Ok(Box::new(reader))
+            }
+            None => Ok(conn),
+        }
+    }
+}
+
+/// Compare without an early exit, so that the time does not depend on the matching prefix
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    let diff = (0..a.len().max(b.len())).fold(a.len() ^ b.len(), |d, i| {
+        d | (a.get(i).unwrap_or(&0) ^ b.get(i).unwrap_or(&0)) as usize
+    });
+    diff == 0
+}
+
+/// Clients that completed the handshake
+struct Accepted {
+    queue: Mutex<VecDeque<(Box<dyn Connection>, SocketAddr)>>,
+    waker: AtomicWaker,
+}
+
+/// Listener that accepts clients and runs the TLS and token handshake on a separate thread, so
+/// that slow or malicious clients do not stall the block
+pub(super) struct Acceptor {
+    accepted: Arc<Accepted>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl Acceptor {
+    /// Listen on the `bind` address; `name` is used for the thread and log messages
+    pub fn bind(bind: &str, security: TcpSecurity, name: &'static str) -> Result<Self> {
+        let addr = bind
+            .to_socket_addrs()?
+            .next()
+            .context("invalid bind address")?;
+        let listener = Async::<TcpListener>::bind(addr)?;
+        #[cfg(feature = "tls")]
+        let cleartext = security.tls.is_none();
+        #[cfg(not(feature = "tls"))]
+        let cleartext = true;
+        if security.token.is_some() && cleartext {
+            warn!(
+                "{} on {} receives the token in the clear, use TLS",
+                name, addr
+            );
+        }
+        let accepted = Arc::new(Accepted {
+            queue: Mutex::new(VecDeque::new()),
+            waker: AtomicWaker::new(),
+        });
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+
+        let a = accepted.clone();
+        let security = Arc::new(security);
+        std::thread::Builder::new()
+            .name(format!("{name} accept"))
+            .spawn(move || {
+                let ex = LocalExecutor::new();
+                let accept = async {
+                    loop {
+                        let (stream, peer) = match listener.accept().await {
+                            Ok(c) => c,
+                            Err(e) => {
+                                warn!("{} accept failed ({:?})", name, e);
+                                Timer::after(Duration::from_millis(100)).await;
+                                continue;
+                            }
+                        };
+                        let a = a.clone();
+                        let security = security.clone();
+                        ex.spawn(async move {
+                            match security.handshake(stream).await {
+                                Ok(conn) => {
+                                    a.queue.lock().unwrap().push_back((conn, peer));
+                                    a.waker.wake();
+                                }
+                                Err(e) => {
+                                    warn!("{} rejected connection from {} ({:?})", name, peer, e)
+                                }
+                            }
+                        })
+                        .detach();
+                    }
+                };
+                // the block is gone, once the shutdown sender is dropped
+                async_io::block_on(ex.run(future::select(Box::pin(accept), shutdown_rx)));
+            })?;
+
+        Ok(Self {
+            accepted,
+            _shutdown: shutdown_tx,
+        })
+    }
+
+    /// Take the next client that completed the handshake
+    pub fn try_next(&self) -> Option<(Box<dyn Connection>, SocketAddr)> {
+        self.accepted.queue.lock().unwrap().pop_front()
+    }
+
+    /// Wait until a client completed the handshake
+    pub fn ready(&self) -> impl Future<Output = ()> + Send + 'static {
+        let accepted = self.accepted.clone();
+        future::poll_fn(move |cx| {
+            accepted.waker.register(cx.waker());
+            if accepted.queue.lock().unwrap().is_empty() {
+                Poll::Pending
+            } else {
+                Poll::Ready(())
+            }
+        })
+    }
+}
@@ -1,7 +1,13 @@
-use async_net::{TcpListener, TcpStream};
 use futures::AsyncWriteExt;
+use std::collections::VecDeque;
 
 use crate::anyhow::{bail, Context, Result};
+use crate::blocks::tcp::Acceptor;
+use crate::blocks::tcp::Connection;
+use crate::blocks::tcp::TcpSecurity;
+use crate::blocks::TcpBufferPolicy;
+#[cfg(feature = "tls")]
+use crate::blocks::TcpTlsConfig;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -13,27 +19,42 @@ use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
 /// Push samples into a TCP socket.
+///
+/// The sink listens on a local port or on the `bind` address, e.g., `0.0.0.0:1234`, and sends
+/// samples to the first client that connects. Use the [`TcpSinkBuilder`] to accept new clients after a disconnect, to
+/// configure what happens with samples while no client is connected, and to secure the
+/// connection. Clients are authenticated on a separate thread, so the block keeps running
+/// during the handshake.
 pub struct TcpSink<T: Send + 'static> {
-    port: u32,
-    listener: Option<TcpListener>,
-    socket: Option<TcpStream>,
+    bind: String,
+    acceptor: Option<Acceptor>,
+    socket: Option<Box<dyn Connection>>,
+    reconnect: bool,
+    policy: TcpBufferPolicy,
+    security: TcpSecurity,
+    buffer: VecDeque<u8>,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> TcpSink<T> {
-    /// Create TCP Sink block
+    /// Create TCP Sink block, listening on `127.0.0.1:<port>`
     pub fn new(port: u32) -> Block {
-        Block::new(
-            BlockMetaBuilder::new("TcpSink").build(),
-            StreamIoBuilder::new().add_input::<T>("in").build(),
-            MessageIoBuilder::new().build(),
-            TcpSink {
-                port,
-                listener: None,
-                socket: None,
-                _type: std::marker::PhantomData::<T>,
-            },
-        )
+        Self::bind(format!("127.0.0.1:{port}"))
+    }
+
+    /// Create TCP Sink block, listening on the `bind` address
+    pub fn bind(bind: impl Into<String>) -> Block {
+        TcpSinkBuilder::<T>::new(bind).build()
+    }
+
+    /// Keep the latest `max` samples of the input in the buffer
+    fn buffer_input(&mut self, i: &[u8], max: usize) {
+        let item_size = std::mem::size_of::<T>();
+        let max = max * item_size;
+        let i = &i[i.len().saturating_sub(max)..];
+        let overflow = (self.buffer.len() + i.len()).saturating_sub(max);
+        self.buffer.drain(0..overflow);
+        self.buffer.extend(i);
     }
 }
 
@@ -47,39 +68,62 @@ impl<T: Send + 'static> Kernel for TcpSink<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        let item_size = std::mem::size_of::<T>();
+        let i = sio.input(0).slice_unchecked::<u8>();
+
         if self.socket.is_none() {
-            let (socket, _) = self
-                .listener
-                .as_mut()
-                .context("no listener")?
-                .accept()
-                .await?;
-            self.socket = Some(socket);
-            debug!("tcp sink accepted connection");
+            let acceptor = self.acceptor.as_ref().context("no listener")?;
+            if let Some((socket, addr)) = acceptor.try_next() {
+                debug!("tcp sink accepted connection from {}", addr);
+                self.socket = Some(socket);
+                io.call_again = true;
+                return Ok(());
+            }
+            let ready = acceptor.ready();
+
+            match self.policy {
+                TcpBufferPolicy::Block => {}
+                TcpBufferPolicy::Drop => {
+                    sio.input(0).consume(i.len() / item_size);
+                }
+                TcpBufferPolicy::Buffer(max) => {
+                    self.buffer_input(i, max);
+                    sio.input(0).consume(i.len() / item_size);
+                }
+            }
+
+            if sio.input(0).finished() && self.policy != TcpBufferPolicy::Block {
+                io.finished = true;
+            } else {
+                io.block_on(ready);
+            }
+            return Ok(());
         }
 
-        let i = sio.input(0).slice_unchecked::<u8>();
+        let socket = self.socket.as_mut().context("no socket")?;
+
+        if !self.buffer.is_empty() {
+            let (a, b) = self.buffer.as_slices();
+            let mut v = Vec::with_capacity(a.len() + b.len());
+            v.extend_from_slice(a);
+            v.extend_from_slice(b);
+            self.buffer.clear();
+            if socket.write_all(&v).await.is_err() {
+                return self.disconnected(io);
+            }
+        }
 
-        match self
-            .socket
-            .as_mut()
-            .context("no socket")?
-            .write_all(i)
-            .await
-        {
-            Ok(()) => {}
-            Err(_) => bail!("tcp sink socket error"),
+        // flush, so that TLS records are not held back
+        if socket.write_all(i).await.is_err() || socket.flush().await.is_err() {
+            return self.disconnected(io);
         }
 
         if sio.input(0).finished() {
             io.finished = true;
         }
 
-        debug!(
-            "tcp sink wrote bytes {}",
-            i.len() / std::mem::size_of::<T>()
-        );
-        sio.input(0).consume(i.len() / std::mem::size_of::<T>());
+        debug!("tcp sink wrote bytes {}", i.len() / item_size);
+        sio.input(0).consume(i.len() / item_size);
 
         Ok(())
     }
@@ -90,7 +134,97 @@ impl<T: Send + 'static> Kernel for TcpSink<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.listener = Some(TcpListener::bind(format!("127.0.0.1:{}", self.port)).await?);
+        self.acceptor = Some(Acceptor::bind(
+            &self.bind,
+            self.security.clone(),
+            "tcp sink",
+        )?);
         Ok(())
     }
 }
+
+impl<T: Send + 'static> TcpSink<T> {
+    fn disconnected(&mut self, io: &mut WorkIo) -> Result<()> {
+        self.socket = None;
+        if self.reconnect {
+            debug!("tcp sink client disconnected");
+            io.call_again = true;
+            Ok(())
+        } else {
+            bail!("tcp sink socket error")
+        }
+    }
+}
+
+/// Build a [TcpSink].
+pub struct TcpSinkBuilder<T> {
+    bind: String,
+    reconnect: bool,
+    policy: TcpBufferPolicy,
+    security: TcpSecurity,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> TcpSinkBuilder<T> {
+    /// Create TcpSink builder
+    pub fn new(bind: impl Into<String>) -> TcpSinkBuilder<T> {
+        TcpSinkBuilder {
+            bind: bind.into(),
+            reconnect: false,
+            policy: TcpBufferPolicy::Block,
+            security: TcpSecurity::default(),
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Accept a new client after the connection is lost, instead of failing
+    #[must_use]
+    pub fn reconnect(mut self, reconnect: bool) -> TcpSinkBuilder<T> {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Set what happens with samples while no client is connected
+    #[must_use]
+    pub fn buffer_policy(mut self, policy: TcpBufferPolicy) -> TcpSinkBuilder<T> {
+        self.policy = policy;
+        self
+    }
+
+    /// Only accept clients that send the given token, followed by a newline
+    ///
+    /// **Without TLS (`tls` feature), the token is sent in the clear** and can be read by anyone
+    /// on the network path. It only keeps out clients that connect by accident.
+    #[must_use]
+    pub fn token(mut self, token: impl Into<String>) -> TcpSinkBuilder<T> {
+        self.security.token = Some(token.into());
+        self
+    }
+
+    /// Use TLS
+    #[cfg(feature = "tls")]
+    #[must_use]
+    pub fn tls(mut self, tls: TcpTlsConfig) -> TcpSinkBuilder<T> {
+        self.security.tls = Some(tls);
+        self
+    }
+
+    /// Build TcpSink
+    pub fn build(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TcpSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            TcpSink {
+                bind: self.bind,
+                acceptor: None,
+                socket: None,
+                reconnect: self.reconnect,
+                policy: self.policy,
+                security: self.security,
+                buffer: VecDeque::new(),
+                _type: std::marker::PhantomData::<T>,
+            },
+        )
+    }
+}
@@ -1,7 +1,11 @@
-use async_net::{TcpListener, TcpStream};
 use futures::AsyncReadExt;
 
 use crate::anyhow::{Context, Result};
+use crate::blocks::tcp::Acceptor;
+use crate::blocks::tcp::Connection;
+use crate::blocks::tcp::TcpSecurity;
+#[cfg(feature = "tls")]
+use crate::blocks::TcpTlsConfig;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -13,27 +17,24 @@ use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
 /// Read samples from a TCP socket.
+///
+/// The source reads samples from the first client that connects and terminates when the
+/// connection is closed. Use the [`TcpSourceBuilder`] to accept new clients instead and to
+/// secure the connection. Clients are authenticated on a separate thread, so the block keeps
+/// running during the handshake.
 pub struct TcpSource<T: Send + 'static> {
     bind: String,
-    listener: Option<TcpListener>,
-    socket: Option<TcpStream>,
+    acceptor: Option<Acceptor>,
+    socket: Option<Box<dyn Connection>>,
+    reconnect: bool,
+    security: TcpSecurity,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> TcpSource<T> {
     /// Create TCP Source block
     pub fn new(bind: impl Into<String>) -> Block {
-        Block::new(
-            BlockMetaBuilder::new("TcpSource").build(),
-            StreamIoBuilder::new().add_output::<T>("out").build(),
-            MessageIoBuilder::new().build(),
-            TcpSource {
-                bind: bind.into(),
-                listener: None,
-                socket: None,
-                _type: std::marker::PhantomData::<T>,
-            },
-        )
+        TcpSourceBuilder::<T>::new(bind).build()
     }
 }
 
@@ -48,14 +49,17 @@ impl<T: Send + 'static> Kernel for TcpSource<T> {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         if self.socket.is_none() {
-            let (socket, _) = self
-                .listener
-                .as_mut()
-                .context("no listener")?
-                .accept()
-                .await?;
-            self.socket = Some(socket);
-            debug!("tcp source accepted connection");
+            let acceptor = self.acceptor.as_ref().context("no listener")?;
+            match acceptor.try_next() {
+                Some((socket, addr)) => {
+                    debug!("tcp source accepted connection from {}", addr);
+                    self.socket = Some(socket);
+                }
+                None => {
+                    io.block_on(acceptor.ready());
+                    return Ok(());
+                }
+            }
         }
 
         let out = sio.output(0).slice_unchecked::<u8>();
@@ -63,20 +67,34 @@ impl<T: Send + 'static> Kernel for TcpSource<T> {
             return Ok(());
         }
 
-        match self
-            .socket
-            .as_mut()
-            .context("no socket")?
-            .read_exact(out)
-            .await
-        {
-            Ok(_) => {
-                debug!("tcp source read bytes {}", out.len());
-                sio.output(0).produce(out.len() / std::mem::size_of::<T>());
+        let item_size = std::mem::size_of::<T>();
+        let socket = self.socket.as_mut().context("no socket")?;
+        let n = match socket.read(out).await {
+            Ok(n) if n > 0 => {
+                // complete partially received items
+                let missing = (item_size - n % item_size) % item_size;
+                socket
+                    .read_exact(&mut out[n..n + missing])
+                    .await
+                    .map(|_| n + missing)
+                    .ok()
             }
-            Err(_) => {
+            _ => None,
+        };
+
+        match n {
+            Some(n) => {
+                debug!("tcp source read bytes {}", n);
+                sio.output(0).produce(n / item_size);
+            }
+            None => {
                 debug!("tcp source socket closed");
-                io.finished = true;
+                self.socket = None;
+                if self.reconnect {
+                    io.call_again = true;
+                } else {
+                    io.finished = true;
+                }
             }
         }
 
@@ -89,7 +107,73 @@ impl<T: Send + 'static> Kernel for TcpSource<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        self.listener = Some(TcpListener::bind(self.bind.clone()).await?);
+        self.acceptor = Some(Acceptor::bind(
+            &self.bind,
+            self.security.clone(),
+            "tcp source",
+        )?);
         Ok(())
     }
 }
+
+/// Build a [TcpSource].
+pub struct TcpSourceBuilder<T> {
+    bind: String,
+    reconnect: bool,
+    security: TcpSecurity,
+    _type: std::marker::PhantomData<T>,
+}
+
+impl<T: Send + 'static> TcpSourceBuilder<T> {
+    /// Create TcpSource builder
+    pub fn new(bind: impl Into<String>) -> TcpSourceBuilder<T> {
+        TcpSourceBuilder {
+            bind: bind.into(),
+            reconnect: false,
+            security: TcpSecurity::default(),
+            _type: std::marker::PhantomData,
+        }
+    }
+
+    /// Wait for a new client after the connection is closed, instead of terminating
+    #[must_use]
+    pub fn reconnect(mut self, reconnect: bool) -> TcpSourceBuilder<T> {
+        self.reconnect = reconnect;
+        self
+    }
+
+    /// Only accept clients that send the given token, followed by a newline
+    ///
+    /// **Without TLS (`tls` feature), the token is sent in the clear** and can be read by anyone
+    /// on the network path. It only keeps out clients that connect by accident.
+    #[must_use]
+    pub fn token(mut self, token: impl Into<String>) -> TcpSourceBuilder<T> {
+        self.security.token = Some(token.into());
+        self
+    }
+
+    /// Use TLS
+    #[cfg(feature = "tls")]
+    #[must_use]
+    pub fn tls(mut self, tls: TcpTlsConfig) -> TcpSourceBuilder<T> {
+        self.security.tls = Some(tls);
+        self
+    }
+
+    /// Build TcpSource
+    pub fn build(self) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TcpSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            TcpSource {
+                bind: self.bind,
+                acceptor: None,
+                socket: None,
+                reconnect: self.reconnect,
+                security: self.security,
+                _type: std::marker::PhantomData::<T>,
+            },
+        )
+    }
+}
@@ -25,6 +25,9 @@ use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
 /// Push Samples from PMTs in a WebSocket.
+///
+/// [`Pmt::VecCF32`] samples are sent as binary messages with interleaved little-endian `f32`
+/// values. All other PMTs are sent as JSON text messages.
 pub struct WebsocketPmtSink {
     port: u32,
     listener: Option<Arc<Async<TcpListener>>>,
@@ -81,7 +84,7 @@ impl Kernel for WebsocketPmtSink {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         if let Some(ref mut conn) = self.conn {
-            match self.pmts.pop_front() {
+            let msg = match self.pmts.pop_front() {
                 Some(Pmt::VecCF32(v)) => {
                     let v: Vec<u8> = v
                         .into_iter()
@@ -92,35 +95,41 @@ impl Kernel for WebsocketPmtSink {
                             b
                         })
                         .collect();
-                    if !v.is_empty() {
-                        let acc = Box::pin(self.listener.as_ref().context("no listener")?.accept());
-                        let send = conn.send(Message::Binary(v));
-
-                        match future::select(acc, send).await {
-                            Either::Left((a, _)) => {
-                                if let Ok((stream, _)) = a {
-                                    self.conn = Some(WsStream {
-                                        inner: async_tungstenite::accept_async(stream).await?,
-                                    });
-                                }
-                            }
-                            Either::Right((s, _)) => {
-                                if s.is_err() {
-                                    debug!("websocket: client disconnected");
-                                    self.conn = None;
-                                }
-                            }
+                    (!v.is_empty()).then_some(Message::Binary(v))
+                }
+                Some(p) => match serde_json::to_string(&p) {
+                    Ok(s) => Some(Message::Text(s)),
+                    Err(_) => {
+                        warn!("WebsocketPmtSink: cannot serialize PMT {:?}", p);
+                        None
+                    }
+                },
+                _ => None,
+            };
+
+            if let Some(msg) = msg {
+                let acc = Box::pin(self.listener.as_ref().context("no listener")?.accept());
+                let send = conn.send(msg);
+
+                match future::select(acc, send).await {
+                    Either::Left((a, _)) => {
+                        if let Ok((stream, _)) = a {
+                            self.conn = Some(WsStream {
+                                inner: async_tungstenite::accept_async(stream).await?,
+                            });
                         }
-
-                        if !self.pmts.is_empty() {
-                            io.call_again = true;
+                    }
+                    Either::Right((s, _)) => {
+                        if s.is_err() {
+                            debug!("websocket: client disconnected");
+                            self.conn = None;
                         }
                     }
                 }
-                Some(p) => {
-                    warn!("WebsocketPmtSink: wrong PMT type {:?}", p);
-                }
-                _ => {}
+            }
+
+            if !self.pmts.is_empty() {
+                io.call_again = true;
             }
         } else if let Ok((stream, socket)) = self
             .listener
@@ -1,4 +1,5 @@
 use async_io::Async;
+use async_io::Timer;
 use async_tungstenite::tungstenite::Message;
 use async_tungstenite::WebSocketStream;
 use futures::future;
@@ -11,6 +12,8 @@ use std::net::{SocketAddr, TcpListener, TcpStream};
 use std::pin::Pin;
 use std::sync::Arc;
 use std::task::{Context, Poll};
+use std::time::Duration;
+use std::time::Instant;
 
 use crate::anyhow::Context as _;
 use crate::anyhow::Result;
@@ -40,12 +43,19 @@ pub struct WebsocketSink<T> {
     listener: Option<Arc<Async<TcpListener>>>,
     conn: Option<WsStream>,
     mode: WebsocketSinkMode,
+    interval: Option<Duration>,
+    t_last: Option<Instant>,
     _p: PhantomData<T>,
 }
 
 impl<T: Send + Sync + 'static> WebsocketSink<T> {
     /// Create WebsocketSink block
     pub fn new(port: u32, mode: WebsocketSinkMode) -> Block {
+        Self::with_interval(port, mode, None)
+    }
+
+    /// Create WebsocketSink block, sending at most one message per `interval`
+    pub fn with_interval(port: u32, mode: WebsocketSinkMode, interval: Option<Duration>) -> Block {
         Block::new(
             BlockMetaBuilder::new("WebsocketSink").build(),
             StreamIoBuilder::new().add_input::<T>("in").build(),
@@ -55,6 +65,8 @@ impl<T: Send + Sync + 'static> WebsocketSink<T> {
                 listener: None,
                 conn: None,
                 mode,
+                interval,
+                t_last: None,
                 _p: PhantomData,
             },
         )
@@ -86,6 +98,20 @@ impl<T: Send + Sync + 'static> Kernel for WebsocketSink<T> {
                 return Ok(());
             }
 
+            if let (Some(interval), Some(t_last)) = (self.interval, self.t_last) {
+                let next = t_last + interval;
+                if Instant::now() < next {
+                    if let WebsocketSinkMode::FixedDropping(block_size) = &self.mode {
+                        let n = items / block_size;
+                        sio.input(0).consume(n * block_size);
+                    }
+                    io.block_on(async move {
+                        Timer::at(next).await;
+                    });
+                    return Ok(());
+                }
+            }
+
             let mut v = Vec::new();
 
             match &self.mode {
@@ -111,6 +137,7 @@ impl<T: Send + Sync + 'static> Kernel for WebsocketSink<T> {
             }
 
             if !v.is_empty() {
+                self.t_last = Some(Instant::now());
                 let acc = Box::pin(self.listener.as_ref().context("no listener")?.accept());
                 let send = conn.send(Message::Binary(v));
 
@@ -174,6 +201,7 @@ impl<T: Send + Sync + 'static> Kernel for WebsocketSink<T> {
 pub struct WebsocketSinkBuilder<T> {
     port: u32,
     mode: WebsocketSinkMode,
+    interval: Option<Duration>,
     _p: PhantomData<T>,
 }
 
@@ -183,6 +211,7 @@ impl<T: Send + Sync + 'static> WebsocketSinkBuilder<T> {
         WebsocketSinkBuilder {
             port,
             mode: WebsocketSinkMode::Blocking,
+            interval: None,
             _p: PhantomData,
         }
     }
@@ -194,9 +223,19 @@ impl<T: Send + Sync + 'static> WebsocketSinkBuilder<T> {
         self
     }
 
+    /// Limit the update rate to one message per `interval`
+    ///
+    /// In [`WebsocketSinkMode::FixedDropping`] mode, chunks that arrive in between are dropped.
+    /// In the blocking modes, the sink applies backpressure.
+    #[must_use]
+    pub fn interval(mut self, interval: Duration) -> WebsocketSinkBuilder<T> {
+        self.interval = Some(interval);
+        self
+    }
+
     /// Build WebsocketSink
     pub fn build(self) -> Block {
-        WebsocketSink::<T>::new(self.port, self.mode)
+        WebsocketSink::<T>::with_interval(self.port, self.mode, self.interval)
     }
 }
 
@@ -0,0 +1,334 @@
+use futuredsp::fir::PolyphaseResamplingFirKernel;
+use futuredsp::firdes;
+use futuredsp::UnaryKernel;
+
+use crate::anyhow::{bail, Result};
+use crate::blocks::signal_source::NCO;
+use crate::num_complex::Complex32;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Frequency-translating FIR filter.
+pub struct XlatingFir {
+    taps: Vec<f32>,
+    decimation: usize,
+    sample_rate: f64,
+    offset: f64,
+    bandwidth: Option<f64>,
+    kernel: PolyphaseResamplingFirKernel<Complex32, Complex32, Vec<Complex32>, Complex32>,
+    nco: NCO,
+}
+
+impl XlatingFir {
+    /// Create XlatingFir block
+    pub fn new(taps: Vec<f32>, decimation: usize, offset: f64, sample_rate: f64) -> Block {
+        Self::with_bandwidth(taps, decimation, offset, sample_rate, None)
+    }
+
+    fn with_bandwidth(
+        taps: Vec<f32>,
+        decimation: usize,
+        offset: f64,
+        sample_rate: f64,
+        bandwidth: Option<f64>,
+    ) -> Block {
+        assert!(!taps.is_empty());
+        assert!(decimation > 0);
+        let (kernel, nco) = Self::design(&taps, decimation, offset, sample_rate);
+        Block::new(
+            BlockMetaBuilder::new("XlatingFir").build(),
+            StreamIoBuilder::new()
+                .add_input::<Complex32>("in")
+                .add_output::<Complex32>("out")
+                .build(),
+            MessageIoBuilder::new()
+                .add_input("freq", Self::freq_handler)
+                .add_input("bandwidth", Self::bandwidth_handler)
+                .add_input("sample_rate", Self::sample_rate_handler)
+                .build(),
+            XlatingFir {
+                taps,
+                decimation,
+                sample_rate,
+                offset,
+                bandwidth,
+                kernel,
+                nco,
+            },
+        )
+    }
+
+    /// Rotate the lowpass to a bandpass at `offset` and set up the NCO that mixes the filtered
+    /// samples down to baseband.
+    ///
+    /// An output, computed over the inputs `x[s..s + N]`, is
+    /// `exp(-jws) * sum_t x[s + t] * h[N - 1 - t] * exp(-jwt)`, i.e., the NCO advances by
+    /// `-w * decimation` per output.
+    fn design(
+        taps: &[f32],
+        decimation: usize,
+        offset: f64,
+        sample_rate: f64,
+    ) -> (
+        PolyphaseResamplingFirKernel<Complex32, Complex32, Vec<Complex32>, Complex32>,
+        NCO,
+    ) {
+        let w = 2.0 * std::f64::consts::PI * offset / sample_rate;
+        let n = taps.len();
+        let bandpass: Vec<Complex32> = taps
+            .iter()
+            .enumerate()
+            .map(|(j, h)| {
+                let phi = -w * (n - 1 - j) as f64;
+                Complex32::new(phi.cos() as f32, phi.sin() as f32) * *h
+            })
+            .collect();
+        let nco = NCO::new(0.0, (-w * decimation as f64) as f32);
+        (
+            PolyphaseResamplingFirKernel::new(1, decimation, bandpass),
+            nco,
+        )
+    }
+
+    /// Lowpass for a channel of `bandwidth` Hz, passing up to half the bandwidth with the
+    /// stopband at half the output rate
+    fn lowpass(decimation: usize, sample_rate: f64, bandwidth: f64) -> Result<Vec<f32>> {
+        let out_rate = sample_rate / decimation as f64;
+        if bandwidth <= 0.0 || bandwidth > out_rate {
+            bail!("bandwidth has to be between 0 and the output sample rate");
+        }
+        let transition =
+            ((out_rate - bandwidth) / 2.0).clamp(0.05 * out_rate, 0.2 * out_rate) / sample_rate;
+        let cutoff = (bandwidth / 2.0 / sample_rate).min(0.49 / decimation as f64 - transition);
+        Ok(firdes::kaiser::lowpass::<f32>(cutoff, transition, 0.0001))
+    }
+
+    /// Redesign the filter, keeping the phase of the NCO
+    fn redesign(&mut self) {
+        let phase = self.nco.phase;
+        let (kernel, mut nco) =
+            Self::design(&self.taps, self.decimation, self.offset, self.sample_rate);
+        nco.phase = phase;
+        self.kernel = kernel;
+        self.nco = nco;
+    }
+
+    fn frequency(p: &Pmt) -> Option<f64> {
+        match p {
+            Pmt::F32(f) => Some(*f as f64),
+            Pmt::F64(f) => Some(*f),
+            Pmt::U32(f) => Some(*f as f64),
+            Pmt::U64(f) => Some(*f as f64),
+            _ => None,
+        }
+    }
+
+    #[message_handler]
+    async fn freq_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Pmt::Null = p {
+            return Ok(Pmt::F64(self.offset));
+        }
+        match Self::frequency(&p) {
+            Some(offset) => self.offset = offset,
+            None => return Ok(Pmt::InvalidValue),
+        }
+        self.redesign();
+        Ok(Pmt::Ok)
+    }
+
+    #[message_handler]
+    async fn bandwidth_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Pmt::Null = p {
+            return Ok(self.bandwidth.map(Pmt::F64).unwrap_or(Pmt::Null));
+        }
+        let bandwidth = match Self::frequency(&p) {
+            Some(b) => b,
+            None => return Ok(Pmt::InvalidValue),
+        };
+        match Self::lowpass(self.decimation, self.sample_rate, bandwidth) {
+            Ok(taps) => {
+                self.taps = taps;
+                self.bandwidth = Some(bandwidth);
+                self.redesign();
+                Ok(Pmt::Ok)
+            }
+            Err(_) => Ok(Pmt::InvalidValue),
+        }
+    }
+
+    #[message_handler]
+    async fn sample_rate_handler(
+        &mut self,
+        _io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        if let Pmt::Null = p {
+            return Ok(Pmt::F64(self.sample_rate));
+        }
+        let sample_rate = match Self::frequency(&p) {
+            Some(s) if s > 0.0 => s,
+            _ => return Ok(Pmt::InvalidValue),
+        };
+        // with a designed lowpass, keep the bandwidth, limited to 80% of the new output rate
+        if let Some(bandwidth) = self.bandwidth {
+            let bandwidth = bandwidth.min(0.8 * sample_rate / self.decimation as f64);
+            self.taps = Self::lowpass(self.decimation, sample_rate, bandwidth)?;
+            self.bandwidth = Some(bandwidth);
+        }
+        self.sample_rate = sample_rate;
+        self.redesign();
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for XlatingFir {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        let o = sio.output(0).slice::<Complex32>();
+
+        let (consumed, produced, status) = self.kernel.work(i, o);
+        for v in o[..produced].iter_mut() {
+            *v *= Complex32::new(self.nco.phase.cos(), self.nco.phase.sin());
+            self.nco.step();
+        }
+
+        sio.input(0).consume(consumed);
+        sio.output(0).produce(produced);
+
+        if sio.input(0).finished() && status.produced_all_samples() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+/// Build a [XlatingFir] filter.
+///
+/// Shifts the channel at `offset` Hz to baseband, filters it, and decimates it, like the
+/// `Frequency Xlating FIR Filter` of GNU Radio. Instead of mixing every input sample, the
+/// lowpass is rotated to a bandpass around the offset and only the decimated output is mixed.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Input samples
+///
+/// **Message** `freq`: Set the offset in Hz ([`Pmt::F32`], [`Pmt::F64`], [`Pmt::U32`],
+/// [`Pmt::U64`]). [`Pmt::Null`] returns the current offset.
+///
+/// **Message** `bandwidth`: Redesign the lowpass for a channel bandwidth in Hz (same types as
+/// `freq`). [`Pmt::Null`] returns the current bandwidth or [`Pmt::Null`] for custom taps.
+///
+/// **Message** `sample_rate`: Set the input sample rate in Hz (same types as `freq`). A
+/// designed lowpass keeps its bandwidth, as long as it fits into the new output rate.
+/// [`Pmt::Null`] returns the current sample rate.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Channel at baseband, decimated by `decimation`
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::XlatingFirBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// // select the 125 kHz channel at -250 kHz of a 1 MHz capture
+/// let xlating = fg.add_block(
+///     XlatingFirBuilder::new(4, -250e3, 1e6)
+///         .bandwidth(125e3)
+///         .build()
+///         .unwrap(),
+/// );
+/// ```
+pub struct XlatingFirBuilder {
+    decimation: usize,
+    offset: f64,
+    sample_rate: f64,
+    bandwidth: Option<f64>,
+    taps: Option<Vec<f32>>,
+}
+
+impl XlatingFirBuilder {
+    /// Create XlatingFir builder, shifting the channel at `offset` Hz of a signal sampled at
+    /// `sample_rate` Hz to baseband
+    pub fn new(decimation: usize, offset: f64, sample_rate: f64) -> XlatingFirBuilder {
+        XlatingFirBuilder {
+            decimation,
+            offset,
+            sample_rate,
+            bandwidth: None,
+            taps: None,
+        }
+    }
+    /// Bandwidth of the channel in Hz, used to design the lowpass
+    /// (default: 80% of the output sample rate)
+    #[must_use]
+    pub fn bandwidth(mut self, bandwidth: f64) -> XlatingFirBuilder {
+        self.bandwidth = Some(bandwidth);
+        self
+    }
+    /// Taps of the lowpass, overriding the bandwidth
+    #[must_use]
+    pub fn taps(mut self, taps: Vec<f32>) -> XlatingFirBuilder {
+        self.taps = Some(taps);
+        self
+    }
+    /// Build XlatingFir block
+    pub fn build(self) -> Result<Block> {
+        if self.decimation == 0 {
+            bail!("decimation has to be positive");
+        }
+        if self.sample_rate <= 0.0 {
+            bail!("sample rate has to be positive");
+        }
+        let (taps, bandwidth) = match self.taps {
+            Some(t) if t.is_empty() => bail!("no taps"),
+            Some(t) => (t, None),
+            None => {
+                let out_rate = self.sample_rate / self.decimation as f64;
+                let bandwidth = self.bandwidth.unwrap_or(0.8 * out_rate);
+                let taps = XlatingFir::lowpass(self.decimation, self.sample_rate, bandwidth)?;
+                (taps, Some(bandwidth))
+            }
+        };
+        Ok(XlatingFir::with_bandwidth(
+            taps,
+            self.decimation,
+            self.offset,
+            self.sample_rate,
+            bandwidth,
+        ))
+    }
+}
@@ -0,0 +1,150 @@
+use crate::anyhow::Result;
+use crate::blocks::zeromq::pmt;
+use crate::blocks::zeromq::socket;
+use crate::blocks::zeromq::ZmqPattern;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Pmt;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Send [Pmts](Pmt) through a [ZeroMQ](https://zeromq.org/) PUB or PUSH socket.
+pub struct ZmqMessageSink {
+    address: String,
+    pattern: ZmqPattern,
+    bind: bool,
+    socket: Option<zmq::Socket>,
+}
+
+impl ZmqMessageSink {
+    /// Create ZmqMessageSink block
+    pub fn new(address: impl Into<String>, pattern: ZmqPattern, bind: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ZmqMessageSink").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new()
+                .add_input("in", Self::handler)
+                .build(),
+            ZmqMessageSink {
+                address: address.into(),
+                pattern,
+                bind,
+                socket: None,
+            },
+        )
+    }
+
+    #[message_handler]
+    async fn handler(
+        &mut self,
+        io: &mut WorkIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+        p: Pmt,
+    ) -> Result<Pmt> {
+        match p {
+            Pmt::Finished => {
+                io.finished = true;
+            }
+            p => match pmt::serialize(&p) {
+                Ok(b) => self.socket.as_mut().unwrap().send(b, 0)?,
+                Err(e) => {
+                    warn!("ZmqMessageSink: {}", e);
+                    return Ok(Pmt::InvalidValue);
+                }
+            },
+        }
+        Ok(Pmt::Ok)
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ZmqMessageSink {
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.socket = Some(socket(self.pattern.sink_type(), &self.address, self.bind)?);
+        Ok(())
+    }
+}
+
+/// Build a [ZmqMessageSink].
+///
+/// Messages are serialized in the GNU Radio [PMT format](pmt), compatible with the GNU Radio
+/// `ZMQ PUB Message Sink` and `ZMQ PUSH Message Sink` blocks.
+///
+/// # Inputs
+///
+/// **Message** `in`: [Pmts](Pmt) to send, [`Pmt::Finished`] finishes the block. Returns
+/// [`Pmt::InvalidValue`] for [Pmts](Pmt) that cannot be serialized.
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::zeromq::ZmqMessageSinkBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     ZmqMessageSinkBuilder::new()
+///         .address("tcp://*:5556")
+///         .build(),
+/// );
+/// ```
+pub struct ZmqMessageSinkBuilder {
+    address: String,
+    pattern: ZmqPattern,
+    bind: bool,
+}
+
+impl ZmqMessageSinkBuilder {
+    /// Create ZmqMessageSink builder
+    pub fn new() -> ZmqMessageSinkBuilder {
+        ZmqMessageSinkBuilder {
+            address: "tcp://*:5555".into(),
+            pattern: ZmqPattern::PubSub,
+            bind: true,
+        }
+    }
+
+    /// Socket address (default: `tcp://*:5555`)
+    #[must_use]
+    pub fn address(mut self, address: &str) -> ZmqMessageSinkBuilder {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Messaging pattern (default: [ZmqPattern::PubSub])
+    #[must_use]
+    pub fn pattern(mut self, pattern: ZmqPattern) -> ZmqMessageSinkBuilder {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Bind to the address or connect to it (default: bind)
+    #[must_use]
+    pub fn bind(mut self, bind: bool) -> ZmqMessageSinkBuilder {
+        self.bind = bind;
+        self
+    }
+
+    /// Build ZmqMessageSink
+    pub fn build(self) -> Block {
+        ZmqMessageSink::new(self.address, self.pattern, self.bind)
+    }
+}
+
+impl Default for ZmqMessageSinkBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
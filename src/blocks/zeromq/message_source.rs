@@ -0,0 +1,150 @@
+use crate::anyhow::Result;
+use crate::blocks::zeromq::pmt;
+use crate::blocks::zeromq::socket;
+use crate::blocks::zeromq::ZmqPattern;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::WorkIo;
+
+/// Timeout of a poll on the socket, after which the block checks its inbox
+const POLL_TIMEOUT_MS: i64 = 100;
+
+/// Receive [Pmts](crate::runtime::Pmt) from a [ZeroMQ](https://zeromq.org/) SUB or PULL socket.
+pub struct ZmqMessageSource {
+    address: String,
+    pattern: ZmqPattern,
+    bind: bool,
+    socket: Option<zmq::Socket>,
+}
+
+impl ZmqMessageSource {
+    /// Create ZmqMessageSource block
+    pub fn new(address: impl Into<String>, pattern: ZmqPattern, bind: bool) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("ZmqMessageSource").blocking().build(),
+            StreamIoBuilder::new().build(),
+            MessageIoBuilder::new().add_output("out").build(),
+            ZmqMessageSource {
+                address: address.into(),
+                pattern,
+                bind,
+                socket: None,
+            },
+        )
+    }
+}
+
+#[doc(hidden)]
+#[async_trait]
+impl Kernel for ZmqMessageSource {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        _sio: &mut StreamIo,
+        mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let socket = self.socket.as_mut().unwrap();
+        if socket.poll(zmq::POLLIN, POLL_TIMEOUT_MS)? > 0 {
+            let msg = socket.recv_msg(0)?;
+            match pmt::deserialize(&msg) {
+                Ok(p) => mio.post(0, p).await,
+                Err(e) => warn!("ZmqMessageSource: dropping message ({})", e),
+            }
+        }
+        io.call_again = true;
+        Ok(())
+    }
+
+    async fn init(
+        &mut self,
+        _sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        self.socket = Some(socket(
+            self.pattern.source_type(),
+            &self.address,
+            self.bind,
+        )?);
+        Ok(())
+    }
+}
+
+/// Build a [ZmqMessageSource].
+///
+/// Expects messages in the GNU Radio [PMT format](pmt), as sent by the GNU Radio
+/// `ZMQ PUB Message Sink` and `ZMQ PUSH Message Sink` blocks. Messages that cannot be
+/// deserialized are dropped.
+///
+/// # Outputs
+///
+/// **Message** `out`: Received [Pmts](crate::runtime::Pmt)
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::zeromq::ZmqMessageSourceBuilder;
+/// use futuresdr::runtime::Flowgraph;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     ZmqMessageSourceBuilder::new()
+///         .address("tcp://127.0.0.1:5556")
+///         .build(),
+/// );
+/// ```
+pub struct ZmqMessageSourceBuilder {
+    address: String,
+    pattern: ZmqPattern,
+    bind: bool,
+}
+
+impl ZmqMessageSourceBuilder {
+    /// Create ZmqMessageSource builder
+    pub fn new() -> ZmqMessageSourceBuilder {
+        ZmqMessageSourceBuilder {
+            address: "tcp://127.0.0.1:5555".into(),
+            pattern: ZmqPattern::PubSub,
+            bind: false,
+        }
+    }
+
+    /// Socket address (default: `tcp://127.0.0.1:5555`)
+    #[must_use]
+    pub fn address(mut self, address: &str) -> ZmqMessageSourceBuilder {
+        self.address = address.to_string();
+        self
+    }
+
+    /// Messaging pattern (default: [ZmqPattern::PubSub])
+    #[must_use]
+    pub fn pattern(mut self, pattern: ZmqPattern) -> ZmqMessageSourceBuilder {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Bind to the address or connect to it (default: connect)
+    #[must_use]
+    pub fn bind(mut self, bind: bool) -> ZmqMessageSourceBuilder {
+        self.bind = bind;
+        self
+    }
+
+    /// Build ZmqMessageSource
+    pub fn build(self) -> Block {
+        ZmqMessageSource::new(self.address, self.pattern, self.bind)
+    }
+}
+
+impl Default for ZmqMessageSourceBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
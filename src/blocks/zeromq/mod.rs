@@ -1,6 +1,64 @@
 //! ## [ZeroMQ](https://zeromq.org/) Blocks
+//!
+//! [PubSink] and [SubSource] exchange samples, [ZmqMessageSink] and [ZmqMessageSource]
+//! exchange [Pmts](crate::runtime::Pmt). Both are compatible with the ZeroMQ blocks of GNU
+//! Radio: streams are sent as raw items without tag headers, messages are serialized in the
+//! GNU Radio [PMT format](pmt).
 mod pub_sink;
 pub use pub_sink::{PubSink, PubSinkBuilder};
 
 mod sub_source;
 pub use sub_source::{SubSource, SubSourceBuilder};
+
+pub mod pmt;
+
+mod message_sink;
+pub use message_sink::{ZmqMessageSink, ZmqMessageSinkBuilder};
+
+mod message_source;
+pub use message_source::{ZmqMessageSource, ZmqMessageSourceBuilder};
+
+use crate::anyhow::Result;
+
+/// ZeroMQ messaging pattern
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ZmqPattern {
+    /// Publish/subscribe: every subscriber receives all data, data is dropped without subscriber
+    #[default]
+    PubSub,
+    /// Push/pull: data is distributed among the peers, sending blocks without peer
+    PushPull,
+}
+
+impl ZmqPattern {
+    fn sink_type(&self) -> zmq::SocketType {
+        match self {
+            ZmqPattern::PubSub => zmq::PUB,
+            ZmqPattern::PushPull => zmq::PUSH,
+        }
+    }
+
+    fn source_type(&self) -> zmq::SocketType {
+        match self {
+            ZmqPattern::PubSub => zmq::SUB,
+            ZmqPattern::PushPull => zmq::PULL,
+        }
+    }
+}
+
+/// Create a socket that binds to or connects to `address`
+fn socket(t: zmq::SocketType, address: &str, bind: bool) -> Result<zmq::Socket> {
+    let context = zmq::Context::new();
+    let socket = context.socket(t)?;
+    if bind {
+        info!("ZeroMQ binding to {:?}", address);
+        socket.bind(address)?;
+    } else {
+        info!("ZeroMQ connecting to {:?}", address);
+        socket.connect(address)?;
+    }
+    if t == zmq::SUB {
+        socket.set_subscribe(b"")?;
+    }
+    Ok(socket)
+}
@@ -0,0 +1,287 @@
+//! [GNU Radio](https://www.gnuradio.org/) PMT serialization, as used by its ZeroMQ message blocks
+use num_complex::Complex32;
+use std::collections::HashMap;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::Pmt;
+
+const PST_TRUE: u8 = 0x00;
+const PST_FALSE: u8 = 0x01;
+const PST_SYMBOL: u8 = 0x02;
+const PST_INT32: u8 = 0x03;
+const PST_DOUBLE: u8 = 0x04;
+const PST_NULL: u8 = 0x06;
+const PST_PAIR: u8 = 0x07;
+const PST_VECTOR: u8 = 0x08;
+const PST_UNIFORM_VECTOR: u8 = 0x0a;
+const PST_UINT64: u8 = 0x0b;
+const PST_INT64: u8 = 0x0d;
+
+const UVI_U8: u8 = 0x00;
+const UVI_U64: u8 = 0x06;
+const UVI_F32: u8 = 0x08;
+const UVI_C32: u8 = 0x0a;
+
+/// Serialize a [`Pmt`] in the GNU Radio format
+///
+/// Integers are sent as GNU Radio integers (`U32`) or unsigned 64-bit integers (`U64`,
+/// `Usize`), floating point numbers as doubles, strings as symbols, and [`Pmt::MapStrPmt`] as
+/// dictionaries. [`Pmt::VecPmt`] with two elements, where the first is a map or null, is sent as
+/// pair, i.e., as GNU Radio PDU, if the second is a [`Pmt::Blob`].
+pub fn serialize(p: &Pmt) -> Result<Vec<u8>> {
+    let mut v = Vec::new();
+    write(p, &mut v)?;
+    Ok(v)
+}
+
+/// Deserialize a [`Pmt`] from the GNU Radio format
+///
+/// Negative integers are converted to [`Pmt::F64`]. Pairs that are no dictionaries are mapped
+/// to a [`Pmt::VecPmt`] with two elements, i.e., a PDU becomes a vector of the metadata and the
+/// [`Pmt::Blob`].
+pub fn deserialize(b: &[u8]) -> Result<Pmt> {
+    let mut r = Reader { b, pos: 0 };
+    let node = r.read()?;
+    if r.pos != b.len() {
+        bail!("trailing bytes after PMT");
+    }
+    Ok(node.into_pmt())
+}
+
+fn write_uniform_header(t: u8, len: usize, v: &mut Vec<u8>) {
+    v.push(PST_UNIFORM_VECTOR);
+    v.push(t);
+    v.extend_from_slice(&(len as u32).to_be_bytes());
+    // one padding byte
+    v.push(1);
+    v.push(0);
+}
+
+fn write(p: &Pmt, v: &mut Vec<u8>) -> Result<()> {
+    match p {
+        Pmt::Null => v.push(PST_NULL),
+        Pmt::Bool(true) => v.push(PST_TRUE),
+        Pmt::Bool(false) => v.push(PST_FALSE),
+        Pmt::String(s) => {
+            if s.len() > u16::MAX as usize {
+                bail!("string too long for PMT symbol");
+            }
+            v.push(PST_SYMBOL);
+            v.extend_from_slice(&(s.len() as u16).to_be_bytes());
+            v.extend_from_slice(s.as_bytes());
+        }
+        Pmt::U32(i) => {
+            if *i <= i32::MAX as u32 {
+                v.push(PST_INT32);
+                v.extend_from_slice(&i.to_be_bytes());
+            } else {
+                v.push(PST_INT64);
+                v.extend_from_slice(&(*i as u64).to_be_bytes());
+            }
+        }
+        Pmt::U64(i) => {
+            v.push(PST_UINT64);
+            v.extend_from_slice(&i.to_be_bytes());
+        }
+        Pmt::Usize(i) => {
+            v.push(PST_UINT64);
+            v.extend_from_slice(&(*i as u64).to_be_bytes());
+        }
+        Pmt::F32(f) => {
+            v.push(PST_DOUBLE);
+            v.extend_from_slice(&(*f as f64).to_be_bytes());
+        }
+        Pmt::F64(f) => {
+            v.push(PST_DOUBLE);
+            v.extend_from_slice(&f.to_be_bytes());
+        }
+        Pmt::Blob(b) => {
+            write_uniform_header(UVI_U8, b.len(), v);
+            v.extend_from_slice(b);
+        }
+        Pmt::VecU64(x) => {
+            write_uniform_header(UVI_U64, x.len(), v);
+            for i in x {
+                v.extend_from_slice(&i.to_be_bytes());
+            }
+        }
+        Pmt::VecF32(x) => {
+            write_uniform_header(UVI_F32, x.len(), v);
+            for f in x {
+                v.extend_from_slice(&(*f as f64).to_be_bytes());
+            }
+        }
+        Pmt::VecCF32(x) => {
+            write_uniform_header(UVI_C32, x.len(), v);
+            for c in x {
+                v.extend_from_slice(&(c.re as f64).to_be_bytes());
+                v.extend_from_slice(&(c.im as f64).to_be_bytes());
+            }
+        }
+        Pmt::VecPmt(x) => match x.as_slice() {
+            [meta @ (Pmt::Null | Pmt::MapStrPmt(_)), data @ Pmt::Blob(_)] => {
+                v.push(PST_PAIR);
+                write(meta, v)?;
+                write(data, v)?;
+            }
+            _ => {
+                v.push(PST_VECTOR);
+                v.extend_from_slice(&(x.len() as u32).to_be_bytes());
+                for p in x {
+                    write(p, v)?;
+                }
+            }
+        },
+        Pmt::MapStrPmt(m) => {
+            // dictionaries are lists of key/value pairs
+            for (k, p) in m {
+                v.push(PST_PAIR);
+                v.push(PST_PAIR);
+                write(&Pmt::String(k.clone()), v)?;
+                write(p, v)?;
+            }
+            v.push(PST_NULL);
+        }
+        _ => bail!("PMT {:?} cannot be serialized in the GNU Radio format", p),
+    }
+    Ok(())
+}
+
+enum Node {
+    Pmt(Pmt),
+    Pair(Box<Node>, Box<Node>),
+}
+
+impl Node {
+    fn is_dict(&self) -> bool {
+        let mut n = self;
+        loop {
+            match n {
+                Node::Pmt(Pmt::Null) => return true,
+                Node::Pair(car, cdr) => match &**car {
+                    Node::Pair(k, _) if matches!(**k, Node::Pmt(Pmt::String(_))) => n = cdr,
+                    _ => return false,
+                },
+                _ => return false,
+            }
+        }
+    }
+
+    fn into_pmt(self) -> Pmt {
+        match self {
+            Node::Pmt(p) => p,
+            n if n.is_dict() => {
+                let mut m = HashMap::new();
+                let mut n = n;
+                while let Node::Pair(car, cdr) = n {
+                    if let Node::Pair(k, v) = *car {
+                        if let Node::Pmt(Pmt::String(k)) = *k {
+                            m.insert(k, v.into_pmt());
+                        }
+                    }
+                    n = *cdr;
+                }
+                Pmt::MapStrPmt(m)
+            }
+            Node::Pair(car, cdr) => Pmt::VecPmt(vec![car.into_pmt(), cdr.into_pmt()]),
+        }
+    }
+}
+
+struct Reader<'a> {
+    b: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8]> {
+        let s = self
+            .b
+            .get(self.pos..self.pos + n)
+            .context("PMT truncated")?;
+        self.pos += n;
+        Ok(s)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn read(&mut self) -> Result<Node> {
+        let p = match self.u8()? {
+            PST_TRUE => Pmt::Bool(true),
+            PST_FALSE => Pmt::Bool(false),
+            PST_NULL => Pmt::Null,
+            PST_SYMBOL => {
+                let len = u16::from_be_bytes(self.take(2)?.try_into()?) as usize;
+                Pmt::String(String::from_utf8(self.take(len)?.to_vec())?)
+            }
+            PST_INT32 => {
+                let i = self.u32()? as i32;
+                if i >= 0 {
+                    Pmt::U32(i as u32)
+                } else {
+                    Pmt::F64(i as f64)
+                }
+            }
+            PST_INT64 => {
+                let i = self.u64()? as i64;
+                if i >= 0 {
+                    Pmt::U64(i as u64)
+                } else {
+                    Pmt::F64(i as f64)
+                }
+            }
+            PST_UINT64 => Pmt::U64(self.u64()?),
+            PST_DOUBLE => Pmt::F64(self.f64()?),
+            PST_PAIR => {
+                let car = self.read()?;
+                let cdr = self.read()?;
+                return Ok(Node::Pair(Box::new(car), Box::new(cdr)));
+            }
+            PST_VECTOR => {
+                let len = self.u32()?;
+                let mut v = Vec::new();
+                for _ in 0..len {
+                    v.push(self.read()?.into_pmt());
+                }
+                Pmt::VecPmt(v)
+            }
+            PST_UNIFORM_VECTOR => {
+                let t = self.u8()?;
+                let len = self.u32()? as usize;
+                let npad = self.u8()? as usize;
+                self.take(npad)?;
+                match t {
+                    UVI_U8 => Pmt::Blob(self.take(len)?.to_vec()),
+                    UVI_U64 => Pmt::VecU64((0..len).map(|_| self.u64()).collect::<Result<_>>()?),
+                    UVI_F32 => Pmt::VecF32(
+                        (0..len)
+                            .map(|_| self.f64().map(|f| f as f32))
+                            .collect::<Result<_>>()?,
+                    ),
+                    UVI_C32 => Pmt::VecCF32(
+                        (0..len)
+                            .map(|_| Ok(Complex32::new(self.f64()? as f32, self.f64()? as f32)))
+                            .collect::<Result<_>>()?,
+                    ),
+                    t => bail!("unsupported uniform vector type {:#04x}", t),
+                }
+            }
+            t => bail!("unsupported PMT type {:#04x}", t),
+        };
+        Ok(Node::Pmt(p))
+    }
+}
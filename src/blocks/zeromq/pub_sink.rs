@@ -1,4 +1,6 @@
 use crate::anyhow::Result;
+use crate::blocks::zeromq::socket;
+use crate::blocks::zeromq::ZmqPattern;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -12,6 +14,8 @@ use crate::runtime::WorkIo;
 /// Push samples into [ZeroMQ](https://zeromq.org/) socket.
 pub struct PubSink<T: Send + 'static> {
     address: String,
+    pattern: ZmqPattern,
+    bind: bool,
     publisher: Option<zmq::Socket>,
     _type: std::marker::PhantomData<T>,
     min_item: usize,
@@ -19,13 +23,26 @@ pub struct PubSink<T: Send + 'static> {
 
 impl<T: Send + 'static> PubSink<T> {
     /// Create PubSink
+    ///
+    /// Binds a PUB socket to the address. Use the [PubSinkBuilder] for other options.
     pub fn new(address: impl Into<String>, min_item: usize) -> Block {
+        Self::with_options(address, min_item, ZmqPattern::PubSub, true)
+    }
+
+    fn with_options(
+        address: impl Into<String>,
+        min_item: usize,
+        pattern: ZmqPattern,
+        bind: bool,
+    ) -> Block {
         Block::new(
             BlockMetaBuilder::new("PubSink").blocking().build(),
             StreamIoBuilder::new().add_input::<T>("in").build(),
             MessageIoBuilder::new().build(),
             PubSink {
                 address: address.into(),
+                pattern,
+                bind,
                 publisher: None,
                 _type: std::marker::PhantomData::<T>,
                 min_item,
@@ -49,7 +66,7 @@ impl<T: Send + 'static> Kernel for PubSink<T> {
         let n = i.len();
         if n > 0 && n > self.min_item {
             let i = sio.input(0).slice_unchecked::<u8>();
-            self.publisher.as_mut().unwrap().send(i, 0).unwrap();
+            self.publisher.as_mut().unwrap().send(i, 0)?;
             sio.input(0).consume(n);
         }
 
@@ -66,19 +83,40 @@ impl<T: Send + 'static> Kernel for PubSink<T> {
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
-        let context = zmq::Context::new();
-        let publisher = context.socket(zmq::PUB)?;
-        info!("SubSource Binding to {:?}", self.address);
-        publisher.bind(&self.address)?;
-        self.publisher = Some(publisher);
-
+        self.publisher = Some(socket(self.pattern.sink_type(), &self.address, self.bind)?);
         Ok(())
     }
 }
 
 /// Build a ZeroMQ [PubSink].
+///
+/// Samples are sent as ZeroMQ messages of raw items, compatible with the GNU Radio
+/// `ZMQ PUB Sink` and `ZMQ PUSH Sink` blocks without tags.
+///
+/// # Inputs
+///
+/// **Stream** `in`: Samples to send
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::zeromq::PubSinkBuilder;
+/// use futuresdr::blocks::zeromq::ZmqPattern;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex32;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let snk = fg.add_block(
+///     PubSinkBuilder::<Complex32>::new()
+///         .address("tcp://*:5555")
+///         .pattern(ZmqPattern::PushPull)
+///         .build(),
+/// );
+/// ```
 pub struct PubSinkBuilder<T: Send + 'static> {
     address: String,
+    pattern: ZmqPattern,
+    bind: bool,
     _type: std::marker::PhantomData<T>,
     /// Minimum number of items per send
     min_item: usize,
@@ -89,6 +127,8 @@ impl<T: Send + 'static> PubSinkBuilder<T> {
     pub fn new() -> PubSinkBuilder<T> {
         PubSinkBuilder {
             address: "tcp://*:5555".into(),
+            pattern: ZmqPattern::PubSub,
+            bind: true,
             _type: std::marker::PhantomData,
             min_item: 1,
         }
@@ -101,6 +141,20 @@ impl<T: Send + 'static> PubSinkBuilder<T> {
         self
     }
 
+    /// Messaging pattern (default: [ZmqPattern::PubSub])
+    #[must_use]
+    pub fn pattern(mut self, pattern: ZmqPattern) -> PubSinkBuilder<T> {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Bind to the address or connect to it (default: bind)
+    #[must_use]
+    pub fn bind(mut self, bind: bool) -> PubSinkBuilder<T> {
+        self.bind = bind;
+        self
+    }
+
     /// Set minimum number of items in send buffer
     pub fn min_item_per_send(mut self, min_item: usize) -> PubSinkBuilder<T> {
         self.min_item = min_item;
@@ -109,7 +163,7 @@ impl<T: Send + 'static> PubSinkBuilder<T> {
 
     /// Build PubSink
     pub fn build(self) -> Block {
-        PubSink::<T>::new(self.address, self.min_item)
+        PubSink::<T>::with_options(self.address, self.min_item, self.pattern, self.bind)
     }
 }
 
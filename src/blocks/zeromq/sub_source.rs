@@ -1,4 +1,6 @@
 use crate::anyhow::Result;
+use crate::blocks::zeromq::socket;
+use crate::blocks::zeromq::ZmqPattern;
 use crate::runtime::Block;
 use crate::runtime::BlockMeta;
 use crate::runtime::BlockMetaBuilder;
@@ -9,23 +11,38 @@ use crate::runtime::StreamIo;
 use crate::runtime::StreamIoBuilder;
 use crate::runtime::WorkIo;
 
+/// Timeout of a poll on the socket, after which the block checks its inbox
+const POLL_TIMEOUT_MS: i64 = 100;
+
 /// Read samples from [ZeroMQ](https://zeromq.org/) socket.
 pub struct SubSource<T: Send + 'static> {
     address: String,
+    pattern: ZmqPattern,
+    bind: bool,
     receiver: Option<zmq::Socket>,
+    pending: Vec<u8>,
     _type: std::marker::PhantomData<T>,
 }
 
 impl<T: Send + 'static> SubSource<T> {
     /// Create SubSource block
+    ///
+    /// Connects a SUB socket to the address. Use the [SubSourceBuilder] for other options.
     pub fn new(address: impl Into<String>) -> Block {
+        Self::with_options(address, ZmqPattern::PubSub, false)
+    }
+
+    fn with_options(address: impl Into<String>, pattern: ZmqPattern, bind: bool) -> Block {
         Block::new(
             BlockMetaBuilder::new("SubSource").blocking().build(),
             StreamIoBuilder::new().add_output::<T>("out").build(),
             MessageIoBuilder::new().build(),
             SubSource {
                 address: address.into(),
+                pattern,
+                bind,
                 receiver: None,
+                pending: Vec::new(),
                 _type: std::marker::PhantomData::<T>,
             },
         )
@@ -37,18 +54,31 @@ impl<T: Send + 'static> SubSource<T> {
 impl<T: Send + 'static> Kernel for SubSource<T> {
     async fn work(
         &mut self,
-        _io: &mut WorkIo,
+        io: &mut WorkIo,
         sio: &mut StreamIo,
         _mio: &mut MessageIo<Self>,
         _meta: &mut BlockMeta,
     ) -> Result<()> {
+        let item = std::mem::size_of::<T>();
+
+        if self.pending.len() < item {
+            let receiver = self.receiver.as_mut().unwrap();
+            if receiver.poll(zmq::POLLIN, POLL_TIMEOUT_MS)? > 0 {
+                let msg = receiver.recv_msg(0)?;
+                self.pending.extend_from_slice(&msg);
+            }
+        }
+
         let o = sio.output(0).slice_unchecked::<u8>();
-        let n_bytes = self.receiver.as_mut().unwrap().recv_into(o, 0)?;
-        debug_assert_eq!(o.len() % std::mem::size_of::<T>(), 0);
-        let n = n_bytes / std::mem::size_of::<T>();
+        let n = std::cmp::min(self.pending.len(), o.len()) / item;
+        o[..n * item].copy_from_slice(&self.pending[..n * item]);
+        self.pending.drain(..n * item);
         debug!("SubSource received {}", n);
         sio.output(0).produce(n);
 
+        // wait for the downstream block, if the output buffer is full
+        io.call_again = n > 0 || self.pending.len() < item;
+
         Ok(())
     }
 
@@ -59,20 +89,44 @@ impl<T: Send + 'static> Kernel for SubSource<T> {
         _meta: &mut BlockMeta,
     ) -> Result<()> {
         debug!("SubSource Init");
-
-        let context = zmq::Context::new();
-        let receiver = context.socket(zmq::SUB).unwrap();
-        info!("SubSource Connecting to {:?}", self.address);
-        receiver.connect(&self.address)?;
-        receiver.set_subscribe(b"")?;
-        self.receiver = Some(receiver);
+        self.receiver = Some(socket(
+            self.pattern.source_type(),
+            &self.address,
+            self.bind,
+        )?);
         Ok(())
     }
 }
 
 /// Build a ZeroMQ [SubSource].
+///
+/// Expects ZeroMQ messages of raw items, as sent by the GNU Radio `ZMQ PUB Sink` and
+/// `ZMQ PUSH Sink` blocks without tags. Messages do not have to contain whole items.
+///
+/// # Outputs
+///
+/// **Stream** `out`: Received samples
+///
+/// # Usage
+/// ```
+/// use futuresdr::blocks::zeromq::SubSourceBuilder;
+/// use futuresdr::blocks::zeromq::ZmqPattern;
+/// use futuresdr::runtime::Flowgraph;
+/// use num_complex::Complex32;
+///
+/// let mut fg = Flowgraph::new();
+///
+/// let src = fg.add_block(
+///     SubSourceBuilder::<Complex32>::new()
+///         .address("tcp://127.0.0.1:5555")
+///         .pattern(ZmqPattern::PushPull)
+///         .build(),
+/// );
+/// ```
 pub struct SubSourceBuilder<T: Send + 'static> {
     address: String,
+    pattern: ZmqPattern,
+    bind: bool,
     _type: std::marker::PhantomData<T>,
 }
 
@@ -81,6 +135,8 @@ impl<T: Send + 'static> SubSourceBuilder<T> {
     pub fn new() -> SubSourceBuilder<T> {
         SubSourceBuilder {
             address: "tcp://*:5555".into(),
+            pattern: ZmqPattern::PubSub,
+            bind: false,
             _type: std::marker::PhantomData,
         }
     }
@@ -92,9 +148,23 @@ impl<T: Send + 'static> SubSourceBuilder<T> {
         self
     }
 
+    /// Messaging pattern (default: [ZmqPattern::PubSub])
+    #[must_use]
+    pub fn pattern(mut self, pattern: ZmqPattern) -> SubSourceBuilder<T> {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Bind to the address or connect to it (default: connect)
+    #[must_use]
+    pub fn bind(mut self, bind: bool) -> SubSourceBuilder<T> {
+        self.bind = bind;
+        self
+    }
+
     /// Build ZMQ source
     pub fn build(self) -> Block {
-        SubSource::<T>::new(self.address)
+        SubSource::<T>::with_options(self.address, self.pattern, self.bind)
     }
 }
 
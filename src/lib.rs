@@ -37,6 +37,8 @@
 
 pub mod blocks;
 pub mod runtime;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod testing;
 
 // re-exports
 pub use anyhow;
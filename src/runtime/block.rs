@@ -10,6 +10,7 @@ use std::future::Future;
 use std::pin::Pin;
 
 use crate::anyhow::{Context, Result};
+use crate::runtime::metrics::Counters;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
 use crate::runtime::BlockMeta;
@@ -131,6 +132,7 @@ pub trait BlockT: Send + Any {
 
     // ##### MESSAGE IO
     fn message_input_name_to_id(&self, name: &str) -> Option<usize>;
+    fn message_input_names(&self) -> Vec<String>;
     fn message_outputs(&self) -> &Vec<MessageOutput>;
     fn message_output_name_to_id(&self, name: &str) -> Option<usize>;
 }
@@ -215,6 +217,8 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
             block_on: None,
         };
 
+        let mut counters = Counters::new(&sio);
+
         // setup phase
         loop {
             match inbox.next().await.context("no msg")? {
@@ -256,6 +260,13 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                 } => {
                     mio.output_mut(src_port).connect(dst_port, dst_inbox);
                 }
+                BlockMessage::Terminate | BlockMessage::Detach => {
+                    debug!(
+                        "{} terminated before initialization",
+                        meta.instance_name().unwrap()
+                    );
+                    return Ok(());
+                }
                 t => warn!(
                     "{} unhandled message during init {:?}",
                     meta.instance_name().unwrap(),
@@ -266,6 +277,7 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
 
         let inbox = inbox.peekable();
         futures::pin_mut!(inbox);
+        let mut detached = false;
 
         // main loop
         loop {
@@ -294,6 +306,15 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         };
                         tx.send(description).unwrap();
                     }
+                    Some(Some(BlockMessage::Metrics { tx })) => {
+                        let _ = tx.send(counters.metrics(
+                            block_id,
+                            meta.type_name(),
+                            meta.instance_name().unwrap(),
+                            &sio,
+                            &mio,
+                        ));
+                    }
                     Some(Some(BlockMessage::StreamInputDone { input_id })) => {
                         sio.input(input_id).finish();
                     }
@@ -301,6 +322,7 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         work_io.finished = true;
                     }
                     Some(Some(BlockMessage::Call { port_id, data })) => {
+                        counters.message();
                         match Self::call_handler(
                             &mut work_io,
                             &mut mio,
@@ -341,6 +363,7 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         }
                     }
                     Some(Some(BlockMessage::Callback { port_id, data, tx })) => {
+                        counters.message();
                         match Self::call_handler(
                             &mut work_io,
                             &mut mio,
@@ -378,6 +401,36 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                         }
                     }
                     Some(Some(BlockMessage::Terminate)) => work_io.finished = true,
+                    Some(Some(BlockMessage::Detach)) => {
+                        // mark inputs as finished, so that upstream blocks are not notified
+                        for i in sio.inputs_mut() {
+                            i.finish();
+                        }
+                        detached = true;
+                        work_io.finished = true;
+                    }
+                    Some(Some(BlockMessage::StreamOutputConnect {
+                        src_port,
+                        dst_port,
+                        dst_inbox,
+                        tx,
+                    })) => {
+                        let _ = tx.send(sio.output(src_port).add_reader(dst_inbox, dst_port));
+                    }
+                    Some(Some(BlockMessage::MessageOutputConnect {
+                        src_port,
+                        dst_port,
+                        dst_inbox,
+                    })) => {
+                        mio.output_mut(src_port).connect(dst_port, dst_inbox);
+                    }
+                    Some(Some(BlockMessage::MessageOutputDisconnect {
+                        src_port,
+                        dst_port,
+                        dst_inbox,
+                    })) => {
+                        mio.output_mut(src_port).disconnect(dst_port, &dst_inbox);
+                    }
                     Some(Some(t)) => warn!("block unhandled message in main loop {:?}", t),
                     _ => break,
                 };
@@ -390,7 +443,9 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                 debug!("{} terminating ", meta.instance_name().unwrap());
                 join_all(sio.inputs_mut().iter_mut().map(|i| i.notify_finished())).await;
                 join_all(sio.outputs_mut().iter_mut().map(|o| o.notify_finished())).await;
-                join_all(mio.outputs_mut().iter_mut().map(|o| o.notify_finished())).await;
+                if !detached {
+                    join_all(mio.outputs_mut().iter_mut().map(|o| o.notify_finished())).await;
+                }
 
                 match kernel.deinit(&mut sio, &mut mio, &mut meta).await {
                     Ok(_) => {
@@ -479,6 +534,7 @@ impl<T: Kernel + Send + 'static> TypedBlockWrapper<T> {
                     .await?;
                 return Err(e);
             }
+            counters.work(&sio);
             sio.commit();
 
             futures_lite::future::yield_now().await;
@@ -574,6 +630,9 @@ impl<T: Kernel + Send + 'static> BlockT for TypedBlockWrapper<T> {
             .map(|i| i.mio.input_name_to_id(name))
             .unwrap()
     }
+    fn message_input_names(&self) -> Vec<String> {
+        self.inner.as_ref().map(|i| i.mio.input_names()).unwrap()
+    }
     fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.inner.as_ref().map(|i| i.mio.outputs()).unwrap()
     }
@@ -695,6 +754,10 @@ impl Block {
     pub fn message_input_name_to_id(&self, name: &str) -> Option<usize> {
         self.0.message_input_name_to_id(name)
     }
+    /// Get message input port names
+    pub fn message_input_names(&self) -> Vec<String> {
+        self.0.message_input_names()
+    }
     /// Get message output ports
     pub fn message_outputs(&self) -> &Vec<MessageOutput> {
         self.0.message_outputs()
@@ -34,6 +34,10 @@ pub trait BufferWriterHost: Send + Any + Debug {
     fn produce(&mut self, amount: usize, tags: Vec<ItemTag>);
     /// Get buffer
     fn bytes(&mut self) -> (*mut u8, usize);
+    /// Memory regions of the buffer, e.g., to place them on a NUMA node (default: none)
+    fn memory(&mut self) -> Vec<(*mut u8, usize)> {
+        Vec::new()
+    }
     /// Notify readers that we are finished
     async fn notify_finished(&mut self);
     /// Mark as finished
@@ -103,6 +107,15 @@ impl BufferWriter {
             _ => unimplemented!(),
         }
     }
+    /// Memory regions of the buffer
+    ///
+    /// Empty for custom buffers and buffers that do not expose their memory.
+    pub fn memory(&mut self) -> Vec<(*mut u8, usize)> {
+        match self {
+            BufferWriter::Host(w) => w.memory(),
+            BufferWriter::Custom(_) => Vec::new(),
+        }
+    }
     /// Notify readers that we are finished
     pub async fn notify_finished(&mut self) {
         match self {
@@ -138,6 +151,12 @@ pub trait BufferReaderHost: Send + Any + Debug {
     /// Consume samples
     fn consume(&mut self, amount: usize);
 
+    /// Release the buffer returned by [`bytes`](Self::bytes), after the block is done with it
+    ///
+    /// Called after each call to `work()` that accessed the buffer, also if nothing was
+    /// consumed (default: nothing).
+    fn release(&mut self) {}
+
     /// Notify writers that we are finished
     async fn notify_finished(&mut self);
 
@@ -185,6 +204,12 @@ impl BufferReader {
             _ => unimplemented!(),
         }
     }
+    /// Release the buffer, after the block is done with it
+    pub fn release(&mut self) {
+        if let BufferReader::Host(w) = self {
+            w.release();
+        }
+    }
     /// Try to cast to given type
     pub fn try_as<W: 'static>(&mut self) -> Option<&mut W> {
         match self {
@@ -2,6 +2,8 @@ use futures::channel::mpsc::Sender;
 use futures::prelude::*;
 use std::any::Any;
 use std::fmt;
+use std::sync::Arc;
+use std::sync::Mutex;
 use vmcircbuffer::generic;
 
 use crate::runtime::buffer::BufferBuilder;
@@ -12,6 +14,11 @@ use crate::runtime::buffer::BufferWriterHost;
 use crate::runtime::config;
 use crate::runtime::BlockMessage;
 use crate::runtime::ItemTag;
+use crate::runtime::Tag;
+
+/// Name of the [`Tag::NamedUsize`] that marks the first item after a gap, holding the number of
+/// items dropped by the [`OverflowPolicy`] of the buffer
+pub const OVERFLOW_TAG: &str = "overflow";
 
 // everything is measured in items, e.g., offsets, capacity, space available
 
@@ -54,10 +61,30 @@ impl generic::Metadata for MyMetadata {
     }
 }
 
+/// What happens when a reader does not keep up and the buffer is full
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// The writer waits for the readers (back pressure)
+    #[default]
+    Block,
+    /// The oldest half of the buffer is dropped, once the writer finds the buffer full
+    ///
+    /// The writer advances the readers that hold it back and keeps producing, even if they are
+    /// not scheduled. Only while a reader is in its `work()` call, the writer waits for it. The
+    /// first item after the gap is marked with an [`OVERFLOW_TAG`].
+    DropOldest,
+    /// New items are dropped while the buffer is full
+    ///
+    /// The writer gets a scratch buffer, whose content is discarded. The first item after the gap
+    /// is marked with an [`OVERFLOW_TAG`].
+    DropNewest,
+}
+
 /// Circular builder
-#[derive(Clone, Debug, PartialEq, Hash)]
+#[derive(Clone, PartialEq, Hash)]
 pub struct Circular {
     min_bytes: usize,
+    overflow: OverflowPolicy,
 }
 
 impl Eq for Circular {}
@@ -67,11 +94,21 @@ impl Circular {
     pub fn new() -> Circular {
         Circular {
             min_bytes: config::config().buffer_size,
+            overflow: OverflowPolicy::Block,
         }
     }
     /// Create Circular builder with minimum size
     pub fn with_size(min_bytes: usize) -> Circular {
-        Circular { min_bytes }
+        Circular {
+            min_bytes,
+            overflow: OverflowPolicy::Block,
+        }
+    }
+    /// Set [`OverflowPolicy`] (default: [`OverflowPolicy::Block`])
+    #[must_use]
+    pub fn overflow(mut self, overflow: OverflowPolicy) -> Circular {
+        self.overflow = overflow;
+        self
     }
 }
 
@@ -81,6 +118,19 @@ impl Default for Circular {
     }
 }
 
+// The Debug output is used as the buffer description (e.g., in DOT exports). Only show the
+// overflow policy if it differs from the default, to keep it stable for blocking buffers.
+impl fmt::Debug for Circular {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut d = f.debug_struct("Circular");
+        d.field("min_bytes", &self.min_bytes);
+        if self.overflow != OverflowPolicy::Block {
+            d.field("overflow", &self.overflow);
+        }
+        d.finish()
+    }
+}
+
 impl BufferBuilder for Circular {
     fn build(
         &self,
@@ -88,23 +138,38 @@ impl BufferBuilder for Circular {
         writer_inbox: Sender<BlockMessage>,
         writer_output_id: usize,
     ) -> BufferWriter {
-        BufferWriter::Host(Box::new(Writer::new(
-            item_size,
-            self.min_bytes,
-            writer_inbox,
-            writer_output_id,
-        )))
+        BufferWriter::Host(Box::new(
+            Writer::new(item_size, self.min_bytes, writer_inbox, writer_output_id)
+                .with_overflow(self.overflow),
+        ))
     }
 }
 
+/// State of a reader, shared with the writer to drop items for the [`OverflowPolicy`]
+struct ReaderState {
+    reader: generic::Reader<u8, MyNotifier, MyMetadata>,
+    /// the block holds a slice of the buffer, i.e., between `bytes()` and `release()`
+    busy: bool,
+    /// the writer skipped the busy reader and waits to be notified
+    writer_waiting: bool,
+    /// items dropped since the reader last consumed
+    dropped: usize,
+}
+
 /// Circular writer
 pub struct Writer {
     writer: generic::Writer<u8, MyNotifier, MyMetadata>,
     readers: Vec<(Sender<BlockMessage>, usize)>,
+    states: Vec<Arc<Mutex<ReaderState>>>,
     item_size: usize,
     inbox: Sender<BlockMessage>,
     output_id: usize,
     finished: bool,
+    capacity: usize,
+    overflow: OverflowPolicy,
+    scratch: Vec<u8>,
+    discarding: bool,
+    dropped: usize,
 }
 
 impl Writer {
@@ -125,12 +190,51 @@ impl Writer {
         Writer {
             writer: generic::Circular::with_capacity(buffer_size).unwrap(),
             readers: Vec::new(),
+            states: Vec::new(),
             item_size,
             inbox,
             output_id,
             finished: false,
+            capacity: buffer_size,
+            overflow: OverflowPolicy::Block,
+            scratch: Vec::new(),
+            discarding: false,
+            dropped: 0,
         }
     }
+
+    /// Set [`OverflowPolicy`]
+    ///
+    /// Has to be set before readers are added.
+    pub fn with_overflow(mut self, overflow: OverflowPolicy) -> Writer {
+        debug_assert!(self.readers.is_empty());
+        self.overflow = overflow;
+        self
+    }
+
+    /// Drop the oldest half of the buffer for readers that hold back the writer
+    ///
+    /// Returns `true` if space was freed.
+    fn drop_oldest(&mut self) -> bool {
+        let items = (self.capacity / self.item_size / 2).max(1);
+        let mut freed = false;
+        for state in self.states.iter() {
+            let mut state = state.lock().unwrap();
+            let available = state.reader.slice(false).map(|(s, _)| s.len()).unwrap_or(0);
+            if available + self.item_size <= self.capacity {
+                continue;
+            }
+            if state.busy {
+                // the reader uses the data, retry once it returns from work()
+                state.writer_waiting = true;
+                continue;
+            }
+            state.reader.consume(items * self.item_size);
+            state.dropped += items;
+            freed = true;
+        }
+        freed
+    }
 }
 
 impl fmt::Debug for Writer {
@@ -139,6 +243,7 @@ impl fmt::Debug for Writer {
             .field("item_size", &self.item_size)
             .field("output_id", &self.output_id)
             .field("finished", &self.finished)
+            .field("overflow", &self.overflow)
             .finish()
     }
 }
@@ -154,16 +259,23 @@ impl BufferWriterHost for Writer {
             sender: inbox.clone(),
         };
 
-        let reader = self.writer.add_reader(reader_notifier, writer_notifier);
+        let state = Arc::new(Mutex::new(ReaderState {
+            reader: self.writer.add_reader(reader_notifier, writer_notifier),
+            busy: false,
+            writer_waiting: false,
+            dropped: 0,
+        }));
 
         self.readers.push((inbox, input_id));
+        self.states.push(state.clone());
 
         BufferReader::Host(Box::new(Reader {
-            reader,
+            state,
             item_size: self.item_size,
             finished: false,
             writer_inbox: self.inbox.clone(),
             writer_output_id: self.output_id,
+            overflow: self.overflow,
         }))
     }
 
@@ -172,15 +284,49 @@ impl BufferWriterHost for Writer {
     }
 
     fn produce(&mut self, items: usize, mut tags: Vec<ItemTag>) {
+        if self.discarding {
+            self.discarding = false;
+            self.dropped += items;
+            return;
+        }
         for t in tags.iter_mut() {
             t.index *= self.item_size;
         }
+        if self.dropped > 0 {
+            tags.push(ItemTag {
+                index: 0,
+                tag: Tag::NamedUsize(OVERFLOW_TAG.to_string(), self.dropped),
+            });
+            self.dropped = 0;
+        }
         self.writer.produce(items * self.item_size, tags);
     }
 
     fn bytes(&mut self) -> (*mut u8, usize) {
+        if self.overflow == OverflowPolicy::DropOldest
+            && self.writer.slice(false).len() < self.item_size
+        {
+            self.drop_oldest();
+        }
+
+        if !self.discarding {
+            let s = self.writer.slice(false);
+            if s.len() >= self.item_size || self.overflow != OverflowPolicy::DropNewest {
+                return (s.as_mut_ptr(), s.len());
+            }
+            // buffer is full, let the block write into the scratch buffer until it produces
+            self.discarding = true;
+            if self.scratch.is_empty() {
+                self.scratch = vec![0; self.capacity];
+            }
+        }
+        (self.scratch.as_mut_ptr(), self.scratch.len())
+    }
+
+    fn memory(&mut self) -> Vec<(*mut u8, usize)> {
+        // the second mapping refers to the same pages
         let s = self.writer.slice(false);
-        (s.as_mut_ptr(), s.len())
+        vec![(s.as_mut_ptr(), s.len())]
     }
 
     async fn notify_finished(&mut self) {
@@ -206,11 +352,12 @@ impl BufferWriterHost for Writer {
 
 /// Circular reader
 pub struct Reader {
-    reader: generic::Reader<u8, MyNotifier, MyMetadata>,
+    state: Arc<Mutex<ReaderState>>,
     item_size: usize,
     finished: bool,
     writer_inbox: Sender<BlockMessage>,
     writer_output_id: usize,
+    overflow: OverflowPolicy,
 }
 
 #[async_trait]
@@ -220,10 +367,20 @@ impl BufferReaderHost for Reader {
     }
 
     fn bytes(&mut self) -> (*const u8, usize, Vec<ItemTag>) {
-        if let Some((s, mut tags)) = self.reader.slice(false) {
+        let mut state = self.state.lock().unwrap();
+        state.busy = true;
+        let dropped = state.dropped;
+
+        if let Some((s, mut tags)) = state.reader.slice(false) {
             for t in tags.iter_mut() {
                 t.index /= self.item_size;
             }
+            if dropped > 0 {
+                tags.push(ItemTag {
+                    index: 0,
+                    tag: Tag::NamedUsize(OVERFLOW_TAG.to_string(), dropped),
+                });
+            }
             (s.as_ptr(), s.len(), tags)
         } else {
             (std::ptr::null(), 0, Vec::new())
@@ -231,7 +388,20 @@ impl BufferReaderHost for Reader {
     }
 
     fn consume(&mut self, amount: usize) {
-        self.reader.consume(amount * self.item_size);
+        let mut state = self.state.lock().unwrap();
+        if amount > 0 {
+            state.dropped = 0;
+        }
+        state.reader.consume(amount * self.item_size);
+    }
+
+    fn release(&mut self) {
+        let mut state = self.state.lock().unwrap();
+        state.busy = false;
+        if state.writer_waiting {
+            state.writer_waiting = false;
+            let _ = self.writer_inbox.try_send(BlockMessage::Notify);
+        }
     }
 
     async fn notify_finished(&mut self) {
@@ -263,6 +433,7 @@ impl fmt::Debug for Reader {
             .field("item_size", &self.item_size)
             .field("writer_output_id", &self.writer_output_id)
             .field("finished", &self.finished)
+            .field("overflow", &self.overflow)
             .finish()
     }
 }
@@ -221,6 +221,19 @@ impl BufferWriterHost for Writer {
         }
     }
 
+    fn memory(&mut self) -> Vec<(*mut u8, usize)> {
+        let mut state = self.state.lock().unwrap();
+        let mut regions: Vec<(*mut u8, usize)> = state
+            .writer_input
+            .iter_mut()
+            .map(|b| (b.buffer.as_mut_ptr(), b.buffer.len()))
+            .collect();
+        if let Some(c) = self.current.as_mut() {
+            regions.push((c.buffer.as_mut_ptr(), c.buffer.len()));
+        }
+        regions
+    }
+
     fn produce(&mut self, amount: usize, mut tags: Vec<ItemTag>) {
         debug_assert!(amount > 0);
 
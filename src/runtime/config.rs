@@ -91,6 +91,9 @@ static CONFIG: Lazy<Mutex<Config>> = Lazy::new(|| {
                 "frontend_path" => {
                     c.frontend_path = Some(config_parse::<PathBuf>(v));
                 }
+                "metrics_enable" => {
+                    c.metrics_enable = config_parse::<bool>(v);
+                }
                 _ => {
                     c.misc.insert(k.clone(), v.clone());
                 }
@@ -124,6 +127,8 @@ pub struct Config {
     pub ctrlport_bind: Option<SocketAddr>,
     /// Frontend path for Webserver
     pub frontend_path: Option<PathBuf>,
+    /// Export runtime statistics at `/metrics` of the control port
+    pub metrics_enable: bool,
     misc: HashMap<String, Value>,
 }
 
@@ -166,6 +171,9 @@ impl Config {
             "frontend_path" => {
                 self.frontend_path = Some(config_parse::<PathBuf>(&value));
             }
+            "metrics_enable" => {
+                self.metrics_enable = config_parse::<bool>(&value);
+            }
             _ => {
                 self.misc.insert(name, value);
             }
@@ -186,6 +194,7 @@ impl Default for Config {
             ctrlport_enable: true,
             ctrlport_bind: "127.0.0.1:1337".parse::<SocketAddr>().ok(),
             frontend_path: None,
+            metrics_enable: false,
             misc: HashMap::new(),
         }
     }
@@ -201,6 +210,7 @@ impl Default for Config {
             ctrlport_enable: true,
             ctrlport_bind: "127.0.0.1:1337".parse::<SocketAddr>().ok(),
             frontend_path: None,
+            metrics_enable: false,
             misc: HashMap::new(),
         }
     }
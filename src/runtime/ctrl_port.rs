@@ -1,8 +1,8 @@
 //! Remote Control through REST API
 use axum::extract::{Path, State};
-use axum::http::{StatusCode, Uri};
-use axum::response::Redirect;
-use axum::routing::{any, get, get_service};
+use axum::http::{header, StatusCode, Uri};
+use axum::response::{IntoResponse, Redirect};
+use axum::routing::{any, get, get_service, post};
 use axum::Json;
 use axum::Router;
 use std::path;
@@ -12,6 +12,7 @@ use tower_http::cors::CorsLayer;
 use tower_http::services::ServeDir;
 
 use crate::runtime::config;
+use crate::runtime::metrics;
 use crate::runtime::BlockDescription;
 use crate::runtime::FlowgraphDescription;
 use crate::runtime::Pmt;
@@ -45,6 +46,16 @@ async fn flowgraph_description(
     Err(StatusCode::BAD_REQUEST)
 }
 
+async fn flowgraph_terminate(Path(fg): Path<usize>, State(rt): State<RuntimeHandle>) -> StatusCode {
+    let fg = rt.get_flowgraph(fg);
+    if let Some(mut fg) = fg {
+        if fg.terminate().await.is_ok() {
+            return StatusCode::OK;
+        }
+    }
+    StatusCode::BAD_REQUEST
+}
+
 async fn block_description(
     Path((fg, blk)): Path<(usize, usize)>,
     State(rt): State<RuntimeHandle>,
@@ -96,6 +107,21 @@ async fn handler_id_post(
     Err(StatusCode::BAD_REQUEST)
 }
 
+async fn metrics(State(rt): State<RuntimeHandle>) -> impl IntoResponse {
+    let mut flowgraphs = Vec::new();
+    for id in rt.get_flowgraphs() {
+        if let Some(mut fg) = rt.get_flowgraph(id) {
+            if let Ok(m) = fg.metrics().await {
+                flowgraphs.push((id, m));
+            }
+        }
+    }
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        metrics::encode(&flowgraphs),
+    )
+}
+
 pub struct ControlPort {
     thread: Option<JoinHandle<()>>,
     handle: RuntimeHandle,
@@ -123,6 +149,7 @@ impl ControlPort {
         let mut app = Router::new()
             .route("/api/fg/", get(flowgraphs))
             .route("/api/fg/:fg/", get(flowgraph_description))
+            .route("/api/fg/:fg/terminate/", post(flowgraph_terminate))
             .route("/api/fg/:fg/block/:blk/", get(block_description))
             .route(
                 "/api/fg/:fg/block/:blk/call/:handler/",
@@ -138,6 +165,10 @@ impl ControlPort {
             .layer(CorsLayer::permissive())
             .with_state(self.handle.clone());
 
+        if config::config().metrics_enable {
+            app = app.route("/metrics", get(metrics).with_state(self.handle.clone()));
+        }
+
         if let Some(c) = custom_routes {
             app = app.nest("/", c);
         }
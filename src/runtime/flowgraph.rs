@@ -3,6 +3,7 @@ use futures::channel::oneshot;
 use futures::SinkExt;
 use std::cmp::PartialEq;
 use std::fmt::Debug;
+use std::fmt::Write;
 use std::hash::Hash;
 use std::result;
 
@@ -13,6 +14,7 @@ use crate::runtime::buffer::circular::Circular;
 use crate::runtime::buffer::slab::Slab;
 use crate::runtime::buffer::BufferBuilder;
 use crate::runtime::buffer::BufferWriter;
+use crate::runtime::metrics::BlockMetrics;
 use crate::runtime::Block;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
@@ -111,6 +113,231 @@ impl Flowgraph {
             .and_then(|t| t.block_mut(id))
             .and_then(|b| b.kernel_mut())
     }
+
+    /// Export the flowgraph in the [DOT](https://graphviz.org/doc/info/lang.html) format of
+    /// Graphviz
+    ///
+    /// Blocks are shown with their ports. Stream connections are labeled with the buffer type,
+    /// message connections are dashed.
+    pub fn to_dot(&self) -> String {
+        let (blocks, stream_edges, message_edges) = self.export();
+
+        let mut s = String::new();
+        s.push_str("digraph flowgraph {\n");
+        s.push_str("    rankdir=LR;\n");
+        s.push_str("    node [shape=record];\n");
+        for b in blocks.iter() {
+            let inputs: Vec<String> = b
+                .stream_inputs
+                .iter()
+                .enumerate()
+                .map(|(i, n)| format!("<si{i}> {}", dot_escape(n)))
+                .chain(
+                    b.message_inputs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, n)| format!("<mi{i}> {}", dot_escape(n))),
+                )
+                .collect();
+            let outputs: Vec<String> = b
+                .stream_outputs
+                .iter()
+                .enumerate()
+                .map(|(i, n)| format!("<so{i}> {}", dot_escape(n)))
+                .chain(
+                    b.message_outputs
+                        .iter()
+                        .enumerate()
+                        .map(|(i, n)| format!("<mo{i}> {}", dot_escape(n))),
+                )
+                .collect();
+            let _ = writeln!(
+                s,
+                "    b{} [label=\"{{{{{}}}|{}\\n({})|{{{}}}}}\"];",
+                b.id,
+                inputs.join("|"),
+                dot_escape(&b.instance_name),
+                dot_escape(&b.type_name),
+                outputs.join("|"),
+            );
+        }
+        for e in stream_edges.iter() {
+            let _ = writeln!(
+                s,
+                "    b{}:so{} -> b{}:si{} [label=\"{}\"];",
+                e.src,
+                e.src_port,
+                e.dst,
+                e.dst_port,
+                e.buffer.replace('\\', "\\\\").replace('"', "\\\""),
+            );
+        }
+        for e in message_edges.iter() {
+            let _ = writeln!(
+                s,
+                "    b{}:mo{} -> b{}:mi{} [style=dashed];",
+                e.src, e.src_port, e.dst, e.dst_port,
+            );
+        }
+        s.push_str("}\n");
+        s
+    }
+
+    /// Export the flowgraph as [Mermaid](https://mermaid.js.org/) flowchart
+    ///
+    /// Edges are labeled with the port names. Stream connections also show the buffer type,
+    /// message connections are dotted.
+    pub fn to_mermaid(&self) -> String {
+        let (blocks, stream_edges, message_edges) = self.export();
+
+        let mut s = String::new();
+        s.push_str("flowchart LR\n");
+        for b in blocks.iter() {
+            let _ = writeln!(
+                s,
+                "    b{}[\"{}<br/>({})\"]",
+                b.id,
+                mermaid_escape(&b.instance_name),
+                mermaid_escape(&b.type_name),
+            );
+        }
+        for e in stream_edges.iter() {
+            let _ = writeln!(
+                s,
+                "    b{} -- \"{} → {}<br/>{}\" --> b{}",
+                e.src,
+                mermaid_escape(&e.src_name),
+                mermaid_escape(&e.dst_name),
+                mermaid_escape(&e.buffer),
+                e.dst,
+            );
+        }
+        for e in message_edges.iter() {
+            let _ = writeln!(
+                s,
+                "    b{} -. \"{} → {}\" .-> b{}",
+                e.src,
+                mermaid_escape(&e.src_name),
+                mermaid_escape(&e.dst_name),
+                e.dst,
+            );
+        }
+        s
+    }
+
+    fn export(&self) -> (Vec<ExportBlock>, Vec<ExportEdge>, Vec<ExportEdge>) {
+        let topology = match self.topology.as_ref() {
+            Some(t) => t,
+            None => return (Vec::new(), Vec::new(), Vec::new()),
+        };
+
+        let blocks: Vec<ExportBlock> = topology
+            .blocks
+            .iter()
+            .filter_map(|(id, b)| b.as_ref().map(|b| (id, b)))
+            .map(|(id, b)| ExportBlock {
+                id,
+                instance_name: b.instance_name().unwrap_or("").to_string(),
+                type_name: b.type_name().to_string(),
+                stream_inputs: b
+                    .stream_inputs()
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect(),
+                stream_outputs: b
+                    .stream_outputs()
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect(),
+                message_inputs: b.message_input_names(),
+                message_outputs: b
+                    .message_outputs()
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect(),
+            })
+            .collect();
+
+        let port_name = |id: usize, ports: fn(&ExportBlock) -> &Vec<String>, port: usize| {
+            blocks
+                .iter()
+                .find(|b| b.id == id)
+                .and_then(|b| ports(b).get(port))
+                .cloned()
+                .unwrap_or_else(|| port.to_string())
+        };
+
+        let mut stream_edges: Vec<ExportEdge> = topology
+            .stream_edges
+            .iter()
+            .flat_map(|((src, src_port, buffer), dsts)| {
+                dsts.iter()
+                    .map(move |(dst, dst_port)| (*src, *src_port, buffer, *dst, *dst_port))
+            })
+            .map(|(src, src_port, buffer, dst, dst_port)| ExportEdge {
+                src,
+                src_port,
+                dst,
+                dst_port,
+                src_name: port_name(src, |b| &b.stream_outputs, src_port),
+                dst_name: port_name(dst, |b| &b.stream_inputs, dst_port),
+                buffer: format!("{}, {} B/item", buffer.description(), buffer.item_size()),
+            })
+            .collect();
+        stream_edges.sort_by_key(|e| (e.src, e.src_port, e.dst, e.dst_port));
+
+        let message_edges = topology
+            .message_edges
+            .iter()
+            .map(|&(src, src_port, dst, dst_port)| ExportEdge {
+                src,
+                src_port,
+                dst,
+                dst_port,
+                src_name: port_name(src, |b| &b.message_outputs, src_port),
+                dst_name: port_name(dst, |b| &b.message_inputs, dst_port),
+                buffer: String::new(),
+            })
+            .collect();
+
+        (blocks, stream_edges, message_edges)
+    }
+}
+
+struct ExportBlock {
+    id: usize,
+    instance_name: String,
+    type_name: String,
+    stream_inputs: Vec<String>,
+    stream_outputs: Vec<String>,
+    message_inputs: Vec<String>,
+    message_outputs: Vec<String>,
+}
+
+struct ExportEdge {
+    src: usize,
+    src_port: usize,
+    dst: usize,
+    dst_port: usize,
+    src_name: String,
+    dst_name: String,
+    buffer: String,
+}
+
+/// Escape characters with special meaning in record labels
+fn dot_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        if matches!(c, '{' | '}' | '|' | '<' | '>' | '"' | '\\') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn mermaid_escape(s: &str) -> String {
+    s.replace('"', "#quot;")
 }
 
 impl Default for Flowgraph {
@@ -119,6 +346,33 @@ impl Default for Flowgraph {
     }
 }
 
+/// Reference to a [`Block`] of a running [`Flowgraph`], either by Id or by instance name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum BlockRef {
+    /// Block Id
+    Id(usize),
+    /// Instance name
+    Name(String),
+}
+
+impl From<usize> for BlockRef {
+    fn from(id: usize) -> Self {
+        BlockRef::Id(id)
+    }
+}
+
+impl From<&str> for BlockRef {
+    fn from(name: &str) -> Self {
+        BlockRef::Name(name.to_string())
+    }
+}
+
+impl From<String> for BlockRef {
+    fn from(name: String) -> Self {
+        BlockRef::Name(name)
+    }
+}
+
 /// Handle to interact with running [`Flowgraph`]
 #[derive(Debug, Clone)]
 pub struct FlowgraphHandle {
@@ -176,6 +430,64 @@ impl FlowgraphHandle {
         rx.await.map_err(|_| Error::HandlerError)?
     }
 
+    /// Call message handler of a block, addressed by name, ignoring the result
+    ///
+    /// The block is given by its Id or instance name. Other than [`call`](Self::call), the
+    /// message input is looked up in the running [`Flowgraph`], returning
+    /// [`Error::InvalidHandler`] if the block has no such input.
+    pub async fn call_by_name(
+        &mut self,
+        block: impl Into<BlockRef>,
+        port: &str,
+        data: Pmt,
+    ) -> result::Result<(), Error> {
+        let (block_id, port_id) = self.resolve(block.into(), port).await?;
+        self.call(block_id, port_id, data).await
+    }
+
+    /// Call message handler of a block, addressed by name
+    ///
+    /// The block is given by its Id or instance name.
+    pub async fn callback_by_name(
+        &mut self,
+        block: impl Into<BlockRef>,
+        port: &str,
+        data: Pmt,
+    ) -> result::Result<Pmt, Error> {
+        let (block_id, port_id) = self.resolve(block.into(), port).await?;
+        self.callback(block_id, port_id, data).await
+    }
+
+    /// Get the Id of a block by its instance name
+    pub async fn block_id(&mut self, name: &str) -> result::Result<usize, Error> {
+        let (tx, rx) = oneshot::channel::<result::Result<usize, Error>>();
+        self.inbox
+            .send(FlowgraphMessage::BlockId {
+                name: name.to_string(),
+                tx,
+            })
+            .await
+            .or(Err(Error::FlowgraphTerminated))?;
+        rx.await.or(Err(Error::FlowgraphTerminated))?
+    }
+
+    async fn resolve(
+        &mut self,
+        block: BlockRef,
+        port: &str,
+    ) -> result::Result<(usize, usize), Error> {
+        let (tx, rx) = oneshot::channel::<result::Result<(usize, usize), Error>>();
+        self.inbox
+            .send(FlowgraphMessage::ResolveMessageInput {
+                block,
+                port: port.to_string(),
+                tx,
+            })
+            .await
+            .or(Err(Error::FlowgraphTerminated))?;
+        rx.await.or(Err(Error::FlowgraphTerminated))?
+    }
+
     /// Get [`FlowgraphDescription`]
     pub async fn description(&mut self) -> result::Result<FlowgraphDescription, Error> {
         let (tx, rx) = oneshot::channel::<FlowgraphDescription>();
@@ -187,6 +499,16 @@ impl FlowgraphHandle {
         Ok(d)
     }
 
+    /// Get [`BlockMetrics`] of all blocks
+    pub async fn metrics(&mut self) -> result::Result<Vec<BlockMetrics>, Error> {
+        let (tx, rx) = oneshot::channel::<Vec<BlockMetrics>>();
+        self.inbox
+            .send(FlowgraphMessage::Metrics { tx })
+            .await
+            .or(Err(Error::FlowgraphTerminated))?;
+        rx.await.or(Err(Error::FlowgraphTerminated))
+    }
+
     /// Get [`BlockDescription`]
     pub async fn block_description(&mut self, block_id: usize) -> Result<BlockDescription> {
         let (tx, rx) = oneshot::channel::<result::Result<BlockDescription, Error>>();
@@ -197,6 +519,112 @@ impl FlowgraphHandle {
         Ok(d)
     }
 
+    async fn reconfigure<T>(
+        &mut self,
+        m: impl FnOnce(oneshot::Sender<result::Result<T, Error>>) -> FlowgraphMessage,
+    ) -> result::Result<T, Error> {
+        let (tx, rx) = oneshot::channel::<result::Result<T, Error>>();
+        self.inbox
+            .send(m(tx))
+            .await
+            .or(Err(Error::FlowgraphTerminated))?;
+        rx.await.or(Err(Error::FlowgraphTerminated))?
+    }
+
+    /// Add a [`Block`] to the running [`Flowgraph`]
+    ///
+    /// The block is not started, before it is connected and
+    /// [`start_blocks`](Self::start_blocks) is called. Returns the Id of the block.
+    pub async fn add_block(&mut self, block: Block) -> result::Result<usize, Error> {
+        self.reconfigure(|tx| FlowgraphMessage::AddBlock { block, tx })
+            .await
+    }
+
+    /// Make stream connection in the running [`Flowgraph`]
+    ///
+    /// The destination has to be a block that was added with [`add_block`](Self::add_block)
+    /// but not started yet. If the source block is running, its output buffer gets an
+    /// additional reader, which requires a buffer that supports multiple readers (e.g., the
+    /// default circular buffer).
+    pub async fn connect_stream(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> result::Result<(), Error> {
+        let (src_port, dst_port) = (src_port.into(), dst_port.into());
+        self.reconfigure(|tx| FlowgraphMessage::ConnectStream {
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            tx,
+        })
+        .await
+    }
+
+    /// Make message connection in the running [`Flowgraph`]
+    ///
+    /// Connections between running blocks are made immediately. Connections to or from added
+    /// blocks are made, when the blocks are started.
+    pub async fn connect_message(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> result::Result<(), Error> {
+        let (src_port, dst_port) = (src_port.into(), dst_port.into());
+        self.reconfigure(|tx| FlowgraphMessage::ConnectMessage {
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            tx,
+        })
+        .await
+    }
+
+    /// Remove message connection from the running [`Flowgraph`]
+    pub async fn disconnect_message(
+        &mut self,
+        src_block: usize,
+        src_port: impl Into<PortId>,
+        dst_block: usize,
+        dst_port: impl Into<PortId>,
+    ) -> result::Result<(), Error> {
+        let (src_port, dst_port) = (src_port.into(), dst_port.into());
+        self.reconfigure(|tx| FlowgraphMessage::DisconnectMessage {
+            src_block,
+            src_port,
+            dst_block,
+            dst_port,
+            tx,
+        })
+        .await
+    }
+
+    /// Start the blocks that were added to the running [`Flowgraph`]
+    ///
+    /// All stream ports of the added blocks have to be connected. Returns, once the blocks are
+    /// initialized and running.
+    pub async fn start_blocks(&mut self) -> result::Result<(), Error> {
+        self.reconfigure(|tx| FlowgraphMessage::StartBlocks { tx })
+            .await
+    }
+
+    /// Remove a block from the running [`Flowgraph`]
+    ///
+    /// The block terminates without notifying upstream blocks or the receivers of its messages,
+    /// i.e., they keep running. Downstream blocks see the end of their input stream, like
+    /// when the block finishes. Removed blocks are dropped and not part of the flowgraph that
+    /// is returned, once it terminates.
+    pub async fn remove_block(&mut self, block_id: usize) -> result::Result<(), Error> {
+        self.reconfigure(|tx| FlowgraphMessage::RemoveBlock { block_id, tx })
+            .await
+    }
+
     /// Send a terminate message to the [`Flowgraph`]
     ///
     /// Does not wait until the [`Flowgraph`] is actually terminated.
@@ -226,7 +654,7 @@ pub struct DefaultBuffer;
 impl Eq for DefaultBuffer {}
 
 impl DefaultBuffer {
-    fn new() -> DefaultBuffer {
+    pub(crate) fn new() -> DefaultBuffer {
         DefaultBuffer
     }
 }
@@ -97,6 +97,7 @@ impl<T: Send + ?Sized> MessageInput<T> {
 pub struct MessageOutput {
     name: String,
     handlers: Vec<(usize, Sender<BlockMessage>)>,
+    sent: u64,
 }
 
 impl MessageOutput {
@@ -105,6 +106,7 @@ impl MessageOutput {
         MessageOutput {
             name: name.to_string(),
             handlers: Vec::new(),
+            sent: 0,
         }
     }
 
@@ -118,6 +120,12 @@ impl MessageOutput {
         self.handlers.push((port, sender));
     }
 
+    /// Disconnect port from downstream message input
+    pub fn disconnect(&mut self, port: usize, sender: &Sender<BlockMessage>) {
+        self.handlers
+            .retain(|(p, s)| *p != port || !s.same_receiver(sender));
+    }
+
     /// Notify connected downstream message ports that we are finished
     pub async fn notify_finished(&mut self) {
         for (port_id, sender) in self.handlers.iter_mut() {
@@ -130,8 +138,14 @@ impl MessageOutput {
         }
     }
 
+    /// Number of messages posted to the port
+    pub fn sent(&self) -> u64 {
+        self.sent
+    }
+
     /// Post data to connected downstream message port
     pub async fn post(&mut self, p: Pmt) {
+        self.sent += 1;
         for (port_id, sender) in self.handlers.iter_mut() {
             let _ = sender
                 .send(BlockMessage::Call {
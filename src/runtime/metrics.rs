@@ -0,0 +1,231 @@
+//! Runtime Statistics
+//!
+//! Every block keeps track of its work calls, the items it consumes and produces, and the
+//! messages it receives and sends. The statistics of a running
+//! [`Flowgraph`](crate::runtime::Flowgraph) can be queried through
+//! [`FlowgraphHandle::metrics`](crate::runtime::FlowgraphHandle::metrics).
+//!
+//! If `metrics_enable` is set in the [config](crate::runtime::config), the control port also
+//! exports the statistics of all flowgraphs in the Prometheus/OpenMetrics text format at
+//! `/metrics`.
+//!
+//! Only monotonic counters and the current buffer fill are reported. Queries do not change the
+//! statistics, i.e., any number of consumers can poll them. Rates have to be derived by the
+//! consumer from the difference of two queries, e.g., with `rate()` in Prometheus.
+use std::fmt::Write;
+
+use crate::runtime::MessageIo;
+use crate::runtime::StreamIo;
+
+/// Statistics of a stream port
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamPortMetrics {
+    /// Port name
+    pub name: String,
+    /// Items consumed (inputs) or produced (outputs)
+    pub items: u64,
+    /// Items available in the input buffer after the last call to `work()`
+    ///
+    /// Only set for inputs.
+    pub buffered: Option<u64>,
+}
+
+/// Statistics of a block
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockMetrics {
+    /// Block Id
+    pub id: usize,
+    /// Type name
+    pub type_name: String,
+    /// Instance name
+    pub instance_name: String,
+    /// Calls to `work()`
+    pub work_calls: u64,
+    /// Stream inputs
+    pub stream_inputs: Vec<StreamPortMetrics>,
+    /// Stream outputs
+    pub stream_outputs: Vec<StreamPortMetrics>,
+    /// Messages received through message inputs
+    pub messages_received: u64,
+    /// Messages posted to message outputs
+    pub messages_sent: u64,
+}
+
+/// Counters, maintained in the main loop of a block
+pub(crate) struct Counters {
+    work_calls: u64,
+    consumed: Vec<u64>,
+    produced: Vec<u64>,
+    buffered: Vec<u64>,
+    received: u64,
+}
+
+impl Counters {
+    pub(crate) fn new(sio: &StreamIo) -> Counters {
+        let inputs = sio.inputs().len();
+        let outputs = sio.outputs().len();
+        Counters {
+            work_calls: 0,
+            consumed: vec![0; inputs],
+            produced: vec![0; outputs],
+            buffered: vec![0; inputs],
+            received: 0,
+        }
+    }
+
+    /// Account for a call to `work()`, has to be called before committing the stream io
+    pub(crate) fn work(&mut self, sio: &StreamIo) {
+        self.work_calls += 1;
+        for (i, input) in sio.inputs().iter().enumerate() {
+            self.consumed[i] += input.consumed().0 as u64;
+            if let Some(n) = input.available() {
+                self.buffered[i] = n as u64;
+            }
+        }
+        for (i, output) in sio.outputs().iter().enumerate() {
+            self.produced[i] += output.produced() as u64;
+        }
+    }
+
+    /// Account for a received message
+    pub(crate) fn message(&mut self) {
+        self.received += 1;
+    }
+
+    /// Current statistics
+    pub(crate) fn metrics<T>(
+        &self,
+        id: usize,
+        type_name: &str,
+        instance_name: &str,
+        sio: &StreamIo,
+        mio: &MessageIo<T>,
+    ) -> BlockMetrics {
+        let sent: u64 = mio.outputs().iter().map(|o| o.sent()).sum();
+
+        let stream_inputs = sio
+            .inputs()
+            .iter()
+            .enumerate()
+            .map(|(i, p)| StreamPortMetrics {
+                name: p.name().to_string(),
+                items: self.consumed[i],
+                buffered: Some(self.buffered[i]),
+            })
+            .collect();
+        let stream_outputs = sio
+            .outputs()
+            .iter()
+            .enumerate()
+            .map(|(i, p)| StreamPortMetrics {
+                name: p.name().to_string(),
+                items: self.produced[i],
+                buffered: None,
+            })
+            .collect();
+
+        BlockMetrics {
+            id,
+            type_name: type_name.to_string(),
+            instance_name: instance_name.to_string(),
+            work_calls: self.work_calls,
+            stream_inputs,
+            stream_outputs,
+            messages_received: self.received,
+            messages_sent: sent,
+        }
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+struct Family {
+    name: &'static str,
+    kind: &'static str,
+    help: &'static str,
+    samples: String,
+}
+
+impl Family {
+    fn new(name: &'static str, kind: &'static str, help: &'static str) -> Family {
+        Family {
+            name,
+            kind,
+            help,
+            samples: String::new(),
+        }
+    }
+
+    fn add(&mut self, labels: &str, value: impl std::fmt::Display) {
+        writeln!(self.samples, "{}{{{}}} {}", self.name, labels, value).unwrap();
+    }
+}
+
+/// Encode the statistics of flowgraphs, given by their Id, in the Prometheus text format
+pub fn encode(flowgraphs: &[(usize, Vec<BlockMetrics>)]) -> String {
+    let mut work = Family::new(
+        "futuresdr_block_work_calls_total",
+        "counter",
+        "Calls to work()",
+    );
+    let mut items = Family::new(
+        "futuresdr_stream_items_total",
+        "counter",
+        "Items consumed or produced by a stream port",
+    );
+    let mut buffered = Family::new(
+        "futuresdr_stream_buffered_items",
+        "gauge",
+        "Items available in the buffer of a stream input",
+    );
+    let mut messages = Family::new(
+        "futuresdr_messages_total",
+        "counter",
+        "Messages received or sent by a block",
+    );
+
+    for (fg, blocks) in flowgraphs {
+        for b in blocks {
+            let block = format!(
+                "flowgraph=\"{}\",block=\"{}\",name=\"{}\",type=\"{}\"",
+                fg,
+                b.id,
+                escape(&b.instance_name),
+                escape(&b.type_name)
+            );
+            work.add(&block, b.work_calls);
+
+            for (direction, ports) in [("in", &b.stream_inputs), ("out", &b.stream_outputs)] {
+                for p in ports {
+                    let labels = format!(
+                        "{},port=\"{}\",direction=\"{}\"",
+                        block,
+                        escape(&p.name),
+                        direction
+                    );
+                    items.add(&labels, p.items);
+                    if let Some(n) = p.buffered {
+                        buffered.add(&labels, n);
+                    }
+                }
+            }
+
+            for (direction, n) in [("in", b.messages_received), ("out", b.messages_sent)] {
+                let labels = format!("{block},direction=\"{direction}\"");
+                messages.add(&labels, n);
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for f in [work, items, buffered, messages] {
+        writeln!(out, "# HELP {} {}", f.name, f.help).unwrap();
+        writeln!(out, "# TYPE {} {}", f.name, f.kind).unwrap();
+        out.push_str(&f.samples);
+    }
+    out
+}
@@ -27,6 +27,7 @@ mod logging;
 
 mod flowgraph;
 pub mod message_io;
+pub mod metrics;
 mod mocker;
 #[allow(clippy::module_inception)]
 mod runtime;
@@ -41,6 +42,7 @@ pub use block::TypedBlock;
 pub use block::WorkIo;
 pub use block_meta::BlockMeta;
 pub use block_meta::BlockMetaBuilder;
+pub use flowgraph::BlockRef;
 pub use flowgraph::Flowgraph;
 pub use flowgraph::FlowgraphHandle;
 pub use message_io::MessageInput;
@@ -80,6 +82,7 @@ pub fn init() {
 
 /// Flowgraph inbox message type
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum FlowgraphMessage {
     /// Terminate
     Terminate,
@@ -121,6 +124,22 @@ pub enum FlowgraphMessage {
         /// Back channel for result
         tx: oneshot::Sender<result::Result<Pmt, Error>>,
     },
+    /// Look up a block, given by Id or instance name, and one of its message inputs by name
+    ResolveMessageInput {
+        /// Block
+        block: BlockRef,
+        /// Message input name
+        port: String,
+        /// Back channel for the block Id and the message input Id
+        tx: oneshot::Sender<result::Result<(usize, usize), Error>>,
+    },
+    /// Look up the Id of a block by its instance name
+    BlockId {
+        /// Instance name
+        name: String,
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<usize, Error>>,
+    },
     /// Get [`FlowgraphDescription`]
     FlowgraphDescription {
         /// Back channel for result
@@ -133,15 +152,83 @@ pub enum FlowgraphMessage {
         /// Back channel for result
         tx: oneshot::Sender<result::Result<BlockDescription, Error>>,
     },
+    /// Get [`BlockMetrics`](metrics::BlockMetrics) of all blocks
+    Metrics {
+        /// Back channel for result
+        tx: oneshot::Sender<Vec<metrics::BlockMetrics>>,
+    },
+    /// Add block to the running flowgraph
+    AddBlock {
+        /// Block
+        block: Block,
+        /// Back channel for the Id of the block
+        tx: oneshot::Sender<result::Result<usize, Error>>,
+    },
+    /// Connect stream ports in the running flowgraph
+    ConnectStream {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<(), Error>>,
+    },
+    /// Connect message ports in the running flowgraph
+    ConnectMessage {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<(), Error>>,
+    },
+    /// Disconnect message ports in the running flowgraph
+    DisconnectMessage {
+        /// Source block Id
+        src_block: usize,
+        /// Source port Id
+        src_port: PortId,
+        /// Destination block Id
+        dst_block: usize,
+        /// Destination port Id
+        dst_port: PortId,
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<(), Error>>,
+    },
+    /// Start blocks that were added to the running flowgraph
+    StartBlocks {
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<(), Error>>,
+    },
+    /// Remove block from the running flowgraph
+    RemoveBlock {
+        /// Block Id
+        block_id: usize,
+        /// Back channel for result
+        tx: oneshot::Sender<result::Result<(), Error>>,
+    },
 }
 
 /// Block inbox message type
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum BlockMessage {
     /// Initialize
     Initialize,
     /// Terminate
     Terminate,
+    /// Terminate without notifying upstream blocks and message receivers
+    ///
+    /// Used to remove blocks from a running flowgraph.
+    Detach,
     /// Notify
     Notify,
     /// Get [`BlockDescription`]
@@ -149,6 +236,11 @@ pub enum BlockMessage {
         /// Channel for return value
         tx: oneshot::Sender<BlockDescription>,
     },
+    /// Get [`BlockMetrics`](metrics::BlockMetrics)
+    Metrics {
+        /// Channel for return value
+        tx: oneshot::Sender<metrics::BlockMetrics>,
+    },
     /// Initialize [`StreamOutput`]
     StreamOutputInit {
         /// Stream output ID
@@ -173,6 +265,17 @@ pub enum BlockMessage {
         /// Stream output Id
         output_id: usize,
     },
+    /// Connect a reader to an initialized [`StreamOutput`]
+    StreamOutputConnect {
+        /// Stream output Id
+        src_port: usize,
+        /// Destination input port Id
+        dst_port: usize,
+        /// Destination block inbox
+        dst_inbox: mpsc::Sender<BlockMessage>,
+        /// Back channel for the [`BufferReader`]
+        tx: oneshot::Sender<BufferReader>,
+    },
     /// Connect message output
     MessageOutputConnect {
         /// Message output port Id
@@ -182,6 +285,15 @@ pub enum BlockMessage {
         /// Destination block inbox
         dst_inbox: mpsc::Sender<BlockMessage>,
     },
+    /// Disconnect message output
+    MessageOutputDisconnect {
+        /// Message output port Id
+        src_port: usize,
+        /// Destination input port Id
+        dst_port: usize,
+        /// Destination block inbox
+        dst_inbox: mpsc::Sender<BlockMessage>,
+    },
     /// Call handler (return value is ignored)
     Call {
         /// Message handler Id
@@ -222,4 +334,7 @@ pub enum Error {
     /// Runtime error
     #[error("Error in runtime")]
     RuntimeError,
+    /// Invalid connection of ports
+    #[error("Invalid connection: {0}")]
+    InvalidConnection(String),
 }
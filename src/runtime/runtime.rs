@@ -7,6 +7,9 @@ use futures::channel::oneshot;
 use futures::prelude::*;
 use futures::FutureExt;
 use slab::Slab;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::collections::VecDeque;
 use std::fmt;
 use std::pin::Pin;
 use std::result;
@@ -17,15 +20,20 @@ use std::task::Poll;
 
 use crate::anyhow::{bail, Context, Result};
 use crate::runtime;
+use crate::runtime::buffer::BufferReader;
 use crate::runtime::config;
+use crate::runtime::flowgraph::DefaultBuffer;
+use crate::runtime::metrics::BlockMetrics;
 use crate::runtime::scheduler::Scheduler;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::runtime::scheduler::SmolScheduler;
 use crate::runtime::scheduler::Task;
 #[cfg(target_arch = "wasm32")]
 use crate::runtime::scheduler::WasmScheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockDescription;
 use crate::runtime::BlockMessage;
+use crate::runtime::BlockRef;
 use crate::runtime::ControlPort;
 use crate::runtime::Error;
 use crate::runtime::Flowgraph;
@@ -33,6 +41,8 @@ use crate::runtime::FlowgraphDescription;
 use crate::runtime::FlowgraphHandle;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Pmt;
+use crate::runtime::PortId;
+use crate::runtime::Topology;
 
 pub struct TaskHandle<'a, T> {
     task: Option<Task<T>>,
@@ -352,6 +362,7 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
     debug!("in run_flowgraph");
     let mut topology = fg.topology.take().context("flowgraph not initialized")?;
     topology.validate()?;
+    let mut reconf = Reconfiguration::new(&topology);
 
     let mut inboxes = scheduler.run_topology(&mut topology, &main_channel);
 
@@ -362,6 +373,7 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
 
         let src_inbox = inboxes[*src].as_ref().unwrap().clone();
         let mut writer = buffer_builder.build(src_inbox, *src_port);
+        scheduler.init_buffer(*src, &mut writer);
 
         for (dst, dst_port) in v.iter() {
             let dst_inbox = inboxes[*dst].as_ref().unwrap().clone();
@@ -470,6 +482,8 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
     }
 
     let mut terminated = false;
+    // messages received while starting blocks of a reconfiguration
+    let mut backlog = VecDeque::new();
 
     // main loop
     loop {
@@ -477,7 +491,11 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
             break;
         }
 
-        let m = main_rx.next().await.context("no msg")?;
+        let m = if let Some(m) = backlog.pop_front() {
+            m
+        } else {
+            main_rx.next().await.context("no msg")?
+        };
         match m {
             FlowgraphMessage::BlockCall {
                 block_id,
@@ -485,7 +503,7 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
                 data,
                 tx,
             } => {
-                if let Some(inbox) = inboxes[block_id].as_mut() {
+                if let Some(Some(inbox)) = inboxes.get_mut(block_id) {
                     if inbox
                         .send(BlockMessage::Call { port_id, data })
                         .await
@@ -528,16 +546,24 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
                 }
             }
             FlowgraphMessage::BlockDone { block_id, block } => {
-                *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                if reconf.removed(block_id) {
+                    retire_block(&mut topology, block_id);
+                } else {
+                    *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                }
                 inboxes[block_id] = None;
                 active_blocks -= 1;
             }
             FlowgraphMessage::BlockError { block_id, block } => {
-                *topology.blocks.get_mut(block_id).unwrap() = Some(block);
                 inboxes[block_id] = None;
-                block_error = true;
                 active_blocks -= 1;
-                let _ = main_channel.send(FlowgraphMessage::Terminate).await;
+                if reconf.removed(block_id) {
+                    retire_block(&mut topology, block_id);
+                } else {
+                    *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                    block_error = true;
+                    let _ = main_channel.send(FlowgraphMessage::Terminate).await;
+                }
             }
             FlowgraphMessage::BlockDescription { block_id, tx } => {
                 if let Some(Some(ref mut b)) = inboxes.get_mut(block_id) {
@@ -558,17 +584,28 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
                     let _ = tx.send(Err(Error::InvalidBlock));
                 }
             }
+            FlowgraphMessage::ResolveMessageInput { block, port, tx } => {
+                let _ = tx.send(reconf.resolve(&block, &port));
+            }
+            FlowgraphMessage::BlockId { name, tx } => {
+                let _ = tx.send(reconf.block_id(&BlockRef::Name(name)));
+            }
             FlowgraphMessage::FlowgraphDescription { tx } => {
                 let mut blocks = Vec::new();
                 let ids: Vec<usize> = topology.blocks.iter().map(|x| x.0).collect();
                 for id in ids {
                     let (b_tx, rx) = oneshot::channel::<BlockDescription>();
                     if let Some(Some(inbox)) = inboxes.get_mut(id) {
-                        inbox
+                        // blocks that are being removed might have terminated already
+                        if inbox
                             .send(BlockMessage::BlockDescription { tx: b_tx })
                             .await
-                            .unwrap();
-                        blocks.push(rx.await.unwrap());
+                            .is_ok()
+                        {
+                            if let Ok(b) = rx.await {
+                                blocks.push(b);
+                            }
+                        }
                     }
                 }
 
@@ -586,6 +623,21 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
                 })
                 .unwrap();
             }
+            FlowgraphMessage::Metrics { tx } => {
+                let mut blocks = Vec::new();
+                let ids: Vec<usize> = topology.blocks.iter().map(|x| x.0).collect();
+                for id in ids {
+                    let (b_tx, rx) = oneshot::channel::<BlockMetrics>();
+                    if let Some(Some(inbox)) = inboxes.get_mut(id) {
+                        if inbox.send(BlockMessage::Metrics { tx: b_tx }).await.is_ok() {
+                            if let Ok(m) = rx.await {
+                                blocks.push(m);
+                            }
+                        }
+                    }
+                }
+                let _ = tx.send(blocks);
+            }
             FlowgraphMessage::Terminate => {
                 if !terminated {
                     for (_, opt) in inboxes.iter_mut() {
@@ -600,10 +652,241 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
                     terminated = true;
                 }
             }
+            FlowgraphMessage::AddBlock { block, tx } => {
+                if terminated {
+                    let _ = tx.send(Err(Error::FlowgraphTerminated));
+                } else {
+                    let _ = tx.send(Ok(reconf.add_block(&mut topology, block)));
+                }
+            }
+            FlowgraphMessage::ConnectStream {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let _ = tx.send(reconf.connect_stream(
+                    &mut topology,
+                    &inboxes,
+                    src_block,
+                    src_port,
+                    dst_block,
+                    dst_port,
+                ));
+            }
+            FlowgraphMessage::ConnectMessage {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let r = reconf
+                    .connect_message(
+                        &mut topology,
+                        &mut inboxes,
+                        src_block,
+                        src_port,
+                        dst_block,
+                        dst_port,
+                    )
+                    .await;
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::DisconnectMessage {
+                src_block,
+                src_port,
+                dst_block,
+                dst_port,
+                tx,
+            } => {
+                let r = reconf
+                    .disconnect_message(
+                        &mut topology,
+                        &mut inboxes,
+                        src_block,
+                        src_port,
+                        dst_block,
+                        dst_port,
+                    )
+                    .await;
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::RemoveBlock { block_id, tx } => {
+                let r = reconf
+                    .remove_block(&mut topology, &mut inboxes, block_id)
+                    .await;
+                let _ = tx.send(r);
+            }
+            FlowgraphMessage::StartBlocks { tx } => {
+                if terminated {
+                    let _ = tx.send(Err(Error::FlowgraphTerminated));
+                    continue;
+                }
+                if let Err(e) = reconf.validate(&topology) {
+                    let _ = tx.send(Err(e));
+                    continue;
+                }
+                let pending = std::mem::take(&mut reconf.pending);
+
+                debug!("spawn added blocks");
+                for id in pending.iter() {
+                    let block = topology.blocks[*id].take().unwrap();
+                    while !inboxes.contains(*id) {
+                        inboxes.insert(None);
+                    }
+                    inboxes[*id] = Some(scheduler.run_block(*id, block, &main_channel));
+                }
+
+                debug!("connect stream io of added blocks");
+                for ((src, src_port, buffer_builder), v) in topology
+                    .stream_edges
+                    .iter()
+                    .filter(|(k, _)| pending.contains(&k.0))
+                {
+                    let src_inbox = inboxes[*src].as_ref().unwrap().clone();
+                    let mut writer = buffer_builder.build(src_inbox, *src_port);
+                    scheduler.init_buffer(*src, &mut writer);
+
+                    for (dst, dst_port) in v.iter() {
+                        let dst_inbox = inboxes[*dst].as_ref().unwrap().clone();
+                        let _ = inboxes[*dst]
+                            .as_mut()
+                            .unwrap()
+                            .send(BlockMessage::StreamInputInit {
+                                dst_port: *dst_port,
+                                reader: writer.add_reader(dst_inbox, *dst_port),
+                            })
+                            .await;
+                    }
+
+                    let _ = inboxes[*src]
+                        .as_mut()
+                        .unwrap()
+                        .send(BlockMessage::StreamOutputInit {
+                            src_port: *src_port,
+                            writer,
+                        })
+                        .await;
+                }
+
+                let mut connected = true;
+                for (src, src_port, dst, dst_port) in std::mem::take(&mut reconf.readers) {
+                    let dst_inbox = inboxes[dst].as_ref().unwrap().clone();
+                    let (r_tx, r_rx) = oneshot::channel::<BufferReader>();
+                    let reader = match inboxes.get_mut(src) {
+                        Some(Some(inbox)) => {
+                            if inbox
+                                .send(BlockMessage::StreamOutputConnect {
+                                    src_port,
+                                    dst_port,
+                                    dst_inbox,
+                                    tx: r_tx,
+                                })
+                                .await
+                                .is_ok()
+                            {
+                                r_rx.await.ok()
+                            } else {
+                                None
+                            }
+                        }
+                        _ => None,
+                    };
+                    if let Some(reader) = reader {
+                        let _ = inboxes[dst]
+                            .as_mut()
+                            .unwrap()
+                            .send(BlockMessage::StreamInputInit { dst_port, reader })
+                            .await;
+                    } else {
+                        connected = false;
+                    }
+                }
+
+                if !connected {
+                    warn!("upstream block terminated, while starting added blocks");
+                    for id in pending.iter() {
+                        if let Some(mut inbox) = inboxes[*id].take() {
+                            let _ = inbox.send(BlockMessage::Terminate).await;
+                        }
+                        reconf.ports.remove(id);
+                        retire_block(&mut topology, *id);
+                        topology.delete_edges(*id);
+                    }
+                    reconf
+                        .messages
+                        .retain(|e| !pending.contains(&e.0) && !pending.contains(&e.2));
+                    let _ = tx.send(Err(Error::BlockTerminated));
+                    continue;
+                }
+
+                debug!("connect message io of added blocks");
+                for (src, src_port, dst, dst_port) in std::mem::take(&mut reconf.messages) {
+                    if let Some(dst_inbox) = inboxes.get(dst).and_then(|i| i.clone()) {
+                        if let Some(Some(inbox)) = inboxes.get_mut(src) {
+                            let _ = inbox
+                                .send(BlockMessage::MessageOutputConnect {
+                                    src_port,
+                                    dst_port,
+                                    dst_inbox,
+                                })
+                                .await;
+                        }
+                    }
+                }
+
+                debug!("init added blocks");
+                for id in pending.iter() {
+                    let _ = inboxes[*id]
+                        .as_mut()
+                        .unwrap()
+                        .send(BlockMessage::Initialize)
+                        .await;
+                }
+
+                let mut i = pending.len();
+                let mut init_error = false;
+                while i > 0 {
+                    match main_rx.next().await.context("no msg")? {
+                        FlowgraphMessage::Initialized => i -= 1,
+                        FlowgraphMessage::BlockError { block_id, block }
+                            if pending.contains(&block_id) =>
+                        {
+                            *topology.blocks.get_mut(block_id).unwrap() = Some(block);
+                            inboxes[block_id] = None;
+                            i -= 1;
+                            init_error = true;
+                        }
+                        x => backlog.push_back(x),
+                    }
+                }
+
+                debug!("running added blocks");
+                for id in pending.iter() {
+                    if let Some(inbox) = inboxes[*id].as_mut() {
+                        active_blocks += 1;
+                        if inbox.send(BlockMessage::Notify).await.is_err() {
+                            debug!("runtime wanted to start block that already terminated");
+                        }
+                    }
+                }
+
+                if init_error {
+                    block_error = true;
+                    let _ = tx.send(Err(Error::RuntimeError));
+                    let _ = main_channel.send(FlowgraphMessage::Terminate).await;
+                } else {
+                    let _ = tx.send(Ok(()));
+                }
+            }
             _ => warn!("main loop received unhandled message"),
         }
     }
 
+    // the flowgraph is not running anymore, so the Ids of removed blocks can be reused
+    topology.blocks.retain(|_, b| b.is_some());
     fg.topology = Some(topology);
     if block_error {
         bail!("flowgraph error");
@@ -611,3 +894,388 @@ pub(crate) async fn run_flowgraph<S: Scheduler>(
 
     Ok(fg)
 }
+
+/// Drop a removed block, but keep its slot, so that the Id is not reused while the flowgraph
+/// is running and messages to the old Id fail instead of reaching a new block
+fn retire_block(topology: &mut Topology, id: usize) {
+    if let Some(b) = topology.blocks.get_mut(id) {
+        *b = None;
+    }
+}
+
+/// Ports of a block, kept to connect blocks that are added to a running flowgraph
+#[derive(Debug)]
+struct BlockPorts {
+    name: String,
+    stream_inputs: Vec<(String, TypeId)>,
+    stream_outputs: Vec<(String, TypeId)>,
+    message_inputs: Vec<String>,
+    message_outputs: Vec<String>,
+}
+
+impl BlockPorts {
+    fn new(block: &Block) -> Self {
+        BlockPorts {
+            name: block.instance_name().unwrap_or_default().to_string(),
+            stream_inputs: block
+                .stream_inputs()
+                .iter()
+                .map(|p| (p.name().to_string(), p.type_id()))
+                .collect(),
+            stream_outputs: block
+                .stream_outputs()
+                .iter()
+                .map(|p| (p.name().to_string(), p.type_id()))
+                .collect(),
+            message_inputs: block.message_input_names(),
+            message_outputs: block
+                .message_outputs()
+                .iter()
+                .map(|p| p.name().to_string())
+                .collect(),
+        }
+    }
+}
+
+fn port_index<'a>(mut names: impl Iterator<Item = &'a str>, port: &PortId) -> Option<usize> {
+    match port {
+        PortId::Name(n) => names.position(|x| x == n),
+        PortId::Index(i) => {
+            if *i < names.count() {
+                Some(*i)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// Blocks and connections that are added to or removed from a running flowgraph
+#[derive(Debug, Default)]
+struct Reconfiguration {
+    ports: HashMap<usize, BlockPorts>,
+    // blocks that were added but not started
+    pending: Vec<usize>,
+    // stream connections from running to pending blocks (src, src port, dst, dst port)
+    readers: Vec<(usize, usize, usize, usize)>,
+    // message connections to or from pending blocks
+    messages: Vec<(usize, usize, usize, usize)>,
+    // blocks that were removed but did not terminate yet
+    removing: Vec<usize>,
+}
+
+impl Reconfiguration {
+    fn new(topology: &Topology) -> Self {
+        Reconfiguration {
+            ports: topology
+                .blocks
+                .iter()
+                .filter_map(|(id, b)| b.as_ref().map(|b| (id, BlockPorts::new(b))))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn ports(&self, id: usize) -> result::Result<&BlockPorts, Error> {
+        if self.removing.contains(&id) {
+            return Err(Error::InvalidBlock);
+        }
+        self.ports.get(&id).ok_or(Error::InvalidBlock)
+    }
+
+    fn block_id(&self, block: &BlockRef) -> result::Result<usize, Error> {
+        match block {
+            BlockRef::Id(id) => self.ports(*id).map(|_| *id),
+            BlockRef::Name(name) => self
+                .ports
+                .iter()
+                .find(|(id, p)| p.name == *name && !self.removing.contains(id))
+                .map(|(id, _)| *id)
+                .ok_or(Error::InvalidBlock),
+        }
+    }
+
+    fn resolve(&self, block: &BlockRef, port: &str) -> result::Result<(usize, usize), Error> {
+        let id = self.block_id(block)?;
+        let port_id = self
+            .ports(id)?
+            .message_inputs
+            .iter()
+            .position(|p| p == port)
+            .ok_or_else(|| Error::InvalidHandler(PortId::Name(port.to_string())))?;
+        Ok((id, port_id))
+    }
+
+    /// Returns `true`, if the block was removed and is now terminated
+    fn removed(&mut self, id: usize) -> bool {
+        if let Some(i) = self.removing.iter().position(|x| *x == id) {
+            self.removing.remove(i);
+            self.ports.remove(&id);
+            true
+        } else {
+            false
+        }
+    }
+
+    fn add_block(&mut self, topology: &mut Topology, mut block: Block) -> usize {
+        // the blocks of the running flowgraph are not in the topology, so names are checked here
+        let (mut i, base_name, mut block_name) = if let Some(name) = block.instance_name() {
+            (-1, name.to_string(), name.to_string())
+        } else {
+            (
+                0,
+                block.type_name().to_string(),
+                format!("{}_0", block.type_name()),
+            )
+        };
+        while self.ports.values().any(|p| p.name == block_name) {
+            i += 1;
+            block_name = format!("{base_name}_{i}");
+        }
+        block.set_instance_name(block_name);
+
+        let ports = BlockPorts::new(&block);
+        let id = topology.blocks.insert(Some(block));
+        self.ports.insert(id, ports);
+        self.pending.push(id);
+        id
+    }
+
+    fn connect_stream(
+        &mut self,
+        topology: &mut Topology,
+        inboxes: &Slab<Option<Sender<BlockMessage>>>,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+    ) -> result::Result<(), Error> {
+        let src = self.ports(src_block)?;
+        let dst = self.ports(dst_block)?;
+        let src_port_id = port_index(src.stream_outputs.iter().map(|p| p.0.as_str()), &src_port)
+            .ok_or_else(|| Error::InvalidConnection(format!("invalid src port {src_port:?}")))?;
+        let dst_port_id = port_index(dst.stream_inputs.iter().map(|p| p.0.as_str()), &dst_port)
+            .ok_or_else(|| Error::InvalidConnection(format!("invalid dst port {dst_port:?}")))?;
+
+        if !self.pending.contains(&dst_block) {
+            return Err(Error::InvalidConnection(
+                "stream inputs of started blocks cannot be connected".to_string(),
+            ));
+        }
+        if src.stream_outputs[src_port_id].1 != dst.stream_inputs[dst_port_id].1 {
+            return Err(Error::InvalidConnection(
+                "item types do not match".to_string(),
+            ));
+        }
+        if topology
+            .stream_edges
+            .values()
+            .any(|v| v.contains(&(dst_block, dst_port_id)))
+        {
+            return Err(Error::InvalidConnection(
+                "stream input is already connected".to_string(),
+            ));
+        }
+
+        if self.pending.contains(&src_block) {
+            topology
+                .connect_stream(
+                    src_block,
+                    PortId::Index(src_port_id),
+                    dst_block,
+                    PortId::Index(dst_port_id),
+                    DefaultBuffer::new(),
+                )
+                .map_err(|e| Error::InvalidConnection(e.to_string()))
+        } else {
+            // add a reader to the buffer of the running block
+            if !matches!(inboxes.get(src_block), Some(Some(_))) {
+                return Err(Error::BlockTerminated);
+            }
+            let readers = topology
+                .stream_edges
+                .iter_mut()
+                .find(|(k, _)| k.0 == src_block && k.1 == src_port_id)
+                .map(|(_, v)| v)
+                .ok_or(Error::RuntimeError)?;
+            readers.push((dst_block, dst_port_id));
+            self.readers
+                .push((src_block, src_port_id, dst_block, dst_port_id));
+            Ok(())
+        }
+    }
+
+    fn message_edge(
+        &self,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+    ) -> result::Result<(usize, usize, usize, usize), Error> {
+        let src = self.ports(src_block)?;
+        let dst = self.ports(dst_block)?;
+        let src_port_id = port_index(src.message_outputs.iter().map(|p| p.as_str()), &src_port)
+            .ok_or_else(|| Error::InvalidConnection(format!("invalid src port {src_port:?}")))?;
+        let dst_port_id = port_index(dst.message_inputs.iter().map(|p| p.as_str()), &dst_port)
+            .ok_or_else(|| Error::InvalidConnection(format!("invalid dst port {dst_port:?}")))?;
+        Ok((src_block, src_port_id, dst_block, dst_port_id))
+    }
+
+    async fn connect_message(
+        &mut self,
+        topology: &mut Topology,
+        inboxes: &mut Slab<Option<Sender<BlockMessage>>>,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+    ) -> result::Result<(), Error> {
+        let edge = self.message_edge(src_block, src_port, dst_block, dst_port)?;
+        if topology.message_edges.contains(&edge) {
+            return Err(Error::InvalidConnection(
+                "message ports are already connected".to_string(),
+            ));
+        }
+
+        if self.pending.contains(&src_block) || self.pending.contains(&dst_block) {
+            // connected, when the blocks are started
+            self.messages.push(edge);
+        } else {
+            let dst_inbox = inboxes
+                .get(dst_block)
+                .and_then(|i| i.clone())
+                .ok_or(Error::BlockTerminated)?;
+            inboxes
+                .get_mut(src_block)
+                .and_then(|i| i.as_mut())
+                .ok_or(Error::BlockTerminated)?
+                .send(BlockMessage::MessageOutputConnect {
+                    src_port: edge.1,
+                    dst_port: edge.3,
+                    dst_inbox,
+                })
+                .await
+                .or(Err(Error::BlockTerminated))?;
+        }
+        topology.message_edges.push(edge);
+        Ok(())
+    }
+
+    async fn disconnect_message(
+        &mut self,
+        topology: &mut Topology,
+        inboxes: &mut Slab<Option<Sender<BlockMessage>>>,
+        src_block: usize,
+        src_port: PortId,
+        dst_block: usize,
+        dst_port: PortId,
+    ) -> result::Result<(), Error> {
+        let edge = self.message_edge(src_block, src_port, dst_block, dst_port)?;
+        let i = topology
+            .message_edges
+            .iter()
+            .position(|e| *e == edge)
+            .ok_or_else(|| {
+                Error::InvalidConnection("message ports are not connected".to_string())
+            })?;
+        topology.message_edges.remove(i);
+
+        if let Some(i) = self.messages.iter().position(|e| *e == edge) {
+            self.messages.remove(i);
+        } else {
+            Self::disconnect(inboxes, edge).await;
+        }
+        Ok(())
+    }
+
+    async fn disconnect(
+        inboxes: &mut Slab<Option<Sender<BlockMessage>>>,
+        (src_block, src_port, dst_block, dst_port): (usize, usize, usize, usize),
+    ) {
+        if let Some(dst_inbox) = inboxes.get(dst_block).and_then(|i| i.clone()) {
+            if let Some(Some(inbox)) = inboxes.get_mut(src_block) {
+                let _ = inbox
+                    .send(BlockMessage::MessageOutputDisconnect {
+                        src_port,
+                        dst_port,
+                        dst_inbox,
+                    })
+                    .await;
+            }
+        }
+    }
+
+    async fn remove_block(
+        &mut self,
+        topology: &mut Topology,
+        inboxes: &mut Slab<Option<Sender<BlockMessage>>>,
+        block_id: usize,
+    ) -> result::Result<(), Error> {
+        self.ports(block_id)?;
+
+        self.readers.retain(|r| r.0 != block_id && r.2 != block_id);
+        self.messages.retain(|e| e.0 != block_id && e.2 != block_id);
+
+        if let Some(i) = self.pending.iter().position(|x| *x == block_id) {
+            self.pending.remove(i);
+            self.ports.remove(&block_id);
+            retire_block(topology, block_id);
+            topology.delete_edges(block_id);
+            return Ok(());
+        }
+
+        let edges: Vec<_> = topology
+            .message_edges
+            .iter()
+            .filter(|e| e.2 == block_id && e.0 != block_id)
+            .copied()
+            .collect();
+        for e in edges {
+            Self::disconnect(inboxes, e).await;
+        }
+        topology.delete_edges(block_id);
+
+        if let Some(Some(inbox)) = inboxes.get_mut(block_id) {
+            // the block is dropped, once it terminated
+            let _ = inbox.send(BlockMessage::Detach).await;
+            self.removing.push(block_id);
+        } else {
+            // the block terminated already
+            self.ports.remove(&block_id);
+            retire_block(topology, block_id);
+        }
+        Ok(())
+    }
+
+    /// Check that all stream ports of pending blocks are connected
+    fn validate(&self, topology: &Topology) -> result::Result<(), Error> {
+        for id in self.pending.iter() {
+            let ports = &self.ports[id];
+            for out_id in 0..ports.stream_outputs.len() {
+                if !topology
+                    .stream_edges
+                    .iter()
+                    .any(|(k, v)| k.0 == *id && k.1 == out_id && !v.is_empty())
+                {
+                    return Err(Error::InvalidConnection(format!(
+                        "unconnected stream output port of {}",
+                        ports.name
+                    )));
+                }
+            }
+            for in_id in 0..ports.stream_inputs.len() {
+                if !topology
+                    .stream_edges
+                    .values()
+                    .any(|v| v.contains(&(*id, in_id)))
+                {
+                    return Err(Error::InvalidConnection(format!(
+                        "unconnected stream input port of {}",
+                        ports.name
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
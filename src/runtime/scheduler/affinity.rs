@@ -0,0 +1,432 @@
+use async_executor::{Executor, Task};
+use core_affinity::CoreId;
+use futures::channel::mpsc::{channel, Sender};
+use futures::channel::oneshot;
+use futures::future::Future;
+use slab::Slab;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::runtime::buffer::BufferWriter;
+use crate::runtime::config;
+use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
+use crate::runtime::BlockMessage;
+use crate::runtime::FlowgraphMessage;
+use crate::runtime::Topology;
+
+/// CPU cores of a group of blocks
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cores {
+    /// Cores, given by their Id
+    Ids(Vec<usize>),
+    /// All cores of a NUMA node
+    NumaNode(usize),
+}
+
+impl Cores {
+    fn node(&self) -> Option<usize> {
+        match self {
+            Cores::Ids(_) => None,
+            Cores::NumaNode(node) => Some(*node),
+        }
+    }
+
+    fn resolve(&self, available: &[CoreId]) -> Result<Vec<CoreId>> {
+        let ids = match self {
+            Cores::Ids(ids) => ids.clone(),
+            Cores::NumaNode(node) => numa_node_cores(*node)
+                .with_context(|| format!("cannot read cores of NUMA node {node}"))?,
+        };
+        if ids.is_empty() {
+            bail!("empty set of cores");
+        }
+        ids.into_iter()
+            .map(|id| match available.iter().find(|c| c.id == id) {
+                Some(c) => Ok(*c),
+                None => bail!("core {} not available", id),
+            })
+            .collect()
+    }
+}
+
+/// Cores of a NUMA node, parsed from a Linux cpulist, e.g., `0-3,8-11`
+fn numa_node_cores(node: usize) -> Option<Vec<usize>> {
+    let s = std::fs::read_to_string(format!("/sys/devices/system/node/node{node}/cpulist")).ok()?;
+    let mut cores = Vec::new();
+    for range in s.trim().split(',').filter(|r| !r.is_empty()) {
+        match range.split_once('-') {
+            Some((a, b)) => cores.extend(a.parse::<usize>().ok()?..=b.parse::<usize>().ok()?),
+            None => cores.push(range.parse().ok()?),
+        }
+    }
+    Some(cores)
+}
+
+/// Bind the pages of a memory region to a NUMA node, moving pages that are already allocated
+///
+/// Only pages that are fully contained in the region are bound.
+#[cfg(target_os = "linux")]
+fn bind_to_node(ptr: *mut u8, len: usize, node: usize) -> std::io::Result<()> {
+    const MPOL_BIND: libc::c_int = 2;
+    const MPOL_MF_MOVE: libc::c_uint = 1 << 1;
+
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let start = (ptr as usize + page_size - 1) & !(page_size - 1);
+    let end = (ptr as usize + len) & !(page_size - 1);
+    if end <= start {
+        return Ok(());
+    }
+
+    // the kernel ignores the last bit of the mask, so reserve an additional word
+    let bits = 8 * std::mem::size_of::<libc::c_ulong>();
+    let mut mask = vec![0 as libc::c_ulong; node / bits + 2];
+    mask[node / bits] |= 1 << (node % bits);
+
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_mbind,
+            start,
+            end - start,
+            MPOL_BIND,
+            mask.as_ptr(),
+            mask.len() * bits,
+            MPOL_MF_MOVE,
+        )
+    };
+    if ret == 0 {
+        Ok(())
+    } else {
+        Err(std::io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn bind_to_node(_ptr: *mut u8, _len: usize, _node: usize) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "NUMA placement is only supported on Linux",
+    ))
+}
+
+struct Pool {
+    executor: Arc<Executor<'static>>,
+    cores: Vec<CoreId>,
+    pinned: bool,
+    node: Option<usize>,
+    next: AtomicUsize,
+}
+
+impl Pool {
+    fn next_core(&self) -> CoreId {
+        self.cores[self.next.fetch_add(1, Ordering::Relaxed) % self.cores.len()]
+    }
+}
+
+/// Affinity scheduler
+///
+/// Runs groups of blocks on dedicated worker threads that are pinned to a given set of CPU
+/// cores, e.g., all cores of a NUMA node. Blocks that are not part of a group run on a shared
+/// pool of unpinned worker threads. Blocking blocks of a group get a separate thread that is
+/// pinned to one of the cores of the group.
+///
+/// If a group is pinned to a [`Cores::NumaNode`], the stream output buffers of its blocks are
+/// bound to the memory of that node, i.e., the buffers are local to the writing block and
+/// pages that were already allocated elsewhere are moved. This works for buffers that expose
+/// their memory, like the [circular](crate::runtime::buffer::circular) and
+/// [slab](crate::runtime::buffer::slab) buffers, and requires Linux. If a buffer cannot be
+/// placed, a warning is logged and the flowgraph runs with the placement of the operating
+/// system.
+///
+/// Groups refer to blocks by their Id, so create the scheduler with
+/// [`AffinitySchedulerBuilder`] once all blocks are added to the flowgraph.
+#[derive(Clone, Debug)]
+pub struct AffinityScheduler {
+    inner: Arc<AffinitySchedulerInner>,
+}
+
+struct AffinitySchedulerInner {
+    pools: Vec<Pool>,
+    mapping: HashMap<usize, usize>,
+    workers: Vec<(thread::JoinHandle<()>, oneshot::Sender<()>)>,
+}
+
+impl fmt::Debug for AffinitySchedulerInner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AffinitySchedulerInner")
+            .field(
+                "pools",
+                &self
+                    .pools
+                    .iter()
+                    .map(|p| p.cores.iter().map(|c| c.id).collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "nodes",
+                &self.pools.iter().map(|p| p.node).collect::<Vec<_>>(),
+            )
+            .field("mapping", &self.mapping)
+            .finish()
+    }
+}
+
+impl Drop for AffinitySchedulerInner {
+    fn drop(&mut self) {
+        for i in self.workers.drain(..) {
+            i.1.send(()).unwrap();
+            i.0.join().unwrap();
+        }
+    }
+}
+
+impl AffinityScheduler {
+    fn new(
+        groups: Vec<(Vec<usize>, Vec<CoreId>, Option<usize>)>,
+        default: (Vec<CoreId>, Option<usize>),
+    ) -> AffinityScheduler {
+        let mut pools = vec![Pool {
+            executor: Arc::new(Executor::new()),
+            cores: default.0,
+            pinned: false,
+            node: default.1,
+            next: AtomicUsize::new(0),
+        }];
+        let mut mapping = HashMap::new();
+        for (blocks, cores, node) in groups {
+            for b in blocks {
+                mapping.insert(b, pools.len());
+            }
+            pools.push(Pool {
+                executor: Arc::new(Executor::new()),
+                cores,
+                pinned: true,
+                node,
+                next: AtomicUsize::new(0),
+            });
+        }
+
+        let mut workers = Vec::new();
+        for (i, pool) in pools.iter().enumerate() {
+            for c in pool.cores.iter().cloned() {
+                let e = pool.executor.clone();
+                let pinned = pool.pinned;
+                let (sender, receiver) = oneshot::channel::<()>();
+
+                let handle = thread::Builder::new()
+                    .stack_size(config::config().stack_size)
+                    .name(format!("affinity-{}-{}", i, &c.id))
+                    .spawn(move || {
+                        if pinned {
+                            debug!("starting executor thread on core id {}", &c.id);
+                            core_affinity::set_for_current(c);
+                        }
+                        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                            async_io::block_on(e.run(receiver)).unwrap();
+                        }));
+                        if result.is_err() {
+                            eprintln!("affinity worker panicked {result:?}");
+                            std::process::exit(1);
+                        }
+                    })
+                    .expect("failed to spawn executor thread");
+
+                workers.push((handle, sender));
+            }
+        }
+
+        AffinityScheduler {
+            inner: Arc::new(AffinitySchedulerInner {
+                pools,
+                mapping,
+                workers,
+            }),
+        }
+    }
+
+    fn pool(&self, block_id: usize) -> &Pool {
+        &self.inner.pools[self.inner.mapping.get(&block_id).copied().unwrap_or(0)]
+    }
+
+    fn spawn_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        let pool = self.pool(block_id);
+        let blocking = block.is_blocking();
+        let future = block.run(block_id, main_channel.clone(), receiver);
+
+        if blocking && pool.pinned {
+            let c = pool.next_core();
+            thread::Builder::new()
+                .stack_size(config::config().stack_size)
+                .name(format!("affinity-block-{block_id}"))
+                .spawn(move || {
+                    core_affinity::set_for_current(c);
+                    async_io::block_on(future)
+                })
+                .expect("failed to spawn block thread");
+        } else if blocking {
+            self.spawn_blocking(future).detach();
+        } else {
+            pool.executor.spawn(future).detach();
+        }
+        sender
+    }
+}
+
+impl Scheduler for AffinityScheduler {
+    fn run_topology(
+        &self,
+        topology: &mut Topology,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Slab<Option<Sender<BlockMessage>>> {
+        let mut inboxes = Slab::new();
+        let max = topology.blocks.iter().map(|(i, _)| i).max().unwrap_or(0);
+        for _ in 0..=max {
+            inboxes.insert(None);
+        }
+
+        // spawn block executors
+        for (id, block_o) in topology.blocks.iter_mut() {
+            let block = block_o.take().unwrap();
+            inboxes[id] = Some(self.spawn_block(id, block, main_channel));
+        }
+
+        inboxes
+    }
+
+    fn run_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        self.spawn_block(block_id, block, main_channel)
+    }
+
+    fn init_buffer(&self, block_id: usize, writer: &mut BufferWriter) {
+        if let Some(node) = self.pool(block_id).node {
+            for (ptr, len) in writer.memory() {
+                if let Err(e) = bind_to_node(ptr, len, node) {
+                    warn!(
+                        "cannot place buffer of block {} on NUMA node {}: {}",
+                        block_id, node, e
+                    );
+                }
+            }
+        }
+    }
+
+    fn spawn<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.inner.pools[0].executor.spawn(future)
+    }
+
+    fn spawn_blocking<T: Send + 'static>(
+        &self,
+        future: impl Future<Output = T> + Send + 'static,
+    ) -> Task<T> {
+        self.inner.pools[0]
+            .executor
+            .spawn(blocking::unblock(|| async_io::block_on(future)))
+    }
+}
+
+/// Build an [`AffinityScheduler`]
+///
+/// # Usage
+/// ```no_run
+/// use futuresdr::blocks::Copy;
+/// use futuresdr::blocks::NullSink;
+/// use futuresdr::blocks::NullSource;
+/// use futuresdr::runtime::scheduler::AffinitySchedulerBuilder;
+/// use futuresdr::runtime::scheduler::Cores;
+/// use futuresdr::runtime::Flowgraph;
+/// use futuresdr::runtime::Runtime;
+///
+/// let mut fg = Flowgraph::new();
+/// let src = fg.add_block(NullSource::<f32>::new());
+/// let copy = fg.add_block(Copy::<f32>::new());
+/// let snk = fg.add_block(NullSink::<f32>::new());
+/// fg.connect_stream(src, "out", copy, "in").unwrap();
+/// fg.connect_stream(copy, "out", snk, "in").unwrap();
+///
+/// let scheduler = AffinitySchedulerBuilder::new()
+///     .group([src, copy], Cores::NumaNode(0))
+///     .pin(snk, 1)
+///     .build()
+///     .unwrap();
+/// Runtime::with_scheduler(scheduler).run(fg).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AffinitySchedulerBuilder {
+    groups: Vec<(Vec<usize>, Cores)>,
+    default: Option<Cores>,
+}
+
+impl AffinitySchedulerBuilder {
+    /// Create affinity scheduler builder
+    pub fn new() -> AffinitySchedulerBuilder {
+        AffinitySchedulerBuilder::default()
+    }
+    /// Run a group of blocks, given by their Id, on worker threads pinned to `cores`
+    ///
+    /// One worker thread is started per core. With [`Cores::NumaNode`], the output buffers of
+    /// the blocks are placed on the memory of the node.
+    #[must_use]
+    pub fn group(
+        mut self,
+        blocks: impl IntoIterator<Item = usize>,
+        cores: Cores,
+    ) -> AffinitySchedulerBuilder {
+        self.groups.push((blocks.into_iter().collect(), cores));
+        self
+    }
+    /// Run a block on a worker thread pinned to `core`
+    #[must_use]
+    pub fn pin(self, block: usize, core: usize) -> AffinitySchedulerBuilder {
+        self.group([block], Cores::Ids(vec![core]))
+    }
+    /// Cores of the shared pool for blocks that are not part of a group (default: all cores)
+    ///
+    /// With [`Cores::NumaNode`], the output buffers of these blocks are placed on the memory of
+    /// the node. The threads of the shared pool are not pinned.
+    #[must_use]
+    pub fn default_cores(mut self, cores: Cores) -> AffinitySchedulerBuilder {
+        self.default = Some(cores);
+        self
+    }
+    /// Build affinity scheduler
+    pub fn build(self) -> Result<AffinityScheduler> {
+        let available = core_affinity::get_core_ids().context("cannot get core ids")?;
+
+        let mut groups = Vec::new();
+        let mut seen = Vec::new();
+        for (blocks, cores) in self.groups {
+            for b in blocks.iter() {
+                if seen.contains(b) {
+                    bail!("block {} is part of more than one group", b);
+                }
+                seen.push(*b);
+            }
+            groups.push((blocks, cores.resolve(&available)?, cores.node()));
+        }
+
+        let default = match self.default {
+            Some(c) => (c.resolve(&available)?, c.node()),
+            None => (available, None),
+        };
+
+        Ok(AffinityScheduler::new(groups, default))
+    }
+}
@@ -1,4 +1,9 @@
 //! Flowgraph Scheduler Trait and Implementations
+#[cfg(feature = "affinity_scheduler")]
+mod affinity;
+#[cfg(feature = "affinity_scheduler")]
+pub use crate::runtime::scheduler::affinity::{AffinityScheduler, AffinitySchedulerBuilder, Cores};
+
 #[cfg(feature = "flow_scheduler")]
 mod flow;
 #[cfg(feature = "flow_scheduler")]
@@ -1,8 +1,11 @@
-use futures::channel::mpsc::Sender;
+use futures::channel::mpsc::{channel, Sender};
 use futures::future::Future;
 use slab::Slab;
 
+use crate::runtime::buffer::BufferWriter;
+use crate::runtime::config;
 use crate::runtime::scheduler::Task;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Topology;
@@ -29,6 +32,32 @@ pub trait Scheduler: Clone + Send + 'static {
         &self,
         future: impl Future<Output = T> + Send + 'static,
     ) -> Task<T>;
+
+    /// Prepare the buffer of a stream output of `block_id`, before it is passed to the block
+    ///
+    /// Schedulers can use this, e.g., to place the buffer close to the cores that run the
+    /// block. The default implementation does nothing.
+    fn init_buffer(&self, _block_id: usize, _writer: &mut BufferWriter) {}
+
+    /// Run a block that is added to a running [`Flowgraph`](crate::runtime::Flowgraph)
+    ///
+    /// Returns the inbox of the block.
+    fn run_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        if block.is_blocking() {
+            self.spawn_blocking(block.run(block_id, main_channel.clone(), receiver))
+                .detach();
+        } else {
+            self.spawn(block.run(block_id, main_channel.clone(), receiver))
+                .detach();
+        }
+        sender
+    }
 }
 
 /// Scheduler trait
@@ -52,4 +81,30 @@ pub trait Scheduler: Clone + Send + 'static {
         &self,
         future: impl Future<Output = T> + 'static,
     ) -> Task<T>;
+
+    /// Prepare the buffer of a stream output of `block_id`, before it is passed to the block
+    ///
+    /// Schedulers can use this, e.g., to place the buffer close to the cores that run the
+    /// block. The default implementation does nothing.
+    fn init_buffer(&self, _block_id: usize, _writer: &mut BufferWriter) {}
+
+    /// Run a block that is added to a running [`Flowgraph`](crate::runtime::Flowgraph)
+    ///
+    /// Returns the inbox of the block.
+    fn run_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        if block.is_blocking() {
+            self.spawn_blocking(block.run(block_id, main_channel.clone(), receiver))
+                .detach();
+        } else {
+            self.spawn(block.run(block_id, main_channel.clone(), receiver))
+                .detach();
+        }
+        sender
+    }
 }
@@ -10,6 +10,7 @@ use std::thread;
 
 use crate::runtime::config;
 use crate::runtime::scheduler::Scheduler;
+use crate::runtime::Block;
 use crate::runtime::BlockMessage;
 use crate::runtime::FlowgraphMessage;
 use crate::runtime::Topology;
@@ -107,6 +108,18 @@ impl Scheduler for TpbScheduler {
         inboxes
     }
 
+    fn run_block(
+        &self,
+        block_id: usize,
+        block: Block,
+        main_channel: &Sender<FlowgraphMessage>,
+    ) -> Sender<BlockMessage> {
+        let (sender, receiver) = channel::<BlockMessage>(config::config().queue_size);
+        self.spawn_blocking(block.run(block_id, main_channel.clone(), receiver))
+            .detach();
+        sender
+    }
+
     fn spawn<T: Send + 'static>(
         &self,
         future: impl Future<Output = T> + Send + 'static,
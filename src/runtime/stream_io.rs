@@ -139,6 +139,7 @@ impl StreamInput {
             if amount != 0 {
                 self.reader.as_mut().unwrap().consume(amount);
             }
+            self.reader.as_mut().unwrap().release();
             self.current = None;
         }
     }
@@ -152,6 +153,13 @@ impl StreamInput {
         }
     }
 
+    /// Items left in the buffer in this call to work, if the buffer was accessed
+    pub(crate) fn available(&self) -> Option<usize> {
+        self.current
+            .as_ref()
+            .map(|c| (c.len - c.index) / self.item_size)
+    }
+
     /// Set the buffer reader
     pub fn set_reader(&mut self, reader: BufferReader) {
         debug_assert!(self.reader.is_none());
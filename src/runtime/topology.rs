@@ -49,6 +49,16 @@ pub struct BufferBuilderEntry {
 }
 
 impl BufferBuilderEntry {
+    /// Size of the items in the buffer
+    pub(crate) fn item_size(&self) -> usize {
+        self.item_size
+    }
+
+    /// Description of the buffer type, e.g., for visualization
+    pub(crate) fn description(&self) -> String {
+        format!("{:?}", self.builder)
+    }
+
     pub(crate) fn build(
         &self,
         writer_inbox: Sender<BlockMessage>,
@@ -143,17 +153,23 @@ impl Topology {
     pub fn delete_block(&mut self, id: usize) {
         // remove from registry
         self.blocks.remove(id);
+        self.delete_edges(id);
+        self.stream_edges = self
+            .stream_edges
+            .drain()
+            .filter(|(_, v)| !v.is_empty())
+            .collect();
+    }
 
+    /// Removes all edges connected to a [Block] from the [Topology].
+    ///
+    /// Buffers of other blocks are kept, even if they have no readers left.
+    pub(crate) fn delete_edges(&mut self, id: usize) {
         // delete associated stream edges
         self.stream_edges.retain(|k, _| k.0 != id);
         for (_, vec) in self.stream_edges.iter_mut() {
             *vec = vec.iter().filter(|x| x.0 != id).copied().collect();
         }
-        self.stream_edges = self
-            .stream_edges
-            .drain()
-            .filter(|(_, v)| !v.is_empty())
-            .collect();
 
         // delete associated message edges
         self.message_edges.retain(|x| x.0 != id && x.2 != id);
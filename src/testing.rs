@@ -0,0 +1,436 @@
+//! Helpers to test blocks
+//!
+//! [`BlockTest`] runs a block with one stream input and one stream output in a small flowgraph,
+//! feeding it from a vector and collecting its output and tags. The flowgraph is terminated if it
+//! does not finish within a timeout.
+//!
+//! ```
+//! use futuresdr::anyhow::Result;
+//! use futuresdr::blocks::Apply;
+//! use futuresdr::testing::assert_all_close;
+//! use futuresdr::testing::BlockTest;
+//!
+//! fn main() -> Result<()> {
+//!     let block = Apply::new(|x: &f32| x * 2.0);
+//!     let out = BlockTest::new(block, vec![1.0f32, 2.0, 3.0]).run::<f32>()?;
+//!     assert_all_close(out.items(), &[2.0, 4.0, 6.0], 1e-6);
+//!     Ok(())
+//! }
+//! ```
+//!
+//! For protocol decoders, [`golden_fixtures`] finds recorded samples together with the frames
+//! that they are expected to decode to. Frames are stored in text files, one hex-encoded frame
+//! per line, so that changes to the expected output show up in diffs.
+use futures::future;
+use futures::future::Either;
+use num_complex::Complex;
+use std::fmt::Debug;
+use std::fmt::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::anyhow::{bail, Context, Result};
+use crate::async_io::block_on;
+use crate::async_io::Timer;
+use crate::runtime::Block;
+use crate::runtime::BlockMeta;
+use crate::runtime::BlockMetaBuilder;
+use crate::runtime::Flowgraph;
+use crate::runtime::ItemTag;
+use crate::runtime::Kernel;
+use crate::runtime::MessageIo;
+use crate::runtime::MessageIoBuilder;
+use crate::runtime::Runtime;
+use crate::runtime::StreamIo;
+use crate::runtime::StreamIoBuilder;
+use crate::runtime::Tag;
+use crate::runtime::WorkIo;
+
+/// Default time a test flowgraph is allowed to run
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Run `fg`, terminating it if it does not finish within `timeout`
+pub fn run_with_timeout(fg: Flowgraph, timeout: Duration) -> Result<Flowgraph> {
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        match future::select(task, Timer::after(timeout)).await {
+            Either::Left((fg, _)) => fg,
+            Either::Right((_, task)) => {
+                let _ = handle.terminate().await;
+                let _ = task.await;
+                bail!("flowgraph did not finish within {:?}", timeout)
+            }
+        }
+    })
+}
+
+/// Run a block with samples from a vector and collect its output.
+pub struct BlockTest<I> {
+    block: Block,
+    items: Vec<I>,
+    tags: Vec<ItemTag>,
+    input: String,
+    output: String,
+    timeout: Duration,
+}
+
+impl<I: Copy + Send + 'static> BlockTest<I> {
+    /// Create test that feeds `items` into `block`
+    pub fn new(block: Block, items: Vec<I>) -> Self {
+        Self {
+            block,
+            items,
+            tags: Vec::new(),
+            input: "in".to_string(),
+            output: "out".to_string(),
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Add a tag to the input sample with the given index
+    #[must_use]
+    pub fn tag(mut self, index: usize, tag: Tag) -> Self {
+        self.tags.push(ItemTag { index, tag });
+        self
+    }
+
+    /// Set stream ports of the block (default: `in` and `out`)
+    #[must_use]
+    pub fn ports(mut self, input: impl Into<String>, output: impl Into<String>) -> Self {
+        self.input = input.into();
+        self.output = output.into();
+        self
+    }
+
+    /// Set the time the flowgraph is allowed to run (default: 10s)
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run the flowgraph until the block finishes
+    pub fn run<O: Clone + Debug + Send + 'static>(self) -> Result<TestOutput<O>> {
+        let mut fg = Flowgraph::new();
+        let src = fg.add_block(TestSource::new(self.items, self.tags));
+        let block = fg.add_block(self.block);
+        let snk = fg.add_block(TestSink::<O>::new());
+        fg.connect_stream(src, "out", block, self.input)?;
+        fg.connect_stream(block, self.output, snk, "in")?;
+
+        let fg = run_with_timeout(fg, self.timeout)?;
+        let sink = fg.kernel::<TestSink<O>>(snk).context("wrong output type")?;
+        Ok(TestOutput {
+            items: sink.items.clone(),
+            tags: sink.tags.clone(),
+            fg,
+            block,
+        })
+    }
+}
+
+/// Output of a [`BlockTest`]
+pub struct TestOutput<O> {
+    items: Vec<O>,
+    tags: Vec<ItemTag>,
+    fg: Flowgraph,
+    block: usize,
+}
+
+impl<O> TestOutput<O> {
+    /// Output samples
+    pub fn items(&self) -> &[O] {
+        &self.items
+    }
+
+    /// Output tags, indexed by output sample
+    pub fn tags(&self) -> &[ItemTag] {
+        &self.tags
+    }
+
+    /// Tags of the output sample with the given index
+    pub fn tags_at(&self, index: usize) -> impl Iterator<Item = &Tag> {
+        self.tags
+            .iter()
+            .filter(move |t| t.index == index)
+            .map(|t| &t.tag)
+    }
+
+    /// Assert that the output sample with the given index has a tag matching `f`
+    #[track_caller]
+    pub fn assert_tag(&self, index: usize, f: impl Fn(&Tag) -> bool) {
+        assert!(
+            self.tags_at(index).any(f),
+            "no matching tag at index {index}, tags: {:?}",
+            self.tags
+        );
+    }
+
+    /// Kernel of the tested block, e.g., to inspect its state
+    pub fn kernel<K: Kernel + 'static>(&self) -> Option<&K> {
+        self.fg.kernel::<K>(self.block)
+    }
+}
+
+/// Types that can be compared approximately
+pub trait ApproxEq: Debug {
+    /// Whether `self` and `other` differ by at most `tolerance`
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool;
+}
+
+impl ApproxEq for f32 {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        ((self - other).abs() as f64) <= tolerance
+    }
+}
+
+impl ApproxEq for f64 {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        (self - other).abs() <= tolerance
+    }
+}
+
+impl ApproxEq for Complex<f32> {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        ((self - other).norm() as f64) <= tolerance
+    }
+}
+
+impl ApproxEq for Complex<f64> {
+    fn approx_eq(&self, other: &Self, tolerance: f64) -> bool {
+        (self - other).norm() <= tolerance
+    }
+}
+
+/// Assert that both slices have the same length and their elements differ by at most `tolerance`
+#[track_caller]
+pub fn assert_all_close<T: ApproxEq>(actual: &[T], expected: &[T], tolerance: f64) {
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "length mismatch: {} != {}",
+        actual.len(),
+        expected.len()
+    );
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        assert!(
+            a.approx_eq(e, tolerance),
+            "mismatch at index {i}: {a:?} != {e:?} (tolerance {tolerance})"
+        );
+    }
+}
+
+/// Recorded samples and the frames a receiver should decode from them
+#[derive(Debug, Clone)]
+pub struct GoldenFixture {
+    /// Name of the fixture, i.e., the file stem
+    pub name: String,
+    /// Sample file
+    pub samples: PathBuf,
+    /// Expected frames
+    pub frames: PathBuf,
+}
+
+impl GoldenFixture {
+    /// Read the expected frames
+    pub fn expected(&self) -> Result<Vec<Vec<u8>>> {
+        read_frames(&self.frames)
+    }
+}
+
+/// Find all sample files with the given extension in `dir` that have a `.frames` file next to
+/// them
+///
+/// A missing directory or a directory without fixtures is an error, so that a test does not
+/// pass without checking anything.
+pub fn golden_fixtures(dir: impl AsRef<Path>, extension: &str) -> Result<Vec<GoldenFixture>> {
+    let dir = dir.as_ref();
+    if !dir.is_dir() {
+        bail!("fixture directory {} does not exist", dir.display());
+    }
+
+    let mut fixtures = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let samples = entry?.path();
+        if samples.extension().and_then(|e| e.to_str()) != Some(extension) {
+            continue;
+        }
+        let frames = samples.with_extension("frames");
+        if !frames.exists() {
+            continue;
+        }
+        let name = samples
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .context("invalid file name")?
+            .to_string();
+        fixtures.push(GoldenFixture {
+            name,
+            samples,
+            frames,
+        });
+    }
+    if fixtures.is_empty() {
+        bail!("no .{} fixtures in {}", extension, dir.display());
+    }
+    fixtures.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(fixtures)
+}
+
+/// Read frames from a file with one hex-encoded frame per line
+///
+/// Empty lines and lines starting with `#` are ignored.
+pub fn read_frames(path: impl AsRef<Path>) -> Result<Vec<Vec<u8>>> {
+    let path = path.as_ref();
+    let mut frames = Vec::new();
+    for (n, line) in std::fs::read_to_string(path)?.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.len() % 2 != 0 || !line.is_ascii() {
+            bail!("{}:{}: invalid frame", path.display(), n + 1);
+        }
+        let frame = (0..line.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&line[i..i + 2], 16))
+            .collect::<Result<Vec<u8>, _>>()
+            .with_context(|| format!("{}:{}: invalid frame", path.display(), n + 1))?;
+        frames.push(frame);
+    }
+    Ok(frames)
+}
+
+/// Write frames to a file with one hex-encoded frame per line
+pub fn write_frames(path: impl AsRef<Path>, frames: &[Vec<u8>]) -> Result<()> {
+    let mut s = String::new();
+    for f in frames {
+        for b in f {
+            write!(s, "{b:02x}")?;
+        }
+        s.push('\n');
+    }
+    std::fs::write(path, s)?;
+    Ok(())
+}
+
+/// Assert that the decoded frames match the expected frames
+#[track_caller]
+pub fn assert_frames_eq(name: &str, actual: &[Vec<u8>], expected: &[Vec<u8>]) {
+    for (i, (a, e)) in actual.iter().zip(expected.iter()).enumerate() {
+        assert_eq!(a, e, "{name}: frame {i} differs");
+    }
+    assert_eq!(
+        actual.len(),
+        expected.len(),
+        "{name}: decoded {} frames, expected {}",
+        actual.len(),
+        expected.len()
+    );
+}
+
+struct TestSource<T> {
+    items: Vec<T>,
+    tags: Vec<ItemTag>,
+    n_copied: usize,
+}
+
+impl<T: Copy + Send + 'static> TestSource<T> {
+    fn new(items: Vec<T>, mut tags: Vec<ItemTag>) -> Block {
+        tags.sort_by_key(|t| t.index);
+        Block::new(
+            BlockMetaBuilder::new("TestSource").build(),
+            StreamIoBuilder::new().add_output::<T>("out").build(),
+            MessageIoBuilder::new().build(),
+            TestSource {
+                items,
+                tags,
+                n_copied: 0,
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl<T: Copy + Send + 'static> Kernel for TestSource<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let out = sio.output(0).slice::<T>();
+        let n = std::cmp::min(out.len(), self.items.len() - self.n_copied);
+
+        out[0..n].copy_from_slice(&self.items[self.n_copied..self.n_copied + n]);
+
+        for t in self
+            .tags
+            .iter()
+            .filter(|t| t.index >= self.n_copied && t.index < self.n_copied + n)
+        {
+            sio.output(0)
+                .add_tag(t.index - self.n_copied, t.tag.clone());
+        }
+
+        self.n_copied += n;
+        sio.output(0).produce(n);
+        if self.n_copied == self.items.len() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
+
+struct TestSink<T> {
+    items: Vec<T>,
+    tags: Vec<ItemTag>,
+}
+
+impl<T: Clone + Debug + Send + 'static> TestSink<T> {
+    fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("TestSink").build(),
+            StreamIoBuilder::new().add_input::<T>("in").build(),
+            MessageIoBuilder::new().build(),
+            TestSink {
+                items: Vec::new(),
+                tags: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl<T: Clone + Debug + Send + 'static> Kernel for TestSink<T> {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let offset = self.items.len();
+        let i = sio.input(0).slice::<T>();
+        self.items.extend_from_slice(i);
+        let n = i.len();
+
+        for t in sio.input(0).tags().iter().filter(|t| t.index < n) {
+            self.tags.push(ItemTag {
+                index: offset + t.index,
+                tag: t.tag.clone(),
+            });
+        }
+
+        sio.input(0).consume(n);
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::runtime::buffer::slab::Slab;
+use futuresdr::runtime::scheduler::AffinitySchedulerBuilder;
+use futuresdr::runtime::scheduler::Cores;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn flowgraph_affinity() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let null_source = fg.add_block(NullSource::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(1_000_000));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let vect_sink = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    fg.connect_stream(null_source, "out", head, "in")?;
+    fg.connect_stream(head, "out", copy, "in")?;
+    fg.connect_stream(copy, "out", vect_sink, "in")?;
+
+    let scheduler = AffinitySchedulerBuilder::new()
+        .group([null_source, head], Cores::Ids(vec![0]))
+        .pin(copy, 0)
+        .build()?;
+    fg = Runtime::with_scheduler(scheduler).run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(vect_sink).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v.len(), 1_000_000);
+    for i in v {
+        assert!(i.abs() < f32::EPSILON);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn flowgraph_numa_node() -> Result<()> {
+    if !std::path::Path::new("/sys/devices/system/node/node0").exists() {
+        return Ok(());
+    }
+
+    let mut fg = Flowgraph::new();
+
+    let null_source = fg.add_block(NullSource::<f32>::new());
+    let head = fg.add_block(Head::<f32>::new(1_000_000));
+    let copy = fg.add_block(Copy::<f32>::new());
+    let vect_sink = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+
+    // circular and slab buffers, placed on the node
+    fg.connect_stream(null_source, "out", head, "in")?;
+    fg.connect_stream(head, "out", copy, "in")?;
+    fg.connect_stream_with_type(copy, "out", vect_sink, "in", Slab::new())?;
+
+    let scheduler = AffinitySchedulerBuilder::new()
+        .group([null_source, head, copy], Cores::NumaNode(0))
+        .default_cores(Cores::NumaNode(0))
+        .build()?;
+    fg = Runtime::with_scheduler(scheduler).run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<f32>>(vect_sink).unwrap();
+    assert_eq!(snk.items().len(), 1_000_000);
+
+    Ok(())
+}
+
+#[test]
+fn invalid_groups() {
+    assert!(AffinitySchedulerBuilder::new()
+        .group([0, 1], Cores::Ids(vec![0]))
+        .pin(1, 0)
+        .build()
+        .is_err());
+    assert!(AffinitySchedulerBuilder::new()
+        .group([0], Cores::Ids(vec![]))
+        .build()
+        .is_err());
+    assert!(AffinitySchedulerBuilder::new()
+        .pin(0, usize::MAX)
+        .build()
+        .is_err());
+    assert!(AffinitySchedulerBuilder::new()
+        .group([0], Cores::NumaNode(usize::MAX))
+        .build()
+        .is_err());
+}
@@ -0,0 +1,104 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::AgcBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn run<T: Copy + Send + Sync + std::fmt::Debug + 'static>(
+    input: Vec<T>,
+    agc: Block,
+) -> Result<Vec<T>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<T>::new(input));
+    let agc = fg.add_block(agc);
+    let snk = fg.add_block(VectorSinkBuilder::<T>::new().build());
+
+    fg.connect_stream(src, "out", agc, "in")?;
+    fg.connect_stream(agc, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<T>>(snk).unwrap().items().clone())
+}
+
+#[test]
+fn agc_f32() -> Result<()> {
+    let input: Vec<f32> = (0..20_000)
+        .map(|i| if i % 2 == 0 { 0.1 } else { -0.1 })
+        .collect();
+    let v = run(
+        input,
+        AgcBuilder::<f32>::new()
+            .reference_power(0.5)
+            .adjustment_rate(0.01)
+            .build(),
+    )?;
+
+    assert_eq!(v.len(), 20_000);
+    for x in v.iter().skip(10_000) {
+        assert!((x.abs() - 0.5).abs() < 0.01);
+    }
+    Ok(())
+}
+
+#[test]
+fn agc_complex_attack() -> Result<()> {
+    let input = vec![Complex32::new(3.0, 4.0); 20_000];
+    let v = run(
+        input,
+        AgcBuilder::<Complex32>::new()
+            .attack_rate(0.01)
+            .decay_rate(0.0)
+            .build(),
+    )?;
+
+    // gain decreases until the output reaches the reference
+    for w in v.windows(2) {
+        assert!(w[1].norm() <= w[0].norm() + 1e-4);
+    }
+    for x in v.iter().skip(10_000) {
+        assert!((x.norm() - 1.0).abs() < 0.01);
+    }
+    Ok(())
+}
+
+#[test]
+fn agc_decay_only() -> Result<()> {
+    // without attack, a loud signal is not attenuated
+    let input = vec![Complex32::new(3.0, 4.0); 1000];
+    let v = run(
+        input,
+        AgcBuilder::<Complex32>::new()
+            .attack_rate(0.0)
+            .decay_rate(0.01)
+            .build(),
+    )?;
+
+    for x in v {
+        assert!((x.norm() - 5.0).abs() < 1e-4);
+    }
+    Ok(())
+}
+
+#[test]
+fn agc_gain_locked() -> Result<()> {
+    let input = vec![0.1f32; 1000];
+    let v = run(
+        input,
+        AgcBuilder::<f32>::new()
+            .gain(2.0)
+            .adjustment_rate(0.1)
+            .gain_locked(true)
+            .build(),
+    )?;
+
+    for x in v {
+        assert!((x - 0.2).abs() < 1e-6);
+    }
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::ArbitraryResamplerBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+fn resample(input: Vec<Complex32>, rate: f64) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let resamp = fg.add_block(ArbitraryResamplerBuilder::<Complex32>::new(rate).build()?);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", resamp, "in")?;
+    fg.connect_stream(resamp, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    Ok(snk.items().clone())
+}
+
+fn check_tone(input_rate: f64, output_rate: f64) -> Result<()> {
+    let freq = 10e3;
+    let n = 200_000;
+    let input: Vec<Complex32> = (0..n)
+        .map(|i| {
+            let phi = 2.0 * std::f64::consts::PI * freq * i as f64 / input_rate;
+            Complex32::new(phi.cos() as f32, phi.sin() as f32)
+        })
+        .collect();
+
+    let rate = output_rate / input_rate;
+    let v = resample(input, rate)?;
+
+    let expected = (n as f64 * rate) as usize;
+    assert!(v.len() <= expected + 1);
+    assert!(v.len() + 200 >= expected);
+
+    let step = 2.0 * std::f32::consts::PI * (freq / output_rate) as f32;
+    // skip the transient of the filter
+    for w in v.windows(2).skip(200).take(v.len() - 400) {
+        assert!((w[0].norm() - 1.0).abs() < 0.02);
+        assert!(((w[1] / w[0]).arg() - step).abs() < 0.01);
+    }
+    Ok(())
+}
+
+#[test]
+fn arbitrary_resampler_decimate() -> Result<()> {
+    check_tone(2.048e6, 500e3)
+}
+
+#[test]
+fn arbitrary_resampler_interpolate() -> Result<()> {
+    check_tone(44.1e3, 48e3)
+}
+
+#[test]
+fn arbitrary_resampler_invalid() {
+    assert!(ArbitraryResamplerBuilder::<f32>::new(0.0).build().is_err());
+    assert!(ArbitraryResamplerBuilder::<f32>::new(1.5)
+        .phases(0)
+        .build()
+        .is_err());
+    assert!(ArbitraryResamplerBuilder::<f32>::new(1.5)
+        .taps(Vec::new())
+        .build()
+        .is_err());
+}
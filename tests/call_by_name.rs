@@ -0,0 +1,58 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::MessageSelector;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::runtime::Error;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn call_by_name() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let selector = fg.add_block(MessageSelector::<2>::new());
+    let (tx, mut rx) = mpsc::channel(10);
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(selector, "out1", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+    block_on(async {
+        assert_eq!(
+            handle.block_id("MessageSelector_0").await.unwrap(),
+            selector
+        );
+        assert!(matches!(
+            handle.block_id("foo").await,
+            Err(Error::InvalidBlock)
+        ));
+
+        let r = handle
+            .callback_by_name("MessageSelector_0", "select", Pmt::Usize(1))
+            .await;
+        assert!(matches!(r, Ok(Pmt::Usize(1))));
+        handle
+            .call_by_name(selector, "in", Pmt::U32(42))
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            handle.call_by_name("foo", "in", Pmt::Null).await,
+            Err(Error::InvalidBlock)
+        ));
+        assert!(matches!(
+            handle
+                .call_by_name("MessageSelector_0", "foo", Pmt::Null)
+                .await,
+            Err(Error::InvalidHandler(_))
+        ));
+
+        handle.terminate_and_wait().await.unwrap();
+    });
+
+    assert!(matches!(rx.try_next(), Ok(Some(Pmt::U32(42)))));
+
+    Ok(())
+}
@@ -0,0 +1,280 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::channel::Awgn;
+use futuresdr::blocks::channel::AwgnBuilder;
+use futuresdr::blocks::channel::FadingBuilder;
+use futuresdr::blocks::channel::FrequencyOffset;
+use futuresdr::blocks::channel::Multipath;
+use futuresdr::blocks::channel::TimingOffset;
+use futuresdr::blocks::ChannelSink;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::SinkExt;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::f64::consts::PI;
+
+fn run(input: Vec<Complex32>, block: Block) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+fn phasor(phase: f64) -> Complex32 {
+    Complex32::from_polar(1.0, (phase % (2.0 * PI)) as f32)
+}
+
+fn tone(freq: f64, n: usize) -> Vec<Complex32> {
+    (0..n).map(|i| phasor(2.0 * PI * freq * i as f64)).collect()
+}
+
+fn power(v: &[Complex32]) -> f32 {
+    v.iter().map(|x| x.norm_sqr()).sum::<f32>() / v.len() as f32
+}
+
+#[test]
+fn awgn() -> Result<()> {
+    let input = vec![Complex32::new(0.0, 0.0); 100_000];
+    let a = run(input.clone(), AwgnBuilder::new(0.5).seed(1).build()?)?;
+    let b = run(input.clone(), AwgnBuilder::new(0.5).seed(1).build()?)?;
+    let c = run(input, AwgnBuilder::with_snr(1.0, 10.0).seed(2).build()?)?;
+
+    assert_eq!(a.len(), 100_000);
+    assert_eq!(a, b);
+    assert!((power(&a) / 0.25 - 1.0).abs() < 0.05);
+    assert!((power(&c) / 0.1 - 1.0).abs() < 0.05);
+    let re = a.iter().map(|x| x.re * x.re).sum::<f32>() / a.len() as f32;
+    assert!((re / 0.125 - 1.0).abs() < 0.05);
+    Ok(())
+}
+
+async fn receive(rx: &mut mpsc::Receiver<Box<[Complex32]>>, n: usize) -> Vec<Complex32> {
+    let mut items = Vec::new();
+    while items.len() < n {
+        items.extend_from_slice(&rx.next().await.unwrap());
+    }
+    assert_eq!(items.len(), n);
+    items
+}
+
+fn assert_f32(p: Pmt, want: f32) {
+    match p {
+        Pmt::F32(v) => assert!((v - want).abs() < 1e-4, "{v} != {want}"),
+        p => panic!("unexpected {p:?}"),
+    }
+}
+
+#[test]
+fn awgn_gain() -> Result<()> {
+    let input = vec![Complex32::new(1.0, 0.0); 100];
+    let gain = Complex32::new(0.0, 0.5);
+    let v = run(input, AwgnBuilder::new(0.0).gain(gain).build()?)?;
+    assert!(v.iter().all(|x| *x == gain));
+    Ok(())
+}
+
+#[test]
+fn awgn_reconfigure() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (mut tx, rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let (out_tx, mut out_rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let src = fg.add_block(ChannelSource::<Complex32>::new(rx));
+    let awgn = fg.add_block(AwgnBuilder::with_snr(2.0, 10.0).seed(1).build()?);
+    let snk = fg.add_block(ChannelSink::<Complex32>::new(out_tx));
+    fg.connect_stream(src, "out", awgn, "in")?;
+    fg.connect_stream(awgn, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        assert_f32(handle.callback(awgn, "snr", Pmt::Null).await?, 10.0);
+        assert_f32(handle.callback(awgn, "noise_power", Pmt::Null).await?, 0.2);
+
+        // SNR relative to the signal power of the builder
+        assert_eq!(handle.callback(awgn, "snr", Pmt::F64(20.0)).await?, Pmt::Ok);
+        assert_f32(handle.callback(awgn, "noise_power", Pmt::Null).await?, 0.02);
+        tx.send(vec![Complex32::new(0.0, 0.0); 100_000].into())
+            .await?;
+        let v = receive(&mut out_rx, 100_000).await;
+        assert!((power(&v) / 0.02 - 1.0).abs() < 0.05);
+
+        assert_eq!(
+            handle.callback(awgn, "noise_power", Pmt::F32(0.5)).await?,
+            Pmt::Ok
+        );
+        assert_f32(handle.callback(awgn, "snr", Pmt::Null).await?, 6.0206);
+        assert_f32(
+            handle.callback(awgn, "noise_voltage", Pmt::Null).await?,
+            0.5f32.sqrt(),
+        );
+
+        // flat fading without noise
+        assert_eq!(
+            handle.callback(awgn, "noise_power", Pmt::F32(0.0)).await?,
+            Pmt::Ok
+        );
+        assert_eq!(handle.callback(awgn, "gain", Pmt::F32(0.5)).await?, Pmt::Ok);
+        tx.send(vec![Complex32::new(1.0, 1.0); 10].into()).await?;
+        let v = receive(&mut out_rx, 10).await;
+        assert!(v.iter().all(|x| *x == Complex32::new(0.5, 0.5)));
+
+        let gain = Complex32::new(0.0, -2.0);
+        assert_eq!(
+            handle
+                .callback(awgn, "gain", Pmt::VecCF32(vec![gain]))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(awgn, "gain", Pmt::Null).await?,
+            Pmt::VecCF32(vec![gain])
+        );
+        tx.send(vec![Complex32::new(1.0, 0.0); 10].into()).await?;
+        let v = receive(&mut out_rx, 10).await;
+        assert!(v.iter().all(|x| *x == gain));
+
+        // invalid values are rejected
+        for (port, p) in [
+            ("noise_voltage", Pmt::F64(f64::INFINITY)),
+            ("noise_voltage", Pmt::F32(f32::NAN)),
+            ("noise_power", Pmt::F32(-1.0)),
+            ("snr", Pmt::F64(f64::NAN)),
+            ("snr", Pmt::String("10".to_string())),
+            ("gain", Pmt::VecCF32(Vec::new())),
+            ("gain", Pmt::F32(f32::INFINITY)),
+        ] {
+            assert_eq!(handle.callback(awgn, port, p).await?, Pmt::InvalidValue);
+        }
+
+        handle.terminate_and_wait().await?;
+        task.await?;
+        Ok::<(), futuresdr::anyhow::Error>(())
+    })
+}
+
+#[test]
+fn frequency_offset() -> Result<()> {
+    let input = vec![Complex32::new(1.0, 0.0); 10_000];
+    let v = run(input, FrequencyOffset::new(0.01, PI / 2.0))?;
+
+    assert_eq!(v.len(), 10_000);
+    for (i, x) in v.iter().enumerate() {
+        let want = phasor(2.0 * PI * 0.01 * i as f64 + PI / 2.0);
+        assert!((x - want).norm() < 1e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn timing_offset_delay() -> Result<()> {
+    let freq = 0.05;
+    let v = run(tone(freq, 10_000), TimingOffset::new(2.5, 0.0))?;
+
+    assert!(v.len() + 10 >= 10_000 && v.len() <= 10_000);
+    for (i, x) in v.iter().enumerate().skip(20) {
+        let want = phasor(2.0 * PI * freq * (i as f64 - 2.5));
+        assert!((x - want).norm() < 1e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn timing_offset_clock() -> Result<()> {
+    let freq = 0.05;
+    let ppm = 1000.0;
+    let v = run(tone(freq, 100_000), TimingOffset::new(0.0, ppm))?;
+
+    // more output samples, sampling the tone at a lower normalized frequency
+    let expected = 100_000.0 * (1.0 + ppm * 1e-6);
+    assert!((v.len() as f64 - expected).abs() < 20.0);
+    let step = 2.0 * PI * freq / (1.0 + ppm * 1e-6);
+    for w in v.windows(2).skip(20) {
+        assert!(((w[1] / w[0]).arg() as f64 - step).abs() < 1e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn multipath() -> Result<()> {
+    let taps = vec![
+        Complex32::new(1.0, 0.0),
+        Complex32::new(0.0, 0.0),
+        Complex32::new(0.5, -0.5),
+        Complex32::new(0.0, 0.25),
+    ];
+    let mut input = vec![Complex32::new(0.0, 0.0); 100];
+    input[10] = Complex32::new(1.0, 0.0);
+    input[50] = Complex32::new(0.0, 2.0);
+    let v = run(input, Multipath::new(taps.clone()))?;
+
+    assert_eq!(v.len(), 100);
+    for (i, x) in v.iter().enumerate() {
+        let want = match i {
+            10..=13 => taps[i - 10],
+            50..=53 => taps[i - 50] * Complex32::new(0.0, 2.0),
+            _ => Complex32::new(0.0, 0.0),
+        };
+        assert!((x - want).norm() < 1e-6);
+    }
+    Ok(())
+}
+
+#[test]
+fn fading() -> Result<()> {
+    let input = vec![Complex32::new(1.0, 0.0); 100_000];
+    let rayleigh = run(input.clone(), FadingBuilder::new(0.01).seed(3).build()?)?;
+    let again = run(input.clone(), FadingBuilder::new(0.01).seed(3).build()?)?;
+    let rician = run(
+        input,
+        FadingBuilder::new(0.01).k_factor(10.0).seed(3).build()?,
+    )?;
+
+    assert_eq!(rayleigh, again);
+
+    let variance = |v: &[Complex32]| {
+        let p = power(v);
+        v.iter().map(|x| (x.norm_sqr() - p).powi(2)).sum::<f32>() / v.len() as f32
+    };
+    for v in [&rayleigh[..], &rician[..]] {
+        assert!((power(v) - 1.0).abs() < 0.05);
+    }
+    // deep fades without line of sight
+    assert!(variance(&rayleigh[..]) > 0.6);
+    assert!(rayleigh.iter().any(|x| x.norm() < 0.05));
+    assert!(variance(&rician[..]) < 0.4);
+    Ok(())
+}
+
+#[test]
+fn channel_invalid() {
+    assert!(AwgnBuilder::new(-1.0).build().is_err());
+    assert!(Awgn::new(f32::NAN, None).is_err());
+    assert!(AwgnBuilder::with_snr(0.0, 10.0).build().is_err());
+    assert!(AwgnBuilder::new(0.1)
+        .gain(Complex32::new(f32::NAN, 0.0))
+        .build()
+        .is_err());
+    assert!(FadingBuilder::new(0.5).build().is_err());
+    assert!(FadingBuilder::new(0.01).k_factor(-1.0).build().is_err());
+    assert!(FadingBuilder::new(0.01).sinusoids(0).build().is_err());
+}
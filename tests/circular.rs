@@ -0,0 +1,235 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Throttle;
+use futuresdr::blocks::VectorSource;
+use futuresdr::macros::async_trait;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::buffer::circular::OverflowPolicy;
+use futuresdr::runtime::buffer::circular::OVERFLOW_TAG;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+use futuresdr::testing::run_with_timeout;
+
+const N: u32 = 100_000;
+
+/// Sink that sleeps before reading, collecting items and overflow tags
+struct Collector {
+    delay: Option<Duration>,
+    /// do not read before the counter reaches the given value
+    wait_for: Option<(Arc<AtomicUsize>, usize)>,
+    items: Vec<u32>,
+    gaps: Vec<(usize, usize)>,
+}
+
+impl Collector {
+    fn new(delay: Duration) -> Block {
+        Self::block(Some(delay), None)
+    }
+
+    fn stalled(counter: Arc<AtomicUsize>, n: usize) -> Block {
+        Self::block(None, Some((counter, n)))
+    }
+
+    fn block(delay: Option<Duration>, wait_for: Option<(Arc<AtomicUsize>, usize)>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Collector").build(),
+            StreamIoBuilder::new().add_input::<u32>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Collector {
+                delay,
+                wait_for,
+                items: Vec::new(),
+                gaps: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Collector {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        if let Some(d) = self.delay.take() {
+            Timer::after(d).await;
+        }
+        if let Some((counter, n)) = self.wait_for.as_ref() {
+            if counter.load(Ordering::SeqCst) < *n {
+                io.block_on(Timer::after(Duration::from_millis(10)));
+                return Ok(());
+            }
+        }
+
+        let i = sio.input(0).slice::<u32>();
+        for t in sio.input(0).tags().iter() {
+            if let Tag::NamedUsize(n, dropped) = &t.tag {
+                if n == OVERFLOW_TAG {
+                    self.gaps.push((self.items.len() + t.index, *dropped));
+                }
+            }
+        }
+        self.items.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+/// Sink that counts the items it receives
+struct Counter {
+    counter: Arc<AtomicUsize>,
+}
+
+impl Counter {
+    fn new(counter: Arc<AtomicUsize>) -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Counter").build(),
+            StreamIoBuilder::new().add_input::<u32>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Counter { counter },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Counter {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let n = sio.input(0).slice::<u32>().len();
+        self.counter.fetch_add(n, Ordering::SeqCst);
+        sio.input(0).consume(n);
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn run(overflow: OverflowPolicy) -> Result<(Vec<u32>, Vec<(usize, usize)>)> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..N).collect()));
+    let throttle = fg.add_block(Throttle::<u32>::new(1e6));
+    let snk = fg.add_block(Collector::new(Duration::from_millis(30)));
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream_with_type(
+        throttle,
+        "out",
+        snk,
+        "in",
+        Circular::with_size(4096).overflow(overflow),
+    )?;
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<Collector>(snk).unwrap();
+    Ok((snk.items.clone(), snk.gaps.clone()))
+}
+
+/// Items are increasing and every jump is annotated with the number of dropped items
+fn check_gaps(items: &[u32], gaps: &[(usize, usize)]) {
+    let tagged = |i: usize| gaps.iter().find(|g| g.0 == i).map(|g| g.1).unwrap_or(0);
+    // items can already be dropped before the first read
+    assert_eq!(items[0] as usize, tagged(0));
+    for i in 1..items.len() {
+        let jump = (items[i] - items[i - 1] - 1) as usize;
+        assert_eq!(jump, tagged(i), "gap before item {i}");
+    }
+}
+
+#[test]
+fn overflow_block() -> Result<()> {
+    let (items, gaps) = run(OverflowPolicy::Block)?;
+    assert_eq!(items, (0..N).collect::<Vec<u32>>());
+    assert!(gaps.is_empty());
+    Ok(())
+}
+
+#[test]
+fn overflow_drop_oldest() -> Result<()> {
+    let (items, gaps) = run(OverflowPolicy::DropOldest)?;
+    assert!(!gaps.is_empty());
+    check_gaps(&items, &gaps);
+    assert_eq!(*items.last().unwrap(), N - 1);
+    assert_eq!(
+        items.len() + gaps.iter().map(|g| g.1).sum::<usize>(),
+        N as usize
+    );
+    Ok(())
+}
+
+#[test]
+fn overflow_drop_oldest_stalled_reader() -> Result<()> {
+    // the stalled reader does not read before the other reader got all items, which would
+    // deadlock with back pressure
+    let counter = Arc::new(AtomicUsize::new(0));
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..N).collect()));
+    let fast = fg.add_block(Counter::new(counter.clone()));
+    let stalled = fg.add_block(Collector::stalled(counter.clone(), N as usize));
+
+    let buffer = Circular::with_size(4096).overflow(OverflowPolicy::DropOldest);
+    fg.connect_stream_with_type(src, "out", fast, "in", buffer.clone())?;
+    fg.connect_stream_with_type(src, "out", stalled, "in", buffer)?;
+    fg = run_with_timeout(fg, Duration::from_secs(30))?;
+
+    assert_eq!(counter.load(Ordering::SeqCst), N as usize);
+    let snk = fg.kernel::<Collector>(stalled).unwrap();
+    assert!(!snk.gaps.is_empty());
+    assert!(snk.items.len() < N as usize);
+    check_gaps(&snk.items, &snk.gaps);
+    assert_eq!(*snk.items.last().unwrap(), N - 1);
+    Ok(())
+}
+
+#[test]
+fn overflow_drop_newest() -> Result<()> {
+    let (items, gaps) = run(OverflowPolicy::DropNewest)?;
+    assert!(!gaps.is_empty());
+    check_gaps(&items, &gaps);
+    assert!(items.len() < N as usize);
+    Ok(())
+}
+
+#[test]
+fn overflow_description() {
+    assert_eq!(
+        format!("{:?}", Circular::with_size(8192)),
+        "Circular { min_bytes: 8192 }"
+    );
+    assert_eq!(
+        format!(
+            "{:?}",
+            Circular::with_size(8192).overflow(OverflowPolicy::DropNewest)
+        ),
+        "Circular { min_bytes: 8192, overflow: DropNewest }"
+    );
+}
@@ -0,0 +1,486 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::doa::ArrayGeometry;
+use futuresdr::blocks::doa::ArrayManifold;
+use futuresdr::blocks::doa::Calibration;
+use futuresdr::blocks::doa::DoaEstimatorBuilder;
+use futuresdr::blocks::doa::DoaMethod;
+use futuresdr::blocks::doa::Georeference;
+use futuresdr::blocks::doa::InterferometerBuilder;
+use futuresdr::blocks::doa::ManifoldWriter;
+use futuresdr::blocks::doa::MusicBuilder;
+use futuresdr::blocks::doa::PhaseCalibration;
+use futuresdr::blocks::doa::PseudoDopplerBuilder;
+use futuresdr::blocks::doa::TriangulationBuilder;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::SinkExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::collections::HashMap;
+
+const FREQUENCY: f64 = 868e6;
+
+fn channels(array: &ArrayGeometry, bearing: f64, n: usize) -> Vec<Vec<Complex32>> {
+    array
+        .steering_vector(bearing, FREQUENCY)
+        .into_iter()
+        .map(|a| {
+            (0..n)
+                .map(|i| {
+                    let s = Complex32::from_polar(1.0, 0.1 * i as f32);
+                    s * Complex32::new(a.re as f32, a.im as f32)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[test]
+fn music_single_source() -> Result<()> {
+    let array = ArrayGeometry::uniform_circular(4, 0.0864);
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let music = fg.add_block(
+        MusicBuilder::new(array.clone(), FREQUENCY)
+            .snapshots(256)
+            .build()?,
+    );
+    for (i, c) in channels(&array, 60.0, 256).into_iter().enumerate() {
+        let src = fg.add_block(VectorSource::<Complex32>::new(c));
+        fg.connect_stream(src, "out", music, format!("in{i}"))?;
+    }
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(music, "bearings", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    match rx.try_next() {
+        Ok(Some(Pmt::VecF32(b))) => assert_eq!(b, vec![60.0]),
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn music_invalid_config() {
+    let array = ArrayGeometry::uniform_linear(3, 0.17);
+    assert!(MusicBuilder::new(array.clone(), FREQUENCY)
+        .sources(3)
+        .build()
+        .is_err());
+    assert!(MusicBuilder::new(array, FREQUENCY)
+        .scan(180.0, 0.0, 1.0)
+        .build()
+        .is_err());
+}
+
+#[test]
+fn interferometer_single_source() -> Result<()> {
+    let array = ArrayGeometry::uniform_circular(5, 0.1);
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let df = fg.add_block(
+        InterferometerBuilder::new(array.clone(), FREQUENCY)
+            .snapshots(256)
+            .build()?,
+    );
+    for (i, c) in channels(&array, 135.0, 256).into_iter().enumerate() {
+        let src = fg.add_block(VectorSource::<Complex32>::new(c));
+        fg.connect_stream(src, "out", df, format!("in{i}"))?;
+    }
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(df, "bearing", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    match rx.try_next() {
+        Ok(Some(Pmt::MapStrPmt(m))) => {
+            assert_eq!(m.get("bearing"), Some(&Pmt::F32(135.0)));
+            match m.get("quality") {
+                Some(Pmt::F32(q)) => assert!(*q > 0.99),
+                q => panic!("unexpected quality {q:?}"),
+            }
+        }
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn doa_estimator() -> Result<()> {
+    let arrays = [
+        (ArrayGeometry::uniform_linear(2, 0.17), 0.0, 180.0, 70.0),
+        (ArrayGeometry::uniform_circular(5, 0.1), 0.0, 360.0, 250.0),
+    ];
+    for (array, start, stop, bearing) in arrays {
+        for method in [DoaMethod::Interferometry, DoaMethod::Music { sources: 1 }] {
+            let mut fg = Flowgraph::new();
+
+            let (tx, mut rx) = mpsc::channel(10);
+            let doa = fg.add_block(
+                DoaEstimatorBuilder::new(array.clone(), FREQUENCY)
+                    .method(method)
+                    .snapshots(256)
+                    .scan(start, stop, 1.0)
+                    .build()?,
+            );
+            for (i, c) in channels(&array, bearing, 512).into_iter().enumerate() {
+                let src = fg.add_block(VectorSource::<Complex32>::new(c));
+                fg.connect_stream(src, "out", doa, format!("in{i}"))?;
+            }
+            let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+            let pipe = fg.add_block(MessagePipe::new(tx));
+            fg.connect_stream(doa, "out", snk, "in")?;
+            fg.connect_message(doa, "bearing", pipe, "in")?;
+
+            let fg = Runtime::new().run(fg)?;
+
+            let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+            assert_eq!(snk.items(), &vec![bearing as f32; 2]);
+            match rx.try_next() {
+                Ok(Some(Pmt::MapStrPmt(m))) => {
+                    assert_eq!(m.get("bearing"), Some(&Pmt::F32(bearing as f32)));
+                    assert_eq!(m.get("bearings"), Some(&Pmt::VecF32(vec![bearing as f32])));
+                }
+                p => panic!("unexpected message {p:?}"),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn doa_estimator_invalid_config() {
+    let array = ArrayGeometry::uniform_linear(2, 0.17);
+    assert!(DoaEstimatorBuilder::new(array.clone(), FREQUENCY)
+        .method(DoaMethod::Music { sources: 2 })
+        .build()
+        .is_err());
+    assert!(DoaEstimatorBuilder::new(array, FREQUENCY)
+        .method(DoaMethod::Interferometry)
+        .snapshots(0)
+        .build()
+        .is_err());
+    assert!(
+        DoaEstimatorBuilder::new(ArrayGeometry::new(vec![(0.0, 0.0)]), FREQUENCY)
+            .build()
+            .is_err()
+    );
+}
+
+#[test]
+fn doa_estimator_gated() -> Result<()> {
+    let array = ArrayGeometry::uniform_circular(5, 0.1);
+    let mut fg = Flowgraph::new();
+
+    let doa = fg.add_block(
+        DoaEstimatorBuilder::new(array.clone(), FREQUENCY)
+            .method(DoaMethod::Interferometry)
+            .gated(true)
+            .build()?,
+    );
+    let mut txs = Vec::new();
+    for i in 0..array.len() {
+        let (tx, rx) = mpsc::channel::<Box<[Complex32]>>(10);
+        let src = fg.add_block(ChannelSource::<Complex32>::new(rx));
+        fg.connect_stream(src, "out", doa, format!("in{i}"))?;
+        txs.push(tx);
+    }
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(doa, "out", snk, "in")?;
+
+    // frame from 250 degrees between an interferer from 40 degrees
+    let interferer = channels(&array, 40.0, 600);
+    let frame = channels(&array, 250.0, 600);
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    let fg = block_on(async move {
+        assert_eq!(
+            handle.callback(doa, "burst", Pmt::Usize(0)).await?,
+            Pmt::InvalidValue
+        );
+        let burst = Pmt::MapStrPmt(HashMap::from([
+            ("start".to_string(), Pmt::U64(200)),
+            ("len".to_string(), Pmt::U64(200)),
+        ]));
+        assert_eq!(handle.callback(doa, "burst", burst).await?, Pmt::Ok);
+
+        for (i, mut tx) in txs.into_iter().enumerate() {
+            let samples: Vec<Complex32> = interferer[i][..200]
+                .iter()
+                .chain(&frame[i][200..400])
+                .chain(&interferer[i][400..])
+                .copied()
+                .collect();
+            tx.send(samples.into()).await?;
+        }
+        task.await
+    })?;
+
+    // one estimate at the end of the burst, the interferer is discarded
+    let snk = fg.kernel::<VectorSink<f32>>(snk).unwrap();
+    assert_eq!(snk.items(), &vec![250.0]);
+
+    Ok(())
+}
+
+#[test]
+fn phase_calibration() -> Result<()> {
+    let offsets = [(1.0, 0.0), (0.5, 1.0), (2.0, -2.5)];
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let calibration = fg.add_block(PhaseCalibration::new(offsets.len(), 256, true));
+    for (i, (g, phi)) in offsets.iter().enumerate() {
+        let c: Vec<Complex32> = (0..256)
+            .map(|k| Complex32::from_polar(*g, 0.1 * k as f32 + phi))
+            .collect();
+        let src = fg.add_block(VectorSource::<Complex32>::new(c));
+        fg.connect_stream(src, "out", calibration, format!("in{i}"))?;
+    }
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(calibration, "corrections", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    match rx.try_next() {
+        Ok(Some(Pmt::VecCF32(c))) => {
+            assert_eq!(c.len(), offsets.len());
+            for (c, (g, phi)) in c.iter().zip(offsets.iter()) {
+                let aligned = c * Complex32::from_polar(*g, *phi);
+                assert!((aligned - Complex32::new(1.0, 0.0)).norm() < 1e-3);
+            }
+        }
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn pseudo_doppler() -> Result<()> {
+    let array = ArrayGeometry::uniform_circular(4, 0.1);
+    let dwell = 50;
+    let a = array.steering_vector(120.0, FREQUENCY);
+    let samples: Vec<Complex32> = (0..4 * dwell * 20)
+        .map(|i| {
+            let a = a[(i / dwell) % 4];
+            Complex32::from_polar(1.0, 0.01 * i as f32) * Complex32::new(a.re as f32, a.im as f32)
+        })
+        .collect();
+
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let src = fg.add_block(VectorSource::<Complex32>::new(samples));
+    let df = fg.add_block(PseudoDopplerBuilder::new(4, dwell).rotations(20).build()?);
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_stream(src, "out", df, "in")?;
+    fg.connect_message(df, "bearing", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    match rx.try_next() {
+        Ok(Some(Pmt::F32(b))) => assert!((b - 120.0).abs() < 2.0),
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn triangulation() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let triangulation = fg.add_block(
+        TriangulationBuilder::new()
+            .station("a", (0.0, 0.0))
+            .station("b", (1000.0, 0.0))
+            .build(),
+    );
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(triangulation, "position", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut fg_handle) = rt.start_sync(fg);
+    block_on(async {
+        for (station, bearing) in [("a", 45.0), ("b", 135.0)] {
+            let p = Pmt::MapStrPmt(HashMap::from([
+                ("station".to_string(), Pmt::String(station.to_string())),
+                ("bearing".to_string(), Pmt::F64(bearing)),
+            ]));
+            fg_handle
+                .callback(triangulation, "bearing", p)
+                .await
+                .unwrap();
+        }
+        fg_handle.terminate_and_wait().await.unwrap();
+    });
+
+    match rx.try_next() {
+        Ok(Some(Pmt::MapStrPmt(m))) => {
+            for (k, v) in [("x", 500.0), ("y", 500.0)] {
+                match m.get(k) {
+                    Some(Pmt::F64(p)) => assert!((p - v).abs() < 1e-6),
+                    p => panic!("unexpected {k} {p:?}"),
+                }
+            }
+            assert_eq!(m.get("stations"), Some(&Pmt::Usize(2)));
+        }
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn georeference() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let georeference = fg.add_block(Georeference::new());
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(georeference, "out", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut fg_handle) = rt.start_sync(fg);
+    block_on(async {
+        let nmea = concat!(
+            "$GPRMC,123519.00,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*44\r\n",
+            "$GPHDT,90.0,T*0C\r\n"
+        );
+        let r = fg_handle
+            .callback(georeference, "nmea", Pmt::String(nmea.to_string()))
+            .await
+            .unwrap();
+        assert_eq!(r, Pmt::Ok);
+        let r = fg_handle
+            .callback(
+                georeference,
+                "nmea",
+                Pmt::String("$GPHDT,1.0,T*00".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(r, Pmt::InvalidValue);
+        fg_handle
+            .callback(georeference, "bearing", Pmt::F32(30.0))
+            .await
+            .unwrap();
+        fg_handle.terminate_and_wait().await.unwrap();
+    });
+
+    match rx.try_next() {
+        Ok(Some(Pmt::MapStrPmt(m))) => {
+            match (m.get("lat"), m.get("lon")) {
+                (Some(Pmt::F64(lat)), Some(Pmt::F64(lon))) => {
+                    assert!((lat - 48.1173).abs() < 1e-6);
+                    assert!((lon - 11.516_666).abs() < 1e-5);
+                }
+                p => panic!("unexpected position {p:?}"),
+            }
+            assert_eq!(m.get("heading"), Some(&Pmt::F64(90.0)));
+            assert_eq!(m.get("azimuth"), Some(&Pmt::F64(60.0)));
+            assert_eq!(
+                m.get("time"),
+                Some(&Pmt::String("1994-03-23T12:35:19.00Z".to_string()))
+            );
+        }
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn music_calibrated_manifold() -> Result<()> {
+    let array = ArrayGeometry::uniform_circular(4, 0.0864);
+    let calibration = Calibration {
+        frequency: FREQUENCY,
+        gain: vec![1.0, 0.8, 1.2, 1.0],
+        phase: vec![0.0, 70.0, -120.0, 35.0],
+    };
+    let response = calibration.response();
+    let mut manifold = ArrayManifold::from(array.clone());
+    manifold.add_calibration(calibration)?;
+
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let music = fg.add_block(
+        MusicBuilder::new(manifold, FREQUENCY)
+            .snapshots(256)
+            .build()?,
+    );
+    for (i, c) in channels(&array, 250.0, 256).into_iter().enumerate() {
+        let r = Complex32::new(response[i].re as f32, response[i].im as f32);
+        let c: Vec<Complex32> = c.into_iter().map(|x| x * r).collect();
+        let src = fg.add_block(VectorSource::<Complex32>::new(c));
+        fg.connect_stream(src, "out", music, format!("in{i}"))?;
+    }
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(music, "bearings", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    match rx.try_next() {
+        Ok(Some(Pmt::VecF32(b))) => assert_eq!(b, vec![250.0]),
+        p => panic!("unexpected message {p:?}"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn manifold_writer() -> Result<()> {
+    let path = std::env::temp_dir().join("futuresdr-manifold.json");
+    let array = ArrayGeometry::uniform_linear(2, 0.17);
+    let mut fg = Flowgraph::new();
+    let writer = fg.add_block(ManifoldWriter::new(
+        path.to_str().unwrap(),
+        array.clone(),
+        FREQUENCY,
+    ));
+
+    let rt = Runtime::new();
+    let (_task, mut fg_handle) = rt.start_sync(fg);
+    block_on(async {
+        let c = vec![Complex32::new(1.0, 0.0), Complex32::new(0.0, 0.5)];
+        let r = fg_handle
+            .callback(writer, "corrections", Pmt::VecCF32(c))
+            .await
+            .unwrap();
+        assert_eq!(r, Pmt::Ok);
+        fg_handle.terminate_and_wait().await.unwrap();
+    });
+
+    let manifold = ArrayManifold::from_file(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(manifold.geometry(), &array);
+    let c = manifold.calibration(900e6).unwrap();
+    assert_eq!(c.frequency, FREQUENCY);
+    assert!((c.gain[1] - 2.0).abs() < 1e-6);
+    assert!((c.phase[1] + 90.0).abs() < 1e-6);
+
+    let invalid = r#"{
+        "positions": [[0.0, 0.0]],
+        "calibrations": [{ "frequency": 1.0, "gain": [], "phase": [] }]
+    }"#;
+    assert!(ArrayManifold::from_json(invalid).is_err());
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::MessageCopy;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::runtime::buffer::circular::Circular;
+use futuresdr::runtime::Flowgraph;
+
+fn flowgraph() -> Result<Flowgraph> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(NullSource::<f32>::new());
+    let copy = fg.add_block(Copy::<f32>::new());
+    let snk = fg.add_block(NullSink::<f32>::new());
+    let msg_copy = fg.add_block(MessageCopy::new());
+    let msg_snk = fg.add_block(MessageSink::new());
+
+    fg.connect_stream(src, "out", copy, "in")?;
+    fg.connect_stream_with_type(copy, "out", snk, "in", Circular::with_size(8192))?;
+    fg.connect_message(msg_copy, "out", msg_snk, "in")?;
+    Ok(fg)
+}
+
+#[test]
+fn dot() -> Result<()> {
+    let dot = flowgraph()?.to_dot();
+
+    assert!(dot.starts_with("digraph flowgraph {"));
+    assert!(dot.contains("b0 [label=\"{{}|NullSource_0\\n(NullSource)|{<so0> out}}\"];"));
+    assert!(dot.contains("b0:so0 -> b1:si0 [label=\"DefaultBuffer, 4 B/item\"];"));
+    assert!(dot.contains("b1:so0 -> b2:si0 [label=\"Circular { min_bytes: 8192 }, 4 B/item\"];"));
+    assert!(dot.contains("b3:mo0 -> b4:mi0 [style=dashed];"));
+
+    Ok(())
+}
+
+#[test]
+fn mermaid() -> Result<()> {
+    let mermaid = flowgraph()?.to_mermaid();
+
+    assert!(mermaid.starts_with("flowchart LR"));
+    assert!(mermaid.contains("b1[\"Copy_0<br/>(Copy)\"]"));
+    assert!(mermaid.contains("b0 -- \"out → in<br/>DefaultBuffer, 4 B/item\" --> b1"));
+    assert!(mermaid.contains("b3 -. \"out → in\" .-> b4"));
+
+    Ok(())
+}
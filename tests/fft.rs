@@ -0,0 +1,89 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelSink;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::Fft;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::SinkExt;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+/// Complex tone with unit amplitude, `bin` cycles per `n` samples
+fn tone(bin: usize, n: usize) -> Box<[Complex32]> {
+    (0..n)
+        .map(|i| {
+            let phase = 2.0 * std::f32::consts::PI * (bin * i) as f32 / n as f32;
+            Complex32::from_polar(1.0, phase)
+        })
+        .collect()
+}
+
+async fn receive(rx: &mut mpsc::Receiver<Box<[Complex32]>>, n: usize) -> Vec<Complex32> {
+    let mut items = Vec::new();
+    while items.len() < n {
+        items.extend_from_slice(&rx.next().await.unwrap());
+    }
+    assert_eq!(items.len(), n);
+    items
+}
+
+fn assert_tone(spectrum: &[Complex32], bin: usize) {
+    let n = spectrum.len() as f32;
+    for (i, v) in spectrum.iter().enumerate() {
+        let expected = if i == bin { n } else { 0.0 };
+        assert!((v.norm() - expected).abs() < 1e-2 * n, "bin {i}: {v}");
+    }
+}
+
+#[test]
+fn fft_resize() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (mut tx, rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let (out_tx, mut out_rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let src = fg.add_block(ChannelSource::<Complex32>::new(rx));
+    let fft = fg.add_block(Fft::new(64));
+    let snk = fg.add_block(ChannelSink::<Complex32>::new(out_tx));
+    fg.connect_stream(src, "out", fft, "in")?;
+    fg.connect_stream(fft, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        tx.send(tone(8, 64)).await?;
+        assert_tone(&receive(&mut out_rx, 64).await, 8);
+
+        assert_eq!(
+            handle.callback(fft, "fft_size", Pmt::Null).await?,
+            Pmt::Usize(64)
+        );
+        assert_eq!(
+            handle.callback(fft, "fft_size", Pmt::U32(128)).await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(fft, "fft_size", Pmt::Null).await?,
+            Pmt::Usize(128)
+        );
+        tx.send(tone(8, 128)).await?;
+        assert_tone(&receive(&mut out_rx, 128).await, 8);
+
+        // invalid sizes are rejected and keep the current size
+        assert_eq!(
+            handle.callback(fft, "fft_size", Pmt::Usize(0)).await?,
+            Pmt::InvalidValue
+        );
+        assert_eq!(
+            handle.callback(fft, "fft_size", Pmt::F32(64.0)).await?,
+            Pmt::InvalidValue
+        );
+        tx.send(tone(3, 128)).await?;
+        assert_tone(&receive(&mut out_rx, 128).await, 3);
+
+        handle.terminate_and_wait().await?;
+        task.await?;
+        Ok::<(), futuresdr::anyhow::Error>(())
+    })
+}
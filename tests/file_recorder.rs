@@ -0,0 +1,55 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::FileRecorderBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::macros::connect;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn record_from_start() -> Result<()> {
+    let dir = std::env::temp_dir();
+    let template = dir
+        .join("futuresdr-file-recorder-{n}.u32")
+        .display()
+        .to_string();
+
+    let orig: Vec<u32> = (0..1000).collect();
+    let mut fg = Flowgraph::new();
+    let src = VectorSource::<u32>::new(orig.clone());
+    let rec = FileRecorderBuilder::<u32>::new(template)
+        .start(true)
+        .build();
+    connect!(fg, src > rec);
+
+    Runtime::new().run(fg)?;
+
+    let path = dir.join("futuresdr-file-recorder-0.u32");
+    let bytes = std::fs::read(&path)?;
+    let v: Vec<u32> = bytes
+        .chunks_exact(4)
+        .map(|b| u32::from_ne_bytes(b.try_into().unwrap()))
+        .collect();
+    assert_eq!(v, orig);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn drop_while_not_recording() -> Result<()> {
+    let dir = std::env::temp_dir();
+    let template = dir
+        .join("futuresdr-file-recorder-idle-{n}.u32")
+        .display()
+        .to_string();
+
+    let mut fg = Flowgraph::new();
+    let src = VectorSource::<u32>::new((0..1000).collect());
+    let rec = FileRecorderBuilder::<u32>::new(template).build();
+    connect!(fg, src > rec);
+
+    Runtime::new().run(fg)?;
+
+    assert!(!dir.join("futuresdr-file-recorder-idle-0.u32").exists());
+    Ok(())
+}
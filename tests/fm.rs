@@ -0,0 +1,132 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::fm::Deemphasis;
+use futuresdr::blocks::fm::NbfmReceiveBuilder;
+use futuresdr::blocks::fm::QuadratureDemod;
+use futuresdr::blocks::fm::WbfmReceiveBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f64::consts::PI;
+
+fn run<A, B>(input: Vec<A>, block: Block) -> Result<Vec<B>>
+where
+    A: Send + 'static,
+    B: Clone + std::fmt::Debug + Send + Sync + 'static,
+{
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<A>::new(input));
+    let block = fg.add_block(block);
+    let snk = fg.add_block(VectorSinkBuilder::<B>::new().build());
+
+    fg.connect_stream(src, "out", block, "in")?;
+    fg.connect_stream(block, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<B>>(snk).unwrap().items().clone())
+}
+
+/// FM modulated `tone` Hz with `deviation` Hz, sampled at `sample_rate`
+fn modulate(tone: f64, deviation: f64, sample_rate: f64, n: usize) -> Vec<Complex32> {
+    let mut phase = 0.0f64;
+    (0..n)
+        .map(|i| {
+            let t = i as f64 / sample_rate;
+            phase += 2.0 * PI * deviation * (2.0 * PI * tone * t).sin() / sample_rate;
+            Complex32::new(phase.cos() as f32, phase.sin() as f32)
+        })
+        .collect()
+}
+
+/// Gain of the 75 µs de-emphasis at `freq` Hz
+fn deemphasis(freq: f64) -> f64 {
+    1.0 / (1.0 + (2.0 * PI * freq * 75e-6).powi(2)).sqrt()
+}
+
+/// Checks that `audio` is a tone of `freq` Hz with `amplitude`, after the filter transients
+fn check_tone(audio: &[f32], freq: f64, audio_rate: f64, amplitude: f64) {
+    let audio = &audio[500..audio.len() - 100];
+    let power = audio.iter().map(|v| (*v as f64).powi(2)).sum::<f64>() / audio.len() as f64;
+    assert!(((2.0 * power).sqrt() / amplitude - 1.0).abs() < 0.05);
+
+    let crossings = audio
+        .windows(2)
+        .filter(|w| (w[0] < 0.0) != (w[1] < 0.0))
+        .count() as f64;
+    let expected = 2.0 * freq * audio.len() as f64 / audio_rate;
+    assert!((crossings - expected).abs() <= 2.0);
+}
+
+#[test]
+fn quadrature_demod() -> Result<()> {
+    // 10 kHz offset at 100 kHz
+    let input: Vec<Complex32> = (0..1000)
+        .map(|i| Complex32::from_polar(1.0, (2.0 * PI * 0.1 * i as f64) as f32))
+        .collect();
+    let v: Vec<f32> = run(input, QuadratureDemod::with_deviation(100e3, 10e3))?;
+
+    assert_eq!(v.len(), 1000);
+    for x in v.iter().skip(1) {
+        assert!((x - 1.0).abs() < 1e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn deemphasis_filter() -> Result<()> {
+    let v: Vec<f32> = run(vec![1.0f32; 1000], Deemphasis::new(48e3, 50e-6))?;
+    assert!((v[999] - 1.0).abs() < 1e-3);
+
+    // zero at half the sample rate
+    let input: Vec<f32> = (0..1000)
+        .map(|i| if i % 2 == 0 { 1.0 } else { -1.0 })
+        .collect();
+    let v: Vec<f32> = run(input, Deemphasis::new(48e3, 50e-6))?;
+    for x in v.iter().skip(100) {
+        assert!(x.abs() < 1e-3);
+    }
+    Ok(())
+}
+
+#[test]
+fn wbfm_receive() -> Result<()> {
+    let input = modulate(1e3, 30e3, 240e3, 48_000);
+    let audio: Vec<f32> = run(input, WbfmReceiveBuilder::new(240e3, 5).build()?)?;
+
+    assert!(audio.len() <= 9600 && audio.len() + 100 >= 9600);
+    check_tone(&audio, 1e3, 48e3, 0.4 * deemphasis(1e3));
+    Ok(())
+}
+
+#[test]
+fn nbfm_receive() -> Result<()> {
+    let input = modulate(1e3, 2.5e3, 48e3, 24_000);
+    let audio: Vec<f32> = run(input.clone(), NbfmReceiveBuilder::new(48e3, 3).build()?)?;
+    check_tone(&audio, 1e3, 16e3, 0.5 * deemphasis(1e3));
+
+    let audio: Vec<f32> = run(
+        input,
+        NbfmReceiveBuilder::new(48e3, 3).no_deemphasis().build()?,
+    )?;
+    check_tone(&audio, 1e3, 16e3, 0.5);
+    Ok(())
+}
+
+#[test]
+fn fm_receive_invalid() {
+    assert!(WbfmReceiveBuilder::new(240e3, 0).build().is_err());
+    assert!(WbfmReceiveBuilder::new(240e3, 5)
+        .audio_bandwidth(30e3)
+        .build()
+        .is_err());
+    assert!(NbfmReceiveBuilder::new(48e3, 6).tau(0.0).build().is_err());
+    assert!(NbfmReceiveBuilder::new(48e3, 6)
+        .max_deviation(0.0)
+        .build()
+        .is_err());
+}
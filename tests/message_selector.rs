@@ -0,0 +1,56 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::MessageSelector;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn message_selector() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let selector = fg.add_block(MessageSelector::<3>::new());
+    let mut receivers = Vec::new();
+    for i in 0..3 {
+        let (tx, rx) = mpsc::channel(10);
+        let pipe = fg.add_block(MessagePipe::new(tx));
+        fg.connect_message(selector, format!("out{i}"), pipe, "in")?;
+        receivers.push(rx);
+    }
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+    block_on(async {
+        handle.call(selector, "in", Pmt::U32(0)).await.unwrap();
+
+        let r = handle.callback(selector, "select", Pmt::Usize(2)).await;
+        assert!(matches!(r, Ok(Pmt::Usize(2))));
+        let r = handle.callback(selector, "select", Pmt::U32(3)).await;
+        assert!(matches!(r, Ok(Pmt::InvalidValue)));
+        let r = handle.callback(selector, "select", Pmt::Null).await;
+        assert!(matches!(r, Ok(Pmt::Usize(2))));
+        handle.call(selector, "in", Pmt::U32(1)).await.unwrap();
+
+        let r = handle
+            .callback(selector, "broadcast", Pmt::Bool(true))
+            .await;
+        assert!(matches!(r, Ok(Pmt::Bool(true))));
+        handle.call(selector, "in", Pmt::U32(2)).await.unwrap();
+
+        handle.terminate_and_wait().await.unwrap();
+    });
+
+    let mut received = Vec::new();
+    for rx in receivers.iter_mut() {
+        let mut v = Vec::new();
+        while let Ok(Some(Pmt::U32(i))) = rx.try_next() {
+            v.push(i);
+        }
+        received.push(v);
+    }
+    assert_eq!(received, vec![vec![0, 2], vec![2], vec![1, 2]]);
+
+    Ok(())
+}
@@ -0,0 +1,115 @@
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSource;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::Throttle;
+use futuresdr::runtime::metrics;
+use futuresdr::runtime::metrics::BlockMetrics;
+use futuresdr::runtime::metrics::StreamPortMetrics;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn block_metrics() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e6));
+    let snk = fg.add_block(NullSink::<f32>::new());
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    let msg_src = fg.add_block(MessageSource::new(
+        Pmt::Null,
+        Duration::from_millis(10),
+        None,
+    ));
+    let msg_snk = fg.add_block(MessageSink::new());
+    fg.connect_message(msg_src, "out", msg_snk, "in")?;
+
+    let rt = Runtime::new();
+    let (fg, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        Timer::after(Duration::from_millis(300)).await;
+        let m = handle.metrics().await.unwrap();
+        assert_eq!(m.len(), 5);
+
+        let throttle = m.iter().find(|b| b.id == throttle).unwrap();
+        assert_eq!(throttle.type_name, "Throttle");
+        assert!(throttle.work_calls > 0);
+        assert!(throttle.stream_inputs[0].items > 0);
+        assert!(throttle.stream_inputs[0].buffered.is_some());
+        assert!(throttle.stream_outputs[0].items > 0);
+        assert!(throttle.stream_outputs[0].buffered.is_none());
+
+        let snk = m.iter().find(|b| b.id == snk).unwrap();
+        assert!(snk.stream_inputs[0].items <= throttle.stream_outputs[0].items);
+
+        let msg_src = m.iter().find(|b| b.id == msg_src).unwrap();
+        let msg_snk = m.iter().find(|b| b.id == msg_snk).unwrap();
+        assert!(msg_src.messages_sent > 0);
+        assert!(msg_snk.messages_received > 0);
+        assert!(msg_snk.messages_received <= msg_src.messages_sent);
+
+        // queries do not interfere with each other, the counters only grow
+        Timer::after(Duration::from_millis(50)).await;
+        let m2 = handle.metrics().await.unwrap();
+        let throttle2 = m2.iter().find(|b| b.id == throttle.id).unwrap();
+        assert!(throttle2.work_calls >= throttle.work_calls);
+        assert!(throttle2.stream_outputs[0].items >= throttle.stream_outputs[0].items);
+
+        handle.terminate().await.unwrap();
+        fg.await.unwrap();
+    });
+
+    Ok(())
+}
+
+#[test]
+fn encode() {
+    let b = BlockMetrics {
+        id: 2,
+        type_name: "Copy".to_string(),
+        instance_name: "my \"copy\"".to_string(),
+        work_calls: 7,
+        stream_inputs: vec![StreamPortMetrics {
+            name: "in".to_string(),
+            items: 100,
+            buffered: Some(5),
+        }],
+        stream_outputs: vec![StreamPortMetrics {
+            name: "out".to_string(),
+            items: 95,
+            buffered: None,
+        }],
+        messages_received: 3,
+        messages_sent: 0,
+    };
+    let s = metrics::encode(&[(1, vec![b])]);
+    let labels = r#"flowgraph="1",block="2",name="my \"copy\"",type="Copy""#;
+
+    assert!(s.contains("# TYPE futuresdr_block_work_calls_total counter\n"));
+    assert!(s.contains(&format!("futuresdr_block_work_calls_total{{{labels}}} 7\n")));
+    assert!(s.contains(&format!(
+        "futuresdr_stream_items_total{{{labels},port=\"in\",direction=\"in\"}} 100\n"
+    )));
+    assert!(s.contains(&format!(
+        "futuresdr_stream_items_total{{{labels},port=\"out\",direction=\"out\"}} 95\n"
+    )));
+    assert!(!s.contains("per_second"));
+    assert!(s.contains(&format!(
+        "futuresdr_stream_buffered_items{{{labels},port=\"in\",direction=\"in\"}} 5\n"
+    )));
+    assert!(!s.contains(&format!(
+        "futuresdr_stream_buffered_items{{{labels},port=\"out\""
+    )));
+    assert!(s.contains(&format!(
+        "futuresdr_messages_total{{{labels},direction=\"in\"}} 3\n"
+    )));
+}
@@ -0,0 +1,193 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Timer;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::Copy;
+use futuresdr::blocks::MessageSink;
+use futuresdr::blocks::MessageSource;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::blocks::Throttle;
+use futuresdr::runtime::Error;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn add_remove_stream() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e6));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    let n = Arc::new(AtomicUsize::new(0));
+    let counter = n.clone();
+
+    let rt = Runtime::new();
+    let (fg, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        Timer::after(Duration::from_millis(100)).await;
+
+        let apply = handle
+            .add_block(Apply::new(move |i: &f32| {
+                counter.fetch_add(1, Ordering::Relaxed);
+                *i
+            }))
+            .await
+            .unwrap();
+        let new_snk = handle.add_block(NullSink::<f32>::new()).await.unwrap();
+        handle
+            .connect_stream(throttle, "out", apply, "in")
+            .await
+            .unwrap();
+        handle
+            .connect_stream(apply, "out", new_snk, "in")
+            .await
+            .unwrap();
+        handle.start_blocks().await.unwrap();
+
+        Timer::after(Duration::from_millis(300)).await;
+        assert!(n.load(Ordering::Relaxed) > 0);
+
+        handle.remove_block(new_snk).await.unwrap();
+        handle.remove_block(apply).await.unwrap();
+        Timer::after(Duration::from_millis(100)).await;
+
+        let before = n.load(Ordering::Relaxed);
+        Timer::after(Duration::from_millis(200)).await;
+        assert_eq!(n.load(Ordering::Relaxed), before);
+
+        let desc = handle.description().await.unwrap();
+        assert_eq!(desc.blocks.len(), 3);
+        assert_eq!(desc.stream_edges.len(), 2);
+
+        handle.terminate().await.unwrap();
+        fg.await.unwrap();
+    });
+
+    Ok(())
+}
+
+#[test]
+fn add_message() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(MessageSource::new(
+        Pmt::Null,
+        Duration::from_millis(10),
+        None,
+    ));
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    let (fg, snk) = block_on(async move {
+        let snk = handle.add_block(MessageSink::new()).await.unwrap();
+        handle.connect_message(src, "out", snk, "in").await.unwrap();
+        handle.start_blocks().await.unwrap();
+
+        Timer::after(Duration::from_millis(200)).await;
+        handle
+            .disconnect_message(src, "out", snk, "in")
+            .await
+            .unwrap();
+        handle.terminate().await.unwrap();
+        (task.await.unwrap(), snk)
+    });
+
+    let snk = fg.kernel::<MessageSink>(snk).unwrap();
+    assert!(snk.received() > 0);
+
+    Ok(())
+}
+
+#[test]
+fn invalid_connections() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(NullSource::<f32>::new());
+    let throttle = fg.add_block(Throttle::<f32>::new(1e6));
+    let snk = fg.add_block(NullSink::<f32>::new());
+
+    fg.connect_stream(src, "out", throttle, "in")?;
+    fg.connect_stream(throttle, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (fg, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        let copy = handle.add_block(Copy::<f32>::new()).await.unwrap();
+
+        // inputs of running blocks are already connected
+        assert!(matches!(
+            handle.connect_stream(copy, "out", snk, "in").await,
+            Err(Error::InvalidConnection(_))
+        ));
+        // type mismatch
+        let copy_u8 = handle.add_block(Copy::<u8>::new()).await.unwrap();
+        assert!(matches!(
+            handle.connect_stream(throttle, "out", copy_u8, "in").await,
+            Err(Error::InvalidConnection(_))
+        ));
+        handle.remove_block(copy_u8).await.unwrap();
+
+        // output of the copy block is not connected
+        handle
+            .connect_stream(throttle, "out", copy, "in")
+            .await
+            .unwrap();
+        assert!(matches!(
+            handle.start_blocks().await,
+            Err(Error::InvalidConnection(_))
+        ));
+
+        let copy_snk = handle.add_block(NullSink::<f32>::new()).await.unwrap();
+        handle
+            .connect_stream(copy, "out", copy_snk, "in")
+            .await
+            .unwrap();
+        handle.start_blocks().await.unwrap();
+
+        handle.terminate().await.unwrap();
+        fg.await.unwrap();
+    });
+
+    Ok(())
+}
+
+#[test]
+fn removed_ids_are_not_reused() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    fg.add_block(MessageSink::new());
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    let (fg, new) = block_on(async move {
+        let old = handle.add_block(MessageSink::new()).await.unwrap();
+        handle.start_blocks().await.unwrap();
+        handle.remove_block(old).await.unwrap();
+        // let the removed block terminate
+        Timer::after(Duration::from_millis(100)).await;
+
+        let new = handle.add_block(MessageSink::new()).await.unwrap();
+        handle.start_blocks().await.unwrap();
+        assert_ne!(old, new);
+
+        // messages to the old Id must not reach the new block
+        assert!(handle.call(old, "in", Pmt::Null).await.is_err());
+        handle.call(new, "in", Pmt::Null).await.unwrap();
+
+        handle.terminate().await.unwrap();
+        (task.await.unwrap(), new)
+    });
+
+    let snk = fg.kernel::<MessageSink>(new).unwrap();
+    assert_eq!(snk.received(), 1);
+
+    Ok(())
+}
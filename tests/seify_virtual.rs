@@ -0,0 +1,337 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::seify::device_from_args;
+use futuresdr::blocks::seify::enumerate;
+use futuresdr::blocks::seify::enumerate_with_args;
+use futuresdr::blocks::seify::AntennaSwitchBuilder;
+use futuresdr::blocks::seify::OverflowPolicy;
+use futuresdr::blocks::seify::SinkBuilder;
+use futuresdr::blocks::seify::SourceBuilder;
+use futuresdr::blocks::seify::VirtualDevice;
+use futuresdr::blocks::seify::OVERFLOW_TAG;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::macros::async_trait;
+use futuresdr::macros::connect;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::BlockMeta;
+use futuresdr::runtime::BlockMetaBuilder;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Kernel;
+use futuresdr::runtime::MessageIo;
+use futuresdr::runtime::MessageIoBuilder;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use futuresdr::runtime::StreamIo;
+use futuresdr::runtime::StreamIoBuilder;
+use futuresdr::runtime::Tag;
+use futuresdr::runtime::WorkIo;
+use futuresdr::seify::Device;
+use futuresdr::seify::Direction;
+use std::time::Duration;
+
+/// Sink that collects items and overflow tags
+struct Collector {
+    items: Vec<Complex32>,
+    gaps: Vec<(usize, usize)>,
+}
+
+impl Collector {
+    fn new() -> Block {
+        Block::new(
+            BlockMetaBuilder::new("Collector").build(),
+            StreamIoBuilder::new().add_input::<Complex32>("in").build(),
+            MessageIoBuilder::<Self>::new().build(),
+            Collector {
+                items: Vec::new(),
+                gaps: Vec::new(),
+            },
+        )
+    }
+}
+
+#[async_trait]
+impl Kernel for Collector {
+    async fn work(
+        &mut self,
+        io: &mut WorkIo,
+        sio: &mut StreamIo,
+        _mio: &mut MessageIo<Self>,
+        _meta: &mut BlockMeta,
+    ) -> Result<()> {
+        let i = sio.input(0).slice::<Complex32>();
+        for t in sio.input(0).tags().iter().filter(|t| t.index < i.len()) {
+            if let Tag::NamedUsize(n, dropped) = &t.tag {
+                if n == OVERFLOW_TAG {
+                    self.gaps.push((self.items.len() + t.index, *dropped));
+                }
+            }
+        }
+        self.items.extend_from_slice(i);
+        sio.input(0).consume(i.len());
+
+        if sio.input(0).finished() {
+            io.finished = true;
+        }
+        Ok(())
+    }
+}
+
+fn write_ramp(name: &str, n: usize) -> Result<std::path::PathBuf> {
+    let bytes: Vec<u8> = (1..=n)
+        .flat_map(|i| [(i as f32).to_ne_bytes(), 0f32.to_ne_bytes()].concat())
+        .collect();
+    let path = std::env::temp_dir().join(name);
+    std::fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+#[test]
+fn overflow_zero_fill() -> Result<()> {
+    let path = write_ramp("futuresdr-seify-virtual-overflow.cf32", 1000)?;
+    let dev = VirtualDevice::from_file(&path, false)?;
+    dev.set_throttle(false);
+    dev.inject_overflow(100);
+    let clock = dev.rx_clock();
+
+    let mut fg = Flowgraph::new();
+    let src = SourceBuilder::new()
+        .device(Device::from_impl(dev))
+        .sample_rate(1e6)
+        .overflow(OverflowPolicy::ZeroFill)
+        .rx_clock(clock)
+        .build()?;
+    let snk = Collector::new();
+    connect!(fg, src > snk);
+
+    fg = Runtime::new().run(fg)?;
+    let snk = fg.kernel::<Collector>(snk).unwrap();
+
+    // the dropped samples are replaced by exactly as many zeros
+    assert_eq!(snk.gaps, vec![(0, 100)]);
+    assert_eq!(snk.items.len(), 1000);
+    assert!(snk.items[0..100].iter().all(|s| s.re == 0.0));
+    for (i, s) in snk.items[100..].iter().enumerate() {
+        assert_eq!(s.re, (i + 101) as f32);
+    }
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn overflow_without_clock() -> Result<()> {
+    let path = write_ramp("futuresdr-seify-virtual-overflow-no-clock.cf32", 1000)?;
+    let dev = VirtualDevice::from_file(&path, false)?;
+    dev.set_throttle(false);
+    dev.inject_overflow(100);
+
+    let mut fg = Flowgraph::new();
+    let src = SourceBuilder::new()
+        .device(Device::from_impl(dev))
+        .sample_rate(1e6)
+        .overflow(OverflowPolicy::ZeroFill)
+        .build()?;
+    let snk = Collector::new();
+    connect!(fg, src > snk);
+
+    fg = Runtime::new().run(fg)?;
+    let snk = fg.kernel::<Collector>(snk).unwrap();
+
+    // without timestamps, the gap is unknown and only tagged
+    assert_eq!(snk.gaps, vec![(0, 0)]);
+    assert_eq!(snk.items.len(), 900);
+    assert_eq!(snk.items[0].re, 101.0);
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn virtual_file_source() -> Result<()> {
+    let orig: Vec<Complex32> = (0..1000)
+        .map(|i| Complex32::new(i as f32, -(i as f32)))
+        .collect();
+    let bytes: Vec<u8> = orig
+        .iter()
+        .flat_map(|c| [c.re.to_ne_bytes(), c.im.to_ne_bytes()].concat())
+        .collect();
+
+    let path = std::env::temp_dir().join("futuresdr-seify-virtual-test.cf32");
+    std::fs::write(&path, bytes)?;
+
+    let mut fg = Flowgraph::new();
+    let src = SourceBuilder::new()
+        .args(format!("driver=virtual,file={},throttle=false", path.display()).as_str())?
+        .sample_rate(1e6)
+        .frequency(868e6)
+        .build()?;
+    let head = Head::<Complex32>::new(2500);
+    let snk = VectorSinkBuilder::<Complex32>::new().build();
+
+    connect!(fg, src > head > snk);
+
+    fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    let v = snk.items();
+
+    assert_eq!(v.len(), 2500);
+    for (i, s) in v.iter().enumerate() {
+        assert_eq!(*s, orig[i % orig.len()]);
+    }
+
+    std::fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn virtual_loopback_from_args() -> Result<()> {
+    // source and sink are created independently from the same arguments
+    let args = "driver=virtual,id=loopback-test";
+    let tx: Vec<Complex32> = (1..=200_000)
+        .map(|i| Complex32::new(i as f32, 0.0))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let vec_src = VectorSource::<Complex32>::new(tx);
+    let snk = SinkBuilder::new().args(args)?.sample_rate(1e6).build()?;
+    let src = SourceBuilder::new().args(args)?.sample_rate(1e6).build()?;
+    let head = Head::<Complex32>::new(300_000);
+    let vec_snk = VectorSinkBuilder::<Complex32>::new().build();
+
+    connect!(fg, vec_src > snk; src > head > vec_snk);
+
+    fg = Runtime::new().run(fg)?;
+
+    let rx: Vec<Complex32> = fg
+        .kernel::<VectorSink<Complex32>>(vec_snk)
+        .unwrap()
+        .items()
+        .iter()
+        .filter(|s| s.re != 0.0)
+        .copied()
+        .collect();
+
+    // the beginning might be missed, if it is sent before the RX stream is active
+    assert!(!rx.is_empty());
+    for w in rx.windows(2) {
+        assert_eq!(w[1].re, w[0].re + 1.0);
+    }
+    Ok(())
+}
+
+#[test]
+fn virtual_enumerate() -> Result<()> {
+    assert!(enumerate()?.iter().any(VirtualDevice::matches));
+    assert_eq!(enumerate_with_args("driver=virtual")?.len(), 1);
+
+    let dev = device_from_args("driver=virtual,channels=2,id=enumerate-test")?;
+    assert_eq!(dev.num_channels(Direction::Rx)?, 2);
+    Ok(())
+}
+
+#[test]
+fn antenna_switch() -> Result<()> {
+    let dev = Device::from_impl(VirtualDevice::new());
+    assert!(AntennaSwitchBuilder::new(dev.clone())
+        .channels(vec![])
+        .states(["A", "B"])
+        .build()
+        .is_err());
+
+    let mut fg = Flowgraph::new();
+    let switch = fg.add_block(
+        AntennaSwitchBuilder::new(dev.clone())
+            .states(["A", "B", "C"])
+            .build()?,
+    );
+    let (tx, mut rx) = mpsc::channel(100);
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(switch, "state", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    block_on(async {
+        let antenna = || dev.antenna(Direction::Rx, 0).unwrap();
+        assert_eq!(
+            handle.callback(switch, "select", Pmt::Null).await?,
+            Pmt::Usize(0)
+        );
+        assert_eq!(antenna(), "A");
+
+        assert_eq!(
+            handle.callback(switch, "select", Pmt::Usize(2)).await?,
+            Pmt::Ok
+        );
+        assert_eq!(antenna(), "C");
+        assert_eq!(
+            handle
+                .callback(switch, "select", Pmt::String("B".to_string()))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(antenna(), "B");
+        assert_eq!(
+            handle.callback(switch, "select", Pmt::Usize(3)).await?,
+            Pmt::InvalidValue
+        );
+        assert_eq!(
+            handle
+                .callback(switch, "select", Pmt::String("D".to_string()))
+                .await?,
+            Pmt::InvalidValue
+        );
+
+        assert_eq!(
+            handle.callback(switch, "next", Pmt::Null).await?,
+            Pmt::Usize(2)
+        );
+        assert_eq!(
+            handle.callback(switch, "next", Pmt::Null).await?,
+            Pmt::Usize(0)
+        );
+        assert_eq!(antenna(), "A");
+        for s in [2, 1, 2, 0] {
+            assert_eq!(rx.next().await, Some(Pmt::Usize(s)));
+        }
+
+        assert_eq!(
+            handle.callback(switch, "period", Pmt::Null).await?,
+            Pmt::F64(0.0)
+        );
+        for p in [f64::NAN, f64::INFINITY, 1e30] {
+            assert_eq!(
+                handle.callback(switch, "period", Pmt::F64(p)).await?,
+                Pmt::InvalidValue
+            );
+        }
+        assert_eq!(
+            handle.callback(switch, "period", Pmt::F64(0.01)).await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(switch, "period", Pmt::Null).await?,
+            Pmt::F64(0.01)
+        );
+        // timed switching cycles through the states
+        for s in [1, 2, 0, 1] {
+            assert_eq!(rx.next().await, Some(Pmt::Usize(s)));
+        }
+        assert_eq!(
+            handle.callback(switch, "period", Pmt::F64(0.0)).await?,
+            Pmt::Ok
+        );
+
+        handle.terminate_and_wait().await?;
+        task.await
+    })?;
+
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::SemtechUdpForwarderBuilder;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::StreamExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+const EUI: u64 = 0x0102_0304_0506_0708;
+
+/// Receive datagrams of the given type, until `f` accepts one
+fn recv<F>(server: &UdpSocket, kind: u8, f: F) -> (Vec<u8>, SocketAddr)
+where
+    F: Fn(&[u8]) -> bool,
+{
+    let mut buf = [0; 65536];
+    loop {
+        let (n, gw) = server.recv_from(&mut buf).unwrap();
+        let d = &buf[..n];
+        assert_eq!(d[0], 2);
+        if d[3] == kind && f(d) {
+            return (d.to_vec(), gw);
+        }
+    }
+}
+
+fn json(d: &[u8]) -> Value {
+    assert_eq!(&d[4..12], &EUI.to_be_bytes());
+    serde_json::from_slice(&d[12..]).unwrap()
+}
+
+#[test]
+fn semtech_udp_forwarder() -> Result<()> {
+    let server = UdpSocket::bind("127.0.0.1:0")?;
+    server.set_read_timeout(Some(Duration::from_secs(5)))?;
+
+    let mut fg = Flowgraph::new();
+    let forwarder = fg.add_block(
+        SemtechUdpForwarderBuilder::new(server.local_addr()?.to_string(), EUI)
+            .frequency(868.3e6)
+            .spreading_factor(9)
+            .stat_interval(Duration::from_millis(100))
+            .build()?,
+    );
+    let (tx, mut rx) = mpsc::channel(10);
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(forwarder, "tx", pipe, "in")?;
+
+    let rt = Runtime::new();
+    let (_task, mut handle) = rt.start_sync(fg);
+
+    // keepalive
+    let (d, gw) = recv(&server, 2, |_| true);
+    assert_eq!(&d[4..], &EUI.to_be_bytes());
+    server.send_to(&[2, d[1], d[2], 4], gw)?;
+
+    // uplink
+    block_on(handle.call(
+        forwarder,
+        "rx",
+        Pmt::MapStrPmt(HashMap::from([
+            ("payload".to_string(), Pmt::Blob(b"hello".to_vec())),
+            ("snr".to_string(), Pmt::F32(7.25)),
+            ("rssi".to_string(), Pmt::F64(-97.4)),
+            ("tmst".to_string(), Pmt::U32(1234)),
+        ])),
+    ))?;
+    let (d, gw) = recv(&server, 0, |d| json(d).get("rxpk").is_some());
+    server.send_to(&[2, d[1], d[2], 1], gw)?;
+    let rxpk = &json(&d)["rxpk"][0];
+    assert_eq!(rxpk["freq"], 868.3);
+    assert_eq!(rxpk["modu"], "LORA");
+    assert_eq!(rxpk["datr"], "SF9BW125");
+    assert_eq!(rxpk["codr"], "4/5");
+    assert_eq!(rxpk["stat"], 1);
+    assert_eq!(rxpk["tmst"], 1234);
+    assert_eq!(rxpk["rssi"], -97);
+    assert_eq!(rxpk["lsnr"], 7.3);
+    assert_eq!(rxpk["size"], 5);
+    assert_eq!(rxpk["data"], "aGVsbG8=");
+    assert!(rxpk["time"].as_str().unwrap().ends_with('Z'));
+
+    // statistics
+    let (d, _) = recv(&server, 0, |d| json(d)["stat"]["rxfw"] == 1);
+    let stat = &json(&d)["stat"];
+    assert_eq!(stat["rxnr"], 1);
+    assert_eq!(stat["rxok"], 1);
+    assert!(stat["time"].as_str().unwrap().ends_with(" GMT"));
+
+    // downlink
+    let mut resp = vec![2, 0xab, 0xcd, 3];
+    resp.extend_from_slice(
+        br#"{"txpk":{"imme":true,"freq":869.525,"rfch":0,"powe":14,"modu":"LORA",
+            "datr":"SF12BW125","codr":"4/6","ipol":true,"size":4,"data":"AQIDBA=="}}"#,
+    );
+    server.send_to(&resp, gw)?;
+    let (d, _) = recv(&server, 5, |_| true);
+    assert_eq!(&d[1..3], &[0xab, 0xcd]);
+    assert_eq!(json(&d)["txpk_ack"]["error"], "NONE");
+
+    let m = match block_on(rx.next()) {
+        Some(Pmt::MapStrPmt(m)) => m,
+        p => panic!("unexpected downlink {p:?}"),
+    };
+    assert_eq!(m["payload"], Pmt::Blob(vec![1, 2, 3, 4]));
+    assert!(matches!(m["freq"], Pmt::F64(f) if (f - 869.525e6).abs() < 1.0));
+    assert_eq!(m["sf"], Pmt::Usize(12));
+    assert_eq!(m["bw"], Pmt::F64(125e3));
+    assert_eq!(m["cr"], Pmt::Usize(2));
+    assert_eq!(m["power"], Pmt::F64(14.0));
+    assert_eq!(m["immediate"], Pmt::Bool(true));
+    assert_eq!(m["invert_iq"], Pmt::Bool(true));
+
+    block_on(handle.terminate_and_wait())?;
+    Ok(())
+}
+
+#[test]
+fn semtech_udp_forwarder_invalid() {
+    let b = || SemtechUdpForwarderBuilder::new("127.0.0.1:1700", EUI);
+    assert!(b().build().is_ok());
+    assert!(b().spreading_factor(13).build().is_err());
+    assert!(b().coding_rate(0).build().is_err());
+    assert!(b().bandwidth(0.0).build().is_err());
+    assert!(b().stat_interval(Duration::ZERO).build().is_err());
+    assert!(SemtechUdpForwarderBuilder::new("no address", EUI)
+        .build()
+        .is_err());
+}
@@ -0,0 +1,176 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::sigmf::data_path;
+use futuresdr::blocks::sigmf::meta_path;
+use futuresdr::blocks::sigmf::SigmfAnnotation;
+use futuresdr::blocks::sigmf::SigmfCapture;
+use futuresdr::blocks::sigmf::SigmfDatatype;
+use futuresdr::blocks::sigmf::SigmfGlobal;
+use futuresdr::blocks::sigmf::SigmfMeta;
+use futuresdr::blocks::SigmfSinkBuilder;
+use futuresdr::blocks::SigmfSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::macros::connect;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use futuresdr::testing::assert_all_close;
+
+#[test]
+fn datatype() -> Result<()> {
+    for s in ["cf32_le", "ci16_be", "ri8", "cu8", "cf64_le", "ru16_le"] {
+        assert_eq!(s.parse::<SigmfDatatype>()?.to_string(), s);
+    }
+    assert!("cf32".parse::<SigmfDatatype>().is_err());
+    assert!("cf16_le".parse::<SigmfDatatype>().is_err());
+    assert_eq!("ci16_le".parse::<SigmfDatatype>()?.sample_size(), 4);
+    Ok(())
+}
+
+#[test]
+fn paths() {
+    for p in [
+        "rec_868.1MHz",
+        "rec_868.1MHz.sigmf-meta",
+        "rec_868.1MHz.sigmf-data",
+    ] {
+        assert_eq!(meta_path(p).to_str(), Some("rec_868.1MHz.sigmf-meta"));
+        assert_eq!(data_path(p).to_str(), Some("rec_868.1MHz.sigmf-data"));
+    }
+    assert_eq!(
+        meta_path("/tmp/a.b/rec").to_str(),
+        Some("/tmp/a.b/rec.sigmf-meta")
+    );
+    assert_eq!(meta_path("rec.cf32").to_str(), Some("rec.cf32.sigmf-meta"));
+}
+
+#[test]
+fn dotted_base_name() -> Result<()> {
+    let base = std::env::temp_dir().join("futuresdr-sigmf-868.1MHz");
+    let orig: Vec<Complex32> = (0..1000).map(|i| Complex32::new(i as f32, 0.0)).collect();
+
+    let mut fg = Flowgraph::new();
+    let src = VectorSource::<Complex32>::new(orig.clone());
+    let snk = SigmfSinkBuilder::new(&base).build();
+    connect!(fg, src > snk);
+    Runtime::new().run(fg)?;
+
+    assert!(meta_path(&base).exists());
+    assert!(data_path(&base).exists());
+
+    let mut fg = Flowgraph::new();
+    let src = SigmfSourceBuilder::new(meta_path(&base)).build()?;
+    let snk = VectorSinkBuilder::<Complex32>::new().build();
+    connect!(fg, src > snk);
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    assert_all_close(snk.items(), &orig, 1e-6);
+
+    std::fs::remove_file(meta_path(&base))?;
+    std::fs::remove_file(data_path(&base))?;
+    Ok(())
+}
+
+#[test]
+fn roundtrip() -> Result<()> {
+    let base = std::env::temp_dir().join("futuresdr-sigmf-roundtrip");
+    let orig: Vec<Complex32> = (0..10000)
+        .map(|i| Complex32::from_polar(0.9, i as f32 * 0.01))
+        .collect();
+
+    let mut fg = Flowgraph::new();
+    let src = VectorSource::<Complex32>::new(orig.clone());
+    let snk = SigmfSinkBuilder::new(&base)
+        .datatype(SigmfDatatype::CI16_LE)
+        .sample_rate(1e6)
+        .frequency(100e6)
+        .description("roundtrip")
+        .build();
+    connect!(fg, src > snk);
+    Runtime::new().run(fg)?;
+
+    let meta = SigmfMeta::from_file(&base)?;
+    assert_eq!(meta.global.datatype, "ci16_le");
+    assert_eq!(meta.global.sample_rate, Some(1e6));
+    assert_eq!(meta.captures.len(), 1);
+    assert_eq!(meta.captures[0].frequency, Some(100e6));
+    assert_eq!(
+        std::fs::metadata(base.with_extension("sigmf-data"))?.len(),
+        40000
+    );
+
+    let mut fg = Flowgraph::new();
+    let src = SigmfSourceBuilder::new(base.with_extension("sigmf-meta")).build()?;
+    let snk = VectorSinkBuilder::<Complex32>::new().build();
+    connect!(fg, src > snk);
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<Complex32>>(snk).unwrap();
+    assert_all_close(snk.items(), &orig, 1e-4);
+
+    std::fs::remove_file(base.with_extension("sigmf-meta"))?;
+    std::fs::remove_file(base.with_extension("sigmf-data"))?;
+    Ok(())
+}
+
+#[test]
+fn tags() -> Result<()> {
+    let input = std::env::temp_dir().join("futuresdr-sigmf-tags-in");
+    let output = std::env::temp_dir().join("futuresdr-sigmf-tags-out");
+
+    let samples: Vec<i8> = (0..2000).map(|i| (i % 100) as i8).collect();
+    std::fs::write(
+        input.with_extension("sigmf-data"),
+        samples.iter().map(|s| *s as u8).collect::<Vec<u8>>(),
+    )?;
+    let annotation = SigmfAnnotation {
+        sample_start: 300,
+        sample_count: Some(50),
+        label: Some("burst".to_string()),
+        ..Default::default()
+    };
+    let meta = SigmfMeta {
+        global: SigmfGlobal {
+            datatype: "ci8".to_string(),
+            ..Default::default()
+        },
+        captures: vec![
+            SigmfCapture {
+                sample_start: 0,
+                frequency: Some(1e9),
+                ..Default::default()
+            },
+            SigmfCapture {
+                sample_start: 500,
+                frequency: Some(2e9),
+                ..Default::default()
+            },
+        ],
+        annotations: vec![annotation.clone()],
+    };
+    meta.to_file(&input)?;
+
+    let mut fg = Flowgraph::new();
+    let src = SigmfSourceBuilder::new(&input).build()?;
+    let snk = SigmfSinkBuilder::new(&output)
+        .datatype(SigmfDatatype::CI8)
+        .build();
+    connect!(fg, src > snk);
+    Runtime::new().run(fg)?;
+
+    let copy = SigmfMeta::from_file(&output)?;
+    assert_eq!(copy.captures, meta.captures);
+    assert_eq!(copy.annotations, vec![annotation]);
+    assert_eq!(
+        std::fs::read(output.with_extension("sigmf-data"))?,
+        std::fs::read(input.with_extension("sigmf-data"))?
+    );
+
+    for base in [input, output] {
+        std::fs::remove_file(base.with_extension("sigmf-meta"))?;
+        std::fs::remove_file(base.with_extension("sigmf-data"))?;
+    }
+    Ok(())
+}
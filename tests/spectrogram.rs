@@ -0,0 +1,203 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelSink;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::FftWindow;
+use futuresdr::blocks::SpectrogramBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::SinkExt;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+/// Complex tone with unit amplitude, `bin` cycles per `n` samples
+fn tone(bin: usize, n: usize, len: usize) -> Vec<Complex32> {
+    (0..len)
+        .map(|i| {
+            let phase = 2.0 * std::f32::consts::PI * (bin * i) as f32 / n as f32;
+            Complex32::from_polar(1.0, phase)
+        })
+        .collect()
+}
+
+fn run(builder: SpectrogramBuilder, input: Vec<Complex32>) -> Result<Vec<f32>> {
+    let mut fg = Flowgraph::new();
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let spectrogram = fg.add_block(builder.build()?);
+    let snk = fg.add_block(VectorSinkBuilder::<f32>::new().build());
+    fg.connect_stream(src, "out", spectrogram, "in")?;
+    fg.connect_stream(spectrogram, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+    Ok(fg.kernel::<VectorSink<f32>>(snk).unwrap().items().clone())
+}
+
+fn peak(row: &[f32]) -> (usize, f32) {
+    row.iter()
+        .copied()
+        .enumerate()
+        .fold((0, 0.0), |a, (i, v)| if v > a.1 { (i, v) } else { a })
+}
+
+/// Receive `n` rows of 64 bins
+async fn rows(rx: &mut mpsc::Receiver<Box<[f32]>>, n: usize) -> Vec<Vec<f32>> {
+    let mut items = Vec::new();
+    while items.len() < n * 64 {
+        items.extend_from_slice(&rx.next().await.unwrap());
+    }
+    assert_eq!(items.len(), n * 64);
+    items.chunks(64).map(|r| r.to_vec()).collect()
+}
+
+#[test]
+fn spectrogram_tone() -> Result<()> {
+    let out = run(SpectrogramBuilder::new(64), tone(8, 64, 128))?;
+    assert_eq!(out.len(), 2 * 64);
+    for row in out.chunks(64) {
+        // fft shifted
+        let (i, v) = peak(row);
+        assert_eq!(i, 32 + 8);
+        assert!((v - 1.0).abs() < 1e-3, "peak {v}");
+    }
+    Ok(())
+}
+
+#[test]
+fn spectrogram_window() -> Result<()> {
+    let builder = SpectrogramBuilder::new(64).window(FftWindow::Hann);
+    let out = run(builder, tone(8, 64, 64))?;
+    let (i, v) = peak(&out);
+    assert_eq!(i, 40);
+    // normalized to the window
+    assert!((v - 1.0).abs() < 1e-3, "peak {v}");
+    // the Hann window leaks into the neighboring bins only
+    assert!(out[39] > 0.1 && out[41] > 0.1);
+    assert!(out[37] < 1e-6 && out[43] < 1e-6);
+    Ok(())
+}
+
+#[test]
+fn spectrogram_overlap() -> Result<()> {
+    let out = run(SpectrogramBuilder::new(64).overlap(0.5), tone(8, 64, 256))?;
+    // hop of 32 samples
+    assert_eq!(out.len(), ((256 - 64) / 32 + 1) * 64);
+    let out = run(SpectrogramBuilder::new(64).overlap(0.75), tone(8, 64, 256))?;
+    assert_eq!(out.len(), ((256 - 64) / 16 + 1) * 64);
+    Ok(())
+}
+
+#[test]
+fn spectrogram_bins() -> Result<()> {
+    // more columns than FFT bins repeats the bins
+    let out = run(SpectrogramBuilder::new(32).bins(64), tone(4, 32, 32))?;
+    assert_eq!(out.len(), 64);
+    assert_eq!(peak(&out).0, 2 * (16 + 4));
+    assert_eq!(out[2 * (16 + 4)], out[2 * (16 + 4) + 1]);
+
+    // fewer columns keeps the peak of the merged bins
+    let out = run(SpectrogramBuilder::new(128).bins(64), tone(16, 128, 128))?;
+    assert_eq!(out.len(), 64);
+    let (i, v) = peak(&out);
+    assert_eq!(i, (64 + 16) / 2);
+    assert!((v - 1.0).abs() < 1e-3, "peak {v}");
+    Ok(())
+}
+
+#[test]
+fn spectrogram_invalid_config() {
+    assert!(SpectrogramBuilder::new(0).build().is_err());
+    assert!(SpectrogramBuilder::new(64).bins(0).build().is_err());
+    assert!(SpectrogramBuilder::new(64).overlap(1.0).build().is_err());
+    assert!(SpectrogramBuilder::new(64).overlap(-0.1).build().is_err());
+}
+
+#[test]
+fn spectrogram_reconfigure() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let (mut tx, rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let (out_tx, mut out_rx) = mpsc::channel::<Box<[f32]>>(10);
+    let src = fg.add_block(ChannelSource::<Complex32>::new(rx));
+    let spectrogram = fg.add_block(SpectrogramBuilder::new(64).build()?);
+    let snk = fg.add_block(ChannelSink::<f32>::new(out_tx));
+    fg.connect_stream(src, "out", spectrogram, "in")?;
+    fg.connect_stream(spectrogram, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        tx.send(tone(8, 64, 64).into()).await?;
+        let (i, _) = peak(&rows(&mut out_rx, 1).await[0]);
+        assert_eq!(i, 40);
+
+        // resize: the row width stays the same, the tone moves with the bin spacing
+        assert_eq!(
+            handle.callback(spectrogram, "fft_size", Pmt::Null).await?,
+            Pmt::Usize(64)
+        );
+        assert_eq!(
+            handle
+                .callback(spectrogram, "fft_size", Pmt::Usize(128))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(spectrogram, "fft_size", Pmt::Null).await?,
+            Pmt::Usize(128)
+        );
+        tx.send(tone(8, 64, 128).into()).await?;
+        let (i, v) = peak(&rows(&mut out_rx, 1).await[0]);
+        assert_eq!(i, 40);
+        assert!((v - 1.0).abs() < 1e-3, "peak {v}");
+
+        // overlap and window
+        assert_eq!(
+            handle
+                .callback(spectrogram, "overlap", Pmt::F64(0.5))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(spectrogram, "overlap", Pmt::Null).await?,
+            Pmt::F32(0.5)
+        );
+        assert_eq!(
+            handle
+                .callback(spectrogram, "window", Pmt::String("Hann".to_string()))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(spectrogram, "window", Pmt::Null).await?,
+            Pmt::String("hann".to_string())
+        );
+        // 128 samples, then a hop of 64 samples
+        tx.send(tone(8, 64, 192).into()).await?;
+        for row in rows(&mut out_rx, 2).await {
+            let (i, v) = peak(&row);
+            assert_eq!(i, 40);
+            assert!((v - 1.0).abs() < 1e-3, "peak {v}");
+        }
+
+        // invalid values are rejected
+        for (port, p) in [
+            ("fft_size", Pmt::Usize(0)),
+            ("fft_size", Pmt::F32(1.0)),
+            ("overlap", Pmt::F64(1.0)),
+            ("window", Pmt::String("foo".to_string())),
+        ] {
+            assert_eq!(
+                handle.callback(spectrogram, port, p).await?,
+                Pmt::InvalidValue
+            );
+        }
+
+        handle.terminate_and_wait().await?;
+        task.await?;
+        Ok::<(), futuresdr::anyhow::Error>(())
+    })
+}
@@ -0,0 +1,41 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::MessagePipe;
+use futuresdr::blocks::SweeperBuilder;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+use std::time::Duration;
+
+#[test]
+fn sweep_once() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let (tx, mut rx) = mpsc::channel(10);
+    let sweeper = fg.add_block(
+        SweeperBuilder::new(100e6, 103e6, 1e6)
+            .dwell(Duration::from_millis(1))
+            .repeat(false)
+            .build()?,
+    );
+    let pipe = fg.add_block(MessagePipe::new(tx));
+    fg.connect_message(sweeper, "freq", pipe, "in")?;
+
+    Runtime::new().run(fg)?;
+
+    let mut freqs = Vec::new();
+    while let Ok(Some(p)) = rx.try_next() {
+        if let Pmt::F64(f) = p {
+            freqs.push(f);
+        }
+    }
+    assert_eq!(freqs, vec![100e6, 101e6, 102e6, 103e6]);
+
+    Ok(())
+}
+
+#[test]
+fn invalid_range() {
+    assert!(SweeperBuilder::new(100e6, 90e6, 1e6).build().is_err());
+    assert!(SweeperBuilder::new(100e6, 110e6, 0.0).build().is_err());
+}
@@ -0,0 +1,172 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::SymbolSyncBuilder;
+use futuresdr::blocks::SymbolSyncSample;
+use futuresdr::blocks::TimingErrorDetector;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::f64::consts::PI;
+
+const ROLL_OFF: f64 = 0.35;
+const SYMBOLS: usize = 3000;
+
+fn raised_cosine(t: f64) -> f64 {
+    if t.abs() < 1e-9 {
+        1.0
+    } else if ((2.0 * ROLL_OFF * t).abs() - 1.0).abs() < 1e-9 {
+        PI / 4.0 * (PI / (2.0 * ROLL_OFF)).sin() / (PI / (2.0 * ROLL_OFF))
+    } else {
+        (PI * t).sin() / (PI * t) * (PI * ROLL_OFF * t).cos() / (1.0 - (2.0 * ROLL_OFF * t).powi(2))
+    }
+}
+
+fn root_raised_cosine(t: f64) -> f64 {
+    let b = ROLL_OFF;
+    if t.abs() < 1e-9 {
+        1.0 - b + 4.0 * b / PI
+    } else if ((4.0 * b * t).abs() - 1.0).abs() < 1e-9 {
+        b / 2f64.sqrt()
+            * ((1.0 + 2.0 / PI) * (PI / (4.0 * b)).sin()
+                + (1.0 - 2.0 / PI) * (PI / (4.0 * b)).cos())
+    } else {
+        ((PI * t * (1.0 - b)).sin() + 4.0 * b * t * (PI * t * (1.0 + b)).cos())
+            / (PI * t * (1.0 - (4.0 * b * t).powi(2)))
+    }
+}
+
+/// Symbols of a pseudo-random sequence
+fn symbols() -> Vec<(f64, f64)> {
+    let mut state = 0xace1u16;
+    let mut bit = || {
+        let b = (state ^ (state >> 2) ^ (state >> 3) ^ (state >> 5)) & 1;
+        state = (state >> 1) | (b << 15);
+        if b == 1 {
+            1.0
+        } else {
+            -1.0
+        }
+    };
+    (0..SYMBOLS).map(|_| (bit(), bit())).collect()
+}
+
+/// Pulse-shaped symbols, sampled at `samples_per_symbol`, starting at `offset` symbols
+fn shape(
+    symbols: &[(f64, f64)],
+    pulse: fn(f64) -> f64,
+    samples_per_symbol: f64,
+) -> Vec<(f64, f64)> {
+    let offset = 0.3;
+    let mut samples = Vec::new();
+    let mut n = 0;
+    loop {
+        let t = n as f64 / samples_per_symbol + offset;
+        if t > symbols.len() as f64 {
+            return samples;
+        }
+        let first = (t as usize).saturating_sub(8);
+        let last = std::cmp::min(t as usize + 9, symbols.len());
+        let mut sample = (0.0, 0.0);
+        for (k, s) in symbols.iter().enumerate().take(last).skip(first) {
+            let p = pulse(t - k as f64);
+            sample.0 += s.0 * p;
+            sample.1 += s.1 * p;
+        }
+        samples.push(sample);
+        n += 1;
+    }
+}
+
+fn run<T>(input: Vec<T>, sync: Block) -> Result<Vec<T>>
+where
+    T: SymbolSyncSample + std::fmt::Debug,
+{
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<T>::new(input));
+    let sync = fg.add_block(sync);
+    let snk = fg.add_block(VectorSinkBuilder::<T>::new().build());
+
+    fg.connect_stream(src, "out", sync, "in")?;
+    fg.connect_stream(sync, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg.kernel::<VectorSink<T>>(snk).unwrap().items().clone())
+}
+
+/// Decisions of the converged output match the symbols, and the symbols have about the same
+/// amplitude, i.e., are sampled close to the peak of the pulse
+fn check(output: &[f32], symbols: &[f64]) {
+    assert!(output.len() + 20 >= SYMBOLS && output.len() <= SYMBOLS + 20);
+    let output = &output[1000..output.len() - 20];
+
+    let mean = output.iter().map(|v| v.abs()).sum::<f32>() / output.len() as f32;
+    for v in output {
+        assert!((v.abs() / mean - 1.0).abs() < 0.15);
+    }
+
+    let matches = |lag: usize| {
+        output
+            .iter()
+            .zip(symbols[lag..].iter())
+            .all(|(v, s)| (*v > 0.0) == (*s > 0.0))
+    };
+    assert!((990..1010).any(matches));
+}
+
+#[test]
+fn symbol_sync_mueller_muller_f32() -> Result<()> {
+    let symbols = symbols();
+    let input: Vec<f32> = shape(&symbols, raised_cosine, 4.004)
+        .into_iter()
+        .map(|s| s.0 as f32)
+        .collect();
+
+    let output = run(input, SymbolSyncBuilder::<f32>::new(4.0).build()?)?;
+
+    let i: Vec<f64> = symbols.iter().map(|s| s.0).collect();
+    check(&output, &i);
+    Ok(())
+}
+
+#[test]
+fn symbol_sync_polyphase_complex() -> Result<()> {
+    let symbols = symbols();
+    let input: Vec<Complex32> = shape(&symbols, root_raised_cosine, 3.996)
+        .into_iter()
+        .map(|s| Complex32::new(s.0 as f32, s.1 as f32))
+        .collect();
+
+    let output = run(
+        input,
+        SymbolSyncBuilder::<Complex32>::new(4.0)
+            .ted(TimingErrorDetector::PolyphaseFilterBank)
+            .build()?,
+    )?;
+
+    let re: Vec<f32> = output.iter().map(|v| v.re).collect();
+    let im: Vec<f32> = output.iter().map(|v| v.im).collect();
+    check(&re, &symbols.iter().map(|s| s.0).collect::<Vec<f64>>());
+    check(&im, &symbols.iter().map(|s| s.1).collect::<Vec<f64>>());
+    Ok(())
+}
+
+#[test]
+fn symbol_sync_invalid() {
+    assert!(SymbolSyncBuilder::<f32>::new(0.5).build().is_err());
+    assert!(SymbolSyncBuilder::<f32>::new(2.0).mu(1.0).build().is_err());
+    assert!(SymbolSyncBuilder::<f32>::new(2.0)
+        .ted(TimingErrorDetector::PolyphaseFilterBank)
+        .filters(0)
+        .build()
+        .is_err());
+    assert!(SymbolSyncBuilder::<f32>::new(2.0)
+        .ted(TimingErrorDetector::PolyphaseFilterBank)
+        .taps(Vec::new())
+        .build()
+        .is_err());
+}
@@ -0,0 +1,257 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::TcpBufferPolicy;
+use futuresdr::blocks::TcpSink;
+use futuresdr::blocks::TcpSinkBuilder;
+use futuresdr::blocks::TcpSourceBuilder;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::SinkExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::io::Read;
+use std::io::Write;
+use std::net::TcpStream;
+use std::time::Duration;
+
+fn connect(addr: &str) -> TcpStream {
+    loop {
+        if let Ok(s) = TcpStream::connect(addr) {
+            return s;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Read `u32` items until `last` is received
+fn read_until(s: &mut TcpStream, last: u32) -> Vec<u32> {
+    s.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let mut items = Vec::new();
+    let mut buf = [0; 4];
+    while items.last() != Some(&last) {
+        s.read_exact(&mut buf).unwrap();
+        items.push(u32::from_ne_bytes(buf));
+    }
+    items
+}
+
+fn items(range: std::ops::Range<u32>) -> Box<[u32]> {
+    range.collect::<Vec<u32>>().into_boxed_slice()
+}
+
+#[test]
+fn tcp_source_token() -> Result<()> {
+    let addr = "127.0.0.1:47811";
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(TcpSourceBuilder::<u32>::new(addr).token("secret").build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let client = std::thread::spawn(move || {
+        // rejected client
+        let mut s = connect(addr);
+        s.write_all(b"wrong\n").unwrap();
+        let mut buf = [0; 1];
+        assert!(matches!(s.read(&mut buf), Ok(0) | Err(_)));
+
+        let mut s = connect(addr);
+        s.write_all(b"secret\n").unwrap();
+        let data: Vec<u8> = (0..1000u32).flat_map(|i| i.to_ne_bytes()).collect();
+        // split an item across writes
+        s.write_all(&data[0..10]).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        s.write_all(&data[10..]).unwrap();
+    });
+
+    let fg = Runtime::new().run(fg)?;
+    client.join().unwrap();
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    let v = snk.items();
+    assert_eq!(v.len(), 1000);
+    for (i, x) in v.iter().enumerate() {
+        assert_eq!(i as u32, *x);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn tcp_sink_token() -> Result<()> {
+    let addr = "127.0.0.1:47812";
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..1000).collect()));
+    let snk = fg.add_block(TcpSinkBuilder::<u32>::new(addr).token("secret").build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let client = std::thread::spawn(move || {
+        // rejected clients
+        for token in [&b"wrong\n"[..], b"secre\n", b"secrets\n"] {
+            let mut s = connect(addr);
+            s.write_all(token).unwrap();
+            let mut buf = [0; 1];
+            assert!(matches!(s.read(&mut buf), Ok(0) | Err(_)));
+        }
+
+        let mut s = connect(addr);
+        s.write_all(b"secret\n").unwrap();
+        read_until(&mut s, 999)
+    });
+
+    Runtime::new().run(fg)?;
+    let v = client.join().unwrap();
+    assert_eq!(v, (0..1000).collect::<Vec<u32>>());
+
+    Ok(())
+}
+
+#[test]
+fn tcp_sink_port() -> Result<()> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..1000).collect()));
+    let snk = fg.add_block(TcpSink::<u32>::new(47817));
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let client = std::thread::spawn(move || read_until(&mut connect("127.0.0.1:47817"), 999));
+
+    Runtime::new().run(fg)?;
+    assert_eq!(client.join().unwrap(), (0..1000).collect::<Vec<u32>>());
+
+    Ok(())
+}
+
+#[test]
+fn tcp_sink_slow_handshake() -> Result<()> {
+    let addr = "127.0.0.1:47813";
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new((0..1000).collect()));
+    let snk = fg.add_block(TcpSinkBuilder::<u32>::new(addr).token("secret").build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let client = std::thread::spawn(move || {
+        // a client that never sends its token must not block other clients
+        let _idle = connect(addr);
+        let mut s = connect(addr);
+        s.write_all(b"secret\n").unwrap();
+        let start = std::time::Instant::now();
+        let v = read_until(&mut s, 999);
+        (v, start.elapsed())
+    });
+
+    Runtime::new().run(fg)?;
+    let (v, elapsed) = client.join().unwrap();
+    assert_eq!(v.len(), 1000);
+    assert!(elapsed < Duration::from_secs(4), "took {elapsed:?}");
+
+    Ok(())
+}
+
+#[test]
+fn tcp_sink_buffer_policy() -> Result<()> {
+    let addr = "127.0.0.1:47814";
+    let mut fg = Flowgraph::new();
+
+    let (mut tx, rx) = mpsc::channel(10);
+    let src = fg.add_block(ChannelSource::<u32>::new(rx));
+    let snk = fg.add_block(
+        TcpSinkBuilder::<u32>::new(addr)
+            .buffer_policy(TcpBufferPolicy::Buffer(100))
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start_sync(fg);
+    block_on(tx.send(items(0..1000)))?;
+    // without a client, only the latest 100 items are kept
+    std::thread::sleep(Duration::from_millis(100));
+
+    let mut s = connect(addr);
+    assert_eq!(read_until(&mut s, 999), (900..1000).collect::<Vec<u32>>());
+    // connected: nothing is dropped
+    block_on(tx.send(items(1000..1010)))?;
+    assert_eq!(read_until(&mut s, 1009), (1000..1010).collect::<Vec<u32>>());
+
+    drop(tx);
+    block_on(task)?;
+    Ok(())
+}
+
+#[test]
+fn tcp_sink_drop_policy() -> Result<()> {
+    let addr = "127.0.0.1:47815";
+    let mut fg = Flowgraph::new();
+
+    let (mut tx, rx) = mpsc::channel(10);
+    let src = fg.add_block(ChannelSource::<u32>::new(rx));
+    let snk = fg.add_block(
+        TcpSinkBuilder::<u32>::new(addr)
+            .buffer_policy(TcpBufferPolicy::Drop)
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start_sync(fg);
+    block_on(tx.send(items(0..1000)))?;
+    std::thread::sleep(Duration::from_millis(100));
+
+    // the sink drops samples, until it picked up the client
+    let mut s = connect(addr);
+    let client = std::thread::spawn(move || read_until(&mut s, 1999));
+    for i in 100..200 {
+        block_on(tx.send(items(i * 10..(i + 1) * 10)))?;
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let v = client.join().unwrap();
+    assert!(v[0] >= 1000 && v[0] % 10 == 0, "first item {}", v[0]);
+    assert!(v.windows(2).all(|w| w[1] == w[0] + 1), "gap in {v:?}");
+
+    drop(tx);
+    block_on(task)?;
+    Ok(())
+}
+
+#[test]
+fn tcp_sink_reconnect() -> Result<()> {
+    let addr = "127.0.0.1:47816";
+    let mut fg = Flowgraph::new();
+
+    let (mut tx, rx) = mpsc::channel(10);
+    let src = fg.add_block(ChannelSource::<u32>::new(rx));
+    let snk = fg.add_block(
+        TcpSinkBuilder::<u32>::new(addr)
+            .reconnect(true)
+            .buffer_policy(TcpBufferPolicy::Buffer(10_000))
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let rt = Runtime::new();
+    let (task, _handle) = rt.start_sync(fg);
+
+    let mut s = connect(addr);
+    block_on(tx.send(items(0..10)))?;
+    assert_eq!(read_until(&mut s, 9), (0..10).collect::<Vec<u32>>());
+    drop(s);
+
+    // writes fail once the sink notices the disconnect, the second client gets the rest
+    let mut s = connect(addr);
+    for i in 1..20 {
+        block_on(tx.send(items(i * 10..(i + 1) * 10)))?;
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let v = read_until(&mut s, 199);
+    assert!(v.windows(2).all(|w| w[1] == w[0] + 1), "gap in {v:?}");
+
+    drop(tx);
+    block_on(task)?;
+    Ok(())
+}
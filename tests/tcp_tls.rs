@@ -0,0 +1,134 @@
+use futures_rustls::client::TlsStream;
+use futures_rustls::rustls::pki_types::ServerName;
+use futures_rustls::rustls::ClientConfig;
+use futures_rustls::rustls::RootCertStore;
+use futures_rustls::TlsConnector;
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::async_io::Async;
+use futuresdr::blocks::TcpSinkBuilder;
+use futuresdr::blocks::TcpSourceBuilder;
+use futuresdr::blocks::TcpTlsConfig;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::futures::AsyncReadExt;
+use futuresdr::futures::AsyncWriteExt;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Runtime;
+use std::net::TcpStream;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Test PKI: a CA, a certificate for localhost, and a client certificate
+fn pem(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/tls")
+        .join(name)
+}
+
+fn client_config(client_auth: bool) -> ClientConfig {
+    let mut roots = RootCertStore::empty();
+    let mut ca = std::io::BufReader::new(std::fs::File::open(pem("ca.pem")).unwrap());
+    for c in rustls_pemfile::certs(&mut ca) {
+        roots.add(c.unwrap()).unwrap();
+    }
+    let builder = ClientConfig::builder().with_root_certificates(roots);
+    if client_auth {
+        let mut cert = std::io::BufReader::new(std::fs::File::open(pem("client.pem")).unwrap());
+        let mut key = std::io::BufReader::new(std::fs::File::open(pem("client.key")).unwrap());
+        let certs = rustls_pemfile::certs(&mut cert)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let key = rustls_pemfile::private_key(&mut key).unwrap().unwrap();
+        builder.with_client_auth_cert(certs, key).unwrap()
+    } else {
+        builder.with_no_client_auth()
+    }
+}
+
+async fn connect(addr: &str, client_auth: bool) -> std::io::Result<TlsStream<Async<TcpStream>>> {
+    let stream = loop {
+        if let Ok(s) =
+            Async::<TcpStream>::connect(addr.parse::<std::net::SocketAddr>().unwrap()).await
+        {
+            break s;
+        }
+        futuresdr::async_io::Timer::after(Duration::from_millis(10)).await;
+    };
+    TlsConnector::from(Arc::new(client_config(client_auth)))
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+}
+
+#[test]
+fn tcp_sink_tls_token() -> Result<()> {
+    let addr = "127.0.0.1:47821";
+    let mut fg = Flowgraph::new();
+
+    let tls = TcpTlsConfig::new(pem("cert.pem"), pem("key.pem"))?;
+    let src = fg.add_block(VectorSource::<u32>::new((0..1000).collect()));
+    let snk = fg.add_block(
+        TcpSinkBuilder::<u32>::new(addr)
+            .tls(tls)
+            .token("secret")
+            .build(),
+    );
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let client = std::thread::spawn(move || {
+        block_on(async {
+            let mut s = connect(addr, false).await.unwrap();
+            s.write_all(b"secret\n").await.unwrap();
+            s.flush().await.unwrap();
+            let mut buf = vec![0; 4000];
+            s.read_exact(&mut buf).await.unwrap();
+            buf.chunks_exact(4)
+                .map(|b| u32::from_ne_bytes(b.try_into().unwrap()))
+                .collect::<Vec<u32>>()
+        })
+    });
+
+    Runtime::new().run(fg)?;
+    let v = client.join().unwrap();
+    assert_eq!(v, (0..1000).collect::<Vec<u32>>());
+
+    Ok(())
+}
+
+#[test]
+fn tcp_source_tls_client_auth() -> Result<()> {
+    let addr = "127.0.0.1:47822";
+    let mut fg = Flowgraph::new();
+
+    let tls = TcpTlsConfig::with_client_auth(pem("cert.pem"), pem("key.pem"), pem("ca.pem"))?;
+    let src = fg.add_block(TcpSourceBuilder::<u32>::new(addr).tls(tls).build());
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+    fg.connect_stream(src, "out", snk, "in")?;
+
+    let client = std::thread::spawn(move || {
+        block_on(async {
+            // without a client certificate, the connection is closed
+            if let Ok(mut s) = connect(addr, false).await {
+                let _ = s.write_all(&[0; 64]).await;
+                let _ = s.flush().await;
+                let mut buf = [0; 1];
+                assert!(matches!(s.read(&mut buf).await, Ok(0) | Err(_)));
+            }
+
+            let mut s = connect(addr, true).await.unwrap();
+            let data: Vec<u8> = (0..1000u32).flat_map(|i| i.to_ne_bytes()).collect();
+            s.write_all(&data).await.unwrap();
+            s.close().await.unwrap();
+        })
+    });
+
+    let fg = Runtime::new().run(fg)?;
+    client.join().unwrap();
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &(0..1000).collect::<Vec<u32>>());
+
+    Ok(())
+}
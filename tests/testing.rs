@@ -0,0 +1,80 @@
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::Apply;
+use futuresdr::blocks::ApplyIntoIter;
+use futuresdr::blocks::NullSink;
+use futuresdr::blocks::NullSource;
+use futuresdr::macros::connect;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Tag;
+use futuresdr::testing::assert_all_close;
+use futuresdr::testing::golden_fixtures;
+use futuresdr::testing::run_with_timeout;
+use futuresdr::testing::write_frames;
+use futuresdr::testing::BlockTest;
+use std::time::Duration;
+
+#[test]
+fn approx_output() -> Result<()> {
+    let block = Apply::new(|x: &Complex32| x * Complex32::new(0.0, 1.0));
+    let input: Vec<Complex32> = (0..100).map(|i| Complex32::new(i as f32, 0.0)).collect();
+    let expected: Vec<Complex32> = (0..100).map(|i| Complex32::new(0.0, i as f32)).collect();
+
+    let out = BlockTest::new(block, input).run::<Complex32>()?;
+    assert_all_close(out.items(), &expected, 1e-6);
+
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "mismatch at index 1")]
+fn approx_mismatch() {
+    assert_all_close(&[1.0f32, 2.0], &[1.0, 2.1], 0.01);
+}
+
+#[test]
+fn tags() -> Result<()> {
+    let block = ApplyIntoIter::new(|x: &u32| std::iter::repeat(*x).take(2));
+    let out = BlockTest::new(block, (0..10u32).collect())
+        .tag(3, Tag::Id(42))
+        .run::<u32>()?;
+
+    assert_eq!(out.items().len(), 20);
+    out.assert_tag(6, |t| matches!(t, Tag::Id(42)));
+    assert_eq!(out.tags().len(), 1);
+
+    Ok(())
+}
+
+#[test]
+fn timeout() -> Result<()> {
+    let mut fg = Flowgraph::new();
+    let src = NullSource::<u8>::new();
+    let snk = NullSink::<u8>::new();
+    connect!(fg, src > snk);
+
+    assert!(run_with_timeout(fg, Duration::from_millis(100)).is_err());
+
+    Ok(())
+}
+
+#[test]
+fn golden_fixtures_required() -> Result<()> {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden_fixtures");
+    let _ = std::fs::remove_dir_all(&dir);
+    assert!(golden_fixtures(&dir, "cf32").is_err());
+
+    std::fs::create_dir_all(&dir)?;
+    // samples without frames are not a fixture
+    std::fs::write(dir.join("b.cf32"), [0u8; 8])?;
+    assert!(golden_fixtures(&dir, "cf32").is_err());
+
+    write_frames(dir.join("b.frames"), &[vec![1, 2, 3]])?;
+    std::fs::write(dir.join("a.cf32"), [0u8; 8])?;
+    write_frames(dir.join("a.frames"), &[vec![4, 5], vec![6]])?;
+    let fixtures = golden_fixtures(&dir, "cf32")?;
+    let names: Vec<&str> = fixtures.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(names, ["a", "b"]);
+    assert_eq!(fixtures[0].expected()?, vec![vec![4, 5], vec![6]]);
+    Ok(())
+}
@@ -0,0 +1,190 @@
+use futuresdr::anyhow::Result;
+use futuresdr::async_io::block_on;
+use futuresdr::blocks::ChannelSink;
+use futuresdr::blocks::ChannelSource;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::blocks::XlatingFirBuilder;
+use futuresdr::futures::channel::mpsc;
+use futuresdr::futures::SinkExt;
+use futuresdr::futures::StreamExt;
+use futuresdr::num_complex::Complex32;
+use futuresdr::runtime::Block;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+const SAMPLE_RATE: f64 = 1e6;
+
+fn tone(freq: f64, n: usize) -> Vec<Complex32> {
+    (0..n)
+        .map(|i| {
+            let phi = 2.0 * std::f64::consts::PI * freq * i as f64 / SAMPLE_RATE;
+            Complex32::new(phi.cos() as f32, phi.sin() as f32)
+        })
+        .collect()
+}
+
+fn run(input: Vec<Complex32>, xlating: Block) -> Result<Vec<Complex32>> {
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<Complex32>::new(input));
+    let xlating = fg.add_block(xlating);
+    let snk = fg.add_block(VectorSinkBuilder::<Complex32>::new().build());
+
+    fg.connect_stream(src, "out", xlating, "in")?;
+    fg.connect_stream(xlating, "out", snk, "in")?;
+
+    fg = Runtime::new().run(fg)?;
+
+    Ok(fg
+        .kernel::<VectorSink<Complex32>>(snk)
+        .unwrap()
+        .items()
+        .clone())
+}
+
+#[test]
+fn xlating_mixes() -> Result<()> {
+    let v = run(
+        tone(100e3, 1000),
+        XlatingFirBuilder::new(2, 150e3, SAMPLE_RATE)
+            .taps(vec![1.0])
+            .build()?,
+    )?;
+
+    let want = tone(-50e3, 1000);
+    assert!(v.len() >= 499);
+    for (i, have) in v.iter().enumerate() {
+        assert!((have - want[2 * i]).norm() < 0.01);
+    }
+    Ok(())
+}
+
+#[test]
+fn xlating_selects_channel() -> Result<()> {
+    let n = 40_000;
+    let channel = run(
+        tone(200e3, n),
+        XlatingFirBuilder::new(4, 200e3, SAMPLE_RATE).build()?,
+    )?;
+    let other = run(
+        tone(0.0, n),
+        XlatingFirBuilder::new(4, 200e3, SAMPLE_RATE).build()?,
+    )?;
+
+    // skip the transient of the filter
+    for v in channel.iter().skip(1000) {
+        assert!((v.norm() - 1.0).abs() < 0.01);
+    }
+    for w in channel.windows(2).skip(1000) {
+        assert!((w[1] - w[0]).norm() < 0.01);
+    }
+    for v in other.iter().skip(1000) {
+        assert!(v.norm() < 0.01);
+    }
+    Ok(())
+}
+
+#[test]
+fn xlating_invalid() {
+    assert!(XlatingFirBuilder::new(0, 0.0, SAMPLE_RATE).build().is_err());
+    assert!(XlatingFirBuilder::new(4, 0.0, SAMPLE_RATE)
+        .bandwidth(1e6)
+        .build()
+        .is_err());
+    assert!(XlatingFirBuilder::new(4, 0.0, SAMPLE_RATE)
+        .taps(Vec::new())
+        .build()
+        .is_err());
+}
+
+#[test]
+fn xlating_retune() -> Result<()> {
+    let n = 40_000;
+    let mut fg = Flowgraph::new();
+    let (mut tx, rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let (out_tx, mut out_rx) = mpsc::channel::<Box<[Complex32]>>(10);
+    let src = fg.add_block(ChannelSource::<Complex32>::new(rx));
+    let xlating = fg.add_block(XlatingFirBuilder::new(4, 0.0, SAMPLE_RATE).build()?);
+    let snk = fg.add_block(ChannelSink::<Complex32>::new(out_tx));
+    fg.connect_stream(src, "out", xlating, "in")?;
+    fg.connect_stream(xlating, "out", snk, "in")?;
+
+    // the filter does not output the last samples, so only wait for most of them
+    async fn receive(rx: &mut mpsc::Receiver<Box<[Complex32]>>, n: usize) -> Vec<Complex32> {
+        let mut items = Vec::new();
+        while items.len() < n {
+            items.extend_from_slice(&rx.next().await.unwrap());
+        }
+        items
+    }
+
+    let rt = Runtime::new();
+    let (task, mut handle) = rt.start_sync(fg);
+    block_on(async move {
+        // default bandwidth of 80% of the output rate
+        assert_eq!(
+            handle.callback(xlating, "bandwidth", Pmt::Null).await?,
+            Pmt::F64(200e3)
+        );
+
+        // 80 kHz from the center of the channel is within the passband
+        assert_eq!(
+            handle.callback(xlating, "freq", Pmt::F64(200e3)).await?,
+            Pmt::Ok
+        );
+        tx.send(tone(280e3, n).into()).await?;
+        let channel = receive(&mut out_rx, n / 4 - 500).await;
+        for v in channel.iter().skip(1000) {
+            assert!((v.norm() - 1.0).abs() < 0.01, "{v}");
+        }
+
+        // narrow the channel, so that the tone is in the stopband
+        assert_eq!(
+            handle
+                .callback(xlating, "bandwidth", Pmt::F64(20e3))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(xlating, "bandwidth", Pmt::Null).await?,
+            Pmt::F64(20e3)
+        );
+        tx.send(tone(280e3, n).into()).await?;
+        let channel = receive(&mut out_rx, n / 4 - 500).await;
+        for v in channel.iter().skip(1000) {
+            assert!(v.norm() < 0.01, "{v}");
+        }
+
+        assert_eq!(
+            handle.callback(xlating, "bandwidth", Pmt::F64(1e6)).await?,
+            Pmt::InvalidValue
+        );
+        assert_eq!(
+            handle
+                .callback(xlating, "sample_rate", Pmt::F64(0.0))
+                .await?,
+            Pmt::InvalidValue
+        );
+        assert_eq!(
+            handle
+                .callback(xlating, "sample_rate", Pmt::F64(2e6))
+                .await?,
+            Pmt::Ok
+        );
+        assert_eq!(
+            handle.callback(xlating, "bandwidth", Pmt::Null).await?,
+            Pmt::F64(20e3)
+        );
+        assert_eq!(
+            handle.callback(xlating, "sample_rate", Pmt::Null).await?,
+            Pmt::F64(2e6)
+        );
+
+        handle.terminate_and_wait().await?;
+        task.await?;
+        Ok::<(), futuresdr::anyhow::Error>(())
+    })
+}
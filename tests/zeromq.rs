@@ -0,0 +1,97 @@
+use num_complex::Complex32;
+use std::collections::HashMap;
+
+use futuresdr::anyhow::Result;
+use futuresdr::blocks::zeromq::pmt;
+use futuresdr::blocks::zeromq::PubSinkBuilder;
+use futuresdr::blocks::zeromq::SubSourceBuilder;
+use futuresdr::blocks::zeromq::ZmqPattern;
+use futuresdr::blocks::Head;
+use futuresdr::blocks::VectorSink;
+use futuresdr::blocks::VectorSinkBuilder;
+use futuresdr::blocks::VectorSource;
+use futuresdr::runtime::Flowgraph;
+use futuresdr::runtime::Pmt;
+use futuresdr::runtime::Runtime;
+
+#[test]
+fn pmt_gnuradio_format() -> Result<()> {
+    // pmt.serialize_str(pmt.intern("hi"))
+    assert_eq!(
+        pmt::serialize(&Pmt::String("hi".into()))?,
+        b"\x02\x00\x02hi"
+    );
+    // pmt.serialize_str(pmt.from_long(5))
+    assert_eq!(pmt::serialize(&Pmt::U32(5))?, b"\x03\x00\x00\x00\x05");
+    // pmt.serialize_str(pmt.cons(pmt.PMT_NIL, pmt.init_u8vector(2, [1, 2])))
+    let pdu = b"\x07\x06\x0a\x00\x00\x00\x00\x02\x01\x00\x01\x02";
+    let p = Pmt::VecPmt(vec![Pmt::Null, Pmt::Blob(vec![1, 2])]);
+    assert_eq!(pmt::serialize(&p)?, pdu);
+    assert_eq!(pmt::deserialize(pdu)?, p);
+
+    assert!(pmt::deserialize(b"\x03\x00").is_err());
+    assert!(pmt::serialize(&Pmt::Any(Box::new(1u8))).is_err());
+    Ok(())
+}
+
+#[test]
+fn pmt_roundtrip() -> Result<()> {
+    let pmts = [
+        Pmt::Null,
+        Pmt::Bool(true),
+        Pmt::U32(42),
+        Pmt::U64(u64::MAX),
+        Pmt::F64(1.5),
+        Pmt::String("foo".into()),
+        Pmt::VecU64(vec![1, 2, 3]),
+        Pmt::VecF32(vec![0.5, -1.0]),
+        Pmt::VecCF32(vec![Complex32::new(1.0, -2.0)]),
+        Pmt::VecPmt(vec![Pmt::U32(1), Pmt::String("bar".into()), Pmt::Null]),
+        Pmt::MapStrPmt(HashMap::from([
+            ("a".to_string(), Pmt::U32(1)),
+            ("b".to_string(), Pmt::String("c".into())),
+        ])),
+        Pmt::VecPmt(vec![
+            Pmt::MapStrPmt(HashMap::from([("freq".to_string(), Pmt::F64(868e6))])),
+            Pmt::Blob(vec![0, 1, 2, 255]),
+        ]),
+    ];
+
+    for p in pmts {
+        assert_eq!(pmt::deserialize(&pmt::serialize(&p)?)?, p);
+    }
+    Ok(())
+}
+
+#[test]
+fn zmq_push_pull() -> Result<()> {
+    let n = 100_000;
+    let orig: Vec<u32> = (0..n).collect();
+    let mut fg = Flowgraph::new();
+
+    let src = fg.add_block(VectorSource::<u32>::new(orig.clone()));
+    let zmq_snk = fg.add_block(
+        PubSinkBuilder::<u32>::new()
+            .address("tcp://127.0.0.1:47821")
+            .pattern(ZmqPattern::PushPull)
+            .build(),
+    );
+    let zmq_src = fg.add_block(
+        SubSourceBuilder::<u32>::new()
+            .address("tcp://127.0.0.1:47821")
+            .pattern(ZmqPattern::PushPull)
+            .build(),
+    );
+    let head = fg.add_block(Head::<u32>::new(n as u64));
+    let snk = fg.add_block(VectorSinkBuilder::<u32>::new().build());
+
+    fg.connect_stream(src, "out", zmq_snk, "in")?;
+    fg.connect_stream(zmq_src, "out", head, "in")?;
+    fg.connect_stream(head, "out", snk, "in")?;
+
+    let fg = Runtime::new().run(fg)?;
+
+    let snk = fg.kernel::<VectorSink<u32>>(snk).unwrap();
+    assert_eq!(snk.items(), &orig);
+    Ok(())
+}